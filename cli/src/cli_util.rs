@@ -33,6 +33,7 @@ use std::rc::Rc;
 use std::str;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use bstr::ByteVec as _;
@@ -55,7 +56,9 @@ use jj_lib::backend::CommitId;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
+use jj_lib::conflicts::extract_mode_conflict;
 use jj_lib::dag_walk;
+use jj_lib::default_index::DefaultIndexStore;
 use jj_lib::file_util;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetExpression;
@@ -65,13 +68,17 @@ use jj_lib::gitignore::GitIgnoreError;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::hex_util::to_reverse_hex;
 use jj_lib::id_prefix::IdPrefixContext;
+use jj_lib::learned_resolutions::LearnedResolutions;
+use jj_lib::lock;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
+use jj_lib::merge_drivers::MergeDrivers;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_heads_store;
 use jj_lib::op_store::OpStoreError;
 use jj_lib::op_store::OperationId;
+use jj_lib::op_store::OperationMetadata;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::WorkspaceId;
 use jj_lib::op_walk;
@@ -108,6 +115,7 @@ use jj_lib::signing::SignInitError;
 use jj_lib::str_util::StringPattern;
 use jj_lib::transaction::Transaction;
 use jj_lib::view::View;
+use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
 use jj_lib::working_copy::LockedWorkingCopy;
 use jj_lib::working_copy::SnapshotOptions;
@@ -122,6 +130,7 @@ use jj_lib::workspace::Workspace;
 use jj_lib::workspace::WorkspaceLoadError;
 use jj_lib::workspace::WorkspaceLoader;
 use jj_lib::workspace::WorkspaceLoaderFactory;
+use pollster::FutureExt as _;
 use tracing::instrument;
 use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::prelude::*;
@@ -151,13 +160,19 @@ use crate::formatter::FormatRecorder;
 use crate::formatter::Formatter;
 use crate::formatter::PlainTextFormatter;
 use crate::git_util::is_colocated_git_workspace;
+use crate::git_util::is_git_worktree_workspace;
 use crate::git_util::print_failed_git_export;
 use crate::git_util::print_git_import_stats;
+use crate::merge_tools;
 use crate::merge_tools::DiffEditor;
 use crate::merge_tools::MergeEditor;
 use crate::merge_tools::MergeToolConfigError;
+use crate::op_hooks::run_post_operation_hook;
+use crate::op_hooks::run_pre_operation_hook;
 use crate::operation_templater::OperationTemplateLanguage;
 use crate::operation_templater::OperationTemplateLanguageExtension;
+use crate::operation_templater::OperationTemplateTypes as _;
+use crate::profile::Profiler;
 use crate::revset_util;
 use crate::revset_util::RevsetExpressionEvaluator;
 use crate::template_builder;
@@ -193,6 +208,7 @@ pub struct TracingSubscription {
         tracing_subscriber::Registry,
     >,
     _chrome_tracing_flush_guard: ChromeTracingFlushGuard,
+    profiler: Profiler,
 }
 
 impl TracingSubscription {
@@ -232,6 +248,7 @@ impl TracingSubscription {
             Err(_) => (None, ChromeTracingFlushGuard { _inner: None }),
         };
 
+        let profiler = Profiler::default();
         tracing_subscriber::registry()
             .with(
                 tracing_subscriber::fmt::Layer::default()
@@ -239,10 +256,12 @@ impl TracingSubscription {
                     .with_filter(filter),
             )
             .with(chrome_tracing_layer)
+            .with(profiler.layer())
             .init();
         TracingSubscription {
             reload_log_filter,
             _chrome_tracing_flush_guard: chrome_tracing_flush_guard,
+            profiler,
         }
     }
 
@@ -257,6 +276,16 @@ impl TracingSubscription {
         tracing::info!("debug logging enabled");
         Ok(())
     }
+
+    /// Starts recording the timings of spans. See `Profiler::enable()`.
+    pub fn enable_profiling(&self, trace_path: Option<PathBuf>) {
+        self.profiler.enable(trace_path);
+    }
+
+    /// Reports the recorded timings if profiling was enabled.
+    pub fn finish_profiling(&self, ui: &Ui) -> io::Result<()> {
+        self.profiler.finish(&mut ui.stderr())
+    }
 }
 
 #[derive(Clone)]
@@ -276,6 +305,7 @@ struct CommandHelperData {
     commit_template_extensions: Vec<Arc<dyn CommitTemplateLanguageExtension>>,
     operation_template_extensions: Vec<Arc<dyn OperationTemplateLanguageExtension>>,
     maybe_workspace_loader: Result<Box<dyn WorkspaceLoader>, CommandError>,
+    workspace_loader_factory: Box<dyn WorkspaceLoaderFactory>,
     store_factories: StoreFactories,
     working_copy_factories: WorkingCopyFactories,
 }
@@ -373,6 +403,7 @@ impl CommandHelper {
     ) -> Result<WorkspaceCommandHelper, CommandError> {
         let workspace = self.load_workspace()?;
         let op_head = self.resolve_operation(ui, workspace.repo_loader())?;
+        build_index_with_progress(ui, &workspace, &op_head)?;
         let repo = workspace.repo_loader().load_at(&op_head)?;
         let env = self.workspace_environment(ui, &workspace)?;
         WorkspaceCommandHelper::new(ui, workspace, repo, env, self.is_at_head_operation())
@@ -405,6 +436,23 @@ impl CommandHelper {
             })
     }
 
+    /// Loads the workspace at `workspace_path`, relative to the current
+    /// directory. It may be a different workspace than the current one.
+    pub fn load_workspace_at(&self, workspace_path: &str) -> Result<Workspace, CommandError> {
+        let loader = self
+            .data
+            .workspace_loader_factory
+            .create(&self.cwd().join(workspace_path))
+            .map_err(|err| map_workspace_load_error(err, Some(workspace_path)))?;
+        loader
+            .load(
+                &self.data.settings,
+                &self.data.store_factories,
+                &self.data.working_copy_factories,
+            )
+            .map_err(|err| map_workspace_load_error(err, Some(workspace_path)))
+    }
+
     /// Loads command environment for the given `workspace`.
     pub fn workspace_environment(
         &self,
@@ -777,10 +825,11 @@ impl WorkspaceCommandEnvironment {
     }
 
     /// Creates commit template language environment for this workspace and the
-    /// given `repo`.
+    /// given `repo` loaded at `operation`.
     pub fn commit_template_language<'a>(
         &'a self,
         repo: &'a dyn Repo,
+        operation: &Operation,
         id_prefix_context: &'a IdPrefixContext,
     ) -> CommitTemplateLanguage<'a> {
         CommitTemplateLanguage::new(
@@ -790,6 +839,7 @@ impl WorkspaceCommandEnvironment {
             self.revset_parse_context(),
             id_prefix_context,
             self.immutable_expression(),
+            operation,
             &self.command.data.commit_template_extensions,
         )
     }
@@ -810,6 +860,10 @@ pub struct WorkspaceCommandHelper {
     op_summary_template_text: String,
     may_update_working_copy: bool,
     working_copy_shared_with_git: bool,
+    /// Whether the workspace is a linked worktree of the Git repo, which has
+    /// its own HEAD.
+    git_worktree: bool,
+    merge_drivers: Option<Arc<MergeDrivers>>,
 }
 
 impl WorkspaceCommandHelper {
@@ -827,7 +881,10 @@ impl WorkspaceCommandHelper {
         let op_summary_template_text = settings.config().get_string("templates.op_summary")?;
         let may_update_working_copy =
             loaded_at_head && !env.command.global_args().ignore_working_copy;
-        let working_copy_shared_with_git = is_colocated_git_workspace(&workspace, &repo);
+        let git_worktree = is_git_worktree_workspace(&workspace, &repo);
+        let working_copy_shared_with_git =
+            git_worktree || is_colocated_git_workspace(&workspace, &repo);
+        let merge_drivers = merge_tools::load_merge_drivers(settings.config())?.map(Arc::new);
         let helper = Self {
             workspace,
             user_repo: ReadonlyUserRepo::new(repo),
@@ -836,6 +893,8 @@ impl WorkspaceCommandHelper {
             op_summary_template_text,
             may_update_working_copy,
             working_copy_shared_with_git,
+            git_worktree,
+            merge_drivers,
         };
         // Parse commit_summary template early to report error before starting
         // mutable operation.
@@ -853,6 +912,13 @@ impl WorkspaceCommandHelper {
         self.user_repo.git_backend()
     }
 
+    /// Leaves the working copy alone from now on, like `--ignore-working-copy`.
+    /// If a transaction rewrites the working-copy commit, the working copy
+    /// becomes stale instead of being updated.
+    pub fn ignore_working_copy(&mut self) {
+        self.may_update_working_copy = false;
+    }
+
     pub fn check_working_copy_writable(&self) -> Result<(), CommandError> {
         if self.may_update_working_copy {
             Ok(())
@@ -874,7 +940,9 @@ impl WorkspaceCommandHelper {
     #[instrument(skip_all)]
     pub fn maybe_snapshot(&mut self, ui: &Ui) -> Result<(), CommandError> {
         if self.may_update_working_copy {
-            if self.working_copy_shared_with_git {
+            if self.git_worktree {
+                self.import_git_worktree_head(ui)?;
+            } else if self.working_copy_shared_with_git {
                 self.import_git_head(ui)?;
             }
             // Because the Git refs (except HEAD) aren't imported yet, the ref
@@ -947,6 +1015,41 @@ impl WorkspaceCommandHelper {
         Ok(())
     }
 
+    /// Imports new HEAD from the linked Git worktree of this workspace.
+    ///
+    /// This is similar to `import_git_head()`, but the last known HEAD is
+    /// recorded per worktree instead of in the view.
+    #[instrument(skip_all)]
+    fn import_git_worktree_head(&mut self, ui: &Ui) -> Result<(), CommandError> {
+        assert!(self.may_update_working_copy);
+        let command = self.env.command.clone();
+        let worktree_repo = git2::Repository::open(self.workspace_root())?;
+        let workspace_id = self.workspace_id().to_owned();
+        let mut tx = self.start_transaction();
+        let Some(new_git_head_id) =
+            git::import_worktree_head(tx.repo_mut(), &worktree_repo, &workspace_id)?
+        else {
+            return Ok(());
+        };
+
+        let mut tx = tx.into_inner();
+        let new_git_head_commit = tx.repo().store().get_commit(&new_git_head_id)?;
+        tx.repo_mut()
+            .check_out(workspace_id, command.settings(), &new_git_head_commit)?;
+        let mut locked_ws = self.workspace.start_working_copy_mutation()?;
+        // The worktree files were presumably updated by the git command that
+        // moved HEAD, so only the working-copy state needs to be reset.
+        locked_ws.locked_wc().reset(&new_git_head_commit)?;
+        tx.repo_mut().rebase_descendants(command.settings())?;
+        self.user_repo = ReadonlyUserRepo::new(tx.commit("import git head"));
+        locked_ws.finish(self.user_repo.repo.op_id().clone())?;
+        writeln!(
+            ui.status(),
+            "Reset the working copy parent to the new Git HEAD."
+        )?;
+        Ok(())
+    }
+
     /// Imports branches and tags from the underlying Git repo, abandons old
     /// bookmarks.
     ///
@@ -1418,8 +1521,11 @@ impl WorkspaceCommandHelper {
 
     /// Creates commit template language environment for this workspace.
     pub fn commit_template_language(&self) -> CommitTemplateLanguage<'_> {
-        self.env
-            .commit_template_language(self.repo().as_ref(), self.id_prefix_context())
+        self.env.commit_template_language(
+            self.repo().as_ref(),
+            self.repo().operation(),
+            self.id_prefix_context(),
+        )
     }
 
     /// Creates operation template language environment for this workspace.
@@ -1513,6 +1619,9 @@ impl WorkspaceCommandHelper {
         // Compare working-copy tree and operation with repo's, and reload as needed.
         let fsmonitor_settings = self.settings().fsmonitor_settings()?;
         let max_new_file_size = self.settings().max_new_file_size()?;
+        let conflict_marker_style = self.settings().conflict_marker_style()?;
+        let learned_resolutions = self.learned_resolutions();
+        let merge_drivers = self.merge_drivers.clone();
         let command = self.env.command.clone();
         let mut locked_ws = self.workspace.start_working_copy_mutation()?;
         let old_op_id = locked_ws.locked_wc().old_operation_id().clone();
@@ -1566,6 +1675,7 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
             progress: progress.as_ref().map(|x| x as _),
             start_tracking_matcher: &auto_tracking_matcher,
             max_new_file_size,
+            conflict_marker_style,
         })?;
         drop(progress);
         if new_tree_id != *wc_commit.tree_id() {
@@ -1575,12 +1685,20 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
                 command.string_args(),
             );
             tx.set_is_snapshot(true);
+            tx.set_workspace_id(workspace_id.clone());
             let mut_repo = tx.repo_mut();
+            if let Some(merge_drivers) = merge_drivers {
+                mut_repo.set_merge_drivers(merge_drivers);
+            }
+            if let Some(learned_resolutions) = learned_resolutions {
+                mut_repo.set_learned_resolutions(learned_resolutions);
+            }
             let commit = mut_repo
                 .rewrite_commit(command.settings(), &wc_commit)
                 .set_tree_id(new_tree_id)
                 .write()?;
             mut_repo.set_wc_commit(workspace_id, commit.id().clone())?;
+            print_learned_resolutions(ui, mut_repo.learn_resolutions()?)?;
 
             // Rebase descendants
             let num_rebased = mut_repo.rebase_descendants(command.settings())?;
@@ -1602,6 +1720,13 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
         Ok(())
     }
 
+    /// Options to use when checking out commits in the working copy.
+    pub fn checkout_options(&self) -> Result<CheckoutOptions, CommandError> {
+        Ok(CheckoutOptions {
+            conflict_marker_style: self.settings().conflict_marker_style()?,
+        })
+    }
+
     fn update_working_copy(
         &mut self,
         ui: &Ui,
@@ -1609,11 +1734,13 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
         new_commit: &Commit,
     ) -> Result<(), CommandError> {
         assert!(self.may_update_working_copy);
+        let checkout_options = self.checkout_options()?;
         let stats = update_working_copy(
             &self.user_repo.repo,
             &mut self.workspace,
             maybe_old_commit,
             new_commit,
+            &checkout_options,
         )?;
         if Some(new_commit) != maybe_old_commit {
             if let Some(mut formatter) = ui.status_formatter() {
@@ -1645,9 +1772,24 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
         Ok(())
     }
 
+    /// Returns the learned resolutions of the repo if they're enabled by the
+    /// `merge.learn-resolutions` setting.
+    fn learned_resolutions(&self) -> Option<Arc<LearnedResolutions>> {
+        self.settings()
+            .learn_resolutions()
+            .then(|| Arc::new(LearnedResolutions::new(self.workspace.repo_path())))
+    }
+
     pub fn start_transaction(&mut self) -> WorkspaceCommandTransaction {
-        let tx =
+        let mut tx =
             start_repo_transaction(self.repo(), self.settings(), self.env.command.string_args());
+        tx.set_workspace_id(self.workspace_id().clone());
+        if let Some(merge_drivers) = &self.merge_drivers {
+            tx.repo_mut().set_merge_drivers(merge_drivers.clone());
+        }
+        if let Some(learned_resolutions) = self.learned_resolutions() {
+            tx.repo_mut().set_learned_resolutions(learned_resolutions);
+        }
         let id_prefix_context = mem::take(&mut self.user_repo.id_prefix_context);
         WorkspaceCommandTransaction {
             helper: self,
@@ -1662,10 +1804,14 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
         mut tx: Transaction,
         description: impl Into<String>,
     ) -> Result<(), CommandError> {
+        let description = description.into();
         if !tx.repo().has_changes() {
             writeln!(ui.status(), "Nothing changed.")?;
             return Ok(());
         }
+        // Learn before rebasing so that the descendants can reuse the
+        // resolutions.
+        print_learned_resolutions(ui, tx.repo().learn_resolutions()?)?;
         let num_rebased = tx.repo_mut().rebase_descendants(self.settings())?;
         if num_rebased > 0 {
             writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
@@ -1691,6 +1837,20 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
             }
         }
 
+        // The command's changes are complete once they're committed. Errors
+        // after that, e.g. in updating the working copy, don't undo them.
+        tx.set_exit_status(0);
+
+        // The hook runs before anything is exported to Git, so that nothing
+        // changes if it rejects the operation.
+        run_pre_operation_hook(
+            ui,
+            self.settings(),
+            self.workspace_root(),
+            &tx,
+            &description,
+        )?;
+
         let old_repo = tx.base_repo().clone();
 
         let maybe_old_wc_commit = old_repo
@@ -1705,7 +1865,14 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
             .map(|commit_id| tx.repo().store().get_commit(commit_id))
             .transpose()?;
 
-        if self.working_copy_shared_with_git {
+        if self.git_worktree {
+            let worktree_repo = git2::Repository::open(self.workspace_root())?;
+            if let Some(wc_commit) = &maybe_new_wc_commit {
+                git::reset_worktree_head(&worktree_repo, self.workspace_id(), wc_commit)?;
+            }
+            let refs = git::export_refs(tx.repo_mut())?;
+            print_failed_git_export(ui, &refs)?;
+        } else if self.working_copy_shared_with_git {
             let git_repo = self.git_backend().unwrap().open_git_repo()?;
             if let Some(wc_commit) = &maybe_new_wc_commit {
                 git::reset_head(tx.repo_mut(), &git_repo, wc_commit)?;
@@ -1725,6 +1892,20 @@ See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy \
                 // update it.
             }
         }
+        run_post_operation_hook(
+            ui,
+            self.settings(),
+            self.workspace_root(),
+            self.repo().operation(),
+        )?;
+        // Only commands that change the repo fetch in the background, and not
+        // with --ignore-working-copy. Git commands talk to the remotes
+        // themselves.
+        let command = &self.env.command;
+        if command.is_working_copy_writable() && command.matches().subcommand_name() != Some("git")
+        {
+            crate::git_util::maybe_start_auto_fetch(ui, self)?;
+        }
 
         let settings = self.settings();
         if settings.user_name().is_empty() || settings.user_email().is_empty() {
@@ -2019,9 +2200,11 @@ impl WorkspaceCommandTransaction<'_> {
         let id_prefix_context = self
             .id_prefix_context
             .get_or_init(|| self.helper.env.new_id_prefix_context());
-        self.helper
-            .env
-            .commit_template_language(self.tx.repo(), id_prefix_context)
+        self.helper.env.commit_template_language(
+            self.tx.repo(),
+            self.tx.base_repo().operation(),
+            id_prefix_context,
+        )
     }
 
     /// Parses commit template with the current transaction state.
@@ -2115,14 +2298,67 @@ jj git init --colocate",
     }
 }
 
+/// Builds the index of the operation if it's missing, showing the progress.
+/// Building the index of a large repo can take minutes.
+fn build_index_with_progress(
+    ui: &Ui,
+    workspace: &Workspace,
+    op: &Operation,
+) -> Result<(), CommandError> {
+    let repo_loader = workspace.repo_loader();
+    let index_store = repo_loader.index_store();
+    let Some(default_index_store) = index_store.as_any().downcast_ref::<DefaultIndexStore>() else {
+        return Ok(());
+    };
+    // The index caches the parents of the commits, which change with the Git
+    // replace refs and shallow commits.
+    let git_backend = repo_loader
+        .store()
+        .backend_impl()
+        .downcast_ref::<GitBackend>();
+    if let Some(git_backend) = git_backend {
+        if git_backend.indexed_state_changed()? {
+            default_index_store.reinit().map_err(internal_error)?;
+            git_backend.record_indexed_state()?;
+        }
+    }
+    if default_index_store.has_index_at_operation(op.id()) {
+        return Ok(());
+    }
+    // Let the daemon build the index if there's one, so the work isn't repeated
+    // by concurrent commands.
+    if crate::daemon::request_index(workspace.repo_path(), op).is_ok() {
+        return Ok(());
+    }
+    let Some(progress) = crate::progress::index_progress(ui) else {
+        return Ok(());
+    };
+    default_index_store
+        .build_index_at_operation_with_progress(op, repo_loader.store(), Some(&progress))
+        .map_err(|err| internal_error_with_message("Failed to build the commit index", err))?;
+    Ok(())
+}
+
 pub fn start_repo_transaction(
     repo: &Arc<ReadonlyRepo>,
     settings: &UserSettings,
     string_args: &[String],
 ) -> Transaction {
     let mut tx = repo.start_transaction(settings);
-    // TODO: Either do better shell-escaping here or store the values in some list
-    // type (which we currently don't have).
+    let mut args = vec!["jj".to_string()];
+    args.extend(string_args.iter().skip(1).cloned());
+    tx.set_args(args);
+    tx
+}
+
+/// Returns the command line of the command that created the operation, with
+/// the arguments shell-escaped. Operations written by older versions only have
+/// it in the `args` tag.
+pub fn operation_command_line(metadata: &OperationMetadata) -> Option<String> {
+    if metadata.args.is_empty() {
+        return metadata.tags.get("args").cloned();
+    }
+    // TODO: Do better shell-escaping here
     let shell_escape = |arg: &String| {
         if arg.as_bytes().iter().all(|b| {
             matches!(b,
@@ -2143,10 +2379,7 @@ pub fn start_repo_transaction(
             format!("'{}'", arg.replace('\'', "\\'"))
         }
     };
-    let mut quoted_strings = vec!["jj".to_string()];
-    quoted_strings.extend(string_args.iter().skip(1).map(shell_escape));
-    tx.set_tag("args".to_string(), quoted_strings.join(" "));
-    tx
+    Some(metadata.args.iter().map(shell_escape).join(" "))
 }
 
 /// Whether the working copy is stale or not.
@@ -2205,6 +2438,16 @@ pub fn check_stale_working_copy(
     }
 }
 
+fn print_learned_resolutions(ui: &Ui, num_learned: usize) -> io::Result<()> {
+    if num_learned > 0 {
+        writeln!(
+            ui.status(),
+            "Learned the resolutions of {num_learned} conflicted hunks"
+        )?;
+    }
+    Ok(())
+}
+
 #[instrument(skip_all)]
 pub fn print_conflicted_paths(
     conflicts: &[(RepoPathBuf, MergedTreeValue)],
@@ -2220,7 +2463,24 @@ pub fn print_conflicted_paths(
         .into_iter()
         .map(|p| format!("{:width$}", p, width = max_path_len.min(32) + 3));
 
-    for ((_, conflict), formatted_path) in std::iter::zip(conflicts.iter(), formatted_paths) {
+    let store = workspace_command.repo().store();
+    for ((path, conflict), formatted_path) in std::iter::zip(conflicts.iter(), formatted_paths) {
+        if let Some(mode_conflict) = extract_mode_conflict(store, path, conflict).block_on()? {
+            write!(formatter, "{formatted_path} ")?;
+            formatter.with_label("conflict_description", |formatter| {
+                write!(formatter, "mode conflict ")?;
+                for (i, mode) in mode_conflict.modes.adds().flatten().enumerate() {
+                    if i > 0 {
+                        write!(formatter, " vs ")?;
+                    }
+                    write!(formatter.labeled("difficult"), "{mode}")?;
+                }
+                io::Result::Ok(())
+            })?;
+            writeln!(formatter)?;
+            continue;
+        }
+
         let conflict = conflict.clone().simplify();
         let sides = conflict.num_sides();
         let n_adds = conflict.adds().flatten().count();
@@ -2395,13 +2655,19 @@ pub fn update_working_copy(
     workspace: &mut Workspace,
     old_commit: Option<&Commit>,
     new_commit: &Commit,
+    options: &CheckoutOptions,
 ) -> Result<Option<CheckoutStats>, CommandError> {
     let old_tree_id = old_commit.map(|commit| commit.tree_id().clone());
     let stats = if Some(new_commit.tree_id()) != old_tree_id.as_ref() {
         // TODO: CheckoutError::ConcurrentCheckout should probably just result in a
         // warning for most commands (but be an error for the checkout command)
         let stats = workspace
-            .check_out(repo.op_id().clone(), old_tree_id.as_ref(), new_commit)
+            .check_out(
+                repo.op_id().clone(),
+                old_tree_id.as_ref(),
+                new_commit,
+                options,
+            )
             .map_err(|err| {
                 internal_error_with_message(
                     format!("Failed to check out commit {}", new_commit.id().hex()),
@@ -2756,13 +3022,42 @@ pub struct GlobalArgs {
     ///
     /// It is possible to run mutating commands when loading the repo at an
     /// earlier operation. Doing that is equivalent to having run concurrent
-    /// commands starting at the earlier operation. There's rarely a reason to
-    /// do that, but it is possible.
+    /// commands starting at the earlier operation. Use `jj op reconcile`
+    /// afterwards to merge the divergent operations and choose where
+    /// conflicting bookmarks should point.
     #[arg(long, visible_alias = "at-op", global = true)]
     pub at_operation: Option<String>,
+    /// Give up waiting for another jj process after this long
+    ///
+    /// By default, a command that needs to update the working copy waits for
+    /// any other jj process updating it to finish. With this option, the
+    /// command fails instead if the working copy is still locked after the
+    /// given time, such as `30s`, `500ms`, or `2m`. A number without a unit
+    /// is in seconds.
+    #[arg(long, value_name = "TIMEOUT", global = true, value_parser = parse_lock_timeout)]
+    pub wait_for_lock: Option<Duration>,
     /// Enable debug logging
     #[arg(long, global = true)]
     pub debug: bool,
+    /// Print how long the phases of the command took
+    ///
+    /// The time spent in each traced function, such as snapshotting the
+    /// working copy, evaluating revsets, diffing trees, rendering templates,
+    /// and talking to Git remotes, is printed as a table when the command
+    /// exits.
+    ///
+    /// With `--profile=<FILE>`, the timings are instead written to the file as
+    /// a Chrome trace, which can be loaded by `chrome://tracing` or
+    /// https://ui.perfetto.dev.
+    #[arg(
+        long,
+        value_name = "FILE",
+        global = true,
+        num_args = 0..=1,
+        require_equals = true,
+        value_hint = clap::ValueHint::FilePath,
+    )]
+    pub profile: Option<Option<PathBuf>>,
 
     #[command(flatten)]
     pub early_args: EarlyArgs,
@@ -2795,6 +3090,20 @@ pub struct EarlyArgs {
     pub config_toml: Vec<String>,
 }
 
+fn parse_lock_timeout(s: &str) -> Result<Duration, String> {
+    let unit_start = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(unit_start);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid timeout: {s}"))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(number * 60)),
+        _ => Err(format!("Invalid timeout unit: {unit}")),
+    }
+}
+
 /// Wrapper around revset expression argument.
 ///
 /// An empty string is rejected early by the CLI value parser, but it's still
@@ -3034,6 +3343,10 @@ pub fn parse_args(
         // TODO: set up debug logging as early as possible
         tracing_subscription.enable_debug_logging()?;
     }
+    if let Some(trace_path) = &args.global_args.profile {
+        tracing_subscription.enable_profiling(trace_path.clone());
+    }
+    lock::set_lock_timeout(args.global_args.wait_for_lock);
 
     Ok((matches, args))
 }
@@ -3306,6 +3619,7 @@ impl CliRunner {
             commit_template_extensions: self.commit_template_extensions,
             operation_template_extensions: self.operation_template_extensions,
             maybe_workspace_loader,
+            workspace_loader_factory: self.workspace_loader_factory,
             store_factories: self.store_factories,
             working_copy_factories: self.working_copy_factories,
         };
@@ -3331,9 +3645,14 @@ impl CliRunner {
         let layered_configs = LayeredConfigs::from_environment(config);
         let mut ui = Ui::with_config(&layered_configs.merge())
             .expect("default config should be valid, env vars are stringly typed");
+        let tracing_subscription = self.tracing_subscription.clone();
         let result = self.run_internal(&mut ui, layered_configs);
         let exit_code = handle_command_result(&mut ui, result);
         ui.finalize_pager();
+        // Failing to report the profile shouldn't change the exit code.
+        if let Err(err) = tracing_subscription.finish_profiling(&ui) {
+            writeln!(ui.warning_default(), "Failed to write profile: {err}").ok();
+        }
         exit_code
     }
 }
@@ -19,21 +19,28 @@ use std::iter;
 use std::process::ExitCode;
 use std::str;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use itertools::Itertools as _;
+use jj_lib::absorb::AbsorbError;
+use jj_lib::annotate::AnnotateError;
 use jj_lib::backend::BackendError;
+use jj_lib::bisect::BisectError;
 use jj_lib::fileset::FilePatternParseError;
 use jj_lib::fileset::FilesetParseError;
 use jj_lib::fileset::FilesetParseErrorKind;
+use jj_lib::git::GitApplyError;
 use jj_lib::git::GitConfigParseError;
 use jj_lib::git::GitExportError;
 use jj_lib::git::GitImportError;
 use jj_lib::git::GitRemoteManagementError;
 use jj_lib::gitignore::GitIgnoreError;
+use jj_lib::lock::FileLockError;
 use jj_lib::op_heads_store::OpHeadResolutionError;
 use jj_lib::op_store::OpStoreError;
 use jj_lib::op_walk::OpsetEvaluationError;
 use jj_lib::op_walk::OpsetResolutionError;
+use jj_lib::protected_ops::ProtectedOperationsError;
 use jj_lib::repo::CheckOutCommitError;
 use jj_lib::repo::EditCommitError;
 use jj_lib::repo::RepoLoaderError;
@@ -280,6 +287,36 @@ impl From<BackendError> for CommandError {
     }
 }
 
+impl From<AbsorbError> for CommandError {
+    fn from(err: AbsorbError) -> Self {
+        match err {
+            AbsorbError::Backend(err) => err.into(),
+            AbsorbError::RevsetEvaluation(err) => err.into(),
+        }
+    }
+}
+
+impl From<AnnotateError> for CommandError {
+    fn from(err: AnnotateError) -> Self {
+        match err {
+            AnnotateError::Backend(err) => err.into(),
+            AnnotateError::RevsetEvaluation(err) => err.into(),
+        }
+    }
+}
+
+impl From<BisectError> for CommandError {
+    fn from(err: BisectError) -> Self {
+        match err {
+            BisectError::BadIsAncestorOfGood(_) => user_error_with_hint(
+                err,
+                "Mark a descendant of the good revisions as bad, or restart the bisection.",
+            ),
+            BisectError::RevsetEvaluation(err) => err.into(),
+        }
+    }
+}
+
 impl From<WorkspaceInitError> for CommandError {
     fn from(err: WorkspaceInitError) -> Self {
         match err {
@@ -382,6 +419,17 @@ impl From<OpStoreError> for CommandError {
     }
 }
 
+impl From<ProtectedOperationsError> for CommandError {
+    fn from(err: ProtectedOperationsError) -> Self {
+        match err {
+            ProtectedOperationsError::Path(err) => err.into(),
+            ProtectedOperationsError::InvalidId { .. } => {
+                internal_error_with_message("Failed to read the protected operations", err)
+            }
+        }
+    }
+}
+
 impl From<RepoLoaderError> for CommandError {
     fn from(err: RepoLoaderError) -> Self {
         internal_error_with_message("Failed to load the repo", err)
@@ -442,6 +490,19 @@ impl From<git2::Error> for CommandError {
     }
 }
 
+impl From<GitApplyError> for CommandError {
+    fn from(err: GitApplyError) -> Self {
+        match err {
+            GitApplyError::Backend(err) => err.into(),
+            GitApplyError::InternalGitError(err) => err.into(),
+            GitApplyError::InvalidPatch(_)
+            | GitApplyError::InvalidPath(_)
+            | GitApplyError::DoesNotApply(_)
+            | GitApplyError::AlreadyExists(_) => user_error(err),
+        }
+    }
+}
+
 impl From<GitImportError> for CommandError {
     fn from(err: GitImportError) -> Self {
         let hint = match &err {
@@ -559,7 +620,26 @@ impl From<GitConfigParseError> for CommandError {
 
 impl From<WorkingCopyStateError> for CommandError {
     fn from(err: WorkingCopyStateError) -> Self {
-        internal_error_with_message("Failed to access working copy state", err)
+        match err.err.downcast_ref::<FileLockError>() {
+            Some(FileLockError::Timeout { holder, .. }) => {
+                let hint = match holder {
+                    Some(holder) => {
+                        let age = SystemTime::now()
+                            .duration_since(holder.acquired_at)
+                            .unwrap_or_default();
+                        format!(
+                            "The lock has been held by process {} (`{}`) for {}s.",
+                            holder.pid,
+                            holder.command,
+                            age.as_secs()
+                        )
+                    }
+                    None => "Another jj process is probably running.".to_owned(),
+                };
+                user_error_with_hint(err, hint)
+            }
+            _ => internal_error_with_message("Failed to access working copy state", err),
+        }
     }
 }
 
@@ -17,10 +17,12 @@ use std::io::Write;
 use itertools::Itertools as _;
 use jj_lib::commit::CommitIteratorExt;
 use jj_lib::object_id::ObjectId;
+use jj_lib::settings::ConfigResultExt as _;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
@@ -32,6 +34,9 @@ use crate::ui::Ui;
 ///
 /// If a working-copy commit gets abandoned, it will be given a new, empty
 /// commit. This is true in general; it is not specific to this command.
+///
+/// If `ui.confirm.abandon-limit` is set, abandoning more revisions than that
+/// asks for confirmation first.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct AbandonArgs {
     /// The revision(s) to abandon
@@ -61,6 +66,21 @@ pub(crate) fn cmd_abandon(
         return Ok(());
     }
     workspace_command.check_rewritable(to_abandon.iter().ids())?;
+    let abandon_limit: Option<usize> = command
+        .settings()
+        .config()
+        .get("ui.confirm.abandon-limit")
+        .optional()?;
+    // Scripts can't answer the prompt, so only interactive runs are asked.
+    if abandon_limit.is_some_and(|limit| to_abandon.len() > limit)
+        && Ui::can_prompt()
+        && !ui.prompt_yes_no(
+            &format!("Abandon {} revisions?", to_abandon.len()),
+            Some(false),
+        )?
+    {
+        return Err(user_error("Abandon aborted"));
+    }
 
     let mut tx = workspace_command.start_transaction();
     for commit in &to_abandon {
@@ -0,0 +1,166 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools as _;
+use jj_lib::absorb::absorb_hunks;
+use jj_lib::absorb::split_hunks_to_trees;
+use jj_lib::absorb::AbsorbSource;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::repo::Repo as _;
+use jj_lib::revset::RevsetExpression;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::diff_util::DiffFormat;
+use crate::ui::Ui;
+
+/// Move changes from a revision into the stack of mutable revisions
+///
+/// This command splits changes in the source revision and moves each change to
+/// the closest mutable ancestor where the corresponding lines were modified
+/// last. If the destination revision cannot be determined unambiguously, the
+/// change will be left in the source revision. Changes that only add lines are
+/// always left in the source revision.
+///
+/// The source revision will not be abandoned even if all changes are moved
+/// into the ancestor revisions.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct AbsorbArgs {
+    /// Source revision to absorb from
+    #[arg(long, short, default_value = "@")]
+    from: RevisionArg,
+    /// Destination revisions to absorb into
+    ///
+    /// Only ancestors of the source revision will be considered.
+    #[arg(long, short = 't', visible_alias = "to", default_value = "mutable()")]
+    into: Vec<RevisionArg>,
+    /// Show which revisions the changes would be absorbed into, without
+    /// changing anything
+    #[arg(long)]
+    dry_run: bool,
+    /// Move only changes to these paths (instead of all paths)
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_absorb(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &AbsorbArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+
+    let source_commit = workspace_command.resolve_single_rev(ui, &args.from)?;
+    let mut destinations = workspace_command.parse_union_revsets(ui, &args.into)?;
+    destinations.intersect_with(
+        &RevsetExpression::commit(source_commit.id().clone())
+            .parents()
+            .ancestors(),
+    );
+    // Sorted from the newest to the oldest
+    let destination_ids = destinations.evaluate_to_commit_ids()?.collect_vec();
+
+    let matcher = workspace_command
+        .parse_file_patterns(ui, &args.paths)?
+        .to_matcher();
+
+    let repo = workspace_command.repo().as_ref();
+    let source = AbsorbSource::from_commit(repo, source_commit)?;
+    let selected_trees = split_hunks_to_trees(
+        repo,
+        &source,
+        &RevsetExpression::commits(destination_ids.clone()),
+        matcher.as_ref(),
+    )?;
+    for (path, reason) in &selected_trees.skipped_paths {
+        let ui_path = workspace_command.format_file_path(path);
+        writeln!(ui.warning_default(), "Skipping {ui_path}: {reason}")?;
+    }
+    if selected_trees.target_commits.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    if args.dry_run {
+        if let Some(mut formatter) = ui.status_formatter() {
+            writeln!(formatter, "Would absorb changes into these revisions:")?;
+            for id in &destination_ids {
+                if !selected_trees.target_commits.contains_key(id) {
+                    continue;
+                }
+                let commit = repo.store().get_commit(id)?;
+                write!(formatter, "  ")?;
+                workspace_command.write_commit_summary(formatter.as_mut(), &commit)?;
+                writeln!(formatter)?;
+            }
+        }
+        return Ok(());
+    }
+
+    workspace_command.check_rewritable(selected_trees.target_commits.keys())?;
+    let mut tx = workspace_command.start_transaction();
+    let stats = absorb_hunks(
+        tx.repo_mut(),
+        command.settings(),
+        &source,
+        selected_trees.target_commits,
+    )?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Absorbed changes into these revisions:")?;
+        // The destinations are rewritten from the oldest to the newest
+        for commit in stats.rewritten_destinations.iter().rev() {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+        if stats.num_rebased > 0 {
+            writeln!(
+                formatter,
+                "Rebased {} descendant commits.",
+                stats.num_rebased
+            )?;
+        }
+    }
+    tx.finish(
+        ui,
+        format!(
+            "absorb changes into {} commits",
+            stats.rewritten_destinations.len()
+        ),
+    )?;
+
+    if let Some(commit) = &stats.rewritten_source {
+        let repo = workspace_command.repo().as_ref();
+        if let Some(mut formatter) = ui.status_formatter() {
+            if commit.is_empty(repo)? {
+                writeln!(formatter, "All changes were absorbed.")?;
+            } else {
+                writeln!(formatter, "Remaining changes:")?;
+                let diff_renderer = workspace_command.diff_renderer(vec![DiffFormat::Summary]);
+                let width = ui.term_width();
+                diff_renderer.show_patch(
+                    ui,
+                    formatter.as_mut(),
+                    commit,
+                    &EverythingMatcher,
+                    width,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
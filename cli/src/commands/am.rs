@@ -0,0 +1,364 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use bstr::ByteSlice as _;
+use chrono::DateTime;
+use jj_lib::backend::Timestamp;
+use jj_lib::git;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::commands::git::apply::read_patches;
+use crate::git_util::get_git_repo;
+use crate::ui::Ui;
+
+/// Apply patch emails as new commits
+///
+/// The emails are read from mbox files, as written by `jj format-patch` or
+/// `git format-patch`. Each email becomes a commit on top of the previous one,
+/// with the author and the date of the email. The description is taken from
+/// the subject, without the `[PATCH ...]` prefix, and the body up to the `---`
+/// line.
+///
+/// Patches that don't apply cleanly are merged like with `jj git apply`.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct AmArgs {
+    /// The mbox files to read, or `-` to read from stdin
+    ///
+    /// If no files are given, the emails are read from stdin.
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    mboxes: Vec<PathBuf>,
+    /// The revision to apply the patches on top of
+    #[arg(long, short, default_value = "@")]
+    destination: RevisionArg,
+}
+
+/// A patch parsed from an email.
+#[derive(Debug, PartialEq, Eq)]
+struct PatchEmail {
+    author_name: String,
+    author_email: String,
+    date: Option<Timestamp>,
+    description: String,
+    patch: Vec<u8>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_am(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &AmArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let destination = workspace_command.resolve_single_rev(ui, &args.destination)?;
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+    let mut emails = vec![];
+    for mbox in read_patches(&args.mboxes)? {
+        for message in split_mbox(&mbox) {
+            emails.push(parse_patch_email(message)?);
+        }
+    }
+    if emails.is_empty() {
+        writeln!(ui.status(), "No patches to apply.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let mut parent = destination.clone();
+    let mut tree = destination.tree()?;
+    let mut new_commits = vec![];
+    let mut conflicted_paths = vec![];
+    for email in emails {
+        let applied = git::apply_patch(&git_repo, &tree, &email.patch)?;
+        conflicted_paths.extend(applied.conflicted_paths);
+        tree = applied.tree;
+        let mut author = command.settings().signature();
+        author.name = email.author_name;
+        author.email = email.author_email;
+        if let Some(date) = email.date {
+            author.timestamp = date;
+        }
+        parent = tx
+            .repo_mut()
+            .new_commit(command.settings(), vec![parent.id().clone()], tree.id())
+            .set_description(email.description)
+            .set_author(author)
+            .write()?;
+        new_commits.push(parent.clone());
+    }
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Applied {} patches:", new_commits.len())?;
+        for commit in &new_commits {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+    }
+    if !conflicted_paths.is_empty() {
+        writeln!(
+            ui.warning_default(),
+            "There were conflicts in {} files when merging the patches:",
+            conflicted_paths.len()
+        )?;
+        for path in &conflicted_paths {
+            writeln!(
+                ui.warning_no_heading(),
+                "  {}",
+                tx.base_workspace_helper().format_file_path(path)
+            )?;
+        }
+    }
+    tx.finish(
+        ui,
+        format!(
+            "apply {} patches on top of commit {}",
+            new_commits.len(),
+            destination.id().hex()
+        ),
+    )?;
+    Ok(())
+}
+
+/// Splits an mbox into messages. Messages start with a `From ` line. Input
+/// without such a line is a single message.
+fn split_mbox(mbox: &[u8]) -> Vec<&[u8]> {
+    let mut starts = vec![];
+    let mut offset = 0;
+    let mut after_blank_line = true;
+    for line in mbox.split_inclusive(|&b| b == b'\n') {
+        if after_blank_line && is_from_line(line) {
+            starts.push(offset);
+        }
+        after_blank_line = line.trim().is_empty();
+        offset += line.len();
+    }
+    if starts.is_empty() {
+        return if mbox.trim().is_empty() {
+            vec![]
+        } else {
+            vec![mbox]
+        };
+    }
+    starts.push(mbox.len());
+    starts
+        .windows(2)
+        .map(|range| &mbox[range[0]..range[1]])
+        .collect()
+}
+
+/// Checks if the line is the `From ` line starting a message, which is followed
+/// by the sender and a date, e.g. `From 1234abcd Mon Sep 17 00:00:00 2001`.
+fn is_from_line(line: &[u8]) -> bool {
+    let Some(rest) = line.strip_prefix(b"From ") else {
+        return false;
+    };
+    rest.split_once_str(" ")
+        .is_some_and(|(sender, date)| !sender.is_empty() && date.contains(&b':'))
+}
+
+fn parse_patch_email(message: &[u8]) -> Result<PatchEmail, CommandError> {
+    let message = message.to_str_lossy();
+    let mut lines = message.lines().peekable();
+    if lines
+        .peek()
+        .is_some_and(|line| is_from_line(line.as_bytes()))
+    {
+        lines.next();
+    }
+    // Headers, unfolding continuation lines
+    let mut headers: Vec<(String, String)> = vec![];
+    for line in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_owned()));
+        }
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(header_name, _)| header_name == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let subject = strip_subject_prefix(header("subject").unwrap_or_default());
+    let Some(from) = header("from") else {
+        return Err(user_error(format!(
+            "Email \"{subject}\" has no From header"
+        )));
+    };
+    let (author_name, author_email) = parse_address(from);
+    let date = header("date")
+        .map(|date| {
+            DateTime::parse_from_rfc2822(date)
+                .map_err(|err| user_error(format!("Invalid date in email \"{subject}\": {err}")))
+        })
+        .transpose()?
+        .map(Timestamp::from_datetime);
+
+    let mut body = vec![];
+    let mut patch = String::new();
+    let mut in_patch = false;
+    let mut in_diffstat = false;
+    for line in lines {
+        if in_patch {
+            // The signature of `git format-patch`
+            if line == "-- " {
+                break;
+            }
+            patch.push_str(line);
+            patch.push('\n');
+        } else if line.starts_with("diff --git ") || line.starts_with("--- ") {
+            in_patch = true;
+            patch.push_str(line);
+            patch.push('\n');
+        } else if line == "---" {
+            in_diffstat = true;
+        } else if !in_diffstat {
+            body.push(line);
+        }
+    }
+    if patch.is_empty() {
+        return Err(user_error(format!("Email \"{subject}\" has no patch")));
+    }
+    let body = body.join("\n");
+    let body = body.trim();
+    let description = if body.is_empty() {
+        format!("{subject}\n")
+    } else {
+        format!("{subject}\n\n{body}\n")
+    };
+    Ok(PatchEmail {
+        author_name,
+        author_email,
+        date,
+        description,
+        patch: patch.into_bytes(),
+    })
+}
+
+/// Removes the `[PATCH ...]` prefix from a subject.
+fn strip_subject_prefix(subject: &str) -> &str {
+    let mut subject = subject.trim();
+    while let Some(rest) = subject.strip_prefix('[') {
+        let Some((_, rest)) = rest.split_once(']') else {
+            break;
+        };
+        subject = rest.trim_start();
+    }
+    subject
+}
+
+/// Parses an address like `Name <email>` into the name and the email.
+fn parse_address(address: &str) -> (String, String) {
+    match address.rsplit_once('<') {
+        Some((name, email)) => {
+            let name = name.trim().trim_matches('"');
+            let email = email.trim_end().trim_end_matches('>');
+            (name.to_owned(), email.to_owned())
+        }
+        None => (String::new(), address.trim().to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_patch_email() {
+        let message = indoc! {"
+            From 1234567890abcdef1234567890abcdef12345678 Mon Sep 17 00:00:00 2001
+            From: \"Some One\" <some.one@example.com>
+            Date: Tue, 1 Oct 2024 12:34:56 +0200
+            Subject: [PATCH v2 1/2] file: change
+             the contents
+            Message-Id: <1234@example.com>
+
+            The body.
+            ---
+             file | 2 +-
+             1 file changed, 1 insertion(+), 1 deletion(-)
+
+            diff --git a/file b/file
+            --- a/file
+            +++ b/file
+            @@ -1 +1 @@
+            -a
+            +b
+            -- 
+            jj 0.22.0
+        "};
+        let email = parse_patch_email(message.as_bytes()).unwrap();
+        assert_eq!(email.author_name, "Some One");
+        assert_eq!(email.author_email, "some.one@example.com");
+        assert_eq!(
+            email.date.map(|date| (date.timestamp.0, date.tz_offset)),
+            Some((1727778896000, 120))
+        );
+        assert_eq!(
+            email.description,
+            "file: change the contents\n\nThe body.\n"
+        );
+        assert_eq!(
+            String::from_utf8(email.patch).unwrap(),
+            indoc! {"
+                diff --git a/file b/file
+                --- a/file
+                +++ b/file
+                @@ -1 +1 @@
+                -a
+                +b
+            "}
+        );
+    }
+
+    #[test]
+    fn test_split_mbox() {
+        let mbox = indoc! {b"
+            From a Mon Sep 17 00:00:00 2001
+            Subject: one
+
+            From the body
+
+            From b Mon Sep 17 00:00:00 2001
+            Subject: two
+        "};
+        assert_eq!(split_mbox(mbox), vec![&mbox[..61], &mbox[61..]]);
+        assert_eq!(split_mbox(b"Subject: one\n"), vec![&b"Subject: one\n"[..]]);
+        assert!(split_mbox(b"\n").is_empty());
+    }
+
+    #[test]
+    fn test_strip_subject_prefix() {
+        assert_eq!(strip_subject_prefix("[PATCH v2 1/3] foo"), "foo");
+        assert_eq!(strip_subject_prefix("[RFC] [PATCH] foo [bar]"), "foo [bar]");
+        assert_eq!(strip_subject_prefix("foo"), "foo");
+    }
+}
@@ -24,6 +24,8 @@ use criterion::BatchSize;
 use criterion::BenchmarkGroup;
 use criterion::BenchmarkId;
 use criterion::Criterion;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
 use jj_lib::object_id::HexPrefix;
 use jj_lib::repo::Repo;
 use jj_lib::revset;
@@ -35,6 +37,7 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::CommandError;
+use crate::commit_templater::CommitTemplateLanguage;
 use crate::ui::Ui;
 
 /// Commands for benchmarking internal operations
@@ -45,6 +48,8 @@ pub enum BenchCommand {
     CommonAncestors(BenchCommonAncestorsArgs),
     #[command(name = "isancestor")]
     IsAncestor(BenchIsAncestorArgs),
+    #[command(name = "log")]
+    Log(BenchLogArgs),
     #[command(name = "resolveprefix")]
     ResolvePrefix(BenchResolvePrefixArgs),
     #[command(name = "revset")]
@@ -69,6 +74,24 @@ pub struct BenchIsAncestorArgs {
     criterion: CriterionArgs,
 }
 
+/// Render the log template of the revisions, without the graph
+///
+/// The commits are loaded before the benchmark, so only the rendering is
+/// measured.
+#[derive(clap::Args, Clone, Debug)]
+pub struct BenchLogArgs {
+    /// Which revisions to render
+    #[arg(default_value = "::")]
+    revisions: RevisionArg,
+    /// Render each revision using the given template
+    ///
+    /// Defaults to the `templates.log` setting.
+    #[arg(long, short = 'T')]
+    template: Option<String>,
+    #[command(flatten)]
+    criterion: CriterionArgs,
+}
+
 /// Walk the revisions in the revset
 #[derive(clap::Args, Clone, Debug)]
 #[command(group(clap::ArgGroup::new("revset_source").required(true)))]
@@ -168,6 +191,47 @@ pub(crate) fn cmd_bench(
                 routine,
             )?;
         }
+        BenchCommand::Log(args) => {
+            let workspace_command = command.workspace_helper(ui)?;
+            let commits: Vec<Commit> = workspace_command
+                .parse_revset(ui, &args.revisions)?
+                .evaluate_to_commits()?
+                .try_collect()?;
+            let template_string = match &args.template {
+                Some(value) => value.clone(),
+                None => command.settings().config().get_string("templates.log")?,
+            };
+            let language = workspace_command.commit_template_language();
+            let template = workspace_command.parse_template(
+                ui,
+                &language,
+                &template_string,
+                CommitTemplateLanguage::wrap_commit,
+            )?;
+            let formatter_factory = ui.formatter_factory().clone();
+            let routine = || {
+                // Reused for all commits like `jj log` does.
+                let mut buffer = vec![];
+                let mut total_len = 0;
+                for commit in &commits {
+                    buffer.clear();
+                    template
+                        .format(
+                            commit,
+                            formatter_factory.new_formatter(&mut buffer).as_mut(),
+                        )
+                        .unwrap();
+                    total_len += buffer.len();
+                }
+                total_len
+            };
+            run_bench(
+                ui,
+                &format!("log-{}", args.revisions),
+                &args.criterion,
+                routine,
+            )?;
+        }
         BenchCommand::ResolvePrefix(args) => {
             let workspace_command = command.workspace_helper(ui)?;
             let prefix = HexPrefix::new(&args.prefix).unwrap();
@@ -0,0 +1,147 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::BisectState;
+use tracing::instrument;
+
+use super::advance_bisection;
+use super::load_bisect_state;
+use super::resolve_marked_commit_ids;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Mark a revision as having the bug
+///
+/// Unlike good revisions, there is only one bad revision. Marking another
+/// revision as bad replaces the previous one.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectBadArgs {
+    /// The bad revision [default: the revision being tested]
+    revision: Option<RevisionArg>,
+}
+
+/// Mark revisions as not having the bug
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectGoodArgs {
+    /// The good revisions [default: the revision being tested]
+    revisions: Vec<RevisionArg>,
+}
+
+/// Skip revisions that can't be tested
+///
+/// Skipped revisions are not checked out again. If only skipped revisions are
+/// left, the bisection ends with the list of revisions that could have
+/// introduced the bug.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectSkipArgs {
+    /// The revisions to skip [default: the revision being tested]
+    revisions: Vec<RevisionArg>,
+}
+
+/// How a revision was judged while bisecting.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Verdict {
+    Bad,
+    Good,
+    Skip,
+}
+
+impl Verdict {
+    pub(super) fn name(self) -> &'static str {
+        match self {
+            Verdict::Bad => "bad",
+            Verdict::Good => "good",
+            Verdict::Skip => "skipped",
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_bad(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectBadArgs,
+) -> Result<(), CommandError> {
+    mark_revisions(ui, command, args.revision.as_slice(), Verdict::Bad)
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_good(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectGoodArgs,
+) -> Result<(), CommandError> {
+    mark_revisions(ui, command, &args.revisions, Verdict::Good)
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_skip(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectSkipArgs,
+) -> Result<(), CommandError> {
+    mark_revisions(ui, command, &args.revisions, Verdict::Skip)
+}
+
+fn mark_revisions(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    revisions: &[RevisionArg],
+    verdict: Verdict,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let state = load_bisect_state(&workspace_command)?;
+    let commit_ids = resolve_marked_commit_ids(ui, &workspace_command, &state, revisions)?;
+    let mut tx = workspace_command.start_transaction();
+    let state = apply_verdict(state, &commit_ids, verdict)?;
+    advance_bisection(ui, &mut tx, state)?;
+    let description = format!(
+        "mark {} as {} in bisection",
+        commit_ids.iter().map(short_commit_hash).join(", "),
+        verdict.name()
+    );
+    tx.finish(ui, description)?;
+    Ok(())
+}
+
+/// Records the verdict for the revisions in the bisection state.
+pub(super) fn apply_verdict(
+    mut state: BisectState,
+    commit_ids: &[CommitId],
+    verdict: Verdict,
+) -> Result<BisectState, CommandError> {
+    match verdict {
+        Verdict::Bad => {
+            let [commit_id] = commit_ids else {
+                return Err(user_error("Only one revision can be marked as bad"));
+            };
+            state.bad = Some(commit_id.clone());
+        }
+        Verdict::Good => {
+            state.good.extend(commit_ids.iter().cloned());
+            state.good = state.good.into_iter().unique().collect();
+        }
+        Verdict::Skip => {
+            state.skipped.extend(commit_ids.iter().cloned());
+            state.skipped = state.skipped.into_iter().unique().collect();
+        }
+    }
+    Ok(state)
+}
@@ -0,0 +1,206 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod mark;
+mod reset;
+mod run;
+mod start;
+
+use std::collections::HashSet;
+use std::io::Write as _;
+
+use clap::Subcommand;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::bisect::next_step;
+use jj_lib::bisect::BisectState;
+use jj_lib::bisect::BisectStep;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use self::mark::cmd_bisect_bad;
+use self::mark::cmd_bisect_good;
+use self::mark::cmd_bisect_skip;
+use self::mark::BisectBadArgs;
+use self::mark::BisectGoodArgs;
+use self::mark::BisectSkipArgs;
+use self::reset::cmd_bisect_reset;
+use self::reset::BisectResetArgs;
+use self::run::cmd_bisect_run;
+use self::run::BisectRunArgs;
+use self::start::cmd_bisect_start;
+use self::start::BisectStartArgs;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::cli_util::WorkspaceCommandTransaction;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Find the revision that introduced a bug by bisection
+///
+/// Start with `jj bisect start`, then mark a revision that has the bug with
+/// `jj bisect bad` and one that doesn't with `jj bisect good`. Each step checks
+/// out a new working-copy commit on top of the next revision to test, until
+/// the first bad revision is found. Revisions that can't be tested can be
+/// skipped with `jj bisect skip`, and `jj bisect run` automates the testing
+/// with a command.
+///
+/// The bisection state is recorded in the operation log, so a mistake can be
+/// corrected with `jj undo`. Use `jj bisect reset` to end the bisection and
+/// return to the original working-copy commit.
+#[derive(Subcommand, Clone, Debug)]
+pub(crate) enum BisectCommand {
+    Bad(BisectBadArgs),
+    Good(BisectGoodArgs),
+    Reset(BisectResetArgs),
+    Run(BisectRunArgs),
+    Skip(BisectSkipArgs),
+    Start(BisectStartArgs),
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &BisectCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        BisectCommand::Bad(args) => cmd_bisect_bad(ui, command, args),
+        BisectCommand::Good(args) => cmd_bisect_good(ui, command, args),
+        BisectCommand::Reset(args) => cmd_bisect_reset(ui, command, args),
+        BisectCommand::Run(args) => cmd_bisect_run(ui, command, args),
+        BisectCommand::Skip(args) => cmd_bisect_skip(ui, command, args),
+        BisectCommand::Start(args) => cmd_bisect_start(ui, command, args),
+    }
+}
+
+/// Loads the state of the bisection, failing if none is in progress.
+fn load_bisect_state(
+    workspace_command: &WorkspaceCommandHelper,
+) -> Result<BisectState, CommandError> {
+    let state = BisectState::load(workspace_command.repo().view());
+    if !state.is_in_progress() {
+        return Err(user_error_with_hint(
+            "No bisection in progress",
+            "Use `jj bisect start` to start one.",
+        ));
+    }
+    Ok(state)
+}
+
+/// Resolves the revisions to mark. Defaults to the revision being tested, or
+/// to the working-copy commit if no revision has been checked out yet.
+fn resolve_marked_commit_ids(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    state: &BisectState,
+    revisions: &[RevisionArg],
+) -> Result<Vec<CommitId>, CommandError> {
+    if revisions.is_empty() {
+        if let Some(id) = &state.current {
+            return Ok(vec![id.clone()]);
+        }
+        let commit = workspace_command.resolve_single_rev(ui, &RevisionArg::AT)?;
+        return Ok(vec![commit.id().clone()]);
+    }
+    let ids: Vec<_> = workspace_command
+        .parse_union_revsets(ui, revisions)?
+        .evaluate_to_commit_ids()?
+        .collect();
+    Ok(ids)
+}
+
+/// Saves the state, and checks out the next revision to test if a good and a
+/// bad revision are known.
+///
+/// Returns the step that was taken, or `None` if more revisions have to be
+/// marked first.
+fn advance_bisection(
+    ui: &Ui,
+    tx: &mut WorkspaceCommandTransaction,
+    mut state: BisectState,
+) -> Result<Option<BisectStep>, CommandError> {
+    let step = match &state.bad {
+        None => {
+            writeln!(
+                ui.hint_default(),
+                "Mark a revision that has the bug with `jj bisect bad`."
+            )?;
+            None
+        }
+        Some(_) if state.good.is_empty() => {
+            writeln!(
+                ui.hint_default(),
+                "Mark a revision that doesn't have the bug with `jj bisect good`."
+            )?;
+            None
+        }
+        Some(bad) => {
+            let skipped: HashSet<_> = state.skipped.iter().cloned().collect();
+            Some(next_step(tx.repo(), bad, &state.good, &skipped)?)
+        }
+    };
+
+    match &step {
+        None => {}
+        Some(BisectStep::Test {
+            commit_id,
+            remaining,
+        }) => {
+            let commit = tx.repo().store().get_commit(commit_id)?;
+            tx.check_out(&commit)?;
+            state.current = Some(commit_id.clone());
+            // Each step halves the remaining revisions in the best case.
+            let steps = usize::BITS - remaining.leading_zeros();
+            writeln!(
+                ui.status(),
+                "Bisecting: {remaining} revisions left to test after this (roughly {steps} \
+                 steps)"
+            )?;
+            if let Some(mut formatter) = ui.status_formatter() {
+                write!(formatter, "Now testing: ")?;
+                tx.write_commit_summary(formatter.as_mut(), &commit)?;
+                writeln!(formatter)?;
+            }
+        }
+        Some(BisectStep::Found(commit_id)) => {
+            let commit = tx.repo().store().get_commit(commit_id)?;
+            let mut formatter = ui.stdout_formatter();
+            write!(formatter, "The first bad revision is: ")?;
+            tx.write_commit_summary(formatter.as_mut(), &commit)?;
+            writeln!(formatter)?;
+        }
+        Some(BisectStep::Inconclusive(commit_ids)) => {
+            let commits: Vec<_> = commit_ids
+                .iter()
+                .map(|id| tx.repo().store().get_commit(id))
+                .try_collect()?;
+            let mut formatter = ui.stdout_formatter();
+            writeln!(
+                formatter,
+                "Only skipped revisions are left to test. The first bad revision could be \
+                 any of:"
+            )?;
+            for commit in &commits {
+                write!(formatter, "  ")?;
+                tx.write_commit_summary(formatter.as_mut(), commit)?;
+                writeln!(formatter)?;
+            }
+        }
+    }
+    state.save(tx.repo_mut());
+    Ok(step)
+}
@@ -0,0 +1,62 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::bisect::BisectState;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use super::load_bisect_state;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// End the bisection
+///
+/// The working copy returns to the commit it was at when the bisection was
+/// started, unless that commit has since been rewritten or abandoned.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectResetArgs {}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_reset(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &BisectResetArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let state = load_bisect_state(&workspace_command)?;
+    let original_id = state.original.unwrap();
+    let original_commit = workspace_command.repo().store().get_commit(&original_id)?;
+    let is_visible = workspace_command
+        .repo()
+        .resolve_change_id(original_commit.change_id())
+        .is_some_and(|ids| ids.contains(&original_id));
+
+    let mut tx = workspace_command.start_transaction();
+    if is_visible {
+        if tx.base_workspace_helper().get_wc_commit_id() != Some(&original_id) {
+            tx.edit(&original_commit)?;
+        }
+    } else {
+        writeln!(
+            ui.warning_default(),
+            "The original working-copy commit {} is no longer visible; staying at the current \
+             working-copy commit",
+            tx.format_commit_summary(&original_commit)
+        )?;
+    }
+    BisectState::default().save(tx.repo_mut());
+    tx.finish(ui, "reset bisection")?;
+    Ok(())
+}
@@ -0,0 +1,116 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+use std::process::Stdio;
+use std::slice;
+
+use jj_lib::bisect::BisectState;
+use jj_lib::bisect::BisectStep;
+use tracing::instrument;
+
+use super::advance_bisection;
+use super::load_bisect_state;
+use super::mark::apply_verdict;
+use super::mark::Verdict;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::commands::run::shell;
+use crate::ui::Ui;
+
+/// Test revisions with a command until the first bad revision is found
+///
+/// The command is run with the shell in the workspace root, with the revision
+/// being tested checked out. Its exit status determines how the revision is
+/// marked: 0 means good, 125 means that the revision can't be tested and is
+/// skipped, and any other status up to 127 means bad. Any other status, or
+/// termination by a signal, stops the bisection.
+///
+/// The command should not leave changes in the working copy, since they would
+/// be overwritten when the next revision is checked out.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectRunArgs {
+    /// The command to test each revision with
+    command: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_run(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectRunArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    load_bisect_state(&workspace_command)?;
+    run_bisection(ui, &mut workspace_command, &args.command)
+}
+
+/// Runs the command on the revision being tested and marks it accordingly,
+/// until the bisection ends.
+pub(super) fn run_bisection(
+    ui: &mut Ui,
+    workspace_command: &mut WorkspaceCommandHelper,
+    shell_command: &str,
+) -> Result<(), CommandError> {
+    loop {
+        let state = BisectState::load(workspace_command.repo().view());
+        let Some(commit_id) = state.current.clone() else {
+            return Err(user_error_with_hint(
+                "No revision to test",
+                "Mark a good and a bad revision first.",
+            ));
+        };
+        writeln!(
+            ui.status(),
+            "Running '{shell_command}' on revision {}",
+            short_commit_hash(&commit_id)
+        )?;
+        let status = shell(shell_command)
+            .current_dir(workspace_command.workspace_root())
+            .stdin(Stdio::null())
+            .stdout(ui.stderr_for_child()?)
+            .stderr(ui.stderr_for_child()?)
+            .status()
+            .map_err(|err| user_error(format!("Failed to run '{shell_command}': {err}")))?;
+        let verdict = match status.code() {
+            Some(0) => Verdict::Good,
+            Some(125) => Verdict::Skip,
+            Some(1..=127) => Verdict::Bad,
+            _ => {
+                return Err(user_error(format!(
+                    "Command '{shell_command}' failed with {status}, stopping the bisection"
+                )));
+            }
+        };
+
+        let mut tx = workspace_command.start_transaction();
+        let state = apply_verdict(state, slice::from_ref(&commit_id), verdict)?;
+        let step = advance_bisection(ui, &mut tx, state)?;
+        tx.finish(
+            ui,
+            format!(
+                "mark {} as {} in bisection",
+                short_commit_hash(&commit_id),
+                verdict.name()
+            ),
+        )?;
+        if !matches!(step, Some(BisectStep::Test { .. })) {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::bisect::BisectState;
+use tracing::instrument;
+
+use super::advance_bisection;
+use super::run::run_bisection;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Start a bisection
+///
+/// The current working-copy commit is recorded so `jj bisect reset` can return
+/// to it. If both a bad and a good revision are given, the first revision to
+/// test is checked out right away.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct BisectStartArgs {
+    /// A revision that has the bug
+    #[arg(long)]
+    bad: Option<RevisionArg>,
+    /// Revisions that don't have the bug
+    #[arg(long)]
+    good: Vec<RevisionArg>,
+    /// Test each revision with this command, like `jj bisect run`
+    #[arg(long, value_name = "COMMAND", requires = "bad", requires = "good")]
+    run: Option<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_bisect_start(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BisectStartArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    if BisectState::load(workspace_command.repo().view()).is_in_progress() {
+        return Err(user_error_with_hint(
+            "A bisection is already in progress",
+            "Use `jj bisect reset` to end it first.",
+        ));
+    }
+    let Some(wc_commit_id) = workspace_command.get_wc_commit_id().cloned() else {
+        return Err(user_error("This command requires a working copy"));
+    };
+    let bad = args
+        .bad
+        .as_ref()
+        .map(|arg| workspace_command.resolve_single_rev(ui, arg))
+        .transpose()?;
+    let good: Vec<_> = if args.good.is_empty() {
+        vec![]
+    } else {
+        workspace_command
+            .parse_union_revsets(ui, &args.good)?
+            .evaluate_to_commit_ids()?
+            .collect()
+    };
+    let state = BisectState {
+        original: Some(wc_commit_id),
+        bad: bad.map(|commit| commit.id().clone()),
+        good,
+        ..Default::default()
+    };
+
+    let mut tx = workspace_command.start_transaction();
+    advance_bisection(ui, &mut tx, state)?;
+    tx.finish(ui, "start bisection")?;
+    if let Some(shell_command) = &args.run {
+        run_bisection(ui, &mut workspace_command, shell_command)?;
+    }
+    Ok(())
+}
@@ -12,26 +12,47 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
+
 use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
 use jj_lib::op_store::RefTarget;
 use jj_lib::str_util::StringPattern;
 
-use super::find_local_bookmarks;
+use super::find_bookmarks_with;
 use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 /// Delete an existing bookmark and propagate the deletion to remotes on the
 /// next push
+///
+/// If `--revisions` options are given, bookmarks currently pointing to the
+/// specified revisions will be deleted. The bookmarks can also be filtered by
+/// names.
+///
+/// Example: delete the bookmarks of the commits that were merged into `main`
+///
+/// $ jj bookmark delete -r '::main ~ main'
+///
+/// If `ui.confirm.bookmark-delete-pattern` is enabled, deleting bookmarks
+/// selected by a pattern other than an exact name asks for confirmation first.
 #[derive(clap::Args, Clone, Debug)]
+#[command(group(clap::ArgGroup::new("source").multiple(true).required(true)))]
 pub struct BookmarkDeleteArgs {
     /// The bookmarks to delete
     ///
     /// By default, the specified name matches exactly. Use `glob:` prefix to
     /// select bookmarks by wildcard pattern. For details, see
     /// https://martinvonz.github.io/jj/latest/revsets/#string-patterns.       
-    #[arg(required = true, value_parser = StringPattern::parse)]
+    #[arg(group = "source", value_parser = StringPattern::parse)]
     names: Vec<StringPattern>,
+
+    /// Delete bookmarks pointing to the given revisions
+    #[arg(long, short, group = "source")]
+    revisions: Vec<RevisionArg>,
 }
 
 pub fn cmd_bookmark_delete(
@@ -41,7 +62,46 @@ pub fn cmd_bookmark_delete(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo().clone();
-    let matched_bookmarks = find_local_bookmarks(repo.view(), &args.names)?;
+    let target_ids: Option<HashSet<CommitId>> = if !args.revisions.is_empty() {
+        let expression = workspace_command.parse_union_revsets(ui, &args.revisions)?;
+        Some(expression.evaluate_to_commit_ids()?.collect())
+    } else {
+        None
+    };
+    let is_target_commit = |id: &CommitId| match &target_ids {
+        Some(target_ids) => target_ids.contains(id),
+        None => true,
+    };
+    let matched_bookmarks = if !args.names.is_empty() {
+        find_bookmarks_with(&args.names, |pattern| {
+            repo.view()
+                .local_bookmarks_matching(pattern)
+                .filter(|(_, target)| target.added_ids().any(&is_target_commit))
+        })?
+    } else {
+        repo.view()
+            .local_bookmarks()
+            .filter(|(_, target)| target.added_ids().any(&is_target_commit))
+            .collect()
+    };
+    let confirm_pattern: bool = command
+        .settings()
+        .config()
+        .get("ui.confirm.bookmark-delete-pattern")?;
+    if confirm_pattern
+        && args.names.iter().any(|pattern| !pattern.is_exact())
+        && !matched_bookmarks.is_empty()
+        && Ui::can_prompt()
+        && !ui.prompt_yes_no(
+            &format!(
+                "Delete bookmarks {}?",
+                matched_bookmarks.iter().map(|(name, _)| name).join(", ")
+            ),
+            Some(false),
+        )?
+    {
+        return Err(user_error("Bookmark deletion aborted"));
+    }
     let mut tx = workspace_command.start_transaction();
     for (name, _) in &matched_bookmarks {
         tx.repo_mut()
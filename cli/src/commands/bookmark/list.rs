@@ -14,6 +14,7 @@
 
 use std::collections::HashSet;
 
+use itertools::Itertools as _;
 use jj_lib::git;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::str_util::StringPattern;
@@ -51,6 +52,14 @@ pub struct BookmarkListArgs {
     #[arg(long, short, conflicts_with_all = ["all_remotes"])]
     conflicted: bool,
 
+    /// Group bookmarks by namespace
+    ///
+    /// The namespace of a bookmark is the part of its name before the last
+    /// `/`, so `feature/ui/menu` is listed under `feature/ui/`. Bookmarks
+    /// without a namespace are listed first.
+    #[arg(long)]
+    group_by_prefix: bool,
+
     /// Show bookmarks whose local name matches
     ///
     /// By default, the specified name matches exactly. Use `glob:` prefix to
@@ -130,13 +139,30 @@ pub fn cmd_bookmark_list(
 
     let mut found_deleted_local_bookmark = false;
     let mut found_deleted_tracking_local_bookmark = false;
-    let bookmarks_to_list = view.bookmarks().filter(|(name, target)| {
-        bookmark_names_to_list
-            .as_ref()
-            .map_or(true, |bookmark_names| bookmark_names.contains(name))
-            && (!args.conflicted || target.local_target.has_conflict())
-    });
+    let mut bookmarks_to_list = view
+        .bookmarks()
+        .filter(|(name, target)| {
+            bookmark_names_to_list
+                .as_ref()
+                .map_or(true, |bookmark_names| bookmark_names.contains(name))
+                && (!args.conflicted || target.local_target.has_conflict())
+        })
+        .collect_vec();
+    if args.group_by_prefix {
+        // The bookmarks are already sorted by name, and the sort is stable.
+        bookmarks_to_list.sort_by_key(|(name, _)| bookmark_namespace(name));
+    }
+    let mut last_namespace = "";
     for (name, bookmark_target) in bookmarks_to_list {
+        if args.group_by_prefix {
+            let namespace = bookmark_namespace(name);
+            if namespace != last_namespace {
+                formatter.with_label("bookmark_list", |formatter| {
+                    writeln!(formatter.labeled("namespace"), "{namespace}/")
+                })?;
+                last_namespace = namespace;
+            }
+        }
         let local_target = bookmark_target.local_target;
         let remote_refs = bookmark_target.remote_refs;
         let (mut tracking_remote_refs, untracked_remote_refs) = remote_refs
@@ -200,3 +226,9 @@ pub fn cmd_bookmark_list(
 
     Ok(())
 }
+
+/// Returns the part of the bookmark name before the last `/`, or an empty
+/// string if there's none.
+fn bookmark_namespace(name: &str) -> &str {
+    name.rsplit_once('/').map_or("", |(namespace, _)| namespace)
+}
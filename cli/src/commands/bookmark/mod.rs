@@ -99,15 +99,6 @@ pub fn cmd_bookmark(
     }
 }
 
-fn find_local_bookmarks<'a>(
-    view: &'a View,
-    name_patterns: &[StringPattern],
-) -> Result<Vec<(&'a str, &'a RefTarget)>, CommandError> {
-    find_bookmarks_with(name_patterns, |pattern| {
-        view.local_bookmarks_matching(pattern)
-    })
-}
-
 fn find_bookmarks_with<'a, 'b, V, I: Iterator<Item = (&'a str, V)>>(
     name_patterns: &'b [StringPattern],
     mut find_matches: impl FnMut(&'b StringPattern) -> I,
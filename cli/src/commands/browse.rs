@@ -0,0 +1,807 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::RefCell;
+use std::io;
+use std::io::IsTerminal as _;
+use std::rc::Rc;
+
+use crossterm::cursor;
+use crossterm::event;
+use crossterm::event::Event;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use crossterm::execute;
+use crossterm::queue;
+use crossterm::style::Attribute;
+use crossterm::style::SetAttribute;
+use crossterm::terminal;
+use indexmap::IndexSet;
+use jj_lib::backend::CommitId;
+use jj_lib::graph::GraphEdgeType;
+use jj_lib::graph::TopoGroupedGraphIterator;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::rewrite::RebaseOptions;
+use tracing::instrument;
+
+use super::log::get_node_template;
+use super::rebase::rebase_descendants_transaction;
+use crate::cli_util::format_template;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::LogContentFormat;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::commit_templater::CommitTemplateLanguage;
+use crate::description_util::description_template;
+use crate::description_util::edit_description;
+use crate::diff_util::DiffFormatArgs;
+use crate::graphlog::get_graphlog;
+use crate::graphlog::Edge;
+use crate::graphlog::GraphStyle;
+use crate::ui::Ui;
+
+/// Browse the revision history interactively
+///
+/// Shows the same graph as `jj log` in a full-screen view, where revisions can
+/// be selected with the arrow keys (or `j` and `k`). The following keys act on
+/// the selected revision:
+///
+/// * `Enter`: Show the revision and its diff
+/// * `n`: Create a new revision on top of it
+/// * `e`: Edit it
+/// * `D`: Edit its description
+/// * `a`: Abandon it
+/// * `r`: Rebase it and its descendants onto another revision, which is
+///   selected next and confirmed with `Enter`
+/// * `q`: Quit
+#[derive(clap::Args, Clone, Debug)]
+#[command(verbatim_doc_comment)]
+pub(crate) struct BrowseArgs {
+    /// Which revisions to show. Defaults to the `revsets.log` setting.
+    #[arg(long, short)]
+    revisions: Vec<RevisionArg>,
+    /// Render each revision using the given template
+    ///
+    /// For the syntax, see https://martinvonz.github.io/jj/latest/templates/
+    #[arg(long, short = 'T')]
+    template: Option<String>,
+    #[command(flatten)]
+    diff_format: DiffFormatArgs,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_browse(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &BrowseArgs,
+) -> Result<(), CommandError> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(user_error("`jj browse` must be run in a terminal"));
+    }
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let mut view = render_log(ui, command, &workspace_command, args)?;
+    let mut state = BrowseState::default();
+    if let Some(wc_commit_id) = workspace_command.get_wc_commit_id() {
+        state.select_commit(&view, wc_commit_id);
+    }
+
+    let terminal = Terminal::enter()?;
+    loop {
+        let (width, height) = terminal::size()?;
+        draw(&mut io::stdout(), &mut state, &view, width, height)?;
+        let key = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            _ => continue,
+        };
+        let action = match state.handle_key(key, &view) {
+            Action::None => continue,
+            Action::Quit => break,
+            Action::ShowDiff(commit_id) => {
+                let lines = render_diff(ui, command, &workspace_command, args, &commit_id)?;
+                state.mode = Mode::Diff { lines, scroll: 0 };
+                continue;
+            }
+            action => action,
+        };
+        // Actions may print messages or launch an editor, so they run outside
+        // of the full-screen view.
+        terminal.suspend()?;
+        let result = run_action(ui, command, &mut workspace_command, &action);
+        terminal.resume()?;
+        state.message = Some(match result {
+            Ok(message) => message,
+            Err(err) => format!("Error: {}", err.error),
+        });
+        let selected_commit_id = state.selected_commit_id(&view).cloned();
+        view = render_log(ui, command, &workspace_command, args)?;
+        match (&action, workspace_command.get_wc_commit_id()) {
+            (Action::New(_) | Action::Edit(_), Some(wc_commit_id)) => {
+                state.select_commit(&view, wc_commit_id);
+            }
+            _ => {
+                if let Some(commit_id) = &selected_commit_id {
+                    state.select_commit(&view, commit_id);
+                }
+            }
+        }
+        state.selected = state.selected.min(view.entries.len().saturating_sub(1));
+    }
+    drop(terminal);
+    Ok(())
+}
+
+/// The rendered graph, with the position of each revision in it.
+#[derive(Debug, Default)]
+struct LogView {
+    lines: Vec<String>,
+    /// The revisions in the order they are shown, with the index of the first
+    /// line of each.
+    entries: Vec<(CommitId, usize)>,
+}
+
+impl LogView {
+    /// Returns the range of lines that show the entry.
+    fn entry_lines(&self, index: usize) -> std::ops::Range<usize> {
+        let start = self.entries[index].1;
+        let end = self
+            .entries
+            .get(index + 1)
+            .map_or(self.lines.len(), |(_, line)| *line);
+        start..end
+    }
+}
+
+/// A `Write` that can be read back while a formatter is writing to it.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Renders the graph like `jj log` does, keeping track of the lines of each
+/// revision.
+fn render_log(
+    ui: &Ui,
+    command: &CommandHelper,
+    workspace_command: &WorkspaceCommandHelper,
+    args: &BrowseArgs,
+) -> Result<LogView, CommandError> {
+    let revset_expression = if args.revisions.is_empty() {
+        workspace_command
+            .parse_revset(ui, &RevisionArg::from(command.settings().default_revset()))?
+    } else {
+        workspace_command.parse_union_revsets(ui, &args.revisions)?
+    };
+    let revset = revset_expression.evaluate()?;
+    let store = workspace_command.repo().store();
+    let graph_style = GraphStyle::from_settings(command.settings())?;
+    let with_content_format = LogContentFormat::new(ui, command.settings())?;
+    let language = workspace_command.commit_template_language();
+    let template_string = match &args.template {
+        Some(value) => value.to_string(),
+        None => command.settings().config().get_string("templates.log")?,
+    };
+    let template = workspace_command
+        .parse_template(
+            ui,
+            &language,
+            &template_string,
+            CommitTemplateLanguage::wrap_commit,
+        )?
+        .labeled("log");
+    let node_template = workspace_command
+        .parse_template(
+            ui,
+            &language,
+            &get_node_template(graph_style, command.settings())?,
+            CommitTemplateLanguage::wrap_commit_opt,
+        )?
+        .labeled("node");
+
+    let output = SharedBuffer::default();
+    let mut entries = vec![];
+    {
+        let mut formatter = ui.new_formatter(output.clone());
+        let mut graph = get_graphlog(graph_style, formatter.as_mut());
+        for (commit_id, edges) in TopoGroupedGraphIterator::new(revset.iter_graph()) {
+            let mut graphlog_edges = vec![];
+            let mut has_missing = false;
+            for edge in edges {
+                match edge.edge_type {
+                    GraphEdgeType::Missing => has_missing = true,
+                    GraphEdgeType::Direct => graphlog_edges.push(Edge::Direct(edge.target)),
+                    GraphEdgeType::Indirect => graphlog_edges.push(Edge::Indirect(edge.target)),
+                }
+            }
+            if has_missing {
+                graphlog_edges.push(Edge::Missing);
+            }
+            let commit = store.get_commit(&commit_id)?;
+            let mut buffer = vec![];
+            let within_graph =
+                with_content_format.sub_width(graph.width(&commit_id, &graphlog_edges));
+            within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
+                template.format(&commit, formatter)
+            })?;
+            if !buffer.ends_with(b"\n") {
+                buffer.push(b'\n');
+            }
+            let node_symbol = format_template(ui, &Some(commit), &node_template);
+            let first_line = output.0.borrow().iter().filter(|b| **b == b'\n').count();
+            entries.push((commit_id.clone(), first_line));
+            graph.add_node(
+                &commit_id,
+                &graphlog_edges,
+                &node_symbol,
+                &String::from_utf8_lossy(&buffer),
+            )?;
+        }
+    }
+    let lines = String::from_utf8_lossy(&output.0.borrow())
+        .lines()
+        .map(ToOwned::to_owned)
+        .collect();
+    Ok(LogView { lines, entries })
+}
+
+/// Renders the revision like `jj show` does.
+fn render_diff(
+    ui: &Ui,
+    command: &CommandHelper,
+    workspace_command: &WorkspaceCommandHelper,
+    args: &BrowseArgs,
+    commit_id: &CommitId,
+) -> Result<Vec<String>, CommandError> {
+    let commit = workspace_command.repo().store().get_commit(commit_id)?;
+    let template_string = command.settings().config().get_string("templates.show")?;
+    let template = workspace_command.parse_commit_template(ui, &template_string)?;
+    let diff_renderer = workspace_command.diff_renderer_for(&args.diff_format)?;
+    let mut output = vec![];
+    {
+        let mut formatter = ui.new_formatter(&mut output);
+        template.format(&commit, formatter.as_mut())?;
+        diff_renderer.show_patch(
+            ui,
+            formatter.as_mut(),
+            &commit,
+            &EverythingMatcher,
+            ui.term_width(),
+        )?;
+    }
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+/// Runs the action, returning a message that describes the result.
+fn run_action(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    workspace_command: &mut WorkspaceCommandHelper,
+    action: &Action,
+) -> Result<String, CommandError> {
+    let settings = command.settings();
+    let store = workspace_command.repo().store().clone();
+    match action {
+        Action::None | Action::Quit | Action::ShowDiff(_) => Ok(String::new()),
+        Action::New(commit_id) => {
+            let parent = store.get_commit(commit_id)?;
+            let mut tx = workspace_command.start_transaction();
+            let new_commit = tx
+                .repo_mut()
+                .new_commit(
+                    settings,
+                    vec![parent.id().clone()],
+                    parent.tree_id().clone(),
+                )
+                .write()?;
+            tx.edit(&new_commit)?;
+            tx.finish(ui, "new empty commit")?;
+            Ok(format!(
+                "Created new commit {}",
+                short_commit_hash(new_commit.id())
+            ))
+        }
+        Action::Edit(commit_id) => {
+            let commit = store.get_commit(commit_id)?;
+            workspace_command.check_rewritable([commit.id()])?;
+            if workspace_command.get_wc_commit_id() == Some(commit.id()) {
+                return Ok("Already editing that commit".to_owned());
+            }
+            let mut tx = workspace_command.start_transaction();
+            tx.edit(&commit)?;
+            tx.finish(ui, format!("edit commit {}", commit.id().hex()))?;
+            Ok(format!("Now editing {}", short_commit_hash(commit.id())))
+        }
+        Action::Describe(commit_id) => {
+            let commit = store.get_commit(commit_id)?;
+            workspace_command.check_rewritable([commit.id()])?;
+            let mut tx = workspace_command.start_transaction();
+            let mut commit_builder = tx.repo_mut().rewrite_commit(settings, &commit).detach();
+            if commit_builder.description().is_empty() {
+                commit_builder.set_description(settings.default_description());
+            }
+            let temp_commit = commit_builder.write_hidden()?;
+            let template = description_template(ui, &tx, "", &temp_commit)?;
+            let description = edit_description(tx.base_workspace_helper(), &template, settings)?;
+            if description == commit.description() {
+                return Ok("Description unchanged".to_owned());
+            }
+            tx.repo_mut()
+                .rewrite_commit(settings, &commit)
+                .set_description(description)
+                .write()?;
+            tx.repo_mut().rebase_descendants(settings)?;
+            tx.finish(ui, format!("describe commit {}", commit.id().hex()))?;
+            Ok(format!("Described {}", short_commit_hash(commit.id())))
+        }
+        Action::Abandon(commit_id) => {
+            let commit = store.get_commit(commit_id)?;
+            workspace_command.check_rewritable([commit.id()])?;
+            let mut tx = workspace_command.start_transaction();
+            tx.repo_mut().record_abandoned_commit(commit.id().clone());
+            tx.repo_mut().rebase_descendants(settings)?;
+            tx.finish(ui, format!("abandon commit {}", commit.id().hex()))?;
+            Ok(format!("Abandoned {}", short_commit_hash(commit.id())))
+        }
+        Action::Rebase {
+            source,
+            destination,
+        } => {
+            let source = store.get_commit(source)?;
+            let destination = store.get_commit(destination)?;
+            rebase_descendants_transaction(
+                ui,
+                settings,
+                workspace_command,
+                vec![destination.clone()],
+                &IndexSet::from([source.clone()]),
+                RebaseOptions::default(),
+            )?;
+            Ok(format!(
+                "Rebased {} onto {}",
+                short_commit_hash(source.id()),
+                short_commit_hash(destination.id())
+            ))
+        }
+    }
+}
+
+/// Switches the terminal to a full-screen view, and restores it when dropped.
+struct Terminal;
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        let terminal = Terminal;
+        terminal.resume()?;
+        Ok(terminal)
+    }
+
+    fn resume(&self) -> io::Result<()> {
+        terminal::enable_raw_mode()?;
+        execute!(
+            io::stdout(),
+            terminal::EnterAlternateScreen,
+            terminal::DisableLineWrap,
+            cursor::Hide
+        )
+    }
+
+    fn suspend(&self) -> io::Result<()> {
+        execute!(
+            io::stdout(),
+            cursor::Show,
+            terminal::EnableLineWrap,
+            terminal::LeaveAlternateScreen
+        )?;
+        terminal::disable_raw_mode()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        self.suspend().ok();
+    }
+}
+
+/// How far the page up and down keys move.
+const PAGE_SIZE: usize = 20;
+
+/// What the keys currently do.
+#[derive(Debug, Default, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    Log,
+    /// Showing the selected revision, scrolled down by `scroll` lines.
+    Diff { lines: Vec<String>, scroll: usize },
+    /// Waiting for confirmation before abandoning the selected revision.
+    ConfirmAbandon,
+    /// Selecting the destination to rebase `source` onto.
+    RebaseDestination { source: CommitId },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    None,
+    Quit,
+    ShowDiff(CommitId),
+    New(CommitId),
+    Edit(CommitId),
+    Describe(CommitId),
+    Abandon(CommitId),
+    Rebase {
+        source: CommitId,
+        destination: CommitId,
+    },
+}
+
+#[derive(Debug, Default)]
+struct BrowseState {
+    mode: Mode,
+    /// Index of the selected entry of the log.
+    selected: usize,
+    /// The first line of the log that is shown.
+    top: usize,
+    /// Message to show in the status line until the next key press.
+    message: Option<String>,
+}
+
+impl BrowseState {
+    fn selected_commit_id<'a>(&self, view: &'a LogView) -> Option<&'a CommitId> {
+        view.entries.get(self.selected).map(|(id, _)| id)
+    }
+
+    fn select_commit(&mut self, view: &LogView, commit_id: &CommitId) {
+        if let Some(index) = view.entries.iter().position(|(id, _)| id == commit_id) {
+            self.selected = index;
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, view: &LogView) -> Action {
+        self.message = None;
+        let is_ctrl_c =
+            key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+        if is_ctrl_c {
+            return Action::Quit;
+        }
+        if let Mode::Diff { lines, scroll } = &mut self.mode {
+            let max_scroll = lines.len().saturating_sub(1);
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down => *scroll = (*scroll + 1).min(max_scroll),
+                KeyCode::Char('k') | KeyCode::Up => *scroll = scroll.saturating_sub(1),
+                KeyCode::Char(' ') | KeyCode::PageDown => {
+                    *scroll = (*scroll + PAGE_SIZE).min(max_scroll);
+                }
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(PAGE_SIZE),
+                KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => self.mode = Mode::Log,
+                _ => {}
+            }
+            return Action::None;
+        }
+        if self.mode == Mode::ConfirmAbandon {
+            self.mode = Mode::Log;
+            return match (key.code, self.selected_commit_id(view)) {
+                (KeyCode::Char('y'), Some(id)) => Action::Abandon(id.clone()),
+                _ => {
+                    self.message = Some("Not abandoned".to_owned());
+                    Action::None
+                }
+            };
+        }
+
+        let num_entries = view.entries.len();
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = (self.selected + 1).min(num_entries.saturating_sub(1));
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::PageDown => {
+                self.selected = (self.selected + PAGE_SIZE).min(num_entries.saturating_sub(1));
+            }
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(PAGE_SIZE),
+            KeyCode::Char('g') | KeyCode::Home => self.selected = 0,
+            KeyCode::Char('G') | KeyCode::End => self.selected = num_entries.saturating_sub(1),
+            KeyCode::Esc if matches!(self.mode, Mode::RebaseDestination { .. }) => {
+                self.mode = Mode::Log;
+                self.message = Some("Rebase cancelled".to_owned());
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            _ => {
+                let Some(commit_id) = self.selected_commit_id(view).cloned() else {
+                    return Action::None;
+                };
+                if let Mode::RebaseDestination { source } = &self.mode {
+                    if key.code != KeyCode::Enter {
+                        return Action::None;
+                    }
+                    let source = source.clone();
+                    self.mode = Mode::Log;
+                    return Action::Rebase {
+                        source,
+                        destination: commit_id,
+                    };
+                }
+                match key.code {
+                    KeyCode::Enter => return Action::ShowDiff(commit_id),
+                    KeyCode::Char('n') => return Action::New(commit_id),
+                    KeyCode::Char('e') => return Action::Edit(commit_id),
+                    KeyCode::Char('D') => return Action::Describe(commit_id),
+                    KeyCode::Char('a') => self.mode = Mode::ConfirmAbandon,
+                    KeyCode::Char('r') => {
+                        self.mode = Mode::RebaseDestination { source: commit_id };
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Action::None
+    }
+
+    /// Scrolls the log so that the selected entry is visible within `height`
+    /// lines.
+    fn scroll_to_selection(&mut self, view: &LogView, height: usize) {
+        if view.entries.is_empty() {
+            return;
+        }
+        let lines = view.entry_lines(self.selected);
+        if lines.start < self.top {
+            self.top = lines.start;
+        } else if lines.end > self.top + height {
+            self.top = lines.end.saturating_sub(height).min(lines.start);
+        }
+    }
+
+    fn status_line(&self) -> String {
+        if let Some(message) = &self.message {
+            return message.clone();
+        }
+        match self.mode {
+            Mode::Log => {
+                "enter: show  n: new  e: edit  D: describe  a: abandon  r: rebase  q: quit"
+                    .to_owned()
+            }
+            Mode::Diff { .. } => "j/k: scroll  space: page down  q: back".to_owned(),
+            Mode::ConfirmAbandon => "Abandon the selected revision? (y/n)".to_owned(),
+            Mode::RebaseDestination { .. } => {
+                "Select the destination and press enter, or esc to cancel".to_owned()
+            }
+        }
+    }
+}
+
+fn draw(
+    out: &mut impl io::Write,
+    state: &mut BrowseState,
+    view: &LogView,
+    width: u16,
+    height: u16,
+) -> io::Result<()> {
+    let body_height = usize::from(height.saturating_sub(1));
+    queue!(out, terminal::Clear(terminal::ClearType::All))?;
+    if !matches!(state.mode, Mode::Diff { .. }) {
+        state.scroll_to_selection(view, body_height);
+    }
+    let mut rows: Vec<(&str, &str)> = vec![];
+    if let Mode::Diff { lines, scroll } = &state.mode {
+        rows.extend(
+            lines
+                .iter()
+                .skip(*scroll)
+                .take(body_height)
+                .map(|line| ("", line.as_str())),
+        );
+    } else {
+        let selected_lines = if view.entries.is_empty() {
+            0..0
+        } else {
+            view.entry_lines(state.selected)
+        };
+        let source_lines = match &state.mode {
+            Mode::RebaseDestination { source } => view
+                .entries
+                .iter()
+                .position(|(id, _)| id == source)
+                .map_or(0..0, |index| view.entry_lines(index)),
+            _ => 0..0,
+        };
+        for (line_number, line) in view
+            .lines
+            .iter()
+            .enumerate()
+            .skip(state.top)
+            .take(body_height)
+        {
+            let marker = if selected_lines.contains(&line_number) {
+                "> "
+            } else if source_lines.contains(&line_number) {
+                "* "
+            } else {
+                "  "
+            };
+            rows.push((marker, line.as_str()));
+        }
+    }
+    for (row, (marker, line)) in rows.into_iter().enumerate() {
+        queue!(out, cursor::MoveTo(0, row as u16))?;
+        write!(out, "{marker}{line}")?;
+        queue!(out, SetAttribute(Attribute::Reset))?;
+    }
+    let status: String = state.status_line().chars().take(width.into()).collect();
+    queue!(
+        out,
+        cursor::MoveTo(0, height.saturating_sub(1)),
+        SetAttribute(Attribute::Reverse)
+    )?;
+    write!(out, "{status:width$}", width = usize::from(width))?;
+    queue!(out, SetAttribute(Attribute::Reset))?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn test_view() -> LogView {
+        let lines = (0..6).map(|i| format!("line {i}")).collect();
+        let entries = vec![
+            (CommitId::from_hex("aa"), 0),
+            (CommitId::from_hex("bb"), 2),
+            (CommitId::from_hex("cc"), 4),
+        ];
+        LogView { lines, entries }
+    }
+
+    #[test]
+    fn test_move_selection() {
+        let view = test_view();
+        let mut state = BrowseState::default();
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('j')), &view),
+            Action::None
+        );
+        assert_eq!(state.selected, 1);
+        state.handle_key(key(KeyCode::Down), &view);
+        state.handle_key(key(KeyCode::Down), &view);
+        assert_eq!(state.selected, 2);
+        state.handle_key(key(KeyCode::Char('k')), &view);
+        assert_eq!(state.selected, 1);
+        state.handle_key(key(KeyCode::Char('g')), &view);
+        assert_eq!(state.selected, 0);
+        state.handle_key(key(KeyCode::Char('G')), &view);
+        assert_eq!(state.selected, 2);
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('q')), &view),
+            Action::Quit
+        );
+    }
+
+    #[test]
+    fn test_scroll_to_selection() {
+        let view = test_view();
+        let mut state = BrowseState::default();
+        state.scroll_to_selection(&view, 3);
+        assert_eq!(state.top, 0);
+        state.selected = 2;
+        state.scroll_to_selection(&view, 3);
+        assert_eq!(state.top, 3);
+        state.selected = 0;
+        state.scroll_to_selection(&view, 3);
+        assert_eq!(state.top, 0);
+    }
+
+    #[test]
+    fn test_actions() {
+        let view = test_view();
+        let mut state = BrowseState {
+            selected: 1,
+            ..Default::default()
+        };
+        let bb = CommitId::from_hex("bb");
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('n')), &view),
+            Action::New(bb.clone())
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('e')), &view),
+            Action::Edit(bb.clone())
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('D')), &view),
+            Action::Describe(bb.clone())
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Enter), &view),
+            Action::ShowDiff(bb.clone())
+        );
+
+        // Abandoning needs confirmation
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('a')), &view),
+            Action::None
+        );
+        assert_eq!(state.mode, Mode::ConfirmAbandon);
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('n')), &view),
+            Action::None
+        );
+        assert_eq!(state.mode, Mode::Log);
+        state.handle_key(key(KeyCode::Char('a')), &view);
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('y')), &view),
+            Action::Abandon(bb.clone())
+        );
+
+        // The rebase destination is selected after the source
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('r')), &view),
+            Action::None
+        );
+        state.handle_key(key(KeyCode::Down), &view);
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('n')), &view),
+            Action::None
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Enter), &view),
+            Action::Rebase {
+                source: bb,
+                destination: CommitId::from_hex("cc"),
+            }
+        );
+        assert_eq!(state.mode, Mode::Log);
+
+        // Escape cancels the rebase instead of quitting
+        state.handle_key(key(KeyCode::Char('r')), &view);
+        assert_eq!(state.handle_key(key(KeyCode::Esc), &view), Action::None);
+        assert_eq!(state.mode, Mode::Log);
+    }
+
+    #[test]
+    fn test_diff_mode() {
+        let view = test_view();
+        let mut state = BrowseState {
+            mode: Mode::Diff {
+                lines: vec!["a".to_owned(), "b".to_owned()],
+                scroll: 0,
+            },
+            ..Default::default()
+        };
+        state.handle_key(key(KeyCode::Char('j')), &view);
+        state.handle_key(key(KeyCode::Char('j')), &view);
+        assert!(matches!(state.mode, Mode::Diff { scroll: 1, .. }));
+        // Moving doesn't change the selection while the diff is shown
+        assert_eq!(state.selected, 0);
+        state.handle_key(key(KeyCode::Char('q')), &view);
+        assert_eq!(state.mode, Mode::Log);
+    }
+}
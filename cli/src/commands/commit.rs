@@ -21,6 +21,7 @@ use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
+use crate::description_util::finalize_description;
 use crate::description_util::join_message_paragraphs;
 use crate::ui::Ui;
 
@@ -117,6 +118,7 @@ new working-copy commit.
         let template = description_template(ui, &tx, "", &temp_commit)?;
         edit_description(tx.base_workspace_helper(), &template, command.settings())?
     };
+    let description = finalize_description(ui, &tx, &commit, description)?;
     commit_builder.set_description(description);
     let new_commit = commit_builder.write(tx.repo_mut())?;
 
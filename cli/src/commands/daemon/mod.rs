@@ -0,0 +1,67 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod run;
+mod start;
+mod status;
+mod stop;
+
+use clap::Subcommand;
+use tracing::instrument;
+
+use self::run::cmd_daemon_run;
+use self::run::DaemonRunArgs;
+use self::start::cmd_daemon_start;
+use self::start::DaemonStartArgs;
+use self::status::cmd_daemon_status;
+use self::status::DaemonStatusArgs;
+use self::stop::cmd_daemon_stop;
+use self::stop::DaemonStopArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage a background process that keeps the commit index ready
+///
+/// Loading the commit index dominates the startup time of commands in large
+/// repos, especially after other processes added operations. The daemon keeps
+/// the indexes at the current operations loaded and builds the indexes of new
+/// operations as soon as they appear. Once an index is stacked on many segment
+/// files, the daemon squashes them into one, so commands load it faster.
+/// Commands ask the daemon for a missing index instead of building it
+/// themselves.
+///
+/// There's one daemon per repo, shared by all workspaces. The daemon is only
+/// supported on Unix.
+#[derive(Subcommand, Clone, Debug)]
+pub(crate) enum DaemonCommand {
+    Run(DaemonRunArgs),
+    Start(DaemonStartArgs),
+    Status(DaemonStatusArgs),
+    Stop(DaemonStopArgs),
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_daemon(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &DaemonCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        DaemonCommand::Run(args) => cmd_daemon_run(ui, command, args),
+        DaemonCommand::Start(args) => cmd_daemon_start(ui, command, args),
+        DaemonCommand::Status(args) => cmd_daemon_status(ui, command, args),
+        DaemonCommand::Stop(args) => cmd_daemon_stop(ui, command, args),
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::daemon;
+use crate::ui::Ui;
+
+/// Run the daemon in the foreground
+///
+/// The daemon runs until it's stopped by `jj daemon stop`.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct DaemonRunArgs {}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_daemon_run(
+    _ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DaemonRunArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    daemon::serve(workspace.repo_loader(), workspace.repo_path())
+        .map_err(|err| user_error_with_message("Failed to run the daemon", err))?;
+    Ok(())
+}
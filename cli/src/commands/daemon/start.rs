@@ -0,0 +1,66 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+use std::thread;
+use std::time::Duration;
+
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::daemon;
+use crate::git_util::spawn_detached_jj;
+use crate::ui::Ui;
+
+/// Start the daemon in the background
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct DaemonStartArgs {}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_daemon_start(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DaemonStartArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    let repo_path = workspace.repo_path();
+    if daemon::is_running(repo_path) {
+        writeln!(ui.status(), "The daemon is already running.")?;
+        return Ok(());
+    }
+    let workspace_root = workspace.workspace_root();
+    let args = [
+        "-R".to_owned(),
+        workspace_root.to_string_lossy().into_owned(),
+        "daemon".to_owned(),
+        "run".to_owned(),
+    ];
+    spawn_detached_jj(workspace_root, &args)
+        .map_err(|err| user_error_with_message("Failed to start the daemon", err))?;
+    // Wait until the daemon accepts requests, so commands run right after this
+    // one can use it.
+    for _ in 0..50 {
+        if daemon::is_running(repo_path) {
+            writeln!(ui.status(), "Started the daemon.")?;
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    Err(user_error(
+        "The daemon didn't start. Run `jj daemon run` to see why.",
+    ))
+}
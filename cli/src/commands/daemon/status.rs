@@ -0,0 +1,41 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::daemon;
+use crate::ui::Ui;
+
+/// Show whether the daemon is running
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct DaemonStatusArgs {}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_daemon_status(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DaemonStatusArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    if daemon::is_running(workspace.repo_path()) {
+        writeln!(ui.stdout(), "The daemon is running.")?;
+    } else {
+        writeln!(ui.stdout(), "The daemon is not running.")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,93 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use itertools::Itertools as _;
+use jj_lib::fast_import::write_fast_export;
+use jj_lib::fast_import::FastExportError;
+use jj_lib::repo::Repo as _;
+
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Write commits as a `git fast-import` stream
+///
+/// Parents of the exported commits that aren't exported themselves are
+/// referred to by commit id. Conflicted files are written with conflict
+/// markers.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugFastExportArgs {
+    /// The revisions to export
+    #[arg(long, short, default_value = "~root()")]
+    revisions: Vec<RevisionArg>,
+    /// Write the stream to this file instead of stdout
+    #[arg(long, short, value_hint = clap::ValueHint::FilePath)]
+    output: Option<PathBuf>,
+}
+
+pub fn cmd_debug_fast_export(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugFastExportArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let root_commit_id = repo.store().root_commit_id();
+    // Revsets are evaluated in reverse topological order.
+    let commits: Vec<_> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .filter_ok(|commit| commit.id() != root_commit_id)
+        .try_collect()?;
+    let commits = commits.into_iter().rev().collect_vec();
+
+    let stats = if let Some(path) = &args.output {
+        let file = File::create(path).map_err(|err| {
+            user_error_with_message(format!("Failed to create {}", path.display()), err)
+        })?;
+        let mut output = BufWriter::new(file);
+        let stats = write_fast_export(repo.as_ref(), &commits, &mut output)
+            .map_err(map_fast_export_error)?;
+        output.flush()?;
+        stats
+    } else {
+        let mut output = ui.stdout();
+        write_fast_export(repo.as_ref(), &commits, &mut output).map_err(map_fast_export_error)?
+    };
+    for commit_id in &stats.conflicted_commits {
+        writeln!(
+            ui.warning_default(),
+            "Commit {} has conflicts, which were written with conflict markers",
+            short_commit_hash(commit_id)
+        )?;
+    }
+    writeln!(ui.status(), "Exported {} commits", stats.num_commits)?;
+    Ok(())
+}
+
+fn map_fast_export_error(err: FastExportError) -> CommandError {
+    match err {
+        FastExportError::Io(err) => err.into(),
+        FastExportError::Backend(err) => err.into(),
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use jj_lib::fast_import::read_fast_import;
+use jj_lib::fast_import::FastImportError;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Create commits from a `git fast-import` stream
+///
+/// Refs named `refs/heads/*` and `refs/tags/*` are imported as bookmarks and
+/// tags. Commits without parents in the stream become children of the root
+/// commit. New change ids are assigned to the imported commits.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugFastImportArgs {
+    /// Read the stream from this file instead of stdin
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    input: Option<PathBuf>,
+}
+
+pub fn cmd_debug_fast_import(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugFastImportArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let mut input: Box<dyn BufRead> = if let Some(path) = &args.input {
+        let file = File::open(path).map_err(|err| {
+            user_error_with_message(format!("Failed to open {}", path.display()), err)
+        })?;
+        Box::new(BufReader::new(file))
+    } else {
+        Box::new(io::stdin().lock())
+    };
+    let mut tx = workspace_command.start_transaction();
+    let stats = read_fast_import(tx.repo_mut(), command.settings(), &mut *input).map_err(
+        |err| match err {
+            FastImportError::Io(err) => err.into(),
+            FastImportError::Syntax { .. } => {
+                user_error_with_message("Invalid fast-import stream", err)
+            }
+            FastImportError::Backend(err) => err.into(),
+        },
+    )?;
+    if stats.num_commits == 0 {
+        return Err(user_error("The stream contains no commits"));
+    }
+    writeln!(ui.status(), "Imported {} commits", stats.num_commits)?;
+    if !stats.bookmarks.is_empty() {
+        writeln!(ui.status(), "Set bookmarks: {}", stats.bookmarks.join(", "))?;
+    }
+    if !stats.tags.is_empty() {
+        writeln!(ui.status(), "Set tags: {}", stats.tags.join(", "))?;
+    }
+    tx.finish(ui, "import fast-import stream")?;
+    Ok(())
+}
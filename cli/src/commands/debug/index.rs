@@ -53,6 +53,15 @@ pub fn cmd_debug_index(
         )?;
         writeln!(ui.stdout(), "Number of heads: {}", stats.num_heads)?;
         writeln!(ui.stdout(), "Number of changes: {}", stats.num_changes)?;
+        if let Some(num_filters) = default_index.num_changed_path_filters() {
+            writeln!(ui.stdout(), "Number of changed-path filters: {num_filters}")?;
+        }
+        if let Some(num_entries) = default_index.num_copy_record_entries() {
+            writeln!(
+                ui.stdout(),
+                "Number of commits with copy records: {num_entries}"
+            )?;
+        }
         writeln!(ui.stdout(), "Stats per level:")?;
         for (i, level) in stats.levels.iter().enumerate() {
             writeln!(ui.stdout(), "  Level {i}:")?;
@@ -0,0 +1,65 @@
+// Copyright 2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::io::Write as _;
+
+use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::default_index::DefaultReadonlyIndex;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Compute the changed-path filters of indexed commits
+///
+/// The filters record which paths each commit changed, so `file()` revsets
+/// can skip commits without diffing their trees. Once computed, the filters of
+/// new commits are computed when they're indexed.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugIndexChangedPathsArgs {}
+
+pub fn cmd_debug_index_changed_paths(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DebugIndexChangedPathsArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = repo_loader.index_store();
+    let Some(default_index_store) = index_store.as_any().downcast_ref::<DefaultIndexStore>() else {
+        return Err(user_error(format!(
+            "Cannot compute changed paths for indexes of type '{}'",
+            index_store.name()
+        )));
+    };
+    let index = index_store
+        .get_index_at_op(&op, repo_loader.store())
+        .map_err(internal_error)?;
+    let default_index = index
+        .as_any()
+        .downcast_ref::<DefaultReadonlyIndex>()
+        .unwrap();
+    let num_added = default_index_store
+        .build_changed_path_index(default_index, repo_loader.store())
+        .map_err(internal_error)?;
+    writeln!(
+        ui.status(),
+        "Computed changed paths of {num_added} commits."
+    )?;
+    Ok(())
+}
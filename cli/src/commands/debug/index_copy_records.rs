@@ -0,0 +1,63 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::io::Write as _;
+
+use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::default_index::DefaultReadonlyIndex;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Store the copy records of indexed commits
+///
+/// The copy records tell which files each commit copied or renamed, so
+/// `follows()` revsets, `jj file annotate`, and diffs can find renames without
+/// detecting them again. Once stored, the copy records of new commits are
+/// detected when they're indexed.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugIndexCopyRecordsArgs {}
+
+pub fn cmd_debug_index_copy_records(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DebugIndexCopyRecordsArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = repo_loader.index_store();
+    let Some(default_index_store) = index_store.as_any().downcast_ref::<DefaultIndexStore>() else {
+        return Err(user_error(format!(
+            "Cannot detect copies for indexes of type '{}'",
+            index_store.name()
+        )));
+    };
+    let index = index_store
+        .get_index_at_op(&op, repo_loader.store())
+        .map_err(internal_error)?;
+    let default_index = index
+        .as_any()
+        .downcast_ref::<DefaultReadonlyIndex>()
+        .unwrap();
+    let num_added = default_index_store
+        .build_copy_record_index(default_index, repo_loader.store())
+        .map_err(internal_error)?;
+    writeln!(ui.status(), "Detected copies of {num_added} commits.")?;
+    Ok(())
+}
@@ -0,0 +1,89 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+use std::io::Write as _;
+use std::time::SystemTime;
+
+use jj_lib::lock::read_lock_holder;
+use jj_lib::lock::FileLock;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Show which processes hold the repository's locks
+///
+/// The locks are probed without waiting, so this command doesn't block when
+/// another jj process is running.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugLocksArgs {}
+
+pub fn cmd_debug_locks(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &DebugLocksArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    let repo_path = workspace.repo_path();
+    let locks = [
+        (
+            "working copy",
+            workspace
+                .workspace_root()
+                .join(".jj")
+                .join("working_copy")
+                .join("working_copy.lock"),
+        ),
+        (
+            "operation heads",
+            repo_path.join("op_heads").join("heads").join("lock"),
+        ),
+        (
+            "commit metadata",
+            repo_path.join("store").join("extra").join("lock"),
+        ),
+    ];
+    for (name, path) in locks {
+        // Locks of backends that aren't in use don't exist.
+        if !path.parent().is_some_and(|dir| dir.is_dir()) {
+            continue;
+        }
+        // Read the holder first, since taking a free lock overwrites it.
+        let holder = read_lock_holder(&path);
+        let is_locked = FileLock::try_lock(path.clone())
+            .map_err(|err| {
+                user_error_with_message(format!("Failed to probe {}", path.display()), err)
+            })?
+            .is_none();
+        if !is_locked {
+            writeln!(ui.stdout(), "{name}: not locked")?;
+        } else if let Some(holder) = holder {
+            let age = SystemTime::now()
+                .duration_since(holder.acquired_at)
+                .unwrap_or_default();
+            writeln!(
+                ui.stdout(),
+                "{name}: locked by process {} for {}s: {}",
+                holder.pid,
+                age.as_secs(),
+                holder.command
+            )?;
+        } else {
+            writeln!(ui.stdout(), "{name}: locked by an unknown process")?;
+        }
+    }
+    Ok(())
+}
@@ -13,13 +13,19 @@
 // limitations under the License.
 
 pub mod copy_detection;
+pub mod fast_export;
+pub mod fast_import;
 pub mod fileset;
 pub mod index;
+pub mod index_changed_paths;
+pub mod index_copy_records;
 pub mod local_working_copy;
+pub mod locks;
 pub mod operation;
 pub mod reindex;
 pub mod revset;
 pub mod snapshot;
+pub mod store_bench;
 pub mod template;
 pub mod tree;
 pub mod watchman;
@@ -33,12 +39,22 @@ use jj_lib::local_working_copy::LocalWorkingCopy;
 
 use self::copy_detection::cmd_debug_copy_detection;
 use self::copy_detection::CopyDetectionArgs;
+use self::fast_export::cmd_debug_fast_export;
+use self::fast_export::DebugFastExportArgs;
+use self::fast_import::cmd_debug_fast_import;
+use self::fast_import::DebugFastImportArgs;
 use self::fileset::cmd_debug_fileset;
 use self::fileset::DebugFilesetArgs;
 use self::index::cmd_debug_index;
 use self::index::DebugIndexArgs;
+use self::index_changed_paths::cmd_debug_index_changed_paths;
+use self::index_changed_paths::DebugIndexChangedPathsArgs;
+use self::index_copy_records::cmd_debug_index_copy_records;
+use self::index_copy_records::DebugIndexCopyRecordsArgs;
 use self::local_working_copy::cmd_debug_local_working_copy;
 use self::local_working_copy::DebugLocalWorkingCopyArgs;
+use self::locks::cmd_debug_locks;
+use self::locks::DebugLocksArgs;
 use self::operation::cmd_debug_operation;
 use self::operation::DebugOperationArgs;
 use self::reindex::cmd_debug_reindex;
@@ -47,6 +63,8 @@ use self::revset::cmd_debug_revset;
 use self::revset::DebugRevsetArgs;
 use self::snapshot::cmd_debug_snapshot;
 use self::snapshot::DebugSnapshotArgs;
+use self::store_bench::cmd_debug_store_bench;
+use self::store_bench::DebugStoreBenchArgs;
 use self::template::cmd_debug_template;
 use self::template::DebugTemplateArgs;
 use self::tree::cmd_debug_tree;
@@ -65,14 +83,20 @@ use crate::ui::Ui;
 #[command(hide = true)]
 pub enum DebugCommand {
     CopyDetection(CopyDetectionArgs),
+    FastExport(DebugFastExportArgs),
+    FastImport(DebugFastImportArgs),
     Fileset(DebugFilesetArgs),
     Index(DebugIndexArgs),
+    IndexChangedPaths(DebugIndexChangedPathsArgs),
+    IndexCopyRecords(DebugIndexCopyRecordsArgs),
     LocalWorkingCopy(DebugLocalWorkingCopyArgs),
+    Locks(DebugLocksArgs),
     #[command(visible_alias = "view")]
     Operation(DebugOperationArgs),
     Reindex(DebugReindexArgs),
     Revset(DebugRevsetArgs),
     Snapshot(DebugSnapshotArgs),
+    StoreBench(DebugStoreBenchArgs),
     Template(DebugTemplateArgs),
     Tree(DebugTreeArgs),
     #[command(subcommand)]
@@ -86,14 +110,20 @@ pub fn cmd_debug(
     subcommand: &DebugCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        DebugCommand::FastExport(args) => cmd_debug_fast_export(ui, command, args),
+        DebugCommand::FastImport(args) => cmd_debug_fast_import(ui, command, args),
         DebugCommand::Fileset(args) => cmd_debug_fileset(ui, command, args),
         DebugCommand::Index(args) => cmd_debug_index(ui, command, args),
+        DebugCommand::IndexChangedPaths(args) => cmd_debug_index_changed_paths(ui, command, args),
+        DebugCommand::IndexCopyRecords(args) => cmd_debug_index_copy_records(ui, command, args),
         DebugCommand::LocalWorkingCopy(args) => cmd_debug_local_working_copy(ui, command, args),
+        DebugCommand::Locks(args) => cmd_debug_locks(ui, command, args),
         DebugCommand::Operation(args) => cmd_debug_operation(ui, command, args),
         DebugCommand::Reindex(args) => cmd_debug_reindex(ui, command, args),
         DebugCommand::CopyDetection(args) => cmd_debug_copy_detection(ui, command, args),
         DebugCommand::Revset(args) => cmd_debug_revset(ui, command, args),
         DebugCommand::Snapshot(args) => cmd_debug_snapshot(ui, command, args),
+        DebugCommand::StoreBench(args) => cmd_debug_store_bench(ui, command, args),
         DebugCommand::Template(args) => cmd_debug_template(ui, command, args),
         DebugCommand::Tree(args) => cmd_debug_tree(ui, command, args),
         DebugCommand::Watchman(args) => cmd_debug_watchman(ui, command, args),
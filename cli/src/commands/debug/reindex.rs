@@ -17,21 +17,35 @@ use std::io::Write as _;
 
 use jj_lib::default_index::AsCompositeIndex as _;
 use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::object_id::ObjectId as _;
 
 use crate::cli_util::CommandHelper;
 use crate::command_error::internal_error;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
+use crate::git_util::spawn_detached_jj;
+use crate::progress::index_progress;
 use crate::ui::Ui;
 
 /// Rebuild commit index
+///
+/// The index is saved in chunks while it's built, so an interrupted rebuild is
+/// resumed by the next command that needs the index.
 #[derive(clap::Args, Clone, Debug)]
-pub struct DebugReindexArgs {}
+pub struct DebugReindexArgs {
+    /// Rebuild the index in a background process
+    ///
+    /// Commands run while the index is being rebuilt may have to index some
+    /// commits themselves.
+    #[arg(long)]
+    background: bool,
+}
 
 pub fn cmd_debug_reindex(
     ui: &mut Ui,
     command: &CommandHelper,
-    _args: &DebugReindexArgs,
+    args: &DebugReindexArgs,
 ) -> Result<(), CommandError> {
     // Resolve the operation without loading the repo. The index might have to
     // be rebuilt while loading the repo.
@@ -40,10 +54,32 @@ pub fn cmd_debug_reindex(
     let op = command.resolve_operation(ui, repo_loader)?;
     let index_store = repo_loader.index_store();
     if let Some(default_index_store) = index_store.as_any().downcast_ref::<DefaultIndexStore>() {
+        if args.background {
+            let workspace_root = workspace.workspace_root();
+            let args = [
+                "-R".to_owned(),
+                workspace_root.to_string_lossy().into_owned(),
+                "--at-op".to_owned(),
+                op.id().hex(),
+                "debug".to_owned(),
+                "reindex".to_owned(),
+            ];
+            spawn_detached_jj(workspace_root, &args).map_err(|err| {
+                user_error_with_message("Failed to start reindexing in the background", err)
+            })?;
+            writeln!(ui.status(), "Started reindexing in the background.")?;
+            return Ok(());
+        }
         default_index_store.reinit().map_err(internal_error)?;
+        let progress = index_progress(ui);
         let default_index = default_index_store
-            .build_index_at_operation(&op, repo_loader.store())
+            .build_index_at_operation_with_progress(
+                &op,
+                repo_loader.store(),
+                progress.as_ref().map(|progress| progress as _),
+            )
             .map_err(internal_error)?;
+        drop(progress);
         writeln!(
             ui.status(),
             "Finished indexing {:?} commits.",
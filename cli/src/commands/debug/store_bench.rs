@@ -0,0 +1,136 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::time::Duration;
+use std::time::Instant;
+
+use jj_lib::backend::Backend;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::TreeValue;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathBuf;
+use pollster::FutureExt as _;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Measure how fast the backend reads objects
+///
+/// Reads the commits and all the trees, files, and symlinks reachable from the
+/// given revisions directly from the backend, bypassing the caches of jj's
+/// store. Each object is read once per pass. The first pass shows the cold read
+/// throughput, the later passes show the effect of the backend's own caches.
+#[derive(clap::Args, Clone, Debug)]
+pub struct DebugStoreBenchArgs {
+    /// The revisions whose objects to read
+    #[arg(long, short, default_value = "@")]
+    revisions: RevisionArg,
+    /// Number of times to read the objects
+    #[arg(long, default_value_t = 2)]
+    passes: usize,
+}
+
+pub fn cmd_debug_store_bench(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &DebugStoreBenchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit_ids: Vec<CommitId> = workspace_command
+        .parse_revset(ui, &args.revisions)?
+        .evaluate_to_commit_ids()?
+        .collect();
+    let backend = workspace_command.repo().store().backend();
+    for pass in 1..=args.passes {
+        let stats = read_objects(backend, &commit_ids)?;
+        writeln!(
+            ui.stdout(),
+            "Pass {pass}: read {} objects ({} bytes) in {:?}, {:.0} objects/s",
+            stats.num_objects,
+            stats.num_bytes,
+            stats.elapsed,
+            stats.num_objects as f64 / stats.elapsed.as_secs_f64().max(f64::EPSILON)
+        )?;
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct ReadStats {
+    num_objects: usize,
+    num_bytes: usize,
+    elapsed: Duration,
+}
+
+fn read_objects(backend: &dyn Backend, commit_ids: &[CommitId]) -> Result<ReadStats, CommandError> {
+    let mut stats = ReadStats::default();
+    let mut visited = HashSet::new();
+    let start = Instant::now();
+    for commit_id in commit_ids {
+        if commit_id == backend.root_commit_id() {
+            continue;
+        }
+        let commit = backend.read_commit(commit_id).block_on()?;
+        stats.num_objects += 1;
+        let mut pending_trees: Vec<_> = commit
+            .root_tree
+            .to_merge()
+            .into_iter()
+            .map(|id| (RepoPathBuf::root(), id))
+            .collect();
+        while let Some((dir, tree_id)) = pending_trees.pop() {
+            if !visited.insert(tree_id.hex()) {
+                continue;
+            }
+            let tree = backend.read_tree(&dir, &tree_id).block_on()?;
+            stats.num_objects += 1;
+            for entry in tree.entries() {
+                let path = dir.join(entry.name());
+                match entry.value() {
+                    TreeValue::Tree(id) => pending_trees.push((path, id.clone())),
+                    TreeValue::File { id, .. } => {
+                        if !visited.insert(id.hex()) {
+                            continue;
+                        }
+                        let mut content = vec![];
+                        backend
+                            .read_file(&path, id)
+                            .block_on()?
+                            .read_to_end(&mut content)?;
+                        stats.num_objects += 1;
+                        stats.num_bytes += content.len();
+                    }
+                    TreeValue::Symlink(id) => {
+                        if !visited.insert(id.hex()) {
+                            continue;
+                        }
+                        let target = backend.read_symlink(&path, id).block_on()?;
+                        stats.num_objects += 1;
+                        stats.num_bytes += target.len();
+                    }
+                    TreeValue::GitSubmodule(_) | TreeValue::Conflict(_) => {}
+                }
+            }
+        }
+    }
+    stats.elapsed = start.elapsed();
+    Ok(stats)
+}
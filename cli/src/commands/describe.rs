@@ -28,7 +28,9 @@ use crate::command_error::CommandError;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::edit_multiple_descriptions;
+use crate::description_util::finalize_description;
 use crate::description_util::join_message_paragraphs;
+use crate::description_util::render_description_template;
 use crate::description_util::ParsedBulkEditMessage;
 use crate::ui::Ui;
 
@@ -36,6 +38,11 @@ use crate::ui::Ui;
 ///
 /// Starts an editor to let you edit the description of changes. The editor
 /// will be $EDITOR, or `pico` if that's not defined (`Notepad` on Windows).
+///
+/// If multiple revisions are specified, their descriptions are edited together
+/// in a single editor, separated by `JJ: describe <commit id>` lines, and all
+/// of them are updated in a single operation. For example, `jj describe -r
+/// 'trunk()..@'` lets you reword the whole stack at once.
 #[derive(clap::Args, Clone, Debug)]
 #[command(visible_aliases = &["desc"])]
 pub(crate) struct DescribeArgs {
@@ -62,6 +69,17 @@ pub(crate) struct DescribeArgs {
     /// This is mainly useful in combination with e.g. `--reset-author`.
     #[arg(long)]
     no_edit: bool,
+    /// Render the descriptions in the editor with this template
+    ///
+    /// Defaults to the `templates.draft_commit_description` template. This can
+    /// be used to show more information about each revision while rewording
+    /// them, e.g. the list of changed files.
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["message_paragraphs", "stdin", "no_edit"]
+    )]
+    reword_editor_template: Option<String>,
     /// Reset the author to the configured user
     ///
     /// This resets the author name, email, and timestamp.
@@ -145,7 +163,12 @@ pub(crate) fn cmd_describe(
             .try_collect()?;
 
         if let [(_, temp_commit)] = &*temp_commits {
-            let template = description_template(ui, &tx, "", temp_commit)?;
+            let template = match &args.reword_editor_template {
+                Some(template_text) => {
+                    render_description_template(ui, &tx, "", temp_commit, template_text)?
+                }
+                None => description_template(ui, &tx, "", temp_commit)?,
+            };
             let description =
                 edit_description(tx.base_workspace_helper(), &template, command.settings())?;
 
@@ -156,7 +179,13 @@ pub(crate) fn cmd_describe(
                 missing,
                 duplicates,
                 unexpected,
-            } = edit_multiple_descriptions(ui, &tx, &temp_commits, command.settings())?;
+            } = edit_multiple_descriptions(
+                ui,
+                &tx,
+                &temp_commits,
+                args.reword_editor_template.as_deref(),
+                command.settings(),
+            )?;
             if !missing.is_empty() {
                 return Err(user_error(format!(
                     "The description for the following commits were not found in the edited \
@@ -190,6 +219,14 @@ pub(crate) fn cmd_describe(
         }
     };
 
+    let commit_descriptions: Vec<(_, _)> = commit_descriptions
+        .into_iter()
+        .map(|(commit, description)| {
+            let description = finalize_description(ui, &tx, commit, description)?;
+            Ok::<_, CommandError>((commit, description))
+        })
+        .try_collect()?;
+
     // Filter out unchanged commits to avoid rebasing descendants in
     // `transform_descendants` below unnecessarily.
     let commit_descriptions: HashMap<_, _> = commit_descriptions
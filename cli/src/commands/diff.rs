@@ -14,7 +14,6 @@
 
 use itertools::Itertools;
 use jj_lib::copies::CopyRecords;
-use jj_lib::repo::Repo;
 use jj_lib::rewrite::merge_commit_trees;
 use tracing::instrument;
 
@@ -82,7 +81,7 @@ pub(crate) fn cmd_diff(
         from_tree = from.tree()?;
         to_tree = to.tree()?;
 
-        let records = get_copy_records(repo.store(), from.id(), to.id(), &matcher)?;
+        let records = get_copy_records(repo.as_ref(), from.id(), to.id(), &matcher)?;
         copy_records.add_records(records)?;
     } else {
         let to = resolve_revision(&args.revision)?;
@@ -91,7 +90,7 @@ pub(crate) fn cmd_diff(
         to_tree = to.tree()?;
 
         for p in &parents {
-            let records = get_copy_records(repo.store(), p.id(), to.id(), &matcher)?;
+            let records = get_copy_records(repo.as_ref(), p.id(), to.id(), &matcher)?;
             copy_records.add_records(records)?;
         }
     }
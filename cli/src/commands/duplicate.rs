@@ -15,9 +15,11 @@
 use std::io::Write;
 
 use indexmap::IndexMap;
+use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::repo::Repo;
+use jj_lib::rewrite::CommitRewriter;
 use tracing::instrument;
 
 use crate::cli_util::short_commit_hash;
@@ -28,11 +30,25 @@ use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 /// Create a new change with the same content as an existing one
+///
+/// Duplicates of revisions whose parents are also duplicated are put on top of
+/// the duplicated parents, so duplicating a stack creates a copy of the whole
+/// stack.
+///
+/// With `--destination`, the duplicates are rebased onto the given revisions
+/// instead of being created next to the originals. Parents that aren't being
+/// duplicated are replaced by the destination, so the internal structure of
+/// the duplicated revisions is preserved. For example, `jj duplicate A::C -d
+/// release` copies the stack from A to C onto `release`, leaving the original
+/// stack in place.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct DuplicateArgs {
     /// The revision(s) to duplicate
     #[arg(default_value = "@")]
     revisions: Vec<RevisionArg>,
+    /// The revision(s) to put the duplicates on top of
+    #[arg(long, short, visible_alias = "onto")]
+    destination: Vec<RevisionArg>,
     /// Ignored (but lets you pass `-r` for consistency with other commands)
     #[arg(short = 'r', hide = true, action = clap::ArgAction::Count)]
     unused_revision: u8,
@@ -56,6 +72,15 @@ pub(crate) fn cmd_duplicate(
     if to_duplicate.last() == Some(workspace_command.repo().store().root_commit_id()) {
         return Err(user_error("Cannot duplicate the root commit"));
     }
+    let destination_ids: Vec<CommitId> = if args.destination.is_empty() {
+        vec![]
+    } else {
+        workspace_command
+            .resolve_some_revsets_default_single(ui, &args.destination)?
+            .into_iter()
+            .map(|commit| commit.id().clone())
+            .collect()
+    };
     let mut duplicated_old_to_new: IndexMap<&CommitId, Commit> = IndexMap::new();
 
     let mut tx = workspace_command.start_transaction();
@@ -70,12 +95,16 @@ pub(crate) fn cmd_duplicate(
         let new_parents = original_commit
             .parent_ids()
             .iter()
-            .map(|id| duplicated_old_to_new.get(id).map_or(id, |c| c.id()).clone())
+            .flat_map(|id| match duplicated_old_to_new.get(id) {
+                Some(new_parent) => vec![new_parent.id().clone()],
+                None if destination_ids.is_empty() => vec![id.clone()],
+                None => destination_ids.clone(),
+            })
+            .unique()
             .collect();
-        let new_commit = mut_repo
-            .rewrite_commit(command.settings(), &original_commit)
+        let new_commit = CommitRewriter::new(mut_repo, original_commit, new_parents)
+            .rebase(command.settings())?
             .generate_new_change_id()
-            .set_parents(new_parents)
             .write()?;
         duplicated_old_to_new.insert(original_commit_id, new_commit);
     }
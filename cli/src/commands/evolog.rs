@@ -63,6 +63,11 @@ pub(crate) struct EvologArgs {
     /// If the previous version has different parents, it will be temporarily
     /// rebased to the parents of the new version, so the diff is not
     /// contaminated by unrelated changes.
+    ///
+    /// The patch shows what each rewrite changed in the change itself, not
+    /// the diff against the parents, so pure rebases have an empty patch. To
+    /// compare two arbitrary versions, use `jj interdiff --from <old version>
+    /// --to <new version>`.
     #[arg(long, short = 'p')]
     patch: bool,
     #[command(flatten)]
@@ -142,7 +147,7 @@ pub(crate) fn cmd_evolog(
         commits.truncate(n);
     }
     if !args.no_graph {
-        let mut graph = get_graphlog(graph_style, formatter.raw());
+        let mut graph = get_graphlog(graph_style, formatter);
         for commit in commits {
             let edges = commit
                 .predecessor_ids()
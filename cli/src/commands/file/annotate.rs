@@ -0,0 +1,246 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use jj_lib::annotate::get_annotation_for_file;
+use jj_lib::annotate::AnnotatedLine;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::Signature;
+use jj_lib::backend::Timestamp;
+use jj_lib::commit::Commit;
+use jj_lib::hex_util::to_reverse_hex;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo;
+use once_cell::sync::Lazy;
+use tracing::instrument;
+
+use crate::cli_util::short_change_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::formatter::Formatter;
+use crate::time_util::format_absolute_timestamp_with;
+use crate::time_util::FormattingItems;
+use crate::ui::Ui;
+
+/// Show the source change for each line of the target file
+///
+/// Annotates a revision line by line. Each line includes the change that
+/// introduced it, its author and time, and the line number. Lines are followed
+/// across renames and copies if the backend records them.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileAnnotateArgs {
+    /// The file to annotate
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    path: String,
+    /// The revision to start at
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+    /// Print the output in the format of `git blame --line-porcelain`
+    ///
+    /// Every line is preceded by the full information about the commit that
+    /// introduced it, so the output can be consumed by tools that understand
+    /// `git blame`. The change ID is printed as an extra `change-id` field.
+    #[arg(long)]
+    line_porcelain: bool,
+    /// Render the prefix of each line using the given template
+    ///
+    /// The template is rendered for the commit that introduced the line, and
+    /// the content of the line is written after it.
+    ///
+    /// For the syntax, see https://martinvonz.github.io/jj/latest/templates/
+    #[arg(long, short = 'T', conflicts_with = "line_porcelain")]
+    template: Option<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_annotate(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileAnnotateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let starting_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let file_path = workspace_command.parse_file_path(&args.path)?;
+    let file_value = starting_commit.tree()?.path_value(&file_path)?;
+    let ui_path = workspace_command.format_file_path(&file_path);
+    if file_value.is_absent() {
+        return Err(user_error(format!("No such path: {ui_path}")));
+    }
+    if file_value.is_tree() {
+        return Err(user_error(format!(
+            "Path exists but is not a regular file: {ui_path}"
+        )));
+    }
+    let template = args
+        .template
+        .as_ref()
+        .map(|text| workspace_command.parse_commit_template(ui, text))
+        .transpose()?;
+
+    let annotated_lines = get_annotation_for_file(repo.as_ref(), &starting_commit, &file_path)?;
+    let mut commits: HashMap<CommitId, Commit> = HashMap::new();
+    for line in &annotated_lines {
+        if !commits.contains_key(&line.commit_id) {
+            let commit = repo.store().get_commit(&line.commit_id)?;
+            commits.insert(line.commit_id.clone(), commit);
+        }
+    }
+
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    if args.line_porcelain {
+        write_line_porcelain(formatter.as_mut(), &annotated_lines, &commits)?;
+    } else if let Some(template) = &template {
+        for line in &annotated_lines {
+            template.format(&commits[&line.commit_id], formatter.as_mut())?;
+            write_line_content(formatter.as_mut(), line)?;
+        }
+    } else {
+        write_annotation(formatter.as_mut(), &annotated_lines, &commits)?;
+    }
+    Ok(())
+}
+
+fn write_annotation(
+    formatter: &mut dyn Formatter,
+    annotated_lines: &[AnnotatedLine],
+    commits: &HashMap<CommitId, Commit>,
+) -> Result<(), CommandError> {
+    static TIMESTAMP_FORMAT: Lazy<FormattingItems> =
+        Lazy::new(|| FormattingItems::parse("%Y-%m-%d %H:%M:%S").unwrap());
+    let author_width = commits
+        .values()
+        .map(|commit| commit.author().name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let line_number_width = annotated_lines.len().to_string().len();
+    for (line_number, line) in annotated_lines.iter().enumerate() {
+        let commit = &commits[&line.commit_id];
+        let timestamp =
+            format_absolute_timestamp_with(&commit.author().timestamp, &TIMESTAMP_FORMAT)
+                .unwrap_or_else(|_| "<out-of-range date>".to_owned());
+        write!(
+            formatter.labeled("change_id"),
+            "{}",
+            short_change_hash(commit.change_id())
+        )?;
+        write!(formatter, " ")?;
+        write!(
+            formatter.labeled("author"),
+            "{:author_width$}",
+            commit.author().name
+        )?;
+        write!(formatter, " ")?;
+        write!(formatter.labeled("timestamp"), "{timestamp}")?;
+        write!(formatter, " {:>line_number_width$}: ", line_number + 1)?;
+        write_line_content(formatter, line)?;
+    }
+    Ok(())
+}
+
+/// Writes the content of the line, terminating it if it's the last line of a
+/// file without a trailing newline.
+fn write_line_content(formatter: &mut dyn Formatter, line: &AnnotatedLine) -> std::io::Result<()> {
+    formatter.write_all(&line.content)?;
+    if !line.content.ends_with(b"\n") {
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
+
+/// Writes the annotation in the format of `git blame --line-porcelain`.
+fn write_line_porcelain(
+    formatter: &mut dyn Formatter,
+    annotated_lines: &[AnnotatedLine],
+    commits: &HashMap<CommitId, Commit>,
+) -> Result<(), CommandError> {
+    for (line_number, line) in annotated_lines.iter().enumerate() {
+        let commit = &commits[&line.commit_id];
+        write!(
+            formatter,
+            "{} {} {}",
+            commit.id().hex(),
+            line.original_line_number + 1,
+            line_number + 1
+        )?;
+        // Like Git, the number of lines in a group of consecutive lines from
+        // the same commit is only printed for the first line of the group.
+        let starts_group = line_number == 0 || {
+            let previous = &annotated_lines[line_number - 1];
+            previous.commit_id != line.commit_id
+                || previous.path != line.path
+                || previous.original_line_number + 1 != line.original_line_number
+        };
+        if starts_group {
+            let group_size = annotated_lines[line_number..]
+                .iter()
+                .zip(line.original_line_number..)
+                .take_while(|(other, original_line_number)| {
+                    other.commit_id == line.commit_id
+                        && other.path == line.path
+                        && other.original_line_number == *original_line_number
+                })
+                .count();
+            write!(formatter, " {group_size}")?;
+        }
+        writeln!(formatter)?;
+        write_porcelain_signature(formatter, "author", commit.author())?;
+        write_porcelain_signature(formatter, "committer", commit.committer())?;
+        let summary = commit.description().lines().next().unwrap_or_default();
+        writeln!(formatter, "summary {summary}")?;
+        writeln!(
+            formatter,
+            "change-id {}",
+            to_reverse_hex(&commit.change_id().hex()).unwrap()
+        )?;
+        writeln!(
+            formatter,
+            "filename {}",
+            line.path.as_internal_file_string()
+        )?;
+        write!(formatter, "\t")?;
+        write_line_content(formatter, line)?;
+    }
+    Ok(())
+}
+
+fn write_porcelain_signature(
+    formatter: &mut dyn Formatter,
+    role: &str,
+    signature: &Signature,
+) -> std::io::Result<()> {
+    writeln!(formatter, "{role} {}", signature.name)?;
+    writeln!(formatter, "{role}-mail <{}>", signature.email)?;
+    writeln!(
+        formatter,
+        "{role}-time {}",
+        signature.timestamp.timestamp.0.div_euclid(1000)
+    )?;
+    writeln!(
+        formatter,
+        "{role}-tz {}",
+        format_tz_offset(&signature.timestamp)
+    )
+}
+
+/// Formats the time zone offset like Git does, e.g. `+0700`.
+fn format_tz_offset(timestamp: &Timestamp) -> String {
+    let sign = if timestamp.tz_offset < 0 { '-' } else { '+' };
+    let minutes = timestamp.tz_offset.abs();
+    format!("{sign}{:02}{:02}", minutes / 60, minutes % 60)
+}
@@ -12,21 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod annotate;
 pub mod chmod;
+pub mod copy;
 pub mod list;
+pub mod r#move;
 pub mod show;
 pub mod track;
 pub mod untrack;
 
+use std::fs;
+
+use jj_lib::backend::RecordedCopy;
+use jj_lib::backend::TreeValue;
+use jj_lib::repo::Repo as _;
+
 use crate::cli_util::CommandHelper;
+use crate::command_error::cli_error;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 /// File operations.
 #[derive(clap::Subcommand, Clone, Debug)]
 pub enum FileCommand {
+    Annotate(annotate::FileAnnotateArgs),
     Chmod(chmod::FileChmodArgs),
+    Copy(copy::FileCopyArgs),
     List(list::FileListArgs),
+    Move(r#move::FileMoveArgs),
     Show(show::FileShowArgs),
     Track(track::FileTrackArgs),
     Untrack(untrack::FileUntrackArgs),
@@ -38,10 +53,109 @@ pub fn cmd_file(
     subcommand: &FileCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        FileCommand::Annotate(args) => annotate::cmd_file_annotate(ui, command, args),
         FileCommand::Chmod(args) => chmod::cmd_file_chmod(ui, command, args),
+        FileCommand::Copy(args) => copy::cmd_file_copy(ui, command, args),
         FileCommand::List(args) => list::cmd_file_list(ui, command, args),
+        FileCommand::Move(args) => r#move::cmd_file_move(ui, command, args),
         FileCommand::Show(args) => show::cmd_file_show(ui, command, args),
         FileCommand::Track(args) => track::cmd_file_track(ui, command, args),
         FileCommand::Untrack(args) => untrack::cmd_file_untrack(ui, command, args),
     }
 }
+
+/// Moves or copies a file in the working copy, and records the copy in the
+/// working-copy commit.
+fn move_or_copy_file(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    source: &str,
+    destination: &str,
+    is_move: bool,
+) -> Result<(), CommandError> {
+    // The file is moved on disk, so the working copy must be snapshotted
+    // afterwards.
+    if command.global_args().ignore_working_copy {
+        return Err(cli_error("--ignore-working-copy is not respected"));
+    }
+    if command.global_args().at_operation.is_some() {
+        return Err(cli_error("--at-op is not respected"));
+    }
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let source_path = workspace_command.parse_file_path(source)?;
+    let destination_path = workspace_command.parse_file_path(destination)?;
+    let wc_commit_id = workspace_command
+        .get_wc_commit_id()
+        .ok_or_else(|| user_error("This command requires a working copy"))?
+        .clone();
+    let wc_commit = workspace_command.repo().store().get_commit(&wc_commit_id)?;
+    let source_value = wc_commit.tree()?.path_value(&source_path)?;
+    if !matches!(
+        source_value.as_resolved(),
+        Some(Some(TreeValue::File { .. }))
+    ) {
+        return Err(user_error(format!(
+            "No such file: {}",
+            workspace_command.format_file_path(&source_path)
+        )));
+    }
+    let source_fs_path = source_path.to_fs_path(workspace_command.workspace_root());
+    let destination_fs_path = destination_path.to_fs_path(workspace_command.workspace_root());
+    if destination_fs_path.symlink_metadata().is_ok() {
+        return Err(user_error(format!(
+            "Destination already exists: {}",
+            workspace_command.format_file_path(&destination_path)
+        )));
+    }
+    let result = match destination_fs_path.parent() {
+        Some(parent) => fs::create_dir_all(parent),
+        None => Ok(()),
+    };
+    let result = result.and_then(|()| {
+        if is_move {
+            fs::rename(&source_fs_path, &destination_fs_path)
+        } else {
+            fs::copy(&source_fs_path, &destination_fs_path).map(|_| ())
+        }
+    });
+    result.map_err(|err| {
+        user_error_with_message(
+            format!(
+                "Failed to {} {} to {}",
+                if is_move { "move" } else { "copy" },
+                workspace_command.format_file_path(&source_path),
+                workspace_command.format_file_path(&destination_path)
+            ),
+            err,
+        )
+    })?;
+    workspace_command.maybe_snapshot(ui)?;
+
+    let wc_commit_id = workspace_command.get_wc_commit_id().unwrap().clone();
+    let wc_commit = workspace_command.repo().store().get_commit(&wc_commit_id)?;
+    let mut recorded_copies = wc_commit.recorded_copies().to_vec();
+    // If the source was itself copied in this commit, the copy is recorded
+    // from where it was copied from.
+    let origin = recorded_copies
+        .iter()
+        .find(|copy| copy.target == source_path)
+        .map_or_else(|| source_path.clone(), |copy| copy.source.clone());
+    recorded_copies
+        .retain(|copy| copy.target != destination_path && (!is_move || copy.target != source_path));
+    recorded_copies.push(RecordedCopy {
+        source: origin,
+        target: destination_path.clone(),
+    });
+    let mut tx = workspace_command.start_transaction();
+    tx.repo_mut()
+        .rewrite_commit(command.settings(), &wc_commit)
+        .set_recorded_copies(recorded_copies)
+        .write()?;
+    let description = format!(
+        "{} file {} to {}",
+        if is_move { "move" } else { "copy" },
+        source_path.as_internal_file_string(),
+        destination_path.as_internal_file_string()
+    );
+    tx.finish(ui, description)
+}
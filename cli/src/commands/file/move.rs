@@ -0,0 +1,43 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tracing::instrument;
+
+use super::move_or_copy_file;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Move a file in the working copy, recording the rename
+///
+/// The rename is recorded in the working-copy commit, so diffs show the file
+/// as renamed even if its content is changed substantially afterwards.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FileMoveArgs {
+    /// The file to move
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    source: String,
+    /// The path to move the file to. It must not exist.
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    destination: String,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_file_move(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FileMoveArgs,
+) -> Result<(), CommandError> {
+    move_or_copy_file(ui, command, &args.source, &args.destination, true)
+}
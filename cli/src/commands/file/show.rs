@@ -12,13 +12,21 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fs;
 use std::io;
+use std::io::Read as _;
 use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::Path;
+use std::path::PathBuf;
 
 use jj_lib::backend::BackendResult;
+use jj_lib::commit::Commit;
 use jj_lib::conflicts::materialize_merge_result;
 use jj_lib::conflicts::materialize_tree_value;
 use jj_lib::conflicts::MaterializedTreeValue;
+use jj_lib::file_util::IoResultExt as _;
 use jj_lib::fileset::FilePattern;
 use jj_lib::fileset::FilesetExpression;
 use jj_lib::merge::MergedTreeValue;
@@ -32,13 +40,20 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
+use crate::commands::git::archive::append_tar_file;
+use crate::commands::git::archive::append_tar_symlink;
 use crate::ui::Ui;
 
 /// Print contents of files in a revision
 ///
 /// If the given path is a directory, files in the directory will be visited
 /// recursively.
+///
+/// With `--output` or `--archive`, the files are extracted instead of printed,
+/// keeping their paths relative to the workspace root. This can be used to get
+/// parts of an old revision without checking it out.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct FileShowArgs {
     /// The revision to get the file contents from
@@ -47,6 +62,20 @@ pub(crate) struct FileShowArgs {
     /// Paths to print
     #[arg(required = true, value_hint = clap::ValueHint::FilePath)]
     paths: Vec<String>,
+    /// Write the files to this directory instead of printing them
+    ///
+    /// The directory is created if it doesn't exist. Existing files are
+    /// overwritten.
+    #[arg(long, short, value_hint = clap::ValueHint::DirPath)]
+    output: Option<PathBuf>,
+    /// Write the files to stdout as an archive of this format
+    #[arg(long, value_enum, conflicts_with = "output")]
+    archive: Option<FileArchiveFormat>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+enum FileArchiveFormat {
+    Tar,
 }
 
 #[instrument(skip_all)]
@@ -69,7 +98,7 @@ pub(crate) fn cmd_file_show(
             let ui_path = workspace_command.format_file_path(path);
             return Err(user_error(format!("No such path: {ui_path}")));
         }
-        if !value.is_tree() {
+        if !value.is_tree() && args.output.is_none() && args.archive.is_none() {
             ui.request_pager();
             write_tree_entries(ui, &workspace_command, [(path, Ok(value))])?;
             return Ok(());
@@ -77,12 +106,21 @@ pub(crate) fn cmd_file_show(
     }
 
     let matcher = fileset_expression.to_matcher();
-    ui.request_pager();
-    write_tree_entries(
-        ui,
-        &workspace_command,
-        tree.entries_matching(matcher.as_ref()),
-    )?;
+    let entries = tree.entries_matching(matcher.as_ref());
+    if let Some(output) = &args.output {
+        let output_dir = command.cwd().join(output);
+        let num_files = write_tree_entries_to_dir(ui, &workspace_command, &output_dir, entries)?;
+        writeln!(
+            ui.status(),
+            "Wrote {num_files} files to {}",
+            output.display()
+        )?;
+    } else if let Some(FileArchiveFormat::Tar) = args.archive {
+        write_tree_entries_to_tar(ui, &workspace_command, &commit, entries)?;
+    } else {
+        ui.request_pager();
+        write_tree_entries(ui, &workspace_command, entries)?;
+    }
     print_unmatched_explicit_paths(ui, &workspace_command, &fileset_expression, [&tree])?;
     Ok(())
 }
@@ -138,3 +176,142 @@ fn write_tree_entries<P: AsRef<RepoPath>>(
     }
     Ok(())
 }
+
+/// Writes the files to `output_dir` at their repo paths. Returns the number of
+/// files written.
+fn write_tree_entries_to_dir<P: AsRef<RepoPath>>(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    output_dir: &Path,
+    entries: impl IntoIterator<Item = (P, BackendResult<MergedTreeValue>)>,
+) -> Result<usize, CommandError> {
+    let repo = workspace_command.repo();
+    let mut num_files = 0;
+    for (path, result) in entries {
+        let path = path.as_ref();
+        let value = result?;
+        let (contents, executable) =
+            match materialize_tree_value(repo.store(), path, value).block_on()? {
+                MaterializedTreeValue::Absent => panic!("absent values should be excluded"),
+                MaterializedTreeValue::AccessDenied(err) => {
+                    let ui_path = workspace_command.format_file_path(path);
+                    writeln!(
+                        ui.warning_default(),
+                        "Path '{ui_path}' exists but access is denied: {err}"
+                    )?;
+                    continue;
+                }
+                MaterializedTreeValue::File {
+                    executable,
+                    mut reader,
+                    ..
+                } => {
+                    let mut contents = vec![];
+                    reader.read_to_end(&mut contents)?;
+                    (contents, executable)
+                }
+                MaterializedTreeValue::FileConflict {
+                    contents,
+                    executable,
+                    ..
+                } => {
+                    let mut materialized = vec![];
+                    materialize_merge_result(&contents, &mut materialized)?;
+                    (materialized, executable)
+                }
+                MaterializedTreeValue::OtherConflict { id } => (id.describe().into_bytes(), false),
+                MaterializedTreeValue::Symlink { .. } | MaterializedTreeValue::GitSubmodule(_) => {
+                    let ui_path = workspace_command.format_file_path(path);
+                    writeln!(
+                        ui.warning_default(),
+                        "Path '{ui_path}' exists but is not a file"
+                    )?;
+                    continue;
+                }
+                MaterializedTreeValue::Tree(_) => panic!("entries should not contain trees"),
+            };
+        let disk_path = path.to_fs_path(output_dir);
+        if let Some(parent) = disk_path.parent() {
+            fs::create_dir_all(parent).context(parent)?;
+        }
+        fs::write(&disk_path, contents).map_err(|err| {
+            user_error_with_message(format!("Failed to write {}", disk_path.display()), err)
+        })?;
+        #[cfg(unix)]
+        {
+            let mode = if executable { 0o755 } else { 0o644 };
+            fs::set_permissions(&disk_path, fs::Permissions::from_mode(mode))
+                .context(&disk_path)?;
+        }
+        #[cfg(not(unix))]
+        let _ = executable;
+        num_files += 1;
+    }
+    Ok(num_files)
+}
+
+/// Writes the files to stdout as a tar archive. The files are timestamped
+/// with the committer timestamp of the `commit`, like `jj git archive` does.
+fn write_tree_entries_to_tar<P: AsRef<RepoPath>>(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    commit: &Commit,
+    entries: impl IntoIterator<Item = (P, BackendResult<MergedTreeValue>)>,
+) -> Result<(), CommandError> {
+    let repo = workspace_command.repo();
+    let mtime = (commit.committer().timestamp.timestamp.0 / 1000)
+        .try_into()
+        .unwrap_or(0);
+    let mut builder = tar::Builder::new(ui.stdout());
+    for (path, result) in entries {
+        let path = path.as_ref();
+        let value = result?;
+        let archive_path = path.as_internal_file_string();
+        match materialize_tree_value(repo.store(), path, value).block_on()? {
+            MaterializedTreeValue::Absent => panic!("absent values should be excluded"),
+            MaterializedTreeValue::AccessDenied(err) => {
+                let ui_path = workspace_command.format_file_path(path);
+                writeln!(
+                    ui.warning_default(),
+                    "Path '{ui_path}' exists but access is denied: {err}"
+                )?;
+            }
+            MaterializedTreeValue::File {
+                executable,
+                mut reader,
+                ..
+            } => {
+                let mode = if executable { 0o755 } else { 0o644 };
+                let mut contents = vec![];
+                reader.read_to_end(&mut contents)?;
+                append_tar_file(&mut builder, mtime, archive_path, mode, &contents)?;
+            }
+            MaterializedTreeValue::FileConflict {
+                contents,
+                executable,
+                ..
+            } => {
+                let mode = if executable { 0o755 } else { 0o644 };
+                let mut materialized = vec![];
+                materialize_merge_result(&contents, &mut materialized)?;
+                append_tar_file(&mut builder, mtime, archive_path, mode, &materialized)?;
+            }
+            MaterializedTreeValue::OtherConflict { id } => {
+                append_tar_file(
+                    &mut builder,
+                    mtime,
+                    archive_path,
+                    0o644,
+                    id.describe().as_bytes(),
+                )?;
+            }
+            MaterializedTreeValue::Symlink { target, .. } => {
+                append_tar_symlink(&mut builder, mtime, archive_path, &target)?;
+            }
+            MaterializedTreeValue::GitSubmodule(_) => {}
+            MaterializedTreeValue::Tree(_) => panic!("entries should not contain trees"),
+        }
+    }
+    builder.into_inner()?.flush()?;
+    Ok(())
+}
@@ -57,6 +57,7 @@ pub(crate) fn cmd_file_track(
         progress: None,
         start_tracking_matcher: &matcher,
         max_new_file_size: command.settings().max_new_file_size()?,
+        conflict_marker_style: command.settings().conflict_marker_style()?,
     })?;
     let num_rebased = tx.repo_mut().rebase_descendants(command.settings())?;
     if num_rebased > 0 {
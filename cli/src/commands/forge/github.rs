@@ -0,0 +1,228 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Pull requests on GitHub, managed with the `gh` command-line tool.
+
+use std::process::Command;
+use std::process::Stdio;
+
+use jj_lib::settings::ConfigResultExt as _;
+use jj_lib::settings::UserSettings;
+
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+
+/// An open pull request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PullRequest {
+    pub number: u64,
+    /// Name of the branch the pull request is meant to be merged into.
+    pub base: String,
+    pub url: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct GitHub {
+    program: String,
+    /// Repository in `owner/name` form.
+    repository: String,
+}
+
+impl GitHub {
+    /// Sets up access to the repository configured by `forge.github.*`, or
+    /// else the one at the given remote URL.
+    pub fn from_settings(
+        settings: &UserSettings,
+        remote_url: Option<&str>,
+    ) -> Result<Self, CommandError> {
+        let config = settings.config();
+        let program = config
+            .get_string("forge.github.program")
+            .optional()?
+            .unwrap_or_else(|| "gh".to_owned());
+        let repository = match config.get_string("forge.github.repository").optional()? {
+            Some(repository) => repository,
+            None => remote_url
+                .and_then(parse_github_repository)
+                .ok_or_else(|| {
+                    user_error_with_hint(
+                        format!(
+                            "Cannot tell the GitHub repository from the remote URL: {}",
+                            remote_url.unwrap_or("<no URL>")
+                        ),
+                        "Set `forge.github.repository` to the repository in `owner/name` form.",
+                    )
+                })?,
+        };
+        Ok(GitHub {
+            program,
+            repository,
+        })
+    }
+
+    /// Finds the open pull request for the given head branch.
+    pub fn find_pull_request(&self, head: &str) -> Result<Option<PullRequest>, CommandError> {
+        let output = self.run(&[
+            "pr",
+            "list",
+            "--repo",
+            &self.repository,
+            "--head",
+            head,
+            "--state",
+            "open",
+            "--json",
+            "number,baseRefName,url",
+            "--jq",
+            r#".[] | "\(.number) \(.baseRefName) \(.url)""#,
+        ])?;
+        let Some(line) = output.lines().find(|line| !line.trim().is_empty()) else {
+            return Ok(None);
+        };
+        let mut fields = line.split_whitespace();
+        let (Some(number), Some(base), Some(url)) = (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(user_error(format!(
+                "Unexpected output from {}: {line}",
+                self.program
+            )));
+        };
+        let number = number
+            .parse()
+            .map_err(|_| user_error(format!("Unexpected output from {}: {line}", self.program)))?;
+        Ok(Some(PullRequest {
+            number,
+            base: base.to_owned(),
+            url: url.to_owned(),
+        }))
+    }
+
+    pub fn create_pull_request(
+        &self,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+        draft: bool,
+    ) -> Result<PullRequest, CommandError> {
+        let mut args = vec![
+            "pr",
+            "create",
+            "--repo",
+            &self.repository,
+            "--head",
+            head,
+            "--base",
+            base,
+            "--title",
+            title,
+            "--body",
+            body,
+        ];
+        if draft {
+            args.push("--draft");
+        }
+        let output = self.run(&args)?;
+        // `gh pr create` prints the URL of the new pull request last.
+        let url = output.lines().last().unwrap_or_default().trim();
+        let number = url
+            .rsplit('/')
+            .next()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| user_error(format!("Unexpected output from {}: {url}", self.program)))?;
+        Ok(PullRequest {
+            number,
+            base: base.to_owned(),
+            url: url.to_owned(),
+        })
+    }
+
+    pub fn set_pull_request_base(&self, number: u64, base: &str) -> Result<(), CommandError> {
+        self.run(&[
+            "pr",
+            "edit",
+            &number.to_string(),
+            "--repo",
+            &self.repository,
+            "--base",
+            base,
+        ])?;
+        Ok(())
+    }
+
+    fn run(&self, args: &[&str]) -> Result<String, CommandError> {
+        tracing::info!(?args, "running {}", self.program);
+        let output = Command::new(&self.program)
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|err| {
+                user_error_with_message(format!("Failed to run {}", self.program), err)
+            })?;
+        if !output.status.success() {
+            return Err(user_error(format!(
+                "{} {} failed: {}",
+                self.program,
+                args[..2].join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Extracts `owner/name` from a GitHub SSH or HTTPS URL.
+fn parse_github_repository(url: &str) -> Option<String> {
+    let path = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.strip_prefix("ssh://git@github.com/"))
+        .or_else(|| url.strip_prefix("https://github.com/"))?;
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let (owner, name) = path.split_once('/')?;
+    (!owner.is_empty() && !name.is_empty() && !name.contains('/'))
+        .then(|| format!("{owner}/{name}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_github_repository() {
+        assert_eq!(
+            parse_github_repository("git@github.com:octo/repo.git").as_deref(),
+            Some("octo/repo")
+        );
+        assert_eq!(
+            parse_github_repository("ssh://git@github.com/octo/repo").as_deref(),
+            Some("octo/repo")
+        );
+        assert_eq!(
+            parse_github_repository("https://github.com/octo/repo/").as_deref(),
+            Some("octo/repo")
+        );
+        assert_eq!(
+            parse_github_repository("https://gitlab.com/octo/repo.git"),
+            None
+        );
+        assert_eq!(parse_github_repository("https://github.com/octo"), None);
+        assert_eq!(
+            parse_github_repository("https://github.com/octo/repo/pulls"),
+            None
+        );
+    }
+}
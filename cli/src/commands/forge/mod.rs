@@ -0,0 +1,45 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod github;
+mod submit;
+
+use clap::Subcommand;
+
+use self::submit::cmd_forge_submit;
+use self::submit::ForgeSubmitArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Commands for working with code review forges such as GitHub
+///
+/// A stack of changes is reviewed as one pull request per change. Each change
+/// is pushed to a bookmark named after its change ID, and its pull request
+/// targets the bookmark of its parent change, so the stack can be rewritten
+/// and submitted again without losing track of the pull requests.
+#[derive(Subcommand, Clone, Debug)]
+pub enum ForgeCommand {
+    Submit(ForgeSubmitArgs),
+}
+
+pub fn cmd_forge(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &ForgeCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        ForgeCommand::Submit(args) => cmd_forge_submit(ui, command, args),
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use itertools::Itertools;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::git;
+use jj_lib::git::GitBranchPushTargets;
+use jj_lib::refs::LocalAndRemoteRef;
+use jj_lib::repo::Repo;
+use jj_lib::settings::ConfigResultExt as _;
+
+use super::github::GitHub;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::commands::git::push::classify_bookmark_update;
+use crate::commands::git::push::get_default_push_remote;
+use crate::commands::git::push::map_push_error;
+use crate::commands::git::push::set_change_bookmarks;
+use crate::git_util::get_git_repo;
+use crate::git_util::with_remote_git_callbacks;
+use crate::git_util::GitSidebandProgressMessageWriter;
+use crate::ui::Ui;
+
+/// Create or update a pull request for each change in a stack
+///
+/// Each revision gets a bookmark named after its change ID, the same one `jj
+/// git push --change` would create, and the bookmarks are pushed. Then a pull
+/// request is created for each bookmark that doesn't have an open one yet. The
+/// pull request of a revision targets the bookmark of its parent if the parent
+/// is submitted too, and the base branch otherwise. Submitting again after the
+/// stack was rewritten force-pushes the bookmarks, and retargets the existing
+/// pull requests if the revisions were reordered.
+///
+/// Only GitHub is supported for now. Pull requests are managed with the `gh`
+/// command-line tool, which needs to be installed and logged in. The repository
+/// is derived from the remote URL, or can be set with
+/// `forge.github.repository`.
+#[derive(clap::Args, Clone, Debug)]
+pub struct ForgeSubmitArgs {
+    /// The revisions to submit (can be repeated)
+    #[arg(long, short, required = true)]
+    revisions: Vec<RevisionArg>,
+    /// The remote to push the bookmarks to
+    ///
+    /// Defaults to the `git.push` config, like `jj git push`.
+    #[arg(long)]
+    remote: Option<String>,
+    /// The branch that the bottom of the stack is meant to be merged into
+    ///
+    /// Defaults to the `forge.base-branch` config, or `main` if that is not
+    /// set.
+    #[arg(long, value_name = "BRANCH")]
+    base: Option<String>,
+    /// Create new pull requests as drafts
+    #[arg(long)]
+    draft: bool,
+    /// Only display what would be submitted
+    ///
+    /// Nothing is pushed, and no pull requests are created or updated.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+const DEFAULT_BASE_BRANCH: &str = "main";
+
+pub fn cmd_forge_submit(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &ForgeSubmitArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+    let remote = if let Some(name) = &args.remote {
+        name.clone()
+    } else {
+        get_default_push_remote(ui, command.settings(), &git_repo)?
+    };
+    let base_branch = if let Some(branch) = &args.base {
+        branch.clone()
+    } else {
+        command
+            .settings()
+            .config()
+            .get_string("forge.base-branch")
+            .optional()?
+            .unwrap_or_else(|| DEFAULT_BASE_BRANCH.to_owned())
+    };
+    let remote_url = git_repo
+        .find_remote(&remote)
+        .ok()
+        .and_then(|remote| remote.url().map(str::to_owned));
+    let github = GitHub::from_settings(command.settings(), remote_url.as_deref())?;
+
+    let mut commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to submit.")?;
+        return Ok(());
+    }
+    // Parents first, so the base of each pull request is known before it's
+    // created.
+    commits.reverse();
+    for commit in &commits {
+        let mut reasons = vec![];
+        if commit.description().trim().is_empty() {
+            reasons.push("it has no description");
+        }
+        if commit.has_conflict()? {
+            reasons.push("it has conflicts");
+        }
+        if !reasons.is_empty() {
+            return Err(user_error(format!(
+                "Won't submit commit {} since {}",
+                short_commit_hash(commit.id()),
+                reasons.join(" and ")
+            )));
+        }
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let bookmark_prefix = command.settings().push_bookmark_prefix();
+    let bookmark_names = set_change_bookmarks(ui, &mut tx, &commits, &bookmark_prefix)?;
+    let bookmark_by_commit: HashMap<&CommitId, &str> = commits
+        .iter()
+        .map(Commit::id)
+        .zip(bookmark_names.iter().map(String::as_str))
+        .collect();
+    let pull_request_bases = commits
+        .iter()
+        .map(|commit| {
+            commit
+                .parent_ids()
+                .iter()
+                .find_map(|parent_id| bookmark_by_commit.get(parent_id).copied())
+                .unwrap_or(base_branch.as_str())
+        })
+        .collect_vec();
+
+    if args.dry_run {
+        if let Some(mut formatter) = ui.status_formatter() {
+            writeln!(formatter, "Would submit {} changes:", commits.len())?;
+            for ((commit, head), base) in
+                commits.iter().zip(&bookmark_names).zip(&pull_request_bases)
+            {
+                write!(formatter, "  {head} onto {base}: ")?;
+                tx.write_commit_summary(formatter.as_mut(), commit)?;
+                writeln!(formatter)?;
+            }
+        }
+        writeln!(ui.status(), "Dry-run requested, not submitting.")?;
+        return Ok(());
+    }
+
+    let mut bookmark_updates = vec![];
+    for bookmark_name in &bookmark_names {
+        let view = tx.repo().view();
+        let targets = LocalAndRemoteRef {
+            local_target: view.get_local_bookmark(bookmark_name),
+            remote_ref: view.get_remote_bookmark(bookmark_name, &remote),
+        };
+        if let Some(update) = classify_bookmark_update(bookmark_name, &remote, targets)? {
+            bookmark_updates.push((bookmark_name.clone(), update));
+        }
+    }
+    if !bookmark_updates.is_empty() {
+        let targets = GitBranchPushTargets {
+            branch_updates: bookmark_updates,
+            force: false,
+        };
+        let mut writer = GitSidebandProgressMessageWriter::new(ui);
+        let mut sideband_progress_callback = |progress_message: &[u8]| {
+            _ = writer.write(ui, progress_message);
+        };
+        with_remote_git_callbacks(ui, Some(&mut sideband_progress_callback), |cb| {
+            git::push_branches(
+                tx.repo_mut(),
+                &git_repo,
+                &remote,
+                &targets,
+                &[],
+                cb,
+                &command.settings().git_settings(),
+            )
+        })
+        .map_err(map_push_error)?;
+        writer.flush(ui)?;
+    }
+    // Record the pushed bookmarks before talking to GitHub, which may fail.
+    if tx.repo().has_changes() {
+        tx.finish(
+            ui,
+            format!(
+                "push {} for review to git remote {remote}",
+                bookmark_names.iter().join(", ")
+            ),
+        )?;
+    }
+
+    for ((commit, head), base) in commits.iter().zip(&bookmark_names).zip(&pull_request_bases) {
+        let (title, body) = commit
+            .description()
+            .split_once('\n')
+            .unwrap_or((commit.description(), ""));
+        match github.find_pull_request(head)? {
+            Some(pull_request) if pull_request.base == **base => {
+                writeln!(
+                    ui.status(),
+                    "Pull request #{} for {head} is up to date: {}",
+                    pull_request.number,
+                    pull_request.url
+                )?;
+            }
+            Some(pull_request) => {
+                github.set_pull_request_base(pull_request.number, base)?;
+                writeln!(
+                    ui.status(),
+                    "Retargeted pull request #{} for {head} onto {base}: {}",
+                    pull_request.number,
+                    pull_request.url
+                )?;
+            }
+            None => {
+                let pull_request = github.create_pull_request(
+                    head,
+                    base,
+                    title.trim(),
+                    body.trim(),
+                    args.draft,
+                )?;
+                writeln!(
+                    ui.status(),
+                    "Created pull request #{} for {head}: {}",
+                    pull_request.number,
+                    pull_request.url
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
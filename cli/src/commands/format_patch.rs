@@ -0,0 +1,347 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use itertools::Itertools as _;
+use jj_lib::backend::Signature;
+use jj_lib::backend::Timestamp;
+use jj_lib::commit::Commit;
+use jj_lib::copies::CopyRecords;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::object_id::ObjectId as _;
+use tracing::instrument;
+
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::diff_util::DiffFormat;
+use crate::formatter::PlainTextFormatter;
+use crate::time_util::format_rfc2822_timestamp;
+use crate::ui::Ui;
+
+/// The width of the diffstat, as in `git format-patch`
+const DIFF_STAT_WIDTH: usize = 72;
+
+/// Export revisions as patch emails
+///
+/// Each revision is written as an email in mbox format, like `git
+/// format-patch`. The emails are threaded, so they can be sent to a mailing
+/// list with e.g. `git send-email`. They can be applied with `jj am` or `git
+/// am`.
+///
+/// Merge commits can't be exported.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct FormatPatchArgs {
+    /// The revisions to export
+    #[arg(long, short, required = true)]
+    revisions: Vec<RevisionArg>,
+    /// The directory to write the patch files to
+    ///
+    /// Defaults to the current directory.
+    #[arg(long, short, value_hint = clap::ValueHint::DirPath, conflicts_with = "stdout")]
+    output_directory: Option<PathBuf>,
+    /// Write the patches to stdout as a single mbox instead of to files
+    #[arg(long)]
+    stdout: bool,
+    /// Also write a cover letter with a shortlog and a diffstat of the series
+    ///
+    /// The subject and the body of the cover letter are placeholders to be
+    /// edited before sending.
+    #[arg(long)]
+    cover_letter: bool,
+    /// Mark the series as the given version of the patches, e.g. `[PATCH v2]`
+    #[arg(long, short = 'v', value_name = "N")]
+    reroll_count: Option<u32>,
+    /// The prefix of the subjects
+    #[arg(long, default_value = "PATCH")]
+    subject_prefix: String,
+    /// Make the first email a reply to the given Message-Id
+    ///
+    /// This is useful to send a new version of a series as a reply to the
+    /// previous version.
+    #[arg(long, value_name = "MESSAGE_ID")]
+    in_reply_to: Option<String>,
+}
+
+struct PatchEmail {
+    file_name: String,
+    commit_hex: String,
+    from: Signature,
+    date: Timestamp,
+    subject: String,
+    message_id: String,
+    body: Vec<u8>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_format_patch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &FormatPatchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to format.")?;
+        return Ok(());
+    }
+    // Parents first
+    let commits = commits.into_iter().rev().collect_vec();
+    if let Some(commit) = commits.iter().find(|commit| commit.parent_ids().len() > 1) {
+        return Err(user_error(format!(
+            "Cannot format merge commit {} as a patch",
+            short_commit_hash(commit.id())
+        )));
+    }
+
+    let settings = command.settings();
+    let email = settings.user_email();
+    let host = email
+        .split_once('@')
+        .map(|(_, host)| host)
+        .filter(|host| !host.is_empty())
+        .unwrap_or("localhost");
+    let version_suffix = args
+        .reroll_count
+        .map(|count| format!(".v{count}"))
+        .unwrap_or_default();
+    let file_prefix = args
+        .reroll_count
+        .map(|count| format!("v{count}-"))
+        .unwrap_or_default();
+    let numbered = commits.len() > 1 || args.cover_letter;
+    let subject_prefix = |number: usize| {
+        let mut prefix = args.subject_prefix.clone();
+        if let Some(count) = args.reroll_count {
+            prefix.push_str(&format!(" v{count}"));
+        }
+        if numbered {
+            prefix.push_str(&format!(" {number}/{}", commits.len()));
+        }
+        format!("[{}]", prefix.trim_start())
+    };
+
+    let mut emails = vec![];
+    if args.cover_letter {
+        let last_commit = commits.last().unwrap();
+        emails.push(PatchEmail {
+            file_name: format!("{file_prefix}0000-cover-letter.patch"),
+            commit_hex: "0".repeat(last_commit.id().hex().len()),
+            from: settings.signature(),
+            date: settings.signature().timestamp,
+            subject: format!("{} *** SUBJECT HERE ***", subject_prefix(0)),
+            message_id: format!("<cover.{}{version_suffix}@{host}>", last_commit.id().hex()),
+            body: render_cover_letter(ui, &workspace_command, &commits)?,
+        });
+    }
+    for (index, commit) in commits.iter().enumerate() {
+        let number = index + 1;
+        let (subject, body) = split_description(commit.description());
+        let mut email_body = vec![];
+        if !body.is_empty() {
+            writeln!(email_body, "{body}")?;
+        }
+        writeln!(email_body, "---")?;
+        email_body.extend(render_patch(
+            ui,
+            &workspace_command,
+            commit,
+            vec![DiffFormat::Stat],
+        )?);
+        writeln!(email_body)?;
+        email_body.extend(render_patch(
+            ui,
+            &workspace_command,
+            commit,
+            vec![DiffFormat::Git { context: 3 }],
+        )?);
+        emails.push(PatchEmail {
+            file_name: format!("{file_prefix}{number:04}-{}.patch", subject_slug(subject)),
+            commit_hex: commit.id().hex(),
+            from: commit.author().clone(),
+            date: commit.author().timestamp.clone(),
+            subject: format!("{} {subject}", subject_prefix(number)),
+            message_id: format!("<{}{version_suffix}@{host}>", commit.id().hex()),
+            body: email_body,
+        });
+    }
+
+    // Shallow threading: all emails are replies to the first one
+    let signature = command.app().render_version();
+    let mut outputs = vec![];
+    for (index, email) in emails.iter().enumerate() {
+        let in_reply_to = if index == 0 {
+            args.in_reply_to.as_ref().map(|id| normalize_message_id(id))
+        } else {
+            Some(emails[0].message_id.clone())
+        };
+        let references = [
+            args.in_reply_to.as_ref().map(|id| normalize_message_id(id)),
+            (index > 0).then(|| emails[0].message_id.clone()),
+        ]
+        .into_iter()
+        .flatten()
+        .join(" ");
+        let mut output = vec![];
+        writeln!(output, "From {} Mon Sep 17 00:00:00 2001", email.commit_hex)?;
+        writeln!(output, "From: {} <{}>", email.from.name, email.from.email)?;
+        writeln!(
+            output,
+            "Date: {}",
+            format_rfc2822_timestamp(&email.date).map_err(user_error)?
+        )?;
+        writeln!(output, "Subject: {}", email.subject)?;
+        writeln!(output, "Message-Id: {}", email.message_id)?;
+        if let Some(in_reply_to) = &in_reply_to {
+            writeln!(output, "In-Reply-To: {in_reply_to}")?;
+        }
+        if !references.is_empty() {
+            writeln!(output, "References: {references}")?;
+        }
+        writeln!(output, "MIME-Version: 1.0")?;
+        writeln!(output, "Content-Type: text/plain; charset=UTF-8")?;
+        writeln!(output, "Content-Transfer-Encoding: 8bit")?;
+        writeln!(output)?;
+        output.extend(&email.body);
+        writeln!(output, "-- ")?;
+        writeln!(output, "{}", signature.trim_end())?;
+        writeln!(output)?;
+        outputs.push((&email.file_name, output));
+    }
+
+    if args.stdout {
+        let mut stdout = ui.stdout();
+        for (_, output) in &outputs {
+            stdout.write_all(output)?;
+        }
+        return Ok(());
+    }
+    let output_dir = match &args.output_directory {
+        Some(dir) => command.cwd().join(dir),
+        None => command.cwd().to_owned(),
+    };
+    fs::create_dir_all(&output_dir).map_err(|err| {
+        user_error_with_message(
+            format!("Failed to create directory {}", output_dir.display()),
+            err,
+        )
+    })?;
+    for (file_name, output) in &outputs {
+        let path = output_dir.join(file_name);
+        fs::write(&path, output).map_err(|err| {
+            user_error_with_message(format!("Failed to write {}", path.display()), err)
+        })?;
+        writeln!(ui.stdout(), "{}", path.display())?;
+    }
+    Ok(())
+}
+
+/// Splits a description into the subject and the body of an email.
+fn split_description(description: &str) -> (&str, &str) {
+    let description = description.trim();
+    match description.split_once('\n') {
+        Some((subject, body)) => (subject.trim(), body.trim()),
+        None => (description, ""),
+    }
+}
+
+/// Turns the subject into a file name like `git format-patch` does.
+fn subject_slug(subject: &str) -> String {
+    let slug = subject
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '_'))
+        .filter(|word| !word.is_empty())
+        .join("-");
+    let slug = slug.trim_start_matches('.');
+    let mut end = slug.len().min(52);
+    while end > 0 && !slug.is_char_boundary(end) {
+        end -= 1;
+    }
+    slug[..end].trim_end_matches(['-', '.']).to_owned()
+}
+
+fn normalize_message_id(id: &str) -> String {
+    let id = id.trim().trim_start_matches('<').trim_end_matches('>');
+    format!("<{id}>")
+}
+
+fn render_patch(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    commit: &Commit,
+    formats: Vec<DiffFormat>,
+) -> Result<Vec<u8>, CommandError> {
+    let mut output = vec![];
+    let mut formatter = PlainTextFormatter::new(&mut output);
+    workspace_command.diff_renderer(formats).show_patch(
+        ui,
+        &mut formatter,
+        commit,
+        &EverythingMatcher,
+        DIFF_STAT_WIDTH,
+    )?;
+    Ok(output)
+}
+
+/// Renders the body of the cover letter: placeholder text, the subjects of the
+/// patches grouped by author, and the diffstat of the whole series.
+fn render_cover_letter(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    commits: &[Commit],
+) -> Result<Vec<u8>, CommandError> {
+    let repo = workspace_command.repo().as_ref();
+    let mut output = vec![];
+    writeln!(output, "*** BLURB HERE ***")?;
+    writeln!(output)?;
+    let commits_by_author = commits
+        .iter()
+        .into_group_map_by(|commit| commit.author().name.clone());
+    for (author, author_commits) in commits_by_author
+        .iter()
+        .sorted_by_key(|(author, _)| author.as_str())
+    {
+        writeln!(output, "{author} ({}):", author_commits.len())?;
+        for commit in author_commits {
+            let (subject, _) = split_description(commit.description());
+            writeln!(output, "  {subject}")?;
+        }
+        writeln!(output)?;
+    }
+    let from_tree = commits[0].parent_tree(repo)?;
+    let to_tree = commits.last().unwrap().tree()?;
+    let mut formatter = PlainTextFormatter::new(&mut output);
+    workspace_command
+        .diff_renderer(vec![DiffFormat::Stat])
+        .show_diff(
+            ui,
+            &mut formatter,
+            &from_tree,
+            &to_tree,
+            &EverythingMatcher,
+            &CopyRecords::default(),
+            DIFF_STAT_WIDTH,
+        )?;
+    writeln!(output)?;
+    Ok(output)
+}
@@ -0,0 +1,43 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod send;
+
+use clap::Subcommand;
+
+use self::send::cmd_gerrit_send;
+use self::send::GerritSendArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Commands for working with Gerrit remotes
+///
+/// Gerrit reviews each commit separately, and identifies it across amendments
+/// by the `Change-Id` trailer in its description. This maps naturally onto jj
+/// changes, so the trailer is derived from the change ID.
+#[derive(Subcommand, Clone, Debug)]
+pub enum GerritCommand {
+    Send(GerritSendArgs),
+}
+
+pub fn cmd_gerrit(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &GerritCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        GerritCommand::Send(args) => cmd_gerrit_send(ui, command, args),
+    }
+}
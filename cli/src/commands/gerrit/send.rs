@@ -0,0 +1,326 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use itertools::Itertools;
+use jj_lib::backend::ChangeId;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::git;
+use jj_lib::git::GitRefUpdate;
+use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::settings::ConfigResultExt as _;
+use jj_lib::str_util::StringPattern;
+use jj_lib::trailer::parse_description_trailers;
+
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::commands::git::push::get_default_push_remote;
+use crate::commands::git::push::map_push_error;
+use crate::git_util::get_git_repo;
+use crate::git_util::with_remote_git_callbacks;
+use crate::git_util::GitSidebandProgressMessageWriter;
+use crate::ui::Ui;
+
+/// Send revisions to Gerrit for review
+///
+/// Pushes the given revisions, and their ancestors that are not on the remote
+/// yet, to `refs/for/<branch>` on the remote. Gerrit creates or updates one
+/// review per commit.
+///
+/// Every commit that is sent needs a `Change-Id` trailer. Commits without one
+/// get a trailer derived from their change ID, so amending a change and sending
+/// it again updates the same review. If a commit carries a trailer derived
+/// from a different change ID (e.g. because it was duplicated), the trailer is
+/// updated. Trailers that were not generated by jj are left alone. Commits
+/// whose descriptions change are rewritten, and their descendants are rebased.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GerritSendArgs {
+    /// The revisions to send (can be repeated)
+    #[arg(long, short, required = true)]
+    revisions: Vec<RevisionArg>,
+    /// The remote to push to
+    ///
+    /// Defaults to the `git.push` config, like `jj git push`.
+    #[arg(long)]
+    remote: Option<String>,
+    /// The branch the changes are intended for
+    ///
+    /// Defaults to the `gerrit.default-branch` config, or `main` if that is not
+    /// set.
+    #[arg(long = "for", value_name = "BRANCH")]
+    for_branch: Option<String>,
+    /// Set the topic of the reviews
+    #[arg(long)]
+    topic: Option<String>,
+    /// Add a reviewer to the reviews (can be repeated)
+    #[arg(long, value_name = "EMAIL")]
+    reviewer: Vec<String>,
+    /// CC someone on the reviews (can be repeated)
+    #[arg(long, value_name = "EMAIL")]
+    cc: Vec<String>,
+    /// Only display what would be sent
+    ///
+    /// No commits are rewritten, and the remote is not contacted.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Prefix of the `Change-Id` trailers generated by jj, "jjid" in hex.
+///
+/// Gerrit expects an `I` followed by 40 hex digits. A change ID has 32, which
+/// leaves room for a prefix that marks the trailer as ours.
+const CHANGE_ID_TRAILER_PREFIX: &str = "I6a6a6964";
+
+const DEFAULT_TARGET_BRANCH: &str = "main";
+
+pub fn cmd_gerrit_send(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GerritSendArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+    let remote = if let Some(name) = &args.remote {
+        name.clone()
+    } else {
+        get_default_push_remote(ui, command.settings(), &git_repo)?
+    };
+    let target_branch = if let Some(branch) = &args.for_branch {
+        branch.clone()
+    } else {
+        command
+            .settings()
+            .config()
+            .get_string("gerrit.default-branch")
+            .optional()?
+            .unwrap_or_else(|| DEFAULT_TARGET_BRANCH.to_owned())
+    };
+
+    let revisions_expression = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .expression()
+        .clone();
+    let to_send_expression = RevsetExpression::remote_bookmarks(
+        StringPattern::everything(),
+        StringPattern::exact(&remote),
+        None,
+    )
+    .union(workspace_command.env().immutable_heads_expression())
+    .range(&revisions_expression);
+    let commits_to_send: Vec<Commit> = workspace_command
+        .attach_revset_evaluator(to_send_expression.clone())
+        .evaluate_to_commits()?
+        .try_collect()?;
+    if commits_to_send.is_empty() {
+        writeln!(ui.status(), "No commits to send.")?;
+        return Ok(());
+    }
+    let heads_to_send: Vec<CommitId> = workspace_command
+        .attach_revset_evaluator(to_send_expression.heads())
+        .evaluate_to_commit_ids()?
+        .collect();
+
+    let mut new_descriptions = HashMap::new();
+    for commit in &commits_to_send {
+        let mut reasons = vec![];
+        if commit.description().trim().is_empty() {
+            reasons.push("it has no description");
+        }
+        if commit.has_conflict()? {
+            reasons.push("it has conflicts");
+        }
+        if !reasons.is_empty() {
+            return Err(user_error(format!(
+                "Won't send commit {} since {}",
+                short_commit_hash(commit.id()),
+                reasons.join(" and ")
+            )));
+        }
+        let change_id = gerrit_change_id(commit.change_id());
+        if let Some(description) = update_change_id_trailer(commit.description(), &change_id) {
+            new_descriptions.insert(commit.id().clone(), description);
+        }
+    }
+
+    let ref_name = gerrit_ref_name(&target_branch, args);
+    if args.dry_run {
+        if let Some(mut formatter) = ui.status_formatter() {
+            writeln!(
+                formatter,
+                "Would send {} commits to {ref_name} on {remote}:",
+                commits_to_send.len()
+            )?;
+            for commit in &commits_to_send {
+                write!(formatter, "  ")?;
+                workspace_command.write_commit_summary(formatter.as_mut(), commit)?;
+                writeln!(formatter)?;
+            }
+            if !new_descriptions.is_empty() {
+                writeln!(
+                    formatter,
+                    "Would add or update the Change-Id trailer of {} commits",
+                    new_descriptions.len()
+                )?;
+            }
+        }
+        writeln!(ui.status(), "Dry-run requested, not sending.")?;
+        return Ok(());
+    }
+
+    workspace_command.check_rewritable(new_descriptions.keys())?;
+    let mut tx = workspace_command.start_transaction();
+    let mut rewritten_commits: HashMap<CommitId, Commit> = HashMap::new();
+    let mut num_rebased = 0;
+    tx.repo_mut().transform_descendants(
+        command.settings(),
+        new_descriptions.keys().cloned().collect_vec(),
+        |rewriter| {
+            let old_commit_id = rewriter.old_commit().id().clone();
+            let mut commit_builder = rewriter.rebase(command.settings())?;
+            if let Some(description) = new_descriptions.get(&old_commit_id) {
+                commit_builder = commit_builder.set_description(description);
+            } else {
+                num_rebased += 1;
+            }
+            let new_commit = commit_builder.write()?;
+            rewritten_commits.insert(old_commit_id, new_commit);
+            Ok(())
+        },
+    )?;
+    if num_rebased > 0 {
+        writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
+    }
+
+    let sent_commits = commits_to_send
+        .iter()
+        .map(|commit| rewritten_commits.get(commit.id()).unwrap_or(commit))
+        .collect_vec();
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(
+            formatter,
+            "Sending {} commits to {ref_name} on {remote}:",
+            sent_commits.len()
+        )?;
+        for commit in &sent_commits {
+            write!(formatter, "  ")?;
+            tx.write_commit_summary(formatter.as_mut(), commit)?;
+            writeln!(formatter)?;
+        }
+    }
+
+    // The same ref can only be updated once per push, so each head is sent
+    // separately. Gerrit treats refs/for/* as magic refs that never exist, but
+    // force-pushing keeps repeated sends working with ordinary Git remotes.
+    let mut writer = GitSidebandProgressMessageWriter::new(ui);
+    for head_id in &heads_to_send {
+        let new_head_id = rewritten_commits
+            .get(head_id)
+            .map_or(head_id, |commit| commit.id());
+        let update = GitRefUpdate {
+            qualified_name: ref_name.clone(),
+            expected_current_target: None,
+            new_target: Some(new_head_id.clone()),
+            force: true,
+        };
+        let mut sideband_progress_callback = |progress_message: &[u8]| {
+            _ = writer.write(ui, progress_message);
+        };
+        with_remote_git_callbacks(ui, Some(&mut sideband_progress_callback), |cb| {
+            git::push_updates(
+                tx.repo(),
+                &git_repo,
+                &remote,
+                &[update],
+                &[],
+                cb,
+                &command.settings().git_settings(),
+            )
+        })
+        .map_err(map_push_error)?;
+    }
+    writer.flush(ui)?;
+
+    if tx.repo().has_changes() {
+        tx.finish(
+            ui,
+            format!("add Change-Id trailers to commits sent to gerrit remote {remote}"),
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the `Change-Id` Gerrit should use for the given change.
+fn gerrit_change_id(change_id: &ChangeId) -> String {
+    format!("{CHANGE_ID_TRAILER_PREFIX}{}", change_id.hex())
+}
+
+/// Returns the ref to push to, with the review options encoded as Gerrit
+/// expects them after a `%`.
+fn gerrit_ref_name(target_branch: &str, args: &GerritSendArgs) -> String {
+    let options = itertools::chain!(
+        args.topic.iter().map(|topic| format!("topic={topic}")),
+        args.reviewer.iter().map(|email| format!("r={email}")),
+        args.cc.iter().map(|email| format!("cc={email}")),
+    )
+    .collect_vec();
+    if options.is_empty() {
+        format!("refs/for/{target_branch}")
+    } else {
+        format!("refs/for/{target_branch}%{}", options.join(","))
+    }
+}
+
+/// Returns the new description if the `Change-Id` trailer needs to be added or
+/// updated.
+fn update_change_id_trailer(description: &str, change_id: &str) -> Option<String> {
+    let trailers = parse_description_trailers(description);
+    let existing = trailers
+        .iter()
+        .rfind(|trailer| trailer.key.eq_ignore_ascii_case("Change-Id"));
+    match existing {
+        Some(trailer) if trailer.value == change_id => None,
+        Some(trailer) if trailer.value.starts_with(CHANGE_ID_TRAILER_PREFIX) => {
+            let mut lines = description.trim_end().lines().collect_vec();
+            let new_line = format!("Change-Id: {change_id}");
+            let index = lines.iter().rposition(|line| {
+                line.split_once(':').is_some_and(|(key, value)| {
+                    key.trim_end().eq_ignore_ascii_case("Change-Id")
+                        && value.trim() == trailer.value
+                })
+            })?;
+            lines[index] = &new_line;
+            Some(lines.iter().map(|line| format!("{line}\n")).collect())
+        }
+        // Probably added by Gerrit's commit-msg hook, which Gerrit already
+        // knows the review by.
+        Some(_) => None,
+        None => {
+            let mut new_description = description.trim_end().to_owned();
+            new_description.push('\n');
+            if trailers.is_empty() {
+                new_description.push('\n');
+            }
+            new_description.push_str(&format!("Change-Id: {change_id}\n"));
+            Some(new_description)
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use jj_lib::git;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo::Repo;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::description_util::join_message_paragraphs;
+use crate::git_util::get_git_repo;
+use crate::ui::Ui;
+
+/// Apply patches to a revision
+///
+/// The patches can be in the format of `git diff` or `git format-patch`,
+/// including binary patches and renames, or traditional unified diffs.
+///
+/// If a file patch doesn't apply cleanly, but the preimage blob recorded in
+/// its `index` line exists in the repo, the patch is applied to the preimage
+/// and the result is merged into the revision, like `git apply --3way`. Files
+/// that can't be merged are left conflicted.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitApplyArgs {
+    /// The patch files to apply, or `-` to read from stdin
+    ///
+    /// If no files are given, the patch is read from stdin.
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    patches: Vec<PathBuf>,
+    /// The revision to apply the patches to
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+    /// Create a new commit with the patches applied on top of the revision
+    /// instead of rewriting it
+    ///
+    /// The working copy is not moved to the new commit.
+    #[arg(long)]
+    new: bool,
+    /// The description of the new commit
+    #[arg(long = "message", short, value_name = "MESSAGE", requires = "new")]
+    message_paragraphs: Vec<String>,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_git_apply(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitApplyArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    if !args.new {
+        workspace_command.check_rewritable([commit.id()])?;
+    }
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+
+    let mut tree = commit.tree()?;
+    let mut merged_paths = vec![];
+    let mut conflicted_paths = vec![];
+    for patch in read_patches(&args.patches)? {
+        let applied = git::apply_patch(&git_repo, &tree, &patch)?;
+        tree = applied.tree;
+        merged_paths.extend(applied.merged_paths);
+        conflicted_paths.extend(applied.conflicted_paths);
+    }
+    conflicted_paths
+        .retain(|path| !matches!(tree.path_value(path), Ok(value) if value.is_resolved()));
+
+    if !args.new && tree.id() == *commit.tree_id() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let new_commit = if args.new {
+        tx.repo_mut()
+            .new_commit(command.settings(), vec![commit.id().clone()], tree.id())
+            .set_description(join_message_paragraphs(&args.message_paragraphs))
+            .write()?
+    } else {
+        tx.repo_mut()
+            .rewrite_commit(command.settings(), &commit)
+            .set_tree_id(tree.id())
+            .write()?
+    };
+    let num_rebased = tx.repo_mut().rebase_descendants(command.settings())?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        let tx_repo = tx.base_workspace_helper();
+        for path in &merged_paths {
+            writeln!(
+                formatter,
+                "Applied patch to {} with three-way merge",
+                tx_repo.format_file_path(path)
+            )?;
+        }
+        write!(
+            formatter,
+            "{} ",
+            if args.new { "Created" } else { "Rewrote" }
+        )?;
+        tx.write_commit_summary(formatter.as_mut(), &new_commit)?;
+        writeln!(formatter)?;
+        if num_rebased > 0 {
+            writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
+        }
+    }
+    if !conflicted_paths.is_empty() {
+        writeln!(
+            ui.warning_default(),
+            "There were conflicts in {} files when merging the patches:",
+            conflicted_paths.len()
+        )?;
+        for path in &conflicted_paths {
+            writeln!(
+                ui.warning_no_heading(),
+                "  {}",
+                tx.base_workspace_helper().format_file_path(path)
+            )?;
+        }
+    }
+    tx.finish(ui, format!("apply patches to commit {}", commit.id().hex()))?;
+    Ok(())
+}
+
+/// Reads the given files, or stdin if there are none or for `-`.
+pub fn read_patches(paths: &[PathBuf]) -> Result<Vec<Vec<u8>>, CommandError> {
+    if paths.is_empty() {
+        return Ok(vec![read_stdin()?]);
+    }
+    paths
+        .iter()
+        .map(|path| {
+            if path.as_os_str() == "-" {
+                read_stdin()
+            } else {
+                fs::read(path).map_err(|err| {
+                    user_error_with_message(format!("Failed to read patch {}", path.display()), err)
+                })
+            }
+        })
+        .collect()
+}
+
+fn read_stdin() -> Result<Vec<u8>, CommandError> {
+    let mut patch = vec![];
+    io::stdin().read_to_end(&mut patch)?;
+    Ok(patch)
+}
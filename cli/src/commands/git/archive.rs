@@ -0,0 +1,303 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Read as _;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Datelike as _;
+use chrono::FixedOffset;
+use chrono::TimeZone as _;
+use chrono::Timelike as _;
+use flate2::write::GzEncoder;
+use jj_lib::conflicts::materialize_merge_result;
+use jj_lib::conflicts::materialize_tree_value;
+use jj_lib::conflicts::MaterializedTreeValue;
+use jj_lib::repo::Repo;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Create an archive of the files in a revision
+///
+/// The files are read from the repo, so the revision doesn't need to be checked
+/// out. Conflicted files are archived with conflict markers. Git submodules are
+/// not included.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitArchiveArgs {
+    /// The revision to archive
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+    /// Where to write the archive
+    #[arg(long, short, value_hint = clap::ValueHint::FilePath)]
+    output: PathBuf,
+    /// The format of the archive
+    ///
+    /// If not specified, the format is inferred from the file name of the
+    /// output, and defaults to `tar`.
+    #[arg(long, value_enum)]
+    format: Option<ArchiveFormat>,
+    /// Prepend the given string to the path of each file in the archive
+    ///
+    /// As with `git archive`, no `/` is added, so use e.g. `--prefix=project/`
+    /// to put the files in a directory.
+    #[arg(long)]
+    prefix: Option<String>,
+    /// The modification time of the files in the archive
+    ///
+    /// The time must be in RFC 3339 format, e.g. `2024-01-01T12:00:00+09:00`.
+    /// Defaults to the committer timestamp of the revision.
+    #[arg(long)]
+    mtime: Option<DateTime<FixedOffset>>,
+    /// Only archive files matching these paths
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+enum ArchiveFormat {
+    Tar,
+    #[value(name = "tar.gz", alias = "tgz")]
+    TarGz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+#[instrument(skip_all)]
+pub fn cmd_git_archive(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitArchiveArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let tree = commit.tree()?;
+    let matcher = workspace_command
+        .parse_file_patterns(ui, &args.paths)?
+        .to_matcher();
+    let mtime = match args.mtime {
+        Some(mtime) => mtime,
+        None => {
+            let timestamp = &commit.committer().timestamp;
+            let utc = chrono::Utc
+                .timestamp_millis_opt(timestamp.timestamp.0)
+                .single()
+                .ok_or_else(|| user_error("The committer timestamp is out of range"))?;
+            let offset = FixedOffset::east_opt(timestamp.tz_offset * 60)
+                .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+            utc.with_timezone(&offset)
+        }
+    };
+    let format = args
+        .format
+        .or_else(|| ArchiveFormat::from_path(&args.output))
+        .unwrap_or(ArchiveFormat::Tar);
+
+    let file = File::create(&args.output).map_err(|err| {
+        user_error_with_message(format!("Failed to create {}", args.output.display()), err)
+    })?;
+    let prefix = args.prefix.as_deref().unwrap_or("");
+    let mut archive = ArchiveWriter::new(format, file, mtime);
+    let repo = workspace_command.repo();
+    let mut num_files = 0;
+    for (path, result) in tree.entries_matching(matcher.as_ref()) {
+        let value = result?;
+        let archive_path = format!("{prefix}{}", path.as_internal_file_string());
+        match materialize_tree_value(repo.store(), &path, value).block_on()? {
+            MaterializedTreeValue::Absent => panic!("absent values should be excluded"),
+            MaterializedTreeValue::AccessDenied(err) => {
+                let ui_path = workspace_command.format_file_path(&path);
+                writeln!(
+                    ui.warning_default(),
+                    "Path '{ui_path}' exists but access is denied: {err}"
+                )?;
+                continue;
+            }
+            MaterializedTreeValue::File {
+                executable,
+                mut reader,
+                ..
+            } => {
+                let mut contents = vec![];
+                reader.read_to_end(&mut contents)?;
+                archive.add_file(&archive_path, executable, &contents)?;
+            }
+            MaterializedTreeValue::FileConflict {
+                contents,
+                executable,
+                ..
+            } => {
+                let mut materialized = vec![];
+                materialize_merge_result(&contents, &mut materialized)?;
+                archive.add_file(&archive_path, executable, &materialized)?;
+            }
+            MaterializedTreeValue::OtherConflict { id } => {
+                archive.add_file(&archive_path, false, id.describe().as_bytes())?;
+            }
+            MaterializedTreeValue::Symlink { target, .. } => {
+                archive.add_symlink(&archive_path, &target)?;
+            }
+            MaterializedTreeValue::GitSubmodule(_) => continue,
+            MaterializedTreeValue::Tree(_) => panic!("entries should not contain trees"),
+        }
+        num_files += 1;
+    }
+    archive.finish()?;
+    writeln!(
+        ui.status(),
+        "Wrote {num_files} files to {}",
+        args.output.display()
+    )?;
+    Ok(())
+}
+
+enum ArchiveWriter {
+    Tar(tar::Builder<BufWriter<File>>, u64),
+    TarGz(tar::Builder<GzEncoder<BufWriter<File>>>, u64),
+    Zip(zip::ZipWriter<File>, zip::DateTime),
+}
+
+impl ArchiveWriter {
+    fn new(format: ArchiveFormat, file: File, mtime: DateTime<FixedOffset>) -> Self {
+        let tar_mtime = mtime.timestamp().try_into().unwrap_or(0);
+        match format {
+            ArchiveFormat::Tar => {
+                ArchiveWriter::Tar(tar::Builder::new(BufWriter::new(file)), tar_mtime)
+            }
+            ArchiveFormat::TarGz => {
+                let writer = GzEncoder::new(BufWriter::new(file), flate2::Compression::default());
+                ArchiveWriter::TarGz(tar::Builder::new(writer), tar_mtime)
+            }
+            ArchiveFormat::Zip => {
+                // Zip files store the local time, which can't be before 1980.
+                let zip_mtime = zip::DateTime::from_date_and_time(
+                    mtime.year().try_into().unwrap_or(0),
+                    mtime.month() as u8,
+                    mtime.day() as u8,
+                    mtime.hour() as u8,
+                    mtime.minute() as u8,
+                    mtime.second() as u8,
+                )
+                .unwrap_or_default();
+                ArchiveWriter::Zip(zip::ZipWriter::new(file), zip_mtime)
+            }
+        }
+    }
+
+    fn add_file(&mut self, path: &str, executable: bool, contents: &[u8]) -> io::Result<()> {
+        let mode = if executable { 0o755 } else { 0o644 };
+        match self {
+            ArchiveWriter::Tar(builder, mtime) => {
+                append_tar_file(builder, *mtime, path, mode, contents)
+            }
+            ArchiveWriter::TarGz(builder, mtime) => {
+                append_tar_file(builder, *mtime, path, mode, contents)
+            }
+            ArchiveWriter::Zip(writer, mtime) => {
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .last_modified_time(*mtime)
+                    .unix_permissions(mode)
+                    .large_file(contents.len() as u64 >= u32::MAX as u64);
+                writer.start_file(path, options)?;
+                writer.write_all(contents)
+            }
+        }
+    }
+
+    fn add_symlink(&mut self, path: &str, target: &str) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Tar(builder, mtime) => append_tar_symlink(builder, *mtime, path, target),
+            ArchiveWriter::TarGz(builder, mtime) => {
+                append_tar_symlink(builder, *mtime, path, target)
+            }
+            ArchiveWriter::Zip(writer, mtime) => {
+                let options = zip::write::SimpleFileOptions::default()
+                    .last_modified_time(*mtime)
+                    .unix_permissions(0o777);
+                writer.add_symlink(path, target, options)?;
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Tar(builder, _) => {
+                builder.into_inner()?.flush()?;
+            }
+            ArchiveWriter::TarGz(builder, _) => {
+                builder.into_inner()?.finish()?.flush()?;
+            }
+            ArchiveWriter::Zip(writer, _) => {
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn append_tar_file<W: Write>(
+    builder: &mut tar::Builder<W>,
+    mtime: u64,
+    path: &str,
+    mode: u32,
+    contents: &[u8],
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_mode(mode);
+    header.set_mtime(mtime);
+    header.set_size(contents.len() as u64);
+    builder.append_data(&mut header, path, contents)
+}
+
+pub(crate) fn append_tar_symlink<W: Write>(
+    builder: &mut tar::Builder<W>,
+    mtime: u64,
+    path: &str,
+    target: &str,
+) -> io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_mode(0o777);
+    header.set_mtime(mtime);
+    header.set_size(0);
+    builder.append_link(&mut header, path, target)
+}
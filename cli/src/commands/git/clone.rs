@@ -15,12 +15,15 @@
 use std::fs;
 use std::io;
 use std::io::Write;
+use std::num::NonZeroU32;
 use std::path::Path;
 use std::path::PathBuf;
 
 use jj_lib::git;
 use jj_lib::git::GitFetchError;
+use jj_lib::git::GitFetchOptions;
 use jj_lib::git::GitFetchStats;
+use jj_lib::git_backend::GitBackend;
 use jj_lib::repo::Repo;
 use jj_lib::str_util::StringPattern;
 use jj_lib::workspace::Workspace;
@@ -33,6 +36,7 @@ use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::commands::git::map_git_error;
 use crate::commands::git::maybe_add_gitignore;
+use crate::commands::git::submodule::update_submodules;
 use crate::config::write_config_value_to_file;
 use crate::config::ConfigNamePathBuf;
 use crate::git_util::get_git_repo;
@@ -60,6 +64,24 @@ pub struct GitCloneArgs {
     /// Whether or not to colocate the Jujutsu repo with the git repo
     #[arg(long)]
     colocate: bool,
+    /// Create a shallow clone of the given depth
+    ///
+    /// Commits beyond the given depth are not fetched, and the oldest fetched
+    /// commits appear as children of the root commit.
+    #[arg(long)]
+    depth: Option<NonZeroU32>,
+    /// Create a shallow clone of the commits newer than the given date
+    ///
+    /// The date is passed to `git fetch --shallow-since`, which must be
+    /// installed.
+    #[arg(long, value_name = "DATE", conflicts_with = "depth")]
+    since: Option<String>,
+    /// Check out the submodules after cloning
+    ///
+    /// The submodules are checked out at the commits recorded in the
+    /// working-copy commit, as `jj git submodule update` does.
+    #[arg(long)]
+    recursive: bool,
 }
 
 fn absolute_git_source(cwd: &Path, source: &str) -> String {
@@ -135,6 +157,11 @@ pub fn cmd_git_clone(
         remote_name,
         &source,
         &canonical_wc_path,
+        &GitFetchOptions {
+            depth: args.depth,
+            shallow_since: args.since.clone(),
+            ..Default::default()
+        },
     );
     if clone_result.is_err() {
         let clean_up_dirs = || -> io::Result<()> {
@@ -188,6 +215,9 @@ pub fn cmd_git_clone(
             checkout_tx.finish(ui, "check out git remote's default branch")?;
         }
     }
+    if args.recursive {
+        update_submodules(ui, &workspace_command)?;
+    }
     Ok(())
 }
 
@@ -198,6 +228,7 @@ fn do_git_clone(
     remote_name: &str,
     source: &str,
     wc_path: &Path,
+    fetch_options: &GitFetchOptions,
 ) -> Result<(WorkspaceCommandHelper, GitFetchStats), CommandError> {
     let (workspace, repo) = if colocate {
         Workspace::init_colocated_git(command.settings(), wc_path)?
@@ -221,6 +252,7 @@ fn do_git_clone(
             &git_repo,
             remote_name,
             &[StringPattern::everything()],
+            fetch_options,
             cb,
             &command.settings().git_settings(),
         )
@@ -234,8 +266,21 @@ fn do_git_clone(
         GitFetchError::InvalidBranchPattern => {
             unreachable!("we didn't provide any globs")
         }
+        GitFetchError::FetchCommand(_) | GitFetchError::FetchCommandErrorStatus(_) => {
+            user_error(err)
+        }
     })?;
     print_git_import_stats(ui, fetch_tx.repo(), &stats.import_stats, true)?;
     fetch_tx.finish(ui, "fetch from git remote into empty repo")?;
+    // The index was built with the shallow commits of the clone, so it doesn't
+    // need to be rebuilt by the next command.
+    if let Some(git_backend) = workspace_command
+        .repo()
+        .store()
+        .backend_impl()
+        .downcast_ref::<GitBackend>()
+    {
+        git_backend.record_indexed_state()?;
+    }
     Ok((workspace_command, stats))
 }
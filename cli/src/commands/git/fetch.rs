@@ -12,9 +12,14 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+use std::num::NonZeroU32;
+
 use itertools::Itertools;
 use jj_lib::git;
 use jj_lib::git::GitFetchError;
+use jj_lib::git::GitFetchOptions;
+use jj_lib::git::GitFetchTagPolicy;
 use jj_lib::repo::Repo;
 use jj_lib::settings::ConfigResultExt as _;
 use jj_lib::settings::UserSettings;
@@ -23,11 +28,14 @@ use jj_lib::str_util::StringPattern;
 use crate::cli_util::CommandHelper;
 use crate::command_error::user_error;
 use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
 use crate::commands::git::map_git_error;
 use crate::git_util::get_git_repo;
 use crate::git_util::print_git_import_stats;
+use crate::git_util::record_last_fetch_time;
+use crate::git_util::spawn_detached_jj;
 use crate::git_util::with_remote_git_callbacks;
 use crate::ui::Ui;
 
@@ -41,7 +49,11 @@ pub struct GitFetchArgs {
     ///
     /// By default, the specified name matches exactly. Use `glob:` prefix to
     /// expand `*` as a glob. The other wildcard characters aren't supported.
-    #[arg(long, short, default_value = "glob:*", value_parser = StringPattern::parse)]
+    ///
+    /// If not specified, the branches are taken from the fetch refspecs of the
+    /// remote in the Git config, which include all branches unless changed by
+    /// e.g. `git remote set-branches`.
+    #[arg(long, short, value_parser = StringPattern::parse)]
     branch: Vec<StringPattern>,
     /// The remote to fetch from (only named remotes are supported, can be
     /// repeated)
@@ -50,6 +62,37 @@ pub struct GitFetchArgs {
     /// Fetch from all remotes
     #[arg(long, conflicts_with = "remotes")]
     all_remotes: bool,
+    /// Limit fetching to the given number of commits from the tip of each
+    /// branch
+    #[arg(long)]
+    depth: Option<NonZeroU32>,
+    /// Limit fetching to the commits newer than the given date
+    ///
+    /// The date is passed to `git fetch --shallow-since`, which must be
+    /// installed.
+    #[arg(long, value_name = "DATE", conflicts_with = "depth")]
+    since: Option<String>,
+    /// Fetch the full history of a shallow repo
+    ///
+    /// Like with `--since`, `git fetch` is run to download the history. The
+    /// commit index is rebuilt by the next command, since the oldest commits
+    /// that were fetched before gain parents.
+    #[arg(long, conflicts_with_all = ["depth", "since"])]
+    unshallow: bool,
+    /// Fetch all tags from the remote
+    ///
+    /// By default, only the tags pointing to fetched commits are fetched.
+    #[arg(long, conflicts_with = "no_tags")]
+    tags: bool,
+    /// Don't fetch tags from the remote
+    #[arg(long)]
+    no_tags: bool,
+    /// Fetch in a background process and return immediately
+    ///
+    /// The working copy isn't snapshotted by the background process, and
+    /// nothing is printed. The fetched bookmarks show up once it finishes.
+    #[arg(long)]
+    background: bool,
 }
 
 #[tracing::instrument(skip(ui, command))]
@@ -58,6 +101,9 @@ pub fn cmd_git_fetch(
     command: &CommandHelper,
     args: &GitFetchArgs,
 ) -> Result<(), CommandError> {
+    if args.background {
+        return spawn_background_fetch(ui, command);
+    }
     let mut workspace_command = command.workspace_helper(ui)?;
     let git_repo = get_git_repo(workspace_command.repo().store())?;
     let remotes = if args.all_remotes {
@@ -67,14 +113,32 @@ pub fn cmd_git_fetch(
     } else {
         args.remotes.clone()
     };
+    let options = GitFetchOptions {
+        depth: args.depth,
+        shallow_since: args.since.clone(),
+        unshallow: args.unshallow,
+        tags: if args.tags {
+            GitFetchTagPolicy::All
+        } else if args.no_tags {
+            GitFetchTagPolicy::None
+        } else {
+            GitFetchTagPolicy::Auto
+        },
+    };
     let mut tx = workspace_command.start_transaction();
     for remote in &remotes {
+        let branches = if args.branch.is_empty() {
+            get_default_fetch_branches(&git_repo, remote)?
+        } else {
+            args.branch.clone()
+        };
         let stats = with_remote_git_callbacks(ui, None, |cb| {
             git::fetch(
                 tx.repo_mut(),
                 &git_repo,
                 remote,
-                &args.branch,
+                &branches,
+                &options,
                 cb,
                 &command.settings().git_settings(),
             )
@@ -104,12 +168,37 @@ pub fn cmd_git_fetch(
         ui,
         format!("fetch from git remote(s) {}", remotes.iter().join(",")),
     )?;
+    record_last_fetch_time(workspace_command.repo_path())?;
+    Ok(())
+}
+
+fn spawn_background_fetch(ui: &Ui, command: &CommandHelper) -> Result<(), CommandError> {
+    // Load the workspace to fail early if there's no repo.
+    let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    get_git_repo(workspace_command.repo().store())?;
+    let args = command
+        .string_args()
+        .iter()
+        .skip(1)
+        .filter(|arg| {
+            !matches!(
+                arg.as_str(),
+                "--background" | "--quiet" | "--ignore-working-copy"
+            )
+        })
+        .cloned()
+        .chain(["--quiet".to_owned(), "--ignore-working-copy".to_owned()])
+        .collect_vec();
+    spawn_detached_jj(command.cwd(), &args).map_err(|err| {
+        user_error_with_message("Failed to start fetching in the background", err)
+    })?;
+    writeln!(ui.status(), "Fetching in the background")?;
     Ok(())
 }
 
 const DEFAULT_REMOTE: &str = "origin";
 
-fn get_default_fetch_remotes(
+pub fn get_default_fetch_remotes(
     ui: &Ui,
     settings: &UserSettings,
     git_repo: &git2::Repository,
@@ -133,6 +222,63 @@ fn get_default_fetch_remotes(
     }
 }
 
+/// Returns the branches to fetch from the remote by default, as specified by
+/// the fetch refspecs of the remote in the Git config.
+fn get_default_fetch_branches(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+) -> Result<Vec<StringPattern>, CommandError> {
+    let Ok(remote) = git_repo.find_remote(remote_name) else {
+        // Missing remote will be reported by git::fetch()
+        return Ok(vec![StringPattern::everything()]);
+    };
+    let refspecs = remote.fetch_refspecs()?;
+    if refspecs.is_empty() {
+        return Ok(vec![StringPattern::everything()]);
+    }
+    refspecs
+        .iter()
+        .map(|refspec| {
+            refspec
+                .and_then(|refspec| parse_fetch_refspec(refspec, remote_name))
+                .ok_or_else(|| {
+                    user_error_with_hint(
+                        format!(
+                            "Unsupported fetch refspec for remote {remote_name}: {}",
+                            refspec.unwrap_or("<non-utf8>")
+                        ),
+                        format!(
+                            "Only refspecs of the form \
+                             `+refs/heads/<branch>:refs/remotes/{remote_name}/<branch>` are \
+                             supported"
+                        ),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Parses a refspec like `+refs/heads/*:refs/remotes/origin/*` into a branch
+/// pattern. Returns `None` if the refspec doesn't map remote branches to the
+/// remote-tracking branches of the same names.
+fn parse_fetch_refspec(refspec: &str, remote_name: &str) -> Option<StringPattern> {
+    let refspec = refspec.strip_prefix('+').unwrap_or(refspec);
+    let (src, dst) = refspec.split_once(':')?;
+    let branch = src.strip_prefix("refs/heads/")?;
+    let dst_branch = dst
+        .strip_prefix("refs/remotes/")?
+        .strip_prefix(remote_name)?
+        .strip_prefix('/')?;
+    if branch != dst_branch {
+        return None;
+    }
+    if branch.contains('*') {
+        StringPattern::glob(branch).ok()
+    } else {
+        Some(StringPattern::exact(branch))
+    }
+}
+
 fn get_all_remotes(git_repo: &git2::Repository) -> Result<Vec<String>, CommandError> {
     let git_remotes = git_repo.remotes()?;
     Ok(git_remotes
@@ -12,17 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod apply;
+pub mod archive;
 pub mod clone;
 pub mod export;
 pub mod fetch;
 pub mod import;
 pub mod init;
+pub mod notes;
 pub mod push;
 pub mod remote;
+pub mod replace;
 pub mod submodule;
+pub mod sync;
 
 use clap::Subcommand;
 
+use self::apply::cmd_git_apply;
+use self::apply::GitApplyArgs;
+use self::archive::cmd_git_archive;
+use self::archive::GitArchiveArgs;
 use self::clone::cmd_git_clone;
 use self::clone::GitCloneArgs;
 use self::export::cmd_git_export;
@@ -33,12 +42,18 @@ use self::import::cmd_git_import;
 use self::import::GitImportArgs;
 use self::init::cmd_git_init;
 use self::init::GitInitArgs;
+use self::notes::cmd_git_notes;
+use self::notes::NotesCommand;
 use self::push::cmd_git_push;
 use self::push::GitPushArgs;
 use self::remote::cmd_git_remote;
 use self::remote::RemoteCommand;
+use self::replace::cmd_git_replace;
+use self::replace::ReplaceCommand;
 use self::submodule::cmd_git_submodule;
 use self::submodule::GitSubmoduleCommand;
+use self::sync::cmd_git_sync;
+use self::sync::GitSyncArgs;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::user_error;
@@ -53,16 +68,23 @@ use crate::ui::Ui;
 /// https://martinvonz.github.io/jj/latest/git-comparison/.
 #[derive(Subcommand, Clone, Debug)]
 pub enum GitCommand {
+    Apply(GitApplyArgs),
+    Archive(GitArchiveArgs),
     Clone(GitCloneArgs),
     Export(GitExportArgs),
     Fetch(GitFetchArgs),
     Import(GitImportArgs),
     Init(GitInitArgs),
+    #[command(subcommand)]
+    Notes(NotesCommand),
     Push(GitPushArgs),
     #[command(subcommand)]
     Remote(RemoteCommand),
-    #[command(subcommand, hide = true)]
+    #[command(subcommand)]
+    Replace(ReplaceCommand),
+    #[command(subcommand)]
     Submodule(GitSubmoduleCommand),
+    Sync(GitSyncArgs),
 }
 
 pub fn cmd_git(
@@ -71,18 +93,23 @@ pub fn cmd_git(
     subcommand: &GitCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        GitCommand::Apply(args) => cmd_git_apply(ui, command, args),
+        GitCommand::Archive(args) => cmd_git_archive(ui, command, args),
         GitCommand::Clone(args) => cmd_git_clone(ui, command, args),
         GitCommand::Export(args) => cmd_git_export(ui, command, args),
         GitCommand::Fetch(args) => cmd_git_fetch(ui, command, args),
         GitCommand::Import(args) => cmd_git_import(ui, command, args),
         GitCommand::Init(args) => cmd_git_init(ui, command, args),
+        GitCommand::Notes(args) => cmd_git_notes(ui, command, args),
         GitCommand::Push(args) => cmd_git_push(ui, command, args),
         GitCommand::Remote(args) => cmd_git_remote(ui, command, args),
+        GitCommand::Replace(args) => cmd_git_replace(ui, command, args),
         GitCommand::Submodule(args) => cmd_git_submodule(ui, command, args),
+        GitCommand::Sync(args) => cmd_git_sync(ui, command, args),
     }
 }
 
-fn map_git_error(err: git2::Error) -> CommandError {
+pub fn map_git_error(err: git2::Error) -> CommandError {
     if err.class() == git2::ErrorClass::Ssh {
         let hint =
             if err.code() == git2::ErrorCode::Certificate && std::env::var_os("HOME").is_none() {
@@ -95,6 +122,20 @@ fn map_git_error(err: git2::Error) -> CommandError {
             };
 
         user_error_with_hint(err, hint)
+    } else if err.code() == git2::ErrorCode::Auth {
+        user_error_with_hint(
+            err.to_string(),
+            "For HTTPS remotes, Jujutsu asks the Git credential helpers configured by \
+             `credential.helper` in the Git config, then prompts for a username and password. \
+             Check that the stored credentials or access token are valid for the remote.",
+        )
+    } else if err.class() == git2::ErrorClass::Http && err.code() == git2::ErrorCode::Certificate {
+        user_error_with_hint(
+            err.to_string(),
+            "The server certificate could not be verified. If the server uses a custom \
+             certificate authority, set the `SSL_CERT_FILE` environment variable to its \
+             certificate bundle.",
+        )
     } else {
         user_error(err.to_string())
     }
@@ -122,3 +163,49 @@ fn get_single_remote(git_repo: &git2::Repository) -> Result<Option<String>, Comm
         _ => None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_error::ErrorHint;
+
+    fn plain_text_hints(err: &CommandError) -> Vec<&str> {
+        err.hints
+            .iter()
+            .map(|hint| match hint {
+                ErrorHint::PlainText(text) => text.as_str(),
+                ErrorHint::Formatted(_) => panic!("unexpected formatted hint"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_map_git_error_hints() {
+        let err = map_git_error(git2::Error::new(
+            git2::ErrorCode::Auth,
+            git2::ErrorClass::Http,
+            "authentication failed",
+        ));
+        assert!(err.error.to_string().starts_with("authentication failed"));
+        let hints = plain_text_hints(&err);
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("`credential.helper`"), "{hints:?}");
+
+        let err = map_git_error(git2::Error::new(
+            git2::ErrorCode::Certificate,
+            git2::ErrorClass::Http,
+            "invalid certificate",
+        ));
+        let hints = plain_text_hints(&err);
+        assert_eq!(hints.len(), 1);
+        assert!(hints[0].contains("`SSL_CERT_FILE`"), "{hints:?}");
+
+        // Other errors don't get a hint
+        let err = map_git_error(git2::Error::new(
+            git2::ErrorCode::GenericError,
+            git2::ErrorClass::Http,
+            "request failed",
+        ));
+        assert!(err.hints.is_empty());
+    }
+}
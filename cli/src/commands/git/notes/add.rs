@@ -0,0 +1,75 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::git;
+use jj_lib::git::GitNotesError;
+use jj_lib::repo::Repo;
+
+use super::resolve_notes_ref;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::description_util::join_message_paragraphs;
+use crate::git_util::get_git_repo;
+use crate::ui::Ui;
+
+/// Attach a note to a revision
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitNotesAddArgs {
+    /// The revision to attach the note to
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+    /// The note's message
+    #[arg(long = "message", short, value_name = "MESSAGE", required = true)]
+    message_paragraphs: Vec<String>,
+    /// Replace the existing note of the revision, if any
+    #[arg(long, short)]
+    force: bool,
+    /// The notes ref to write to
+    #[arg(long = "ref", value_name = "REF")]
+    notes_ref: Option<String>,
+}
+
+pub fn cmd_git_notes_add(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitNotesAddArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let git_repo = get_git_repo(repo.store())?;
+    let notes_ref = resolve_notes_ref(&git_repo, args.notes_ref.as_deref())?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    if commit.id() == repo.store().root_commit_id() {
+        return Err(user_error("Cannot attach a note to the root commit"));
+    }
+    let message = join_message_paragraphs(&args.message_paragraphs);
+    git::set_note(
+        &git_repo,
+        &notes_ref,
+        commit.id(),
+        &message,
+        &command.settings().signature(),
+        args.force,
+    )
+    .map_err(|err| match err {
+        GitNotesError::NoteExists(_) => {
+            user_error_with_hint(err, "Use --force to replace the existing note")
+        }
+        _ => user_error(err),
+    })?;
+    Ok(())
+}
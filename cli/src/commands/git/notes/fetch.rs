@@ -0,0 +1,85 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::git;
+use jj_lib::git::GitNotesError;
+use jj_lib::repo::Repo;
+
+use super::resolve_notes_ref;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::commands::git::fetch::get_default_fetch_remotes;
+use crate::commands::git::map_git_error;
+use crate::git_util::get_git_repo;
+use crate::git_util::with_remote_git_callbacks;
+use crate::ui::Ui;
+
+/// Fetch notes from a Git remote
+///
+/// The local notes ref is fast-forwarded to the remote one. Notes can't be
+/// merged yet, so the fetch fails if both the local and the remote notes have
+/// been updated.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitNotesFetchArgs {
+    /// The remote to fetch from (only named remotes are supported, can be
+    /// repeated)
+    ///
+    /// Defaults to the `git.fetch` setting, like `jj git fetch`.
+    #[arg(long = "remote", value_name = "remote")]
+    remotes: Vec<String>,
+    /// The notes ref to fetch
+    #[arg(long = "ref", value_name = "REF")]
+    notes_ref: Option<String>,
+}
+
+pub fn cmd_git_notes_fetch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitNotesFetchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+    let notes_ref = resolve_notes_ref(&git_repo, args.notes_ref.as_deref())?;
+    let remotes = if args.remotes.is_empty() {
+        get_default_fetch_remotes(ui, command.settings(), &git_repo)?
+    } else {
+        args.remotes.clone()
+    };
+    for remote in &remotes {
+        let found = with_remote_git_callbacks(ui, None, |cb| {
+            git::fetch_notes(
+                &git_repo,
+                remote,
+                &notes_ref,
+                cb,
+                &command.settings().git_settings(),
+            )
+        })
+        .map_err(|err| match err {
+            GitNotesError::Diverged { .. } => user_error_with_hint(
+                err,
+                "Merge the notes with `git notes merge`, or remove the local notes ref to use the \
+                 remote's",
+            ),
+            GitNotesError::InternalGitError(err) => map_git_error(err),
+            _ => user_error(err),
+        })?;
+        if !found {
+            writeln!(ui.warning_default(), "Remote {remote} has no {notes_ref}")?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod add;
+pub mod fetch;
+pub mod push;
+pub mod remove;
+pub mod show;
+
+use clap::Subcommand;
+
+use self::add::cmd_git_notes_add;
+use self::add::GitNotesAddArgs;
+use self::fetch::cmd_git_notes_fetch;
+use self::fetch::GitNotesFetchArgs;
+use self::push::cmd_git_notes_push;
+use self::push::GitNotesPushArgs;
+use self::remove::cmd_git_notes_remove;
+use self::remove::GitNotesRemoveArgs;
+use self::show::cmd_git_notes_show;
+use self::show::GitNotesShowArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage Git notes
+///
+/// Notes are stored in the underlying Git repo, like `git notes`. They are
+/// attached to a commit id, so a note doesn't follow its change when the
+/// change is rewritten.
+///
+/// The notes ref defaults to `core.notesRef` in the Git config, or
+/// `refs/notes/commits`.
+#[derive(Subcommand, Clone, Debug)]
+pub enum NotesCommand {
+    Add(GitNotesAddArgs),
+    Fetch(GitNotesFetchArgs),
+    Push(GitNotesPushArgs),
+    Remove(GitNotesRemoveArgs),
+    Show(GitNotesShowArgs),
+}
+
+pub fn cmd_git_notes(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &NotesCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        NotesCommand::Add(args) => cmd_git_notes_add(ui, command, args),
+        NotesCommand::Fetch(args) => cmd_git_notes_fetch(ui, command, args),
+        NotesCommand::Push(args) => cmd_git_notes_push(ui, command, args),
+        NotesCommand::Remove(args) => cmd_git_notes_remove(ui, command, args),
+        NotesCommand::Show(args) => cmd_git_notes_show(ui, command, args),
+    }
+}
+
+/// Resolves the `--ref` argument to a full notes ref name. As in Git, a name
+/// without the `refs/notes/` prefix is looked up in that namespace.
+fn resolve_notes_ref(
+    git_repo: &git2::Repository,
+    notes_ref: Option<&str>,
+) -> Result<String, CommandError> {
+    let notes_ref = match notes_ref {
+        None => return Ok(jj_lib::git::default_notes_ref(git_repo)?),
+        Some(name) if name.starts_with("refs/notes/") => name.to_owned(),
+        Some(name) if name.starts_with("notes/") => format!("refs/{name}"),
+        Some(name) => format!("refs/notes/{name}"),
+    };
+    if !git2::Reference::is_valid_name(&notes_ref) {
+        return Err(user_error(format!("Invalid notes ref: {notes_ref}")));
+    }
+    Ok(notes_ref)
+}
@@ -0,0 +1,79 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::git;
+use jj_lib::git::GitNotesError;
+use jj_lib::repo::Repo;
+
+use super::resolve_notes_ref;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::commands::git::map_git_error;
+use crate::commands::git::push::get_default_push_remote;
+use crate::git_util::get_git_repo;
+use crate::git_util::with_remote_git_callbacks;
+use crate::ui::Ui;
+
+/// Push notes to a Git remote
+///
+/// The push is rejected by the remote unless it's a fast-forward of the
+/// remote notes ref.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitNotesPushArgs {
+    /// The remote to push to (only named remotes are supported)
+    ///
+    /// Defaults to the `git.push` setting, like `jj git push`.
+    #[arg(long)]
+    remote: Option<String>,
+    /// The notes ref to push
+    #[arg(long = "ref", value_name = "REF")]
+    notes_ref: Option<String>,
+}
+
+pub fn cmd_git_notes_push(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitNotesPushArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+    let notes_ref = resolve_notes_ref(&git_repo, args.notes_ref.as_deref())?;
+    if git_repo.find_reference(&notes_ref).is_err() {
+        return Err(user_error(format!("There are no notes in {notes_ref}")));
+    }
+    let remote = match &args.remote {
+        Some(remote) => remote.clone(),
+        None => get_default_push_remote(ui, command.settings(), &git_repo)?,
+    };
+    with_remote_git_callbacks(ui, None, |cb| {
+        git::push_notes(
+            &git_repo,
+            &remote,
+            &notes_ref,
+            cb,
+            &command.settings().git_settings(),
+        )
+    })
+    .map_err(|err| match err {
+        GitNotesError::RefUpdateRejected(..) => user_error_with_hint(
+            err,
+            "Try fetching the notes from the remote with `jj git notes fetch` first.",
+        ),
+        GitNotesError::InternalGitError(err) => map_git_error(err),
+        _ => user_error(err),
+    })?;
+    Ok(())
+}
@@ -0,0 +1,61 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::git;
+use jj_lib::repo::Repo;
+
+use super::resolve_notes_ref;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::git_util::get_git_repo;
+use crate::ui::Ui;
+
+/// Remove the note of a revision
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitNotesRemoveArgs {
+    /// The revision whose note to remove
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+    /// The notes ref to remove the note from
+    #[arg(long = "ref", value_name = "REF")]
+    notes_ref: Option<String>,
+}
+
+pub fn cmd_git_notes_remove(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitNotesRemoveArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let git_repo = get_git_repo(repo.store())?;
+    let notes_ref = resolve_notes_ref(&git_repo, args.notes_ref.as_deref())?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let removed = git::remove_note(
+        &git_repo,
+        &notes_ref,
+        commit.id(),
+        &command.settings().signature(),
+    )?;
+    if !removed {
+        writeln!(
+            ui.warning_default(),
+            "Revision {} has no note in {notes_ref}",
+            short_commit_hash(commit.id())
+        )?;
+    }
+    Ok(())
+}
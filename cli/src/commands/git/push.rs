@@ -20,9 +20,12 @@ use std::io::Write;
 use clap::ArgGroup;
 use itertools::Itertools;
 use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
 use jj_lib::git;
 use jj_lib::git::GitBranchPushTargets;
 use jj_lib::git::GitPushError;
+use jj_lib::git::GitPushPreview;
+use jj_lib::git::GitPushSigning;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::RefTarget;
 use jj_lib::refs::classify_bookmark_push_action;
@@ -32,6 +35,7 @@ use jj_lib::refs::LocalAndRemoteRef;
 use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::settings::ConfigResultExt as _;
+use jj_lib::settings::HumanByteSize;
 use jj_lib::settings::UserSettings;
 use jj_lib::str_util::StringPattern;
 use jj_lib::view::View;
@@ -117,8 +121,32 @@ pub struct GitPushArgs {
     #[arg(long, short)]
     change: Vec<RevisionArg>,
     /// Only display what will change on the remote
+    ///
+    /// The remote is contacted to find out which commits it's missing, and
+    /// whether any bookmarks unexpectedly moved there, but nothing is sent.
     #[arg(long)]
     dry_run: bool,
+    /// Push even if the bookmarks unexpectedly moved on the remote
+    ///
+    /// This skips the check that the bookmarks on the remote are where jj last
+    /// saw them, which may overwrite commits pushed by someone else.
+    #[arg(long)]
+    force: bool,
+    /// Transmit the given string to the server as a Git push option (can be
+    /// repeated)
+    ///
+    /// The meaning of the options depends on the server. For example, GitLab
+    /// accepts `-o ci.skip` and `-o merge_request.create`.
+    #[arg(long = "push-option", short = 'o', value_name = "OPTION")]
+    push_options: Vec<String>,
+    /// Send a push certificate signed with the configured signing backend
+    ///
+    /// This is needed by servers that require signed pushes. The certificate is
+    /// signed with the `signing.backend` and `signing.key` used for signing
+    /// commits. Only the `gpg` and `ssh` backends are supported. Can be enabled
+    /// by default with the `git.sign-on-push` config.
+    #[arg(long)]
+    signed: bool,
 }
 
 fn make_bookmark_term(bookmark_names: &[impl fmt::Display]) -> String {
@@ -269,22 +297,83 @@ pub fn cmd_git_push(
         print_commits_ready_to_push(formatter.as_mut(), repo.as_ref(), &bookmark_updates)?;
     }
 
+    let targets = GitBranchPushTargets {
+        branch_updates: bookmark_updates,
+        force: args.force,
+    };
+    let signing = if args.signed
+        || command
+            .settings()
+            .config()
+            .get_bool("git.sign-on-push")
+            .optional()?
+            .unwrap_or(false)
+    {
+        let signing = GitPushSigning::from_settings(command.settings()).ok_or_else(|| {
+            user_error_with_hint(
+                "Cannot sign the push because no signing backend is configured",
+                "Set `signing.backend` to `gpg` or `ssh`, and `signing.key` to the key to sign \
+                 with.",
+            )
+        })?;
+        Some(signing)
+    } else {
+        None
+    };
     if args.dry_run {
+        let preview = with_remote_git_callbacks(ui, None, |cb| {
+            git::preview_push_branches(
+                tx.repo(),
+                &git_repo,
+                &remote,
+                &targets,
+                cb,
+                &command.settings().git_settings(),
+            )
+        })
+        .map_err(map_push_error)?;
+        print_push_preview(ui, &tx, &preview)?;
         writeln!(ui.status(), "Dry-run requested, not pushing.")?;
         return Ok(());
     }
 
-    let targets = GitBranchPushTargets {
-        branch_updates: bookmark_updates,
-    };
+    let push_options = args.push_options.iter().map(String::as_str).collect_vec();
+    if let Some(signing) = &signing {
+        git::push_branches_signed(
+            tx.repo_mut(),
+            &git_repo,
+            &remote,
+            &targets,
+            &push_options,
+            signing,
+        )
+        .map_err(map_push_error)?;
+        tx.finish(ui, tx_description)?;
+        return Ok(());
+    }
     let mut writer = GitSidebandProgressMessageWriter::new(ui);
     let mut sideband_progress_callback = |progress_message: &[u8]| {
         _ = writer.write(ui, progress_message);
     };
     with_remote_git_callbacks(ui, Some(&mut sideband_progress_callback), |cb| {
-        git::push_branches(tx.repo_mut(), &git_repo, &remote, &targets, cb)
+        git::push_branches(
+            tx.repo_mut(),
+            &git_repo,
+            &remote,
+            &targets,
+            &push_options,
+            cb,
+            &command.settings().git_settings(),
+        )
     })
-    .map_err(|err| match err {
+    .map_err(map_push_error)?;
+    writer.flush(ui)?;
+    tx.finish(ui, tx_description)?;
+    Ok(())
+}
+
+pub fn map_push_error(err: GitPushError) -> CommandError {
+    match err {
         GitPushError::InternalGitError(err) => map_git_error(err),
         GitPushError::RefInUnexpectedLocation(refs) => user_error_with_hint(
             format!(
@@ -295,10 +384,57 @@ pub fn cmd_git_push(
             "Try fetching from the remote, then make the bookmark point to where you want it to \
              be, and push again.",
         ),
+        GitPushError::RefUpdateRejected(ref rejections) => {
+            let hints = rejections
+                .iter()
+                .map(|(name, message)| match message {
+                    Some(message) => format!("The remote rejected {name}: {message}"),
+                    None => format!("The remote rejected {name}"),
+                })
+                .collect_vec();
+            let mut cmd_err = user_error(err);
+            for hint in hints {
+                cmd_err.add_hint(hint);
+            }
+            cmd_err
+        }
         _ => user_error(err),
-    })?;
-    writer.flush(ui)?;
-    tx.finish(ui, tx_description)?;
+    }
+}
+
+/// Prints the commits that would be sent by the push, and warns about
+/// bookmarks that the remote would reject.
+fn print_push_preview(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    preview: &GitPushPreview,
+) -> Result<(), CommandError> {
+    let workspace_helper = tx.base_workspace_helper();
+    if let Some(mut formatter) = ui.status_formatter() {
+        if !preview.commits.is_empty() {
+            writeln!(formatter, "Commits to send:")?;
+            for commit_id in &preview.commits {
+                let commit = workspace_helper.repo().store().get_commit(commit_id)?;
+                write!(formatter, "  ")?;
+                workspace_helper.write_commit_summary(formatter.as_mut(), &commit)?;
+                writeln!(formatter)?;
+            }
+            writeln!(
+                formatter,
+                "Would send {} commits ({} objects, {})",
+                preview.commits.len(),
+                preview.num_objects,
+                HumanByteSize(preview.pack_size)
+            )?;
+        }
+    }
+    for ref_name in &preview.unexpectedly_moved_refs {
+        let bookmark_name = ref_name.strip_prefix("refs/heads/").unwrap_or(ref_name);
+        writeln!(
+            ui.warning_default(),
+            "Bookmark {bookmark_name} unexpectedly moved on the remote. Pushing it would fail."
+        )?;
+    }
     Ok(())
 }
 
@@ -438,7 +574,7 @@ fn print_commits_ready_to_push(
     Ok(())
 }
 
-fn get_default_push_remote(
+pub fn get_default_push_remote(
     ui: &Ui,
     settings: &UserSettings,
     git_repo: &git2::Repository,
@@ -460,7 +596,7 @@ fn get_default_push_remote(
 }
 
 #[derive(Clone, Debug)]
-struct RejectedBookmarkUpdateReason {
+pub struct RejectedBookmarkUpdateReason {
     message: String,
     hint: Option<String>,
 }
@@ -484,7 +620,7 @@ impl From<RejectedBookmarkUpdateReason> for CommandError {
     }
 }
 
-fn classify_bookmark_update(
+pub fn classify_bookmark_update(
     bookmark_name: &str,
     remote_name: &str,
     targets: LocalAndRemoteRef,
@@ -527,11 +663,20 @@ fn update_change_bookmarks(
         return Ok(vec![]);
     }
 
-    let mut bookmark_names = Vec::new();
     let workspace_command = tx.base_workspace_helper();
     let all_commits = workspace_command.resolve_some_revsets_default_single(ui, changes)?;
+    set_change_bookmarks(ui, tx, &all_commits, bookmark_prefix)
+}
 
-    for commit in all_commits {
+/// Creates or moves bookmarks named after the change IDs of the given commits.
+pub fn set_change_bookmarks<'a>(
+    ui: &Ui,
+    tx: &mut WorkspaceCommandTransaction,
+    commits: impl IntoIterator<Item = &'a Commit>,
+    bookmark_prefix: &str,
+) -> Result<Vec<String>, CommandError> {
+    let mut bookmark_names = Vec::new();
+    for commit in commits {
         let workspace_command = tx.base_workspace_helper();
         let short_change_id = short_change_hash(commit.change_id());
         let mut bookmark_name = format!("{bookmark_prefix}{}", commit.change_id().hex());
@@ -13,22 +13,34 @@
 // limitations under the License.
 
 use std::io::Write;
+use std::slice;
 
+use itertools::Itertools as _;
+use jj_lib::git;
+use jj_lib::op_store::RemoteRefState;
+use jj_lib::op_walk;
 use jj_lib::repo::Repo;
 
 use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
 use crate::command_error::CommandError;
 use crate::git_util::get_git_repo;
+use crate::time_util::format_absolute_timestamp;
 use crate::ui::Ui;
 
 /// List Git remotes
 #[derive(clap::Args, Clone, Debug)]
-pub struct GitRemoteListArgs {}
+pub struct GitRemoteListArgs {
+    /// Also show the push URLs, tracked bookmarks, and last fetch time of each
+    /// remote
+    #[arg(long, short)]
+    verbose: bool,
+}
 
 pub fn cmd_git_remote_list(
     ui: &mut Ui,
     command: &CommandHelper,
-    _args: &GitRemoteListArgs,
+    args: &GitRemoteListArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo();
@@ -41,6 +53,39 @@ pub fn cmd_git_remote_list(
             remote_name,
             remote.url().unwrap_or("<no URL>")
         )?;
+        if !args.verbose {
+            continue;
+        }
+        for push_url in git::get_remote_push_urls(&git_repo, remote_name)? {
+            writeln!(ui.stdout(), "  Push URL: {push_url}")?;
+        }
+        let tracked_bookmarks = repo
+            .view()
+            .remote_bookmarks(remote_name)
+            .filter(|(_, remote_ref)| remote_ref.state == RemoteRefState::Tracking)
+            .map(|(name, _)| name)
+            .collect_vec();
+        if !tracked_bookmarks.is_empty() {
+            writeln!(
+                ui.stdout(),
+                "  Tracked bookmarks: {}",
+                tracked_bookmarks.join(", ")
+            )?;
+        }
+        // The op log is the only record of fetches.
+        for op in op_walk::walk_ancestors(slice::from_ref(repo.operation())) {
+            let op = op?;
+            let fetched_remotes = op
+                .metadata()
+                .description
+                .strip_prefix("fetch from git remote(s) ");
+            if fetched_remotes.is_some_and(|remotes| remotes.split(',').contains(&remote_name)) {
+                let time =
+                    format_absolute_timestamp(&op.metadata().end_time).map_err(internal_error)?;
+                writeln!(ui.stdout(), "  Last fetched: {time}")?;
+                break;
+            }
+        }
     }
     Ok(())
 }
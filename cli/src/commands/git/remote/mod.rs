@@ -14,6 +14,7 @@
 
 pub mod add;
 pub mod list;
+pub mod prune;
 pub mod remove;
 pub mod rename;
 pub mod set_url;
@@ -24,6 +25,8 @@ use self::add::cmd_git_remote_add;
 use self::add::GitRemoteAddArgs;
 use self::list::cmd_git_remote_list;
 use self::list::GitRemoteListArgs;
+use self::prune::cmd_git_remote_prune;
+use self::prune::GitRemotePruneArgs;
 use self::remove::cmd_git_remote_remove;
 use self::remove::GitRemoteRemoveArgs;
 use self::rename::cmd_git_remote_rename;
@@ -41,6 +44,7 @@ use crate::ui::Ui;
 pub enum RemoteCommand {
     Add(GitRemoteAddArgs),
     List(GitRemoteListArgs),
+    Prune(GitRemotePruneArgs),
     Remove(GitRemoteRemoveArgs),
     Rename(GitRemoteRenameArgs),
     SetUrl(GitRemoteSetUrlArgs),
@@ -54,6 +58,7 @@ pub fn cmd_git_remote(
     match subcommand {
         RemoteCommand::Add(args) => cmd_git_remote_add(ui, command, args),
         RemoteCommand::List(args) => cmd_git_remote_list(ui, command, args),
+        RemoteCommand::Prune(args) => cmd_git_remote_prune(ui, command, args),
         RemoteCommand::Remove(args) => cmd_git_remote_remove(ui, command, args),
         RemoteCommand::Rename(args) => cmd_git_remote_rename(ui, command, args),
         RemoteCommand::SetUrl(args) => cmd_git_remote_set_url(ui, command, args),
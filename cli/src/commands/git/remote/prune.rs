@@ -0,0 +1,64 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::git;
+use jj_lib::git::GitFetchError;
+use jj_lib::repo::Repo;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::commands::git::map_git_error;
+use crate::git_util::get_git_repo;
+use crate::git_util::print_git_import_stats;
+use crate::git_util::with_remote_git_callbacks;
+use crate::ui::Ui;
+
+/// Forget remote bookmarks that were deleted on the remote
+///
+/// The remote is contacted to list its branches, but nothing is fetched.
+/// Tracked local bookmarks are deleted along with their remote bookmarks, as
+/// by `jj git fetch`.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitRemotePruneArgs {
+    /// The remote's name
+    remote: String,
+}
+
+pub fn cmd_git_remote_prune(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitRemotePruneArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+    let mut tx = workspace_command.start_transaction();
+    let stats = with_remote_git_callbacks(ui, None, |cb| {
+        git::prune_remote(
+            tx.repo_mut(),
+            &git_repo,
+            &args.remote,
+            cb,
+            &command.settings().git_settings(),
+        )
+    })
+    .map_err(|err| match err {
+        GitFetchError::GitImportError(err) => err.into(),
+        GitFetchError::InternalGitError(err) => map_git_error(err),
+        _ => user_error(err),
+    })?;
+    print_git_import_stats(ui, tx.repo(), &stats, true)?;
+    tx.finish(ui, format!("prune git remote {}", &args.remote))?;
+    Ok(())
+}
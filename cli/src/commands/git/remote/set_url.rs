@@ -27,6 +27,14 @@ pub struct GitRemoteSetUrlArgs {
     remote: String,
     /// The desired url for `remote`
     url: String,
+    /// Set the URL used for pushing instead of the fetch URL
+    #[arg(long)]
+    push: bool,
+    /// Add the push URL to the existing ones instead of replacing them
+    ///
+    /// `jj git push` pushes to each of the push URLs.
+    #[arg(long, requires = "push")]
+    add: bool,
 }
 
 pub fn cmd_git_remote_set_url(
@@ -37,6 +45,10 @@ pub fn cmd_git_remote_set_url(
     let workspace_command = command.workspace_helper(ui)?;
     let repo = workspace_command.repo();
     let git_repo = get_git_repo(repo.store())?;
-    git::set_remote_url(&git_repo, &args.remote, &args.url)?;
+    if args.push {
+        git::set_remote_push_url(&git_repo, &args.remote, &args.url, args.add)?;
+    } else {
+        git::set_remote_url(&git_repo, &args.remote, &args.url)?;
+    }
     Ok(())
 }
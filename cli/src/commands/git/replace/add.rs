@@ -0,0 +1,61 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::git;
+use jj_lib::repo::Repo;
+
+use super::get_git_backend;
+use super::map_replace_error;
+use super::rebuild_index;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Replace a commit by another commit
+///
+/// After this, the history shows the contents and parents of the replacement
+/// in place of the original commit.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitReplaceAddArgs {
+    /// The commit to replace
+    original: RevisionArg,
+    /// The commit to replace it with
+    replacement: RevisionArg,
+    /// Overwrite the existing replacement of the commit, if any
+    #[arg(long, short)]
+    force: bool,
+}
+
+pub fn cmd_git_replace_add(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitReplaceAddArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let git_backend = get_git_backend(repo.store())?;
+    let original = workspace_command.resolve_single_rev(ui, &args.original)?;
+    let replacement = workspace_command.resolve_single_rev(ui, &args.replacement)?;
+    let root_commit_id = repo.store().root_commit_id();
+    if original.id() == root_commit_id || replacement.id() == root_commit_id {
+        return Err(user_error(
+            "Cannot replace the root commit or replace by it",
+        ));
+    }
+    git::set_replacement(git_backend, original.id(), replacement.id(), args.force)
+        .map_err(map_replace_error)?;
+    rebuild_index(ui, command)
+}
@@ -0,0 +1,77 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::git;
+use jj_lib::repo::Repo;
+
+use super::get_git_backend;
+use super::map_replace_error;
+use super::rebuild_index;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Replace the parents of a commit
+///
+/// This writes a copy of the commit with the given parents and makes it the
+/// replacement of the commit, like `git replace --graft`. Without parents, or
+/// with only the root commit as parent, the commit becomes the first commit of
+/// its history.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitReplaceGraftArgs {
+    /// The commit whose parents to replace
+    commit: RevisionArg,
+    /// The new parents of the commit
+    parents: Vec<RevisionArg>,
+    /// Overwrite the existing replacement of the commit, if any
+    #[arg(long, short)]
+    force: bool,
+}
+
+pub fn cmd_git_replace_graft(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitReplaceGraftArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let git_backend = get_git_backend(repo.store())?;
+    let root_commit_id = repo.store().root_commit_id();
+    let commit = workspace_command.resolve_single_rev(ui, &args.commit)?;
+    if commit.id() == root_commit_id {
+        return Err(user_error("Cannot replace the root commit"));
+    }
+    let parent_ids: Vec<_> = args
+        .parents
+        .iter()
+        .map(|revision| workspace_command.resolve_single_rev(ui, revision))
+        .map_ok(|parent| parent.id().clone())
+        .filter_ok(|id| id != root_commit_id)
+        .try_collect()?;
+    let replacement_id = git::set_graft(git_backend, commit.id(), &parent_ids, args.force)
+        .map_err(map_replace_error)?;
+    writeln!(
+        ui.status(),
+        "Replaced commit {} by {}",
+        short_commit_hash(commit.id()),
+        short_commit_hash(&replacement_id)
+    )?;
+    rebuild_index(ui, command)
+}
@@ -0,0 +1,54 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::git;
+use jj_lib::repo::Repo;
+
+use super::get_git_backend;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// List the replaced commits and their replacements
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitReplaceListArgs {}
+
+pub fn cmd_git_replace_list(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &GitReplaceListArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let git_backend = get_git_backend(repo.store())?;
+    let mut formatter = ui.stdout_formatter();
+    for (original, replacement) in git::get_replacements(git_backend) {
+        write!(
+            formatter.labeled("commit_id"),
+            "{}",
+            short_commit_hash(&original)
+        )?;
+        write!(formatter, " -> ")?;
+        write!(
+            formatter.labeled("commit_id"),
+            "{}",
+            short_commit_hash(&replacement)
+        )?;
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod add;
+pub mod graft;
+pub mod list;
+pub mod remove;
+
+use clap::Subcommand;
+use jj_lib::default_index::DefaultIndexStore;
+use jj_lib::git::GitReplaceError;
+use jj_lib::git_backend::GitBackend;
+use jj_lib::store::Store;
+
+use self::add::cmd_git_replace_add;
+use self::add::GitReplaceAddArgs;
+use self::graft::cmd_git_replace_graft;
+use self::graft::GitReplaceGraftArgs;
+use self::list::cmd_git_replace_list;
+use self::list::GitReplaceListArgs;
+use self::remove::cmd_git_replace_remove;
+use self::remove::GitReplaceRemoveArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage replacements of commits in the underlying Git repo
+///
+/// Replacements are stored as `refs/replace/*` refs, like `git replace`. When
+/// a commit is replaced, reading it returns the contents and parents of its
+/// replacement, while it keeps its commit id. This can be used to join
+/// truncated histories, or to hide part of the history.
+///
+/// Replacements made by `git replace`, or fetched from a remote, are used as
+/// well. The commit index is rebuilt when the replacements change.
+#[derive(Subcommand, Clone, Debug)]
+pub enum ReplaceCommand {
+    Add(GitReplaceAddArgs),
+    Graft(GitReplaceGraftArgs),
+    List(GitReplaceListArgs),
+    Remove(GitReplaceRemoveArgs),
+}
+
+pub fn cmd_git_replace(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &ReplaceCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        ReplaceCommand::Add(args) => cmd_git_replace_add(ui, command, args),
+        ReplaceCommand::Graft(args) => cmd_git_replace_graft(ui, command, args),
+        ReplaceCommand::List(args) => cmd_git_replace_list(ui, command, args),
+        ReplaceCommand::Remove(args) => cmd_git_replace_remove(ui, command, args),
+    }
+}
+
+fn map_replace_error(err: GitReplaceError) -> CommandError {
+    match err {
+        GitReplaceError::AlreadyReplaced(_) => {
+            user_error_with_hint(err, "Use --force to overwrite the existing replacement")
+        }
+        GitReplaceError::ReplaceWithSelf(_) => user_error(err),
+        GitReplaceError::InternalGitError(err) => err.into(),
+    }
+}
+
+fn get_git_backend(store: &Store) -> Result<&GitBackend, CommandError> {
+    store
+        .backend_impl()
+        .downcast_ref::<GitBackend>()
+        .ok_or_else(|| user_error("The repo is not backed by a git repo"))
+}
+
+/// Rebuilds the commit index, which caches the parents of the commits and so
+/// doesn't know about the replacements yet.
+fn rebuild_index(ui: &mut Ui, command: &CommandHelper) -> Result<(), CommandError> {
+    // Load the workspace again so the backend sees the new replacements.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = repo_loader.index_store();
+    let Some(default_index_store) = index_store.as_any().downcast_ref::<DefaultIndexStore>() else {
+        writeln!(
+            ui.warning_default(),
+            "Cannot rebuild indexes of type '{}'. Run `jj debug reindex` if the history looks \
+             stale.",
+            index_store.name()
+        )?;
+        return Ok(());
+    };
+    default_index_store.reinit().map_err(internal_error)?;
+    get_git_backend(repo_loader.store())?
+        .record_indexed_state()
+        .map_err(internal_error)?;
+    default_index_store
+        .build_index_at_operation(&op, repo_loader.store())
+        .map_err(internal_error)?;
+    Ok(())
+}
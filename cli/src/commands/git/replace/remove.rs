@@ -0,0 +1,52 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::git;
+use jj_lib::repo::Repo;
+
+use super::get_git_backend;
+use super::map_replace_error;
+use super::rebuild_index;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Remove the replacement of a commit
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitReplaceRemoveArgs {
+    /// The replaced commit
+    original: RevisionArg,
+}
+
+pub fn cmd_git_replace_remove(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitReplaceRemoveArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let git_backend = get_git_backend(repo.store())?;
+    let original = workspace_command.resolve_single_rev(ui, &args.original)?;
+    if !git::remove_replacement(git_backend, original.id()).map_err(map_replace_error)? {
+        writeln!(
+            ui.warning_default(),
+            "Commit {} is not replaced",
+            short_commit_hash(original.id())
+        )?;
+        return Ok(());
+    }
+    rebuild_index(ui, command)
+}
@@ -0,0 +1,71 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+
+use jj_lib::backend::TreeValue;
+use jj_lib::repo_path::RepoPathBuf;
+
+use super::read_gitmodules;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// List Git submodules
+///
+/// Each line shows the submodule commit recorded in the revision, the path of
+/// the submodule, and its URL from `.gitmodules`.
+#[derive(clap::Args, Clone, Debug)]
+pub struct SubmoduleListArgs {
+    /// List submodules in the given revision.
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+}
+
+pub fn cmd_submodule_list(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SubmoduleListArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    let tree = commit.tree()?;
+    let Some(submodules) = read_gitmodules(&tree)? else {
+        writeln!(ui.status(), "No submodules.")?;
+        return Ok(());
+    };
+    for submodule in submodules.values() {
+        let path = RepoPathBuf::from_relative_path(&submodule.path).map_err(|err| {
+            user_error_with_message(
+                format!("Invalid path for submodule {}", submodule.name),
+                err,
+            )
+        })?;
+        let commit_hash = match tree.path_value(&path)?.into_resolved() {
+            Ok(Some(TreeValue::GitSubmodule(id))) => short_commit_hash(&id),
+            Ok(_) => "(not recorded)".to_owned(),
+            Err(_) => "(conflicted)".to_owned(),
+        };
+        writeln!(
+            ui.stdout(),
+            "{commit_hash} {path} ({url})",
+            path = workspace_command.format_file_path(&path),
+            url = submodule.url,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+// Copyright 2020-2023 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod list;
+pub mod print_gitmodules;
+pub mod status;
+pub mod update;
+
+use std::collections::BTreeMap;
+use std::io::Write as _;
+
+use clap::Subcommand;
+use jj_lib::backend::CommitId;
+use jj_lib::backend::TreeValue;
+use jj_lib::git;
+use jj_lib::git::parse_gitmodules;
+use jj_lib::git::GitSubmoduleError;
+use jj_lib::git::SubmoduleConfig;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
+
+use self::list::cmd_submodule_list;
+use self::list::SubmoduleListArgs;
+use self::print_gitmodules::cmd_submodule_print;
+use self::print_gitmodules::PrintArgs;
+use self::status::cmd_submodule_status;
+use self::status::SubmoduleStatusArgs;
+use self::update::cmd_submodule_update;
+use self::update::SubmoduleUpdateArgs;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::commands::git::map_git_error;
+use crate::git_util::get_git_repo;
+use crate::git_util::with_remote_git_callbacks;
+use crate::ui::Ui;
+
+/// Interact with Git submodules
+///
+/// Submodules are read from the `.gitmodules` file and the commits recorded in
+/// the tree. Their working copies are checked out by `jj git submodule update`,
+/// and their Git repos are stored in the `modules` directory of the Git repo,
+/// like Git does. Changes in the working copies of the submodules aren't
+/// snapshotted; they are read-only as far as jj is concerned.
+#[derive(Subcommand, Clone, Debug)]
+pub enum GitSubmoduleCommand {
+    List(SubmoduleListArgs),
+    PrintGitmodules(PrintArgs),
+    Status(SubmoduleStatusArgs),
+    Update(SubmoduleUpdateArgs),
+}
+
+pub fn cmd_git_submodule(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &GitSubmoduleCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        GitSubmoduleCommand::List(args) => cmd_submodule_list(ui, command, args),
+        GitSubmoduleCommand::PrintGitmodules(args) => cmd_submodule_print(ui, command, args),
+        GitSubmoduleCommand::Status(args) => cmd_submodule_status(ui, command, args),
+        GitSubmoduleCommand::Update(args) => cmd_submodule_update(ui, command, args),
+    }
+}
+
+/// Reads and parses the `.gitmodules` file in the given tree. Returns `None` if
+/// the tree has no `.gitmodules` file.
+fn read_gitmodules(
+    tree: &MergedTree,
+) -> Result<Option<BTreeMap<String, SubmoduleConfig>>, CommandError> {
+    let gitmodules_path = RepoPath::from_internal_string(".gitmodules");
+    let mut gitmodules_file = match tree.path_value(gitmodules_path)?.into_resolved() {
+        Ok(None) => return Ok(None),
+        Ok(Some(TreeValue::File { id, .. })) => tree.store().read_file(gitmodules_path, &id)?,
+        _ => {
+            return Err(user_error(".gitmodules is not a file."));
+        }
+    };
+    Ok(Some(parse_gitmodules(&mut gitmodules_file)?))
+}
+
+/// Returns the path of the `submodule` in the repo.
+fn submodule_path(submodule: &SubmoduleConfig) -> Result<RepoPathBuf, CommandError> {
+    RepoPathBuf::from_relative_path(&submodule.path).map_err(|err| {
+        user_error_with_message(
+            format!("Invalid path for submodule {}", submodule.name),
+            err,
+        )
+    })
+}
+
+/// Returns the submodules of the working-copy commit with their paths and the
+/// commits recorded at them. Submodules without a recorded commit are skipped.
+fn working_copy_submodules(
+    workspace_command: &WorkspaceCommandHelper,
+) -> Result<Vec<(SubmoduleConfig, RepoPathBuf, CommitId)>, CommandError> {
+    let wc_commit_id = workspace_command
+        .get_wc_commit_id()
+        .ok_or_else(|| user_error("This command requires a working copy"))?;
+    let tree = workspace_command
+        .repo()
+        .store()
+        .get_commit(wc_commit_id)?
+        .tree()?;
+    let Some(submodules) = read_gitmodules(&tree)? else {
+        return Ok(vec![]);
+    };
+    let mut recorded = vec![];
+    for submodule in submodules.into_values() {
+        let path = submodule_path(&submodule)?;
+        if let Ok(Some(TreeValue::GitSubmodule(id))) = tree.path_value(&path)?.into_resolved() {
+            recorded.push((submodule, path, id));
+        }
+    }
+    Ok(recorded)
+}
+
+/// Checks out the submodules of the working-copy commit at the commits
+/// recorded in it.
+pub(crate) fn update_submodules(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+) -> Result<(), CommandError> {
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+    for (submodule, path, commit_id) in working_copy_submodules(workspace_command)? {
+        let workdir = path.to_fs_path(workspace_command.workspace_root());
+        with_remote_git_callbacks(ui, None, |cb| {
+            git::update_submodule(&git_repo, &submodule, &workdir, &commit_id, cb)
+        })
+        .map_err(|err| match err {
+            GitSubmoduleError::InternalGitError(err) => map_git_error(err),
+            GitSubmoduleError::Path(err) => err.into(),
+            _ => user_error(err),
+        })?;
+        writeln!(
+            ui.status(),
+            "Submodule {}: checked out {}",
+            workspace_command.format_file_path(&path),
+            short_commit_hash(&commit_id)
+        )?;
+    }
+    Ok(())
+}
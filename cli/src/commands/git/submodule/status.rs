@@ -0,0 +1,59 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write;
+
+use jj_lib::git;
+use jj_lib::repo::Repo;
+
+use super::working_copy_submodules;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::CommandError;
+use crate::git_util::get_git_repo;
+use crate::ui::Ui;
+
+/// Show the status of the submodule working copies
+///
+/// Like `git submodule status`, each line shows the commit checked out in the
+/// submodule and its path. The commit is prefixed with `-` if the submodule
+/// isn't checked out, and with `+` if it doesn't match the commit recorded in
+/// the working-copy commit.
+#[derive(clap::Args, Clone, Debug)]
+pub struct SubmoduleStatusArgs {}
+
+pub fn cmd_submodule_status(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &SubmoduleStatusArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+    for (submodule, path, commit_id) in working_copy_submodules(&workspace_command)? {
+        let head_id = git::get_submodule_head(&git_repo, &submodule).map_err(internal_error)?;
+        let (prefix, head_id) = match head_id {
+            None => ('-', commit_id),
+            Some(head_id) if head_id == commit_id => (' ', head_id),
+            Some(head_id) => ('+', head_id),
+        };
+        writeln!(
+            ui.stdout(),
+            "{prefix}{} {}",
+            short_commit_hash(&head_id),
+            workspace_command.format_file_path(&path)
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,35 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::update_submodules;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Check out the submodules at the commits recorded in the working-copy commit
+///
+/// Submodules that aren't checked out yet are cloned from the URL in
+/// `.gitmodules`. Changes in the working copies of the submodules are
+/// overwritten.
+#[derive(clap::Args, Clone, Debug)]
+pub struct SubmoduleUpdateArgs {}
+
+pub fn cmd_submodule_update(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &SubmoduleUpdateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    update_submodules(ui, &workspace_command)
+}
@@ -0,0 +1,180 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use jj_lib::git;
+use jj_lib::repo::Repo;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::handle_command_result;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::git_util::get_git_repo;
+use crate::git_util::print_failed_git_export;
+use crate::git_util::print_git_import_stats;
+use crate::ui::Ui;
+
+/// Import and export refs between the repo and the underlying Git repo
+///
+/// This is the same as running `jj git import` followed by `jj git export`,
+/// but in a single operation.
+///
+/// With `--watch`, the command keeps running and syncs the refs whenever they
+/// change on either side. This is useful in colocated repos, where commands
+/// like `git commit` or `git branch` would otherwise only be picked up by the
+/// next `jj` command, possibly after the refs have been modified again by
+/// another `jj` process. The working copy isn't snapshotted while watching,
+/// and errors are reported without stopping the watch.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitSyncArgs {
+    /// Keep running and sync refs whenever they change
+    ///
+    /// Only supported in colocated repos. Stop it with Ctrl-C.
+    #[arg(long)]
+    watch: bool,
+    /// How often to check for changed refs in `--watch` mode, in seconds
+    #[arg(long, default_value_t = 1, requires = "watch", value_name = "SECONDS")]
+    interval: u64,
+    /// Stop watching after syncing this many times (for testing)
+    #[arg(long, requires = "watch", hide = true)]
+    max_syncs: Option<usize>,
+}
+
+pub fn cmd_git_sync(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitSyncArgs,
+) -> Result<(), CommandError> {
+    if !args.watch {
+        return sync_refs(ui, command, false);
+    }
+
+    let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    if !workspace_command.working_copy_shared_with_git() {
+        return Err(user_error_with_hint(
+            "`--watch` is only supported in colocated repos",
+            "Run `jj git sync` without `--watch` to sync the refs once.",
+        ));
+    }
+    let git_dir = get_git_repo(workspace_command.repo().store())?
+        .path()
+        .to_owned();
+    let watched_paths = vec![
+        git_dir.join("HEAD"),
+        git_dir.join("packed-refs"),
+        git_dir.join("refs"),
+        workspace_command.repo_path().join("op_heads"),
+    ];
+    drop(workspace_command);
+
+    let interval = Duration::from_secs(args.interval);
+    let mut last_state = None;
+    let mut num_syncs = 0;
+    loop {
+        let state = watched_state(&watched_paths);
+        if last_state.as_ref() != Some(&state) {
+            if let Err(err) = sync_refs_without_snapshot(ui, command) {
+                // The next change may fix it, e.g. a ref that was locked by a
+                // concurrent Git command.
+                handle_command_result(ui, Err(err));
+            }
+            // Syncing may have updated the refs itself. Take the new state so
+            // that our own updates don't trigger another sync.
+            last_state = Some(watched_state(&watched_paths));
+            num_syncs += 1;
+            if args.max_syncs == Some(num_syncs) {
+                return Ok(());
+            }
+        }
+        thread::sleep(interval);
+    }
+}
+
+/// Imports and exports the refs in a single operation.
+///
+/// In colocated repos, the Git refs are imported when the workspace is loaded,
+/// so only the export needs to be done here.
+fn sync_refs(ui: &mut Ui, command: &CommandHelper, quiet: bool) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let colocated = workspace_command.working_copy_shared_with_git();
+    let mut tx = workspace_command.start_transaction();
+    if !colocated {
+        git::import_head(tx.repo_mut())?;
+        let stats = git::import_refs(tx.repo_mut(), &command.settings().git_settings())?;
+        print_git_import_stats(ui, tx.repo(), &stats, true)?;
+    }
+    let failed_refs = git::export_refs(tx.repo_mut())?;
+    if tx.repo().has_changes() || !quiet {
+        tx.finish(ui, "sync git refs")?;
+    }
+    print_failed_git_export(ui, &failed_refs)?;
+    Ok(())
+}
+
+/// Imports and exports the refs of a colocated repo without snapshotting the
+/// working copy, which may be in the middle of being edited.
+///
+/// The Git HEAD is left for the next command to import, since it may move the
+/// working copy. If importing the refs rewrites the working-copy commit, the
+/// working copy becomes stale.
+fn sync_refs_without_snapshot(ui: &mut Ui, command: &CommandHelper) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    workspace_command.ignore_working_copy();
+    let mut tx = workspace_command.start_transaction();
+    let stats = git::import_some_refs(
+        tx.repo_mut(),
+        &command.settings().git_settings(),
+        |ref_name| !git::is_reserved_git_remote_ref(ref_name),
+    )?;
+    print_git_import_stats(ui, tx.repo(), &stats, true)?;
+    let failed_refs = git::export_refs(tx.repo_mut())?;
+    if tx.repo().has_changes() {
+        tx.finish(ui, "sync git refs")?;
+    }
+    print_failed_git_export(ui, &failed_refs)?;
+    Ok(())
+}
+
+/// Returns the modification times and sizes of the files in `paths`,
+/// recursing into directories. Missing files are skipped.
+fn watched_state(paths: &[PathBuf]) -> BTreeMap<PathBuf, (SystemTime, u64)> {
+    fn visit(path: &Path, state: &mut BTreeMap<PathBuf, (SystemTime, u64)>) {
+        let Ok(metadata) = fs::symlink_metadata(path) else {
+            return;
+        };
+        if metadata.is_dir() {
+            let Ok(entries) = fs::read_dir(path) else {
+                return;
+            };
+            for entry in entries.flatten() {
+                visit(&entry.path(), state);
+            }
+        } else if let Ok(mtime) = metadata.modified() {
+            state.insert(path.to_owned(), (mtime, metadata.len()));
+        }
+    }
+
+    let mut state = BTreeMap::new();
+    for path in paths {
+        visit(path, &mut state);
+    }
+    state
+}
@@ -0,0 +1,319 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Read as _;
+use std::io::Write as _;
+use std::ops::Range;
+
+use itertools::Itertools as _;
+use jj_lib::backend::BackendError;
+use jj_lib::conflicts::materialize_merge_result;
+use jj_lib::conflicts::materialize_tree_value;
+use jj_lib::conflicts::MaterializedTreeValue;
+use jj_lib::merge::MergedTreeValue;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::store::Store;
+use pollster::FutureExt as _;
+use rayon::iter::IntoParallelIterator as _;
+use rayon::iter::ParallelIterator as _;
+use regex::bytes::Regex;
+use regex::bytes::RegexBuilder;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::formatter::Formatter;
+use crate::ui::Ui;
+
+/// Search for a pattern in the contents of files
+///
+/// Searches the files of each revision for lines matching a regular
+/// expression. The files are read from the repository, so the revisions don't
+/// need to be checked out. Matches are grouped by revision. Conflicted files
+/// are searched with their conflict markers materialized.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct GrepArgs {
+    /// The regular expression to search for
+    pattern: String,
+    /// Only search files matching these prefixes (instead of all files)
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+    /// The revisions to search
+    #[arg(long, short, default_value = "@")]
+    revisions: Vec<RevisionArg>,
+    /// Match case-insensitively
+    #[arg(long, short)]
+    ignore_case: bool,
+    /// Match case-insensitively if the pattern is all lowercase
+    #[arg(long, short = 'S', conflicts_with = "ignore_case")]
+    smart_case: bool,
+    /// Match the pattern as a literal string instead of a regular expression
+    #[arg(long, short = 'F')]
+    fixed_strings: bool,
+    /// Only print the names of the files that contain a match
+    #[arg(long, short = 'l')]
+    files_with_matches: bool,
+    /// Print this many lines of context after each match
+    #[arg(
+        long,
+        short = 'A',
+        value_name = "NUM",
+        conflicts_with = "files_with_matches"
+    )]
+    after_context: Option<usize>,
+    /// Print this many lines of context before each match
+    #[arg(
+        long,
+        short = 'B',
+        value_name = "NUM",
+        conflicts_with = "files_with_matches"
+    )]
+    before_context: Option<usize>,
+    /// Print this many lines of context before and after each match
+    #[arg(
+        long,
+        short = 'C',
+        value_name = "NUM",
+        conflicts_with = "files_with_matches"
+    )]
+    context: Option<usize>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_grep(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GrepArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let regex = build_regex(args)?;
+    let commits: Vec<_> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    let matcher = workspace_command
+        .parse_file_patterns(ui, &args.paths)?
+        .to_matcher();
+
+    let mut files = vec![];
+    for (commit_index, commit) in commits.iter().enumerate() {
+        for (path, value) in commit.tree()?.entries_matching(matcher.as_ref()) {
+            files.push((commit_index, path, value?));
+        }
+    }
+    // Reading the files dominates the cost of searching, so it's spread across
+    // threads. The order of the results is preserved.
+    let store = workspace_command.repo().store();
+    let results: Vec<_> = files
+        .into_par_iter()
+        .map(|(commit_index, path, value)| {
+            let file_matches = search_file(store, &path, value, &regex)?;
+            Ok(file_matches.map(|file_matches| (commit_index, path, file_matches)))
+        })
+        .collect::<Result<Vec<_>, CommandError>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let after_context = args.after_context.or(args.context).unwrap_or(0);
+    let before_context = args.before_context.or(args.context).unwrap_or(0);
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    let groups = results
+        .into_iter()
+        .chunk_by(|(commit_index, _, _)| *commit_index);
+    for (group_index, (commit_index, group)) in groups.into_iter().enumerate() {
+        if group_index > 0 {
+            writeln!(formatter)?;
+        }
+        workspace_command.write_commit_summary(formatter.as_mut(), &commits[commit_index])?;
+        writeln!(formatter)?;
+        for (file_index, (_, path, file_matches)) in group.enumerate() {
+            let ui_path = workspace_command.format_file_path(&path);
+            formatter.with_label("grep", |formatter| {
+                if args.files_with_matches {
+                    return writeln!(formatter.labeled("path"), "{ui_path}");
+                }
+                if file_index > 0 && (before_context > 0 || after_context > 0) {
+                    writeln!(formatter.labeled("separator"), "--")?;
+                }
+                write_file_matches(
+                    formatter,
+                    &ui_path,
+                    &file_matches,
+                    &regex,
+                    before_context,
+                    after_context,
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn build_regex(args: &GrepArgs) -> Result<Regex, CommandError> {
+    let pattern = if args.fixed_strings {
+        regex::escape(&args.pattern)
+    } else {
+        args.pattern.clone()
+    };
+    let case_insensitive =
+        args.ignore_case || (args.smart_case && !args.pattern.chars().any(|c| c.is_uppercase()));
+    RegexBuilder::new(&pattern)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|err| user_error_with_message(format!("Invalid pattern: {}", args.pattern), err))
+}
+
+/// The lines of a file that match the pattern.
+struct FileMatches {
+    content: Vec<u8>,
+    /// 0-based numbers of the matching lines, in ascending order.
+    matching_lines: Vec<usize>,
+    /// Whether the file looks like a binary file, in which case the matching
+    /// lines aren't printed.
+    binary: bool,
+}
+
+/// Reads the file and finds the lines that match. Returns `None` if the file
+/// has no matches, or isn't a regular file.
+fn search_file(
+    store: &Store,
+    path: &RepoPath,
+    value: MergedTreeValue,
+    regex: &Regex,
+) -> Result<Option<FileMatches>, CommandError> {
+    let mut content = vec![];
+    match materialize_tree_value(store, path, value).block_on()? {
+        MaterializedTreeValue::File { id, mut reader, .. } => {
+            reader
+                .read_to_end(&mut content)
+                .map_err(|err| BackendError::ReadFile {
+                    path: path.to_owned(),
+                    id,
+                    source: err.into(),
+                })?;
+        }
+        MaterializedTreeValue::FileConflict { contents, .. } => {
+            materialize_merge_result(&contents, &mut content)?;
+        }
+        _ => return Ok(None),
+    }
+    let matching_lines = content
+        .split_inclusive(|b| *b == b'\n')
+        .positions(|line| regex.is_match(strip_line_terminator(line)))
+        .collect_vec();
+    if matching_lines.is_empty() {
+        return Ok(None);
+    }
+    let binary = content.contains(&0);
+    Ok(Some(FileMatches {
+        content,
+        matching_lines,
+        binary,
+    }))
+}
+
+fn strip_line_terminator(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Writes the matching lines of a file like `git grep`, separating
+/// non-adjacent groups of lines with `--` if context is requested.
+fn write_file_matches(
+    formatter: &mut dyn Formatter,
+    ui_path: &str,
+    file_matches: &FileMatches,
+    regex: &Regex,
+    before_context: usize,
+    after_context: usize,
+) -> std::io::Result<()> {
+    if file_matches.binary {
+        write!(formatter, "Binary file ")?;
+        write!(formatter.labeled("path"), "{ui_path}")?;
+        writeln!(formatter, " matches")?;
+        return Ok(());
+    }
+    let lines = file_matches
+        .content
+        .split_inclusive(|b| *b == b'\n')
+        .map(strip_line_terminator)
+        .collect_vec();
+    // Ranges of lines to print, merged where the context of matches overlaps
+    // or touches.
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for &line_number in &file_matches.matching_lines {
+        let start = line_number.saturating_sub(before_context);
+        let end = (line_number + after_context + 1).min(lines.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.end => last.end = last.end.max(end),
+            _ => ranges.push(start..end),
+        }
+    }
+    // Like grep, only separate the groups of lines if they have context.
+    let has_context = before_context > 0 || after_context > 0;
+    for (range_index, range) in ranges.into_iter().enumerate() {
+        if has_context && range_index > 0 {
+            writeln!(formatter.labeled("separator"), "--")?;
+        }
+        for line_number in range {
+            let is_match = file_matches
+                .matching_lines
+                .binary_search(&line_number)
+                .is_ok();
+            write_line(
+                formatter,
+                ui_path,
+                line_number,
+                lines[line_number],
+                is_match.then_some(regex),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a line prefixed by the path and line number. Matching lines have
+/// their matches highlighted and are separated from the prefix by `:`,
+/// context lines by `-`.
+fn write_line(
+    formatter: &mut dyn Formatter,
+    ui_path: &str,
+    line_number: usize,
+    line: &[u8],
+    regex: Option<&Regex>,
+) -> std::io::Result<()> {
+    let separator = if regex.is_some() { ":" } else { "-" };
+    write!(formatter.labeled("path"), "{ui_path}")?;
+    write!(formatter.labeled("separator"), "{separator}")?;
+    write!(formatter.labeled("line_number"), "{}", line_number + 1)?;
+    write!(formatter.labeled("separator"), "{separator}")?;
+    match regex {
+        Some(regex) => {
+            let mut last_end = 0;
+            for m in regex.find_iter(line) {
+                formatter.write_all(&line[last_end..m.start()])?;
+                formatter.with_label("match", |formatter| formatter.write_all(m.as_bytes()))?;
+                last_end = m.end();
+            }
+            formatter.write_all(&line[last_end..])?;
+        }
+        None => formatter.write_all(line)?,
+    }
+    writeln!(formatter)
+}
@@ -12,6 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::iter;
+use std::sync::Arc;
+
+use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::graph::GraphEdgeType;
 use jj_lib::graph::ReverseGraphIterator;
@@ -22,13 +26,17 @@ use jj_lib::revset::RevsetFilterPredicate;
 use jj_lib::revset::RevsetIteratorExt;
 use jj_lib::settings::ConfigResultExt as _;
 use jj_lib::settings::UserSettings;
+use jj_lib::store::Store;
+use pollster::FutureExt as _;
 use tracing::instrument;
 
 use crate::cli_util::format_template;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
 use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
+use crate::commit_templater::CommitPrefetchFields;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::diff_util::DiffFormatArgs;
 use crate::graphlog::get_graphlog;
@@ -54,6 +62,14 @@ pub(crate) struct LogArgs {
     /// Show revisions modifying the given paths
     #[arg(value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
+    /// Follow the history of the given file across copies and renames
+    ///
+    /// Requires a single file path. This is the same as filtering the
+    /// revisions by `follows(PATH)` instead of `file(PATH)`. Copies and renames
+    /// are only detected if the backend records them, like the Git backend
+    /// does.
+    #[arg(long, requires = "paths")]
+    follow: bool,
     /// Show revisions in the opposite order (older revisions first)
     #[arg(long)]
     reversed: bool,
@@ -108,7 +124,14 @@ pub(crate) fn cmd_log(
         if !args.paths.is_empty() {
             // Beware that args.paths = ["root:."] is not identical to []. The
             // former will filter out empty commits.
-            let predicate = RevsetFilterPredicate::File(fileset_expression.clone());
+            let predicate = if args.follow {
+                let [path] = args.paths.as_slice() else {
+                    return Err(user_error("`--follow` requires exactly one path"));
+                };
+                RevsetFilterPredicate::Follows(workspace_command.parse_file_path(path)?)
+            } else {
+                RevsetFilterPredicate::File(fileset_expression.clone())
+            };
             expression.intersect_with(&RevsetExpression::filter(predicate));
         }
         expression
@@ -130,6 +153,7 @@ pub(crate) fn cmd_log(
 
     let template;
     let node_template;
+    let mut prefetch_fields;
     {
         let language = workspace_command.commit_template_language();
         let template_string = match &args.template {
@@ -152,6 +176,10 @@ pub(crate) fn cmd_log(
                 CommitTemplateLanguage::wrap_commit_opt,
             )?
             .labeled("node");
+        prefetch_fields = language.commit_prefetch_fields();
+    }
+    if diff_renderer.is_some() {
+        prefetch_fields.parents = true;
     }
 
     {
@@ -168,14 +196,19 @@ pub(crate) fn cmd_log(
         let limit = args.limit.or(args.deprecated_limit).unwrap_or(usize::MAX);
 
         if !args.no_graph {
-            let mut graph = get_graphlog(graph_style, formatter.raw());
+            let mut graph = get_graphlog(graph_style, formatter);
+            // The buffers are reused for all commits to save allocations when
+            // rendering long logs.
+            let mut buffer = vec![];
+            let mut node_buffer = vec![];
             let forward_iter = TopoGroupedGraphIterator::new(revset.iter_graph());
             let iter: Box<dyn Iterator<Item = _>> = if args.reversed {
                 Box::new(ReverseGraphIterator::new(forward_iter))
             } else {
                 Box::new(forward_iter)
             };
-            for (commit_id, edges) in iter.take(limit) {
+            let iter = prefetch_ahead(store, prefetch_fields, iter.take(limit), |(id, _)| id);
+            for (commit_id, edges) in iter {
                 // The graph is keyed by (CommitId, is_synthetic)
                 let mut graphlog_edges = vec![];
                 // TODO: Should we update revset.iter_graph() to yield this flag instead of all
@@ -204,7 +237,7 @@ pub(crate) fn cmd_log(
                 if has_missing {
                     graphlog_edges.push(Edge::Missing);
                 }
-                let mut buffer = vec![];
+                buffer.clear();
                 let key = (commit_id, false);
                 let commit = store.get_commit(&key.0)?;
                 let within_graph =
@@ -226,18 +259,19 @@ pub(crate) fn cmd_log(
                     )?;
                 }
 
-                let node_symbol = format_template(ui, &Some(commit), &node_template);
+                node_buffer.clear();
+                node_template.format(&Some(commit), ui.new_formatter(&mut node_buffer).as_mut())?;
                 graph.add_node(
                     &key,
                     &graphlog_edges,
-                    &node_symbol,
+                    &String::from_utf8_lossy(&node_buffer),
                     &String::from_utf8_lossy(&buffer),
                 )?;
                 for elided_target in elided_targets {
                     let elided_key = (elided_target, true);
                     let real_key = (elided_key.0.clone(), false);
                     let edges = [Edge::Direct(real_key)];
-                    let mut buffer = vec![];
+                    buffer.clear();
                     let within_graph =
                         with_content_format.sub_width(graph.width(&elided_key, &edges));
                     within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
@@ -258,8 +292,8 @@ pub(crate) fn cmd_log(
             } else {
                 Box::new(revset.iter())
             };
-            for commit_or_error in iter.commits(store).take(limit) {
-                let commit = commit_or_error?;
+            for commit_id in prefetch_ahead(store, prefetch_fields, iter.take(limit), |id| id) {
+                let commit = store.get_commit(&commit_id)?;
                 with_content_format
                     .write(formatter, |formatter| template.format(&commit, formatter))?;
                 if let Some(renderer) = &diff_renderer {
@@ -312,3 +346,39 @@ pub fn get_node_template(
     };
     Ok(symbol.unwrap_or_else(|| default.to_owned()))
 }
+
+/// Number of commits read ahead of rendering. The commits and their parents
+/// should fit in the store's commit cache.
+const PREFETCH_CHUNK_SIZE: usize = 32;
+
+/// Reads the commits of `iter` and the data selected by `fields` in chunks
+/// before the items are yielded, so the backend can read them concurrently.
+fn prefetch_ahead<'a, T: 'a>(
+    store: &'a Arc<Store>,
+    fields: CommitPrefetchFields,
+    iter: impl Iterator<Item = T> + 'a,
+    get_id: impl Fn(&T) -> &CommitId + 'a,
+) -> impl Iterator<Item = T> + 'a {
+    let mut iter = iter.fuse();
+    iter::from_fn(move || {
+        let chunk = iter.by_ref().take(PREFETCH_CHUNK_SIZE).collect_vec();
+        if chunk.is_empty() {
+            return None;
+        }
+        let ids = chunk.iter().map(&get_id).cloned().collect_vec();
+        store.prefetch_commits(&ids).block_on();
+        if fields.parents {
+            // Commits that failed to load will report their errors when
+            // they're rendered.
+            let parent_ids = ids
+                .iter()
+                .filter_map(|id| store.get_commit(id).ok())
+                .flat_map(|commit| commit.parent_ids().to_vec())
+                .unique()
+                .collect_vec();
+            store.prefetch_commits(&parent_ids).block_on();
+        }
+        Some(chunk)
+    })
+    .flatten()
+}
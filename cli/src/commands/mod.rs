@@ -13,13 +13,18 @@
 // limitations under the License.
 
 mod abandon;
+mod absorb;
+mod am;
 mod backout;
 #[cfg(feature = "bench")]
 mod bench;
+mod bisect;
 mod bookmark;
+mod browse;
 mod checkout;
 mod commit;
 mod config;
+mod daemon;
 mod debug;
 mod describe;
 mod diff;
@@ -29,7 +34,11 @@ mod edit;
 mod evolog;
 mod file;
 mod fix;
+mod forge;
+mod format_patch;
+mod gerrit;
 mod git;
+mod grep;
 mod init;
 mod interdiff;
 mod log;
@@ -43,12 +52,15 @@ mod prev;
 mod rebase;
 mod resolve;
 mod restore;
+mod revert;
 mod root;
 mod run;
+mod serialize;
 mod show;
 mod sparse;
 mod split;
 mod squash;
+mod stash;
 mod status;
 mod tag;
 mod unsquash;
@@ -65,14 +77,17 @@ use tracing::instrument;
 
 use crate::cli_util::Args;
 use crate::cli_util::CommandHelper;
-use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 #[derive(clap::Parser, Clone, Debug)]
 enum Command {
     Abandon(abandon::AbandonArgs),
+    Absorb(absorb::AbsorbArgs),
+    Am(am::AmArgs),
     Backout(backout::BackoutArgs),
+    #[command(subcommand)]
+    Bisect(bisect::BisectCommand),
     #[cfg(feature = "bench")]
     #[command(subcommand)]
     Bench(bench::BenchCommand),
@@ -81,6 +96,7 @@ enum Command {
     // TODO: Remove in jj 0.28+
     #[command(subcommand, hide = true)]
     Branch(bookmark::BookmarkCommand),
+    Browse(browse::BrowseArgs),
     #[command(alias = "print", hide = true)]
     Cat(file::show::FileShowArgs),
     #[command(hide = true)]
@@ -92,6 +108,8 @@ enum Command {
     #[command(subcommand)]
     Config(config::ConfigCommand),
     #[command(subcommand)]
+    Daemon(daemon::DaemonCommand),
+    #[command(subcommand)]
     Debug(debug::DebugCommand),
     Describe(describe::DescribeArgs),
     Diff(diff::DiffArgs),
@@ -108,7 +126,13 @@ enum Command {
     Files(file::list::FileListArgs),
     Fix(fix::FixArgs),
     #[command(subcommand)]
+    Forge(forge::ForgeCommand),
+    FormatPatch(format_patch::FormatPatchArgs),
+    #[command(subcommand)]
+    Gerrit(gerrit::GerritCommand),
+    #[command(subcommand)]
     Git(git::GitCommand),
+    Grep(grep::GrepArgs),
     Init(init::InitArgs),
     Interdiff(interdiff::InterdiffArgs),
     Log(log::LogArgs),
@@ -133,22 +157,21 @@ enum Command {
     Parallelize(parallelize::ParallelizeArgs),
     Prev(prev::PrevArgs),
     Rebase(rebase::RebaseArgs),
+    /// Redo the changes undone by the last undo (shortcut for `jj op redo`)
+    Redo(operation::redo::OperationRedoArgs),
     Resolve(resolve::ResolveArgs),
     Restore(restore::RestoreArgs),
-    #[command(
-        hide = true,
-        help_template = "Not a real subcommand; consider `jj backout` or `jj restore`"
-    )]
-    Revert(DummyCommandArgs),
+    Revert(revert::RevertArgs),
     Root(root::RootArgs),
-    #[command(hide = true)]
-    // TODO: Flesh out.
     Run(run::RunArgs),
+    Serialize(serialize::SerializeArgs),
     Show(show::ShowArgs),
     #[command(subcommand)]
     Sparse(sparse::SparseCommand),
     Split(split::SplitArgs),
     Squash(squash::SquashArgs),
+    #[command(subcommand)]
+    Stash(stash::StashCommand),
     Status(status::StatusArgs),
     #[command(subcommand)]
     Tag(tag::TagCommand),
@@ -165,13 +188,6 @@ enum Command {
     Workspace(workspace::WorkspaceCommand),
 }
 
-/// A dummy command that accepts any arguments
-#[derive(clap::Args, Clone, Debug)]
-struct DummyCommandArgs {
-    #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
-    _args: Vec<String>,
-}
-
 pub fn default_app() -> clap::Command {
     Command::augment_subcommands(Args::command())
 }
@@ -181,7 +197,10 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
     let subcommand = Command::from_arg_matches(command_helper.matches()).unwrap();
     match &subcommand {
         Command::Abandon(args) => abandon::cmd_abandon(ui, command_helper, args),
+        Command::Absorb(args) => absorb::cmd_absorb(ui, command_helper, args),
+        Command::Am(args) => am::cmd_am(ui, command_helper, args),
         Command::Backout(args) => backout::cmd_backout(ui, command_helper, args),
+        Command::Bisect(args) => bisect::cmd_bisect(ui, command_helper, args),
         #[cfg(feature = "bench")]
         Command::Bench(args) => bench::cmd_bench(ui, command_helper, args),
         Command::Bookmark(args) => bookmark::cmd_bookmark(ui, command_helper, args),
@@ -189,6 +208,7 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
             let cmd = renamed_cmd("branch", "bookmark", bookmark::cmd_bookmark);
             cmd(ui, command_helper, args)
         }
+        Command::Browse(args) => browse::cmd_browse(ui, command_helper, args),
         Command::Cat(args) => {
             let cmd = renamed_cmd("cat", "file show", file::show::cmd_file_show);
             cmd(ui, command_helper, args)
@@ -200,6 +220,7 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         }
         Command::Commit(args) => commit::cmd_commit(ui, command_helper, args),
         Command::Config(args) => config::cmd_config(ui, command_helper, args),
+        Command::Daemon(args) => daemon::cmd_daemon(ui, command_helper, args),
         Command::Debug(args) => debug::cmd_debug(ui, command_helper, args),
         Command::Describe(args) => describe::cmd_describe(ui, command_helper, args),
         Command::Diff(args) => diff::cmd_diff(ui, command_helper, args),
@@ -212,7 +233,11 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
             cmd(ui, command_helper, args)
         }
         Command::Fix(args) => fix::cmd_fix(ui, command_helper, args),
+        Command::Forge(args) => forge::cmd_forge(ui, command_helper, args),
+        Command::FormatPatch(args) => format_patch::cmd_format_patch(ui, command_helper, args),
+        Command::Gerrit(args) => gerrit::cmd_gerrit(ui, command_helper, args),
         Command::Git(args) => git::cmd_git(ui, command_helper, args),
+        Command::Grep(args) => grep::cmd_grep(ui, command_helper, args),
         Command::Init(args) => init::cmd_init(ui, command_helper, args),
         Command::Interdiff(args) => interdiff::cmd_interdiff(ui, command_helper, args),
         Command::Log(args) => log::cmd_log(ui, command_helper, args),
@@ -225,15 +250,18 @@ pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), Co
         Command::Parallelize(args) => parallelize::cmd_parallelize(ui, command_helper, args),
         Command::Prev(args) => prev::cmd_prev(ui, command_helper, args),
         Command::Rebase(args) => rebase::cmd_rebase(ui, command_helper, args),
+        Command::Redo(args) => operation::redo::cmd_op_redo(ui, command_helper, args),
         Command::Resolve(args) => resolve::cmd_resolve(ui, command_helper, args),
         Command::Restore(args) => restore::cmd_restore(ui, command_helper, args),
-        Command::Revert(_args) => revert(),
+        Command::Revert(args) => revert::cmd_revert(ui, command_helper, args),
         Command::Root(args) => root::cmd_root(ui, command_helper, args),
         Command::Run(args) => run::cmd_run(ui, command_helper, args),
+        Command::Serialize(args) => serialize::cmd_serialize(ui, command_helper, args),
         Command::Show(args) => show::cmd_show(ui, command_helper, args),
         Command::Sparse(args) => sparse::cmd_sparse(ui, command_helper, args),
         Command::Split(args) => split::cmd_split(ui, command_helper, args),
         Command::Squash(args) => squash::cmd_squash(ui, command_helper, args),
+        Command::Stash(args) => stash::cmd_stash(ui, command_helper, args),
         Command::Status(args) => status::cmd_status(ui, command_helper, args),
         Command::Tag(args) => tag::cmd_tag(ui, command_helper, args),
         Command::Undo(args) => operation::undo::cmd_op_undo(ui, command_helper, args),
@@ -267,13 +295,6 @@ pub(crate) fn renamed_cmd<Args>(
     }
 }
 
-fn revert() -> Result<(), CommandError> {
-    Err(user_error_with_hint(
-        "No such subcommand: revert",
-        "Consider `jj backout` or `jj restore`",
-    ))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
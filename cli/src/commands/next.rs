@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use jj_lib::str_util::StringPattern;
+
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
 use crate::movement_util::move_to_commit;
@@ -73,6 +75,14 @@ pub(crate) struct NextArgs {
     /// Jump to the next conflicted descendant
     #[arg(long, conflicts_with = "offset")]
     conflict: bool,
+    /// Only move toward bookmarks matching the pattern
+    ///
+    /// If there are several children, only the ones that are ancestors of a
+    /// matching bookmark are considered. By default, the pattern matches the
+    /// bookmark name exactly. Use `glob:` prefix to select bookmarks by
+    /// wildcard pattern.
+    #[arg(long, short, value_parser = StringPattern::parse)]
+    bookmark: Option<StringPattern>,
 }
 
 impl From<&NextArgs> for MovementArgs {
@@ -82,6 +92,7 @@ impl From<&NextArgs> for MovementArgs {
             edit: val.edit,
             no_edit: val.no_edit,
             conflict: val.conflict,
+            bookmark: val.bookmark.clone(),
         }
     }
 }
@@ -19,6 +19,11 @@ use std::slice;
 use itertools::Itertools as _;
 use jj_lib::op_walk;
 use jj_lib::operation::Operation;
+use jj_lib::protected_ops;
+
+use super::check_protected_operations;
+use super::operation_ids_in_range;
+use super::update_op_heads;
 
 use crate::cli_util::short_operation_hash;
 use crate::cli_util::CommandHelper;
@@ -38,10 +43,17 @@ use crate::ui::Ui;
 ///
 /// The abandoned operations, commits, and other unreachable objects can later
 /// be garbage collected by using `jj util gc` command.
+///
+/// Protected operations (see `jj op protect`) can only be abandoned with
+/// `--force`.
 #[derive(clap::Args, Clone, Debug)]
 pub struct OperationAbandonArgs {
     /// The operation or operation range to abandon
     operation: String,
+
+    /// Abandon the operations even if some of them are protected
+    #[arg(long)]
+    force: bool,
 }
 
 pub fn cmd_op_abandon(
@@ -102,6 +114,10 @@ pub fn cmd_op_abandon(
         return Err(err);
     }
 
+    let abandoned_ids =
+        || operation_ids_in_range(slice::from_ref(&abandon_root_op), &abandon_head_ops);
+    check_protected_operations(workspace.repo_path(), abandoned_ids, args.force)?;
+
     // Reparent descendants, count the number of abandoned operations.
     let stats = op_walk::reparent_range(
         op_store.as_ref(),
@@ -114,8 +130,7 @@ pub fn cmd_op_abandon(
         stats.new_head_ids.len(),
         "all current_head_ops should be reparented as they aren't included in abandon_head_ops"
     );
-    let reparented_head_ops = || iter::zip(&current_head_ops, &stats.new_head_ids);
-    if reparented_head_ops().all(|(old, new_id)| old.id() == new_id) {
+    if iter::zip(&current_head_ops, &stats.new_head_ids).all(|(old, new_id)| old.id() == new_id) {
         writeln!(ui.status(), "Nothing changed.")?;
         return Ok(());
     }
@@ -125,27 +140,18 @@ pub fn cmd_op_abandon(
         stats.unreachable_count,
         stats.rewritten_count,
     )?;
-    for (old, new_id) in reparented_head_ops().filter(|&(old, new_id)| old.id() != new_id) {
-        op_heads_store.update_op_heads(slice::from_ref(old.id()), new_id);
-    }
-    // Remap the operation id of the current workspace. If there were any
-    // divergent operations, user will need to re-abandon their ancestors.
-    if !command.global_args().ignore_working_copy {
-        let mut locked_ws = workspace.start_working_copy_mutation()?;
-        let old_op_id = locked_ws.locked_wc().old_operation_id();
-        if let Some((_, new_id)) = reparented_head_ops().find(|(old, _)| old.id() == old_op_id) {
-            locked_ws.finish(new_id.clone())?
-        } else {
-            writeln!(
-                ui.warning_default(),
-                "The working copy operation {} is not updated because it differs from the repo {}.",
-                short_operation_hash(old_op_id),
-                current_head_ops
-                    .iter()
-                    .map(|op| short_operation_hash(op.id()))
-                    .join(", "),
-            )?;
-        }
-    }
+    update_op_heads(
+        ui,
+        command,
+        &mut workspace,
+        &current_head_ops,
+        &stats.new_head_ids,
+    )?;
+    // Operations abandoned with --force are no longer protected.
+    protected_ops::update_protected_operations(
+        workspace.repo_path(),
+        &stats.rewritten_ids,
+        &abandoned_ids()?,
+    )?;
     Ok(())
 }
@@ -34,6 +34,8 @@ use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
 use jj_lib::revset;
 use jj_lib::revset::RevsetIteratorExt as _;
+use jj_lib::settings::ConfigResultExt as _;
+use jj_lib::settings::UserSettings;
 
 use crate::cli_util::short_change_hash;
 use crate::cli_util::CommandHelper;
@@ -114,11 +116,9 @@ pub fn cmd_op_diff(
     };
     let id_prefix_context = workspace_env.new_id_prefix_context();
     let commit_summary_template = {
-        let language = workspace_env.commit_template_language(merged_repo, &id_prefix_context);
-        let text = command
-            .settings()
-            .config()
-            .get_string("templates.commit_summary")?;
+        let language =
+            workspace_env.commit_template_language(merged_repo, &to_op, &id_prefix_context);
+        let text = get_commit_summary_template_text(command.settings())?;
         workspace_env.parse_template(ui, &language, &text, CommitTemplateLanguage::wrap_commit)?
     };
 
@@ -147,6 +147,22 @@ pub fn cmd_op_diff(
     )
 }
 
+/// Returns the template used to render commits in operation diffs. Falls back
+/// to `templates.commit_summary` if `templates.op_diff_commit_summary` isn't
+/// set.
+pub fn get_commit_summary_template_text(
+    settings: &UserSettings,
+) -> Result<String, config::ConfigError> {
+    let text = settings
+        .config()
+        .get_string("templates.op_diff_commit_summary")
+        .optional()?;
+    match text {
+        Some(text) => Ok(text),
+        None => settings.config().get_string("templates.commit_summary"),
+    }
+}
+
 /// Computes and shows the differences between two operations, using the given
 /// `ReadonlyRepo`s for the operations.
 /// `current_repo` should contain a `Repo` with the indices of both repos merged
@@ -197,7 +213,7 @@ pub fn show_op_diff(
             writeln!(formatter, "Changed commits:")
         })?;
         if let Some(graph_style) = graph_style {
-            let mut graph = get_graphlog(graph_style, formatter.raw());
+            let mut graph = get_graphlog(graph_style, formatter);
 
             let graph_iter =
                 TopoGroupedGraphIterator::new(ordered_change_ids.iter().map(|change_id| {
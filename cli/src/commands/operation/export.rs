@@ -0,0 +1,62 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use jj_lib::simple_op_store;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Export the view of an operation to a file
+///
+/// The view is the state of the repo recorded by the operation: the visible
+/// heads, the bookmarks, the tags, and the working-copy commits. The commits
+/// themselves aren't exported, so the file is small, but it can only be
+/// imported with `jj op import` into a repo that has the commits.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationExportArgs {
+    /// The file to write the view to
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    file: PathBuf,
+
+    /// The operation to export the view of
+    #[arg(long, default_value = "@")]
+    operation: String,
+}
+
+pub fn cmd_op_export(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationExportArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let op = workspace_command.resolve_single_op(&args.operation)?;
+    let view = op.view()?;
+    fs::write(&args.file, simple_op_store::encode_view(view.store_view())).map_err(|err| {
+        user_error_with_message(format!("Failed to write {}", args.file.display()), err)
+    })?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Exported the view of operation ")?;
+        workspace_command
+            .operation_summary_template()
+            .format(&op, formatter.as_mut())?;
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
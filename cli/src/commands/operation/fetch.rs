@@ -0,0 +1,130 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::collections::HashSet;
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::git;
+use jj_lib::op_store::OperationId;
+use jj_lib::op_walk;
+use jj_lib::operation::Operation;
+
+use super::map_op_log_error;
+use crate::cli_util::CommandHelper;
+use crate::command_error::cli_error;
+use crate::command_error::CommandError;
+use crate::commands::git::fetch::get_default_fetch_remotes;
+use crate::git_util::get_git_repo;
+use crate::git_util::with_remote_git_callbacks;
+use crate::ui::Ui;
+
+/// Fetch the operation log from a Git remote
+///
+/// The operations pushed to the remote with `jj op push` are added to the
+/// local operation log, along with the commits they refer to. If operations
+/// were done both locally and on the remote since the last sync, the operation
+/// logs diverge and the next command merges them like concurrent operations.
+/// Bookmarks that were moved differently on both sides become conflicted, which
+/// `jj op reconcile` can resolve interactively.
+///
+/// Change IDs are not synced. Commits fetched from the remote get the change
+/// IDs they would get if they were imported from Git.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationFetchArgs {
+    /// The remote to fetch from (only named remotes are supported, can be
+    /// repeated)
+    ///
+    /// Defaults to the `git.fetch` setting, like `jj git fetch`.
+    #[arg(long = "remote", value_name = "remote")]
+    remotes: Vec<String>,
+}
+
+pub fn cmd_op_fetch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationFetchArgs,
+) -> Result<(), CommandError> {
+    // The fetched operations are added to the head operations, so loading the
+    // repo at another operation makes no sense.
+    if command.global_args().at_operation.is_some() {
+        return Err(cli_error("--at-op is not respected"));
+    }
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op_store = repo_loader.op_store();
+    let op_heads_store = repo_loader.op_heads_store();
+    let git_repo = get_git_repo(repo_loader.store())?;
+    let remotes = if args.remotes.is_empty() {
+        get_default_fetch_remotes(ui, command.settings(), &git_repo)?
+    } else {
+        args.remotes.clone()
+    };
+    let mut diverged = false;
+    for remote in &remotes {
+        let fetched = with_remote_git_callbacks(ui, None, |cb| {
+            git::fetch_operations(
+                &git_repo,
+                repo_loader.store(),
+                op_store.as_ref(),
+                remote,
+                cb,
+                &command.settings().git_settings(),
+            )
+        })
+        .map_err(map_op_log_error)?;
+        let Some(fetched) = fetched else {
+            writeln!(ui.warning_default(), "Remote {remote} has no operation log")?;
+            continue;
+        };
+        writeln!(
+            ui.status(),
+            "Fetched {} operations from remote {remote}",
+            fetched.num_imported
+        )?;
+
+        // Remote heads that are already known locally are left alone, so
+        // operations abandoned locally aren't brought back.
+        let head_ops = op_walk::get_current_head_ops(op_store, op_heads_store.as_ref())?;
+        let local_op_ids: HashSet<OperationId> = op_walk::walk_ancestors(&head_ops)
+            .map_ok(|op| op.id().clone())
+            .try_collect()?;
+        let new_head_ops: Vec<_> = fetched
+            .head_ids
+            .iter()
+            .filter(|id| !local_op_ids.contains(*id))
+            .map(|id| -> Result<_, CommandError> {
+                let data = op_store.read_operation(id)?;
+                Ok(Operation::new(op_store.clone(), id.clone(), data))
+            })
+            .try_collect()?;
+        if new_head_ops.is_empty() {
+            continue;
+        }
+        let remote_op_ids: HashSet<OperationId> = op_walk::walk_ancestors(&new_head_ops)
+            .map_ok(|op| op.id().clone())
+            .try_collect()?;
+        diverged |= head_ops.iter().any(|op| !remote_op_ids.contains(op.id()));
+        for op in &new_head_ops {
+            op_heads_store.update_op_heads(&[], op.id());
+        }
+    }
+    if diverged {
+        writeln!(
+            ui.hint_default(),
+            "The fetched operations diverged from the local ones. The next command will merge \
+             them, or run `jj op reconcile` to choose where conflicted bookmarks point."
+        )?;
+    }
+    Ok(())
+}
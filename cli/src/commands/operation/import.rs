@@ -0,0 +1,88 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::path::PathBuf;
+
+use itertools::Itertools as _;
+use jj_lib::backend::BackendError;
+use jj_lib::repo::Repo as _;
+use jj_lib::simple_op_store;
+
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Restore the repo to a view exported by `jj op export`
+///
+/// Like `jj op restore`, this creates a new operation that sets the heads, the
+/// bookmarks, the tags, and the working-copy commits to the ones in the file.
+/// All commits the view refers to must already exist in the repo, for example
+/// by fetching them from a Git remote first.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationImportArgs {
+    /// The file to read the view from
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    file: PathBuf,
+}
+
+pub fn cmd_op_import(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationImportArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let buf = fs::read(&args.file).map_err(|err| {
+        user_error_with_message(format!("Failed to read {}", args.file.display()), err)
+    })?;
+    let view = simple_op_store::decode_view(&buf).map_err(|err| {
+        user_error_with_message(
+            format!("Failed to read a view from {}", args.file.display()),
+            err,
+        )
+    })?;
+
+    let store = workspace_command.repo().store().clone();
+    let commit_ids = view
+        .head_ids
+        .iter()
+        .chain(view.wc_commit_ids.values())
+        .unique();
+    let mut commits = vec![];
+    for id in commit_ids {
+        match store.get_commit(id) {
+            Ok(commit) => commits.push(commit),
+            Err(BackendError::ObjectNotFound { .. }) => {
+                return Err(user_error_with_hint(
+                    format!(
+                        "Commit {} of the imported view doesn't exist in the repo",
+                        short_commit_hash(id)
+                    ),
+                    "Fetch the commits from a remote before importing the view.",
+                ));
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    // Index the commits, which might not have been visible in this repo before.
+    tx.repo_mut().add_heads(&commits)?;
+    tx.repo_mut().set_view(view);
+    tx.finish(ui, format!("import view from {}", args.file.display()))?;
+    Ok(())
+}
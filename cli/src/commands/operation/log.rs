@@ -12,20 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::slice;
+use std::sync::Arc;
 
 use itertools::Itertools as _;
+use jj_lib::backend::ChangeId;
+use jj_lib::op_store::OperationId;
 use jj_lib::op_walk;
 use jj_lib::operation::Operation;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::repo::Repo as _;
 use jj_lib::repo::RepoLoader;
+use jj_lib::revset;
+use jj_lib::revset::RevsetIteratorExt as _;
 use jj_lib::settings::ConfigResultExt as _;
 use jj_lib::settings::UserSettings;
+use jj_lib::str_util::StringPattern;
+use jj_lib::str_util::StringPatternParseError;
+use jj_lib::time_util::DatePattern;
 
+use super::diff::get_commit_summary_template_text;
 use super::diff::show_op_diff;
 use crate::cli_util::format_template;
+use crate::cli_util::operation_command_line;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
+use crate::cli_util::RevisionArg;
 use crate::cli_util::WorkspaceCommandEnvironment;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::diff_util::diff_formats_for_log;
@@ -36,6 +52,7 @@ use crate::graphlog::get_graphlog;
 use crate::graphlog::Edge;
 use crate::graphlog::GraphStyle;
 use crate::operation_templater::OperationTemplateLanguage;
+use crate::operation_templater::OperationTemplateTypes as _;
 use crate::ui::Ui;
 
 /// Show the operation log
@@ -43,6 +60,11 @@ use crate::ui::Ui;
 /// Like other commands, `jj op log` snapshots the current working-copy changes
 /// and reconciles divergent operations. Use `--at-op=@ --ignore-working-copy`
 /// to inspect the current state without mutation.
+///
+/// The operations can be filtered with the `--since`, `--until`, `--command`,
+/// `--user`, `--touching-bookmark`, and `--touching-rev` options. Only the
+/// operations matching all of the given filters are shown. In the graph, the
+/// edges of an operation then lead to its closest ancestors that are shown.
 #[derive(clap::Args, Clone, Debug)]
 pub struct OperationLogArgs {
     /// Limit number of operations to show
@@ -65,7 +87,13 @@ pub struct OperationLogArgs {
     #[arg(long, short = 'T')]
     template: Option<String>,
     /// Show changes to the repository at each operation
-    #[arg(long)]
+    ///
+    /// Lists the commits that were added, rewritten, or abandoned, including
+    /// snapshots of the working copy, and the bookmarks and tags that were
+    /// moved, like `jj op diff`. Commits are rendered using the
+    /// `templates.op_diff_commit_summary` template, which defaults to
+    /// `templates.commit_summary`.
+    #[arg(long, visible_alias = "diff")]
     op_diff: bool,
     /// Show patch of modifications to changes (implies --op-diff)
     ///
@@ -74,20 +102,77 @@ pub struct OperationLogArgs {
     /// contaminated by unrelated changes.
     #[arg(long, short = 'p')]
     patch: bool,
+    /// Only show operations done at or after this time
+    ///
+    /// Accepts the same formats as the date patterns of revsets, like
+    /// `yesterday` or `2024-02-01`.
+    #[arg(long, value_name = "TIME")]
+    since: Option<String>,
+    /// Only show operations done before this time
+    #[arg(long, value_name = "TIME")]
+    until: Option<String>,
+    /// Only show operations whose description or command line matches the
+    /// pattern
+    ///
+    /// The pattern matches any substring unless it's prefixed by a kind like
+    /// `exact:` or `glob:`.
+    #[arg(long, value_name = "PATTERN", value_parser = parse_substring_pattern)]
+    command: Option<StringPattern>,
+    /// Only show operations done by users matching the pattern
+    ///
+    /// The pattern is matched against both the user name and `user@host`.
+    #[arg(long, value_name = "PATTERN", value_parser = StringPattern::parse)]
+    user: Option<StringPattern>,
+    /// Only show operations that created, moved, or deleted matching local
+    /// bookmarks
+    #[arg(long, value_name = "BOOKMARK", value_parser = StringPattern::parse)]
+    touching_bookmark: Option<StringPattern>,
+    /// Only show operations that created, rewrote, or abandoned the revisions
+    ///
+    /// Any commit with the change ID of one of the revisions counts, so the
+    /// operations that created the earlier versions of the revisions are shown
+    /// too.
+    #[arg(long, value_name = "REVSETS")]
+    touching_rev: Option<RevisionArg>,
     #[command(flatten)]
     diff_format: DiffFormatArgs,
 }
 
+fn parse_substring_pattern(src: &str) -> Result<StringPattern, StringPatternParseError> {
+    if let Some((kind, pat)) = src.split_once(':') {
+        StringPattern::from_str_kind(pat, kind)
+    } else {
+        Ok(StringPattern::substring(src))
+    }
+}
+
 pub fn cmd_op_log(
     ui: &mut Ui,
     command: &CommandHelper,
     args: &OperationLogArgs,
 ) -> Result<(), CommandError> {
-    if command.is_working_copy_writable() {
+    // The revisions can only be resolved with the repo loaded.
+    if command.is_working_copy_writable() || args.touching_rev.is_some() {
         let workspace_command = command.workspace_helper(ui)?;
         let current_op = workspace_command.repo().operation();
         let repo_loader = workspace_command.workspace().repo_loader();
-        do_op_log(ui, workspace_command.env(), repo_loader, current_op, args)
+        let mut filter = OperationFilter::new(workspace_command.env(), args)?;
+        if let Some(revision_arg) = &args.touching_rev {
+            let change_ids = workspace_command
+                .parse_revset(ui, revision_arg)?
+                .evaluate_to_commits()?
+                .map_ok(|commit| commit.change_id().clone())
+                .try_collect()?;
+            filter.touching_changes = Some((workspace_command.repo().clone(), change_ids));
+        }
+        do_op_log(
+            ui,
+            workspace_command.env(),
+            repo_loader,
+            current_op,
+            &filter,
+            args,
+        )
     } else {
         // Don't load the repo so that the operation history can be inspected
         // even with a corrupted repo state. For example, you can find the first
@@ -96,15 +181,171 @@ pub fn cmd_op_log(
         let workspace_env = command.workspace_environment(ui, &workspace)?;
         let repo_loader = workspace.repo_loader();
         let current_op = command.resolve_operation(ui, workspace.repo_loader())?;
-        do_op_log(ui, &workspace_env, repo_loader, &current_op, args)
+        let filter = OperationFilter::new(&workspace_env, args)?;
+        do_op_log(ui, &workspace_env, repo_loader, &current_op, &filter, args)
     }
 }
 
+/// The criteria an operation must match to be shown.
+#[derive(Default)]
+struct OperationFilter {
+    since: Option<DatePattern>,
+    until: Option<DatePattern>,
+    command: Option<StringPattern>,
+    user: Option<StringPattern>,
+    touching_bookmark: Option<StringPattern>,
+    /// The repo at the current operation, which has all the commits of the
+    /// earlier operations in its index, and the change IDs to look for.
+    touching_changes: Option<(Arc<ReadonlyRepo>, HashSet<ChangeId>)>,
+}
+
+impl OperationFilter {
+    fn new(
+        workspace_env: &WorkspaceCommandEnvironment,
+        args: &OperationLogArgs,
+    ) -> Result<Self, CommandError> {
+        let date_context = *workspace_env.revset_parse_context().date_pattern_context();
+        let parse_time = |text: &Option<String>, kind: &str| {
+            text.as_ref()
+                .map(|text| {
+                    date_context.parse_relative(text, kind).map_err(|err| {
+                        user_error_with_message(format!("Invalid time: {text}"), err)
+                    })
+                })
+                .transpose()
+        };
+        Ok(OperationFilter {
+            since: parse_time(&args.since, "after")?,
+            until: parse_time(&args.until, "before")?,
+            command: args.command.clone(),
+            user: args.user.clone(),
+            touching_bookmark: args.touching_bookmark.clone(),
+            touching_changes: None,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.since.is_none()
+            && self.until.is_none()
+            && self.command.is_none()
+            && self.user.is_none()
+            && self.touching_bookmark.is_none()
+            && self.touching_changes.is_none()
+    }
+
+    fn matches(&self, op: &Operation) -> Result<bool, CommandError> {
+        let metadata = op.metadata();
+        let matches_time = |pattern: &Option<DatePattern>| {
+            pattern
+                .as_ref()
+                .map_or(true, |pattern| pattern.matches(&metadata.end_time))
+        };
+        if !matches_time(&self.since) || !matches_time(&self.until) {
+            return Ok(false);
+        }
+        if let Some(pattern) = &self.command {
+            let command_line = operation_command_line(metadata).unwrap_or_default();
+            if !pattern.matches(&metadata.description) && !pattern.matches(&command_line) {
+                return Ok(false);
+            }
+        }
+        if let Some(pattern) = &self.user {
+            let user_at_host = format!("{}@{}", metadata.username, metadata.hostname);
+            if !pattern.matches(&metadata.username) && !pattern.matches(&user_at_host) {
+                return Ok(false);
+            }
+        }
+        if self.touching_bookmark.is_none() && self.touching_changes.is_none() {
+            return Ok(true);
+        }
+
+        // The changes of merge operations are compared to their first parent.
+        let Some(parent_op) = op.parents().next() else {
+            return Ok(false);
+        };
+        let view = op.view()?;
+        let parent_view = parent_op?.view()?;
+        if let Some(pattern) = &self.touching_bookmark {
+            let touched = view
+                .local_bookmarks_matching(pattern)
+                .chain(parent_view.local_bookmarks_matching(pattern))
+                .any(|(name, _)| {
+                    view.get_local_bookmark(name) != parent_view.get_local_bookmark(name)
+                });
+            if !touched {
+                return Ok(false);
+            }
+        }
+        if let Some((repo, change_ids)) = &self.touching_changes {
+            let heads = view.heads().iter().cloned().collect_vec();
+            let parent_heads = parent_view.heads().iter().cloned().collect_vec();
+            let added = revset::walk_revs(repo.as_ref(), &heads, &parent_heads)?;
+            let removed = revset::walk_revs(repo.as_ref(), &parent_heads, &heads)?;
+            for commit in added.iter().chain(removed.iter()).commits(repo.store()) {
+                if change_ids.contains(commit?.change_id()) {
+                    return Ok(true);
+                }
+            }
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+/// An operation with the edges to its parents in the graph.
+type OperationWithEdges = (Operation, Vec<Edge<OperationId>>);
+
+/// Returns the operations matching the filter among the ancestors of
+/// `current_op`, with the edges to their closest ancestors that match too.
+fn walk_filtered_ops(
+    current_op: &Operation,
+    filter: &OperationFilter,
+    limit: usize,
+) -> Result<Vec<OperationWithEdges>, CommandError> {
+    let ops: Vec<_> = op_walk::walk_ancestors(slice::from_ref(current_op)).try_collect()?;
+    let mut matched_ids = HashSet::new();
+    for op in &ops {
+        if filter.matches(op)? {
+            matched_ids.insert(op.id().clone());
+        }
+    }
+    // Parents are visited before their children, so the closest matching
+    // ancestors of the parents are known when a child is visited.
+    let mut closest_matched: HashMap<&OperationId, Vec<OperationId>> = HashMap::new();
+    let mut filtered_ops = vec![];
+    for op in ops.iter().rev() {
+        let mut edges = vec![];
+        let mut ancestor_ids = vec![];
+        for parent_id in op.parent_ids() {
+            if matched_ids.contains(parent_id) {
+                edges.push(Edge::Direct(parent_id.clone()));
+                ancestor_ids.push(parent_id.clone());
+            } else {
+                for id in closest_matched.get(parent_id).into_iter().flatten() {
+                    if !ancestor_ids.contains(id) {
+                        edges.push(Edge::Indirect(id.clone()));
+                        ancestor_ids.push(id.clone());
+                    }
+                }
+            }
+        }
+        if matched_ids.contains(op.id()) {
+            filtered_ops.push((op.clone(), edges));
+        } else {
+            closest_matched.insert(op.id(), ancestor_ids);
+        }
+    }
+    filtered_ops.reverse();
+    filtered_ops.truncate(limit);
+    Ok(filtered_ops)
+}
+
 fn do_op_log(
     ui: &mut Ui,
     workspace_env: &WorkspaceCommandEnvironment,
     repo_loader: &RepoLoader,
     current_op: &Operation,
+    filter: &OperationFilter,
     args: &OperationLogArgs,
 ) -> Result<(), CommandError> {
     let settings = workspace_env.settings();
@@ -143,7 +384,7 @@ fn do_op_log(
 
     let diff_formats = diff_formats_for_log(settings, &args.diff_format, args.patch)?;
     let maybe_show_op_diff = if args.op_diff || !diff_formats.is_empty() {
-        let template_text = settings.config().get_string("templates.commit_summary")?;
+        let template_text = get_commit_summary_template_text(settings)?;
         let show = move |ui: &Ui,
                          formatter: &mut dyn Formatter,
                          op: &Operation,
@@ -156,7 +397,7 @@ fn do_op_log(
             let id_prefix_context = workspace_env.new_id_prefix_context();
             let commit_summary_template = {
                 let language =
-                    workspace_env.commit_template_language(repo.as_ref(), &id_prefix_context);
+                    workspace_env.commit_template_language(repo.as_ref(), op, &id_prefix_context);
                 workspace_env.parse_template(
                     ui,
                     &language,
@@ -195,15 +436,32 @@ fn do_op_log(
         )?;
     }
     let limit = args.limit.or(args.deprecated_limit).unwrap_or(usize::MAX);
-    let iter = op_walk::walk_ancestors(slice::from_ref(current_op)).take(limit);
+    let iter: Box<dyn Iterator<Item = Result<OperationWithEdges, CommandError>>> =
+        if filter.is_empty() {
+            Box::new(
+                op_walk::walk_ancestors(slice::from_ref(current_op))
+                    .take(limit)
+                    .map_ok(|op| {
+                        let edges = op
+                            .parent_ids()
+                            .iter()
+                            .map(|id| Edge::Direct(id.clone()))
+                            .collect_vec();
+                        (op, edges)
+                    })
+                    .map(|result| result.map_err(CommandError::from)),
+            )
+        } else {
+            Box::new(
+                walk_filtered_ops(current_op, filter, limit)?
+                    .into_iter()
+                    .map(Ok),
+            )
+        };
     if !args.no_graph {
-        let mut graph = get_graphlog(graph_style, formatter.raw());
-        for op in iter {
-            let op = op?;
-            let mut edges = vec![];
-            for id in op.parent_ids() {
-                edges.push(Edge::Direct(id.clone()));
-            }
+        let mut graph = get_graphlog(graph_style, formatter);
+        for op_and_edges in iter {
+            let (op, edges) = op_and_edges?;
             let mut buffer = vec![];
             let within_graph = with_content_format.sub_width(graph.width(op.id(), &edges));
             within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
@@ -225,8 +483,8 @@ fn do_op_log(
             )?;
         }
     } else {
-        for op in iter {
-            let op = op?;
+        for op_and_edges in iter {
+            let (op, _) = op_and_edges?;
             with_content_format.write(formatter, |formatter| template.format(&op, formatter))?;
             if let Some(show) = &maybe_show_op_diff {
                 show(ui, formatter, &op, &with_content_format)?;
@@ -14,7 +14,16 @@
 
 mod abandon;
 mod diff;
+mod export;
+mod fetch;
+mod import;
 mod log;
+mod protect;
+mod prune;
+mod push;
+mod reconcile;
+pub mod redo;
+mod replay;
 mod restore;
 mod show;
 pub mod undo;
@@ -24,8 +33,28 @@ use abandon::OperationAbandonArgs;
 use clap::Subcommand;
 use diff::cmd_op_diff;
 use diff::OperationDiffArgs;
+use export::cmd_op_export;
+use export::OperationExportArgs;
+use fetch::cmd_op_fetch;
+use fetch::OperationFetchArgs;
+use import::cmd_op_import;
+use import::OperationImportArgs;
 use log::cmd_op_log;
 use log::OperationLogArgs;
+use protect::cmd_op_protect;
+use protect::cmd_op_unprotect;
+use protect::OperationProtectArgs;
+use protect::OperationUnprotectArgs;
+use prune::cmd_op_prune;
+use prune::OperationPruneArgs;
+use push::cmd_op_push;
+use push::OperationPushArgs;
+use reconcile::cmd_op_reconcile;
+use reconcile::OperationReconcileArgs;
+use redo::cmd_op_redo;
+use redo::OperationRedoArgs;
+use replay::cmd_op_replay;
+use replay::OperationReplayArgs;
 use restore::cmd_op_restore;
 use restore::OperationRestoreArgs;
 use show::cmd_op_show;
@@ -33,8 +62,25 @@ use show::OperationShowArgs;
 use undo::cmd_op_undo;
 use undo::OperationUndoArgs;
 
+use std::collections::HashSet;
+use std::iter;
+use std::path::Path;
+use std::slice;
+
+use itertools::Itertools as _;
+use jj_lib::git::GitOpLogError;
+use jj_lib::op_store::OperationId;
+use jj_lib::op_walk;
+use jj_lib::operation::Operation;
+use jj_lib::protected_ops;
+use jj_lib::workspace::Workspace;
+
+use crate::cli_util::short_operation_hash;
 use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
+use crate::commands::git::map_git_error;
 use crate::ui::Ui;
 
 /// Commands for working with the operation log
@@ -45,10 +91,21 @@ use crate::ui::Ui;
 pub enum OperationCommand {
     Abandon(OperationAbandonArgs),
     Diff(OperationDiffArgs),
+    Export(OperationExportArgs),
+    Fetch(OperationFetchArgs),
+    Import(OperationImportArgs),
     Log(OperationLogArgs),
+    Protect(OperationProtectArgs),
+    Prune(OperationPruneArgs),
+    Push(OperationPushArgs),
+    Reconcile(OperationReconcileArgs),
+    Redo(OperationRedoArgs),
+    Replay(OperationReplayArgs),
     Restore(OperationRestoreArgs),
     Show(OperationShowArgs),
+    #[command(visible_alias = "revert")]
     Undo(OperationUndoArgs),
+    Unprotect(OperationUnprotectArgs),
 }
 
 pub fn cmd_operation(
@@ -59,10 +116,20 @@ pub fn cmd_operation(
     match subcommand {
         OperationCommand::Abandon(args) => cmd_op_abandon(ui, command, args),
         OperationCommand::Diff(args) => cmd_op_diff(ui, command, args),
+        OperationCommand::Export(args) => cmd_op_export(ui, command, args),
+        OperationCommand::Fetch(args) => cmd_op_fetch(ui, command, args),
+        OperationCommand::Import(args) => cmd_op_import(ui, command, args),
         OperationCommand::Log(args) => cmd_op_log(ui, command, args),
+        OperationCommand::Protect(args) => cmd_op_protect(ui, command, args),
+        OperationCommand::Prune(args) => cmd_op_prune(ui, command, args),
+        OperationCommand::Push(args) => cmd_op_push(ui, command, args),
+        OperationCommand::Reconcile(args) => cmd_op_reconcile(ui, command, args),
+        OperationCommand::Redo(args) => cmd_op_redo(ui, command, args),
+        OperationCommand::Replay(args) => cmd_op_replay(ui, command, args),
         OperationCommand::Restore(args) => cmd_op_restore(ui, command, args),
         OperationCommand::Show(args) => cmd_op_show(ui, command, args),
         OperationCommand::Undo(args) => cmd_op_undo(ui, command, args),
+        OperationCommand::Unprotect(args) => cmd_op_unprotect(ui, command, args),
     }
 }
 
@@ -102,5 +169,96 @@ fn view_with_desired_portions_restored(
         git_refs: current_view.git_refs.clone(),
         git_head: current_view.git_head.clone(),
         wc_commit_ids: repo_source.wc_commit_ids.clone(),
+        bisect_refs: repo_source.bisect_refs.clone(),
+        stash_refs: repo_source.stash_refs.clone(),
+    }
+}
+
+/// Replaces the `old_head_ops` with the operations of `new_head_ids`, which
+/// were rewritten from them, and updates the operation of the working copy.
+fn update_op_heads(
+    ui: &Ui,
+    command: &CommandHelper,
+    workspace: &mut Workspace,
+    old_head_ops: &[Operation],
+    new_head_ids: &[OperationId],
+) -> Result<(), CommandError> {
+    let op_heads_store = workspace.repo_loader().op_heads_store().clone();
+    let reparented_head_ops = || iter::zip(old_head_ops, new_head_ids);
+    for (old, new_id) in reparented_head_ops().filter(|&(old, new_id)| old.id() != new_id) {
+        op_heads_store.update_op_heads(slice::from_ref(old.id()), new_id);
+    }
+    // Remap the operation id of the current workspace. If there were any
+    // divergent operations, user will need to re-abandon their ancestors.
+    if !command.global_args().ignore_working_copy {
+        let mut locked_ws = workspace.start_working_copy_mutation()?;
+        let old_op_id = locked_ws.locked_wc().old_operation_id();
+        if let Some((_, new_id)) = reparented_head_ops().find(|(old, _)| old.id() == old_op_id) {
+            locked_ws.finish(new_id.clone())?
+        } else {
+            writeln!(
+                ui.warning_default(),
+                "The working copy operation {} is not updated because it differs from the repo {}.",
+                short_operation_hash(old_op_id),
+                old_head_ops
+                    .iter()
+                    .map(|op| short_operation_hash(op.id()))
+                    .join(", "),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the ids of `head_ops` and their ancestors, excluding `root_ops`
+/// and their ancestors.
+fn operation_ids_in_range(
+    root_ops: &[Operation],
+    head_ops: &[Operation],
+) -> Result<HashSet<OperationId>, CommandError> {
+    let root_ids: HashSet<_> = op_walk::walk_ancestors(root_ops)
+        .map_ok(|op| op.id().clone())
+        .try_collect()?;
+    let ids = op_walk::walk_ancestors(head_ops)
+        .map_ok(|op| op.id().clone())
+        .filter_ok(|id| !root_ids.contains(id))
+        .try_collect()?;
+    Ok(ids)
+}
+
+/// Fails if any of the operations returned by `op_ids` is protected, unless
+/// `force` is set. `op_ids` isn't called if there are no protected operations.
+fn check_protected_operations(
+    repo_path: &Path,
+    op_ids: impl FnOnce() -> Result<HashSet<OperationId>, CommandError>,
+    force: bool,
+) -> Result<(), CommandError> {
+    if force {
+        return Ok(());
+    }
+    let protected_ids = protected_ops::read_protected_operations(repo_path)?;
+    if protected_ids.is_empty() {
+        return Ok(());
+    }
+    let op_ids = op_ids()?;
+    if let Some(id) = protected_ids.iter().sorted().find(|id| op_ids.contains(id)) {
+        return Err(user_error_with_hint(
+            format!("Operation {} is protected", short_operation_hash(id)),
+            "Use --force to proceed anyway, or `jj op unprotect` to remove the protection.",
+        ));
+    }
+    Ok(())
+}
+
+fn map_op_log_error(err: GitOpLogError) -> CommandError {
+    match err {
+        GitOpLogError::RefUpdateRejected(_) => user_error_with_hint(
+            err,
+            "Try fetching the operation log from the remote with `jj op fetch` first.",
+        ),
+        GitOpLogError::OpStore(err) => err.into(),
+        GitOpLogError::Backend(err) => err.into(),
+        GitOpLogError::InternalGitError(err) => map_git_error(err),
+        _ => user_error(err),
     }
 }
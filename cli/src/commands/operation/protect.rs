@@ -0,0 +1,134 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::op_store::OpStoreError;
+use jj_lib::operation::Operation;
+use jj_lib::protected_ops;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Protect operations from being abandoned or undone
+///
+/// `jj op abandon`, `jj op undo`, and `jj op restore` refuse to abandon or
+/// undo a protected operation unless `--force` is given. `jj op prune` keeps
+/// protected operations instead of squashing them.
+///
+/// With no operations given, the protected operations are listed.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationProtectArgs {
+    /// The operations to protect
+    operations: Vec<String>,
+}
+
+/// Remove the protection from operations
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationUnprotectArgs {
+    /// The operations to unprotect
+    #[arg(required = true)]
+    operations: Vec<String>,
+}
+
+pub fn cmd_op_protect(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationProtectArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo_path = workspace_command.repo_path();
+    let mut protected_ids = protected_ops::read_protected_operations(repo_path)?;
+    if args.operations.is_empty() {
+        let repo_loader = workspace_command.repo().loader();
+        let op_store = repo_loader.op_store();
+        let template = workspace_command.operation_summary_template();
+        ui.request_pager();
+        let mut formatter = ui.stdout_formatter();
+        for id in protected_ids.iter().sorted() {
+            // The operation may have been garbage collected.
+            let data = match op_store.read_operation(id) {
+                Ok(data) => data,
+                Err(OpStoreError::ObjectNotFound { .. }) => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let op = Operation::new(op_store.clone(), id.clone(), data);
+            template.format(&op, formatter.as_mut())?;
+            writeln!(formatter)?;
+        }
+        return Ok(());
+    }
+
+    let ops: Vec<_> = args
+        .operations
+        .iter()
+        .map(|op_str| workspace_command.resolve_single_op(op_str))
+        .try_collect()?;
+    let mut new_ops = vec![];
+    for op in ops {
+        if protected_ids.insert(op.id().clone()) {
+            new_ops.push(op);
+        }
+    }
+    protected_ops::write_protected_operations(repo_path, &protected_ids)?;
+    write_ops_summary(ui, &workspace_command, "Protected", &new_ops)
+}
+
+pub fn cmd_op_unprotect(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationUnprotectArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo_path = workspace_command.repo_path();
+    let mut protected_ids = protected_ops::read_protected_operations(repo_path)?;
+    let ops: Vec<_> = args
+        .operations
+        .iter()
+        .map(|op_str| workspace_command.resolve_single_op(op_str))
+        .try_collect()?;
+    let mut removed_ops = vec![];
+    for op in ops {
+        if protected_ids.remove(op.id()) {
+            removed_ops.push(op);
+        }
+    }
+    protected_ops::write_protected_operations(repo_path, &protected_ids)?;
+    write_ops_summary(ui, &workspace_command, "Unprotected", &removed_ops)
+}
+
+fn write_ops_summary(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    verb: &str,
+    ops: &[Operation],
+) -> Result<(), CommandError> {
+    let Some(mut formatter) = ui.status_formatter() else {
+        return Ok(());
+    };
+    if ops.is_empty() {
+        writeln!(formatter, "Nothing changed.")?;
+        return Ok(());
+    }
+    let template = workspace_command.operation_summary_template();
+    for op in ops {
+        write!(formatter, "{verb} operation ")?;
+        template.format(op, formatter.as_mut())?;
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,136 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write as _;
+use std::iter;
+
+use jj_lib::backend::Timestamp;
+use jj_lib::op_walk;
+use jj_lib::protected_ops;
+
+use super::update_op_heads;
+use crate::cli_util::CommandHelper;
+use crate::command_error::cli_error;
+use crate::command_error::config_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Squash old operations into periodic snapshots
+///
+/// Operations done in the last `op-store.retention.keep-recent-days` days are
+/// kept as they are. Of the older operations, only the last one in each period
+/// of `op-store.retention.snapshot-interval-days` days is kept. Since each
+/// operation records the whole state of the repo, the kept operations still
+/// contain the changes of the squashed ones, but those can no longer be undone
+/// individually. Protected operations (see `jj op protect`) are always kept.
+///
+/// The squashed operations, and the commits only they referred to, can later
+/// be garbage collected by using `jj util gc` command.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationPruneArgs {}
+
+pub fn cmd_op_prune(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &OperationPruneArgs,
+) -> Result<(), CommandError> {
+    // It doesn't make sense to create divergent operations that will be merged
+    // with the current head.
+    if command.global_args().at_operation.is_some() {
+        return Err(cli_error("--at-op is not respected"));
+    }
+    let settings = command.settings();
+    let keep_recent_days: i64 = settings
+        .config()
+        .get("op-store.retention.keep-recent-days")?;
+    let snapshot_interval_days: i64 = settings
+        .config()
+        .get("op-store.retention.snapshot-interval-days")?;
+    if keep_recent_days < 0 {
+        return Err(config_error(
+            "op-store.retention.keep-recent-days must not be negative",
+        ));
+    }
+    if snapshot_interval_days <= 0 {
+        return Err(config_error(
+            "op-store.retention.snapshot-interval-days must be positive",
+        ));
+    }
+    let now = settings
+        .operation_timestamp()
+        .unwrap_or_else(Timestamp::now)
+        .timestamp
+        .0;
+    let keep_after = now - keep_recent_days * MILLIS_PER_DAY;
+
+    let mut workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op_store = repo_loader.op_store();
+    let current_head_ops =
+        op_walk::get_current_head_ops(op_store, repo_loader.op_heads_store().as_ref())?;
+
+    // Keep the last of the old operations in each period.
+    let mut last_in_period = HashMap::new();
+    for op in op_walk::walk_ancestors(&current_head_ops) {
+        let op = op?;
+        let end_time = op.metadata().end_time.timestamp.0;
+        if end_time >= keep_after {
+            continue;
+        }
+        let period = end_time.div_euclid(snapshot_interval_days * MILLIS_PER_DAY);
+        last_in_period
+            .entry(period)
+            .and_modify(|(last_time, last_id)| {
+                if end_time > *last_time {
+                    *last_time = end_time;
+                    *last_id = op.id().clone();
+                }
+            })
+            .or_insert_with(|| (end_time, op.id().clone()));
+    }
+    let snapshot_ids: HashSet<_> = last_in_period.into_values().map(|(_, id)| id).collect();
+    let protected_ids = protected_ops::read_protected_operations(workspace.repo_path())?;
+    let stats = op_walk::squash_operations(op_store.as_ref(), &current_head_ops, |op| {
+        op.metadata().end_time.timestamp.0 >= keep_after
+            || snapshot_ids.contains(op.id())
+            || protected_ids.contains(op.id())
+    })?;
+    if iter::zip(&current_head_ops, &stats.new_head_ids).all(|(old, new_id)| old.id() == new_id) {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    writeln!(
+        ui.status(),
+        "Squashed {} operations and reparented {} descendant operations.",
+        stats.unreachable_count,
+        stats.rewritten_count,
+    )?;
+    update_op_heads(
+        ui,
+        command,
+        &mut workspace,
+        &current_head_ops,
+        &stats.new_head_ids,
+    )?;
+    protected_ops::update_protected_operations(
+        workspace.repo_path(),
+        &stats.rewritten_ids,
+        &HashSet::new(),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::io::Write as _;
+use std::slice;
+
+use jj_lib::git;
+use jj_lib::repo::Repo as _;
+
+use super::map_op_log_error;
+use crate::cli_util::CommandHelper;
+use crate::command_error::cli_error;
+use crate::command_error::CommandError;
+use crate::commands::git::push::get_default_push_remote;
+use crate::git_util::get_git_repo;
+use crate::git_util::with_remote_git_callbacks;
+use crate::ui::Ui;
+
+/// Push the operation log to a Git remote
+///
+/// The operations and the views they point to are stored in a Git commit, which
+/// is pushed to the hidden `refs/jj/op-log` ref on the remote along with the
+/// commits the views refer to. Use `jj op fetch` on another machine to add the
+/// pushed operations to its operation log.
+///
+/// The push is rejected if the operation log on the remote has been updated
+/// since it was last fetched.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationPushArgs {
+    /// The remote to push to (only named remotes are supported)
+    ///
+    /// Defaults to the `git.push` setting, like `jj git push`.
+    #[arg(long)]
+    remote: Option<String>,
+}
+
+pub fn cmd_op_push(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationPushArgs,
+) -> Result<(), CommandError> {
+    // The whole operation log is pushed, not the part up to another operation.
+    if command.global_args().at_operation.is_some() {
+        return Err(cli_error("--at-op is not respected"));
+    }
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let git_repo = get_git_repo(repo.store())?;
+    let remote = match &args.remote {
+        Some(remote) => remote.clone(),
+        None => get_default_push_remote(ui, command.settings(), &git_repo)?,
+    };
+    with_remote_git_callbacks(ui, None, |cb| {
+        git::push_operations(
+            &git_repo,
+            &remote,
+            slice::from_ref(repo.operation()),
+            &command.settings().signature(),
+            cb,
+            &command.settings().git_settings(),
+        )
+    })
+    .map_err(map_op_log_error)?;
+    writeln!(ui.status(), "Pushed the operation log to remote {remote}")?;
+    Ok(())
+}
@@ -0,0 +1,150 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::op_store::RefTarget;
+use jj_lib::op_walk;
+use jj_lib::repo::Repo as _;
+use jj_lib::transaction::Transaction;
+
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::start_repo_transaction;
+use crate::cli_util::CommandHelper;
+use crate::command_error::cli_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Merge divergent operations into one
+///
+/// Divergent operations are created when commands run concurrently, or when a
+/// mutating command is run with `--at-op` at an earlier operation. Any command
+/// merges them automatically when it loads the repo, in which case bookmarks
+/// that were moved differently by the divergent operations become conflicted.
+///
+/// This command merges the divergent operations too, but asks which revision
+/// each newly conflicted bookmark should point to. Answer `k` to keep a
+/// bookmark conflicted. If the output is not connected to a terminal, all
+/// conflicts are kept.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationReconcileArgs {}
+
+pub fn cmd_op_reconcile(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &OperationReconcileArgs,
+) -> Result<(), CommandError> {
+    // The head operations are merged by this command, so loading the repo at
+    // another operation makes no sense.
+    if command.global_args().at_operation.is_some() {
+        return Err(cli_error("--at-op is not respected"));
+    }
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let mut head_ops = op_walk::get_current_head_ops(
+        repo_loader.op_store(),
+        repo_loader.op_heads_store().as_ref(),
+    )?;
+    if head_ops.len() < 2 {
+        writeln!(
+            ui.status(),
+            "There are no divergent operations to reconcile."
+        )?;
+        return Ok(());
+    }
+
+    // Bookmarks that were already conflicted before the merge are left alone.
+    let mut conflicted_before = HashSet::new();
+    for op in &head_ops {
+        for (name, target) in op.view()?.local_bookmarks() {
+            if target.has_conflict() {
+                conflicted_before.insert(name.to_owned());
+            }
+        }
+    }
+
+    let num_ops = head_ops.len();
+    let other_ops = head_ops.split_off(1);
+    let base_repo = repo_loader.load_at(&head_ops[0])?;
+    let mut tx = start_repo_transaction(&base_repo, command.settings(), command.string_args());
+    for other_op in other_ops {
+        tx.merge_operation(other_op)?;
+        let num_rebased = tx.repo_mut().rebase_descendants(command.settings())?;
+        if num_rebased > 0 {
+            writeln!(
+                ui.status(),
+                "Rebased {num_rebased} descendant commits onto commits rewritten by other \
+                 operation"
+            )?;
+        }
+    }
+    resolve_bookmark_conflicts(ui, &mut tx, &conflicted_before)?;
+    tx.commit("reconcile divergent operations");
+    writeln!(ui.status(), "Reconciled {num_ops} divergent operations.")?;
+    Ok(())
+}
+
+/// Asks which of the conflicting targets each newly conflicted bookmark should
+/// point to.
+fn resolve_bookmark_conflicts(
+    ui: &Ui,
+    tx: &mut Transaction,
+    conflicted_before: &HashSet<String>,
+) -> Result<(), CommandError> {
+    let conflicted_bookmarks = tx
+        .repo()
+        .view()
+        .local_bookmarks()
+        .filter(|(name, target)| target.has_conflict() && !conflicted_before.contains(*name))
+        .map(|(name, target)| {
+            let added_ids: Vec<CommitId> = target.added_ids().unique().cloned().collect();
+            (name.to_owned(), added_ids)
+        })
+        .collect_vec();
+    for (name, added_ids) in conflicted_bookmarks {
+        writeln!(
+            ui.stdout(),
+            "Bookmark {name} was moved to different revisions by the divergent operations:"
+        )?;
+        for (i, commit_id) in added_ids.iter().enumerate() {
+            let commit = tx.repo().store().get_commit(commit_id)?;
+            let subject = commit.description().lines().next().unwrap_or_default();
+            writeln!(
+                ui.stdout(),
+                "{}: {} {subject}",
+                i + 1,
+                short_commit_hash(commit_id)
+            )?;
+        }
+        let mut choices = (1..=added_ids.len()).map(|i| i.to_string()).collect_vec();
+        choices.push("k".to_owned());
+        let choice = ui.prompt_choice(
+            &format!(
+                "Enter the number of the revision {name} should point to, or \"k\" to keep the \
+                 conflict"
+            ),
+            &choices,
+            Some("k"),
+        )?;
+        if let Ok(index) = choice.parse::<usize>() {
+            let commit_id = added_ids[index - 1].clone();
+            tx.repo_mut()
+                .set_local_bookmark_target(&name, RefTarget::normal(commit_id));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,130 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::slice;
+
+use jj_lib::object_id::ObjectId;
+use jj_lib::op_walk;
+use jj_lib::operation::Operation;
+use jj_lib::repo::Repo;
+
+use super::view_with_desired_portions_restored;
+use super::UndoWhatToRestore;
+use super::DEFAULT_UNDO_WHAT;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+const REDO_DESCRIPTION_PREFIX: &str = "redo the undo operation ";
+
+/// Reapply the changes undone by the last `jj undo`
+///
+/// This finds the most recent undo operation that hasn't been redone yet, and
+/// creates a new operation that applies its inverse. Operations done after the
+/// undo are kept, but their changes might conflict with the redone changes.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationRedoArgs {
+    /// What portions of the local state to restore (can be repeated)
+    ///
+    /// This option is EXPERIMENTAL.
+    #[arg(long, value_enum, default_values_t = DEFAULT_UNDO_WHAT)]
+    what: Vec<UndoWhatToRestore>,
+}
+
+pub fn cmd_op_redo(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationRedoArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let current_op = workspace_command.repo().operation().clone();
+
+    // Find the latest undo operation that hasn't been redone, counting the
+    // other operations done since.
+    let mut redone_op_ids = HashSet::new();
+    let mut num_intervening_ops = 0;
+    let mut undo_op = None;
+    for op in op_walk::walk_ancestors(slice::from_ref(&current_op)) {
+        let op = op?;
+        let description = &op.metadata().description;
+        if let Some(hex) = description.strip_prefix(REDO_DESCRIPTION_PREFIX) {
+            redone_op_ids.insert(hex.to_owned());
+        } else if is_undo_operation(&op) {
+            if !redone_op_ids.contains(&op.id().hex()) {
+                undo_op = Some(op);
+                break;
+            }
+        } else if op.parent_ids().is_empty() {
+            break;
+        } else {
+            num_intervening_ops += 1;
+        }
+    }
+    let Some(undo_op) = undo_op else {
+        return Err(user_error_with_hint(
+            "Nothing to redo",
+            "Only operations undone by `jj undo` can be redone.",
+        ));
+    };
+    let mut parent_ops = undo_op.parents();
+    let Some(parent_op) = parent_ops.next().transpose()? else {
+        return Err(user_error("Cannot redo repo initialization"));
+    };
+    if parent_ops.next().is_some() {
+        return Err(user_error("Cannot redo a merge operation"));
+    }
+    drop(parent_ops);
+    if num_intervening_ops > 0 {
+        writeln!(
+            ui.warning_default(),
+            "{num_intervening_ops} other operations were done since the undo. Their changes are \
+             kept, but might conflict with the redone changes."
+        )?;
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let repo_loader = tx.base_repo().loader();
+    let undo_repo = repo_loader.load_at(&undo_op)?;
+    let parent_repo = repo_loader.load_at(&parent_op)?;
+    tx.repo_mut().merge(&undo_repo, &parent_repo);
+    let new_view = view_with_desired_portions_restored(
+        tx.repo().view().store_view(),
+        tx.base_repo().view().store_view(),
+        &args.what,
+    );
+    tx.repo_mut().set_view(new_view);
+    tx.finish(
+        ui,
+        format!("{REDO_DESCRIPTION_PREFIX}{}", undo_op.id().hex()),
+    )?;
+
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Redid the changes undone by operation ")?;
+        let template = workspace_command.operation_summary_template();
+        template.format(&undo_op, &mut *formatter)?;
+        writeln!(formatter)?;
+    }
+
+    Ok(())
+}
+
+/// Whether the operation was created by `jj undo`.
+fn is_undo_operation(op: &Operation) -> bool {
+    let description = &op.metadata().description;
+    description.starts_with("undo operation ")
+        || (description.starts_with("undo ") && description.contains(" operations ending at "))
+}
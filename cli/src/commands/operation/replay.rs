@@ -0,0 +1,130 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::io::Write as _;
+use std::process::Command;
+
+use itertools::Itertools as _;
+use jj_lib::op_walk;
+use jj_lib::operation::Operation;
+
+use crate::cli_util::operation_command_line;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Run the commands of later operations again
+///
+/// Runs the command lines recorded in the operations after `--from` again,
+/// from the oldest to the newest, against the current state of the repo. This
+/// is useful after `jj op restore` to an earlier operation, to reapply some of
+/// the work done after it.
+///
+/// Each command is confirmed before it's run. Answer `n` to skip it, or `q` to
+/// stop. Snapshots of the working copy, operations without a recorded command
+/// line, and commands that work on the operation log itself, like `jj undo`,
+/// are skipped. The commands run in the current directory, and the ones that
+/// opened an editor open it again.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationReplayArgs {
+    /// Replay the operations after this one
+    #[arg(long)]
+    from: String,
+
+    /// Replay the operations up to and including this one
+    #[arg(long, default_value = "@")]
+    to: String,
+}
+
+pub fn cmd_op_replay(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationReplayArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let from_op = workspace_command.resolve_single_op(&args.from)?;
+    let to_op = workspace_command.resolve_single_op(&args.to)?;
+    let from_ids: HashSet<_> = op_walk::walk_ancestors(&[from_op])
+        .map_ok(|op| op.id().clone())
+        .try_collect()?;
+    let mut ops: Vec<Operation> = op_walk::walk_ancestors(&[to_op])
+        .filter_ok(|op| !from_ids.contains(op.id()))
+        .try_collect()?;
+    ops.reverse();
+
+    let commands = ops
+        .iter()
+        .filter(|op| !op.metadata().is_snapshot)
+        .filter_map(|op| replayable_args(command, op))
+        .collect_vec();
+    if commands.is_empty() {
+        writeln!(ui.status(), "No commands to replay.")?;
+        return Ok(());
+    }
+
+    let jj_path = std::env::current_exe()
+        .map_err(|err| user_error_with_message("Failed to find the jj executable", err))?;
+    let mut num_replayed = 0;
+    for (args, command_line) in commands {
+        let choice = ui.prompt_choice(
+            &format!("Run `{command_line}`? (y/n/q)"),
+            &["y", "n", "q"],
+            Some("n"),
+        )?;
+        match choice.as_str() {
+            "y" => {}
+            "q" => break,
+            _ => continue,
+        }
+        let status = Command::new(&jj_path)
+            .args(&args[1..])
+            .current_dir(command.cwd())
+            .status()
+            .map_err(|err| {
+                user_error_with_message(format!("Failed to run `{command_line}`"), err)
+            })?;
+        if !status.success() {
+            return Err(user_error(format!(
+                "`{command_line}` failed ({status}), stopping the replay"
+            )));
+        }
+        num_replayed += 1;
+    }
+    writeln!(ui.status(), "Replayed {num_replayed} commands.")?;
+    Ok(())
+}
+
+/// Returns the recorded arguments and the command line to show for the
+/// operation, or `None` if its command shouldn't be replayed.
+fn replayable_args(command: &CommandHelper, op: &Operation) -> Option<(Vec<String>, String)> {
+    let args = &op.metadata().args;
+    if args.is_empty() {
+        return None;
+    }
+    // Command aliases can't be parsed without the config, but they're replayed
+    // anyway.
+    if let Ok(matches) = command.app().clone().try_get_matches_from(args) {
+        if matches!(
+            matches.subcommand_name(),
+            Some("operation" | "undo" | "redo")
+        ) {
+            return None;
+        }
+    }
+    let command_line = operation_command_line(op.metadata())?;
+    Some((args.clone(), command_line))
+}
@@ -12,8 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::slice;
+
 use jj_lib::object_id::ObjectId;
 
+use super::check_protected_operations;
+use super::operation_ids_in_range;
 use super::view_with_desired_portions_restored;
 use super::UndoWhatToRestore;
 use super::DEFAULT_UNDO_WHAT;
@@ -25,6 +29,9 @@ use crate::ui::Ui;
 ///
 /// This restores the repo to the state at the specified operation, effectively
 /// undoing all later operations. It does so by creating a new operation.
+///
+/// If any of the undone operations is protected (see `jj op protect`),
+/// `--force` is required.
 #[derive(clap::Args, Clone, Debug)]
 pub struct OperationRestoreArgs {
     /// The operation to restore to
@@ -39,6 +46,10 @@ pub struct OperationRestoreArgs {
     /// This option is EXPERIMENTAL.
     #[arg(long, value_enum, default_values_t = DEFAULT_UNDO_WHAT)]
     what: Vec<UndoWhatToRestore>,
+
+    /// Restore even if some of the undone operations are protected
+    #[arg(long)]
+    force: bool,
 }
 
 pub fn cmd_op_restore(
@@ -48,6 +59,16 @@ pub fn cmd_op_restore(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     let target_op = workspace_command.resolve_single_op(&args.operation)?;
+    check_protected_operations(
+        workspace_command.repo_path(),
+        || {
+            operation_ids_in_range(
+                slice::from_ref(&target_op),
+                slice::from_ref(workspace_command.repo().operation()),
+            )
+        },
+        args.force,
+    )?;
     let mut tx = workspace_command.start_transaction();
     let new_view = view_with_desired_portions_restored(
         target_op.view()?.store_view(),
@@ -14,6 +14,7 @@
 
 use itertools::Itertools;
 
+use super::diff::get_commit_summary_template_text;
 use super::diff::show_op_diff;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
@@ -61,11 +62,9 @@ pub fn cmd_op_show(
 
     let id_prefix_context = workspace_env.new_id_prefix_context();
     let commit_summary_template = {
-        let language = workspace_env.commit_template_language(repo.as_ref(), &id_prefix_context);
-        let text = command
-            .settings()
-            .config()
-            .get_string("templates.commit_summary")?;
+        let language =
+            workspace_env.commit_template_language(repo.as_ref(), &op, &id_prefix_context);
+        let text = get_commit_summary_template_text(command.settings())?;
         workspace_env.parse_template(ui, &language, &text, CommitTemplateLanguage::wrap_commit)?
     };
 
@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::mem;
+
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
 
+use super::check_protected_operations;
 use super::view_with_desired_portions_restored;
 use super::UndoWhatToRestore;
 use super::DEFAULT_UNDO_WHAT;
@@ -27,6 +30,15 @@ use crate::ui::Ui;
 ///
 /// This undoes an individual operation by applying the inverse of the
 /// operation.
+///
+/// Unlike `jj op restore`, the operations done since are kept. For example, if
+/// a revision was abandoned some operations ago, `jj op revert` with that
+/// operation brings it back without affecting the later operations.
+///
+/// Use `jj redo` to reapply the changes undone by the last undo.
+///
+/// Protected operations (see `jj op protect`) can only be undone with
+/// `--force`.
 #[derive(clap::Args, Clone, Debug)]
 pub struct OperationUndoArgs {
     /// The operation to undo
@@ -35,11 +47,22 @@ pub struct OperationUndoArgs {
     #[arg(default_value = "@")]
     operation: String,
 
+    /// Undo this many operations, ending at the given one
+    ///
+    /// The operations are undone together in a single new operation, so they
+    /// can be redone together too.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    steps: u32,
+
     /// What portions of the local state to restore (can be repeated)
     ///
     /// This option is EXPERIMENTAL.
     #[arg(long, value_enum, default_values_t = DEFAULT_UNDO_WHAT)]
     what: Vec<UndoWhatToRestore>,
+
+    /// Undo the operations even if some of them are protected
+    #[arg(long)]
+    force: bool,
 }
 
 pub fn cmd_op_undo(
@@ -49,13 +72,36 @@ pub fn cmd_op_undo(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     let bad_op = workspace_command.resolve_single_op(&args.operation)?;
-    let mut parent_ops = bad_op.parents();
-    let Some(parent_op) = parent_ops.next().transpose()? else {
-        return Err(user_error("Cannot undo repo initialization"));
-    };
-    if parent_ops.next().is_some() {
-        return Err(user_error("Cannot undo a merge operation"));
+    // The operations to undo, from the newest to the oldest.
+    let mut bad_ops = vec![];
+    let mut parent_op = bad_op.clone();
+    let root_op_id = workspace_command
+        .repo()
+        .op_store()
+        .root_operation_id()
+        .clone();
+    for _ in 0..args.steps {
+        let mut parent_ops = parent_op.parents();
+        // Undoing the operation that initialized the repo would leave the
+        // workspace without a working-copy commit.
+        let Some(next_parent_op) = parent_ops
+            .next()
+            .transpose()?
+            .filter(|op| *op.id() != root_op_id)
+        else {
+            return Err(user_error("Cannot undo repo initialization"));
+        };
+        if parent_ops.next().is_some() {
+            return Err(user_error("Cannot undo a merge operation"));
+        }
+        drop(parent_ops);
+        bad_ops.push(mem::replace(&mut parent_op, next_parent_op));
     }
+    check_protected_operations(
+        workspace_command.repo_path(),
+        || Ok(bad_ops.iter().map(|op| op.id().clone()).collect()),
+        args.force,
+    )?;
 
     let mut tx = workspace_command.start_transaction();
     let repo_loader = tx.base_repo().loader();
@@ -68,13 +114,31 @@ pub fn cmd_op_undo(
         &args.what,
     );
     tx.repo_mut().set_view(new_view);
-    tx.finish(ui, format!("undo operation {}", bad_op.id().hex()))?;
+    let description = if bad_ops.len() == 1 {
+        format!("undo operation {}", bad_op.id().hex())
+    } else {
+        format!(
+            "undo {} operations ending at {}",
+            bad_ops.len(),
+            bad_op.id().hex()
+        )
+    };
+    tx.finish(ui, description)?;
 
     if let Some(mut formatter) = ui.status_formatter() {
-        write!(formatter, "Undid operation ")?;
         let template = workspace_command.operation_summary_template();
-        template.format(&bad_op, &mut *formatter)?;
-        writeln!(formatter)?;
+        if bad_ops.len() == 1 {
+            write!(formatter, "Undid operation ")?;
+            template.format(&bad_op, &mut *formatter)?;
+            writeln!(formatter)?;
+        } else {
+            writeln!(formatter, "Undid {} operations:", bad_ops.len())?;
+            for op in &bad_ops {
+                write!(formatter, "  ")?;
+                template.format(op, &mut *formatter)?;
+                writeln!(formatter)?;
+            }
+        }
     }
 
     Ok(())
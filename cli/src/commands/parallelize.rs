@@ -51,11 +51,17 @@ use crate::ui::Ui;
 /// Therefore, `jj parallelize '1 | 3'` is a no-op. That's because 2, which is
 /// not in the target set, was a descendant of 1 before, so it remains a
 /// descendant, and it was an ancestor of 3 before, so it remains an ancestor.
+///
+/// Use `jj serialize` to turn siblings back into a linear stack.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub(crate) struct ParallelizeArgs {
     /// Revisions to parallelize
     revisions: Vec<RevisionArg>,
+    /// Show the revisions that would become siblings, without changing
+    /// anything
+    #[arg(long)]
+    dry_run: bool,
 }
 
 #[instrument(skip_all)]
@@ -71,6 +77,17 @@ pub(crate) fn cmd_parallelize(
         .parse_union_revsets(ui, &args.revisions)?
         .evaluate_to_commits()?
         .try_collect()?;
+    if args.dry_run {
+        if let Some(mut formatter) = ui.status_formatter() {
+            writeln!(formatter, "Would make these revisions siblings:")?;
+            for commit in &target_commits {
+                write!(formatter, "  ")?;
+                workspace_command.write_commit_summary(formatter.as_mut(), commit)?;
+                writeln!(formatter)?;
+            }
+        }
+        return Ok(());
+    }
     workspace_command.check_rewritable(target_commits.iter().ids())?;
 
     let mut tx = workspace_command.start_transaction();
@@ -13,21 +13,25 @@
 // limitations under the License.
 
 use std::borrow::Borrow;
+use std::fmt::Write as _;
 use std::io::Write;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use clap::ArgGroup;
 use indexmap::IndexSet;
+use indoc::indoc;
 use itertools::Itertools;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::commit::CommitIteratorExt;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetIteratorExt;
+use jj_lib::rewrite::merge_commit_trees;
 use jj_lib::rewrite::move_commits;
 use jj_lib::rewrite::rebase_commit_with_options;
 use jj_lib::rewrite::CommitRewriter;
@@ -35,8 +39,10 @@ use jj_lib::rewrite::EmptyBehaviour;
 use jj_lib::rewrite::MoveCommitsStats;
 use jj_lib::rewrite::RebaseOptions;
 use jj_lib::settings::UserSettings;
+use thiserror::Error;
 use tracing::instrument;
 
+use crate::cli_util::edit_temp_file;
 use crate::cli_util::short_commit_hash;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
@@ -45,6 +51,7 @@ use crate::cli_util::WorkspaceCommandTransaction;
 use crate::command_error::cli_error;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
+use crate::description_util::edit_description;
 use crate::ui::Ui;
 
 /// Move revisions to different parent(s)
@@ -130,6 +137,13 @@ use crate::ui::Ui;
 /// J          J
 /// ```
 ///
+/// With `--interactive`, the revisions to rebase are listed in an editor, parents
+/// first, as a plan of what to do with each of them. Lines can be reordered to
+/// reorder the revisions, and the command at the start of each line can be
+/// changed to edit the description of a revision, squash it into the previous
+/// one, or drop it. The revisions are then rebased onto the destination as a
+/// single stack, in the order of the plan, and their descendants follow them.
+///
 /// If a working-copy commit gets abandoned, it will be given a new, empty
 /// commit. This is true in general; it is not specific to this command.
 #[derive(clap::Args, Clone, Debug)]
@@ -207,6 +221,18 @@ pub(crate) struct RebaseArgs {
     /// parents.
     #[arg(long, conflicts_with = "revisions")]
     skip_emptied: bool,
+
+    /// Edit a plan of the rebase in an editor before running it
+    ///
+    /// Only works with `-d`. Merge commits can't be rebased interactively.
+    #[arg(
+        long,
+        short,
+        conflicts_with = "insert_after",
+        conflicts_with = "insert_before",
+        conflicts_with = "skip_emptied"
+    )]
+    interactive: bool,
 }
 
 #[instrument(skip_all)]
@@ -229,6 +255,46 @@ pub(crate) fn cmd_rebase(
         simplify_ancestor_merge: false,
     };
     let mut workspace_command = command.workspace_helper(ui)?;
+    if args.interactive {
+        let new_parents = workspace_command
+            .resolve_some_revsets_default_single(ui, &args.destination)?
+            .into_iter()
+            .collect_vec();
+        let target_commits: Vec<_> = if !args.revisions.is_empty() {
+            workspace_command
+                .parse_union_revsets(ui, &args.revisions)?
+                .evaluate_to_commits()?
+                .try_collect()?
+        } else {
+            let roots_expression = if !args.source.is_empty() {
+                let source_commits =
+                    workspace_command.resolve_some_revsets_default_single(ui, &args.source)?;
+                RevsetExpression::commits(source_commits.iter().ids().cloned().collect())
+            } else {
+                let branch_commits = if args.branch.is_empty() {
+                    IndexSet::from([workspace_command.resolve_single_rev(ui, &RevisionArg::AT)?])
+                } else {
+                    workspace_command.resolve_some_revsets_default_single(ui, &args.branch)?
+                };
+                RevsetExpression::commits(new_parents.iter().ids().cloned().collect()).range(
+                    &RevsetExpression::commits(branch_commits.iter().ids().cloned().collect()),
+                )
+            };
+            roots_expression
+                .descendants()
+                .evaluate_programmatic(workspace_command.repo().as_ref())?
+                .iter()
+                .commits(workspace_command.repo().store())
+                .try_collect()?
+        }; // in reverse topological order
+        return rebase_interactively(
+            ui,
+            command.settings(),
+            &mut workspace_command,
+            &new_parents,
+            &target_commits,
+        );
+    }
     if !args.revisions.is_empty() {
         assert_eq!(
             // In principle, `-r --skip-empty` could mean to abandon the `-r`
@@ -392,7 +458,7 @@ fn rebase_descendants(
     Ok(num_rebased)
 }
 
-fn rebase_descendants_transaction(
+pub(crate) fn rebase_descendants_transaction(
     ui: &mut Ui,
     settings: &UserSettings,
     workspace_command: &mut WorkspaceCommandHelper,
@@ -632,6 +698,304 @@ fn move_commits_transaction(
     tx.finish(ui, tx_description)
 }
 
+/// What to do with a revision in an interactive rebase.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PlanAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl PlanAction {
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "p" | "pick" => Some(PlanAction::Pick),
+            "r" | "reword" => Some(PlanAction::Reword),
+            "s" | "squash" => Some(PlanAction::Squash),
+            "f" | "fixup" => Some(PlanAction::Fixup),
+            "d" | "drop" => Some(PlanAction::Drop),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+enum RebasePlanError {
+    #[error(r#"Unknown command "{0}" in rebase plan"#)]
+    UnknownAction(String),
+    #[error(r#"Missing revision in rebase plan line: "{0}""#)]
+    MissingRevision(String),
+    #[error(r#"Revision "{0}" in rebase plan is not one of the revisions being rebased"#)]
+    UnknownRevision(String),
+    #[error(r#"Revision "{0}" in rebase plan is ambiguous"#)]
+    AmbiguousRevision(String),
+    #[error(r#"Revision "{0}" appears more than once in rebase plan"#)]
+    DuplicateRevision(String),
+    #[error(r#"Cannot squash revision "{0}" without a previous revision to squash into"#)]
+    NothingToSquashInto(String),
+}
+
+/// Parses the edited rebase plan into the actions to run, in order.
+/// `candidates` are the ids of the revisions being rebased. Revisions whose
+/// lines were removed from the plan don't appear in the result.
+fn parse_rebase_plan(
+    text: &str,
+    candidates: &[CommitId],
+) -> Result<Vec<(PlanAction, CommitId)>, RebasePlanError> {
+    let mut steps: Vec<(PlanAction, CommitId)> = vec![];
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("JJ:") {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let action_word = words.next().unwrap();
+        let action = PlanAction::parse(action_word)
+            .ok_or_else(|| RebasePlanError::UnknownAction(action_word.to_owned()))?;
+        let prefix = words
+            .next()
+            .ok_or_else(|| RebasePlanError::MissingRevision(line.to_owned()))?;
+        let mut matches = candidates.iter().filter(|id| id.hex().starts_with(prefix));
+        let commit_id = match (matches.next(), matches.next()) {
+            (Some(id), None) => id.clone(),
+            (Some(_), Some(_)) => {
+                return Err(RebasePlanError::AmbiguousRevision(prefix.to_owned()));
+            }
+            (None, _) => return Err(RebasePlanError::UnknownRevision(prefix.to_owned())),
+        };
+        if steps.iter().any(|(_, id)| *id == commit_id) {
+            return Err(RebasePlanError::DuplicateRevision(prefix.to_owned()));
+        }
+        let is_squash = matches!(action, PlanAction::Squash | PlanAction::Fixup);
+        if is_squash && !steps.iter().any(|(action, _)| *action != PlanAction::Drop) {
+            return Err(RebasePlanError::NothingToSquashInto(prefix.to_owned()));
+        }
+        steps.push((action, commit_id));
+    }
+    Ok(steps)
+}
+
+/// A commit of the new stack that hasn't been written yet, because revisions
+/// may still be squashed into it.
+struct PendingCommit {
+    sources: Vec<Commit>,
+    tree: MergedTree,
+    description: String,
+}
+
+/// Lets the user edit a plan for `target_commits` and rebases them onto
+/// `new_parents` according to it.
+fn rebase_interactively(
+    ui: &mut Ui,
+    settings: &UserSettings,
+    workspace_command: &mut WorkspaceCommandHelper,
+    new_parents: &[Commit],
+    target_commits: &[Commit],
+) -> Result<(), CommandError> {
+    if target_commits.is_empty() {
+        writeln!(ui.status(), "Nothing to rebase.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(target_commits.iter().ids())?;
+    for commit in target_commits {
+        if commit.parent_ids().len() > 1 {
+            return Err(user_error(format!(
+                "Cannot rebase merge commit {} interactively",
+                short_commit_hash(commit.id()),
+            )));
+        }
+        check_rebase_destinations(workspace_command.repo(), new_parents, commit)?;
+    }
+
+    let mut plan = String::new();
+    for commit in target_commits.iter().rev() {
+        let first_line = commit.description().lines().next().unwrap_or_default();
+        writeln!(plan, "pick {} {first_line}", short_commit_hash(commit.id())).unwrap();
+    }
+    plan.push_str(indoc! {"
+
+        JJ: Commands:
+        JJ:  p, pick   = keep the revision
+        JJ:  r, reword = keep the revision, but edit its description
+        JJ:  s, squash = squash the revision into the previous one, and edit the
+        JJ:              combined description
+        JJ:  f, fixup  = squash the revision into the previous one, keeping the
+        JJ:              description of the previous one
+        JJ:  d, drop   = abandon the revision
+        JJ:
+        JJ: The revisions are rebased onto the destination as a single stack, in
+        JJ: the order of the lines. Removing a line abandons the revision.
+        JJ: Lines starting with \"JJ:\" (like this one) are ignored.
+    "});
+    let plan = edit_temp_file(
+        "rebase plan",
+        ".jjrebase",
+        workspace_command.repo_path(),
+        &plan,
+        settings,
+    )?;
+    let candidates = target_commits.iter().ids().cloned().collect_vec();
+    let steps = parse_rebase_plan(&plan, &candidates).map_err(|err| user_error(err.to_string()))?;
+    let num_kept = steps
+        .iter()
+        .filter(|(action, _)| *action != PlanAction::Drop)
+        .count();
+    if num_kept == 0 {
+        writeln!(ui.status(), "Nothing to rebase: the plan is empty.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let store = tx.repo().store().clone();
+    let mut parent_ids = new_parents.iter().ids().cloned().collect_vec();
+    let mut parent_tree = merge_commit_trees(tx.repo(), new_parents)?;
+    let mut pending: Option<PendingCommit> = None;
+    let mut num_rewritten = 0;
+    for (action, commit_id) in &steps {
+        let commit = store.get_commit(commit_id)?;
+        match action {
+            PlanAction::Drop => {}
+            PlanAction::Pick | PlanAction::Reword => {
+                if let Some(pending) = pending.take() {
+                    parent_tree = pending.tree.clone();
+                    let new_commit = write_pending_commit(&mut tx, settings, pending, &parent_ids)?;
+                    num_rewritten += 1;
+                    parent_ids = vec![new_commit.id().clone()];
+                }
+                let tree = parent_tree.merge(&commit.parent_tree(tx.repo())?, &commit.tree()?)?;
+                let description = if *action == PlanAction::Reword {
+                    edit_description(tx.base_workspace_helper(), commit.description(), settings)?
+                } else {
+                    commit.description().to_owned()
+                };
+                pending = Some(PendingCommit {
+                    sources: vec![commit],
+                    tree,
+                    description,
+                });
+            }
+            PlanAction::Squash | PlanAction::Fixup => {
+                let pending = pending.as_mut().unwrap();
+                pending.tree = pending
+                    .tree
+                    .merge(&commit.parent_tree(tx.repo())?, &commit.tree()?)?;
+                if *action == PlanAction::Squash {
+                    pending.description = combine_descriptions(
+                        tx.base_workspace_helper(),
+                        &pending.description,
+                        commit.description(),
+                        settings,
+                    )?;
+                }
+                pending.sources.push(commit);
+            }
+        }
+    }
+    if let Some(pending) = pending.take() {
+        write_pending_commit(&mut tx, settings, pending, &parent_ids)?;
+        num_rewritten += 1;
+    }
+    // Revisions whose lines were removed are dropped too.
+    let num_dropped = target_commits.len() - num_kept;
+    for commit in target_commits {
+        let kept = steps
+            .iter()
+            .any(|(action, id)| id == commit.id() && *action != PlanAction::Drop);
+        if !kept {
+            tx.repo_mut().record_abandoned_commit(commit.id().clone());
+        }
+    }
+    let num_rebased_descendants = tx.repo_mut().rebase_descendants(settings)?;
+
+    if let Some(mut fmt) = ui.status_formatter() {
+        writeln!(fmt, "Rebased {num_rewritten} commits")?;
+        if num_kept > num_rewritten {
+            writeln!(fmt, "Squashed {} commits", num_kept - num_rewritten)?;
+        }
+        if num_dropped > 0 {
+            writeln!(fmt, "Abandoned {num_dropped} commits")?;
+        }
+        if num_rebased_descendants > 0 {
+            writeln!(fmt, "Rebased {num_rebased_descendants} descendant commits")?;
+        }
+    }
+    let tx_description = if target_commits.len() == 1 {
+        format!(
+            "rebase commit {} interactively",
+            target_commits[0].id().hex()
+        )
+    } else {
+        format!(
+            "rebase commit {} and {} more interactively",
+            target_commits[0].id().hex(),
+            target_commits.len() - 1
+        )
+    };
+    tx.finish(ui, tx_description)
+}
+
+/// Writes `pending` on top of `parent_ids`. Revisions squashed into it are
+/// abandoned, and their descendants are moved onto the new commit.
+fn write_pending_commit(
+    tx: &mut WorkspaceCommandTransaction,
+    settings: &UserSettings,
+    pending: PendingCommit,
+    parent_ids: &[CommitId],
+) -> Result<Commit, CommandError> {
+    let PendingCommit {
+        sources,
+        tree,
+        description,
+    } = pending;
+    let (first, squashed) = sources.split_first().unwrap();
+    if squashed.is_empty()
+        && first.parent_ids() == parent_ids
+        && *first.tree_id() == tree.id()
+        && first.description() == description
+    {
+        return Ok(first.clone());
+    }
+    let new_commit = tx
+        .repo_mut()
+        .rewrite_commit(settings, first)
+        .set_parents(parent_ids.to_vec())
+        .set_tree_id(tree.id())
+        .set_description(description)
+        .set_predecessors(sources.iter().ids().cloned().collect())
+        .write()?;
+    for commit in squashed {
+        tx.repo_mut().record_abandoned_commit_with_parents(
+            commit.id().clone(),
+            vec![new_commit.id().clone()],
+        );
+    }
+    Ok(new_commit)
+}
+
+/// Combines the description of a squashed revision with the one it's squashed
+/// into. The user edits the result if both are non-empty.
+fn combine_descriptions(
+    workspace_command: &WorkspaceCommandHelper,
+    destination: &str,
+    source: &str,
+    settings: &UserSettings,
+) -> Result<String, CommandError> {
+    if source.is_empty() {
+        return Ok(destination.to_owned());
+    }
+    if destination.is_empty() {
+        return Ok(source.to_owned());
+    }
+    let mut combined = "JJ: Enter a description for the combined commit.".to_string();
+    combined.push_str("\nJJ: Description from the destination commit:\n");
+    combined.push_str(destination);
+    combined.push_str("\nJJ: Description from source commit:\n");
+    combined.push_str(source);
+    edit_description(workspace_command, &combined, settings)
+}
+
 /// Ensure that there is no possible cycle between the potential children and
 /// parents of rebased commits.
 fn ensure_no_commit_loop(
@@ -670,3 +1034,62 @@ fn check_rebase_destinations(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rebase_plan() {
+        let candidates = [
+            CommitId::from_hex("aaa111"),
+            CommitId::from_hex("aaa222"),
+            CommitId::from_hex("bbb333"),
+        ];
+        let plan = indoc! {"
+            pick aaa2 second
+            JJ: a comment
+              f bbb fixup
+
+            reword aaa1
+        "};
+        assert_eq!(
+            parse_rebase_plan(plan, &candidates),
+            Ok(vec![
+                (PlanAction::Pick, candidates[1].clone()),
+                (PlanAction::Fixup, candidates[2].clone()),
+                (PlanAction::Reword, candidates[0].clone()),
+            ])
+        );
+        assert_eq!(parse_rebase_plan("", &candidates), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_parse_rebase_plan_errors() {
+        let candidates = [CommitId::from_hex("aaa111"), CommitId::from_hex("aaa222")];
+        assert_eq!(
+            parse_rebase_plan("edit aaa1", &candidates),
+            Err(RebasePlanError::UnknownAction("edit".to_owned()))
+        );
+        assert_eq!(
+            parse_rebase_plan("pick", &candidates),
+            Err(RebasePlanError::MissingRevision("pick".to_owned()))
+        );
+        assert_eq!(
+            parse_rebase_plan("pick ccc", &candidates),
+            Err(RebasePlanError::UnknownRevision("ccc".to_owned()))
+        );
+        assert_eq!(
+            parse_rebase_plan("pick aaa", &candidates),
+            Err(RebasePlanError::AmbiguousRevision("aaa".to_owned()))
+        );
+        assert_eq!(
+            parse_rebase_plan("pick aaa1\ndrop aaa111", &candidates),
+            Err(RebasePlanError::DuplicateRevision("aaa111".to_owned()))
+        );
+        assert_eq!(
+            parse_rebase_plan("drop aaa1\nsquash aaa2", &candidates),
+            Err(RebasePlanError::NothingToSquashInto("aaa2".to_owned()))
+        );
+    }
+}
@@ -12,16 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fmt;
 use std::io::Write;
+use std::num::NonZeroUsize;
 
 use itertools::Itertools;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::conflicts::extract_mode_conflict;
+use jj_lib::learned_resolutions::LearnedResolutions;
+use jj_lib::merge::Merge;
+use jj_lib::merge::MergedTreeValue;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::object_id::ObjectId;
+use jj_lib::repo_path::RepoPathBuf;
+use pollster::FutureExt as _;
 use tracing::instrument;
 
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::cli_error;
+use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
@@ -33,6 +46,18 @@ use crate::ui::Ui;
 /// Note that conflicts can also be resolved without using this command. You may
 /// edit the conflict markers in the conflicted file directly with a text
 /// editor.
+///
+/// If `merge.learn-resolutions` is enabled, the resolutions of conflicts are
+/// recorded, and reapplied when the same conflicts appear again while
+/// rebasing. Use `--forget-learned` to forget the recorded resolutions.
+///
+/// Conflicts where the sides only differ in their modes (whether the file is
+/// executable or a symlink) are listed as mode conflicts. Use
+/// `--take-mode-from` to resolve them with the mode of one of the sides.
+///
+/// Use `--take` to resolve all the conflicts at the given paths with one of
+/// their sides, for example to resolve conflicts in generated files while
+/// rebasing many commits.
 //  TODOs:
 //   - `jj resolve --editor` to resolve a conflict in the default text editor. Should work for
 //     conflicts with 3+ adds. Useful to resolve conflicts in a commit other than the current one.
@@ -49,9 +74,41 @@ pub(crate) struct ResolveArgs {
     // `diff --summary`, but should be more verbose.
     #[arg(long, short)]
     list: bool,
+    /// Forget all the learned resolutions of conflicts instead of resolving
+    /// one
+    #[arg(
+        long,
+        conflicts_with_all = ["list", "tool", "paths", "take", "take_mode_from"]
+    )]
+    forget_learned: bool,
     /// Specify 3-way merge tool to be used
     #[arg(long, conflicts_with = "list", value_name = "NAME")]
     tool: Option<String>,
+    /// Resolve all the conflicts with the given side, instead of resolving one
+    /// conflict with a merge tool
+    ///
+    /// Sides are numbered from 1, like in the conflict markers. `ours` is the
+    /// first side, which is the destination when rebasing, and `theirs` is the
+    /// last side, which is the rebased commit.
+    #[arg(
+        long,
+        conflicts_with_all = ["list", "tool", "take_mode_from"],
+        value_name = "SIDE",
+        value_parser = parse_conflict_side
+    )]
+    take: Option<ConflictSide>,
+    /// Resolve all the mode conflicts with the mode of the given side, instead
+    /// of resolving one conflict with a merge tool
+    ///
+    /// Sides are numbered from 1, like in the conflict markers. `ours` and
+    /// `theirs` can be used like with `--take`.
+    #[arg(
+        long,
+        conflicts_with_all = ["list", "tool"],
+        value_name = "SIDE",
+        value_parser = parse_conflict_side
+    )]
+    take_mode_from: Option<ConflictSide>,
     /// Restrict to these paths when searching for a conflict to resolve. We
     /// will attempt to resolve the first conflict we can find. You can use
     /// the `--list` argument to find paths to use here.
@@ -67,6 +124,12 @@ pub(crate) fn cmd_resolve(
     args: &ResolveArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
+    if args.forget_learned {
+        let learned_resolutions = LearnedResolutions::new(workspace_command.repo_path());
+        let num_forgotten = learned_resolutions.clear()?;
+        writeln!(ui.status(), "Forgot {num_forgotten} learned resolutions")?;
+        return Ok(());
+    }
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
         .to_matcher();
@@ -91,16 +154,22 @@ pub(crate) fn cmd_resolve(
         );
     };
 
-    let (repo_path, _) = conflicts.first().unwrap();
     workspace_command.check_rewritable([commit.id()])?;
-    let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
-    writeln!(
-        ui.status(),
-        "Resolving conflicts in: {}",
-        workspace_command.format_file_path(repo_path)
-    )?;
+    let new_tree_id = if let Some(side) = args.take {
+        resolve_with_side(ui, &workspace_command, &tree, &conflicts, side)?
+    } else if let Some(side) = args.take_mode_from {
+        resolve_mode_conflicts(ui, &workspace_command, &tree, &conflicts, side)?
+    } else {
+        let (repo_path, _) = conflicts.first().unwrap();
+        let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
+        writeln!(
+            ui.status(),
+            "Resolving conflicts in: {}",
+            workspace_command.format_file_path(repo_path)
+        )?;
+        merge_editor.edit_file(&tree, repo_path)?
+    };
     let mut tx = workspace_command.start_transaction();
-    let new_tree_id = merge_editor.edit_file(&tree, repo_path)?;
     let new_commit = tx
         .repo_mut()
         .rewrite_commit(command.settings(), &commit)
@@ -129,3 +198,108 @@ pub(crate) fn cmd_resolve(
     }
     Ok(())
 }
+
+/// A side of a conflict, as given on the command line.
+#[derive(Clone, Copy, Debug)]
+enum ConflictSide {
+    Ours,
+    Theirs,
+    Number(NonZeroUsize),
+}
+
+impl ConflictSide {
+    /// Returns the index of the side in a conflict with `num_sides` sides.
+    fn index(self, num_sides: usize) -> Option<usize> {
+        match self {
+            ConflictSide::Ours => Some(0),
+            ConflictSide::Theirs => Some(num_sides - 1),
+            ConflictSide::Number(number) => Some(number.get() - 1).filter(|&i| i < num_sides),
+        }
+    }
+}
+
+impl fmt::Display for ConflictSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictSide::Ours => write!(f, "`ours`"),
+            ConflictSide::Theirs => write!(f, "`theirs`"),
+            ConflictSide::Number(number) => write!(f, "#{number}"),
+        }
+    }
+}
+
+fn parse_conflict_side(s: &str) -> Result<ConflictSide, String> {
+    match s {
+        "ours" => Ok(ConflictSide::Ours),
+        "theirs" => Ok(ConflictSide::Theirs),
+        _ => s
+            .parse()
+            .map(ConflictSide::Number)
+            .map_err(|_| "must be `ours`, `theirs`, or a side number from 1".to_owned()),
+    }
+}
+
+fn resolve_with_side(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    tree: &MergedTree,
+    conflicts: &[(RepoPathBuf, MergedTreeValue)],
+    side: ConflictSide,
+) -> Result<MergedTreeId, CommandError> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for (path, conflict) in conflicts {
+        // Sides are numbered like in the conflict markers, which show the
+        // simplified conflict
+        let conflict = conflict.clone().simplify();
+        let Some(index) = side.index(conflict.num_sides()) else {
+            return Err(user_error(format!(
+                "The conflict at {} has no side {side}",
+                workspace_command.format_file_path(path)
+            )));
+        };
+        writeln!(
+            ui.status(),
+            "Taking side {side} for: {}",
+            workspace_command.format_file_path(path)
+        )?;
+        let new_value = conflict.get_add(index).unwrap().clone();
+        tree_builder.set_or_remove(path.clone(), Merge::resolved(new_value));
+    }
+    Ok(tree_builder.write_tree(tree.store())?)
+}
+
+fn resolve_mode_conflicts(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    tree: &MergedTree,
+    conflicts: &[(RepoPathBuf, MergedTreeValue)],
+    side: ConflictSide,
+) -> Result<MergedTreeId, CommandError> {
+    let store = tree.store();
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    let mut has_mode_conflicts = false;
+    for (path, conflict) in conflicts {
+        let Some(mode_conflict) = extract_mode_conflict(store, path, conflict).block_on()? else {
+            continue;
+        };
+        let modes = &mode_conflict.modes;
+        let Some(Some(mode)) = side.index(modes.num_sides()).and_then(|i| modes.get_add(i)) else {
+            return Err(user_error(format!(
+                "The mode conflict at {} has no side {side}",
+                workspace_command.format_file_path(path)
+            )));
+        };
+        writeln!(
+            ui.status(),
+            "Taking mode {mode} for: {}",
+            workspace_command.format_file_path(path)
+        )?;
+        let new_value = mode_conflict.resolve(store, path, *mode).block_on()?;
+        tree_builder.set_or_remove(path.clone(), Merge::normal(new_value));
+        has_mode_conflicts = true;
+    }
+    if !has_mode_conflicts {
+        return Err(user_error("No mode conflicts found"));
+    }
+    Ok(tree_builder.write_tree(store)?)
+}
@@ -15,7 +15,6 @@
 use std::io::Write;
 
 use jj_lib::object_id::ObjectId;
-use jj_lib::rewrite::restore_tree;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -38,8 +37,9 @@ use crate::ui::Ui;
 /// to `jj abandon`, except that it leaves an empty revision with its
 /// description and other metadata preserved.
 ///
-/// See `jj diffedit` if you'd like to restore portions of files rather than
-/// entire files.
+/// Use `--interactive` to choose which parts of the files to restore, down to
+/// individual hunks or lines. See `jj diffedit` for editing the destination's
+/// contents directly.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct RestoreArgs {
     /// Restore only these paths (instead of all paths)
@@ -61,6 +61,12 @@ pub(crate) struct RestoreArgs {
     /// --changes-in @`.
     #[arg(long, short, value_name="REVISION", conflicts_with_all=["to", "from"])]
     changes_in: Option<RevisionArg>,
+    /// Interactively choose which parts to restore
+    #[arg(long, short)]
+    interactive: bool,
+    /// Specify diff editor to be used (implies --interactive)
+    #[arg(long, value_name = "NAME")]
+    tool: Option<String>,
     /// Prints an error. DO NOT USE.
     ///
     /// If we followed the pattern of `jj diff` and `jj diffedit`, we would use
@@ -79,7 +85,7 @@ pub(crate) fn cmd_restore(
     args: &RestoreArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
-    let (from_tree, to_commit);
+    let (from_tree, to_commit, from_commit);
     if args.revision.is_some() {
         return Err(user_error(
             "`jj restore` does not have a `--revision`/`-r` option. If you'd like to modify\nthe \
@@ -90,21 +96,51 @@ pub(crate) fn cmd_restore(
     if args.from.is_some() || args.to.is_some() {
         to_commit = workspace_command
             .resolve_single_rev(ui, args.to.as_ref().unwrap_or(&RevisionArg::AT))?;
-        from_tree = workspace_command
-            .resolve_single_rev(ui, args.from.as_ref().unwrap_or(&RevisionArg::AT))?
-            .tree()?;
+        let commit = workspace_command
+            .resolve_single_rev(ui, args.from.as_ref().unwrap_or(&RevisionArg::AT))?;
+        from_tree = commit.tree()?;
+        from_commit = Some(commit);
     } else {
         to_commit = workspace_command
             .resolve_single_rev(ui, args.changes_in.as_ref().unwrap_or(&RevisionArg::AT))?;
         from_tree = to_commit.parent_tree(workspace_command.repo().as_ref())?;
+        from_commit = None;
     }
     workspace_command.check_rewritable([to_commit.id()])?;
 
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
         .to_matcher();
+    let diff_selector =
+        workspace_command.diff_selector(ui, args.tool.as_deref(), args.interactive)?;
     let to_tree = to_commit.tree()?;
-    let new_tree_id = restore_tree(&from_tree, &to_tree, matcher.as_ref())?;
+    let format_instructions = || {
+        let from_summary = match &from_commit {
+            Some(commit) => workspace_command.format_commit_summary(commit),
+            None => format!(
+                "the parents of {}",
+                workspace_command.format_commit_summary(&to_commit)
+            ),
+        };
+        format!(
+            "\
+You are restoring changes from: {}
+into commit: {}
+
+The left side of the diff shows the current contents of the commit you're
+restoring into. The right side initially shows the contents you're restoring
+from.
+
+Adjust the right side until it shows the contents you want for the
+destination. If you don't make any changes, then all the changes will be
+restored.
+",
+            from_summary,
+            workspace_command.format_commit_summary(&to_commit)
+        )
+    };
+    let new_tree_id =
+        diff_selector.select(&to_tree, &from_tree, matcher.as_ref(), format_instructions)?;
     if &new_tree_id == to_commit.tree_id() {
         writeln!(ui.status(), "Nothing changed.")?;
     } else {
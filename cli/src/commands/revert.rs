@@ -0,0 +1,112 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools as _;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::rewrite::merge_commit_trees;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Create new commits that undo the changes of revisions
+///
+/// A new commit with the inverse of the changes is created for each of the
+/// given revisions. The commits are stacked on top of the destination, with
+/// the reverts of descendants coming before the reverts of their ancestors, so
+/// each of them applies to the state left by the previous one.
+///
+/// If a change can't be reverted cleanly, the new commit is created with the
+/// conflict recorded in it, and the conflict can be resolved later.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct RevertArgs {
+    /// The revision(s) to revert
+    #[arg(long, short, required = true)]
+    revisions: Vec<RevisionArg>,
+    /// The revision(s) to put the reverting commits on top of
+    #[arg(long, short, default_value = "@")]
+    destination: Vec<RevisionArg>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_revert(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &RevertArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let to_revert: Vec<_> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?; // in reverse topological order
+    if to_revert.is_empty() {
+        writeln!(ui.status(), "No revisions to revert.")?;
+        return Ok(());
+    }
+    let mut parents = vec![];
+    for revision_str in &args.destination {
+        let destination = workspace_command.resolve_single_rev(ui, revision_str)?;
+        parents.push(destination);
+    }
+    let mut tx = workspace_command.start_transaction();
+    let transaction_description = if to_revert.len() == 1 {
+        format!("revert commit {}", to_revert[0].id().hex())
+    } else {
+        format!(
+            "revert commit {} and {} more",
+            to_revert[0].id().hex(),
+            to_revert.len() - 1
+        )
+    };
+    let mut new_base_tree = merge_commit_trees(tx.repo(), &parents)?;
+    let mut reverted = vec![];
+    for commit_to_revert in to_revert {
+        let subject = commit_to_revert
+            .description()
+            .lines()
+            .next()
+            .unwrap_or_default();
+        let new_commit_description = format!(
+            "Revert \"{}\"\n\nThis reverts commit {}.\n",
+            subject,
+            commit_to_revert.id().hex()
+        );
+        let old_base_tree = commit_to_revert.parent_tree(tx.repo())?;
+        let old_tree = commit_to_revert.tree()?;
+        // Conflicts are kept in the new tree rather than failing the command.
+        let new_tree = new_base_tree.merge(&old_tree, &old_base_tree)?;
+        let new_parent_ids = parents.iter().map(|commit| commit.id().clone()).collect();
+        let new_commit = tx
+            .repo_mut()
+            .new_commit(command.settings(), new_parent_ids, new_tree.id())
+            .set_description(new_commit_description)
+            .write()?;
+        parents = vec![new_commit.clone()];
+        new_base_tree = new_tree;
+        reverted.push((commit_to_revert, new_commit));
+    }
+    if let Some(mut formatter) = ui.status_formatter() {
+        for (old_commit, new_commit) in &reverted {
+            write!(formatter, "Reverted ")?;
+            tx.write_commit_summary(formatter.as_mut(), old_commit)?;
+            write!(formatter, " as ")?;
+            tx.write_commit_summary(formatter.as_mut(), new_commit)?;
+            writeln!(formatter)?;
+        }
+    }
+    tx.finish(ui, transaction_description)?;
+    Ok(())
+}
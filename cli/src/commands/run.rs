@@ -14,16 +14,53 @@
 
 //! This file contains the internal implementation of `run`.
 
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Arc;
+
 use itertools::Itertools as _;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::commit::Commit;
+use jj_lib::hex_util::to_reverse_hex;
+use jj_lib::local_working_copy::LocalWorkingCopy;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::OperationId;
+use jj_lib::op_store::WorkspaceId;
+use jj_lib::repo::Repo as _;
+use jj_lib::store::Store;
+use jj_lib::working_copy::CheckoutOptions;
+use jj_lib::working_copy::WorkingCopy as _;
+use rayon::iter::IntoParallelRefIterator as _;
+use rayon::iter::ParallelIterator as _;
+use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::command_error::internal_error;
+use crate::command_error::internal_error_with_message;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
-/// (**Stub**, does not work yet) Run a command across a set of revisions.
+/// Run a command across a set of revisions
+///
+/// Each revision is checked out into a separate working copy under the `.jj`
+/// directory, and the command is run there with the shell. The working copies
+/// are reused between invocations, so untracked build outputs survive from one
+/// run to the next. The command should not modify tracked files.
 ///
+/// The command receives the revision's commit id and change id in the
+/// `JJ_COMMIT_ID` and `JJ_CHANGE_ID` environment variables.
+///
+/// Results are cached by tree and command, so a revision whose content has
+/// already been checked with the same command is not run again. The command is
+/// assumed to be deterministic.
 ///
 /// All recorded state will be persisted in the `.jj` directory, so occasionally
 /// a `jj run --clean` is needed to clean up disk space.
@@ -31,15 +68,16 @@ use crate::ui::Ui;
 /// # Example
 ///
 /// # Run pre-commit on your local work
-/// $ jj run 'pre-commit run .github/pre-commit.yaml' -r (trunk()..@) -j 4
+/// $ jj run 'pre-commit run --config .github/pre-commit.yaml' -r 'trunk()..@' -j 4
 ///
 /// This allows pre-commit integration and other funny stuff.
 #[derive(clap::Args, Clone, Debug)]
 #[command(verbatim_doc_comment)]
 pub struct RunArgs {
     /// The command to run across all selected revisions.
-    shell_command: String,
-    /// The revisions to change.
+    #[arg(required_unless_present = "clean")]
+    shell_command: Option<String>,
+    /// The revisions to run the command on.
     #[arg(long, short, default_value = "@")]
     revisions: Vec<RevisionArg>,
     /// A no-op option to match the interface of `git rebase -x`.
@@ -48,23 +86,233 @@ pub struct RunArgs {
     /// How many processes should run in parallel, uses by default all cores.
     #[arg(long, short)]
     jobs: Option<usize>,
+    /// Remove the cached working copies and results, and exit.
+    #[arg(long, conflicts_with_all = ["shell_command", "revisions", "jobs"])]
+    clean: bool,
 }
 
+#[instrument(skip_all)]
 pub fn cmd_run(ui: &mut Ui, command: &CommandHelper, args: &RunArgs) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
-    let _resolved_commits: Vec<_> = workspace_command
+    let run_dir = workspace_command.repo_path().join("run");
+    if args.clean {
+        match fs::remove_dir_all(&run_dir) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        writeln!(ui.status(), "Removed cached working copies and results.")?;
+        return Ok(());
+    }
+    let shell_command = args.shell_command.as_deref().unwrap();
+    // Run in topological order, so the output reads like `git rebase -x`.
+    let resolved_commits: Vec<_> = workspace_command
         .parse_union_revsets(ui, &args.revisions)?
         .evaluate_to_commits()?
         .try_collect()?;
+    let commits = resolved_commits.into_iter().rev().collect_vec();
     // Jobs are resolved in this order:
     // 1. Commandline argument iff > 0.
     // 2. the amount of cores available.
     // 3. a single job, if all of the above fails.
-    let _jobs = match args.jobs {
+    let jobs = match args.jobs {
         Some(0) | None => std::thread::available_parallelism().map(|t| t.into()).ok(),
         Some(jobs) => Some(jobs),
     }
     // Fallback to a single user-visible job.
     .unwrap_or(1usize);
-    Err(user_error("This is a stub, do not use"))
+
+    let cache = ResultCache::new(run_dir.join("results"));
+    let working_copies_dir = run_dir.join("working_copies");
+    let store = workspace_command.repo().store();
+    let operation_id = workspace_command.repo().op_id();
+    let checkout_options = workspace_command.checkout_options()?;
+    // Each thread of the pool owns one working copy, which is identified by the
+    // thread's index. A thread runs a single command at a time, so no two
+    // commits are ever checked out into the same working copy concurrently.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .map_err(internal_error)?;
+    let outcomes: Vec<RunOutcome> = pool.install(|| {
+        commits
+            .par_iter()
+            .map(|commit| {
+                let cache_key = tree_cache_key(commit.tree_id());
+                if let Some(success) = cache.get(&cache_key, shell_command) {
+                    return Ok(RunOutcome {
+                        success,
+                        cached: true,
+                        output: vec![],
+                    });
+                }
+                let wc_index = rayon::current_thread_index().unwrap_or(0);
+                let wc_dir = working_copies_dir.join(wc_index.to_string());
+                run_in_working_copy(
+                    store,
+                    operation_id,
+                    &wc_dir,
+                    commit,
+                    &checkout_options,
+                    shell_command,
+                )
+            })
+            .collect::<Result<_, CommandError>>()
+    })?;
+
+    let mut num_failed = 0;
+    let mut formatter = ui.stdout_formatter();
+    for (commit, outcome) in commits.iter().zip(&outcomes) {
+        if !outcome.cached {
+            cache.insert(
+                &tree_cache_key(commit.tree_id()),
+                shell_command,
+                outcome.success,
+            )?;
+        }
+        if outcome.success {
+            write!(formatter.labeled("run_passed"), "PASS")?;
+        } else {
+            num_failed += 1;
+            write!(formatter.labeled("run_failed"), "FAIL")?;
+        }
+        write!(formatter, " ")?;
+        workspace_command.write_commit_summary(formatter.as_mut(), commit)?;
+        if outcome.cached {
+            write!(formatter, " (cached)")?;
+        }
+        writeln!(formatter)?;
+        if !outcome.success {
+            // Flush our own output first so it is interleaved correctly with
+            // the command's output.
+            formatter.flush()?;
+            ui.stderr().write_all(&outcome.output)?;
+        }
+    }
+    drop(formatter);
+    if num_failed > 0 {
+        return Err(user_error(format!(
+            "Command failed in {num_failed} of {} revisions",
+            commits.len()
+        )));
+    }
+    Ok(())
+}
+
+/// The result of running the command on a single revision.
+struct RunOutcome {
+    /// Whether the command exited successfully.
+    success: bool,
+    /// Whether the result was taken from the cache instead of running the
+    /// command.
+    cached: bool,
+    /// The combined standard output and standard error of the command.
+    output: Vec<u8>,
+}
+
+/// Checks out `commit` into the working copy at `wc_dir` and runs the command
+/// there.
+///
+/// The working copy state is saved after the checkout, so the files of the
+/// previously checked out revision are updated or removed next time.
+fn run_in_working_copy(
+    store: &Arc<Store>,
+    operation_id: &OperationId,
+    wc_dir: &Path,
+    commit: &Commit,
+    checkout_options: &CheckoutOptions,
+    shell_command: &str,
+) -> Result<RunOutcome, CommandError> {
+    let files_dir = wc_dir.join("files");
+    let state_dir = wc_dir.join("state");
+    let wc = if state_dir.join("checkout").exists() {
+        LocalWorkingCopy::load(store.clone(), files_dir.clone(), state_dir)
+    } else {
+        fs::create_dir_all(&files_dir)?;
+        fs::create_dir_all(&state_dir)?;
+        LocalWorkingCopy::init(
+            store.clone(),
+            files_dir.clone(),
+            state_dir,
+            operation_id.clone(),
+            WorkspaceId::default(),
+        )?
+    };
+    let mut locked_wc = wc.start_mutation()?;
+    locked_wc
+        .check_out(commit, checkout_options)
+        .map_err(|err| internal_error_with_message("Failed to check out revision", err))?;
+    locked_wc.finish(operation_id.clone())?;
+
+    let output = shell(shell_command)
+        .current_dir(&files_dir)
+        .env("JJ_COMMIT_ID", commit.id().hex())
+        .env(
+            "JJ_CHANGE_ID",
+            to_reverse_hex(&commit.change_id().hex()).unwrap(),
+        )
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| user_error(format!("Failed to run '{shell_command}': {err}")))?;
+    let mut combined = output.stdout;
+    combined.extend(output.stderr);
+    Ok(RunOutcome {
+        success: output.status.success(),
+        cached: false,
+        output: combined,
+    })
+}
+
+/// Builds a command that runs `shell_command` with the platform's shell.
+pub(crate) fn shell(shell_command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", shell_command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", shell_command]);
+        cmd
+    }
+}
+
+/// Returns a key that identifies the content of a tree, including all sides of
+/// a conflicted tree.
+fn tree_cache_key(tree_id: &MergedTreeId) -> String {
+    tree_id.to_merge().iter().map(|id| id.hex()).join("-")
+}
+
+/// Persistent record of the outcome of commands per tree.
+///
+/// Each tree has one file, which maps commands to whether they succeeded.
+struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    fn new(dir: PathBuf) -> Self {
+        ResultCache { dir }
+    }
+
+    /// Reads the results recorded for the tree. Unreadable or corrupt entries
+    /// are treated as missing, so the command will be run again.
+    fn read(&self, key: &str) -> BTreeMap<String, bool> {
+        fs::read(self.dir.join(key))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn get(&self, key: &str, shell_command: &str) -> Option<bool> {
+        self.read(key).get(shell_command).copied()
+    }
+
+    fn insert(&self, key: &str, shell_command: &str, success: bool) -> Result<(), CommandError> {
+        let mut results = self.read(key);
+        results.insert(shell_command.to_owned(), success);
+        fs::create_dir_all(&self.dir)?;
+        let data = serde_json::to_vec(&results).map_err(internal_error)?;
+        fs::write(self.dir.join(key), data)?;
+        Ok(())
+    }
 }
@@ -0,0 +1,172 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use indexmap::IndexSet;
+use itertools::Itertools;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::commit::CommitIteratorExt;
+use jj_lib::repo::Repo;
+use jj_lib::rewrite::CommitRewriter;
+use tracing::instrument;
+
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Serialize revisions into a linear stack
+///
+/// Running `jj serialize 1 2 3` will transform the history like this:
+/// ```text
+///                 3
+///                 |
+///   1  2  3       2
+///    \ | /   ->   |
+///      0          1
+///                 |
+///                 0
+/// ```
+///
+/// This is the opposite of `jj parallelize`. The revisions are stacked in the
+/// order they're given on the command line, from the bottom of the stack to
+/// the top. The revisions matched by a single argument are stacked with
+/// ancestors before their descendants. A revision can't be put below one of
+/// its ancestors.
+///
+/// The first revision stays where it is. Each following revision is rebased
+/// onto the previous one, keeping only the parents that aren't already
+/// ancestors of the stack. Revisions outside the set that had several of the
+/// stacked revisions as parents only keep the topmost of them.
+#[derive(clap::Args, Clone, Debug)]
+#[command(verbatim_doc_comment)]
+pub(crate) struct SerializeArgs {
+    /// Revisions to serialize, from the bottom of the stack to the top
+    #[arg(required = true)]
+    revisions: Vec<RevisionArg>,
+    /// Show the resulting order of the stack, without changing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_serialize(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SerializeArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    // The target commits, from the bottom of the stack to the top.
+    let mut target_commits: IndexSet<Commit> = IndexSet::new();
+    for revision_arg in &args.revisions {
+        let commits: Vec<Commit> = workspace_command
+            .parse_revset(ui, revision_arg)?
+            .evaluate_to_commits()?
+            .try_collect()?;
+        target_commits.extend(commits.into_iter().rev());
+    }
+    let target_commits = target_commits.into_iter().collect_vec();
+
+    let index = workspace_command.repo().index();
+    for (i, commit) in target_commits.iter().enumerate() {
+        for later_commit in &target_commits[i + 1..] {
+            if index.is_ancestor(later_commit.id(), commit.id()) {
+                return Err(user_error(format!(
+                    "Revision {} can't be stacked on top of its descendant {}",
+                    short_commit_hash(later_commit.id()),
+                    short_commit_hash(commit.id()),
+                )));
+            }
+        }
+    }
+
+    if args.dry_run {
+        if let Some(mut formatter) = ui.status_formatter() {
+            writeln!(
+                formatter,
+                "Would stack these revisions, from top to bottom:"
+            )?;
+            for commit in target_commits.iter().rev() {
+                write!(formatter, "  ")?;
+                workspace_command.write_commit_summary(formatter.as_mut(), commit)?;
+                writeln!(formatter)?;
+            }
+        }
+        return Ok(());
+    }
+    if target_commits.len() < 2 {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    workspace_command.check_rewritable(target_commits[1..].iter().ids())?;
+
+    let mut tx = workspace_command.start_transaction();
+    let mut_repo = tx.repo_mut();
+    // Positions of the (rewritten) target commits in the stack.
+    let mut stack_positions: HashMap<CommitId, usize> = HashMap::new();
+    stack_positions.insert(target_commits[0].id().clone(), 0);
+    let mut previous_id = target_commits[0].id().clone();
+    for (i, commit) in target_commits.iter().enumerate().skip(1) {
+        // Parents that are ancestors of the stack below are redundant.
+        let other_parents = commit.parent_ids().iter().filter(|parent_id| {
+            !target_commits[..i]
+                .iter()
+                .any(|below| mut_repo.index().is_ancestor(parent_id, below.id()))
+        });
+        let new_parents = [previous_id.clone()]
+            .into_iter()
+            .chain(other_parents.cloned())
+            .collect_vec();
+        let new_commit = CommitRewriter::new(mut_repo, commit.clone(), new_parents)
+            .rebase(command.settings())?
+            .write()?;
+        stack_positions.insert(new_commit.id().clone(), i);
+        previous_id = new_commit.id().clone();
+    }
+
+    let mut num_rebased = 0;
+    mut_repo.transform_descendants(
+        command.settings(),
+        target_commits[1..].iter().ids().cloned().collect_vec(),
+        |mut rewriter| {
+            // Only keep the topmost of the stacked parents, since the others are
+            // its ancestors now.
+            let new_parents = rewriter.new_parents();
+            if let Some(topmost) = new_parents
+                .iter()
+                .filter(|id| stack_positions.contains_key(*id))
+                .max_by_key(|id| stack_positions[*id])
+            {
+                let topmost = topmost.clone();
+                let new_parents = new_parents
+                    .iter()
+                    .filter(|id| **id == topmost || !stack_positions.contains_key(*id))
+                    .cloned()
+                    .collect_vec();
+                rewriter.set_new_parents(new_parents);
+            }
+            num_rebased += 1;
+            rewriter.rebase(command.settings())?.write()?;
+            Ok(())
+        },
+    )?;
+    if num_rebased > 0 {
+        writeln!(ui.status(), "Rebased {num_rebased} descendant commits")?;
+    }
+    tx.finish(ui, format!("serialize {} commits", target_commits.len()))
+}
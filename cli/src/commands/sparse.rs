@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Write as _;
 use std::io::Write;
@@ -20,6 +21,7 @@ use std::path::Path;
 use clap::Subcommand;
 use itertools::Itertools;
 use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::settings::ConfigResultExt as _;
 use jj_lib::settings::UserSettings;
 use tracing::instrument;
 
@@ -27,8 +29,10 @@ use crate::cli_util::edit_temp_file;
 use crate::cli_util::print_checkout_stats;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::config_error_with_message;
 use crate::command_error::internal_error;
 use crate::command_error::internal_error_with_message;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
@@ -56,6 +60,10 @@ pub(crate) struct SparseListArgs {}
 /// For example, if all you need is the `README.md` and the `lib/`
 /// directory, use `jj sparse set --clear --add README.md --add lib`.
 /// If you no longer need the `lib` directory, use `jj sparse set --remove lib`.
+///
+/// Named sets of patterns can be defined in the `sparse.profiles` config
+/// table, and added with `--add-profile`. For example, `jj sparse set --clear
+/// --add-profile web` replaces the patterns with the ones of the `web` profile.
 #[derive(clap::Args, Clone, Debug)]
 pub(crate) struct SparseSetArgs {
     /// Patterns to add to the working copy
@@ -76,6 +84,9 @@ pub(crate) struct SparseSetArgs {
     /// Include no files in the working copy (combine with --add)
     #[arg(long)]
     clear: bool,
+    /// Add the patterns of the named sparse profile to the working copy
+    #[arg(long, value_name = "NAME")]
+    add_profile: Vec<String>,
 }
 
 /// Reset the patterns to include all files in the working copy
@@ -120,6 +131,10 @@ fn cmd_sparse_set(
     args: &SparseSetArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
+    let mut profile_patterns = vec![];
+    for name in &args.add_profile {
+        profile_patterns.extend(sparse_profile_patterns(command.settings(), name)?);
+    }
     update_sparse_patterns_with(ui, &mut workspace_command, |_ui, old_patterns| {
         let mut new_patterns = HashSet::new();
         if !args.clear {
@@ -128,7 +143,7 @@ fn cmd_sparse_set(
                 new_patterns.remove(path);
             }
         }
-        for path in &args.add {
+        for path in args.add.iter().chain(&profile_patterns) {
             new_patterns.insert(path.to_owned());
         }
         Ok(new_patterns.into_iter().sorted_unstable().collect())
@@ -163,6 +178,39 @@ fn cmd_sparse_edit(
     })
 }
 
+/// Looks up the patterns of the sparse profile `name`, which are defined in
+/// the `sparse.profiles` config table.
+pub(crate) fn sparse_profile_patterns(
+    settings: &UserSettings,
+    name: &str,
+) -> Result<Vec<RepoPathBuf>, CommandError> {
+    let profiles: HashMap<String, Vec<String>> = settings
+        .config()
+        .get("sparse.profiles")
+        .optional()?
+        .unwrap_or_default();
+    let patterns = profiles.get(name).ok_or_else(|| {
+        user_error_with_hint(
+            format!("No sparse profile named '{name}'"),
+            "Sparse profiles are defined in the `sparse.profiles` config table.",
+        )
+    })?;
+    let mut patterns: Vec<_> = patterns
+        .iter()
+        .map(|pattern| {
+            RepoPathBuf::from_relative_path(pattern).map_err(|err| {
+                config_error_with_message(
+                    format!("Invalid pattern in sparse profile '{name}': {pattern}"),
+                    err,
+                )
+            })
+        })
+        .try_collect()?;
+    patterns.sort_unstable();
+    patterns.dedup();
+    Ok(patterns)
+}
+
 fn edit_sparse(
     repo_path: &Path,
     sparse: &[RepoPathBuf],
@@ -206,11 +254,12 @@ fn update_sparse_patterns_with(
     workspace_command: &mut WorkspaceCommandHelper,
     f: impl FnOnce(&mut Ui, &[RepoPathBuf]) -> Result<Vec<RepoPathBuf>, CommandError>,
 ) -> Result<(), CommandError> {
+    let checkout_options = workspace_command.checkout_options()?;
     let (mut locked_ws, wc_commit) = workspace_command.start_working_copy_mutation()?;
     let new_patterns = f(ui, locked_ws.locked_wc().sparse_patterns()?)?;
     let stats = locked_ws
         .locked_wc()
-        .set_sparse_patterns(new_patterns)
+        .set_sparse_patterns(new_patterns, &checkout_options)
         .map_err(|err| internal_error_with_message("Failed to update working copy paths", err))?;
     let operation_id = locked_ws.locked_wc().old_operation_id().clone();
     locked_ws.finish(operation_id)?;
@@ -12,17 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use std::io::Write;
+use std::slice;
 
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::commit::CommitIteratorExt as _;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
+use jj_lib::rewrite::restore_tree;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
 use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
+use crate::description_util::finalize_description;
 use crate::ui::Ui;
 
 /// Split a revision in two
@@ -40,6 +47,11 @@ use crate::ui::Ui;
 /// description, the second part will not get a description, and you will be
 /// asked for a description only for the first part.
 ///
+/// If more than one fileset is given, the revision is split into one revision
+/// per fileset, in the order given, plus a final revision with the remaining
+/// changes. For example, `jj split 'glob:src/**' 'glob:docs/**'` creates three
+/// revisions. Use `--parallel` to make them siblings instead of a stack.
+///
 /// Splitting an empty commit is not supported because the same effect can be
 /// achieved with `jj new`.
 #[derive(clap::Args, Clone, Debug)]
@@ -54,12 +66,13 @@ pub(crate) struct SplitArgs {
     /// The revision to split
     #[arg(long, short, default_value = "@")]
     revision: RevisionArg,
-    /// Split the revision into two parallel revisions instead of a parent and
-    /// child.
+    /// Split the revision into parallel revisions instead of a chain of
+    /// parents and children.
     // TODO: Delete `--siblings` alias in jj 0.25+
     #[arg(long, short, alias = "siblings")]
     parallel: bool,
-    /// Put these paths in the first commit
+    /// Put these paths in the first commit. If more than one fileset is given,
+    /// each of them gets its own commit.
     #[arg(value_hint = clap::ValueHint::AnyPath)]
     paths: Vec<String>,
 }
@@ -80,9 +93,27 @@ pub(crate) fn cmd_split(
     }
 
     workspace_command.check_rewritable([commit.id()])?;
+    if args.paths.len() > 1 && (args.interactive || args.tool.is_some()) {
+        return Err(user_error(
+            "`--interactive` can't be used with more than one fileset",
+        ));
+    }
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
         .to_matcher();
+    // Each fileset selects the changes of one commit if more than one is
+    // given.
+    let fileset_matchers: Vec<_> = if args.paths.len() > 1 {
+        args.paths
+            .iter()
+            .map(|path| {
+                let fileset = workspace_command.parse_file_patterns(ui, slice::from_ref(path))?;
+                Ok::<_, CommandError>(fileset.to_matcher())
+            })
+            .try_collect()?
+    } else {
+        vec![]
+    };
     let diff_selector = workspace_command.diff_selector(
         ui,
         args.tool.as_deref(),
@@ -91,9 +122,14 @@ pub(crate) fn cmd_split(
     let mut tx = workspace_command.start_transaction();
     let end_tree = commit.tree()?;
     let base_tree = commit.parent_tree(tx.repo())?;
-    let format_instructions = || {
-        format!(
-            "\
+
+    // The trees of the stacked commits to create, apart from the last one,
+    // which has the remaining changes.
+    let mut selected_trees = vec![];
+    if fileset_matchers.is_empty() {
+        let format_instructions = || {
+            format!(
+                "\
 You are splitting a commit into two: {}
 
 The diff initially shows the changes in the commit you're splitting.
@@ -102,102 +138,98 @@ Adjust the right side until it shows the contents you want for the first commit.
 The remainder will be in the second commit. If you don't make any changes, then
 the operation will be aborted.
 ",
-            tx.format_commit_summary(&commit)
-        )
-    };
-
-    // Prompt the user to select the changes they want for the first commit.
-    let selected_tree_id =
-        diff_selector.select(&base_tree, &end_tree, matcher.as_ref(), format_instructions)?;
-    if &selected_tree_id == commit.tree_id() && diff_selector.is_interactive() {
-        // The user selected everything from the original commit.
-        writeln!(ui.status(), "Nothing changed.")?;
-        return Ok(());
-    }
-    if selected_tree_id == base_tree.id() {
-        // The user selected nothing, so the first commit will be empty.
-        writeln!(
-            ui.warning_default(),
-            "The given paths do not match any file: {}",
-            args.paths.join(" ")
-        )?;
-    }
+                tx.format_commit_summary(&commit)
+            )
+        };
 
-    // Create the first commit, which includes the changes selected by the user.
-    let selected_tree = tx.repo().store().get_root_tree(&selected_tree_id)?;
-    let first_commit = {
-        let mut commit_builder = tx
-            .repo_mut()
-            .rewrite_commit(command.settings(), &commit)
-            .detach();
-        commit_builder.set_tree_id(selected_tree_id);
-        if commit_builder.description().is_empty() {
-            commit_builder.set_description(command.settings().default_description());
+        // Prompt the user to select the changes they want for the first commit.
+        let selected_tree_id =
+            diff_selector.select(&base_tree, &end_tree, matcher.as_ref(), format_instructions)?;
+        if &selected_tree_id == commit.tree_id() && diff_selector.is_interactive() {
+            // The user selected everything from the original commit.
+            writeln!(ui.status(), "Nothing changed.")?;
+            return Ok(());
         }
-        let temp_commit = commit_builder.write_hidden()?;
-        let template = description_template(
-            ui,
-            &tx,
-            "Enter a description for the first commit.",
-            &temp_commit,
-        )?;
-        let description =
-            edit_description(tx.base_workspace_helper(), &template, command.settings())?;
-        commit_builder.set_description(description);
-        commit_builder.write(tx.repo_mut())?
-    };
+        if selected_tree_id == base_tree.id() {
+            // The user selected nothing, so the first commit will be empty.
+            writeln!(
+                ui.warning_default(),
+                "The given paths do not match any file: {}",
+                args.paths.join(" ")
+            )?;
+        }
+        selected_trees.push(tx.repo().store().get_root_tree(&selected_tree_id)?);
+    } else {
+        for (path, fileset_matcher) in args.paths.iter().zip(&fileset_matchers) {
+            let previous_tree = selected_trees.last().unwrap_or(&base_tree);
+            let selected_tree_id =
+                restore_tree(&end_tree, previous_tree, fileset_matcher.as_ref())?;
+            if selected_tree_id == previous_tree.id() {
+                writeln!(
+                    ui.warning_default(),
+                    "The given paths do not match any file: {path}"
+                )?;
+            }
+            selected_trees.push(tx.repo().store().get_root_tree(&selected_tree_id)?);
+        }
+    }
+    let num_parts = selected_trees.len() + 1;
 
-    // Create the second commit, which includes everything the user didn't
-    // select.
-    let second_commit = {
-        let new_tree = if args.parallel {
-            // Merge the original commit tree with its parent using the tree
-            // containing the user selected changes as the base for the merge.
-            // This results in a tree with the changes the user didn't select.
-            end_tree.merge(&selected_tree, &base_tree)?
-        } else {
-            end_tree
-        };
-        let parents = if args.parallel {
-            commit.parent_ids().to_vec()
-        } else {
-            vec![first_commit.id().clone()]
+    let mut new_commits: Vec<Commit> = vec![];
+    for (index, tree) in selected_trees.iter().chain([&end_tree]).enumerate() {
+        let is_last = index == num_parts - 1;
+        let new_tree = match index.checked_sub(1) {
+            // Apply the changes that weren't in the previous part to the
+            // parent, so that the parts are independent of each other.
+            Some(previous_index) if args.parallel => {
+                base_tree.merge(&selected_trees[previous_index], tree)?
+            }
+            _ => tree.clone(),
         };
         let mut commit_builder = tx
             .repo_mut()
             .rewrite_commit(command.settings(), &commit)
             .detach();
-        commit_builder
-            .set_parents(parents)
-            .set_tree_id(new_tree.id())
+        commit_builder.set_tree_id(new_tree.id());
+        if let (Some(previous_commit), false) = (new_commits.last(), args.parallel) {
+            commit_builder.set_parents(vec![previous_commit.id().clone()]);
+        }
+        if index > 0 {
             // Generate a new change id so that the commit being split doesn't
             // become divergent.
-            .generate_new_change_id();
-        let description = if commit.description().is_empty() {
+            commit_builder.generate_new_change_id();
+        }
+        let description = if is_last && commit.description().is_empty() {
             // If there was no description before, don't ask for one for the
-            // second commit.
+            // last commit.
             "".to_string()
         } else {
+            if commit_builder.description().is_empty() {
+                commit_builder.set_description(command.settings().default_description());
+            }
+            let intro = match (num_parts, index) {
+                (2, 0) => "Enter a description for the first commit.".to_owned(),
+                (2, _) => "Enter a description for the second commit.".to_owned(),
+                _ => format!("Enter a description for part {} of {num_parts}.", index + 1),
+            };
             let temp_commit = commit_builder.write_hidden()?;
-            let template = description_template(
-                ui,
-                &tx,
-                "Enter a description for the second commit.",
-                &temp_commit,
-            )?;
-            edit_description(tx.base_workspace_helper(), &template, command.settings())?
+            let template = description_template(ui, &tx, &intro, &temp_commit)?;
+            let description =
+                edit_description(tx.base_workspace_helper(), &template, command.settings())?;
+            finalize_description(ui, &tx, &commit, description)?
         };
         commit_builder.set_description(description);
-        commit_builder.write(tx.repo_mut())?
-    };
+        new_commits.push(commit_builder.write(tx.repo_mut())?);
+    }
+    let last_commit = new_commits.last().unwrap();
 
-    // Mark the commit being split as rewritten to the second commit. As a
+    // Mark the commit being split as rewritten to the last commit. As a
     // result, if @ points to the commit being split, it will point to the
-    // second commit after the command finishes. This also means that any
-    // bookmarks pointing to the commit being split are moved to the second
+    // last commit after the command finishes. This also means that any
+    // bookmarks pointing to the commit being split are moved to the last
     // commit.
     tx.repo_mut()
-        .set_rewritten_commit(commit.id().clone(), second_commit.id().clone());
+        .set_rewritten_commit(commit.id().clone(), last_commit.id().clone());
     let mut num_rebased = 0;
     tx.repo_mut().transform_descendants(
         command.settings(),
@@ -205,8 +237,7 @@ the operation will be aborted.
         |mut rewriter| {
             num_rebased += 1;
             if args.parallel {
-                rewriter
-                    .replace_parent(second_commit.id(), [first_commit.id(), second_commit.id()]);
+                rewriter.replace_parent(last_commit.id(), new_commits.iter().ids());
             }
             // We don't need to do anything special for the non-parallel case
             // since we already marked the original commit as rewritten.
@@ -219,11 +250,15 @@ the operation will be aborted.
         if num_rebased > 0 {
             writeln!(formatter, "Rebased {num_rebased} descendant commits")?;
         }
-        write!(formatter, "First part: ")?;
-        tx.write_commit_summary(formatter.as_mut(), &first_commit)?;
-        write!(formatter, "\nSecond part: ")?;
-        tx.write_commit_summary(formatter.as_mut(), &second_commit)?;
-        writeln!(formatter)?;
+        for (index, new_commit) in new_commits.iter().enumerate() {
+            match (num_parts, index) {
+                (2, 0) => write!(formatter, "First part: ")?,
+                (2, _) => write!(formatter, "Second part: ")?,
+                _ => write!(formatter, "Part {}: ", index + 1)?,
+            }
+            tx.write_commit_summary(formatter.as_mut(), new_commit)?;
+            writeln!(formatter)?;
+        }
     }
     tx.finish(ui, format!("split commit {}", commit.id().hex()))?;
     Ok(())
@@ -19,12 +19,14 @@ use jj_lib::matchers::Matcher;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
 use jj_lib::repo::Repo;
+use jj_lib::rewrite::restore_tree;
 use jj_lib::settings::UserSettings;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
 use crate::cli_util::DiffSelector;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::WorkspaceCommandTransaction;
 use crate::command_error::user_error;
 use crate::command_error::user_error_with_hint;
@@ -52,6 +54,12 @@ use crate::ui::Ui;
 /// non-empty description, you will be asked for the combined description. If
 /// either was empty, then the other one will be used.
 ///
+/// To move the changes to different paths into different revisions, pass
+/// `--into` more than once, each followed by the paths to move into that
+/// revision. For example, `jj squash --into A src --into B docs` moves the
+/// changes to `src` into `A` and the changes to `docs` into `B` in one
+/// operation. The destinations keep their descriptions in that case.
+///
 /// If a working-copy commit gets abandoned, it will be given a new, empty
 /// commit. This is true in general; it is not specific to this command.
 #[derive(clap::Args, Clone, Debug)]
@@ -62,9 +70,9 @@ pub(crate) struct SquashArgs {
     /// Revision(s) to squash from (default: @)
     #[arg(long, conflicts_with = "revision")]
     from: Vec<RevisionArg>,
-    /// Revision to squash into (default: @)
+    /// Revision(s) to squash into (default: @)
     #[arg(long, conflicts_with = "revision", visible_alias = "to")]
-    into: Option<RevisionArg>,
+    into: Vec<RevisionArg>,
     /// The description to use for squashed revision (don't open editor)
     #[arg(long = "message", short, value_name = "MESSAGE")]
     message_paragraphs: Vec<String>,
@@ -93,26 +101,19 @@ pub(crate) fn cmd_squash(
     args: &SquashArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
+    if args.into.len() > 1 {
+        return squash_into_multiple_destinations(ui, command, workspace_command, args);
+    }
 
-    let mut sources: Vec<Commit>;
+    let sources: Vec<Commit>;
     let destination;
-    if !args.from.is_empty() || args.into.is_some() {
-        sources = if args.from.is_empty() {
-            workspace_command.parse_revset(ui, &RevisionArg::AT)?
-        } else {
-            workspace_command.parse_union_revsets(ui, &args.from)?
-        }
-        .evaluate_to_commits()?
-        .try_collect()?;
+    if !args.from.is_empty() || !args.into.is_empty() {
+        sources = resolve_sources(ui, &workspace_command, args)?;
         destination = workspace_command
-            .resolve_single_rev(ui, args.into.as_ref().unwrap_or(&RevisionArg::AT))?;
+            .resolve_single_rev(ui, args.into.first().unwrap_or(&RevisionArg::AT))?;
         if sources.iter().any(|source| source.id() == destination.id()) {
             return Err(user_error("Source and destination cannot be the same"));
         }
-        // Reverse the set so we apply the oldest commits first. It shouldn't affect the
-        // result, but it avoids creating transient conflicts and is therefore probably
-        // a little faster.
-        sources.reverse();
     } else {
         let source = workspace_command
             .resolve_single_rev(ui, args.revision.as_ref().unwrap_or(&RevisionArg::AT))?;
@@ -143,7 +144,7 @@ pub(crate) fn cmd_squash(
         matcher.as_ref(),
         &diff_selector,
         SquashedDescription::from_args(args),
-        args.revision.is_none() && args.from.is_empty() && args.into.is_none(),
+        args.revision.is_none() && args.from.is_empty() && args.into.is_empty(),
         &args.paths,
         args.keep_emptied,
     )?;
@@ -151,6 +152,195 @@ pub(crate) fn cmd_squash(
     Ok(())
 }
 
+/// Resolves the `--from` revisions, oldest first.
+fn resolve_sources(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    args: &SquashArgs,
+) -> Result<Vec<Commit>, CommandError> {
+    let mut sources: Vec<Commit> = if args.from.is_empty() {
+        workspace_command.parse_revset(ui, &RevisionArg::AT)?
+    } else {
+        workspace_command.parse_union_revsets(ui, &args.from)?
+    }
+    .evaluate_to_commits()?
+    .try_collect()?;
+    // Reverse the set so we apply the oldest commits first. It shouldn't affect the
+    // result, but it avoids creating transient conflicts and is therefore probably
+    // a little faster.
+    sources.reverse();
+    Ok(sources)
+}
+
+/// Moves the changes to the paths following each `--into` argument into that
+/// destination.
+fn squash_into_multiple_destinations(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    mut workspace_command: WorkspaceCommandHelper,
+    args: &SquashArgs,
+) -> Result<(), CommandError> {
+    if !args.message_paragraphs.is_empty() {
+        return Err(user_error(
+            "`--message` can't be used with more than one destination",
+        ));
+    }
+    let sources = resolve_sources(ui, &workspace_command, args)?;
+    let mut destinations = vec![];
+    for (revision, paths) in group_paths_by_destination(command, args)? {
+        let destination = workspace_command.resolve_single_rev(ui, revision)?;
+        if sources.iter().any(|source| source.id() == destination.id()) {
+            return Err(user_error("Source and destination cannot be the same"));
+        }
+        let matcher = workspace_command
+            .parse_file_patterns(ui, &paths)?
+            .to_matcher();
+        destinations.push((destination, matcher));
+    }
+    let mut tx = workspace_command.start_transaction();
+    let tx_description = format!(
+        "squash commits into {}",
+        destinations
+            .iter()
+            .map(|(destination, _)| destination.id().hex())
+            .join(", ")
+    );
+    move_diff_to_destinations(
+        &mut tx,
+        command.settings(),
+        &sources,
+        &destinations,
+        args.keep_emptied,
+    )?;
+    tx.finish(ui, tx_description)?;
+    Ok(())
+}
+
+/// Assigns each path to the `--into` argument that precedes it on the command
+/// line.
+fn group_paths_by_destination<'a>(
+    command: &CommandHelper,
+    args: &'a SquashArgs,
+) -> Result<Vec<(&'a RevisionArg, Vec<String>)>, CommandError> {
+    let (_, matches) = command
+        .matches()
+        .subcommand()
+        .expect("squash should be a subcommand");
+    let into_indices = matches
+        .indices_of("into")
+        .into_iter()
+        .flatten()
+        .collect_vec();
+    let path_indices = matches.indices_of("paths").into_iter().flatten();
+    let mut groups = args.into.iter().map(|into| (into, vec![])).collect_vec();
+    for (path, path_index) in args.paths.iter().zip(path_indices) {
+        let Some(group_index) = into_indices.iter().rposition(|&index| index < path_index) else {
+            return Err(user_error_with_hint(
+                format!("No destination given for path {path}"),
+                "Put the paths after the `--into` of the revision to move them into",
+            ));
+        };
+        groups[group_index].1.push(path.clone());
+    }
+    if let Some((into, _)) = groups.iter().find(|(_, paths)| paths.is_empty()) {
+        return Err(user_error(format!("No paths given for destination {into}")));
+    }
+    Ok(groups)
+}
+
+/// Moves the changes matching each destination's matcher from the sources into
+/// that destination. A path that matches more than one destination is moved
+/// into the first of them.
+fn move_diff_to_destinations(
+    tx: &mut WorkspaceCommandTransaction,
+    settings: &UserSettings,
+    sources: &[Commit],
+    destinations: &[(Commit, Box<dyn Matcher>)],
+    keep_emptied: bool,
+) -> Result<(), CommandError> {
+    tx.base_workspace_helper().check_rewritable(
+        sources
+            .iter()
+            .chain(destinations.iter().map(|(destination, _)| destination))
+            .ids(),
+    )?;
+
+    struct SourceCommit<'a> {
+        commit: &'a Commit,
+        parent_tree: MergedTree,
+        // The changes to move into each destination, as a tree relative to
+        // `parent_tree`.
+        selected_trees: Vec<MergedTree>,
+    }
+    let store = tx.repo().store().clone();
+    let mut source_commits = vec![];
+    for source in sources {
+        let parent_tree = source.parent_tree(tx.repo())?;
+        let mut remaining_tree = source.tree()?;
+        let mut selected_trees = vec![];
+        for (_, matcher) in destinations {
+            let selected_tree_id = restore_tree(&remaining_tree, &parent_tree, matcher.as_ref())?;
+            let remaining_tree_id = restore_tree(&parent_tree, &remaining_tree, matcher.as_ref())?;
+            selected_trees.push(store.get_root_tree(&selected_tree_id)?);
+            remaining_tree = store.get_root_tree(&remaining_tree_id)?;
+        }
+        let abandon = !keep_emptied && remaining_tree.id() == parent_tree.id();
+        if abandon {
+            tx.repo_mut().record_abandoned_commit(source.id().clone());
+        } else if remaining_tree.id() == *source.tree_id() {
+            // Nothing selected from this commit.
+            continue;
+        } else {
+            tx.repo_mut()
+                .rewrite_commit(settings, source)
+                .set_tree_id(remaining_tree.id())
+                .write()?;
+        }
+        source_commits.push(SourceCommit {
+            commit: source,
+            parent_tree,
+            selected_trees,
+        });
+    }
+    if source_commits.is_empty() {
+        return Ok(());
+    }
+
+    // Destinations may be descendants of the sources or of each other, so they
+    // are rebased before each one is rewritten.
+    let mut destination_ids = destinations
+        .iter()
+        .map(|(destination, _)| destination.id().clone())
+        .collect_vec();
+    for (index, (destination, _)) in destinations.iter().enumerate() {
+        let rebase_map = tx.repo_mut().rebase_descendants_return_map(settings)?;
+        for id in &mut destination_ids {
+            if let Some(new_id) = rebase_map.get(id) {
+                *id = new_id.clone();
+            }
+        }
+        let rewritten_destination = store.get_commit(&destination_ids[index])?;
+        let mut destination_tree = rewritten_destination.tree()?;
+        let mut predecessors = vec![destination.id().clone()];
+        for source in &source_commits {
+            let selected_tree = &source.selected_trees[index];
+            if selected_tree.id() == source.parent_tree.id() {
+                continue;
+            }
+            destination_tree = destination_tree.merge(&source.parent_tree, selected_tree)?;
+            predecessors.push(source.commit.id().clone());
+        }
+        let new_destination = tx
+            .repo_mut()
+            .rewrite_commit(settings, &rewritten_destination)
+            .set_tree_id(destination_tree.id())
+            .set_predecessors(predecessors)
+            .write()?;
+        destination_ids[index] = new_destination.id().clone();
+    }
+    Ok(())
+}
+
 // TODO(#2882): Remove public visibility once `jj move` is deleted.
 pub(crate) enum SquashedDescription {
     // Use this exact description.
@@ -0,0 +1,52 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use tracing::instrument;
+
+use super::resolve_stash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Discard stashed changes without applying them
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct StashDropArgs {
+    /// The index of the stash to drop, as shown by `jj stash list`
+    #[arg(default_value_t = 0)]
+    index: usize,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_stash_drop(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &StashDropArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let stash = resolve_stash(workspace_command.repo().as_ref(), args.index)?;
+    let mut tx = workspace_command.start_transaction();
+    tx.repo_mut()
+        .set_stash_ref_target(&stash.id().hex(), RefTarget::absent());
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Dropped stash ")?;
+        tx.write_commit_summary(formatter.as_mut(), &stash)?;
+        writeln!(formatter)?;
+    }
+    tx.finish(ui, format!("drop stash {}", stash.id().hex()))?;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use tracing::instrument;
+
+use super::load_stashes;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// List the stashed changes, the most recent first
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct StashListArgs {}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_stash_list(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &StashListArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let stashes = load_stashes(workspace_command.repo().as_ref())?;
+    ui.request_pager();
+    let mut formatter = ui.stdout_formatter();
+    for (index, commit) in stashes.iter().enumerate() {
+        write!(formatter, "{index}: ")?;
+        workspace_command.write_commit_summary(formatter.as_mut(), commit)?;
+        writeln!(formatter)?;
+    }
+    Ok(())
+}
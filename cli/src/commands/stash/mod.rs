@@ -0,0 +1,107 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod drop;
+mod list;
+mod pop;
+mod push;
+
+use std::cmp::Reverse;
+
+use clap::Subcommand;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo;
+use tracing::instrument;
+
+use self::drop::cmd_stash_drop;
+use self::drop::StashDropArgs;
+use self::list::cmd_stash_list;
+use self::list::StashListArgs;
+use self::pop::cmd_stash_pop;
+use self::pop::StashPopArgs;
+use self::push::cmd_stash_push;
+use self::push::StashPushArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Set working-copy changes aside and restore them later
+///
+/// `jj stash push` moves the changes in the working-copy commit into a new
+/// commit that isn't visible in the log, leaving the working-copy commit
+/// without changes. `jj stash pop` applies the changes to the working-copy
+/// commit again, even if it has moved to another revision in the meantime.
+///
+/// Stashes are listed by `jj stash list`, the most recent first, and are
+/// referred to by their index in that list. They're recorded in the operation
+/// log, so `jj undo` brings back a stash that was popped or dropped.
+///
+/// This is mostly a convenience for users coming from Git. Since the working
+/// copy is a commit, `jj new` and `jj edit` can be used to switch between
+/// changes without setting them aside.
+#[derive(Subcommand, Clone, Debug)]
+pub(crate) enum StashCommand {
+    Drop(StashDropArgs),
+    List(StashListArgs),
+    Pop(StashPopArgs),
+    Push(StashPushArgs),
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_stash(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &StashCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        StashCommand::Drop(args) => cmd_stash_drop(ui, command, args),
+        StashCommand::List(args) => cmd_stash_list(ui, command, args),
+        StashCommand::Pop(args) => cmd_stash_pop(ui, command, args),
+        StashCommand::Push(args) => cmd_stash_push(ui, command, args),
+    }
+}
+
+/// Returns the stashed commits, the most recently stashed first.
+fn load_stashes(repo: &dyn Repo) -> Result<Vec<Commit>, CommandError> {
+    // A ref can only become conflicted by being restored concurrently, in
+    // which case all of its commits are kept.
+    let ids = repo
+        .view()
+        .stash_refs()
+        .values()
+        .flat_map(|target| target.added_ids())
+        .unique();
+    let mut commits: Vec<_> = ids.map(|id| repo.store().get_commit(id)).try_collect()?;
+    commits.sort_by_key(|commit| {
+        (
+            Reverse(commit.committer().timestamp.timestamp),
+            commit.id().clone(),
+        )
+    });
+    Ok(commits)
+}
+
+/// Returns the stash at `index` in the list of stashes.
+fn resolve_stash(repo: &dyn Repo, index: usize) -> Result<Commit, CommandError> {
+    let mut stashes = load_stashes(repo)?;
+    if stashes.is_empty() {
+        return Err(user_error("There are no stashed changes"));
+    }
+    if index >= stashes.len() {
+        return Err(user_error(format!("No stash at index {index}")));
+    }
+    Ok(stashes.swap_remove(index))
+}
@@ -0,0 +1,74 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::repo::Repo as _;
+use tracing::instrument;
+
+use super::resolve_stash;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Apply stashed changes to the working-copy commit and drop the stash
+///
+/// The changes are applied the same way as rebasing the stash onto the
+/// working-copy commit, so they can be popped after the working copy has moved
+/// to another revision. If they conflict with the working-copy contents, the
+/// conflicts are recorded in the working-copy commit to be resolved like any
+/// other conflict, and the stash is dropped all the same. Use `jj undo` to
+/// get the stash back instead.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct StashPopArgs {
+    /// The index of the stash to apply, as shown by `jj stash list`
+    #[arg(default_value_t = 0)]
+    index: usize,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_stash_pop(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &StashPopArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let Some(wc_commit_id) = workspace_command.get_wc_commit_id().cloned() else {
+        return Err(user_error("This command requires a working copy"));
+    };
+    let wc_commit = workspace_command.repo().store().get_commit(&wc_commit_id)?;
+    workspace_command.check_rewritable([wc_commit.id()])?;
+    let stash = resolve_stash(workspace_command.repo().as_ref(), args.index)?;
+    let stash_base_tree = stash.parent_tree(workspace_command.repo().as_ref())?;
+    let new_tree = wc_commit.tree()?.merge(&stash_base_tree, &stash.tree()?)?;
+
+    let mut tx = workspace_command.start_transaction();
+    tx.repo_mut()
+        .rewrite_commit(command.settings(), &wc_commit)
+        .set_tree_id(new_tree.id())
+        .write()?;
+    tx.repo_mut().rebase_descendants(command.settings())?;
+    tx.repo_mut()
+        .set_stash_ref_target(&stash.id().hex(), RefTarget::absent());
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Applied stash ")?;
+        tx.write_commit_summary(formatter.as_mut(), &stash)?;
+        writeln!(formatter)?;
+    }
+    tx.finish(ui, format!("pop stash {}", stash.id().hex()))?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::repo::Repo as _;
+use jj_lib::rewrite::restore_tree;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::description_util::join_message_paragraphs;
+use crate::ui::Ui;
+
+/// Move the working-copy changes into a new stash
+///
+/// The changes are removed from the working-copy commit and saved in a commit
+/// on top of its parents, which isn't visible in the log. The stash gets the
+/// working-copy commit's description unless a message is given.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct StashPushArgs {
+    /// Stash only the changes in these paths (instead of all paths)
+    #[arg(value_hint = clap::ValueHint::AnyPath)]
+    paths: Vec<String>,
+    /// The description of the stash
+    #[arg(long = "message", short, value_name = "MESSAGE")]
+    message_paragraphs: Vec<String>,
+}
+
+#[instrument(skip_all)]
+pub(crate) fn cmd_stash_push(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &StashPushArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let Some(wc_commit_id) = workspace_command.get_wc_commit_id().cloned() else {
+        return Err(user_error("This command requires a working copy"));
+    };
+    let wc_commit = workspace_command.repo().store().get_commit(&wc_commit_id)?;
+    workspace_command.check_rewritable([wc_commit.id()])?;
+    let matcher = workspace_command
+        .parse_file_patterns(ui, &args.paths)?
+        .to_matcher();
+    let parent_tree = wc_commit.parent_tree(workspace_command.repo().as_ref())?;
+    let wc_tree = wc_commit.tree()?;
+    let stash_tree_id = restore_tree(&wc_tree, &parent_tree, matcher.as_ref())?;
+    if stash_tree_id == parent_tree.id() {
+        writeln!(ui.status(), "No changes to stash.")?;
+        return Ok(());
+    }
+    let remaining_tree_id = restore_tree(&parent_tree, &wc_tree, matcher.as_ref())?;
+    let description = if args.message_paragraphs.is_empty() {
+        wc_commit.description().to_owned()
+    } else {
+        join_message_paragraphs(&args.message_paragraphs)
+    };
+
+    let mut tx = workspace_command.start_transaction();
+    let stash = tx
+        .repo_mut()
+        .new_commit(
+            command.settings(),
+            wc_commit.parent_ids().to_vec(),
+            stash_tree_id,
+        )
+        .set_description(description)
+        .write()?;
+    // The stash is kept alive by its ref, but shouldn't show up in the log.
+    tx.repo_mut().remove_head(stash.id());
+    tx.repo_mut()
+        .set_stash_ref_target(&stash.id().hex(), RefTarget::normal(stash.id().clone()));
+    tx.repo_mut()
+        .rewrite_commit(command.settings(), &wc_commit)
+        .set_tree_id(remaining_tree_id)
+        .write()?;
+    tx.repo_mut().rebase_descendants(command.settings())?;
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Stashed changes as ")?;
+        tx.write_commit_summary(formatter.as_mut(), &stash)?;
+        writeln!(formatter)?;
+    }
+    tx.finish(
+        ui,
+        format!("stash changes in commit {}", wc_commit.id().hex()),
+    )?;
+    Ok(())
+}
@@ -17,6 +17,7 @@ use jj_lib::copies::CopyRecords;
 use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetFilterPredicate;
+use jj_lib::settings::ConfigResultExt as _;
 use tracing::instrument;
 
 use crate::cli_util::print_conflicted_paths;
@@ -56,6 +57,14 @@ pub(crate) fn cmd_status(
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
         .to_matcher();
+    let status_template = command
+        .settings()
+        .config()
+        .get_string("templates.status")
+        .optional()?
+        .map(|text| workspace_command.parse_commit_template(ui, &text))
+        .transpose()?
+        .map(|template| template.labeled("status"));
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     let formatter = formatter.as_mut();
@@ -69,7 +78,7 @@ pub(crate) fn cmd_status(
             writeln!(formatter, "Working copy changes:")?;
             let mut copy_records = CopyRecords::default();
             for parent in wc_commit.parent_ids() {
-                let records = get_copy_records(repo.store(), parent, wc_commit.id(), &matcher)?;
+                let records = get_copy_records(repo.as_ref(), parent, wc_commit.id(), &matcher)?;
                 copy_records.add_records(records)?;
             }
             let diff_renderer = workspace_command.diff_renderer(vec![DiffFormat::Summary]);
@@ -96,15 +105,19 @@ pub(crate) fn cmd_status(
             print_conflicted_paths(&conflicts, formatter, &workspace_command)?
         }
 
-        let template = workspace_command.commit_summary_template();
-        write!(formatter, "Working copy : ")?;
-        formatter.with_label("working_copy", |fmt| template.format(wc_commit, fmt))?;
-        writeln!(formatter)?;
-        for parent in wc_commit.parents() {
-            let parent = parent?;
-            write!(formatter, "Parent commit: ")?;
-            template.format(&parent, formatter)?;
+        if let Some(template) = &status_template {
+            template.format(wc_commit, formatter)?;
+        } else {
+            let template = workspace_command.commit_summary_template();
+            write!(formatter, "Working copy : ")?;
+            formatter.with_label("working_copy", |fmt| template.format(wc_commit, fmt))?;
             writeln!(formatter)?;
+            for parent in wc_commit.parents() {
+                let parent = parent?;
+                write!(formatter, "Parent commit: ")?;
+                template.format(&parent, formatter)?;
+                writeln!(formatter)?;
+            }
         }
 
         if wc_commit.has_conflict()? {
@@ -12,19 +12,78 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use clap::builder::NonEmptyStringValueParser;
+use itertools::Itertools as _;
+use jj_lib::git;
+use jj_lib::git_backend::GitBackend;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::RefTarget;
+use jj_lib::repo::Repo as _;
 use jj_lib::str_util::StringPattern;
+use jj_lib::view::View;
 
 use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
+use crate::commands::git::push::get_default_push_remote;
+use crate::commands::git::push::map_push_error;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::commit_templater::RefName;
+use crate::git_util::get_git_repo;
+use crate::git_util::print_failed_git_export;
+use crate::git_util::with_remote_git_callbacks;
 use crate::ui::Ui;
 
 /// Manage tags.
 #[derive(clap::Subcommand, Clone, Debug)]
 pub enum TagCommand {
+    #[command(visible_alias("c"))]
+    Create(TagCreateArgs),
+    #[command(visible_alias("d"))]
+    Delete(TagDeleteArgs),
     #[command(visible_alias("l"))]
     List(TagListArgs),
+    Push(TagPushArgs),
+}
+
+/// Create new tags
+///
+/// Tags are lightweight unless a message is given. Annotated tags are only
+/// supported in repos backed by Git, where the tag object is written to the
+/// Git repo right away.
+///
+/// Tagged commits are immutable by default (see `immutable_heads()`), so
+/// tagging the working-copy commit creates a new working-copy commit on top of
+/// it.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagCreateArgs {
+    /// The tag's target revision
+    #[arg(long, short, default_value = "@")]
+    revision: RevisionArg,
+    /// Create an annotated tag with this message
+    #[arg(long, short)]
+    message: Option<String>,
+    /// The tags to create
+    #[arg(required = true, value_parser = NonEmptyStringValueParser::new())]
+    names: Vec<String>,
+}
+
+/// Delete existing tags
+///
+/// In repos backed by Git, the tags are deleted from the Git repo the next
+/// time the refs are exported. Tags that were already pushed aren't deleted
+/// from the remote.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagDeleteArgs {
+    /// The tags to delete
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select tags by wildcard pattern. For details, see
+    /// https://martinvonz.github.io/jj/latest/revsets/#string-patterns.
+    #[arg(required = true, value_parser = StringPattern::parse)]
+    names: Vec<StringPattern>,
 }
 
 /// List tags.
@@ -52,10 +111,86 @@ pub fn cmd_tag(
     subcommand: &TagCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        TagCommand::Create(args) => cmd_tag_create(ui, command, args),
+        TagCommand::Delete(args) => cmd_tag_delete(ui, command, args),
         TagCommand::List(args) => cmd_tag_list(ui, command, args),
+        TagCommand::Push(args) => cmd_tag_push(ui, command, args),
     }
 }
 
+fn cmd_tag_create(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagCreateArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
+    if target_commit.id() == workspace_command.repo().store().root_commit_id() {
+        return Err(user_error("Cannot tag the root commit"));
+    }
+    let view = workspace_command.repo().view();
+    for name in &args.names {
+        if view.get_tag(name).is_present() {
+            return Err(user_error_with_hint(
+                format!("Tag already exists: {name}"),
+                "Use `jj tag delete` to delete it first.",
+            ));
+        }
+    }
+    let is_git_backed = workspace_command
+        .repo()
+        .store()
+        .backend_impl()
+        .downcast_ref::<GitBackend>()
+        .is_some();
+    if args.message.is_some() && !is_git_backed {
+        return Err(user_error(
+            "Annotated tags are only supported in repos backed by Git",
+        ));
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    for name in &args.names {
+        if let Some(message) = &args.message {
+            let tagger = command.settings().signature();
+            git::create_annotated_tag(tx.repo_mut(), name, target_commit.id(), message, &tagger)?;
+        } else {
+            tx.repo_mut()
+                .set_tag_target(name, RefTarget::normal(target_commit.id().clone()));
+        }
+    }
+    if let Some(mut formatter) = ui.status_formatter() {
+        write!(formatter, "Created {} tags pointing to ", args.names.len())?;
+        tx.write_commit_summary(formatter.as_mut(), &target_commit)?;
+        writeln!(formatter)?;
+    }
+    tx.finish(
+        ui,
+        format!(
+            "create tag {names} pointing to commit {id}",
+            names = args.names.join(", "),
+            id = target_commit.id().hex()
+        ),
+    )?;
+    Ok(())
+}
+
+fn cmd_tag_delete(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagDeleteArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let tag_names = find_tags(workspace_command.repo().view(), &args.names)?;
+    let mut tx = workspace_command.start_transaction();
+    for name in &tag_names {
+        tx.repo_mut().set_tag_target(name, RefTarget::absent());
+    }
+    writeln!(ui.status(), "Deleted {} tags.", tag_names.len())?;
+    tx.finish(ui, format!("delete tag {}", tag_names.join(", ")))?;
+    Ok(())
+}
+
 fn cmd_tag_list(
     ui: &mut Ui,
     command: &CommandHelper,
@@ -89,3 +224,88 @@ fn cmd_tag_list(
 
     Ok(())
 }
+
+/// Push tags to a Git remote
+///
+/// The tags are pushed as they are in the underlying Git repo, so annotated
+/// tags keep their messages. The remote rejects tags that already exist there
+/// with a different target.
+#[derive(clap::Args, Clone, Debug)]
+pub struct TagPushArgs {
+    /// The remote to push to (only named remotes are supported)
+    ///
+    /// Defaults to the `git.push` setting, like `jj git push`.
+    #[arg(long)]
+    remote: Option<String>,
+    /// The tags to push
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select tags by wildcard pattern. For details, see
+    /// https://martinvonz.github.io/jj/latest/revsets/#string-patterns.
+    #[arg(required = true, value_parser = StringPattern::parse)]
+    names: Vec<StringPattern>,
+}
+
+fn cmd_tag_push(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &TagPushArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let git_repo = get_git_repo(workspace_command.repo().store())?;
+    let tag_names = find_tags(workspace_command.repo().view(), &args.names)?;
+    let remote = match &args.remote {
+        Some(remote) => remote.clone(),
+        None => get_default_push_remote(ui, command.settings(), &git_repo)?,
+    };
+
+    // Tags created since the last export aren't in the Git repo yet.
+    let mut tx = workspace_command.start_transaction();
+    let failed_refs = git::export_some_refs(
+        tx.repo_mut(),
+        |ref_name| matches!(ref_name, git::RefName::Tag(name) if tag_names.contains(name)),
+    )?;
+    print_failed_git_export(ui, &failed_refs)?;
+    if tx.repo().has_changes() {
+        tx.finish(ui, "export git refs")?;
+    }
+
+    with_remote_git_callbacks(ui, None, |cb| {
+        git::push_tags(
+            &git_repo,
+            &remote,
+            &tag_names,
+            cb,
+            &command.settings().git_settings(),
+        )
+    })
+    .map_err(map_push_error)?;
+    writeln!(
+        ui.status(),
+        "Pushed {} tags to {remote}: {}",
+        tag_names.len(),
+        tag_names.join(", ")
+    )?;
+    Ok(())
+}
+
+/// Returns the names of the tags matching the patterns. Fails if a pattern
+/// doesn't match any tag.
+fn find_tags(view: &View, name_patterns: &[StringPattern]) -> Result<Vec<String>, CommandError> {
+    let mut tag_names = vec![];
+    for pattern in name_patterns {
+        let matches = view
+            .tags()
+            .keys()
+            .filter(|name| pattern.matches(name))
+            .cloned()
+            .collect_vec();
+        if matches.is_empty() {
+            return Err(user_error(format!(
+                "No matching tags for pattern: {pattern}"
+            )));
+        }
+        tag_names.extend(matches);
+    }
+    Ok(tag_names.into_iter().sorted().dedup().collect())
+}
@@ -18,6 +18,7 @@ use itertools::Itertools;
 use jj_lib::commit::CommitIteratorExt;
 use jj_lib::file_util;
 use jj_lib::file_util::IoResultExt;
+use jj_lib::git;
 use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo::Repo;
 use jj_lib::rewrite::merge_commit_trees;
@@ -28,7 +29,12 @@ use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::internal_error_with_message;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
+use crate::commands::git::maybe_add_gitignore;
+use crate::commands::sparse::sparse_profile_patterns;
+use crate::git_util::get_git_repo;
+use crate::git_util::is_linked_git_worktree;
 use crate::ui::Ui;
 
 /// How to handle sparse patterns when creating a new workspace.
@@ -45,7 +51,10 @@ enum SparseInheritance {
 /// Add a workspace
 ///
 /// By default, the new workspace inherits the sparse patterns of the current
-/// workspace. You can override this with the `--sparse-patterns` option.
+/// workspace. You can override this with the `--sparse-patterns` option, copy
+/// the patterns of another workspace with `--sparse-from`, or use the patterns
+/// of a named profile from the `sparse.profiles` config table with
+/// `--sparse-profile`.
 #[derive(clap::Args, Clone, Debug)]
 pub struct WorkspaceAddArgs {
     /// Where to create the new workspace
@@ -73,6 +82,31 @@ pub struct WorkspaceAddArgs {
     /// How to handle sparse patterns when creating a new workspace.
     #[arg(long, value_enum, default_value_t = SparseInheritance::Copy)]
     sparse_patterns: SparseInheritance,
+    /// Copy the sparse patterns from the workspace at this path
+    #[arg(
+        long,
+        value_name = "WORKSPACE",
+        value_hint = clap::ValueHint::DirPath,
+        conflicts_with = "sparse_patterns"
+    )]
+    sparse_from: Option<String>,
+    /// Use the sparse patterns of this profile from the `sparse.profiles`
+    /// config table
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with_all = ["sparse_patterns", "sparse_from"]
+    )]
+    sparse_profile: Option<String>,
+    /// Create the workspace as a linked worktree of the underlying Git repo
+    ///
+    /// Git commands run in the new workspace see the parent of its
+    /// working-copy commit as HEAD, and jj picks up HEAD changes made by Git.
+    /// If the destination is an existing worktree of the Git repo (e.g.
+    /// created by `git worktree add`), it's adopted as the new workspace, and
+    /// its HEAD is checked out.
+    #[arg(long)]
+    colocate: bool,
 }
 
 #[instrument(skip_all)]
@@ -83,7 +117,44 @@ pub fn cmd_workspace_add(
 ) -> Result<(), CommandError> {
     let old_workspace_command = command.workspace_helper(ui)?;
     let destination_path = command.cwd().join(&args.destination);
-    if destination_path.exists() {
+    // Look up the sparse patterns before creating anything, so a bad source
+    // doesn't leave a half-created workspace behind.
+    let sparsity = if let Some(source_path) = &args.sparse_from {
+        let source_workspace = command.load_workspace_at(source_path)?;
+        Some(source_workspace.working_copy().sparse_patterns()?.to_vec())
+    } else if let Some(name) = &args.sparse_profile {
+        Some(sparse_profile_patterns(command.settings(), name)?)
+    } else {
+        match args.sparse_patterns {
+            SparseInheritance::Full => None,
+            SparseInheritance::Empty => Some(vec![]),
+            SparseInheritance::Copy => {
+                let sparse_patterns = old_workspace_command
+                    .working_copy()
+                    .sparse_patterns()?
+                    .to_vec();
+                Some(sparse_patterns)
+            }
+        }
+    };
+    let git_repo = if args.colocate {
+        Some(get_git_repo(old_workspace_command.repo().store())?)
+    } else {
+        None
+    };
+    let adopt_worktree = destination_path.exists()
+        && args.colocate
+        && is_linked_git_worktree(
+            old_workspace_command.git_backend().unwrap(),
+            &destination_path,
+        );
+    if adopt_worktree {
+        if !args.revision.is_empty() {
+            return Err(user_error(
+                "Cannot specify revisions when adopting an existing Git worktree",
+            ));
+        }
+    } else if destination_path.exists() {
         return Err(user_error("Workspace already exists"));
     } else {
         fs::create_dir(&destination_path).context(&destination_path)?;
@@ -106,6 +177,11 @@ pub fn cmd_workspace_add(
         )));
     }
 
+    if let Some(git_repo) = git_repo.as_ref().filter(|_| !adopt_worktree) {
+        git::add_worktree(git_repo, &name, &destination_path)
+            .map_err(|err| user_error_with_message("Failed to create Git worktree", err))?;
+    }
+
     let working_copy_factory = command.get_working_copy_factory()?;
     let repo_path = old_workspace_command.repo_path();
     let (new_workspace, repo) = Workspace::init_workspace_with_existing_repo(
@@ -132,29 +208,26 @@ pub fn cmd_workspace_add(
     }
 
     let mut new_workspace_command = command.for_workable_repo(ui, new_workspace, repo)?;
-
-    let sparsity = match args.sparse_patterns {
-        SparseInheritance::Full => None,
-        SparseInheritance::Empty => Some(vec![]),
-        SparseInheritance::Copy => {
-            let sparse_patterns = old_workspace_command
-                .working_copy()
-                .sparse_patterns()?
-                .to_vec();
-            Some(sparse_patterns)
-        }
-    };
+    maybe_add_gitignore(&new_workspace_command)?;
 
     if let Some(sparse_patterns) = sparsity {
+        let checkout_options = new_workspace_command.checkout_options()?;
         let (mut locked_ws, _wc_commit) = new_workspace_command.start_working_copy_mutation()?;
         locked_ws
             .locked_wc()
-            .set_sparse_patterns(sparse_patterns)
+            .set_sparse_patterns(sparse_patterns, &checkout_options)
             .map_err(|err| internal_error_with_message("Failed to set sparse patterns", err))?;
         let operation_id = locked_ws.locked_wc().old_operation_id().clone();
         locked_ws.finish(operation_id)?;
     }
 
+    if adopt_worktree {
+        // Check out the worktree HEAD, and snapshot the existing files on top
+        // of it.
+        new_workspace_command.maybe_snapshot(ui)?;
+        return Ok(());
+    }
+
     let mut tx = new_workspace_command.start_transaction();
 
     // If no parent revisions are specified, create a working-copy commit based
@@ -13,6 +13,8 @@
 // limitations under the License.
 
 use jj_lib::op_store::WorkspaceId;
+use jj_lib::workspace::register_workspace_root;
+use jj_lib::workspace::unregister_workspace_root;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -74,5 +76,12 @@ pub fn cmd_workspace_rename(
     ));
     locked_ws.finish(repo.op_id().clone())?;
 
+    let workspace = workspace_command.workspace();
+    unregister_workspace_root(workspace.repo_path(), &old_workspace_id)?;
+    register_workspace_root(
+        workspace.repo_path(),
+        workspace.workspace_id(),
+        workspace.workspace_root(),
+    )?;
     Ok(())
 }
@@ -19,6 +19,9 @@ use jj_lib::op_store::OpStoreError;
 use jj_lib::operation::Operation;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::workspace::register_workspace_root;
+use jj_lib::workspace::registered_workspace_roots;
+use jj_lib::workspace::Workspace;
 use tracing::instrument;
 
 use crate::cli_util::check_stale_working_copy;
@@ -37,44 +40,109 @@ use crate::ui::Ui;
 /// For information about stale working copies, see
 /// https://martinvonz.github.io/jj/latest/working-copy/.
 #[derive(clap::Args, Clone, Debug)]
-pub struct WorkspaceUpdateStaleArgs {}
+pub struct WorkspaceUpdateStaleArgs {
+    /// Update all stale workspaces of the repo, not just the current one
+    ///
+    /// Workspaces created by older versions of jj are only found once they
+    /// have been updated from within.
+    #[arg(long)]
+    all: bool,
+}
 
 #[instrument(skip_all)]
 pub fn cmd_workspace_update_stale(
     ui: &mut Ui,
     command: &CommandHelper,
-    _args: &WorkspaceUpdateStaleArgs,
+    args: &WorkspaceUpdateStaleArgs,
 ) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    register_workspace_root(
+        workspace.repo_path(),
+        workspace.workspace_id(),
+        workspace.workspace_root(),
+    )?;
+    if !args.all {
+        let updated = update_stale_workspace(ui, command, &|| command.load_workspace(), false)?;
+        if !updated {
+            writeln!(
+                ui.status(),
+                "Nothing to do (the working copy is not stale)."
+            )?;
+        }
+        return Ok(());
+    }
+
+    let repo_loader = workspace.repo_loader();
+    let op_head = command.resolve_operation(ui, repo_loader)?;
+    let repo = repo_loader.load_at(&op_head)?;
+    let mut num_updated = 0;
+    for (workspace_id, workspace_root) in registered_workspace_roots(workspace.repo_path())? {
+        // Forgotten workspaces are still registered, since forgetting them can
+        // be undone.
+        if repo.view().get_wc_commit_id(&workspace_id).is_none() {
+            continue;
+        }
+        let load_workspace = || {
+            let workspace_root = workspace_root.to_str().unwrap();
+            command.load_workspace_at(workspace_root)
+        };
+        // The workspace may have been deleted from disk without being
+        // forgotten.
+        if let Err(err) = load_workspace() {
+            writeln!(
+                ui.warning_default(),
+                "Skipping workspace {} at {}: {}",
+                workspace_id.as_str(),
+                workspace_root.display(),
+                err.error
+            )?;
+            continue;
+        }
+        if update_stale_workspace(ui, command, &load_workspace, true)? {
+            num_updated += 1;
+        }
+    }
+    if num_updated == 0 {
+        writeln!(ui.status(), "Nothing to do (no working copy is stale).")?;
+    }
+    Ok(())
+}
+
+/// Updates the workspace loaded by `load_workspace` if it's stale. Returns
+/// whether the working copy was updated.
+fn update_stale_workspace(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    load_workspace: &dyn Fn() -> Result<Workspace, CommandError>,
+    show_workspace_name: bool,
+) -> Result<bool, CommandError> {
     // Snapshot the current working copy on top of the last known working-copy
     // operation, then merge the divergent operations. The wc_commit_id of the
     // merged repo wouldn't change because the old one wins, but it's probably
     // fine if we picked the new wc_commit_id.
     let known_wc_commit = {
-        let (mut workspace_command, recovered) = for_stale_working_copy(ui, command)?;
+        let (mut workspace_command, recovered) =
+            for_stale_working_copy(ui, command, load_workspace)?;
         workspace_command.maybe_snapshot(ui)?;
 
         if recovered {
             // We have already recovered from the situation that prompted the user to run
             // this command, and it is known that the workspace is not stale
             // (since we just updated it), so we can return early.
-            return Ok(());
+            return Ok(true);
         }
 
         let wc_commit_id = workspace_command.get_wc_commit_id().unwrap();
         workspace_command.repo().store().get_commit(wc_commit_id)?
     };
-    let mut workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let mut workspace_command = workspace_helper_at_head(ui, command, load_workspace()?)?;
 
     let repo = workspace_command.repo().clone();
+    let checkout_options = workspace_command.checkout_options()?;
     let (mut locked_ws, desired_wc_commit) =
         workspace_command.unchecked_start_working_copy_mutation()?;
     match check_stale_working_copy(locked_ws.locked_wc(), &desired_wc_commit, &repo)? {
-        WorkingCopyFreshness::Fresh | WorkingCopyFreshness::Updated(_) => {
-            writeln!(
-                ui.status(),
-                "Nothing to do (the working copy is not stale)."
-            )?;
-        }
+        WorkingCopyFreshness::Fresh | WorkingCopyFreshness::Updated(_) => Ok(false),
         WorkingCopyFreshness::WorkingCopyStale | WorkingCopyFreshness::SiblingOperation => {
             // The same check as start_working_copy_mutation(), but with the stale
             // working-copy commit.
@@ -83,7 +151,7 @@ pub fn cmd_workspace_update_stale(
             }
             let stats = locked_ws
                 .locked_wc()
-                .check_out(&desired_wc_commit)
+                .check_out(&desired_wc_commit, &checkout_options)
                 .map_err(|err| {
                     internal_error_with_message(
                         format!(
@@ -95,23 +163,44 @@ pub fn cmd_workspace_update_stale(
                 })?;
             locked_ws.finish(repo.op_id().clone())?;
             if let Some(mut formatter) = ui.status_formatter() {
-                write!(formatter, "Working copy now at: ")?;
+                if show_workspace_name {
+                    write!(
+                        formatter,
+                        "Working copy of workspace {} now at: ",
+                        workspace_command.workspace_id().as_str()
+                    )?;
+                } else {
+                    write!(formatter, "Working copy now at: ")?;
+                }
                 formatter.with_label("working_copy", |fmt| {
                     workspace_command.write_commit_summary(fmt, &desired_wc_commit)
                 })?;
                 writeln!(formatter)?;
             }
             print_checkout_stats(ui, stats, &desired_wc_commit)?;
+            Ok(true)
         }
     }
-    Ok(())
+}
+
+/// Loads the workspace and the repo at the head operation, without
+/// snapshotting the working copy.
+fn workspace_helper_at_head(
+    ui: &Ui,
+    command: &CommandHelper,
+    workspace: Workspace,
+) -> Result<WorkspaceCommandHelper, CommandError> {
+    let op_head = command.resolve_operation(ui, workspace.repo_loader())?;
+    let repo = workspace.repo_loader().load_at(&op_head)?;
+    command.for_workable_repo(ui, workspace, repo)
 }
 
 fn create_and_check_out_recovery_commit(
     ui: &mut Ui,
     command: &CommandHelper,
+    load_workspace: &dyn Fn() -> Result<Workspace, CommandError>,
 ) -> Result<Arc<ReadonlyRepo>, CommandError> {
-    let mut workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let mut workspace_command = workspace_helper_at_head(ui, command, load_workspace()?)?;
     let workspace_id = workspace_command.workspace_id().clone();
     let mut tx = workspace_command.start_transaction().into_inner();
 
@@ -146,8 +235,9 @@ fn create_and_check_out_recovery_commit(
 fn for_stale_working_copy(
     ui: &mut Ui,
     command: &CommandHelper,
+    load_workspace: &dyn Fn() -> Result<Workspace, CommandError>,
 ) -> Result<(WorkspaceCommandHelper, bool), CommandError> {
-    let workspace = command.load_workspace()?;
+    let workspace = load_workspace()?;
     let op_store = workspace.repo_loader().op_store();
     let (repo, recovered) = {
         let op_id = workspace.working_copy().operation_id();
@@ -166,7 +256,10 @@ fn for_stale_working_copy(
                     "Failed to read working copy's current operation; attempting recovery. Error \
                      message from read attempt: {e}"
                 )?;
-                (create_and_check_out_recovery_commit(ui, command)?, true)
+                (
+                    create_and_check_out_recovery_commit(ui, command, load_workspace)?,
+                    true,
+                )
             }
             Err(e) => return Err(e.into()),
         }
@@ -13,31 +13,45 @@
 // limitations under the License.
 
 use std::any::Any;
+use std::cell::RefCell;
 use std::cmp::max;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io;
 use std::rc::Rc;
+use std::slice;
 
 use futures::stream::BoxStream;
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
 use itertools::Itertools as _;
 use jj_lib::backend::BackendResult;
 use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::copies::CopiesTreeDiffEntry;
+use jj_lib::copies::CopiesTreeDiffEntryPath;
+use jj_lib::copies::CopyOperation;
 use jj_lib::copies::CopyRecords;
 use jj_lib::extensions_map::ExtensionsMap;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetExpression;
 use jj_lib::git;
+use jj_lib::git_backend::GitBackend;
 use jj_lib::hex_util::to_reverse_hex;
 use jj_lib::id_prefix::IdPrefixContext;
+use jj_lib::mailmap::Mailmap;
 use jj_lib::matchers::Matcher;
+use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::op_store::OpStoreResult;
+use jj_lib::op_store::OperationId;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
 use jj_lib::op_store::WorkspaceId;
+use jj_lib::op_walk;
+use jj_lib::operation::Operation;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::revset;
@@ -47,9 +61,14 @@ use jj_lib::revset::RevsetModifier;
 use jj_lib::revset::RevsetParseContext;
 use jj_lib::store::Store;
 use once_cell::unsync::OnceCell;
+use pollster::FutureExt as _;
 
 use crate::diff_util;
+use crate::diff_util::DiffRenderError;
+use crate::diff_util::DiffStats;
 use crate::formatter::Formatter;
+use crate::operation_templater;
+use crate::operation_templater::OperationTemplateTypes;
 use crate::revset_util;
 use crate::template_builder;
 use crate::template_builder::merge_fn_map;
@@ -57,6 +76,7 @@ use crate::template_builder::BuildContext;
 use crate::template_builder::CoreTemplateBuildFnTable;
 use crate::template_builder::CoreTemplatePropertyKind;
 use crate::template_builder::IntoTemplateProperty;
+use crate::template_builder::TemplateBuildFunctionFnMap;
 use crate::template_builder::TemplateBuildMethodFnMap;
 use crate::template_builder::TemplateLanguage;
 use crate::template_parser;
@@ -65,6 +85,8 @@ use crate::template_parser::FunctionCallNode;
 use crate::template_parser::TemplateParseError;
 use crate::template_parser::TemplateParseResult;
 use crate::templater;
+use crate::templater::JsonValue;
+use crate::templater::Literal;
 use crate::templater::PlainTextFormattedProperty;
 use crate::templater::SizeHint;
 use crate::templater::Template;
@@ -92,14 +114,19 @@ pub struct CommitTemplateLanguage<'repo> {
     revset_parse_context: RevsetParseContext<'repo>,
     id_prefix_context: &'repo IdPrefixContext,
     immutable_expression: Rc<RevsetExpression>,
+    operation: Operation,
     build_fn_table: CommitTemplateBuildFnTable<'repo>,
     keyword_cache: CommitKeywordCache<'repo>,
     cache_extensions: ExtensionsMap,
+    // Names of the commit methods referenced by the parsed templates, used to
+    // decide which data is worth reading ahead of rendering.
+    used_commit_methods: RefCell<HashSet<String>>,
 }
 
 impl<'repo> CommitTemplateLanguage<'repo> {
     /// Sets up environment where commit template will be transformed to
     /// evaluation tree.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         repo: &'repo dyn Repo,
         path_converter: &'repo RepoPathUiConverter,
@@ -107,6 +134,7 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         revset_parse_context: RevsetParseContext<'repo>,
         id_prefix_context: &'repo IdPrefixContext,
         immutable_expression: Rc<RevsetExpression>,
+        operation: &Operation,
         extensions: &[impl AsRef<dyn CommitTemplateLanguageExtension>],
     ) -> Self {
         let mut build_fn_table = CommitTemplateBuildFnTable::builtin();
@@ -126,11 +154,38 @@ impl<'repo> CommitTemplateLanguage<'repo> {
             revset_parse_context,
             id_prefix_context,
             immutable_expression,
+            operation: operation.clone(),
             build_fn_table,
             keyword_cache: CommitKeywordCache::default(),
             cache_extensions,
+            used_commit_methods: RefCell::new(HashSet::new()),
         }
     }
+
+    /// Returns the data of commits the templates parsed so far will read
+    /// besides the commits themselves.
+    pub fn commit_prefetch_fields(&self) -> CommitPrefetchFields {
+        let used = self.used_commit_methods.borrow();
+        CommitPrefetchFields {
+            parents: ["parents", "empty", "diff"]
+                .iter()
+                .any(|name| used.contains(*name)),
+        }
+    }
+
+    fn record_commit_method(&self, name: &str) {
+        let mut used = self.used_commit_methods.borrow_mut();
+        if !used.contains(name) {
+            used.insert(name.to_owned());
+        }
+    }
+}
+
+/// Data of commits that should be read ahead of rendering them.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CommitPrefetchFields {
+    /// The parent commits, e.g. to tell whether a commit is empty.
+    pub parents: bool,
 }
 
 impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
@@ -162,12 +217,14 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
             CommitTemplatePropertyKind::Commit(property) => {
                 let table = &self.build_fn_table.commit_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
+                self.record_commit_method(function.name);
                 build(self, build_ctx, property, function)
             }
             CommitTemplatePropertyKind::CommitOpt(property) => {
                 let type_name = "Commit";
                 let table = &self.build_fn_table.commit_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
+                self.record_commit_method(function.name);
                 let inner_property = property.try_unwrap(type_name);
                 build(self, build_ctx, Box::new(inner_property), function)
             }
@@ -218,6 +275,43 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(self, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::TreeDiffEntry(property) => {
+                let table = &self.build_fn_table.tree_diff_entry_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::TreeDiffEntryList(property) => {
+                // TODO: migrate to table?
+                template_builder::build_unformattable_list_method(
+                    self,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_tree_diff_entry,
+                )
+            }
+            CommitTemplatePropertyKind::DiffStats(property) => {
+                let table = &self.build_fn_table.diff_stats_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::Operation(property) => {
+                let table = &self.build_fn_table.operation_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::OperationOpt(property) => {
+                let type_name = "Operation";
+                let table = &self.build_fn_table.operation_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                let inner_property = property.try_unwrap(type_name);
+                build(self, build_ctx, Box::new(inner_property), function)
+            }
+            CommitTemplatePropertyKind::OperationId(property) => {
+                let table = &self.build_fn_table.operation_id_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, build_ctx, property, function)
+            }
         }
     }
 }
@@ -294,6 +388,44 @@ impl<'repo> CommitTemplateLanguage<'repo> {
     ) -> CommitTemplatePropertyKind<'repo> {
         CommitTemplatePropertyKind::TreeDiff(Box::new(property))
     }
+
+    pub fn wrap_tree_diff_entry(
+        property: impl TemplateProperty<Output = TreeDiffEntry> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TreeDiffEntry(Box::new(property))
+    }
+
+    pub fn wrap_tree_diff_entry_list(
+        property: impl TemplateProperty<Output = Vec<TreeDiffEntry>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TreeDiffEntryList(Box::new(property))
+    }
+
+    pub fn wrap_diff_stats(
+        property: impl TemplateProperty<Output = DiffStatsFormattable> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::DiffStats(Box::new(property))
+    }
+
+    pub fn wrap_operation_opt(
+        property: impl TemplateProperty<Output = Option<Operation>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::OperationOpt(Box::new(property))
+    }
+}
+
+impl<'repo> OperationTemplateTypes<'repo> for CommitTemplateLanguage<'repo> {
+    fn wrap_operation(
+        property: impl TemplateProperty<Output = Operation> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::Operation(Box::new(property))
+    }
+
+    fn wrap_operation_id(
+        property: impl TemplateProperty<Output = OperationId> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::OperationId(Box::new(property))
+    }
 }
 
 pub enum CommitTemplatePropertyKind<'repo> {
@@ -307,6 +439,12 @@ pub enum CommitTemplatePropertyKind<'repo> {
     CommitOrChangeId(Box<dyn TemplateProperty<Output = CommitOrChangeId> + 'repo>),
     ShortestIdPrefix(Box<dyn TemplateProperty<Output = ShortestIdPrefix> + 'repo>),
     TreeDiff(Box<dyn TemplateProperty<Output = TreeDiff> + 'repo>),
+    TreeDiffEntry(Box<dyn TemplateProperty<Output = TreeDiffEntry> + 'repo>),
+    TreeDiffEntryList(Box<dyn TemplateProperty<Output = Vec<TreeDiffEntry>> + 'repo>),
+    DiffStats(Box<dyn TemplateProperty<Output = DiffStatsFormattable> + 'repo>),
+    Operation(Box<dyn TemplateProperty<Output = Operation> + 'repo>),
+    OperationOpt(Box<dyn TemplateProperty<Output = Option<Operation>> + 'repo>),
+    OperationId(Box<dyn TemplateProperty<Output = OperationId> + 'repo>),
 }
 
 impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
@@ -322,6 +460,12 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::CommitOrChangeId(_) => "CommitOrChangeId",
             CommitTemplatePropertyKind::ShortestIdPrefix(_) => "ShortestIdPrefix",
             CommitTemplatePropertyKind::TreeDiff(_) => "TreeDiff",
+            CommitTemplatePropertyKind::TreeDiffEntry(_) => "TreeDiffEntry",
+            CommitTemplatePropertyKind::TreeDiffEntryList(_) => "List<TreeDiffEntry>",
+            CommitTemplatePropertyKind::DiffStats(_) => "DiffStats",
+            CommitTemplatePropertyKind::Operation(_) => "Operation",
+            CommitTemplatePropertyKind::OperationOpt(_) => "Option<Operation>",
+            CommitTemplatePropertyKind::OperationId(_) => "OperationId",
         }
     }
 
@@ -347,6 +491,16 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             // TODO: boolean cast could be implemented, but explicit
             // diff.empty() method might be better.
             CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::TreeDiffEntry(_) => None,
+            CommitTemplatePropertyKind::TreeDiffEntryList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
+            CommitTemplatePropertyKind::DiffStats(_) => None,
+            CommitTemplatePropertyKind::Operation(_) => None,
+            CommitTemplatePropertyKind::OperationOpt(property) => {
+                Some(Box::new(property.map(|opt| opt.is_some())))
+            }
+            CommitTemplatePropertyKind::OperationId(_) => None,
         }
     }
 
@@ -383,6 +537,63 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
                 Some(property.into_template())
             }
             CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::TreeDiffEntry(_) => None,
+            CommitTemplatePropertyKind::TreeDiffEntryList(_) => None,
+            CommitTemplatePropertyKind::DiffStats(property) => Some(property.into_template()),
+            CommitTemplatePropertyKind::Operation(_) => None,
+            CommitTemplatePropertyKind::OperationOpt(_) => None,
+            CommitTemplatePropertyKind::OperationId(property) => Some(property.into_template()),
+        }
+    }
+
+    fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'repo>> {
+        match self {
+            CommitTemplatePropertyKind::Core(property) => property.try_into_json(),
+            CommitTemplatePropertyKind::Commit(property) => {
+                Some(Box::new(property.and_then(|commit| commit.to_json())))
+            }
+            CommitTemplatePropertyKind::CommitOpt(property) => {
+                Some(Box::new(property.and_then(|commit| commit.to_json())))
+            }
+            CommitTemplatePropertyKind::CommitList(property) => {
+                Some(Box::new(property.and_then(|commits| commits.to_json())))
+            }
+            CommitTemplatePropertyKind::RefName(property) => {
+                Some(Box::new(property.and_then(|ref_name| ref_name.to_json())))
+            }
+            CommitTemplatePropertyKind::RefNameOpt(property) => {
+                Some(Box::new(property.and_then(|ref_name| ref_name.to_json())))
+            }
+            CommitTemplatePropertyKind::RefNameList(property) => {
+                Some(Box::new(property.and_then(|ref_names| ref_names.to_json())))
+            }
+            CommitTemplatePropertyKind::CommitOrChangeId(property) => {
+                Some(Box::new(property.and_then(|id| id.to_json())))
+            }
+            CommitTemplatePropertyKind::ShortestIdPrefix(property) => {
+                Some(Box::new(property.and_then(|id| id.to_json())))
+            }
+            CommitTemplatePropertyKind::TreeDiff(_) => None,
+            CommitTemplatePropertyKind::TreeDiffEntry(property) => {
+                Some(Box::new(property.and_then(|entry| entry.to_json())))
+            }
+            CommitTemplatePropertyKind::TreeDiffEntryList(property) => {
+                Some(Box::new(property.and_then(|entries| entries.to_json())))
+            }
+            CommitTemplatePropertyKind::DiffStats(property) => {
+                Some(Box::new(property.and_then(|stats| stats.to_json())))
+            }
+            CommitTemplatePropertyKind::Operation(property) => {
+                Some(Box::new(property.and_then(|op| op.to_json())))
+            }
+            CommitTemplatePropertyKind::OperationOpt(property) => {
+                Some(Box::new(property.and_then(|op| op.to_json())))
+            }
+            CommitTemplatePropertyKind::OperationId(property) => {
+                Some(Box::new(property.and_then(|id| id.to_json())))
+            }
         }
     }
 }
@@ -399,18 +610,28 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub commit_or_change_id_methods: CommitTemplateBuildMethodFnMap<'repo, CommitOrChangeId>,
     pub shortest_id_prefix_methods: CommitTemplateBuildMethodFnMap<'repo, ShortestIdPrefix>,
     pub tree_diff_methods: CommitTemplateBuildMethodFnMap<'repo, TreeDiff>,
+    pub tree_diff_entry_methods: CommitTemplateBuildMethodFnMap<'repo, TreeDiffEntry>,
+    pub diff_stats_methods: CommitTemplateBuildMethodFnMap<'repo, DiffStatsFormattable>,
+    pub operation_methods: CommitTemplateBuildMethodFnMap<'repo, Operation>,
+    pub operation_id_methods: CommitTemplateBuildMethodFnMap<'repo, OperationId>,
 }
 
 impl<'repo> CommitTemplateBuildFnTable<'repo> {
     /// Creates new symbol table containing the builtin methods.
     fn builtin() -> Self {
+        let mut core = CoreTemplateBuildFnTable::builtin();
+        merge_fn_map(&mut core.functions, builtin_commit_functions());
         CommitTemplateBuildFnTable {
-            core: CoreTemplateBuildFnTable::builtin(),
+            core,
             commit_methods: builtin_commit_methods(),
             ref_name_methods: builtin_ref_name_methods(),
             commit_or_change_id_methods: builtin_commit_or_change_id_methods(),
             shortest_id_prefix_methods: builtin_shortest_id_prefix_methods(),
             tree_diff_methods: builtin_tree_diff_methods(),
+            tree_diff_entry_methods: builtin_tree_diff_entry_methods(),
+            diff_stats_methods: builtin_diff_stats_methods(),
+            operation_methods: operation_templater::builtin_operation_methods(),
+            operation_id_methods: operation_templater::builtin_operation_id_methods(),
         }
     }
 
@@ -422,6 +643,10 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             commit_or_change_id_methods: HashMap::new(),
             shortest_id_prefix_methods: HashMap::new(),
             tree_diff_methods: HashMap::new(),
+            tree_diff_entry_methods: HashMap::new(),
+            diff_stats_methods: HashMap::new(),
+            operation_methods: HashMap::new(),
+            operation_id_methods: HashMap::new(),
         }
     }
 
@@ -433,6 +658,10 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             commit_or_change_id_methods,
             shortest_id_prefix_methods,
             tree_diff_methods,
+            tree_diff_entry_methods,
+            diff_stats_methods,
+            operation_methods,
+            operation_id_methods,
         } = extension;
 
         self.core.merge(core);
@@ -447,6 +676,10 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             shortest_id_prefix_methods,
         );
         merge_fn_map(&mut self.tree_diff_methods, tree_diff_methods);
+        merge_fn_map(&mut self.tree_diff_entry_methods, tree_diff_entry_methods);
+        merge_fn_map(&mut self.diff_stats_methods, diff_stats_methods);
+        merge_fn_map(&mut self.operation_methods, operation_methods);
+        merge_fn_map(&mut self.operation_id_methods, operation_id_methods);
     }
 }
 
@@ -456,7 +689,13 @@ pub struct CommitKeywordCache<'repo> {
     bookmarks_index: OnceCell<Rc<RefNamesIndex>>,
     tags_index: OnceCell<Rc<RefNamesIndex>>,
     git_refs_index: OnceCell<Rc<RefNamesIndex>>,
+    git_notes_index: OnceCell<Rc<HashMap<CommitId, String>>>,
+    mailmap: OnceCell<Rc<Mailmap>>,
     is_immutable_fn: OnceCell<Rc<RevsetContainingFn<'repo>>>,
+    diff_stats_caches: RefCell<Vec<(FilesetExpression, Rc<DiffStatsCache>)>>,
+    commit_operations_index: OnceCell<Rc<RefCell<CommitOperationsIndex>>>,
+    nearest_bookmark_finder: OnceCell<Rc<NearestRefFinder>>,
+    nearest_tag_finder: OnceCell<Rc<NearestRefFinder>>,
 }
 
 impl<'repo> CommitKeywordCache<'repo> {
@@ -475,6 +714,50 @@ impl<'repo> CommitKeywordCache<'repo> {
             .get_or_init(|| Rc::new(build_ref_names_index(repo.view().git_refs())))
     }
 
+    pub fn git_notes_index(
+        &self,
+        repo: &dyn Repo,
+        span: pest::Span<'_>,
+    ) -> TemplateParseResult<&Rc<HashMap<CommitId, String>>> {
+        self.git_notes_index.get_or_try_init(|| {
+            let Some(git_backend) = repo.store().backend_impl().downcast_ref::<GitBackend>() else {
+                return Ok(Rc::default());
+            };
+            let read_notes = || {
+                let git_repo = git_backend.open_git_repo()?;
+                let notes_ref = git::default_notes_ref(&git_repo)?;
+                git::get_all_notes(&git_repo, &notes_ref)
+            };
+            let notes = read_notes().map_err(|err| {
+                TemplateParseError::expression("Failed to read Git notes", span).with_source(err)
+            })?;
+            Ok(Rc::new(notes))
+        })
+    }
+
+    /// Returns the `.mailmap` of the working-copy commit of the current
+    /// workspace.
+    pub fn mailmap(
+        &self,
+        language: &CommitTemplateLanguage<'repo>,
+        span: pest::Span<'_>,
+    ) -> TemplateParseResult<&Rc<Mailmap>> {
+        self.mailmap.get_or_try_init(|| {
+            let repo = language.repo;
+            let Some(wc_commit_id) = repo.view().get_wc_commit_id(&language.workspace_id) else {
+                return Ok(Rc::default());
+            };
+            let read_mailmap = || {
+                let wc_commit = repo.store().get_commit(wc_commit_id)?;
+                Mailmap::read_from_commit(&wc_commit)
+            };
+            let mailmap = read_mailmap().map_err(|err| {
+                TemplateParseError::expression("Failed to read .mailmap", span).with_source(err)
+            })?;
+            Ok(Rc::new(mailmap))
+        })
+    }
+
     pub fn is_immutable_fn(
         &self,
         language: &CommitTemplateLanguage<'repo>,
@@ -489,6 +772,33 @@ impl<'repo> CommitKeywordCache<'repo> {
             Ok(revset.containing_fn().into())
         })
     }
+
+    /// Returns cache of diff stats shared by `diff(files)` calls of the same
+    /// `files` expression.
+    fn diff_stats_cache(&self, files: &FilesetExpression) -> Rc<DiffStatsCache> {
+        let mut caches = self.diff_stats_caches.borrow_mut();
+        if let Some((_, cache)) = caches.iter().find(|(expr, _)| expr == files) {
+            return cache.clone();
+        }
+        let cache = Rc::new(DiffStatsCache::default());
+        caches.push((files.clone(), cache.clone()));
+        cache
+    }
+
+    fn nearest_bookmark_finder(&self, repo: &dyn Repo) -> &Rc<NearestRefFinder> {
+        self.nearest_bookmark_finder
+            .get_or_init(|| Rc::new(NearestRefFinder::new(self.bookmarks_index(repo).clone())))
+    }
+
+    fn nearest_tag_finder(&self, repo: &dyn Repo) -> &Rc<NearestRefFinder> {
+        self.nearest_tag_finder
+            .get_or_init(|| Rc::new(NearestRefFinder::new(self.tags_index(repo).clone())))
+    }
+
+    fn commit_operations_index(&self, head_op: &Operation) -> &Rc<RefCell<CommitOperationsIndex>> {
+        self.commit_operations_index
+            .get_or_init(|| Rc::new(RefCell::new(CommitOperationsIndex::new(head_op))))
+    }
 }
 
 fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Commit> {
@@ -532,8 +842,17 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_commit_list(out_property))
         },
     );
+    map.insert("author", |language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let mailmap = language
+            .keyword_cache
+            .mailmap(language, function.name_span)?
+            .clone();
+        let out_property = self_property.map(move |commit| mailmap.resolve(commit.author()));
+        Ok(L::wrap_signature(out_property))
+    });
     map.insert(
-        "author",
+        "author_raw",
         |_language, _build_ctx, self_property, function| {
             function.expect_no_arguments()?;
             let out_property = self_property.map(|commit| commit.author().clone());
@@ -542,6 +861,18 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
     );
     map.insert(
         "committer",
+        |language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let mailmap = language
+                .keyword_cache
+                .mailmap(language, function.name_span)?
+                .clone();
+            let out_property = self_property.map(move |commit| mailmap.resolve(commit.committer()));
+            Ok(L::wrap_signature(out_property))
+        },
+    );
+    map.insert(
+        "committer_raw",
         |_language, _build_ctx, self_property, function| {
             function.expect_no_arguments()?;
             let out_property = self_property.map(|commit| commit.committer().clone());
@@ -643,6 +974,26 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         let out_property = self_property.map(move |commit| index.get(commit.id()).to_vec());
         Ok(L::wrap_ref_name_list(out_property))
     });
+    map.insert(
+        "closest_bookmark",
+        |language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let repo = language.repo;
+            let finder = language.keyword_cache.nearest_bookmark_finder(repo).clone();
+            let out_property = self_property.and_then(move |commit| finder.describe(repo, &commit));
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "nearest_tag",
+        |language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let repo = language.repo;
+            let finder = language.keyword_cache.nearest_tag_finder(repo).clone();
+            let out_property = self_property.and_then(move |commit| finder.describe(repo, &commit));
+            Ok(L::wrap_string(out_property))
+        },
+    );
     map.insert(
         "git_refs",
         |language, _build_ctx, self_property, function| {
@@ -661,6 +1012,19 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_ref_name_opt(out_property))
         },
     );
+    map.insert(
+        "git_notes",
+        |language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let index = language
+                .keyword_cache
+                .git_notes_index(language.repo, function.name_span)?
+                .clone();
+            let out_property = self_property
+                .map(move |commit| index.get(commit.id()).cloned().unwrap_or_default());
+            Ok(L::wrap_string(out_property))
+        },
+    );
     map.insert(
         "divergent",
         |language, _build_ctx, self_property, function| {
@@ -674,6 +1038,24 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "divergent_versions",
+        |language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let repo = language.repo;
+            let out_property = self_property.and_then(|commit| {
+                let ids = repo.resolve_change_id(commit.change_id());
+                let commits = ids
+                    .iter()
+                    .flatten()
+                    .filter(|id| *id != commit.id())
+                    .map(|id| repo.store().get_commit(id))
+                    .try_collect()?;
+                Ok(commits)
+            });
+            Ok(L::wrap_commit_list(out_property))
+        },
+    );
     map.insert("hidden", |language, _build_ctx, self_property, function| {
         function.expect_no_arguments()?;
         let repo = language.repo;
@@ -734,8 +1116,15 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         };
         let repo = language.repo;
         let matcher: Rc<dyn Matcher> = files.to_matcher().into();
-        let out_property = self_property
-            .and_then(move |commit| Ok(TreeDiff::from_commit(repo, &commit, matcher.clone())?));
+        let stats_cache = language.keyword_cache.diff_stats_cache(&files);
+        let out_property = self_property.and_then(move |commit| {
+            Ok(TreeDiff::from_commit(
+                repo,
+                &commit,
+                matcher.clone(),
+                stats_cache.clone(),
+            )?)
+        });
         Ok(L::wrap_tree_diff(out_property))
     });
     map.insert("root", |language, _build_ctx, self_property, function| {
@@ -744,9 +1133,88 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         let out_property = self_property.map(|commit| commit.id() == repo.store().root_commit_id());
         Ok(L::wrap_boolean(out_property))
     });
+    map.insert(
+        "operation",
+        |language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let repo = language.repo;
+            let index = language
+                .keyword_cache
+                .commit_operations_index(&language.operation)
+                .clone();
+            let out_property =
+                self_property.and_then(move |commit| index.borrow_mut().find(repo, commit.id()));
+            Ok(L::wrap_operation_opt(out_property))
+        },
+    );
     map
 }
 
+/// Lazily-built mapping from commits to the operations that made them visible.
+///
+/// Operations are walked from the head operation only as far as needed to find
+/// the requested commit. Each walked operation costs a revset evaluation, so
+/// looking up an old commit, or a commit which no operation added such as an
+/// imported commit, walks most or all of the operation log. The walk is done
+/// only once: after that, commits which weren't found are known to be absent.
+struct CommitOperationsIndex {
+    // None once all operations have been walked.
+    op_iter: Option<Box<dyn Iterator<Item = OpStoreResult<Operation>>>>,
+    operations: HashMap<CommitId, Operation>,
+}
+
+impl CommitOperationsIndex {
+    fn new(head_op: &Operation) -> Self {
+        CommitOperationsIndex {
+            op_iter: Some(Box::new(op_walk::walk_ancestors(slice::from_ref(head_op)))),
+            operations: HashMap::new(),
+        }
+    }
+
+    /// Looks up the latest operation that made the `commit_id` visible.
+    fn find(
+        &mut self,
+        repo: &dyn Repo,
+        commit_id: &CommitId,
+    ) -> Result<Option<Operation>, TemplatePropertyError> {
+        while !self.operations.contains_key(commit_id) {
+            let Some(op_iter) = &mut self.op_iter else {
+                return Ok(None);
+            };
+            let Some(op) = op_iter.next().transpose()? else {
+                self.op_iter = None;
+                return Ok(None);
+            };
+            let mut old_heads = vec![];
+            for parent_op in op.parents() {
+                old_heads.extend(parent_op?.view()?.heads().iter().cloned());
+            }
+            let new_heads = op.view()?.heads().iter().cloned().collect_vec();
+            let added = RevsetExpression::commits(old_heads)
+                .range(&RevsetExpression::commits(new_heads))
+                .evaluate_programmatic(repo)?;
+            for id in added.iter() {
+                // Walking from the latest operation, so the first one wins.
+                self.operations.entry(id).or_insert_with(|| op.clone());
+            }
+        }
+        Ok(self.operations.get(commit_id).cloned())
+    }
+}
+
+impl JsonValue for Commit {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "commit_id": self.id().hex(),
+            "parents": self.parent_ids().iter().map(|id| id.hex()).collect_vec(),
+            "change_id": CommitOrChangeId::Change(self.change_id().clone()).hex(),
+            "description": self.description(),
+            "author": self.author().to_json()?,
+            "committer": self.committer().to_json()?,
+        }))
+    }
+}
+
 // TODO: return Vec<String>
 fn extract_working_copies(repo: &dyn Repo, commit: &Commit) -> String {
     let wc_commit_ids = repo.view().wc_commit_ids();
@@ -986,6 +1454,18 @@ impl Template for Rc<RefName> {
     }
 }
 
+impl JsonValue for Rc<RefName> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "name": self.name,
+            "remote": self.remote,
+            "present": self.is_present(),
+            "conflict": self.has_conflict(),
+            "target": self.target.added_ids().map(|id| id.hex()).collect_vec(),
+        }))
+    }
+}
+
 impl Template for Vec<Rc<RefName>> {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         templater::format_joined(formatter, self, " ")
@@ -1121,6 +1601,72 @@ impl RefNamesIndex {
     }
 }
 
+/// Finds the nearest ancestor commit pointed to by local refs, and describes
+/// commits relative to it in `git describe` style.
+struct NearestRefFinder {
+    index: Rc<RefNamesIndex>,
+    candidate_ids: Vec<CommitId>,
+    // Commit id to description.
+    cache: RefCell<HashMap<CommitId, String>>,
+}
+
+impl NearestRefFinder {
+    fn new(index: Rc<RefNamesIndex>) -> Self {
+        let candidate_ids = index
+            .index
+            .iter()
+            .filter(|(_, names)| names.iter().any(|name| name.remote.is_none()))
+            .map(|(id, _)| id.clone())
+            .collect();
+        NearestRefFinder {
+            index,
+            candidate_ids,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns `<name>+<distance>`, `<name>` if the `commit` is pointed to by
+    /// the ref, or an empty string if no ancestor is pointed to by any ref.
+    fn describe(&self, repo: &dyn Repo, commit: &Commit) -> Result<String, TemplatePropertyError> {
+        if let Some(description) = self.cache.borrow().get(commit.id()) {
+            return Ok(description.clone());
+        }
+        let self_expression = RevsetExpression::commit(commit.id().clone());
+        // The revset is ordered topologically, the first one is the nearest.
+        let nearest_id = self_expression
+            .ancestors()
+            .intersection(&RevsetExpression::commits(self.candidate_ids.clone()))
+            .evaluate_programmatic(repo)?
+            .iter()
+            .next();
+        let description = if let Some(nearest_id) = nearest_id {
+            let name = self
+                .index
+                .get(&nearest_id)
+                .iter()
+                .find(|name| name.remote.is_none())
+                .map(|name| name.name.as_str())
+                .expect("candidate should be pointed to by local ref");
+            let distance = RevsetExpression::commit(nearest_id)
+                .range(&self_expression)
+                .evaluate_programmatic(repo)?
+                .iter()
+                .count();
+            if distance == 0 {
+                name.to_owned()
+            } else {
+                format!("{name}+{distance}")
+            }
+        } else {
+            String::new()
+        };
+        self.cache
+            .borrow_mut()
+            .insert(commit.id().clone(), description.clone());
+        Ok(description)
+    }
+}
+
 fn build_bookmarks_index(repo: &dyn Repo) -> RefNamesIndex {
     let mut index = RefNamesIndex::default();
     for (bookmark_name, bookmark_target) in repo.view().bookmarks() {
@@ -1211,6 +1757,12 @@ impl Template for CommitOrChangeId {
     }
 }
 
+impl JsonValue for CommitOrChangeId {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.hex().into())
+    }
+}
+
 fn builtin_commit_or_change_id_methods<'repo>(
 ) -> CommitTemplateBuildMethodFnMap<'repo, CommitOrChangeId> {
     type L<'repo> = CommitTemplateLanguage<'repo>;
@@ -1270,6 +1822,12 @@ impl Template for ShortestIdPrefix {
     }
 }
 
+impl JsonValue for ShortestIdPrefix {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(format!("{}{}", self.prefix, self.rest).into())
+    }
+}
+
 impl ShortestIdPrefix {
     fn to_upper(&self) -> Self {
         Self {
@@ -1324,6 +1882,8 @@ pub struct TreeDiff {
     to_tree: MergedTree,
     matcher: Rc<dyn Matcher>,
     copy_records: CopyRecords,
+    commit_id: CommitId,
+    stats_cache: Rc<DiffStatsCache>,
 }
 
 impl TreeDiff {
@@ -1331,11 +1891,11 @@ impl TreeDiff {
         repo: &dyn Repo,
         commit: &Commit,
         matcher: Rc<dyn Matcher>,
+        stats_cache: Rc<DiffStatsCache>,
     ) -> BackendResult<Self> {
         let mut copy_records = CopyRecords::default();
         for parent in commit.parent_ids() {
-            let records =
-                diff_util::get_copy_records(repo.store(), parent, commit.id(), &*matcher)?;
+            let records = diff_util::get_copy_records(repo, parent, commit.id(), &*matcher)?;
             copy_records.add_records(records)?;
         }
         Ok(TreeDiff {
@@ -1343,6 +1903,8 @@ impl TreeDiff {
             to_tree: commit.tree()?,
             matcher,
             copy_records,
+            commit_id: commit.id().clone(),
+            stats_cache,
         })
     }
 
@@ -1351,6 +1913,34 @@ impl TreeDiff {
             .diff_stream_with_copies(&self.to_tree, &*self.matcher, &self.copy_records)
     }
 
+    async fn collect_entries(&self) -> BackendResult<Vec<TreeDiffEntry>> {
+        self.diff_stream()
+            .map(TreeDiffEntry::from_backend_entry_with_copies)
+            .try_collect()
+            .await
+    }
+
+    /// Calculates diff stats, or returns the cached stats if they were
+    /// calculated for the same commit.
+    fn stats(
+        &self,
+        store: &Store,
+        path_converter: &RepoPathUiConverter,
+    ) -> Result<Rc<DiffStats>, DiffRenderError> {
+        if let Some((commit_id, stats)) = &*self.stats_cache.borrow() {
+            if *commit_id == self.commit_id {
+                return Ok(stats.clone());
+            }
+        }
+        let stats = Rc::new(DiffStats::calculate(
+            store,
+            self.diff_stream(),
+            path_converter,
+        )?);
+        *self.stats_cache.borrow_mut() = Some((self.commit_id.clone(), stats.clone()));
+        Ok(stats)
+    }
+
     fn into_formatted<F, E>(self, show: F) -> TreeDiffFormatted<F>
     where
         F: Fn(&mut dyn Formatter, &Store, BoxStream<CopiesTreeDiffEntry>) -> Result<(), E>,
@@ -1429,18 +2019,20 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
         Ok(L::wrap_template(template))
     });
     map.insert("stat", |language, build_ctx, self_property, function| {
-        let [width_node] = function.expect_exact_arguments()?;
-        let width_property =
-            template_builder::expect_usize_expression(language, build_ctx, width_node)?;
+        let ([], [width_node]) = function.expect_arguments()?;
+        let width_property = width_node
+            .map(|node| template_builder::expect_usize_expression(language, build_ctx, node))
+            .transpose()?;
+        let repo = language.repo;
         let path_converter = language.path_converter;
-        let template = (self_property, width_property)
-            .map(move |(diff, width)| {
-                diff.into_formatted(move |formatter, store, tree_diff| {
-                    diff_util::show_diff_stat(formatter, store, tree_diff, path_converter, width)
-                })
+        let out_property = (self_property, width_property).and_then(move |(diff, width)| {
+            let stats = diff.stats(repo.store(), path_converter)?;
+            Ok(DiffStatsFormattable {
+                stats,
+                width: width.unwrap_or(DEFAULT_DIFF_STAT_WIDTH),
             })
-            .into_template();
-        Ok(L::wrap_template(template))
+        });
+        Ok(L::wrap_diff_stats(out_property))
     });
     map.insert(
         "summary",
@@ -1457,8 +2049,186 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
             Ok(L::wrap_template(template))
         },
     );
+    map.insert("files", |_language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        // TODO: cache and reuse diff entries within the current evaluation?
+        let out_property = self_property.and_then(|diff| Ok(diff.collect_entries().block_on()?));
+        Ok(L::wrap_tree_diff_entry_list(out_property))
+    });
     // TODO: add types() and name_only()? or let users write their own template?
     // TODO: add support for external tools
-    // TODO: add files() or map() to support custom summary-like formatting?
+    map
+}
+
+/// [`TreeDiff`] entry.
+#[derive(Clone, Debug)]
+pub struct TreeDiffEntry {
+    pub path: CopiesTreeDiffEntryPath,
+    pub source_value: MergedTreeValue,
+    pub target_value: MergedTreeValue,
+}
+
+impl TreeDiffEntry {
+    fn from_backend_entry_with_copies(entry: CopiesTreeDiffEntry) -> BackendResult<Self> {
+        let (source_value, target_value) = entry.values?;
+        Ok(TreeDiffEntry {
+            path: entry.path,
+            source_value,
+            target_value,
+        })
+    }
+
+    fn status_label(&self) -> &'static str {
+        match self.path.copy_operation() {
+            Some(CopyOperation::Copy) => "copied",
+            Some(CopyOperation::Rename) => "renamed",
+            None => match (
+                self.source_value.is_present(),
+                self.target_value.is_present(),
+            ) {
+                (true, true) => "modified",
+                (false, true) => "added",
+                (true, false) => "removed",
+                (false, false) => panic!("values pair must differ"),
+            },
+        }
+    }
+}
+
+impl JsonValue for TreeDiffEntry {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "path": self.path.target().as_internal_file_string(),
+            "source_path": self.path.source().as_internal_file_string(),
+            "status": self.status_label(),
+        }))
+    }
+}
+
+fn builtin_tree_diff_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, TreeDiffEntry>
+{
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<TreeDiffEntry>::new();
+    map.insert("path", |language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let path_converter = language.path_converter;
+        let out_property =
+            self_property.map(|entry| path_converter.format_file_path(entry.path.target()));
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "source_path",
+        |language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let path_converter = language.path_converter;
+            let out_property =
+                self_property.map(|entry| path_converter.format_file_path(entry.path.source()));
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "status",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|entry| entry.status_label().to_owned());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map
+}
+
+/// Default display width of [`DiffStatsFormattable`].
+const DEFAULT_DIFF_STAT_WIDTH: usize = 80;
+
+/// Stats of the last commit they were calculated for. The template is
+/// evaluated for one commit at a time, so this is enough to share the stats
+/// between the `stat()` calls of an evaluation without keeping the stats of
+/// every commit.
+type DiffStatsCache = RefCell<Option<(CommitId, Rc<DiffStats>)>>;
+
+/// [`DiffStats`] with rendering parameters.
+#[derive(Clone, Debug)]
+pub struct DiffStatsFormattable {
+    stats: Rc<DiffStats>,
+    width: usize,
+}
+
+impl Template for DiffStatsFormattable {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        diff_util::show_diff_stats(formatter.as_mut(), &self.stats, self.width)
+    }
+}
+
+impl JsonValue for DiffStatsFormattable {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "files_changed": self.stats.count_files_changed(),
+            "total_added": self.stats.count_total_added(),
+            "total_removed": self.stats.count_total_removed(),
+        }))
+    }
+}
+
+fn builtin_diff_stats_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, DiffStatsFormattable>
+{
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<DiffStatsFormattable>::new();
+    map.insert(
+        "files_changed",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.and_then(|stats| Ok(stats.stats.count_files_changed().try_into()?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "total_added",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.and_then(|stats| Ok(stats.stats.count_total_added().try_into()?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "total_removed",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.and_then(|stats| Ok(stats.stats.count_total_removed().try_into()?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map
+}
+
+/// Global functions providing the context the template is evaluated in.
+fn builtin_commit_functions<'repo>(
+) -> TemplateBuildFunctionFnMap<'repo, CommitTemplateLanguage<'repo>> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = TemplateBuildFunctionFnMap::<L>::new();
+    map.insert("workspace_name", |language, _build_ctx, function| {
+        function.expect_no_arguments()?;
+        let name = language.workspace_id.as_str().to_owned();
+        Ok(L::wrap_string(Literal(name)))
+    });
+    map.insert("workspace_root", |language, _build_ctx, function| {
+        function.expect_no_arguments()?;
+        let RepoPathUiConverter::Fs { base, .. } = language.path_converter;
+        let root = base.to_string_lossy().into_owned();
+        Ok(L::wrap_string(Literal(root)))
+    });
+    map.insert("current_operation_id", |language, _build_ctx, function| {
+        function.expect_no_arguments()?;
+        let id = language.operation.id().clone();
+        Ok(L::wrap_operation_id(Literal(id)))
+    });
     map
 }
@@ -0,0 +1,390 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Long-lived process that keeps the index of a repo up to date and loaded.
+//!
+//! The index at each new head operation is built as soon as the operation
+//! appears, and its segments are squashed into one file once they pile up, so
+//! commands find the index ready and load it from few files.
+//!
+//! The daemon listens on a Unix socket in the repo directory. Requests and
+//! responses are single lines of text:
+//!
+//! * `ping` is answered with `ok`.
+//! * `index <operation id>` builds the index at the operation unless it
+//!   exists, and is answered with `ok` once it's saved.
+//! * `stop` makes the daemon exit after answering `ok`.
+//!
+//! Errors are answered with `error <message>`. Each connection is served on
+//! its own thread, so `ping` and `stop` are answered while indexes are being
+//! built. Concurrent requests for the same operation build its index once.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::operation::Operation;
+
+/// Name of the socket file in the repo directory.
+const SOCKET_FILE: &str = "daemon.sock";
+
+/// How often the daemon checks for new operations.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a client waits for a response, which includes building an index.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How long the daemon waits for a client to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Number of segment files above which the daemon squashes the index at a head
+/// operation into one file.
+const MAX_INDEX_SEGMENTS: usize = 4;
+
+pub fn socket_path(repo_path: &Path) -> PathBuf {
+    repo_path.join(SOCKET_FILE)
+}
+
+/// Returns true if a daemon is serving the repo at `repo_path`.
+pub fn is_running(repo_path: &Path) -> bool {
+    matches!(send_request(repo_path, "ping").as_deref(), Ok("ok"))
+}
+
+/// Asks the daemon to stop. Returns false if no daemon is running.
+pub fn request_stop(repo_path: &Path) -> io::Result<bool> {
+    match send_request(repo_path, "stop") {
+        Ok(response) => parse_response(&response).map(|()| true),
+        Err(err) if is_not_running_error(&err) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Asks the daemon to build the index at `op`. Fails if no daemon is running.
+pub fn request_index(repo_path: &Path, op: &Operation) -> io::Result<()> {
+    let response = send_request(repo_path, &format!("index {}", op.id().hex()))?;
+    parse_response(&response)
+}
+
+fn parse_response(response: &str) -> io::Result<()> {
+    if response == "ok" {
+        Ok(())
+    } else if let Some(message) = response.strip_prefix("error ") {
+        Err(io::Error::other(message.to_owned()))
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unexpected response from daemon: {response:?}"),
+        ))
+    }
+}
+
+fn is_not_running_error(err: &io::Error) -> bool {
+    // A daemon that's exiting may accept the connection but close it without
+    // answering.
+    matches!(
+        err.kind(),
+        io::ErrorKind::NotFound
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+#[cfg(unix)]
+fn send_request(repo_path: &Path, request: &str) -> io::Result<String> {
+    use std::io::BufRead as _;
+    use std::io::BufReader;
+    use std::io::Write as _;
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(socket_path(repo_path))?;
+    stream.set_read_timeout(Some(RESPONSE_TIMEOUT))?;
+    writeln!(stream, "{request}")?;
+    let mut response = String::new();
+    if BufReader::new(stream).read_line(&mut response)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(response.trim_end().to_owned())
+}
+
+#[cfg(not(unix))]
+fn send_request(_repo_path: &Path, _request: &str) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "The daemon is only supported on Unix",
+    ))
+}
+
+#[cfg(unix)]
+pub use self::server::serve;
+
+#[cfg(unix)]
+mod server {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::io;
+    use std::io::BufRead as _;
+    use std::io::BufReader;
+    use std::io::Write as _;
+    use std::os::unix::net::UnixListener;
+    use std::os::unix::net::UnixStream;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::thread;
+
+    use jj_lib::default_index::DefaultIndexStore;
+    use jj_lib::git_backend::GitBackend;
+    use jj_lib::index::ReadonlyIndex;
+    use jj_lib::op_store::OperationId;
+    use jj_lib::op_walk;
+    use jj_lib::operation::Operation;
+    use jj_lib::repo::RepoLoader;
+
+    use super::is_running;
+    use super::socket_path;
+    use super::MAX_INDEX_SEGMENTS;
+    use super::POLL_INTERVAL;
+    use super::REQUEST_TIMEOUT;
+
+    /// Serves requests for the repo at `repo_path` until asked to stop.
+    ///
+    /// The indexes at the current head operations are kept loaded, so their
+    /// segment files stay in the page cache.
+    pub fn serve(repo_loader: &RepoLoader, repo_path: &Path) -> io::Result<()> {
+        let socket_path = socket_path(repo_path);
+        if is_running(repo_path) {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                "A daemon is already running for this repo",
+            ));
+        }
+        // Left behind by a daemon that didn't exit cleanly.
+        match fs::remove_file(&socket_path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        let listener = UnixListener::bind(&socket_path)?;
+        let daemon = Daemon {
+            repo_loader,
+            socket_path: socket_path.clone(),
+            head_indexes: Mutex::new(vec![]),
+            index_locks: Mutex::new(HashMap::new()),
+            stopping: AtomicBool::new(false),
+        };
+        let result = thread::scope(|scope| {
+            scope.spawn(|| daemon.poll_op_heads());
+            let result = daemon.accept_connections(scope, &listener);
+            daemon.stopping.store(true, Ordering::Relaxed);
+            // Clients connecting from now on would never be answered.
+            fs::remove_file(&socket_path).ok();
+            result
+        });
+        result
+    }
+
+    struct Daemon<'a> {
+        repo_loader: &'a RepoLoader,
+        socket_path: PathBuf,
+        head_indexes: Mutex<Vec<(OperationId, Arc<dyn ReadonlyIndex>)>>,
+        /// Held while the index at the operation is being built, so that
+        /// concurrent requests for it wait instead of building it again.
+        index_locks: Mutex<HashMap<OperationId, Arc<Mutex<()>>>>,
+        stopping: AtomicBool,
+    }
+
+    impl Daemon<'_> {
+        fn accept_connections<'scope>(
+            &'scope self,
+            scope: &'scope thread::Scope<'scope, '_>,
+            listener: &UnixListener,
+        ) -> io::Result<()> {
+            for stream in listener.incoming() {
+                if self.stopping.load(Ordering::Relaxed) {
+                    break;
+                }
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err),
+                };
+                scope.spawn(move || {
+                    // A client going away isn't a reason to stop serving others.
+                    if let Ok(Some(Request::Stop)) = self.handle_connection(stream) {
+                        self.stop();
+                    }
+                });
+            }
+            Ok(())
+        }
+
+        /// Makes the accept loop exit. Connections that are being served are
+        /// finished first.
+        fn stop(&self) {
+            self.stopping.store(true, Ordering::Relaxed);
+            // Wake up the accept loop, which checks the flag for each
+            // connection.
+            UnixStream::connect(&self.socket_path).ok();
+        }
+
+        fn handle_connection(&self, stream: UnixStream) -> io::Result<Option<Request>> {
+            stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            reader.read_line(&mut line)?;
+            let (request, response) = match Request::parse(line.trim_end()) {
+                Ok(request) => {
+                    let response = match &request {
+                        Request::Ping | Request::Stop => Ok(()),
+                        Request::Index(op_id) => self.build_index(op_id),
+                    };
+                    (Some(request), response)
+                }
+                Err(message) => (None, Err(message)),
+            };
+            let mut writer = &stream;
+            match response {
+                Ok(()) => writeln!(writer, "ok")?,
+                // Keep the response on a single line.
+                Err(message) => writeln!(writer, "error {}", message.replace('\n', " "))?,
+            }
+            Ok(request)
+        }
+
+        fn build_index(&self, op_id: &OperationId) -> Result<(), String> {
+            let op_store = self.repo_loader.op_store();
+            let data = op_store
+                .read_operation(op_id)
+                .map_err(|err| err.to_string())?;
+            let op = Operation::new(op_store.clone(), op_id.clone(), data);
+            self.load_index(&op)?;
+            Ok(())
+        }
+
+        fn load_index(&self, op: &Operation) -> Result<Arc<dyn ReadonlyIndex>, String> {
+            // The index must be rebuilt by a command, which reads the commits
+            // with the current replace refs and shallow commits.
+            let store = self.repo_loader.store();
+            if let Some(git_backend) = store.backend_impl().downcast_ref::<GitBackend>() {
+                if git_backend
+                    .indexed_state_changed()
+                    .map_err(|err| err.to_string())?
+                {
+                    return Err("The Git replace refs or shallow commits changed".into());
+                }
+            }
+            let lock = self
+                .index_locks
+                .lock()
+                .unwrap()
+                .entry(op.id().clone())
+                .or_default()
+                .clone();
+            let result = {
+                let _guard = lock.lock().unwrap();
+                self.repo_loader
+                    .index_store()
+                    .get_index_at_op(op, self.repo_loader.store())
+            };
+            // Once the index is saved, later requests load it without waiting.
+            self.index_locks.lock().unwrap().remove(op.id());
+            let index = result.map_err(|err| err.to_string())?;
+            Ok(Arc::from(index))
+        }
+
+        /// Loads the index at a head operation, squashing its segments first
+        /// if there are many of them. Commands load the index at the head
+        /// operation, so they then read a single file.
+        fn load_head_index(&self, op: &Operation) -> Result<Arc<dyn ReadonlyIndex>, String> {
+            let index = self.load_index(op)?;
+            let index_store = self.repo_loader.index_store();
+            let Some(default_index_store) =
+                index_store.as_any().downcast_ref::<DefaultIndexStore>()
+            else {
+                return Ok(index);
+            };
+            let squashed = default_index_store
+                .squash_index_at_operation(op.id(), self.repo_loader.store(), MAX_INDEX_SEGMENTS)
+                .map_err(|err| err.to_string())?;
+            if squashed {
+                self.load_index(op)
+            } else {
+                Ok(index)
+            }
+        }
+
+        /// Keeps the indexes at the current head operations loaded, building
+        /// them if needed.
+        fn poll_op_heads(&self) {
+            while !self.stopping.load(Ordering::Relaxed) {
+                // Errors are transient as far as the daemon is concerned, e.g.
+                // an operation that's being written concurrently.
+                self.update_head_indexes().ok();
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+
+        fn update_head_indexes(&self) -> Result<(), String> {
+            let head_ops = op_walk::get_current_head_ops(
+                self.repo_loader.op_store(),
+                self.repo_loader.op_heads_store().as_ref(),
+            )
+            .map_err(|err| err.to_string())?;
+            let old_indexes = self.head_indexes.lock().unwrap().clone();
+            let mut new_indexes = Vec::with_capacity(head_ops.len());
+            for op in &head_ops {
+                let index = match old_indexes.iter().find(|(id, _)| id == op.id()) {
+                    Some((_, index)) => index.clone(),
+                    None => self.load_head_index(op)?,
+                };
+                new_indexes.push((op.id().clone(), index));
+            }
+            *self.head_indexes.lock().unwrap() = new_indexes;
+            Ok(())
+        }
+    }
+
+    enum Request {
+        Ping,
+        Index(OperationId),
+        Stop,
+    }
+
+    impl Request {
+        fn parse(line: &str) -> Result<Self, String> {
+            match line.split_once(' ') {
+                None if line == "ping" => Ok(Request::Ping),
+                None if line == "stop" => Ok(Request::Stop),
+                Some(("index", hex)) => OperationId::try_from_hex(hex)
+                    .map(Request::Index)
+                    .map_err(|_| format!("Invalid operation id: {hex}")),
+                _ => Err(format!("Unknown request: {line}")),
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn serve(_repo_loader: &jj_lib::repo::RepoLoader, _repo_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "The daemon is only supported on Unix",
+    ))
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io::Write as _;
 
 use bstr::ByteVec as _;
@@ -14,6 +15,8 @@ use crate::cli_util::edit_temp_file;
 use crate::cli_util::short_commit_hash;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::WorkspaceCommandTransaction;
+use crate::command_error::config_error_with_message;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::formatter::PlainTextFormatter;
 use crate::text_util;
@@ -57,10 +60,14 @@ JJ: Lines starting with "JJ: " (like this one) will be removed.
 }
 
 /// Edits the descriptions of the given commits in a single editor session.
+///
+/// Each description is rendered with `template_text`, or with the
+/// `templates.draft_commit_description` template if it's not specified.
 pub fn edit_multiple_descriptions(
     ui: &Ui,
     tx: &WorkspaceCommandTransaction,
     commits: &[(&CommitId, Commit)],
+    template_text: Option<&str>,
     settings: &UserSettings,
 ) -> Result<ParsedBulkEditMessage<CommitId>, CommandError> {
     let mut commits_map = IndexMap::new();
@@ -79,7 +86,12 @@ pub fn edit_multiple_descriptions(
         bulk_message.push_str(&commit_hash);
         bulk_message.push_str(" -------\n");
         commits_map.insert(commit_hash, *commit_id);
-        let template = description_template(ui, tx, "", temp_commit)?;
+        let template = match template_text {
+            Some(template_text) => {
+                render_description_template(ui, tx, "", temp_commit, template_text)?
+            }
+            None => description_template(ui, tx, "", temp_commit)?,
+        };
         bulk_message.push_str(&template);
         bulk_message.push('\n');
     }
@@ -241,7 +253,18 @@ pub fn description_template(
     // Named as "draft" because the output can contain "JJ: " comment lines.
     let template_key = "templates.draft_commit_description";
     let template_text = tx.settings().config().get_string(template_key)?;
-    let template = tx.parse_commit_template(ui, &template_text)?;
+    render_description_template(ui, tx, intro, commit, &template_text)
+}
+
+/// Renders the given template as a commit description to be edited by user.
+pub fn render_description_template(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    intro: &str,
+    commit: &Commit,
+    template_text: &str,
+) -> Result<String, CommandError> {
+    let template = tx.parse_commit_template(ui, template_text)?;
 
     let mut output = Vec::new();
     if !intro.is_empty() {
@@ -254,13 +277,178 @@ pub fn description_template(
     Ok(output.into_string_lossy())
 }
 
+/// Adds the trailers rendered by the `templates.commit_trailers` template to
+/// the description of the commit, and checks the result against the
+/// `commit-message.lint` rules.
+///
+/// Empty descriptions are returned unchanged.
+pub fn finalize_description(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    commit: &Commit,
+    description: String,
+) -> Result<String, CommandError> {
+    if description.is_empty() {
+        return Ok(description);
+    }
+    let template_text = tx
+        .settings()
+        .config()
+        .get_string("templates.commit_trailers")?;
+    let template = tx.parse_commit_template(ui, &template_text)?;
+    let mut output = Vec::new();
+    template
+        .format(commit, &mut PlainTextFormatter::new(&mut output))
+        .expect("write() to vec backed formatter should never fail");
+    let description = add_trailers(&description, &output.into_string_lossy());
+
+    let rules = DescriptionLintRules::from_config(tx.settings().config())?;
+    let problems = rules.check(&description);
+    if problems.is_empty() {
+        return Ok(description);
+    }
+    let subject = description.lines().next().unwrap_or_default();
+    match rules.severity {
+        LintSeverity::Warn => {
+            for problem in &problems {
+                writeln!(
+                    ui.warning_default(),
+                    r#"Description "{subject}": {problem}"#
+                )?;
+            }
+            Ok(description)
+        }
+        LintSeverity::Error => Err(user_error_with_hint(
+            format!(r#"Description "{subject}": {}"#, problems.join("; ")),
+            "Fix the description, or set `commit-message.lint.severity` to \"warn\" to allow \
+             it anyway.",
+        )),
+    }
+}
+
+/// Appends the lines of `trailers` that the description doesn't contain yet.
+///
+/// The trailers are added to the last paragraph if it consists of trailers
+/// already, or as a new paragraph otherwise.
+fn add_trailers(description: &str, trailers: &str) -> String {
+    let existing_lines: HashSet<&str> = description.lines().map(str::trim_end).collect();
+    let new_trailers = trailers
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !existing_lines.contains(line))
+        .unique()
+        .collect_vec();
+    if new_trailers.is_empty() {
+        return description.to_owned();
+    }
+    let mut description = text_util::complete_newline(description.trim_end());
+    let paragraphs = description.split("\n\n").collect_vec();
+    let ends_with_trailers =
+        paragraphs.len() > 1 && paragraphs.last().unwrap().lines().all(is_trailer_line);
+    if !ends_with_trailers {
+        description.push('\n');
+    }
+    for trailer in new_trailers {
+        description.push_str(trailer);
+        description.push('\n');
+    }
+    description
+}
+
+/// Whether the line looks like a `Key: value` trailer.
+fn is_trailer_line(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(key, _)| {
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// How descriptions that break the `commit-message.lint` rules are reported.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all(deserialize = "kebab-case"))]
+pub enum LintSeverity {
+    /// Print a warning, and use the description anyway.
+    #[default]
+    Warn,
+    /// Fail the command.
+    Error,
+}
+
+/// Rules that the descriptions set by `jj describe`, `jj commit`, and
+/// `jj split` are checked against.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DescriptionLintRules {
+    pub severity: LintSeverity,
+    /// Maximum number of characters of the first line, or 0 for no limit.
+    pub subject_max_length: usize,
+    /// Maximum number of characters of the other lines, or 0 for no limit.
+    pub body_max_line_length: usize,
+    /// Whether the first line must be followed by a blank line.
+    pub blank_line_after_subject: bool,
+}
+
+impl DescriptionLintRules {
+    pub fn from_config(config: &config::Config) -> Result<Self, CommandError> {
+        let get_usize = |key: &str| {
+            config
+                .get::<usize>(key)
+                .map_err(|err| config_error_with_message(format!("Invalid `{key}`"), err))
+        };
+        Ok(DescriptionLintRules {
+            severity: config
+                .get::<LintSeverity>("commit-message.lint.severity")
+                .map_err(|err| {
+                    config_error_with_message("Invalid `commit-message.lint.severity`", err)
+                })?,
+            subject_max_length: get_usize("commit-message.lint.subject-max-length")?,
+            body_max_line_length: get_usize("commit-message.lint.body-max-line-length")?,
+            blank_line_after_subject: config
+                .get_bool("commit-message.lint.blank-line-after-subject")?,
+        })
+    }
+
+    /// Returns a message for each rule the description breaks.
+    pub fn check(&self, description: &str) -> Vec<String> {
+        let mut problems = vec![];
+        let mut lines = description.lines();
+        let Some(subject) = lines.next() else {
+            return problems;
+        };
+        let max = self.subject_max_length;
+        if max > 0 && subject.chars().count() > max {
+            problems.push(format!("the first line is longer than {max} characters"));
+        }
+        if self.blank_line_after_subject
+            && description
+                .lines()
+                .nth(1)
+                .is_some_and(|line| !line.is_empty())
+        {
+            problems.push("the first line isn't followed by a blank line".to_owned());
+        }
+        let max = self.body_max_line_length;
+        if max > 0 {
+            for (index, line) in lines.enumerate() {
+                if line.chars().count() > max {
+                    problems.push(format!(
+                        "line {} is longer than {max} characters",
+                        index + 2
+                    ));
+                }
+            }
+        }
+        problems
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use indexmap::indexmap;
     use indoc::indoc;
     use maplit::hashmap;
 
+    use super::add_trailers;
     use super::parse_bulk_edit_message;
+    use super::DescriptionLintRules;
     use crate::description_util::ParseBulkEditMessageError;
 
     #[test]
@@ -413,4 +601,66 @@ mod tests {
         assert!(result.duplicates.is_empty());
         assert!(result.unexpected.is_empty());
     }
+
+    #[test]
+    fn test_add_trailers() {
+        let trailers = "Signed-off-by: Test User <test.user@example.com>\n";
+        // The trailers start a new paragraph
+        assert_eq!(
+            add_trailers("subject\n", trailers),
+            "subject\n\nSigned-off-by: Test User <test.user@example.com>\n"
+        );
+        // A subject that looks like a trailer isn't a trailer paragraph
+        assert_eq!(
+            add_trailers("fix: subject", trailers),
+            "fix: subject\n\nSigned-off-by: Test User <test.user@example.com>\n"
+        );
+        // The trailers are added to an existing trailer paragraph
+        assert_eq!(
+            add_trailers("subject\n\nbody\n\nBug: 123\n", trailers),
+            indoc! {"
+                subject
+
+                body
+
+                Bug: 123
+                Signed-off-by: Test User <test.user@example.com>
+            "}
+        );
+        // Existing trailers aren't duplicated
+        assert_eq!(
+            add_trailers(
+                "subject\n\nSigned-off-by: Test User <test.user@example.com>\n",
+                trailers
+            ),
+            "subject\n\nSigned-off-by: Test User <test.user@example.com>\n"
+        );
+        assert_eq!(add_trailers("subject\n", ""), "subject\n");
+    }
+
+    #[test]
+    fn test_description_lint_rules() {
+        let rules = DescriptionLintRules {
+            subject_max_length: 10,
+            body_max_line_length: 20,
+            blank_line_after_subject: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            rules.check("short\n\nthe body is short\n"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            rules.check("a long subject line\nthe body line is too long\n"),
+            vec![
+                "the first line is longer than 10 characters",
+                "the first line isn't followed by a blank line",
+                "line 2 is longer than 20 characters",
+            ]
+        );
+        assert_eq!(
+            DescriptionLintRules::default().check("a long subject line\nbody\n"),
+            Vec::<String>::new()
+        );
+    }
 }
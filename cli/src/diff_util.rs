@@ -36,6 +36,7 @@ use jj_lib::conflicts::materialize_merge_result;
 use jj_lib::conflicts::materialized_diff_stream;
 use jj_lib::conflicts::MaterializedTreeDiffEntry;
 use jj_lib::conflicts::MaterializedTreeValue;
+use jj_lib::copies;
 use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopyOperation;
 use jj_lib::copies::CopyRecords;
@@ -265,6 +266,7 @@ impl<'a> DiffRenderer<'a> {
 
     /// Generates diff between `from_tree` and `to_tree`.
     #[allow(clippy::too_many_arguments)]
+    #[instrument(skip_all)]
     pub fn show_diff(
         &self,
         ui: &Ui, // TODO: remove Ui dependency if possible
@@ -311,7 +313,8 @@ impl<'a> DiffRenderer<'a> {
                 DiffFormat::Stat => {
                     let tree_diff =
                         from_tree.diff_stream_with_copies(to_tree, matcher, copy_records);
-                    show_diff_stat(formatter, store, tree_diff, path_converter, width)?;
+                    let stats = DiffStats::calculate(store, tree_diff, path_converter)?;
+                    show_diff_stats(formatter, &stats, width)?;
                 }
                 DiffFormat::Types => {
                     let tree_diff =
@@ -397,7 +400,7 @@ impl<'a> DiffRenderer<'a> {
         let to_tree = commit.tree()?;
         let mut copy_records = CopyRecords::default();
         for parent_id in commit.parent_ids() {
-            let records = get_copy_records(self.repo.store(), parent_id, commit.id(), matcher)?;
+            let records = get_copy_records(self.repo, parent_id, commit.id(), matcher)?;
             copy_records.add_records(records)?;
         }
         self.show_diff(
@@ -413,13 +416,13 @@ impl<'a> DiffRenderer<'a> {
 }
 
 pub fn get_copy_records<'a>(
-    store: &'a Store,
+    repo: &'a dyn Repo,
     root: &CommitId,
     head: &CommitId,
     matcher: &'a dyn Matcher,
 ) -> BackendResult<impl Iterator<Item = BackendResult<CopyRecord>> + 'a> {
     // TODO: teach backend about matching path prefixes?
-    let stream = store.get_copy_records(None, root, head)?;
+    let stream = copies::get_copy_records(repo, None, root, head)?;
     // TODO: test record.source as well? should be AND-ed or OR-ed?
     Ok(block_on_stream(stream).filter_ok(|record| matcher.matches(&record.target)))
 }
@@ -1043,10 +1046,13 @@ fn git_diff_part(
             };
         }
         MaterializedTreeValue::GitSubmodule(id) => {
-            // TODO: What should we actually do here?
-            mode = "040000";
+            // Same as "git diff", which shows the gitlink as a one-line file.
+            mode = "160000";
             hash = id.hex();
-            content = FileContent::empty();
+            content = FileContent {
+                is_binary: false,
+                contents: format!("Subproject commit {hash}\n").into_bytes(),
+            };
         }
         MaterializedTreeValue::FileConflict {
             id: _,
@@ -1418,6 +1424,63 @@ pub fn show_diff_summary(
     .block_on()
 }
 
+/// Line change statistics of a tree diff.
+#[derive(Clone, Debug)]
+pub struct DiffStats {
+    entries: Vec<DiffStat>,
+}
+
+impl DiffStats {
+    /// Calculates stats of changed lines per file.
+    pub fn calculate(
+        store: &Store,
+        tree_diff: BoxStream<CopiesTreeDiffEntry>,
+        path_converter: &RepoPathUiConverter,
+    ) -> Result<Self, DiffRenderError> {
+        let mut entries: Vec<DiffStat> = vec![];
+        let mut unresolved_renames = HashSet::new();
+        let mut diff_stream = materialized_diff_stream(store, tree_diff);
+        async {
+            while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
+                let (left, right) = values?;
+                let left_path = path.source();
+                let right_path = path.target();
+                let left_content = diff_content(left_path, left)?;
+                let right_content = diff_content(right_path, right)?;
+
+                let left_ui_path = path_converter.format_file_path(left_path);
+                let path = if left_path == right_path {
+                    left_ui_path
+                } else {
+                    unresolved_renames.insert(left_ui_path);
+                    path_converter.format_copied_path(left_path, right_path)
+                };
+                entries.push(get_diff_stat(path, &left_content, &right_content));
+            }
+            Ok::<(), DiffRenderError>(())
+        }
+        .block_on()?;
+        entries.retain(|stat| !(stat.is_deletion && unresolved_renames.contains(&stat.path)));
+        Ok(DiffStats { entries })
+    }
+
+    /// Number of changed files.
+    pub fn count_files_changed(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Total number of inserted lines.
+    pub fn count_total_added(&self) -> usize {
+        self.entries.iter().map(|stat| stat.added).sum()
+    }
+
+    /// Total number of deleted lines.
+    pub fn count_total_removed(&self) -> usize {
+        self.entries.iter().map(|stat| stat.removed).sum()
+    }
+}
+
+#[derive(Clone, Debug)]
 struct DiffStat {
     path: String,
     added: usize,
@@ -1454,42 +1517,23 @@ fn get_diff_stat(
     }
 }
 
-pub fn show_diff_stat(
+pub fn show_diff_stats(
     formatter: &mut dyn Formatter,
-    store: &Store,
-    tree_diff: BoxStream<CopiesTreeDiffEntry>,
-    path_converter: &RepoPathUiConverter,
+    stats: &DiffStats,
     display_width: usize,
-) -> Result<(), DiffRenderError> {
-    let mut stats: Vec<DiffStat> = vec![];
-    let mut unresolved_renames = HashSet::new();
-    let mut max_path_width = 0;
-    let mut max_diffs = 0;
-
-    let mut diff_stream = materialized_diff_stream(store, tree_diff);
-    async {
-        while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
-            let (left, right) = values?;
-            let left_path = path.source();
-            let right_path = path.target();
-            let left_content = diff_content(left_path, left)?;
-            let right_content = diff_content(right_path, right)?;
-
-            let left_ui_path = path_converter.format_file_path(left_path);
-            let path = if left_path == right_path {
-                left_ui_path
-            } else {
-                unresolved_renames.insert(left_ui_path);
-                path_converter.format_copied_path(left_path, right_path)
-            };
-            max_path_width = max(max_path_width, path.width());
-            let stat = get_diff_stat(path, &left_content, &right_content);
-            max_diffs = max(max_diffs, stat.added + stat.removed);
-            stats.push(stat);
-        }
-        Ok::<(), DiffRenderError>(())
-    }
-    .block_on()?;
+) -> io::Result<()> {
+    let max_path_width = stats
+        .entries
+        .iter()
+        .map(|stat| stat.path.width())
+        .max()
+        .unwrap_or(0);
+    let max_diffs = stats
+        .entries
+        .iter()
+        .map(|stat| stat.added + stat.removed)
+        .max()
+        .unwrap_or(0);
 
     let number_padding = max_diffs.to_string().len();
     // 4 characters padding for the graph
@@ -1504,17 +1548,7 @@ pub fn show_diff_stat(
         max_bar_length as f64 / max_diffs as f64
     };
 
-    let mut total_added = 0;
-    let mut total_removed = 0;
-    let mut total_files = 0;
-    for stat in &stats {
-        if stat.is_deletion && unresolved_renames.contains(&stat.path) {
-            continue;
-        }
-
-        total_added += stat.added;
-        total_removed += stat.removed;
-        total_files += 1;
+    for stat in &stats.entries {
         let bar_added = (stat.added as f64 * factor).ceil() as usize;
         let bar_removed = (stat.removed as f64 * factor).ceil() as usize;
         // replace start of path with ellipsis if the path is too long
@@ -1530,6 +1564,10 @@ pub fn show_diff_stat(
         write!(formatter.labeled("added"), "{}", "+".repeat(bar_added))?;
         writeln!(formatter.labeled("removed"), "{}", "-".repeat(bar_removed))?;
     }
+
+    let total_files = stats.count_files_changed();
+    let total_added = stats.count_total_added();
+    let total_removed = stats.count_total_removed();
     writeln!(
         formatter.labeled("stat-summary"),
         "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
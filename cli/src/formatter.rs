@@ -21,6 +21,7 @@ use std::io::Write;
 use std::mem;
 use std::ops::Range;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use crossterm::queue;
 use crossterm::style::Attribute;
@@ -39,6 +40,12 @@ pub trait Formatter: Write {
     fn push_label(&mut self, label: &str) -> io::Result<()>;
 
     fn pop_label(&mut self) -> io::Result<()>;
+
+    /// Starts hyperlink to the `url`. Formatters not supporting hyperlinks
+    /// will output only the link text.
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()>;
+
+    fn pop_hyperlink(&mut self) -> io::Result<()>;
 }
 
 impl dyn Formatter + '_ {
@@ -134,6 +141,10 @@ where
 
 type Rules = Vec<(Vec<String>, Style)>;
 
+/// Styles of the label stacks seen so far, shared by the formatters created by
+/// a factory so that each formatter doesn't have to match the rules again.
+type StyleCache = Arc<Mutex<HashMap<Vec<String>, Style>>>;
+
 /// Creates `Formatter` instances with preconfigured parameters.
 #[derive(Clone, Debug)]
 pub struct FormatterFactory {
@@ -144,7 +155,11 @@ pub struct FormatterFactory {
 enum FormatterFactoryKind {
     PlainText,
     Sanitized,
-    Color { rules: Arc<Rules>, debug: bool },
+    Color {
+        rules: Arc<Rules>,
+        cached_styles: StyleCache,
+        debug: bool,
+    },
 }
 
 impl FormatterFactory {
@@ -160,7 +175,12 @@ impl FormatterFactory {
 
     pub fn color(config: &config::Config, debug: bool) -> Result<Self, config::ConfigError> {
         let rules = Arc::new(rules_from_config(config)?);
-        let kind = FormatterFactoryKind::Color { rules, debug };
+        let cached_styles = StyleCache::default();
+        let kind = FormatterFactoryKind::Color {
+            rules,
+            cached_styles,
+            debug,
+        };
         Ok(FormatterFactory { kind })
     }
 
@@ -171,9 +191,16 @@ impl FormatterFactory {
         match &self.kind {
             FormatterFactoryKind::PlainText => Box::new(PlainTextFormatter::new(output)),
             FormatterFactoryKind::Sanitized => Box::new(SanitizingFormatter::new(output)),
-            FormatterFactoryKind::Color { rules, debug } => {
-                Box::new(ColorFormatter::new(output, rules.clone(), *debug))
-            }
+            FormatterFactoryKind::Color {
+                rules,
+                cached_styles,
+                debug,
+            } => Box::new(ColorFormatter::with_cached_styles(
+                output,
+                rules.clone(),
+                cached_styles.clone(),
+                *debug,
+            )),
         }
     }
 
@@ -214,6 +241,14 @@ impl<W: Write> Formatter for PlainTextFormatter<W> {
     fn pop_label(&mut self) -> io::Result<()> {
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, _url: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 pub struct SanitizingFormatter<W> {
@@ -249,6 +284,14 @@ impl<W: Write> Formatter for SanitizingFormatter<W> {
     fn pop_label(&mut self) -> io::Result<()> {
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, _url: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -275,23 +318,37 @@ pub struct ColorFormatter<W: Write> {
     /// The stack of currently applied labels. These determine the desired
     /// style.
     labels: Vec<String>,
-    cached_styles: HashMap<Vec<String>, Style>,
+    cached_styles: StyleCache,
     /// The style we last wrote to the output.
     current_style: Style,
     /// The debug string (space-separated labels) we last wrote to the output.
     /// Initialize to None to turn debug strings off.
     current_debug: Option<String>,
+    /// The stack of currently applied hyperlink URLs.
+    hyperlinks: Vec<String>,
 }
 
 impl<W: Write> ColorFormatter<W> {
     pub fn new(output: W, rules: Arc<Rules>, debug: bool) -> ColorFormatter<W> {
+        Self::with_cached_styles(output, rules, StyleCache::default(), debug)
+    }
+
+    /// Creates a formatter which shares `cached_styles` with other formatters
+    /// using the same `rules`.
+    fn with_cached_styles(
+        output: W,
+        rules: Arc<Rules>,
+        cached_styles: StyleCache,
+        debug: bool,
+    ) -> ColorFormatter<W> {
         ColorFormatter {
             output,
             rules,
             labels: vec![],
-            cached_styles: HashMap::new(),
+            cached_styles,
             current_style: Style::default(),
             current_debug: debug.then(String::new),
+            hyperlinks: vec![],
         }
     }
 
@@ -305,7 +362,8 @@ impl<W: Write> ColorFormatter<W> {
     }
 
     fn requested_style(&mut self) -> Style {
-        if let Some(cached) = self.cached_styles.get(&self.labels) {
+        let mut cached_styles = self.cached_styles.lock().unwrap();
+        if let Some(cached) = cached_styles.get(&self.labels) {
             cached.clone()
         } else {
             // We use the reverse list of matched indices as a measure of how well the rule
@@ -337,8 +395,7 @@ impl<W: Write> ColorFormatter<W> {
             for (matched_style, _) in matched_styles {
                 style.merge(matched_style);
             }
-            self.cached_styles
-                .insert(self.labels.clone(), style.clone());
+            cached_styles.insert(self.labels.clone(), style.clone());
             style
         }
     }
@@ -557,6 +614,24 @@ impl<W: Write> Formatter for ColorFormatter<W> {
         }
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        write_hyperlink_start(&mut self.output, url)?;
+        self.hyperlinks.push(url.to_owned());
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.hyperlinks.pop();
+        // Restore the outer hyperlink if any.
+        let url = self.hyperlinks.last().map_or("", |url| url.as_str());
+        write_hyperlink_start(&mut self.output, url)
+    }
+}
+
+/// Writes OSC 8 escape sequence. Empty `url` terminates the hyperlink.
+fn write_hyperlink_start(output: &mut impl Write, url: &str) -> io::Result<()> {
+    write!(output, "\x1b]8;;{url}\x1b\\")
 }
 
 impl<W: Write> Drop for ColorFormatter<W> {
@@ -565,6 +640,10 @@ impl<W: Write> Drop for ColorFormatter<W> {
         // because of an error), let's still try to reset any currently active style.
         self.labels.clear();
         self.write_new_style().ok();
+        if !self.hyperlinks.is_empty() {
+            self.hyperlinks.clear();
+            write_hyperlink_start(&mut self.output, "").ok();
+        }
     }
 }
 
@@ -585,6 +664,8 @@ pub struct FormatRecorder {
 enum LabelOp {
     PushLabel(String),
     PopLabel,
+    PushHyperlink(String),
+    PopHyperlink,
 }
 
 impl FormatRecorder {
@@ -624,6 +705,8 @@ impl FormatRecorder {
             match op {
                 LabelOp::PushLabel(label) => formatter.push_label(label)?,
                 LabelOp::PopLabel => formatter.pop_label()?,
+                LabelOp::PushHyperlink(url) => formatter.push_hyperlink(url)?,
+                LabelOp::PopHyperlink => formatter.pop_hyperlink()?,
             }
         }
         flush_data(formatter, self.data.len())
@@ -655,6 +738,16 @@ impl Formatter for FormatRecorder {
         self.push_label_op(LabelOp::PopLabel);
         Ok(())
     }
+
+    fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        self.push_label_op(LabelOp::PushHyperlink(url.to_owned()));
+        Ok(())
+    }
+
+    fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.push_label_op(LabelOp::PopHyperlink);
+        Ok(())
+    }
 }
 
 fn write_sanitized(output: &mut impl Write, buf: &[u8]) -> Result<(), Error> {
@@ -1096,6 +1189,41 @@ mod tests {
         insta::assert_snapshot!(String::from_utf8(output).unwrap(), @" hello ");
     }
 
+    #[test]
+    fn test_formatter_factory_shares_cached_styles() {
+        let config = config_from_string(
+            r#"
+        colors."a" = "red"
+        colors."a b" = "green"
+        "#,
+        );
+        let factory = FormatterFactory::color(&config, false).unwrap();
+        let FormatterFactoryKind::Color { cached_styles, .. } = &factory.kind else {
+            panic!("unexpected formatter kind");
+        };
+        let render = || {
+            let mut output: Vec<u8> = vec![];
+            let mut formatter = factory.new_formatter(&mut output);
+            formatter.push_label("a").unwrap();
+            write!(formatter, " a ").unwrap();
+            formatter.push_label("b").unwrap();
+            write!(formatter, " b ").unwrap();
+            formatter.pop_label().unwrap();
+            formatter.pop_label().unwrap();
+            drop(formatter);
+            String::from_utf8(output).unwrap()
+        };
+
+        let first = render();
+        insta::assert_snapshot!(first, @"[38;5;1m a [38;5;2m b [39m");
+        // The styles of the label stacks are cached by the first formatter
+        let num_cached = cached_styles.lock().unwrap().len();
+        assert!(num_cached >= 2, "{num_cached}");
+        // and reused by the next one, which renders the same output
+        assert_eq!(render(), first);
+        assert_eq!(cached_styles.lock().unwrap().len(), num_cached);
+    }
+
     #[test]
     fn test_color_formatter_innermost_wins() {
         // When two labels match, the innermost one wins.
@@ -1163,6 +1291,33 @@ mod tests {
         insta::assert_snapshot!(String::from_utf8(output).unwrap(), @"[38;5;2m<<outer inner:: inside >>[39m");
     }
 
+    #[test]
+    fn test_color_formatter_hyperlink() {
+        // Hyperlink is written regardless of the active style, and is
+        // terminated at drop.
+        let config = config_from_string(r#" colors.inner = "green" "#);
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = ColorFormatter::for_config(&mut output, &config, false).unwrap();
+        formatter.push_hyperlink("https://example.com/").unwrap();
+        formatter.push_label("inner").unwrap();
+        write!(formatter, " inside ").unwrap();
+        formatter.pop_label().unwrap();
+        formatter.pop_hyperlink().unwrap();
+        formatter.push_hyperlink("dangling").unwrap();
+        drop(formatter);
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r#"]8;;https://example.com/\[38;5;2m inside [39m]8;;\]8;;dangling\]8;;\"#);
+    }
+
+    #[test]
+    fn test_plaintext_formatter_hyperlink() {
+        let mut output: Vec<u8> = vec![];
+        let mut formatter = PlainTextFormatter::new(&mut output);
+        formatter.push_hyperlink("https://example.com/").unwrap();
+        write!(formatter, "text").unwrap();
+        formatter.pop_hyperlink().unwrap();
+        insta::assert_snapshot!(String::from_utf8(output).unwrap(), @"text");
+    }
+
     #[test]
     fn test_heading_labeled_writer() {
         let config = config_from_string(
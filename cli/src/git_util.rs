@@ -15,13 +15,17 @@
 //! Git utilities shared by various commands.
 
 use std::error;
+use std::fs;
+use std::io;
 use std::io::Read;
 use std::io::Write;
 use std::iter;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Stdio;
+use std::time::Duration;
 use std::time::Instant;
+use std::time::SystemTime;
 
 use itertools::Itertools;
 use jj_lib::git;
@@ -34,10 +38,12 @@ use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::settings::ConfigResultExt as _;
 use jj_lib::store::Store;
 use jj_lib::workspace::Workspace;
 use unicode_width::UnicodeWidthStr;
 
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::formatter::Formatter;
@@ -69,6 +75,29 @@ pub fn is_colocated_git_workspace(workspace: &Workspace, repo: &ReadonlyRepo) ->
     git_workdir.canonicalize().ok().as_deref() == dot_git_path.parent()
 }
 
+/// Returns true if `path` is the root of a linked worktree of the Git repo.
+pub fn is_linked_git_worktree(git_backend: &GitBackend, path: &Path) -> bool {
+    // Linked worktrees have ".git" file instead of directory.
+    if !path.join(".git").is_file() {
+        return false;
+    }
+    let Ok(worktree_repo) = git2::Repository::open(path) else {
+        return false;
+    };
+    worktree_repo.is_worktree()
+        && git::git_common_dir(&worktree_repo).canonicalize().ok()
+            == git_backend.git_repo_path().canonicalize().ok()
+}
+
+/// Returns true if the workspace is a linked worktree of the Git repo backing
+/// `repo`.
+pub fn is_git_worktree_workspace(workspace: &Workspace, repo: &ReadonlyRepo) -> bool {
+    let Some(git_backend) = repo.store().backend_impl().downcast_ref::<GitBackend>() else {
+        return false;
+    };
+    is_linked_git_worktree(git_backend, workspace.workspace_root())
+}
+
 fn terminal_get_username(ui: &Ui, url: &str) -> Option<String> {
     ui.prompt(&format!("Username for {url}")).ok()
 }
@@ -130,23 +159,34 @@ fn pinentry_get_pw(url: &str) -> Option<String> {
 
 #[tracing::instrument]
 fn get_ssh_keys(_username: &str) -> Vec<PathBuf> {
-    let mut paths = vec![];
-    if let Some(home_dir) = dirs::home_dir() {
-        let ssh_dir = Path::new(&home_dir).join(".ssh");
-        for filename in ["id_ed25519_sk", "id_ed25519", "id_rsa"] {
-            let key_path = ssh_dir.join(filename);
-            if key_path.is_file() {
-                tracing::info!(path = ?key_path, "found ssh key");
-                paths.push(key_path);
-            }
-        }
-    }
+    let paths = dirs::home_dir()
+        .map(|home_dir| find_ssh_keys(&home_dir.join(".ssh")))
+        .unwrap_or_default();
     if paths.is_empty() {
         tracing::info!("no ssh key found");
     }
     paths
 }
 
+fn find_ssh_keys(ssh_dir: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![];
+    // Same default identity files as OpenSSH, except for the obsolete DSA
+    for filename in [
+        "id_ecdsa_sk",
+        "id_ed25519_sk",
+        "id_ecdsa",
+        "id_ed25519",
+        "id_rsa",
+    ] {
+        let key_path = ssh_dir.join(filename);
+        if key_path.is_file() {
+            tracing::info!(path = ?key_path, "found ssh key");
+            paths.push(key_path);
+        }
+    }
+    paths
+}
+
 // Based on Git's implementation: https://github.com/git/git/blob/43072b4ca132437f21975ac6acc6b72dc22fd398/sideband.c#L178
 pub struct GitSidebandProgressMessageWriter {
     display_prefix: &'static [u8],
@@ -260,6 +300,105 @@ pub fn with_remote_git_callbacks<T>(
     f(callbacks)
 }
 
+/// Spawns a `jj` process with the given arguments that keeps running after
+/// this process exits. Its output is discarded.
+pub fn spawn_detached_jj(cwd: &Path, args: &[String]) -> io::Result<()> {
+    let mut cmd = std::process::Command::new(std::env::current_exe()?);
+    cmd.args(args)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt as _;
+        // Don't get killed along with the terminal's process group.
+        cmd.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt as _;
+        const DETACHED_PROCESS: u32 = 0x00000008;
+        cmd.creation_flags(DETACHED_PROCESS);
+    }
+    cmd.spawn()?;
+    Ok(())
+}
+
+/// Name of the file in the repo directory whose modification time is the time
+/// of the last fetch for the purpose of `git.auto-fetch-interval`.
+const LAST_FETCH_MARKER: &str = "git_last_fetch";
+
+/// Records that the remotes were just fetched from, which postpones the next
+/// automatic background fetch.
+pub fn record_last_fetch_time(repo_path: &Path) -> io::Result<()> {
+    fs::write(repo_path.join(LAST_FETCH_MARKER), b"")
+}
+
+/// Interval between automatic background fetches if `git.auto-fetch-interval`
+/// isn't set.
+const DEFAULT_AUTO_FETCH_INTERVAL: u64 = 15 * 60;
+
+/// Starts `jj git fetch` in the background if `git.auto-fetch` is enabled and
+/// `git.auto-fetch-interval` seconds have passed since the last fetch.
+pub fn maybe_start_auto_fetch(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+) -> Result<(), CommandError> {
+    let config = workspace_command.settings().config();
+    if !config
+        .get_bool("git.auto-fetch")
+        .optional()?
+        .unwrap_or(false)
+    {
+        return Ok(());
+    }
+    let interval = config
+        .get::<u64>("git.auto-fetch-interval")
+        .optional()?
+        .unwrap_or(DEFAULT_AUTO_FETCH_INTERVAL);
+    let store = workspace_command.repo().store();
+    if interval == 0 || store.backend_impl().downcast_ref::<GitBackend>().is_none() {
+        return Ok(());
+    }
+    let repo_path = workspace_command.repo_path();
+    let last_fetch = fs::metadata(repo_path.join(LAST_FETCH_MARKER))
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    if !is_auto_fetch_due(last_fetch, SystemTime::now(), Duration::from_secs(interval)) {
+        return Ok(());
+    }
+    // Record the time before the fetch finishes so concurrent commands don't
+    // start more fetches.
+    record_last_fetch_time(repo_path)?;
+    let workspace_root = workspace_command.workspace_root();
+    let args = [
+        "-R".to_owned(),
+        workspace_root.to_string_lossy().into_owned(),
+        "git".to_owned(),
+        "fetch".to_owned(),
+        "--quiet".to_owned(),
+        "--ignore-working-copy".to_owned(),
+    ];
+    if let Err(err) = spawn_detached_jj(workspace_root, &args) {
+        writeln!(
+            ui.warning_default(),
+            "Failed to start fetching in the background: {err}"
+        )?;
+    }
+    Ok(())
+}
+
+fn is_auto_fetch_due(last_fetch: Option<SystemTime>, now: SystemTime, interval: Duration) -> bool {
+    match last_fetch {
+        None => true,
+        // The clock may have gone backwards, in which case we wait.
+        Some(last_fetch) => now
+            .duration_since(last_fetch)
+            .is_ok_and(|elapsed| elapsed >= interval),
+    }
+}
+
 pub fn print_git_import_stats(
     ui: &Ui,
     repo: &dyn Repo,
@@ -433,3 +572,50 @@ export or their "parent" bookmarks."#,
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_ssh_keys() {
+        let temp_dir = testutils::new_temp_dir();
+        let ssh_dir = temp_dir.path();
+        assert!(find_ssh_keys(ssh_dir).is_empty());
+
+        for filename in [
+            "id_rsa",
+            "id_ecdsa",
+            "id_ed25519_sk",
+            "id_dsa",
+            "id_rsa.pub",
+        ] {
+            fs::write(ssh_dir.join(filename), "").unwrap();
+        }
+        // A directory isn't a key
+        fs::create_dir(ssh_dir.join("id_ed25519")).unwrap();
+        assert_eq!(
+            find_ssh_keys(ssh_dir),
+            vec![
+                ssh_dir.join("id_ed25519_sk"),
+                ssh_dir.join("id_ecdsa"),
+                ssh_dir.join("id_rsa"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_auto_fetch_due() {
+        let interval = Duration::from_secs(60);
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+        assert!(is_auto_fetch_due(None, now, interval));
+        assert!(is_auto_fetch_due(Some(now - interval), now, interval));
+        assert!(!is_auto_fetch_due(
+            Some(now - Duration::from_secs(59)),
+            now,
+            interval
+        ));
+        // Last fetch in the future
+        assert!(!is_auto_fetch_due(Some(now + interval), now, interval));
+    }
+}
@@ -14,7 +14,6 @@
 
 use std::hash::Hash;
 use std::io;
-use std::io::Write;
 
 use itertools::Itertools;
 use jj_lib::settings::UserSettings;
@@ -22,6 +21,8 @@ use renderdag::Ancestor;
 use renderdag::GraphRowRenderer;
 use renderdag::Renderer;
 
+use crate::formatter::Formatter;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 // An edge to another node in the graph
 pub enum Edge<T> {
@@ -44,9 +45,15 @@ pub trait GraphLog<K: Clone + Eq + Hash> {
 
 pub struct SaplingGraphLog<'writer, R> {
     renderer: R,
-    writer: &'writer mut dyn Write,
+    formatter: &'writer mut dyn Formatter,
 }
 
+// Placeholders passed to the renderer in place of the node symbol and the text
+// lines, which may contain escape sequences. The rendered row is then split at
+// them so that only the edges are written with the "edge" label.
+const NODE_PLACEHOLDER: &str = "\0";
+const TEXT_PLACEHOLDER: &str = "\x01";
+
 impl<K: Clone> From<&Edge<K>> for Ancestor<K> {
     fn from(e: &Edge<K>) -> Self {
         match e {
@@ -69,14 +76,37 @@ where
         node_symbol: &str,
         text: &str,
     ) -> io::Result<()> {
+        let mut text_lines = text.lines();
         let row = self.renderer.next_row(
             id.clone(),
             edges.iter().map_into().collect(),
-            node_symbol.into(),
-            text.into(),
+            NODE_PLACEHOLDER.into(),
+            text.lines().map(|_| TEXT_PLACEHOLDER).join("\n"),
         );
 
-        write!(self.writer, "{row}")
+        for line in row.lines() {
+            let (graph, text_line) = match line.split_once(TEXT_PLACEHOLDER) {
+                Some((graph, _)) => {
+                    let text_line = text_lines.next().unwrap_or_default().trim_end();
+                    if text_line.is_empty() {
+                        (graph.trim_end(), text_line)
+                    } else {
+                        (graph, text_line)
+                    }
+                }
+                None => (line, ""),
+            };
+            match graph.split_once(NODE_PLACEHOLDER) {
+                Some((before, after)) => {
+                    write_edges(self.formatter, before)?;
+                    write!(self.formatter.raw(), "{node_symbol}")?;
+                    write_edges(self.formatter, after)?;
+                }
+                None => write_edges(self.formatter, graph)?,
+            }
+            writeln!(self.formatter.raw(), "{text_line}")?;
+        }
+        Ok(())
     }
 
     fn width(&self, id: &K, edges: &[Edge<K>]) -> usize {
@@ -86,10 +116,17 @@ where
     }
 }
 
+fn write_edges(formatter: &mut dyn Formatter, edges: &str) -> io::Result<()> {
+    if edges.is_empty() {
+        return Ok(());
+    }
+    write!(formatter.labeled("edge"), "{edges}")
+}
+
 impl<'writer, R> SaplingGraphLog<'writer, R> {
     pub fn create<K>(
         renderer: R,
-        formatter: &'writer mut dyn Write,
+        formatter: &'writer mut dyn Formatter,
     ) -> Box<dyn GraphLog<K> + 'writer>
     where
         K: Clone + Eq + Hash + 'writer,
@@ -97,7 +134,7 @@ impl<'writer, R> SaplingGraphLog<'writer, R> {
     {
         Box::new(SaplingGraphLog {
             renderer,
-            writer: formatter,
+            formatter,
         })
     }
 }
@@ -126,7 +163,7 @@ impl GraphStyle {
 
 pub fn get_graphlog<'a, K: Clone + Eq + Hash + 'a>(
     style: GraphStyle,
-    formatter: &'a mut dyn Write,
+    formatter: &'a mut dyn Formatter,
 ) -> Box<dyn GraphLog<K> + 'a> {
     let builder = GraphRowRenderer::new().output().with_min_row_height(0);
     match style {
@@ -20,6 +20,7 @@ pub mod command_error;
 pub mod commands;
 pub mod commit_templater;
 pub mod config;
+mod daemon;
 pub mod description_util;
 pub mod diff_util;
 pub mod formatter;
@@ -28,7 +29,9 @@ pub mod git_util;
 pub mod graphlog;
 pub mod merge_tools;
 pub mod movement_util;
+pub mod op_hooks;
 pub mod operation_templater;
+mod profile;
 mod progress;
 pub mod revset_util;
 pub mod template_builder;
@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::iter::zip;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -12,8 +13,10 @@ use jj_lib::backend::BackendResult;
 use jj_lib::backend::FileId;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeValue;
+use jj_lib::conflicts;
 use jj_lib::conflicts::materialize_merge_result;
 use jj_lib::conflicts::materialize_tree_value;
+use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MaterializedTreeValue;
 use jj_lib::diff::Diff;
 use jj_lib::diff::DiffHunk;
@@ -21,6 +24,7 @@ use jj_lib::files;
 use jj_lib::files::MergeResult;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Merge;
+use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::merged_tree::TreeDiffEntry;
@@ -624,12 +628,69 @@ fn make_merge_sections(
     Ok(sections)
 }
 
+/// Resolves each conflicted hunk with the lines selected in its section, and
+/// returns the new contents of the sides. The selected lines are the checked
+/// added lines and the unchecked removed lines, like in a diff. Hunks whose
+/// sections have no checked lines are left unresolved.
+fn apply_merge_sections(
+    hunks: &[Merge<BString>],
+    sections: &[scm_record::Section],
+) -> Merge<BString> {
+    let new_hunks = zip(hunks, sections)
+        .map(|(hunk, section)| match section {
+            scm_record::Section::Changed { lines }
+                if !hunk.is_resolved() && lines.iter().any(|line| line.is_checked) =>
+            {
+                let mut content = BString::default();
+                for line in lines {
+                    let is_selected = match line.change_type {
+                        scm_record::ChangeType::Added => line.is_checked,
+                        scm_record::ChangeType::Removed => !line.is_checked,
+                    };
+                    if is_selected {
+                        content.extend_from_slice(line.line.as_bytes());
+                    }
+                }
+                Merge::resolved(content)
+            }
+            _ => hunk.clone(),
+        })
+        .collect_vec();
+    let Some(conflict) = new_hunks.iter().find(|hunk| !hunk.is_resolved()) else {
+        let content = new_hunks
+            .iter()
+            .map(|hunk| hunk.as_resolved().unwrap().as_slice())
+            .collect_vec()
+            .concat();
+        return Merge::resolved(BString::from(content));
+    };
+    let mut contents = conflict.map(|_| BString::default());
+    for hunk in &new_hunks {
+        if let Some(slice) = hunk.as_resolved() {
+            for content in contents.iter_mut() {
+                content.extend_from_slice(slice);
+            }
+        } else {
+            for (content, slice) in zip(contents.iter_mut(), hunk.iter()) {
+                content.extend_from_slice(slice);
+            }
+        }
+    }
+    contents
+}
+
 pub fn edit_merge_builtin(
     tree: &MergedTree,
     path: &RepoPath,
+    file_merge: &Merge<Option<FileId>>,
+    conflict: &MergedTreeValue,
     content: Merge<BString>,
 ) -> Result<MergedTreeId, BuiltinToolError> {
     let merge_result = files::merge(&content);
+    let hunks = match &merge_result {
+        MergeResult::Resolved(_) => None,
+        MergeResult::Conflict(hunks) => Some(hunks.clone()),
+    };
     let sections = make_merge_sections(merge_result)?;
     let mut input = scm_record::helpers::CrosstermInput;
     let recorder = scm_record::Recorder::new(
@@ -648,14 +709,41 @@ pub fn edit_merge_builtin(
     let state = recorder.run()?;
 
     let file = state.files.into_iter().exactly_one().unwrap();
-    apply_diff_builtin(tree.store(), tree, tree, vec![path.to_owned()], &[file])
-        .map_err(BuiltinToolError::BackendError)
+    let Some(hunks) = hunks else {
+        return apply_diff_builtin(tree.store(), tree, tree, vec![path.to_owned()], &[file])
+            .map_err(BuiltinToolError::BackendError);
+    };
+
+    // The hunks left unresolved are written back as a conflict. The new
+    // contents go through conflict markers so that absent sides and
+    // simplified conflicts are handled like when resolving in a text editor.
+    let new_content = apply_merge_sections(&hunks, &file.sections);
+    let mut materialized = vec![];
+    materialize_merge_result(&new_content, &mut materialized)
+        .expect("Writing to an in-memory buffer should never fail");
+    let new_file_ids = conflicts::update_from_content(
+        file_merge,
+        tree.store(),
+        path,
+        &materialized,
+        ConflictMarkerStyle::default(),
+    )
+    .block_on()?;
+    let new_tree_value = match new_file_ids.into_resolved() {
+        Ok(new_file_id) => Merge::normal(TreeValue::File {
+            id: new_file_id.unwrap(),
+            executable: false,
+        }),
+        Err(new_file_ids) => conflict.with_new_file_ids(&new_file_ids),
+    };
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    tree_builder.set_or_remove(path.to_owned(), new_tree_value);
+    Ok(tree_builder.write_tree(tree.store())?)
 }
 
 #[cfg(test)]
 mod tests {
     use jj_lib::conflicts::extract_as_single_hunk;
-    use jj_lib::merge::MergedTreeValue;
     use jj_lib::repo::Repo;
     use testutils::TestRepo;
 
@@ -1093,4 +1181,50 @@ mod tests {
         ]
         "###);
     }
+
+    #[test]
+    fn test_apply_merge_sections() {
+        let content = Merge::from_vec(vec![
+            BString::from("left 1\nbase 2\nleft 3\n"),
+            BString::from("base 1\nbase 2\nbase 3\n"),
+            BString::from("right 1\nbase 2\nright 3\n"),
+        ]);
+        let MergeResult::Conflict(hunks) = files::merge(&content) else {
+            panic!("merge should have conflicted");
+        };
+        let mut sections = make_merge_sections(MergeResult::Conflict(hunks.clone())).unwrap();
+        fn select(
+            sections: &mut [scm_record::Section],
+            section_index: usize,
+            line_indices: &[usize],
+        ) {
+            let scm_record::Section::Changed { lines } = &mut sections[section_index] else {
+                panic!("section should be a conflict");
+            };
+            for (index, line) in lines.iter_mut().enumerate() {
+                line.is_checked = line_indices.contains(&index);
+            }
+        }
+
+        // Nothing selected: the conflict is unchanged
+        assert_eq!(apply_merge_sections(&hunks, &sections), content);
+
+        // Take the left side of the first hunk only
+        select(&mut sections, 0, &[0, 1]);
+        assert_eq!(
+            apply_merge_sections(&hunks, &sections),
+            Merge::from_vec(vec![
+                BString::from("left 1\nbase 2\nleft 3\n"),
+                BString::from("left 1\nbase 2\nbase 3\n"),
+                BString::from("left 1\nbase 2\nright 3\n"),
+            ])
+        );
+
+        // Take both sides of the second hunk
+        select(&mut sections, 2, &[0, 1, 2]);
+        assert_eq!(
+            apply_merge_sections(&hunks, &sections),
+            Merge::resolved(BString::from("left 1\nbase 2\nleft 3\nright 3\n"))
+        );
+    }
 }
@@ -8,6 +8,7 @@ use std::sync::Arc;
 
 use futures::StreamExt;
 use jj_lib::backend::MergedTreeId;
+use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::fsmonitor::FsmonitorSettings;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::local_working_copy::TreeState;
@@ -19,6 +20,7 @@ use jj_lib::merged_tree::TreeDiffEntry;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::store::Store;
 use jj_lib::working_copy::CheckoutError;
+use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::SnapshotOptions;
 use pollster::FutureExt;
 use tempfile::TempDir;
@@ -88,8 +90,12 @@ fn check_out(
     std::fs::create_dir(&wc_dir).map_err(DiffCheckoutError::SetUpDir)?;
     std::fs::create_dir(&state_dir).map_err(DiffCheckoutError::SetUpDir)?;
     let mut tree_state = TreeState::init(store, wc_dir, state_dir)?;
-    tree_state.set_sparse_patterns(sparse_patterns)?;
-    tree_state.check_out(tree)?;
+    // The trees are snapshotted with the same style of conflict markers.
+    let options = CheckoutOptions {
+        conflict_marker_style: ConflictMarkerStyle::default(),
+    };
+    tree_state.set_sparse_patterns(sparse_patterns, &options)?;
+    tree_state.check_out(tree, &options)?;
     Ok(tree_state)
 }
 
@@ -289,6 +295,7 @@ diff editing in mind and be a little inaccurate.
             progress: None,
             start_tracking_matcher: &EverythingMatcher,
             max_new_file_size: u64::MAX,
+            conflict_marker_style: ConflictMarkerStyle::default(),
         })?;
         Ok(output_tree_state.current_tree_id().clone())
     }
@@ -13,6 +13,7 @@ use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeValue;
 use jj_lib::conflicts;
 use jj_lib::conflicts::materialize_merge_result;
+use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Merge;
@@ -226,6 +227,7 @@ pub fn run_mergetool_external(
             tree.store(),
             repo_path,
             output_file_contents.as_slice(),
+            ConflictMarkerStyle::default(),
         )
         .block_on()?
     } else {
@@ -0,0 +1,156 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::io;
+use std::process::Stdio;
+
+use bstr::BString;
+use itertools::Itertools as _;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::BackendResult;
+use jj_lib::fileset;
+use jj_lib::fileset::FilesetExpression;
+use jj_lib::merge::Merge;
+use jj_lib::merge_drivers::MergeDriver;
+use jj_lib::merge_drivers::MergeDrivers;
+use jj_lib::merge_drivers::MergeStrategy;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathUiConverter;
+use thiserror::Error;
+
+use super::diff_working_copies::new_utf8_temp_dir;
+use crate::command_error::config_error;
+use crate::command_error::CommandError;
+use crate::config::CommandNameAndArgs;
+
+/// Represents an entry in the `merge.drivers` config table.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawMergeDriverConfig {
+    patterns: Vec<String>,
+    strategy: Option<MergeStrategy>,
+    command: Option<CommandNameAndArgs>,
+}
+
+/// Parses the `merge.drivers` config table. Returns `None` if no drivers are
+/// configured.
+pub fn load_merge_drivers(config: &config::Config) -> Result<Option<MergeDrivers>, CommandError> {
+    let Ok(drivers_table) = config.get_table("merge.drivers") else {
+        return Ok(None);
+    };
+    let mut merge_drivers = MergeDrivers::new();
+    // Sorted by name so that it's clear which driver is used if several match.
+    for (name, value) in drivers_table.into_iter().sorted_by(|a, b| a.0.cmp(&b.0)) {
+        let driver_config: RawMergeDriverConfig = value.try_deserialize()?;
+        let driver: Box<dyn MergeDriver> = match (driver_config.strategy, driver_config.command) {
+            (Some(strategy), None) => Box::new(strategy),
+            (None, Some(command)) => Box::new(ExternalMergeDriver {
+                name: name.clone(),
+                command,
+            }),
+            _ => {
+                return Err(config_error(format!(
+                    "Merge driver `{name}` must have either a `strategy` or a `command`"
+                )))
+            }
+        };
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: "".into(),
+            base: "".into(),
+        };
+        let fileset = FilesetExpression::union_all(
+            driver_config
+                .patterns
+                .iter()
+                .map(|pattern| fileset::parse(pattern, &path_converter))
+                .try_collect()?,
+        );
+        merge_drivers.add(fileset, driver);
+    }
+    Ok((!merge_drivers.is_empty()).then_some(merge_drivers))
+}
+
+#[derive(Debug, Error)]
+#[error("Failed to run merge driver `{name}`")]
+struct ExternalMergeDriverError {
+    name: String,
+    #[source]
+    source: io::Error,
+}
+
+/// Merge driver that runs a command with the sides of the conflict in
+/// temporary files. The command writes the merged content to `$output`, or
+/// exits with an error to leave the file conflicted.
+#[derive(Debug)]
+struct ExternalMergeDriver {
+    name: String,
+    command: CommandNameAndArgs,
+}
+
+impl ExternalMergeDriver {
+    fn run(&self, path: &RepoPath, contents: &Merge<BString>) -> io::Result<Option<BString>> {
+        let temp_dir = new_utf8_temp_dir("jj-merge-driver-")?;
+        let suffix = path
+            .components()
+            .next_back()
+            .map(|filename| format!("_{}", filename.as_str()))
+            .unwrap_or_default();
+        let files = [
+            ("base", contents.get_remove(0).unwrap().as_slice()),
+            ("left", contents.get_add(0).unwrap().as_slice()),
+            ("right", contents.get_add(1).unwrap().as_slice()),
+            ("output", [].as_slice()),
+        ];
+        let mut variables = HashMap::new();
+        for (role, content) in files {
+            let file_path = temp_dir.path().join(format!("{role}{suffix}"));
+            std::fs::write(&file_path, content)?;
+            let file_path = file_path
+                .into_os_string()
+                .into_string()
+                .expect("temp_dir should be valid utf-8");
+            variables.insert(role, file_path);
+        }
+        variables.insert("path", path.as_internal_file_string().to_owned());
+
+        let mut cmd = self.command.to_command_with_variables(&variables);
+        tracing::info!(?cmd, "Invoking the merge driver:");
+        let exit_status = cmd.stdin(Stdio::null()).status()?;
+        if !exit_status.success() {
+            return Ok(None);
+        }
+        let output = std::fs::read(&variables["output"])?;
+        Ok(Some(BString::from(output)))
+    }
+}
+
+impl MergeDriver for ExternalMergeDriver {
+    fn merge(&self, path: &RepoPath, contents: &Merge<BString>) -> BackendResult<Option<BString>> {
+        // Like the external merge tools, the command is only given 2-sided
+        // conflicts.
+        if contents.num_sides() != 2 {
+            return Ok(None);
+        }
+        self.run(path, contents).map_err(|source| {
+            BackendError::Other(
+                ExternalMergeDriverError {
+                    name: self.name.clone(),
+                    source,
+                }
+                .into(),
+            )
+        })
+    }
+}
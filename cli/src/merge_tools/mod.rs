@@ -15,6 +15,7 @@
 mod builtin;
 mod diff_working_copies;
 mod external;
+mod merge_driver;
 
 use std::sync::Arc;
 
@@ -43,6 +44,7 @@ pub use self::external::invoke_external_diff;
 pub use self::external::DiffToolMode;
 pub use self::external::ExternalMergeTool;
 use self::external::ExternalToolError;
+pub use self::merge_driver::load_merge_drivers;
 use crate::config::CommandNameAndArgs;
 use crate::ui::Ui;
 
@@ -312,7 +314,8 @@ impl MergeEditor {
 
         match &self.tool {
             MergeTool::Builtin => {
-                let tree_id = edit_merge_builtin(tree, repo_path, content).map_err(Box::new)?;
+                let tree_id = edit_merge_builtin(tree, repo_path, &file_merge, &conflict, content)
+                    .map_err(Box::new)?;
                 Ok(tree_id)
             }
             MergeTool::External(editor) => external::run_mergetool_external(
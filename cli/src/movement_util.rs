@@ -22,6 +22,7 @@ use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetFilterPredicate;
 use jj_lib::revset::RevsetIteratorExt;
+use jj_lib::str_util::StringPattern;
 
 use crate::cli_util::short_commit_hash;
 use crate::cli_util::CommandHelper;
@@ -30,19 +31,21 @@ use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug)]
 pub(crate) struct MovementArgs {
     pub offset: u64,
     pub edit: bool,
     pub no_edit: bool,
     pub conflict: bool,
+    pub bookmark: Option<StringPattern>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug)]
 struct MovementArgsInternal {
     offset: u64,
     should_edit: bool,
     conflict: bool,
+    bookmark: Option<StringPattern>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -66,7 +69,18 @@ impl Direction {
         commits: &[Commit],
     ) -> CommandError {
         let offset = args.offset;
-        let err_msg = match (self, args.should_edit, args.conflict) {
+        let err_msg = match (self, &args.bookmark) {
+            (Direction::Next, Some(pattern)) => Some(format!(
+                "No descendant found {offset} commit(s) forward leading to a bookmark matching \
+                 {pattern}"
+            )),
+            (Direction::Prev, Some(pattern)) => Some(format!(
+                "No ancestor found {offset} commit(s) back descending from a bookmark matching \
+                 {pattern}"
+            )),
+            (_, None) => None,
+        };
+        let err_msg = err_msg.unwrap_or_else(|| match (self, args.should_edit, args.conflict) {
             // in edit mode, start_revset is the WC, so we only look for direct descendants.
             (Direction::Next, true, true) => {
                 String::from("The working copy has no descendants with conflicts")
@@ -97,7 +111,7 @@ impl Direction {
             (Direction::Prev, false, false) => format!(
                 "No ancestor found {offset} commit(s) back from the working copy parents(s)",
             ),
-        };
+        });
 
         let template = workspace_command.commit_summary_template();
         let mut cmd_err = user_error(err_msg);
@@ -144,6 +158,15 @@ impl Direction {
                 .heads(),
         };
 
+        // Only keep the targets on the way to (or from) the matching bookmarks.
+        let target_revset = match (self, &args.bookmark) {
+            (_, None) => target_revset,
+            (Direction::Next, Some(pattern)) => target_revset
+                .intersection(&RevsetExpression::bookmarks(pattern.clone()).ancestors()),
+            (Direction::Prev, Some(pattern)) => target_revset
+                .intersection(&RevsetExpression::bookmarks(pattern.clone()).descendants()),
+        };
+
         Ok(target_revset)
     }
 }
@@ -242,6 +265,7 @@ pub(crate) fn move_to_commit(
         should_edit: args.edit || (!args.no_edit && config_edit_flag),
         offset: args.offset,
         conflict: args.conflict,
+        bookmark: args.bookmark.clone(),
     };
 
     let target = get_target_commit(ui, &workspace_command, direction, current_wc_id, &args)?;
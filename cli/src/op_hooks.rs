@@ -0,0 +1,166 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commands run before and after an operation is committed.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::process::ExitStatus;
+use std::process::Stdio;
+
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::operation::Operation;
+use jj_lib::settings::ConfigResultExt as _;
+use jj_lib::settings::UserSettings;
+use jj_lib::transaction::Transaction;
+use serde::Serialize;
+
+use crate::command_error::config_error_with_message;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::config::CommandNameAndArgs;
+use crate::ui::Ui;
+
+/// Information about the operation, passed to the hooks as JSON on stdin.
+#[derive(Serialize)]
+struct HookInput<'a> {
+    hook: &'a str,
+    /// The ID of the operation, which is only known once it's committed.
+    id: Option<String>,
+    parents: Vec<String>,
+    description: &'a str,
+    hostname: &'a str,
+    username: &'a str,
+    args: &'a [String],
+    workspace_id: Option<&'a str>,
+    exit_status: Option<i32>,
+    tags: &'a HashMap<String, String>,
+}
+
+/// Runs the hook configured by `hooks.<name>` in the workspace root. Returns
+/// `None` if no hook is configured.
+fn run_hook(
+    ui: &Ui,
+    settings: &UserSettings,
+    workspace_root: &Path,
+    input: &HookInput,
+) -> Result<Option<(CommandNameAndArgs, io::Result<ExitStatus>)>, CommandError> {
+    let key = format!("hooks.{}", input.hook);
+    let Some(hook) = settings
+        .config()
+        .get::<CommandNameAndArgs>(&key)
+        .optional()
+        .map_err(|err| config_error_with_message(format!("Invalid `{key}`"), err))?
+    else {
+        return Ok(None);
+    };
+    let mut cmd = hook.to_command();
+    cmd.current_dir(workspace_root)
+        .env("JJ_HOOK", input.hook)
+        .env("JJ_OP_DESCRIPTION", input.description)
+        .stdin(Stdio::piped())
+        .stdout(ui.stderr_for_child()?)
+        .stderr(ui.stderr_for_child()?);
+    if let Some(id) = &input.id {
+        cmd.env("JJ_OP_ID", id);
+    }
+    tracing::info!(?cmd, "running hook");
+    let status = cmd.spawn().and_then(|mut child| {
+        let mut stdin = child.stdin.take().unwrap();
+        // The hook may exit without reading its input.
+        serde_json::to_writer(&mut stdin, input).ok();
+        stdin.flush().ok();
+        drop(stdin);
+        child.wait()
+    });
+    Ok(Some((hook, status)))
+}
+
+/// Runs the `hooks.pre-operation` hook for the transaction about to be
+/// committed with the `description`. Returns an error if the hook fails, in
+/// which case the transaction shouldn't be committed.
+pub fn run_pre_operation_hook(
+    ui: &Ui,
+    settings: &UserSettings,
+    workspace_root: &Path,
+    tx: &Transaction,
+    description: &str,
+) -> Result<(), CommandError> {
+    let metadata = tx.op_metadata();
+    let input = HookInput {
+        hook: "pre-operation",
+        id: None,
+        parents: vec![tx.base_repo().op_id().hex()],
+        description,
+        hostname: &metadata.hostname,
+        username: &metadata.username,
+        args: &metadata.args,
+        workspace_id: metadata.workspace_id.as_ref().map(|id| id.as_str()),
+        exit_status: metadata.exit_status,
+        tags: &metadata.tags,
+    };
+    match run_hook(ui, settings, workspace_root, &input)? {
+        None => Ok(()),
+        Some((_, Ok(status))) if status.success() => Ok(()),
+        Some((hook, Ok(status))) => Err(user_error(format!(
+            "Pre-operation hook '{}' rejected the operation ({status})",
+            hook.split_name()
+        ))),
+        Some((hook, Err(err))) => Err(user_error_with_message(
+            format!("Failed to run pre-operation hook '{}'", hook.split_name()),
+            err,
+        )),
+    }
+}
+
+/// Runs the `hooks.post-operation` hook for the committed operation. Since the
+/// operation can't be aborted anymore, failures are reported as warnings.
+pub fn run_post_operation_hook(
+    ui: &Ui,
+    settings: &UserSettings,
+    workspace_root: &Path,
+    operation: &Operation,
+) -> Result<(), CommandError> {
+    let metadata = operation.metadata();
+    let input = HookInput {
+        hook: "post-operation",
+        id: Some(operation.id().hex()),
+        parents: operation.parent_ids().iter().map(|id| id.hex()).collect(),
+        description: &metadata.description,
+        hostname: &metadata.hostname,
+        username: &metadata.username,
+        args: &metadata.args,
+        workspace_id: metadata.workspace_id.as_ref().map(|id| id.as_str()),
+        exit_status: metadata.exit_status,
+        tags: &metadata.tags,
+    };
+    match run_hook(ui, settings, workspace_root, &input)? {
+        None => {}
+        Some((_, Ok(status))) if status.success() => {}
+        Some((hook, Ok(status))) => writeln!(
+            ui.warning_default(),
+            "Post-operation hook '{}' failed ({status})",
+            hook.split_name()
+        )?,
+        Some((hook, Err(err))) => writeln!(
+            ui.warning_default(),
+            "Failed to run post-operation hook '{}': {err}",
+            hook.split_name()
+        )?,
+    }
+    Ok(())
+}
@@ -22,6 +22,7 @@ use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::OperationId;
 use jj_lib::operation::Operation;
 
+use crate::cli_util::operation_command_line;
 use crate::template_builder;
 use crate::template_builder::merge_fn_map;
 use crate::template_builder::BuildContext;
@@ -33,10 +34,12 @@ use crate::template_builder::TemplateLanguage;
 use crate::template_parser;
 use crate::template_parser::FunctionCallNode;
 use crate::template_parser::TemplateParseResult;
+use crate::templater::JsonValue;
 use crate::templater::PlainTextFormattedProperty;
 use crate::templater::Template;
 use crate::templater::TemplateFormatter;
 use crate::templater::TemplateProperty;
+use crate::templater::TemplatePropertyError;
 use crate::templater::TemplatePropertyExt as _;
 use crate::templater::TimestampRange;
 
@@ -46,6 +49,17 @@ pub trait OperationTemplateLanguageExtension {
     fn build_cache_extensions(&self, extensions: &mut ExtensionsMap);
 }
 
+/// Template language which provides the `Operation` and `OperationId` types.
+///
+/// The methods of these types are shared by the languages implementing this
+/// trait. See `builtin_operation_methods()`.
+pub trait OperationTemplateTypes<'a>: TemplateLanguage<'a> {
+    fn wrap_operation(property: impl TemplateProperty<Output = Operation> + 'a) -> Self::Property;
+    fn wrap_operation_id(
+        property: impl TemplateProperty<Output = OperationId> + 'a,
+    ) -> Self::Property;
+}
+
 pub struct OperationTemplateLanguage {
     root_op_id: OperationId,
     current_op_id: Option<OperationId>,
@@ -124,14 +138,16 @@ impl OperationTemplateLanguage {
     pub fn cache_extension<T: Any>(&self) -> Option<&T> {
         self.cache_extensions.get::<T>()
     }
+}
 
-    pub fn wrap_operation(
+impl OperationTemplateTypes<'static> for OperationTemplateLanguage {
+    fn wrap_operation(
         property: impl TemplateProperty<Output = Operation> + 'static,
     ) -> OperationTemplatePropertyKind {
         OperationTemplatePropertyKind::Operation(Box::new(property))
     }
 
-    pub fn wrap_operation_id(
+    fn wrap_operation_id(
         property: impl TemplateProperty<Output = OperationId> + 'static,
     ) -> OperationTemplatePropertyKind {
         OperationTemplatePropertyKind::OperationId(Box::new(property))
@@ -185,6 +201,18 @@ impl IntoTemplateProperty<'static> for OperationTemplatePropertyKind {
             OperationTemplatePropertyKind::OperationId(property) => Some(property.into_template()),
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value>>> {
+        match self {
+            OperationTemplatePropertyKind::Core(property) => property.try_into_json(),
+            OperationTemplatePropertyKind::Operation(property) => {
+                Some(Box::new(property.and_then(|op| op.to_json())))
+            }
+            OperationTemplatePropertyKind::OperationId(property) => {
+                Some(Box::new(property.and_then(|id| id.to_json())))
+            }
+        }
+    }
 }
 
 /// Table of functions that translate method call node of self type `T`.
@@ -203,7 +231,7 @@ impl OperationTemplateBuildFnTable {
     fn builtin() -> Self {
         OperationTemplateBuildFnTable {
             core: CoreTemplateBuildFnTable::builtin(),
-            operation_methods: builtin_operation_methods(),
+            operation_methods: builtin_operation_template_methods(),
             operation_id_methods: builtin_operation_id_methods(),
         }
     }
@@ -229,11 +257,9 @@ impl OperationTemplateBuildFnTable {
     }
 }
 
-fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
+fn builtin_operation_template_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
     type L = OperationTemplateLanguage;
-    // Not using maplit::hashmap!{} or custom declarative macro here because
-    // code completion inside macro is quite restricted.
-    let mut map = OperationTemplateBuildMethodFnMap::<Operation>::new();
+    let mut map = builtin_operation_methods::<L>();
     map.insert(
         "current_operation",
         |language, _build_ctx, self_property, function| {
@@ -243,6 +269,44 @@ fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert("root", |language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let root_op_id = language.root_op_id.clone();
+        let out_property = self_property.map(move |op| op.id() == &root_op_id);
+        Ok(L::wrap_boolean(out_property))
+    });
+    map
+}
+
+/// Creates new table of the `Operation` methods which don't depend on the
+/// template language.
+pub fn builtin_operation_methods<'a, L: OperationTemplateTypes<'a> + ?Sized>(
+) -> TemplateBuildMethodFnMap<'a, L, Operation> {
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = TemplateBuildMethodFnMap::<L, Operation>::new();
+    map.insert("args", |_language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let out_property = self_property.map(|op| op.metadata().args.clone());
+        Ok(L::wrap_string_list(out_property))
+    });
+    map.insert(
+        "command_line",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.map(|op| operation_command_line(op.metadata()).unwrap_or_default());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "exit_status",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|op| op.metadata().exit_status.map(i64::from));
+            Ok(L::wrap_integer_opt(out_property))
+        },
+    );
     map.insert(
         "description",
         |_language, _build_ctx, self_property, function| {
@@ -251,18 +315,37 @@ fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
             Ok(L::wrap_string(out_property))
         },
     );
+    map.insert(
+        "hostname",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|op| op.metadata().hostname.clone());
+            Ok(L::wrap_string(out_property))
+        },
+    );
     map.insert("id", |_language, _build_ctx, self_property, function| {
         function.expect_no_arguments()?;
         let out_property = self_property.map(|op| op.id().clone());
         Ok(L::wrap_operation_id(out_property))
     });
+    map.insert("tag", |_language, _build_ctx, self_property, function| {
+        let [key_node] = function.expect_exact_arguments()?;
+        let key =
+            template_parser::expect_string_literal_with(key_node, |key, _span| Ok(key.to_owned()))?;
+        let out_property =
+            self_property.map(move |op| op.metadata().tags.get(&key).cloned().unwrap_or_default());
+        Ok(L::wrap_string(out_property))
+    });
     map.insert("tags", |_language, _build_ctx, self_property, function| {
         function.expect_no_arguments()?;
         let out_property = self_property.map(|op| {
             // TODO: introduce map type
+            // The `args` tag of operations written by older versions is shown
+            // by command_line() instead.
             op.metadata()
                 .tags
                 .iter()
+                .filter(|(key, _)| *key != "args")
                 .map(|(key, value)| format!("{key}: {value}"))
                 .join("\n")
         });
@@ -292,26 +375,71 @@ fn builtin_operation_methods() -> OperationTemplateBuildMethodFnMap<Operation> {
         });
         Ok(L::wrap_string(out_property))
     });
-    map.insert("root", |language, _build_ctx, self_property, function| {
-        function.expect_no_arguments()?;
-        let root_op_id = language.root_op_id.clone();
-        let out_property = self_property.map(move |op| op.id() == &root_op_id);
-        Ok(L::wrap_boolean(out_property))
-    });
+    map.insert(
+        "username",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|op| op.metadata().username.clone());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "workspace_id",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|op| {
+                op.metadata()
+                    .workspace_id
+                    .as_ref()
+                    .map_or_else(String::new, |id| id.as_str().to_owned())
+            });
+            Ok(L::wrap_string(out_property))
+        },
+    );
     map
 }
 
+impl JsonValue for Operation {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let metadata = self.metadata();
+        let time = TimestampRange {
+            start: metadata.start_time.clone(),
+            end: metadata.end_time.clone(),
+        };
+        Ok(serde_json::json!({
+            "id": self.id().to_json()?,
+            "parents": self.parent_ids().iter().map(|id| id.hex()).collect_vec(),
+            "time": time.to_json()?,
+            "description": metadata.description,
+            "hostname": metadata.hostname,
+            "username": metadata.username,
+            "is_snapshot": metadata.is_snapshot,
+            "args": metadata.args,
+            "workspace_id": metadata.workspace_id.as_ref().map(|id| id.as_str()),
+            "exit_status": metadata.exit_status,
+            "tags": metadata.tags,
+        }))
+    }
+}
+
 impl Template for OperationId {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         write!(formatter, "{}", self.hex())
     }
 }
 
-fn builtin_operation_id_methods() -> OperationTemplateBuildMethodFnMap<OperationId> {
-    type L = OperationTemplateLanguage;
+impl JsonValue for OperationId {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(self.hex().into())
+    }
+}
+
+/// Creates new table of the `OperationId` methods.
+pub fn builtin_operation_id_methods<'a, L: OperationTemplateTypes<'a> + ?Sized>(
+) -> TemplateBuildMethodFnMap<'a, L, OperationId> {
     // Not using maplit::hashmap!{} or custom declarative macro here because
     // code completion inside macro is quite restricted.
-    let mut map = OperationTemplateBuildMethodFnMap::<OperationId>::new();
+    let mut map = TemplateBuildMethodFnMap::<L, OperationId>::new();
     map.insert("short", |language, build_ctx, self_property, function| {
         let ([], [len_node]) = function.expect_arguments()?;
         let len_property = len_node
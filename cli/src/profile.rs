@@ -0,0 +1,223 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recording of span timings for `jj --profile`.
+//!
+//! The profiling layer is installed with the other tracing layers at startup,
+//! but records nothing until profiling is enabled by the command-line flag.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::thread::ThreadId;
+use std::time::Duration;
+use std::time::Instant;
+
+use itertools::Itertools as _;
+use tracing::span;
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Handle to enable profiling and to report the recorded timings.
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    enabled: Arc<AtomicBool>,
+    state: Arc<Mutex<ProfileState>>,
+}
+
+#[derive(Debug, Default)]
+struct ProfileState {
+    start: Option<Instant>,
+    spans: Vec<SpanRecord>,
+    thread_ids: HashMap<ThreadId, usize>,
+    trace_path: Option<PathBuf>,
+}
+
+/// Timing of a closed span.
+#[derive(Debug)]
+struct SpanRecord {
+    name: &'static str,
+    target: &'static str,
+    start: Instant,
+    duration: Duration,
+    // Time spent in the span minus the time spent in its child spans.
+    self_duration: Duration,
+    thread: usize,
+}
+
+/// Timing of an open span, stored in the span's extensions.
+struct SpanTiming {
+    start: Instant,
+    children: Duration,
+}
+
+impl Profiler {
+    /// Returns the layer recording span timings while profiling is enabled.
+    pub fn layer<S>(&self) -> impl Layer<S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let enabled = self.enabled.clone();
+        ProfileLayer {
+            state: self.state.clone(),
+        }
+        .with_filter(tracing_subscriber::filter::filter_fn(move |_| {
+            enabled.load(Ordering::Relaxed)
+        }))
+    }
+
+    /// Starts recording spans. If `trace_path` is specified, the spans will
+    /// be written there as a Chrome trace instead of being summarized.
+    pub fn enable(&self, trace_path: Option<PathBuf>) {
+        let mut state = self.state.lock().unwrap();
+        state.start = Some(Instant::now());
+        state.trace_path = trace_path;
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Stops recording spans, and reports the recorded timings. Does nothing
+    /// if profiling wasn't enabled.
+    pub fn finish(&self, stderr: &mut dyn Write) -> io::Result<()> {
+        self.enabled.store(false, Ordering::Relaxed);
+        let state = self.state.lock().unwrap();
+        let Some(start) = state.start else {
+            return Ok(());
+        };
+        let elapsed = start.elapsed();
+        match &state.trace_path {
+            Some(path) => {
+                write_chrome_trace(path, start, &state.spans)?;
+                writeln!(stderr, "Wrote profile to {}", path.display())
+            }
+            None => write_summary(stderr, elapsed, &state.spans),
+        }
+    }
+}
+
+struct ProfileLayer {
+    state: Arc<Mutex<ProfileState>>,
+}
+
+impl<S> Layer<S> for ProfileLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanTiming {
+                start: Instant::now(),
+                children: Duration::ZERO,
+            });
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let duration = timing.start.elapsed();
+        if let Some(parent) = span.parent() {
+            if let Some(parent_timing) = parent.extensions_mut().get_mut::<SpanTiming>() {
+                parent_timing.children += duration;
+            }
+        }
+        let mut state = self.state.lock().unwrap();
+        let num_threads = state.thread_ids.len();
+        let thread = *state
+            .thread_ids
+            .entry(std::thread::current().id())
+            .or_insert(num_threads);
+        state.spans.push(SpanRecord {
+            name: span.name(),
+            target: span.metadata().target(),
+            start: timing.start,
+            duration,
+            self_duration: duration.saturating_sub(timing.children),
+            thread,
+        });
+    }
+}
+
+/// Writes the total and self time of the spans grouped by name, slowest
+/// first.
+fn write_summary(
+    stderr: &mut dyn Write,
+    elapsed: Duration,
+    spans: &[SpanRecord],
+) -> io::Result<()> {
+    let mut totals: HashMap<(&str, &str), (Duration, Duration, usize)> = HashMap::new();
+    for span in spans {
+        let (total, self_total, count) = totals.entry((span.target, span.name)).or_default();
+        *total += span.duration;
+        *self_total += span.self_duration;
+        *count += 1;
+    }
+    writeln!(stderr, "Profile of {}:", format_duration(elapsed))?;
+    writeln!(
+        stderr,
+        "{:>10} {:>10} {:>7}  Span",
+        "Total", "Self", "Calls"
+    )?;
+    let rows = totals
+        .into_iter()
+        .sorted_by_key(|&(key, (total, _, _))| (std::cmp::Reverse(total), key));
+    for ((target, name), (total, self_total, count)) in rows {
+        writeln!(
+            stderr,
+            "{:>10} {:>10} {count:>7}  {target}::{name}",
+            format_duration(total),
+            format_duration(self_total),
+        )?;
+    }
+    Ok(())
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Writes the spans as complete events in the Chrome trace format, which can
+/// be loaded by `chrome://tracing` or <https://ui.perfetto.dev>.
+fn write_chrome_trace(path: &Path, start: Instant, spans: &[SpanRecord]) -> io::Result<()> {
+    let events = spans
+        .iter()
+        .map(|span| {
+            serde_json::json!({
+                "name": span.name,
+                "cat": span.target,
+                "ph": "X",
+                "ts": span.start.saturating_duration_since(start).as_micros() as u64,
+                "dur": span.duration.as_micros() as u64,
+                "pid": 1,
+                "tid": span.thread,
+            })
+        })
+        .collect_vec();
+    let mut file = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut file, &serde_json::json!({ "traceEvents": events }))?;
+    file.flush()
+}
@@ -6,6 +6,7 @@ use std::time::Instant;
 
 use crossterm::terminal::Clear;
 use crossterm::terminal::ClearType;
+use jj_lib::default_index::IndexProgress;
 use jj_lib::fmt_util::binary_prefix;
 use jj_lib::git;
 use jj_lib::repo_path::RepoPath;
@@ -19,6 +20,7 @@ use crate::ui::Ui;
 pub struct Progress {
     next_print: Instant,
     rate: RateEstimate,
+    phase: Option<(git::ProgressPhase, Instant)>,
     buffer: String,
     guard: Option<CleanupGuard>,
 }
@@ -28,6 +30,7 @@ impl Progress {
         Self {
             next_print: now + INITIAL_DELAY,
             rate: RateEstimate::new(),
+            phase: None,
             buffer: String::new(),
             guard: None,
         }
@@ -48,8 +51,12 @@ impl Progress {
         }
 
         let rate = progress
-            .bytes_downloaded
+            .bytes_transferred
             .and_then(|x| self.rate.update(now, x));
+        let phase_start = match self.phase {
+            Some((phase, start)) if phase == progress.phase => start,
+            _ => self.phase.insert((progress.phase, now)).1,
+        };
         if now < self.next_print {
             return Ok(());
         }
@@ -66,15 +73,8 @@ impl Progress {
         self.buffer.clear();
         write!(self.buffer, "\r").unwrap();
         let control_chars = self.buffer.len();
-        write!(self.buffer, "{: >3.0}% ", 100.0 * progress.overall).unwrap();
-        if let Some(total) = progress.bytes_downloaded {
-            let (scaled, prefix) = binary_prefix(total as f32);
-            write!(self.buffer, "{scaled: >5.1} {prefix}B ").unwrap();
-        }
-        if let Some(estimate) = rate {
-            let (scaled, prefix) = binary_prefix(estimate);
-            write!(self.buffer, "at {scaled: >5.1} {prefix}B/s ").unwrap();
-        }
+        let remaining = estimate_remaining_time(now - phase_start, progress.overall);
+        write_status(&mut self.buffer, progress, rate, remaining);
 
         let bar_width = output
             .term_width()
@@ -92,6 +92,72 @@ impl Progress {
     }
 }
 
+/// Writes the phase, the object counts, the transferred bytes, the rate, and
+/// the remaining time of the `progress`.
+fn write_status(
+    buffer: &mut String,
+    progress: &git::Progress,
+    rate: Option<f32>,
+    remaining: Option<Duration>,
+) {
+    use std::fmt::Write as _;
+
+    write!(buffer, "{}: ", phase_label(progress.phase)).unwrap();
+    let (current_objects, total_objects) = progress.objects;
+    if total_objects == 0 {
+        // Nothing is known about the total while counting objects.
+        write!(buffer, "{current_objects} ").unwrap();
+    } else {
+        write!(
+            buffer,
+            "{: >3.0}% ({current_objects}/{total_objects}) ",
+            100.0 * progress.overall
+        )
+        .unwrap();
+    }
+    if let Some(total) = progress.bytes_transferred {
+        let (scaled, prefix) = binary_prefix(total as f32);
+        write!(buffer, "{scaled: >5.1} {prefix}B ").unwrap();
+    }
+    if let Some(estimate) = rate {
+        let (scaled, prefix) = binary_prefix(estimate);
+        write!(buffer, "at {scaled: >5.1} {prefix}B/s ").unwrap();
+    }
+    if let Some(remaining) = remaining {
+        write!(buffer, "ETA {} ", format_remaining_time(remaining)).unwrap();
+    }
+}
+
+fn phase_label(phase: git::ProgressPhase) -> &'static str {
+    match phase {
+        git::ProgressPhase::Receiving => "Receiving objects",
+        git::ProgressPhase::Resolving => "Resolving deltas",
+        git::ProgressPhase::Counting => "Counting objects",
+        git::ProgressPhase::Compressing => "Compressing objects",
+        git::ProgressPhase::Sending => "Sending objects",
+    }
+}
+
+/// Extrapolates the remaining time of a phase from the time spent so far.
+/// Returns `None` until there's enough progress to make a guess.
+fn estimate_remaining_time(elapsed: Duration, progress: f32) -> Option<Duration> {
+    if elapsed < Duration::from_secs(1) || !(0.01..1.0).contains(&progress) {
+        return None;
+    }
+    Some(elapsed.mul_f32((1.0 - progress) / progress))
+}
+
+fn format_remaining_time(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
 fn draw_progress(progress: f32, buffer: &mut String, width: usize) {
     const CHARS: [char; 9] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
     const RESOLUTION: usize = CHARS.len() - 1;
@@ -213,6 +279,50 @@ pub fn snapshot_progress(ui: &Ui) -> Option<impl Fn(&RepoPath) + '_> {
     })
 }
 
+pub fn index_progress(ui: &Ui) -> Option<impl Fn(&IndexProgress) + '_> {
+    struct State {
+        guard: Option<OutputGuard>,
+        output: ProgressOutput,
+        next_display_time: Instant,
+    }
+
+    let output = ui.progress_output()?;
+
+    // Don't clutter the output if the index is built quickly.
+    let next_display_time = Instant::now() + INITIAL_DELAY;
+    let state = Mutex::new(State {
+        guard: None,
+        output,
+        next_display_time,
+    });
+
+    Some(move |progress: &IndexProgress| {
+        let mut state = state.lock().unwrap();
+        let now = Instant::now();
+        if now < state.next_display_time {
+            return;
+        }
+        state.next_display_time = now + Duration::from_secs(1) / UPDATE_HZ;
+
+        if state.guard.is_none() {
+            state.guard = Some(
+                state
+                    .output
+                    .output_guard(format!("\r{}", Clear(ClearType::CurrentLine))),
+            );
+        }
+
+        let message = match progress {
+            IndexProgress::LoadingCommits { loaded } => format!("Loading commits: {loaded}"),
+            IndexProgress::IndexingCommits { indexed, total } => {
+                format!("Indexing commits: {indexed}/{total}")
+            }
+        };
+        _ = write!(state.output, "\r{}{message}", Clear(ClearType::CurrentLine),);
+        _ = state.output.flush();
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,4 +343,64 @@ mod tests {
         assert_eq!(buf, "█████▍    ");
         buf.clear();
     }
+
+    #[test]
+    fn test_write_status() {
+        let status = |progress: &git::Progress, rate, remaining| {
+            let mut buffer = String::new();
+            write_status(&mut buffer, progress, rate, remaining);
+            buffer
+        };
+        let receiving = git::Progress {
+            bytes_transferred: Some(3 * 1024 * 1024),
+            overall: 0.25,
+            phase: git::ProgressPhase::Receiving,
+            objects: (100, 400),
+        };
+        assert_eq!(
+            status(&receiving, None, None),
+            "Receiving objects:  25% (100/400)   3.0 MiB "
+        );
+        assert_eq!(
+            status(&receiving, Some(1024.0), Some(Duration::from_secs(90))),
+            "Receiving objects:  25% (100/400)   3.0 MiB at   1.0 KiB/s ETA 1m30s "
+        );
+
+        // The total isn't known while counting
+        let counting = git::Progress {
+            bytes_transferred: None,
+            overall: 0.0,
+            phase: git::ProgressPhase::Counting,
+            objects: (42, 0),
+        };
+        assert_eq!(status(&counting, None, None), "Counting objects: 42 ");
+
+        let sending = git::Progress {
+            bytes_transferred: Some(512),
+            overall: 0.5,
+            phase: git::ProgressPhase::Sending,
+            objects: (1, 2),
+        };
+        assert_eq!(
+            status(&sending, None, None),
+            "Sending objects:  50% (1/2) 512.0 B "
+        );
+    }
+
+    #[test]
+    fn test_remaining_time() {
+        let secs = Duration::from_secs;
+        assert_eq!(
+            estimate_remaining_time(Duration::from_millis(500), 0.5),
+            None
+        );
+        assert_eq!(estimate_remaining_time(secs(10), 0.0), None);
+        assert_eq!(estimate_remaining_time(secs(10), 1.0), None);
+        assert_eq!(estimate_remaining_time(secs(10), 0.5), Some(secs(10)));
+        assert_eq!(estimate_remaining_time(secs(10), 0.25), Some(secs(30)));
+
+        assert_eq!(format_remaining_time(secs(5)), "5s");
+        assert_eq!(format_remaining_time(secs(65)), "1m05s");
+        assert_eq!(format_remaining_time(secs(3 * 3600 + 120)), "3h02m");
+    }
 }
@@ -38,6 +38,7 @@ use jj_lib::revset::RevsetResolutionError;
 use jj_lib::revset::SymbolResolverExtension;
 use jj_lib::settings::ConfigResultExt as _;
 use thiserror::Error;
+use tracing::instrument;
 
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
@@ -175,6 +176,7 @@ pub fn load_revset_aliases(
     Ok(aliases_map)
 }
 
+#[instrument(skip_all)]
 pub fn evaluate<'a>(
     repo: &'a dyn Repo,
     symbol_resolver: &DefaultSymbolResolver,
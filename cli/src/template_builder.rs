@@ -13,11 +13,14 @@
 // limitations under the License.
 
 use std::collections::HashMap;
+use std::env;
 
 use itertools::Itertools as _;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
 use jj_lib::dsl_util::AliasExpandError as _;
+use jj_lib::trailer;
+use jj_lib::trailer::Trailer;
 
 use crate::template_parser;
 use crate::template_parser::BinaryOp;
@@ -32,6 +35,9 @@ use crate::template_parser::UnaryOp;
 use crate::templater::CoalesceTemplate;
 use crate::templater::ConcatTemplate;
 use crate::templater::ConditionalTemplate;
+use crate::templater::HyperlinkTemplate;
+use crate::templater::JsonObjectProperty;
+use crate::templater::JsonValue as _;
 use crate::templater::LabelTemplate;
 use crate::templater::ListPropertyTemplate;
 use crate::templater::ListTemplate;
@@ -69,6 +75,10 @@ pub trait TemplateLanguage<'a> {
     fn wrap_timestamp_range(
         property: impl TemplateProperty<Output = TimestampRange> + 'a,
     ) -> Self::Property;
+    fn wrap_trailer(property: impl TemplateProperty<Output = Trailer> + 'a) -> Self::Property;
+    fn wrap_trailer_list(
+        property: impl TemplateProperty<Output = Vec<Trailer>> + 'a,
+    ) -> Self::Property;
 
     fn wrap_template(template: Box<dyn Template + 'a>) -> Self::Property;
     fn wrap_list_template(template: Box<dyn ListTemplate + 'a>) -> Self::Property;
@@ -111,6 +121,8 @@ macro_rules! impl_core_wrap_property_fns {
                 wrap_size_hint($crate::templater::SizeHint) => SizeHint,
                 wrap_timestamp(jj_lib::backend::Timestamp) => Timestamp,
                 wrap_timestamp_range($crate::templater::TimestampRange) => TimestampRange,
+                wrap_trailer(jj_lib::trailer::Trailer) => Trailer,
+                wrap_trailer_list(Vec<jj_lib::trailer::Trailer>) => TrailerList,
             }
         );
         fn wrap_template(
@@ -154,6 +166,7 @@ pub trait IntoTemplateProperty<'a> {
 
     fn try_into_plain_text(self) -> Option<Box<dyn TemplateProperty<Output = String> + 'a>>;
     fn try_into_template(self) -> Option<Box<dyn Template + 'a>>;
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>>;
 }
 
 pub enum CoreTemplatePropertyKind<'a> {
@@ -166,6 +179,8 @@ pub enum CoreTemplatePropertyKind<'a> {
     SizeHint(Box<dyn TemplateProperty<Output = SizeHint> + 'a>),
     Timestamp(Box<dyn TemplateProperty<Output = Timestamp> + 'a>),
     TimestampRange(Box<dyn TemplateProperty<Output = TimestampRange> + 'a>),
+    Trailer(Box<dyn TemplateProperty<Output = Trailer> + 'a>),
+    TrailerList(Box<dyn TemplateProperty<Output = Vec<Trailer>> + 'a>),
 
     // Both TemplateProperty and Template can represent a value to be evaluated
     // dynamically, which suggests that `Box<dyn Template + 'a>` could be
@@ -193,6 +208,8 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::SizeHint(_) => "SizeHint",
             CoreTemplatePropertyKind::Timestamp(_) => "Timestamp",
             CoreTemplatePropertyKind::TimestampRange(_) => "TimestampRange",
+            CoreTemplatePropertyKind::Trailer(_) => "Trailer",
+            CoreTemplatePropertyKind::TrailerList(_) => "List<Trailer>",
             CoreTemplatePropertyKind::Template(_) => "Template",
             CoreTemplatePropertyKind::ListTemplate(_) => "ListTemplate",
         }
@@ -215,6 +232,10 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::SizeHint(_) => None,
             CoreTemplatePropertyKind::Timestamp(_) => None,
             CoreTemplatePropertyKind::TimestampRange(_) => None,
+            CoreTemplatePropertyKind::Trailer(_) => None,
+            CoreTemplatePropertyKind::TrailerList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
             // Template types could also be evaluated to boolean, but it's less likely
             // to apply label() or .map() and use the result as conditional. It's also
             // unclear whether ListTemplate should behave as a "list" or a "template".
@@ -254,10 +275,53 @@ impl<'a> IntoTemplateProperty<'a> for CoreTemplatePropertyKind<'a> {
             CoreTemplatePropertyKind::SizeHint(_) => None,
             CoreTemplatePropertyKind::Timestamp(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::TimestampRange(property) => Some(property.into_template()),
+            CoreTemplatePropertyKind::Trailer(property) => Some(property.into_template()),
+            CoreTemplatePropertyKind::TrailerList(property) => Some(property.into_template()),
             CoreTemplatePropertyKind::Template(template) => Some(template),
             CoreTemplatePropertyKind::ListTemplate(template) => Some(template.into_template()),
         }
     }
+
+    fn try_into_json(self) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        match self {
+            CoreTemplatePropertyKind::String(property) => Some(Box::new(property.map(Into::into))),
+            CoreTemplatePropertyKind::StringList(property) => {
+                Some(Box::new(property.map(Into::into)))
+            }
+            CoreTemplatePropertyKind::Boolean(property) => Some(Box::new(property.map(Into::into))),
+            CoreTemplatePropertyKind::Integer(property) => Some(Box::new(property.map(Into::into))),
+            CoreTemplatePropertyKind::IntegerOpt(property) => {
+                Some(Box::new(property.map(Into::into)))
+            }
+            CoreTemplatePropertyKind::Signature(property) => {
+                Some(Box::new(property.and_then(|sig| sig.to_json())))
+            }
+            CoreTemplatePropertyKind::SizeHint(property) => {
+                Some(Box::new(property.and_then(|hint| hint.to_json())))
+            }
+            CoreTemplatePropertyKind::Timestamp(property) => {
+                Some(Box::new(property.and_then(|time| time.to_json())))
+            }
+            CoreTemplatePropertyKind::TimestampRange(property) => {
+                Some(Box::new(property.and_then(|range| range.to_json())))
+            }
+            CoreTemplatePropertyKind::Trailer(property) => {
+                Some(Box::new(property.and_then(|trailer| trailer.to_json())))
+            }
+            CoreTemplatePropertyKind::TrailerList(property) => {
+                Some(Box::new(property.and_then(|trailers| trailers.to_json())))
+            }
+            // Templates are serialized as plain text.
+            CoreTemplatePropertyKind::Template(template) => {
+                let property = PlainTextFormattedProperty::new(template);
+                Some(Box::new(property.map(Into::into)))
+            }
+            CoreTemplatePropertyKind::ListTemplate(template) => {
+                let property = PlainTextFormattedProperty::new(template.into_template());
+                Some(Box::new(property.map(Into::into)))
+            }
+        }
+    }
 }
 
 /// Function that translates global function call node.
@@ -299,6 +363,8 @@ pub struct CoreTemplateBuildFnTable<'a, L: TemplateLanguage<'a> + ?Sized> {
     pub size_hint_methods: TemplateBuildMethodFnMap<'a, L, SizeHint>,
     pub timestamp_methods: TemplateBuildMethodFnMap<'a, L, Timestamp>,
     pub timestamp_range_methods: TemplateBuildMethodFnMap<'a, L, TimestampRange>,
+    pub trailer_methods: TemplateBuildMethodFnMap<'a, L, Trailer>,
+    pub trailer_list_methods: TemplateBuildMethodFnMap<'a, L, Vec<Trailer>>,
 }
 
 pub fn merge_fn_map<'s, F>(base: &mut HashMap<&'s str, F>, extension: HashMap<&'s str, F>) {
@@ -321,6 +387,8 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
             size_hint_methods: builtin_size_hint_methods(),
             timestamp_methods: builtin_timestamp_methods(),
             timestamp_range_methods: builtin_timestamp_range_methods(),
+            trailer_methods: builtin_trailer_methods(),
+            trailer_list_methods: builtin_trailer_list_methods(),
         }
     }
 
@@ -334,6 +402,8 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
             size_hint_methods: HashMap::new(),
             timestamp_methods: HashMap::new(),
             timestamp_range_methods: HashMap::new(),
+            trailer_methods: HashMap::new(),
+            trailer_list_methods: HashMap::new(),
         }
     }
 
@@ -347,6 +417,8 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
             size_hint_methods,
             timestamp_methods,
             timestamp_range_methods,
+            trailer_methods,
+            trailer_list_methods,
         } = extension;
 
         merge_fn_map(&mut self.functions, functions);
@@ -357,6 +429,8 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
         merge_fn_map(&mut self.size_hint_methods, size_hint_methods);
         merge_fn_map(&mut self.timestamp_methods, timestamp_methods);
         merge_fn_map(&mut self.timestamp_range_methods, timestamp_range_methods);
+        merge_fn_map(&mut self.trailer_methods, trailer_methods);
+        merge_fn_map(&mut self.trailer_list_methods, trailer_list_methods);
     }
 
     /// Translates the function call node `function` by using this symbol table.
@@ -430,6 +504,22 @@ impl<'a, L: TemplateLanguage<'a> + ?Sized> CoreTemplateBuildFnTable<'a, L> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(language, build_ctx, property, function)
             }
+            CoreTemplatePropertyKind::Trailer(property) => {
+                let table = &self.trailer_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(language, build_ctx, property, function)
+            }
+            CoreTemplatePropertyKind::TrailerList(property) => {
+                // Trailer-specific methods take precedence over the generic
+                // list methods.
+                if let Some(build) = self.trailer_list_methods.get(function.name) {
+                    build(language, build_ctx, property, function)
+                } else {
+                    build_formattable_list_method(language, build_ctx, property, function, |item| {
+                        L::wrap_trailer(item)
+                    })
+                }
+            }
             CoreTemplatePropertyKind::Template(_) => {
                 // TODO: migrate to table?
                 Err(TemplateParseError::no_such_method(type_name, function))
@@ -477,6 +567,12 @@ impl<'a, P: IntoTemplateProperty<'a>> Expression<P> {
         self.property.try_into_plain_text()
     }
 
+    pub fn try_into_json(
+        self,
+    ) -> Option<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+        self.property.try_into_json()
+    }
+
     pub fn try_into_template(self) -> Option<Box<dyn Template + 'a>> {
         let template = self.property.try_into_template()?;
         if self.labels.is_empty() {
@@ -687,9 +783,82 @@ fn builtin_string_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
         let out_property = self_property.map(|s| s.to_lowercase());
         Ok(L::wrap_string(out_property))
     });
+    map.insert(
+        "truncate",
+        |language, build_ctx, self_property, function| {
+            let ([width_node], [ellipsis_node]) = function.expect_arguments()?;
+            let width_property = expect_usize_expression(language, build_ctx, width_node)?;
+            let ellipsis_property: Box<dyn TemplateProperty<Output = String>> = match ellipsis_node
+            {
+                Some(node) => expect_plain_text_expression(language, build_ctx, node)?,
+                None => Box::new(Literal(String::new())),
+            };
+            let out_property =
+                (self_property, width_property, ellipsis_property).map(|(s, width, ellipsis)| {
+                    text_util::elide_end(&s, &ellipsis, width).0.into_owned()
+                });
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert("wrap", |language, build_ctx, self_property, function| {
+        let [width_node] = function.expect_exact_arguments()?;
+        let width_property = expect_usize_expression(language, build_ctx, width_node)?;
+        let out_property = (self_property, width_property).map(|(s, width)| {
+            text_util::wrap_bytes(s.as_bytes(), width)
+                .iter()
+                .map(|line| String::from_utf8_lossy(line))
+                .join("\n")
+        });
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "pad_start",
+        |language, build_ctx, self_property, function| {
+            let ([width_node], [fill_node]) = function.expect_arguments()?;
+            let width_property = expect_usize_expression(language, build_ctx, width_node)?;
+            let fill_property = expect_fill_char_expression(language, build_ctx, fill_node)?;
+            let out_property = (self_property, width_property, fill_property)
+                .map(|(s, width, fill_char)| text_util::pad_start(&s, fill_char, width));
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert("pad_end", |language, build_ctx, self_property, function| {
+        let ([width_node], [fill_node]) = function.expect_arguments()?;
+        let width_property = expect_usize_expression(language, build_ctx, width_node)?;
+        let fill_property = expect_fill_char_expression(language, build_ctx, fill_node)?;
+        let out_property = (self_property, width_property, fill_property)
+            .map(|(s, width, fill_char)| text_util::pad_end(&s, fill_char, width));
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert(
+        "trailers",
+        |_language, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|s| trailer::parse_description_trailers(&s));
+            Ok(L::wrap_trailer_list(out_property))
+        },
+    );
     map
 }
 
+/// Parses the optional fill character argument of the padding methods.
+fn expect_fill_char_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
+    language: &L,
+    build_ctx: &BuildContext<L::Property>,
+    node: Option<&ExpressionNode>,
+) -> TemplateParseResult<Box<dyn TemplateProperty<Output = char> + 'a>> {
+    let Some(node) = node else {
+        return Ok(Box::new(Literal(' ')));
+    };
+    let fill_property = expect_plain_text_expression(language, build_ctx, node)?;
+    let char_property = fill_property.and_then(|fill| {
+        fill.chars()
+            .exactly_one()
+            .map_err(|_| TemplatePropertyError("Fill must be a single character".into()))
+    });
+    Ok(Box::new(char_property))
+}
+
 /// Clamps and aligns the given index `i` to char boundary.
 ///
 /// Negative index counts from the end. If the index isn't at a char boundary,
@@ -788,25 +957,33 @@ fn builtin_timestamp_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
             .and_then(move |timestamp| Ok(time_util::format_duration(&timestamp, &now, &format)?));
         Ok(L::wrap_string(out_property))
     });
-    map.insert(
-        "format",
-        |_language, _build_ctx, self_property, function| {
-            // No dynamic string is allowed as the templater has no runtime error type.
-            let [format_node] = function.expect_exact_arguments()?;
-            let format =
-                template_parser::expect_string_literal_with(format_node, |format, span| {
-                    time_util::FormattingItems::parse(format)
-                        .ok_or_else(|| TemplateParseError::expression("Invalid time format", span))
-                })?
-                .into_owned();
+    map.insert("format", |language, build_ctx, self_property, function| {
+        let [format_node] = function.expect_exact_arguments()?;
+        // Literal format string can be validated at parse time.
+        if let Some(format) =
+            template_parser::try_string_literal_with(format_node, |format, span| {
+                time_util::FormattingItems::parse(format)
+                    .ok_or_else(|| TemplateParseError::expression("Invalid time format", span))
+            })
+        {
+            let format = format?.into_owned();
             let out_property = self_property.and_then(move |timestamp| {
                 Ok(time_util::format_absolute_timestamp_with(
                     &timestamp, &format,
                 )?)
             });
-            Ok(L::wrap_string(out_property))
-        },
-    );
+            return Ok(L::wrap_string(out_property));
+        }
+        let format_property = expect_string_expression(language, build_ctx, format_node)?;
+        let out_property = (self_property, format_property).and_then(|(timestamp, format)| {
+            let format = time_util::FormattingItems::parse(&format)
+                .ok_or_else(|| TemplatePropertyError("Invalid time format".into()))?;
+            Ok(time_util::format_absolute_timestamp_with(
+                &timestamp, &format,
+            )?)
+        });
+        Ok(L::wrap_string(out_property))
+    });
     map.insert("utc", |_language, _build_ctx, self_property, function| {
         function.expect_no_arguments()?;
         let out_property = self_property.map(|mut timestamp| {
@@ -856,6 +1033,45 @@ fn builtin_timestamp_range_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
     map
 }
 
+fn builtin_trailer_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
+) -> TemplateBuildMethodFnMap<'a, L, Trailer> {
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = TemplateBuildMethodFnMap::<L, Trailer>::new();
+    map.insert("key", |_language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let out_property = self_property.map(|trailer| trailer.key);
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert("value", |_language, _build_ctx, self_property, function| {
+        function.expect_no_arguments()?;
+        let out_property = self_property.map(|trailer| trailer.value);
+        Ok(L::wrap_string(out_property))
+    });
+    map
+}
+
+fn builtin_trailer_list_methods<'a, L: TemplateLanguage<'a> + ?Sized>(
+) -> TemplateBuildMethodFnMap<'a, L, Vec<Trailer>> {
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = TemplateBuildMethodFnMap::<L, Vec<Trailer>>::new();
+    map.insert("get", |language, build_ctx, self_property, function| {
+        let [key_node] = function.expect_exact_arguments()?;
+        let key_property = expect_plain_text_expression(language, build_ctx, key_node)?;
+        let out_property = (self_property, key_property).map(|(trailers, key)| {
+            // Trailer keys are case-insensitive as in Git.
+            trailers
+                .into_iter()
+                .find(|trailer| trailer.key.eq_ignore_ascii_case(&key))
+                .map(|trailer| trailer.value)
+                .unwrap_or_default()
+        });
+        Ok(L::wrap_string(out_property))
+    });
+    map
+}
+
 fn build_list_template_method<'a, L: TemplateLanguage<'a> + ?Sized>(
     language: &L,
     build_ctx: &BuildContext<L::Property>,
@@ -1017,6 +1233,31 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
             content, labels,
         ))))
     });
+    map.insert("hyperlink", |language, build_ctx, function| {
+        let [url_node, content_node] = function.expect_exact_arguments()?;
+        let url_property = expect_plain_text_expression(language, build_ctx, url_node)?;
+        let content = expect_template_expression(language, build_ctx, content_node)?;
+        Ok(L::wrap_template(Box::new(HyperlinkTemplate::new(
+            content,
+            url_property,
+        ))))
+    });
+    map.insert("label_if", |language, build_ctx, function| {
+        let [condition_node, label_node, content_node] = function.expect_exact_arguments()?;
+        let condition = expect_boolean_expression(language, build_ctx, condition_node)?;
+        let label_property = expect_plain_text_expression(language, build_ctx, label_node)?;
+        let content = expect_template_expression(language, build_ctx, content_node)?;
+        let labels = (condition, label_property).map(|(condition, s)| {
+            if condition {
+                s.split_whitespace().map(ToString::to_string).collect()
+            } else {
+                vec![]
+            }
+        });
+        Ok(L::wrap_template(Box::new(LabelTemplate::new(
+            content, labels,
+        ))))
+    });
     map.insert("if", |language, build_ctx, function| {
         let ([condition_node, true_node], [false_node]) = function.expect_arguments()?;
         let condition = expect_boolean_expression(language, build_ctx, condition_node)?;
@@ -1070,6 +1311,31 @@ fn builtin_functions<'a, L: TemplateLanguage<'a> + ?Sized>() -> TemplateBuildFun
         });
         Ok(L::wrap_template(Box::new(template)))
     });
+    map.insert("json", |language, build_ctx, function| {
+        let value_property = if function.args.is_empty() && !function.keyword_args.is_empty() {
+            // json(name=value, ..) constructs an object.
+            let fields = function
+                .keyword_args
+                .iter()
+                .map(|arg| {
+                    let property = expect_json_expression(language, build_ctx, &arg.value)?;
+                    Ok((arg.name.to_owned(), property))
+                })
+                .try_collect::<_, _, TemplateParseError>()?;
+            Box::new(JsonObjectProperty::new(fields))
+        } else {
+            let [value_node] = function.expect_exact_arguments()?;
+            expect_json_expression(language, build_ctx, value_node)?
+        };
+        let out_property = value_property.and_then(|value| Ok(serde_json::to_string(&value)?));
+        Ok(L::wrap_string(out_property))
+    });
+    map.insert("env", |language, build_ctx, function| {
+        let [name_node] = function.expect_exact_arguments()?;
+        let name_property = expect_plain_text_expression(language, build_ctx, name_node)?;
+        let out_property = name_property.map(|name| env::var(name).unwrap_or_default());
+        Ok(L::wrap_string(out_property))
+    });
     map
 }
 
@@ -1232,6 +1498,36 @@ pub fn expect_plain_text_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
     })
 }
 
+/// Builds a string expression. Unlike `expect_plain_text_expression()`, values
+/// of other types, such as integers, aren't converted to strings.
+pub fn expect_string_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
+    language: &L,
+    build_ctx: &BuildContext<L::Property>,
+    node: &ExpressionNode,
+) -> TemplateParseResult<Box<dyn TemplateProperty<Output = String> + 'a>> {
+    // Concatenated strings are templates.
+    expect_expression_of_type(
+        language,
+        build_ctx,
+        node,
+        "String",
+        |expression| match expression.type_name() {
+            "String" | "Template" => expression.try_into_plain_text(),
+            _ => None,
+        },
+    )
+}
+
+pub fn expect_json_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
+    language: &L,
+    build_ctx: &BuildContext<L::Property>,
+    node: &ExpressionNode,
+) -> TemplateParseResult<Box<dyn TemplateProperty<Output = serde_json::Value> + 'a>> {
+    expect_expression_of_type(language, build_ctx, node, "Serializable", |expression| {
+        expression.try_into_json()
+    })
+}
+
 pub fn expect_template_expression<'a, L: TemplateLanguage<'a> + ?Sized>(
     language: &L,
     build_ctx: &BuildContext<L::Property>,
@@ -1845,6 +2141,85 @@ mod tests {
         insta::assert_snapshot!(env.render_ok(r#""abcdef".substr(-2, -4)"#), @"");
     }
 
+    #[test]
+    fn test_string_width_method() {
+        let env = TestTemplateEnv::new();
+
+        insta::assert_snapshot!(env.render_ok(r#""abcdef".truncate(6)"#), @"abcdef");
+        insta::assert_snapshot!(env.render_ok(r#""abcdef".truncate(4)"#), @"abcd");
+        insta::assert_snapshot!(env.render_ok(r#""abcdef".truncate(4, "…")"#), @"abc…");
+        insta::assert_snapshot!(env.render_ok(r#""一二三".truncate(5, "…")"#), @"一二…");
+        insta::assert_snapshot!(env.render_ok(r#""💩💩💩".truncate(4)"#), @"💩💩");
+
+        insta::assert_snapshot!(env.render_ok(r#""foo bar baz".wrap(7)"#), @r###"
+        foo bar
+        baz
+        "###);
+        insta::assert_snapshot!(env.render_ok(r#""一二 三四 五".wrap(5)"#), @r###"
+        一二
+        三四
+        五
+        "###);
+
+        insta::assert_snapshot!(env.render_ok(r#""ab".pad_start(4) ++ "|""#), @"  ab|");
+        insta::assert_snapshot!(env.render_ok(r#""ab".pad_end(4) ++ "|""#), @"ab  |");
+        insta::assert_snapshot!(env.render_ok(r#""一".pad_end(4, ".") ++ "|""#), @"一..|");
+        insta::assert_snapshot!(env.render_ok(r#""abcde".pad_start(4)"#), @"abcde");
+        insta::assert_snapshot!(
+            env.render_ok(r#""ab".pad_start(4, "--")"#),
+            @"<Error: Fill must be a single character>");
+
+        insta::assert_snapshot!(env.parse_err(r#""ab".truncate()"#), @r###"
+         --> 1:15
+          |
+        1 | "ab".truncate()
+          |               ^
+          |
+          = Function "truncate": Expected 1 to 2 arguments
+        "###);
+        insta::assert_snapshot!(
+            env.render_ok(r#""ab".truncate(-1)"#),
+            @"<Error: out of range integral type conversion attempted>");
+    }
+
+    #[test]
+    fn test_trailer_method() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("description", || {
+            L::wrap_string(Literal(
+                "subject\n\nbody\n\nSigned-off-by: Alice <alice@example.com>\nChange-Id: I1234\n"
+                    .to_owned(),
+            ))
+        });
+
+        insta::assert_snapshot!(env.render_ok(r#""subject".trailers()"#), @"");
+        insta::assert_snapshot!(env.render_ok(r#"description.trailers().len()"#), @"2");
+        insta::assert_snapshot!(env.render_ok(r#"description.trailers()"#), @r###"
+        Signed-off-by: Alice <alice@example.com>
+        Change-Id: I1234
+        "###);
+        insta::assert_snapshot!(
+            env.render_ok(r#"description.trailers().map(|t| t.key() ++ "=" ++ t.value())"#),
+            @"Signed-off-by=Alice <alice@example.com> Change-Id=I1234");
+        insta::assert_snapshot!(env.render_ok(r#"description.trailers().get("change-id")"#), @"I1234");
+        insta::assert_snapshot!(env.render_ok(r#"description.trailers().get("Fixes")"#), @"");
+        insta::assert_snapshot!(
+            env.render_ok(r#"if(description.trailers().get("Fixes"), "fixes", "no fixes")"#),
+            @"no fixes");
+        insta::assert_snapshot!(
+            env.render_ok(r#"json(description.trailers())"#),
+            @r#"[{"key":"Signed-off-by","value":"Alice <alice@example.com>"},{"key":"Change-Id","value":"I1234"}]"#);
+
+        insta::assert_snapshot!(env.parse_err(r#"description.trailers().get()"#), @r###"
+         --> 1:28
+          |
+        1 | description.trailers().get()
+          |                            ^
+          |
+          = Function "get": Expected 1 arguments
+        "###);
+    }
+
     #[test]
     fn test_signature() {
         let mut env = TestTemplateEnv::new();
@@ -1977,18 +2352,15 @@ mod tests {
         1 | t0.format(0)
           |           ^
           |
-          = Expected string literal
+          = Expected expression of type "String", but actual type is "Integer"
         "###);
 
-        // Dynamic string isn't supported yet
-        insta::assert_snapshot!(env.parse_err(r#"t0.format("%Y" ++ "%m")"#), @r###"
-         --> 1:11
-          |
-        1 | t0.format("%Y" ++ "%m")
-          |           ^----------^
-          |
-          = Expected string literal
-        "###);
+        // Dynamic format string
+        insta::assert_snapshot!(env.render_ok(r#"t0.format("%Y" ++ "%m")"#), @"197001");
+        insta::assert_snapshot!(
+            env.render_ok(r#"t0.format(if(true, "%H:%M", "%_"))"#), @"00:00");
+        insta::assert_snapshot!(
+            env.render_ok(r#"t0.format("%" ++ "_")"#), @"<Error: Invalid time format>");
 
         // Literal alias expansion
         env.add_alias("time_format", r#""%Y-%m-%d""#);
@@ -2170,6 +2542,62 @@ mod tests {
             @"[38;5;1mtext[39m");
     }
 
+    #[test]
+    fn test_hyperlink_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_color("error", crossterm::style::Color::DarkRed);
+
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("https://example.com/", "text")"#),
+            @r#"]8;;https://example.com/\text]8;;\"#);
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("https://example.com/" ++ 1, label("error", "text"))"#),
+            @r#"]8;;https://example.com/1\[38;5;1mtext[39m]8;;\"#);
+
+        // Nested
+        insta::assert_snapshot!(
+            env.render_ok(r#"hyperlink("a", "x" ++ hyperlink("b", "y") ++ "z")"#),
+            @r#"]8;;a\x]8;;b\y]8;;a\z]8;;\"#);
+
+        // Hyperlink can be captured by other templates
+        insta::assert_snapshot!(
+            env.render_ok(r#"coalesce("", hyperlink("a", "x"))"#),
+            @r#"]8;;a\x]8;;\"#);
+    }
+
+    #[test]
+    fn test_label_if_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("empty", || L::wrap_boolean(Literal(true)));
+        env.add_keyword("hidden", || L::wrap_boolean(Literal(false)));
+        env.add_color("error", crossterm::style::Color::DarkRed);
+        env.add_color("warning", crossterm::style::Color::DarkYellow);
+
+        insta::assert_snapshot!(
+            env.render_ok(r#"label_if(empty, "error", "text")"#),
+            @"[38;5;1mtext[39m");
+        insta::assert_snapshot!(env.render_ok(r#"label_if(hidden, "error", "text")"#), @"text");
+
+        // Nested
+        insta::assert_snapshot!(
+            env.render_ok(r#"label_if(hidden, "error", label_if(empty, "warning", "text"))"#),
+            @"[38;5;3mtext[39m");
+
+        // Computed label
+        insta::assert_snapshot!(
+            env.render_ok(r#"label_if(empty, "err" ++ "or", "text")"#),
+            @"[38;5;1mtext[39m");
+
+        insta::assert_snapshot!(env.parse_err(r#"label_if(empty, "error")"#), @r###"
+         --> 1:10
+          |
+        1 | label_if(empty, "error")
+          |          ^------------^
+          |
+          = Function "label_if": Expected 3 arguments
+        "###);
+    }
+
     #[test]
     fn test_coalesce_function() {
         let mut env = TestTemplateEnv::new();
@@ -2303,4 +2731,62 @@ mod tests {
             env.render_ok(r#"surround(lt, gt, if(empty_content, "not empty", ""))"#),
             @"");
     }
+
+    #[test]
+    fn test_json_function() {
+        let mut env = TestTemplateEnv::new();
+        env.add_keyword("author", || {
+            L::wrap_signature(Literal(new_signature(
+                "Test \"User\"",
+                "test.user@example.com",
+            )))
+        });
+        env.add_keyword("none_i64", || L::wrap_integer_opt(Literal(None)));
+        env.add_keyword("words", || {
+            L::wrap_string_list(Literal(vec!["foo".to_owned(), "bar\n".to_owned()]))
+        });
+        env.add_keyword("time_range", || {
+            L::wrap_timestamp_range(Literal(TimestampRange {
+                start: new_timestamp(0, 60),
+                end: new_timestamp(1001, -90),
+            }))
+        });
+
+        insta::assert_snapshot!(env.render_ok(r#"json("a\"b\\c")"#), @r#""a\"b\\c""#);
+        insta::assert_snapshot!(env.render_ok(r#"json(42)"#), @"42");
+        insta::assert_snapshot!(env.render_ok(r#"json(true)"#), @"true");
+        insta::assert_snapshot!(env.render_ok(r#"json(none_i64)"#), @"null");
+        insta::assert_snapshot!(env.render_ok(r#"json(words)"#), @r#"["foo","bar\n"]"#);
+        insta::assert_snapshot!(env.render_ok(r#"json(author)"#), @r#"{"email":"test.user@example.com","name":"Test \"User\"","timestamp":"1970-01-01T00:00:00.000+00:00"}"#);
+        insta::assert_snapshot!(env.render_ok(r#"json(time_range)"#), @r#"{"end":"1969-12-31T22:30:01.001-01:30","start":"1970-01-01T01:00:00.000+01:00"}"#);
+        insta::assert_snapshot!(env.render_ok(r#"json("a" ++ 1)"#), @r#""a1""#);
+        insta::assert_snapshot!(
+            env.render_ok(r#"json(name=author.name(), count=1, ok=true)"#),
+            @r#"{"count":1,"name":"Test \"User\"","ok":true}"#);
+
+        insta::assert_snapshot!(env.parse_err(r#"json()"#), @r###"
+         --> 1:6
+          |
+        1 | json()
+          |      ^
+          |
+          = Function "json": Expected 1 arguments
+        "###);
+        insta::assert_snapshot!(env.parse_err(r#"json(1, x=2)"#), @r###"
+         --> 1:9
+          |
+        1 | json(1, x=2)
+          |         ^-^
+          |
+          = Function "json": Unexpected keyword arguments
+        "###);
+        insta::assert_snapshot!(env.parse_err(r#"json(|x| x)"#), @r###"
+         --> 1:6
+          |
+        1 | json(|x| x)
+          |      ^---^
+          |
+          = Lambda cannot be defined here
+        "###);
+    }
 }
@@ -29,6 +29,7 @@ use jj_lib::dsl_util::AliasesMap;
 use jj_lib::dsl_util::ExpressionFolder;
 use jj_lib::dsl_util::FoldableExpression;
 use jj_lib::dsl_util::InvalidArguments;
+use jj_lib::dsl_util::KeywordArgument;
 use jj_lib::dsl_util::StringLiteralParser;
 use once_cell::sync::Lazy;
 use pest::iterators::Pair;
@@ -70,6 +71,8 @@ impl Rule {
             Rule::prefix_ops => None,
             Rule::infix_ops => None,
             Rule::function => None,
+            Rule::keyword_argument => None,
+            Rule::argument => None,
             Rule::function_arguments => None,
             Rule::lambda => None,
             Rule::formal_parameters => None,
@@ -422,15 +425,41 @@ fn parse_function_call_node(pair: Pair<Rule>) -> TemplateParseResult<FunctionCal
     let args_span = args_pair.as_span();
     assert_eq!(args_pair.as_rule(), Rule::function_arguments);
     let name = parse_identifier_name(name_pair)?;
-    let args = args_pair
-        .into_inner()
-        .map(parse_template_node)
-        .try_collect()?;
+    let mut args = Vec::new();
+    let mut keyword_args = Vec::new();
+    for pair in args_pair.into_inner() {
+        let span = pair.as_span();
+        match pair.as_rule() {
+            Rule::template => {
+                if !keyword_args.is_empty() {
+                    return Err(InvalidArguments {
+                        name,
+                        message: "Positional argument follows keyword argument".to_owned(),
+                        span,
+                    }
+                    .into());
+                }
+                args.push(parse_template_node(pair)?);
+            }
+            Rule::keyword_argument => {
+                let mut pairs = pair.into_inner();
+                let name_pair = pairs.next().unwrap();
+                let value_pair = pairs.next().unwrap();
+                let arg = KeywordArgument {
+                    name: parse_identifier_name(name_pair.clone())?,
+                    name_span: name_pair.as_span(),
+                    value: parse_template_node(value_pair)?,
+                };
+                keyword_args.push(arg);
+            }
+            r => panic!("unexpected argument rule {r:?}"),
+        }
+    }
     Ok(FunctionCallNode {
         name,
         name_span,
         args,
-        keyword_args: vec![], // unsupported
+        keyword_args,
         args_span,
     })
 }
@@ -640,6 +669,28 @@ pub fn expect_string_literal_with<'a, 'i, T>(
     }
 }
 
+/// Applies the given function if the `node` is a string literal. Returns
+/// `None` if the `node` is of another kind.
+pub fn try_string_literal_with<'a, 'i, T>(
+    node: &'a ExpressionNode<'i>,
+    f: impl FnOnce(&'a str, pest::Span<'i>) -> TemplateParseResult<T>,
+) -> Option<TemplateParseResult<T>> {
+    match &node.kind {
+        ExpressionKind::String(s) => Some(f(s, node.span)),
+        ExpressionKind::Identifier(_)
+        | ExpressionKind::Boolean(_)
+        | ExpressionKind::Integer(_)
+        | ExpressionKind::Unary(..)
+        | ExpressionKind::Binary(..)
+        | ExpressionKind::Concat(_)
+        | ExpressionKind::FunctionCall(_)
+        | ExpressionKind::MethodCall(_)
+        | ExpressionKind::Lambda(_) => None,
+        ExpressionKind::AliasExpanded(id, subst) => try_string_literal_with(subst, f)
+            .map(|result| result.map_err(|e| e.within_alias_expansion(*id, node.span))),
+    }
+}
+
 /// Applies the given function if the `node` is a lambda.
 pub fn expect_lambda_with<'a, 'i, T>(
     node: &'a ExpressionNode<'i>,
@@ -896,6 +947,18 @@ mod tests {
         assert!(parse_template("false()").is_err());
         // Function arguments can be any expression
         assert!(parse_template("f(false)").is_ok());
+
+        // Keyword arguments
+        assert!(parse_template("f(x=a)").is_ok());
+        assert!(parse_template("f(a, x = b ++ c,)").is_ok());
+        assert!(parse_template("f(=a)").is_err());
+        assert_eq!(
+            parse_template("f(x=a, b)").unwrap_err().kind,
+            TemplateParseErrorKind::InvalidArguments {
+                name: "f".to_owned(),
+                message: "Positional argument follows keyword argument".to_owned(),
+            },
+        );
     }
 
     #[test]
@@ -1234,6 +1297,36 @@ mod tests {
             parse_normalized("|x| (a ++ b)"),
         );
 
+        // Keyword arguments are matched by parameter name.
+        assert_eq!(
+            with_aliases([("F(x, y)", "x ++ y")]).parse_normalized("F(y=b, x=a)"),
+            parse_normalized("a ++ b"),
+        );
+        assert_eq!(
+            with_aliases([("F(x, y)", "x ++ y")]).parse_normalized("F(a, y=b)"),
+            parse_normalized("a ++ b"),
+        );
+        assert_eq!(
+            with_aliases([("F(x)", "x")])
+                .parse("F(y=a)")
+                .unwrap_err()
+                .kind,
+            TemplateParseErrorKind::InvalidArguments {
+                name: "F".to_owned(),
+                message: r#"Unexpected keyword argument "y""#.to_owned(),
+            },
+        );
+        assert_eq!(
+            with_aliases([("F(x, y)", "x ++ y")])
+                .parse("F(a, x=b)")
+                .unwrap_err()
+                .kind,
+            TemplateParseErrorKind::InvalidArguments {
+                name: "F".to_owned(),
+                message: r#"Got multiple values for keyword "x""#.to_owned(),
+            },
+        );
+
         // Invalid number of arguments.
         assert_matches!(
             with_aliases([("F()", "x")]).parse("F(a)").unwrap_err().kind,
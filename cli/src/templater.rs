@@ -21,6 +21,8 @@ use std::rc::Rc;
 
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
+use jj_lib::trailer::Trailer;
+use tracing::instrument;
 
 use crate::formatter::FormatRecorder;
 use crate::formatter::Formatter;
@@ -98,6 +100,12 @@ impl Template for &str {
     }
 }
 
+impl Template for Trailer {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        write!(formatter, "{}: {}", self.key, self.value)
+    }
+}
+
 impl Template for Timestamp {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         match time_util::format_absolute_timestamp(self) {
@@ -137,12 +145,74 @@ impl Template for TimestampRange {
     }
 }
 
+/// Value that can be serialized by the `json()` template function.
+pub trait JsonValue {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError>;
+}
+
+impl<T: JsonValue> JsonValue for Option<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        self.as_ref()
+            .map_or(Ok(serde_json::Value::Null), |value| value.to_json())
+    }
+}
+
+impl<T: JsonValue> JsonValue for Vec<T> {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        self.iter().map(|value| value.to_json()).collect()
+    }
+}
+
+impl JsonValue for Signature {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "name": self.name,
+            "email": self.email,
+            "timestamp": self.timestamp.to_json()?,
+        }))
+    }
+}
+
+impl JsonValue for SizeHint {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        let (lower, upper) = *self;
+        Ok(serde_json::json!({ "lower": lower, "upper": upper }))
+    }
+}
+
+impl JsonValue for Timestamp {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(time_util::format_rfc3339_timestamp(self)?.into())
+    }
+}
+
+impl JsonValue for TimestampRange {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({
+            "start": self.start.to_json()?,
+            "end": self.end.to_json()?,
+        }))
+    }
+}
+
+impl JsonValue for Trailer {
+    fn to_json(&self) -> Result<serde_json::Value, TemplatePropertyError> {
+        Ok(serde_json::json!({ "key": self.key, "value": self.value }))
+    }
+}
+
 impl Template for Vec<String> {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         format_joined(formatter, self, " ")
     }
 }
 
+impl Template for Vec<Trailer> {
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        format_joined(formatter, self, "\n")
+    }
+}
+
 impl Template for bool {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         let repr = if *self { "true" } else { "false" };
@@ -184,6 +254,38 @@ where
     }
 }
 
+/// Renders the `content` as a hyperlink to the `url`.
+pub struct HyperlinkTemplate<T, P> {
+    content: T,
+    url: P,
+}
+
+impl<T, P> HyperlinkTemplate<T, P> {
+    pub fn new(content: T, url: P) -> Self
+    where
+        T: Template,
+        P: TemplateProperty<Output = String>,
+    {
+        HyperlinkTemplate { content, url }
+    }
+}
+
+impl<T, P> Template for HyperlinkTemplate<T, P>
+where
+    T: Template,
+    P: TemplateProperty<Output = String>,
+{
+    fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
+        let url = match self.url.extract() {
+            Ok(url) => url,
+            Err(err) => return formatter.handle_error(err),
+        };
+        formatter.push_hyperlink(&url)?;
+        self.content.format(formatter)?;
+        formatter.pop_hyperlink()
+    }
+}
+
 /// Renders contents in order, and returns the first non-empty output.
 pub struct CoalesceTemplate<T>(pub Vec<T>);
 
@@ -452,6 +554,30 @@ impl<T: Template> TemplateProperty for PlainTextFormattedProperty<T> {
     }
 }
 
+/// Adapter to build JSON object from named properties.
+pub struct JsonObjectProperty<P> {
+    fields: Vec<(String, P)>,
+}
+
+impl<P> JsonObjectProperty<P> {
+    pub fn new(fields: Vec<(String, P)>) -> Self {
+        JsonObjectProperty { fields }
+    }
+}
+
+impl<P: TemplateProperty<Output = serde_json::Value>> TemplateProperty for JsonObjectProperty<P> {
+    type Output = serde_json::Value;
+
+    fn extract(&self) -> Result<Self::Output, TemplatePropertyError> {
+        let map = self
+            .fields
+            .iter()
+            .map(|(name, property)| Ok((name.clone(), property.extract()?)))
+            .collect::<Result<_, TemplatePropertyError>>()?;
+        Ok(serde_json::Value::Object(map))
+    }
+}
+
 /// Renders template property of list type with the given separator.
 ///
 /// Each list item will be formatted by the given `format_item()` function.
@@ -665,6 +791,7 @@ impl<'a, C: Clone> TemplateRenderer<'a, C> {
         self
     }
 
+    #[instrument(skip_all)]
     pub fn format(&self, context: &C, formatter: &mut dyn Formatter) -> io::Result<()> {
         let mut wrapper = TemplateFormatter::new(formatter, format_property_error_inline);
         self.placeholder.with_value(context.clone(), || {
@@ -712,6 +839,14 @@ impl<'a> TemplateFormatter<'a> {
         self.formatter.pop_label()
     }
 
+    pub fn push_hyperlink(&mut self, url: &str) -> io::Result<()> {
+        self.formatter.push_hyperlink(url)
+    }
+
+    pub fn pop_hyperlink(&mut self) -> io::Result<()> {
+        self.formatter.pop_hyperlink()
+    }
+
     pub fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> io::Result<()> {
         self.formatter.write_fmt(args)
     }
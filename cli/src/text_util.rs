@@ -15,6 +15,7 @@
 use std::borrow::Cow;
 use std::cmp;
 use std::io;
+use std::iter;
 
 use unicode_width::UnicodeWidthChar as _;
 
@@ -66,6 +67,31 @@ pub fn elide_start<'a>(
     (Cow::Owned([ellipsis, text].concat()), concat_width)
 }
 
+/// Shortens `text` to `max_width` by removing trailing characters. `ellipsis`
+/// is added if the `text` gets truncated.
+///
+/// The returned string (including `ellipsis`) never exceeds the `max_width`.
+pub fn elide_end<'a>(text: &'a str, ellipsis: &'a str, max_width: usize) -> (Cow<'a, str>, usize) {
+    let (text_end, text_width) = truncate_end_pos(text, max_width);
+    if text_end == text.len() {
+        return (Cow::Borrowed(text), text_width);
+    }
+
+    let (ellipsis_end, ellipsis_width) = truncate_end_pos(ellipsis, max_width);
+    if ellipsis_end != ellipsis.len() {
+        return (Cow::Borrowed(&ellipsis[..ellipsis_end]), ellipsis_width);
+    }
+
+    let max_text_width = max_width - ellipsis_width;
+    let (text_end, text_width) = truncate_end_pos(text, max_text_width);
+    let concat_width = text_width + ellipsis_width;
+    assert!(concat_width <= max_width);
+    (
+        Cow::Owned([&text[..text_end], ellipsis].concat()),
+        concat_width,
+    )
+}
+
 /// Shortens `text` to `max_width` by removing leading characters, returning
 /// `(start_index, width)`.
 ///
@@ -83,6 +109,20 @@ fn truncate_start_pos(text: &str, max_width: usize) -> (usize, usize) {
     (0, acc_width)
 }
 
+/// Shortens `text` to `max_width` by removing trailing characters, returning
+/// `(end_index, width)`.
+fn truncate_end_pos(text: &str, max_width: usize) -> (usize, usize) {
+    let mut acc_width = 0;
+    for (i, c) in text.char_indices() {
+        let new_width = acc_width + c.width().unwrap_or(0);
+        if new_width > max_width {
+            return (i, acc_width);
+        }
+        acc_width = new_width;
+    }
+    (text.len(), acc_width)
+}
+
 /// Skips `width` leading characters, returning `(start_index, skipped_width)`.
 ///
 /// The `skipped_width` may exceed the given `width` if `width` is not at
@@ -105,6 +145,28 @@ fn trim_start_zero_width_chars(text: &str) -> &str {
     text.trim_start_matches(|c: char| c.width().unwrap_or(0) == 0)
 }
 
+/// Pads `text` with `fill_char`s at start so the display width becomes at
+/// least `min_width`.
+pub fn pad_start(text: &str, fill_char: char, min_width: usize) -> String {
+    let padding = padding_for(text, fill_char, min_width);
+    [padding.as_str(), text].concat()
+}
+
+/// Pads `text` with `fill_char`s at end so the display width becomes at least
+/// `min_width`.
+pub fn pad_end(text: &str, fill_char: char, min_width: usize) -> String {
+    let padding = padding_for(text, fill_char, min_width);
+    [text, padding.as_str()].concat()
+}
+
+/// Builds padding of `fill_char`s that fits in the remainder of `min_width`.
+fn padding_for(text: &str, fill_char: char, min_width: usize) -> String {
+    let text_width = textwrap::core::display_width(text);
+    let fill_width = fill_char.width().unwrap_or(0).max(1);
+    let count = min_width.saturating_sub(text_width) / fill_width;
+    iter::repeat(fill_char).take(count).collect()
+}
+
 /// Indents each line by the given prefix preserving labels.
 pub fn write_indented(
     formatter: &mut dyn Formatter,
@@ -364,6 +426,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_elide_end() {
+        // Empty string
+        assert_eq!(elide_end("", "", 1), ("".into(), 0));
+
+        // Basic truncation
+        assert_eq!(elide_end("abcdef", "", 6), ("abcdef".into(), 6));
+        assert_eq!(elide_end("abcdef", "", 5), ("abcde".into(), 5));
+        assert_eq!(elide_end("abcdef", "", 1), ("a".into(), 1));
+        assert_eq!(elide_end("abcdef", "", 0), ("".into(), 0));
+        assert_eq!(elide_end("abcdef", "-=~", 6), ("abcdef".into(), 6));
+        assert_eq!(elide_end("abcdef", "-=~", 5), ("ab-=~".into(), 5));
+        assert_eq!(elide_end("abcdef", "-=~", 4), ("a-=~".into(), 4));
+        assert_eq!(elide_end("abcdef", "-=~", 3), ("-=~".into(), 3));
+        assert_eq!(elide_end("abcdef", "-=~", 2), ("-=".into(), 2));
+        assert_eq!(elide_end("abcdef", "-=~", 1), ("-".into(), 1));
+        assert_eq!(elide_end("abcdef", "-=~", 0), ("".into(), 0));
+
+        // East Asian characters (char.width() == 2)
+        assert_eq!(elide_end("一二三", "", 6), ("一二三".into(), 6));
+        assert_eq!(elide_end("一二三", "", 5), ("一二".into(), 4));
+        assert_eq!(elide_end("一二三", "", 1), ("".into(), 0));
+        assert_eq!(elide_end("一二三", "-=~", 5), ("一-=~".into(), 5));
+        assert_eq!(elide_end("一二三", "-=~", 4), ("-=~".into(), 3));
+        assert_eq!(elide_end("一二三", "略", 5), ("一略".into(), 4));
+        assert_eq!(elide_end("一二三", "略", 2), ("略".into(), 2));
+        assert_eq!(elide_end("一二三", "略", 1), ("".into(), 0));
+
+        // Decomposed character at boundary
+        assert_eq!(
+            elide_end("a\u{300}bcde\u{300}", "", 5),
+            ("a\u{300}bcde\u{300}".into(), 5)
+        );
+        assert_eq!(
+            elide_end("a\u{300}bcde\u{300}", "", 4),
+            ("a\u{300}bcd".into(), 4)
+        );
+        assert_eq!(
+            elide_end("a\u{300}bcde\u{300}", "", 1),
+            ("a\u{300}".into(), 1)
+        );
+    }
+
+    #[test]
+    fn test_pad() {
+        assert_eq!(pad_start("ab", ' ', 4), "  ab");
+        assert_eq!(pad_end("ab", '.', 4), "ab..");
+        assert_eq!(pad_start("abcde", ' ', 4), "abcde");
+        assert_eq!(pad_end("", ' ', 0), "");
+
+        // East Asian characters (char.width() == 2)
+        assert_eq!(pad_start("一", ' ', 4), "  一");
+        assert_eq!(pad_end("一二", ' ', 5), "一二 ");
+        assert_eq!(pad_start("a", '一', 4), "一a");
+    }
+
     #[test]
     fn test_split_byte_line_to_words() {
         assert_eq!(split_byte_line_to_words(b""), vec![]);
@@ -76,6 +76,18 @@ pub fn format_absolute_timestamp(timestamp: &Timestamp) -> Result<String, Timest
     format_absolute_timestamp_with(timestamp, &DEFAULT_FORMAT)
 }
 
+/// Formats timestamp in RFC 3339 format with millisecond precision.
+pub fn format_rfc3339_timestamp(timestamp: &Timestamp) -> Result<String, TimestampOutOfRange> {
+    static RFC3339_FORMAT: Lazy<FormattingItems> =
+        Lazy::new(|| FormattingItems::parse("%Y-%m-%dT%H:%M:%S%.3f%:z").unwrap());
+    format_absolute_timestamp_with(timestamp, &RFC3339_FORMAT)
+}
+
+/// Formats timestamp in RFC 2822 format, as used in email headers.
+pub fn format_rfc2822_timestamp(timestamp: &Timestamp) -> Result<String, TimestampOutOfRange> {
+    Ok(datetime_from_timestamp(timestamp)?.to_rfc2822())
+}
+
 pub fn format_absolute_timestamp_with(
     timestamp: &Timestamp,
     format: &FormattingItems,
@@ -424,6 +424,10 @@ impl Ui {
         self.formatter_factory.new_formatter(output)
     }
 
+    pub fn formatter_factory(&self) -> &FormatterFactory {
+        &self.formatter_factory
+    }
+
     /// Locked stdout stream.
     pub fn stdout(&self) -> UiStdout<'_> {
         match &self.output {
@@ -466,10 +470,12 @@ impl Ui {
     }
 
     /// Whether continuous feedback should be displayed for long-running
-    /// operations
+    /// operations. Disabled by `--quiet`.
     pub fn use_progress_indicator(&self) -> bool {
         match &self.output {
-            UiOutput::Terminal { stderr, .. } => self.progress_indicator && stderr.is_terminal(),
+            UiOutput::Terminal { stderr, .. } => {
+                self.progress_indicator && !self.quiet && stderr.is_terminal()
+            }
             UiOutput::Paged { .. } => false,
             UiOutput::BuiltinPaged { .. } => false,
         }
@@ -9,11 +9,14 @@ fn test_no_forgotten_test_files() {
 }
 
 mod test_abandon_command;
+mod test_absorb_command;
 mod test_acls;
 mod test_advance_bookmarks;
 mod test_alias;
 mod test_backout_command;
+mod test_bisect_command;
 mod test_bookmark_command;
+mod test_browse_command;
 mod test_builtin_aliases;
 mod test_checkout;
 mod test_commit_command;
@@ -21,6 +24,7 @@ mod test_commit_template;
 mod test_concurrent_operations;
 mod test_config_command;
 mod test_copy_detection;
+mod test_daemon_command;
 mod test_debug_command;
 mod test_describe_command;
 mod test_diff_command;
@@ -28,22 +32,32 @@ mod test_diffedit_command;
 mod test_duplicate_command;
 mod test_edit_command;
 mod test_evolog_command;
+mod test_file_annotate_command;
 mod test_file_chmod_command;
+mod test_file_move_copy_commands;
 mod test_file_print_command;
 mod test_file_track_untrack_commands;
 mod test_fix_command;
+mod test_forge_submit;
+mod test_format_patch_command;
 mod test_generate_md_cli_help;
+mod test_gerrit_send;
+mod test_git_apply;
+mod test_git_archive;
 mod test_git_clone;
 mod test_git_colocated;
 mod test_git_fetch;
 mod test_git_import_export;
 mod test_git_init;
+mod test_git_notes;
 mod test_git_private_commits;
 mod test_git_push;
 mod test_git_remotes;
+mod test_git_replace;
 mod test_git_submodule;
 mod test_gitignores;
 mod test_global_opts;
+mod test_grep_command;
 mod test_immutable_commits;
 mod test_init_command;
 mod test_interdiff_command;
@@ -51,19 +65,24 @@ mod test_log_command;
 mod test_move_command;
 mod test_new_command;
 mod test_next_prev_commands;
+mod test_op_hooks;
 mod test_operations;
 mod test_parallelize_command;
 mod test_rebase_command;
 mod test_repo_change_report;
 mod test_resolve_command;
 mod test_restore_command;
+mod test_revert_command;
 mod test_revset_output;
 mod test_root;
+mod test_run_command;
+mod test_serialize_command;
 mod test_shell_completion;
 mod test_show_command;
 mod test_sparse_command;
 mod test_split_command;
 mod test_squash_command;
+mod test_stash_command;
 mod test_status_command;
 mod test_tag_command;
 mod test_templater;
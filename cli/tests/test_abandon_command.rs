@@ -14,6 +14,8 @@
 
 use std::path::Path;
 
+use crate::common::get_stderr_string;
+use crate::common::get_stdout_string;
 use crate::common::TestEnvironment;
 
 fn create_commit(test_env: &TestEnvironment, repo_path: &Path, name: &str, parents: &[&str]) {
@@ -364,6 +366,46 @@ fn test_double_abandon() {
     "###);
 }
 
+#[test]
+fn test_abandon_confirm_limit() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("ui.confirm.abandon-limit = 1");
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+
+    // Abandoning more revisions than the limit asks for confirmation, which
+    // defaults to no
+    let assert = test_env
+        .jj_cmd_stdin(&repo_path, &["abandon", "a|b"], "\n")
+        .assert()
+        .code(1);
+    let stdout = test_env.normalize_output(&get_stdout_string(&assert));
+    let stderr = test_env.normalize_output(&get_stderr_string(&assert));
+    insta::assert_snapshot!(stdout, @"Abandon 2 revisions? (yN): ");
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Abandon aborted
+    "###);
+
+    // Abandoning no more revisions than the limit doesn't ask
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["abandon", "a"]);
+    assert!(
+        stderr.starts_with("Abandoned commit rlvkpnrz 2443ea76 a | a\n"),
+        "{stderr}"
+    );
+
+    // Non-interactive runs aren't asked
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["abandon", "a|b"]);
+    insta::assert_snapshot!(stdout, @"");
+    assert!(
+        stderr.starts_with("Abandoned the following commits:\n"),
+        "{stderr}"
+    );
+}
+
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     test_env.jj_cmd_success(
         repo_path,
@@ -0,0 +1,118 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_absorb() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "1a\n1b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "1"]);
+    std::fs::write(repo_path.join("file"), "1a\n1b\n2a\n2b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "2"]);
+    std::fs::write(repo_path.join("file"), "1A\n1b\nnew\n2a\n2B\n").unwrap();
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb"]);
+    insta::assert_snapshot!(stdout, @"");
+    assert!(
+        stderr.starts_with("Absorbed changes into these revisions:\n"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("Remaining changes:\nM file\n"), "{stderr}");
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "show", "-r", "description(1)", "file"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    1A
+    1b
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "show", "-r", "description(2)", "file"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    1A
+    1b
+    2a
+    2B
+    "###);
+    // The added line can't be attributed, so it's left in the working copy
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/file b/file
+    index 428796ca20..f599a4e182 100644
+    --- a/file
+    +++ b/file
+    @@ -1,4 +1,5 @@
+     1A
+     1b
+    +new
+     2a
+     2B
+    "###);
+}
+
+#[test]
+fn test_absorb_dry_run() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "1"]);
+    std::fs::write(repo_path.join("file"), "A\n").unwrap();
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb", "--dry-run"]);
+    assert!(
+        stderr.starts_with("Would absorb changes into these revisions:\n"),
+        "{stderr}"
+    );
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "show", "-r", "description(1)", "file"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    a
+    "###);
+}
+
+#[test]
+fn test_absorb_immutable_or_unrelated() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "1"]);
+    std::fs::write(repo_path.join("file"), "A\n").unwrap();
+
+    // Nothing to absorb if the line came from outside the destinations
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb", "--into", "none()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    // Added files are left in the source
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file2"), "b\n").unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["absorb"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+}
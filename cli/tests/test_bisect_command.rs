@@ -0,0 +1,185 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+/// Creates a chain of commits described "1" to "5", where the file contents
+/// become "bad" in commit 3.
+fn create_commits(test_env: &TestEnvironment, repo_path: &Path) {
+    for (description, content) in [
+        ("1", "ok\n"),
+        ("2", "ok\n"),
+        ("3", "bad\n"),
+        ("4", "bad\n"),
+        ("5", "bad\n"),
+    ] {
+        std::fs::write(repo_path.join("file"), content).unwrap();
+        test_env.jj_cmd_ok(repo_path, &["commit", "-m", description]);
+    }
+    test_env.jj_cmd_ok(repo_path, &["describe", "-m", "original"]);
+}
+
+fn get_description(test_env: &TestEnvironment, repo_path: &Path, rev: &str) -> String {
+    test_env.jj_cmd_success(
+        repo_path,
+        &["log", "--no-graph", "-r", rev, "-T", "description"],
+    )
+}
+
+#[test]
+fn test_bisect_manual() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commits(&test_env, &repo_path);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "start"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Hint: Mark a revision that has the bug with `jj bisect bad`.
+    "###);
+
+    test_env.jj_cmd_ok(&repo_path, &["bisect", "bad", "description(5)"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "good", "description(1)"]);
+    assert!(
+        stderr.starts_with("Bisecting: 1 revisions left to test after this (roughly 1 steps)\n"),
+        "{stderr}"
+    );
+    assert_eq!(get_description(&test_env, &repo_path, "@-"), "3\n");
+
+    test_env.jj_cmd_ok(&repo_path, &["bisect", "bad"]);
+    assert_eq!(get_description(&test_env, &repo_path, "@-"), "2\n");
+
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "good"]);
+    assert!(
+        stdout.starts_with("The first bad revision is: "),
+        "{stdout}"
+    );
+    assert!(stdout.ends_with(" 3\n"), "{stdout}");
+
+    test_env.jj_cmd_ok(&repo_path, &["bisect", "reset"]);
+    assert_eq!(get_description(&test_env, &repo_path, "@"), "original\n");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["bisect", "good"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No bisection in progress
+    Hint: Use `jj bisect start` to start one.
+    "###);
+}
+
+#[test]
+fn test_bisect_skip() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commits(&test_env, &repo_path);
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--bad=description(4)",
+            "--good=description(2)",
+        ],
+    );
+    assert_eq!(get_description(&test_env, &repo_path, "@-"), "3\n");
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["bisect", "skip"]);
+    assert!(
+        stdout.starts_with(
+            "Only skipped revisions are left to test. The first bad revision could be any of:\n"
+        ),
+        "{stdout}"
+    );
+    assert_eq!(stdout.lines().count(), 3, "{stdout}");
+}
+
+#[test]
+fn test_bisect_undo() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commits(&test_env, &repo_path);
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--bad=description(5)",
+            "--good=description(1)",
+        ],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["bisect", "good"]);
+    assert_eq!(get_description(&test_env, &repo_path, "@-"), "4\n");
+
+    // Undoing the mistaken mark goes back to testing the previous revision
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    assert_eq!(get_description(&test_env, &repo_path, "@-"), "3\n");
+    test_env.jj_cmd_ok(&repo_path, &["bisect", "bad"]);
+    assert_eq!(get_description(&test_env, &repo_path, "@-"), "2\n");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["bisect", "start"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: A bisection is already in progress
+    Hint: Use `jj bisect reset` to end it first.
+    "###);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_bisect_run() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commits(&test_env, &repo_path);
+
+    let (stdout, _stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--bad=description(5)",
+            "--good=description(1)",
+            "--run",
+            "grep -q ok file",
+        ],
+    );
+    assert!(
+        stdout.starts_with("The first bad revision is: "),
+        "{stdout}"
+    );
+    assert!(stdout.ends_with(" 3\n"), "{stdout}");
+
+    // A status that can't be mapped to a verdict stops the bisection
+    test_env.jj_cmd_ok(&repo_path, &["bisect", "reset"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bisect",
+            "start",
+            "--bad=description(5)",
+            "--good=description(1)",
+        ],
+    );
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["bisect", "run", "exit 200"]);
+    assert!(
+        stderr.ends_with(
+            "Error: Command 'exit 200' failed with exit status: 200, stopping the bisection\n"
+        ),
+        "{stderr}"
+    );
+    assert_eq!(get_description(&test_env, &repo_path, "@-"), "3\n");
+}
@@ -14,6 +14,8 @@
 
 use std::path::Path;
 
+use crate::common::get_stderr_string;
+use crate::common::get_stdout_string;
 use crate::common::TestEnvironment;
 
 #[test]
@@ -537,6 +539,51 @@ fn test_bookmark_forget_glob() {
     "###);
 }
 
+#[test]
+fn test_bookmark_delete_pattern_confirm() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("ui.confirm.bookmark-delete-pattern = true");
+
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "foo-1"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "foo-2"]);
+
+    // Deleting by pattern asks for confirmation, which defaults to no
+    let assert = test_env
+        .jj_cmd_stdin(&repo_path, &["bookmark", "delete", "glob:foo-*"], "\n")
+        .assert()
+        .code(1);
+    let stdout = test_env.normalize_output(&get_stdout_string(&assert));
+    let stderr = test_env.normalize_output(&get_stderr_string(&assert));
+    insta::assert_snapshot!(stdout, @"Delete bookmarks foo-1, foo-2? (yN): ");
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Bookmark deletion aborted
+    "###);
+
+    // Exact names don't need confirmation
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bookmark", "delete", "foo-1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Deleted 1 bookmarks.
+    "###);
+
+    // Confirmed deletion by pattern
+    let (stdout, stderr) =
+        test_env.jj_cmd_stdin_ok(&repo_path, &["bookmark", "delete", "glob:foo-*"], "y\n");
+    insta::assert_snapshot!(stdout, @"Delete bookmarks foo-2? (yN): ");
+    insta::assert_snapshot!(stderr, @r###"
+    Deleted 1 bookmarks.
+    "###);
+
+    // Non-interactive runs aren't asked
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "foo-3"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bookmark", "delete", "glob:foo-*"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Deleted 1 bookmarks.
+    "###);
+}
+
 #[test]
 fn test_bookmark_delete_glob() {
     // Set up a git repo with a bookmark and a jj repo that has it as a remote.
@@ -622,7 +669,7 @@ fn test_bookmark_delete_glob() {
     // Malformed glob
     let stderr = test_env.jj_cmd_cli_error(&repo_path, &["bookmark", "delete", "glob:foo-[1-3"]);
     insta::assert_snapshot!(stderr, @r###"
-    error: invalid value 'glob:foo-[1-3' for '<NAMES>...': Pattern syntax error near position 4: invalid range pattern
+    error: invalid value 'glob:foo-[1-3' for '[NAMES]...': Pattern syntax error near position 4: invalid range pattern
 
     For more information, try '--help'.
     "###);
@@ -638,6 +685,71 @@ fn test_bookmark_delete_glob() {
     "###);
 }
 
+#[test]
+fn test_bookmark_delete_by_revisions() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let get_log = || {
+        let template = r#"separate(" ", description.first_line(), bookmarks)"#;
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", template])
+    };
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=a"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "tmp/a", "keep-a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=b"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "tmp/b"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=c"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "tmp/c"]);
+    insta::assert_snapshot!(get_log(), @r###"
+    @  c tmp/c
+    ○  b tmp/b
+    ○  a keep-a tmp/a
+    ◆
+    "###);
+
+    // Delete all bookmarks pointing into a range of revisions
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["bookmark", "delete", "-r", "::@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Deleted 3 bookmarks.
+    "###);
+    insta::assert_snapshot!(get_log(), @r###"
+    @  c tmp/c
+    ○  b
+    ○  a
+    ◆
+    "###);
+
+    // The names filter the bookmarks pointing to the revisions
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["bookmark", "delete", "-r", "::@-", "glob:tmp/*"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Deleted 2 bookmarks.
+    "###);
+    insta::assert_snapshot!(get_log(), @r###"
+    @  c tmp/c
+    ○  b
+    ○  a keep-a
+    ◆
+    "###);
+
+    // Either names or revisions are required
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["bookmark", "delete"]);
+    insta::assert_snapshot!(stderr, @r###"
+    error: the following required arguments were not provided:
+      <NAMES|--revisions <REVISIONS>>
+
+    Usage: jj bookmark delete <NAMES|--revisions <REVISIONS>>
+
+    For more information, try '--help'.
+    "###);
+}
+
 #[test]
 fn test_bookmark_delete_export() {
     let test_env = TestEnvironment::default();
@@ -1825,6 +1937,52 @@ fn test_bookmark_list_conflicted() {
     "###);
 }
 
+#[test]
+fn test_bookmark_list_group_by_prefix() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bookmark",
+            "create",
+            "main",
+            "feature/ui/menu",
+            "feature/api",
+            "tmp/x",
+            "feature/ui/button",
+            "zzz",
+        ],
+    );
+    let template = r#"name ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["bookmark", "list", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    feature/api
+    feature/ui/button
+    feature/ui/menu
+    main
+    tmp/x
+    zzz
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["bookmark", "list", "--group-by-prefix", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    main
+    zzz
+    feature/
+    feature/api
+    feature/ui/
+    feature/ui/button
+    feature/ui/menu
+    tmp/
+    tmp/x
+    "###);
+}
+
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
     let template = r#"bookmarks ++ " " ++ commit_id.short()"#;
     test_env.jj_cmd_success(cwd, &["log", "-T", template])
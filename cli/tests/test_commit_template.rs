@@ -94,6 +94,34 @@ fn test_log_author_timestamp() {
     "###);
 }
 
+#[test]
+fn test_log_author_mailmap() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let template = r#"
+    separate(" ",
+      author.name(), author.email(), "/", author_raw.name(), author_raw.email(),
+      "/", committer.email(), committer_raw.email(),
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    Test User test.user@example.com / Test User test.user@example.com / test.user@example.com test.user@example.com
+    "###);
+
+    std::fs::write(
+        repo_path.join(".mailmap"),
+        "# Comment\nProper Name <proper@example.com> <TEST.USER@example.com>\n",
+    )
+    .unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    Proper Name proper@example.com / Test User test.user@example.com / proper@example.com test.user@example.com
+    "###);
+}
+
 #[test]
 fn test_log_author_timestamp_ago() {
     let test_env = TestEnvironment::default();
@@ -380,53 +408,53 @@ fn test_log_builtin_templates_colored_debug() {
     test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "my-bookmark"]);
 
     insta::assert_snapshot!(render(r#"builtin_log_oneline"#), @r#"
-    [1m[38;5;2m<<node working_copy::@>>[0m  [1m[38;5;13m<<log working_copy change_id shortest prefix::r>>[38;5;8m<<log working_copy change_id shortest rest::lvkpnrz>>[39m<<log working_copy:: >>[38;5;9m<<log working_copy email placeholder::(no email set)>>[39m<<log working_copy:: >>[38;5;14m<<log working_copy committer timestamp local format::2001-02-03 08:05:08>>[39m<<log working_copy:: >>[38;5;13m<<log working_copy bookmarks name::my-bookmark>>[39m<<log working_copy:: >>[38;5;12m<<log working_copy commit_id shortest prefix::d>>[38;5;8m<<log working_copy commit_id shortest rest::c315397>>[39m<<log working_copy:: >>[38;5;10m<<log working_copy empty::(empty)>>[39m<<log working_copy:: >>[38;5;10m<<log working_copy empty description placeholder::(no description set)>>[39m<<log working_copy::>>[0m
-    <<node::○>>  [1m[38;5;5m<<log change_id shortest prefix::q>>[0m[38;5;8m<<log change_id shortest rest::pvuntsm>>[39m<<log:: >>[38;5;3m<<log author username::test.user>>[39m<<log:: >>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:07>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::2>>[0m[38;5;8m<<log commit_id shortest rest::30dd059>>[39m<<log:: >>[38;5;2m<<log empty::(empty)>>[39m<<log:: >>[38;5;2m<<log empty description placeholder::(no description set)>>[39m<<log::>>
-    [1m[38;5;14m<<node immutable::◆>>[0m  [1m[38;5;5m<<log change_id shortest prefix::z>>[0m[38;5;8m<<log change_id shortest rest::zzzzzzz>>[39m<<log:: >>[38;5;2m<<log root::root()>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::0>>[0m[38;5;8m<<log commit_id shortest rest::0000000>>[39m<<log::>>
+    [1m[38;5;2m<<node working_copy::@>>[0m<<edge::  >>[1m[38;5;13m<<log working_copy change_id shortest prefix::r>>[38;5;8m<<log working_copy change_id shortest rest::lvkpnrz>>[39m<<log working_copy:: >>[38;5;9m<<log working_copy email placeholder::(no email set)>>[39m<<log working_copy:: >>[38;5;14m<<log working_copy committer timestamp local format::2001-02-03 08:05:08>>[39m<<log working_copy:: >>[38;5;13m<<log working_copy bookmarks name::my-bookmark>>[39m<<log working_copy:: >>[38;5;12m<<log working_copy commit_id shortest prefix::d>>[38;5;8m<<log working_copy commit_id shortest rest::c315397>>[39m<<log working_copy:: >>[38;5;10m<<log working_copy empty::(empty)>>[39m<<log working_copy:: >>[38;5;10m<<log working_copy empty description placeholder::(no description set)>>[39m<<log working_copy::>>[0m
+    <<node::○>><<edge::  >>[1m[38;5;5m<<log change_id shortest prefix::q>>[0m[38;5;8m<<log change_id shortest rest::pvuntsm>>[39m<<log:: >>[38;5;3m<<log author username::test.user>>[39m<<log:: >>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:07>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::2>>[0m[38;5;8m<<log commit_id shortest rest::30dd059>>[39m<<log:: >>[38;5;2m<<log empty::(empty)>>[39m<<log:: >>[38;5;2m<<log empty description placeholder::(no description set)>>[39m<<log::>>
+    [1m[38;5;14m<<node immutable::◆>>[0m<<edge::  >>[1m[38;5;5m<<log change_id shortest prefix::z>>[0m[38;5;8m<<log change_id shortest rest::zzzzzzz>>[39m<<log:: >>[38;5;2m<<log root::root()>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::0>>[0m[38;5;8m<<log commit_id shortest rest::0000000>>[39m<<log::>>
     "#);
 
     insta::assert_snapshot!(render(r#"builtin_log_compact"#), @r#"
-    [1m[38;5;2m<<node working_copy::@>>[0m  [1m[38;5;13m<<log working_copy change_id shortest prefix::r>>[38;5;8m<<log working_copy change_id shortest rest::lvkpnrz>>[39m<<log working_copy:: >>[38;5;9m<<log working_copy email placeholder::(no email set)>>[39m<<log working_copy:: >>[38;5;14m<<log working_copy committer timestamp local format::2001-02-03 08:05:08>>[39m<<log working_copy:: >>[38;5;13m<<log working_copy bookmarks name::my-bookmark>>[39m<<log working_copy:: >>[38;5;12m<<log working_copy commit_id shortest prefix::d>>[38;5;8m<<log working_copy commit_id shortest rest::c315397>>[39m<<log working_copy::>>[0m
-    │  [1m[38;5;10m<<log working_copy empty::(empty)>>[39m<<log working_copy:: >>[38;5;10m<<log working_copy empty description placeholder::(no description set)>>[39m<<log working_copy::>>[0m
-    <<node::○>>  [1m[38;5;5m<<log change_id shortest prefix::q>>[0m[38;5;8m<<log change_id shortest rest::pvuntsm>>[39m<<log:: >>[38;5;3m<<log author email::test.user@example.com>>[39m<<log:: >>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:07>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::2>>[0m[38;5;8m<<log commit_id shortest rest::30dd059>>[39m<<log::>>
-    │  [38;5;2m<<log empty::(empty)>>[39m<<log:: >>[38;5;2m<<log empty description placeholder::(no description set)>>[39m<<log::>>
-    [1m[38;5;14m<<node immutable::◆>>[0m  [1m[38;5;5m<<log change_id shortest prefix::z>>[0m[38;5;8m<<log change_id shortest rest::zzzzzzz>>[39m<<log:: >>[38;5;2m<<log root::root()>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::0>>[0m[38;5;8m<<log commit_id shortest rest::0000000>>[39m<<log::>>
+    [1m[38;5;2m<<node working_copy::@>>[0m<<edge::  >>[1m[38;5;13m<<log working_copy change_id shortest prefix::r>>[38;5;8m<<log working_copy change_id shortest rest::lvkpnrz>>[39m<<log working_copy:: >>[38;5;9m<<log working_copy email placeholder::(no email set)>>[39m<<log working_copy:: >>[38;5;14m<<log working_copy committer timestamp local format::2001-02-03 08:05:08>>[39m<<log working_copy:: >>[38;5;13m<<log working_copy bookmarks name::my-bookmark>>[39m<<log working_copy:: >>[38;5;12m<<log working_copy commit_id shortest prefix::d>>[38;5;8m<<log working_copy commit_id shortest rest::c315397>>[39m<<log working_copy::>>[0m
+    <<edge::│  >>[1m[38;5;10m<<log working_copy empty::(empty)>>[39m<<log working_copy:: >>[38;5;10m<<log working_copy empty description placeholder::(no description set)>>[39m<<log working_copy::>>[0m
+    <<node::○>><<edge::  >>[1m[38;5;5m<<log change_id shortest prefix::q>>[0m[38;5;8m<<log change_id shortest rest::pvuntsm>>[39m<<log:: >>[38;5;3m<<log author email::test.user@example.com>>[39m<<log:: >>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:07>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::2>>[0m[38;5;8m<<log commit_id shortest rest::30dd059>>[39m<<log::>>
+    <<edge::│  >>[38;5;2m<<log empty::(empty)>>[39m<<log:: >>[38;5;2m<<log empty description placeholder::(no description set)>>[39m<<log::>>
+    [1m[38;5;14m<<node immutable::◆>>[0m<<edge::  >>[1m[38;5;5m<<log change_id shortest prefix::z>>[0m[38;5;8m<<log change_id shortest rest::zzzzzzz>>[39m<<log:: >>[38;5;2m<<log root::root()>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::0>>[0m[38;5;8m<<log commit_id shortest rest::0000000>>[39m<<log::>>
     "#);
 
     insta::assert_snapshot!(render(r#"builtin_log_comfortable"#), @r#"
-    [1m[38;5;2m<<node working_copy::@>>[0m  [1m[38;5;13m<<log working_copy change_id shortest prefix::r>>[38;5;8m<<log working_copy change_id shortest rest::lvkpnrz>>[39m<<log working_copy:: >>[38;5;9m<<log working_copy email placeholder::(no email set)>>[39m<<log working_copy:: >>[38;5;14m<<log working_copy committer timestamp local format::2001-02-03 08:05:08>>[39m<<log working_copy:: >>[38;5;13m<<log working_copy bookmarks name::my-bookmark>>[39m<<log working_copy:: >>[38;5;12m<<log working_copy commit_id shortest prefix::d>>[38;5;8m<<log working_copy commit_id shortest rest::c315397>>[39m<<log working_copy::>>[0m
-    │  [1m[38;5;10m<<log working_copy empty::(empty)>>[39m<<log working_copy:: >>[38;5;10m<<log working_copy empty description placeholder::(no description set)>>[39m<<log working_copy::>>[0m
-    │  <<log::>>
-    <<node::○>>  [1m[38;5;5m<<log change_id shortest prefix::q>>[0m[38;5;8m<<log change_id shortest rest::pvuntsm>>[39m<<log:: >>[38;5;3m<<log author email::test.user@example.com>>[39m<<log:: >>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:07>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::2>>[0m[38;5;8m<<log commit_id shortest rest::30dd059>>[39m<<log::>>
-    │  [38;5;2m<<log empty::(empty)>>[39m<<log:: >>[38;5;2m<<log empty description placeholder::(no description set)>>[39m<<log::>>
-    │  <<log::>>
-    [1m[38;5;14m<<node immutable::◆>>[0m  [1m[38;5;5m<<log change_id shortest prefix::z>>[0m[38;5;8m<<log change_id shortest rest::zzzzzzz>>[39m<<log:: >>[38;5;2m<<log root::root()>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::0>>[0m[38;5;8m<<log commit_id shortest rest::0000000>>[39m<<log::>>
-       <<log::>>
+    [1m[38;5;2m<<node working_copy::@>>[0m<<edge::  >>[1m[38;5;13m<<log working_copy change_id shortest prefix::r>>[38;5;8m<<log working_copy change_id shortest rest::lvkpnrz>>[39m<<log working_copy:: >>[38;5;9m<<log working_copy email placeholder::(no email set)>>[39m<<log working_copy:: >>[38;5;14m<<log working_copy committer timestamp local format::2001-02-03 08:05:08>>[39m<<log working_copy:: >>[38;5;13m<<log working_copy bookmarks name::my-bookmark>>[39m<<log working_copy:: >>[38;5;12m<<log working_copy commit_id shortest prefix::d>>[38;5;8m<<log working_copy commit_id shortest rest::c315397>>[39m<<log working_copy::>>[0m
+    <<edge::│  >>[1m[38;5;10m<<log working_copy empty::(empty)>>[39m<<log working_copy:: >>[38;5;10m<<log working_copy empty description placeholder::(no description set)>>[39m<<log working_copy::>>[0m
+    <<edge::│  >><<log::>>
+    <<node::○>><<edge::  >>[1m[38;5;5m<<log change_id shortest prefix::q>>[0m[38;5;8m<<log change_id shortest rest::pvuntsm>>[39m<<log:: >>[38;5;3m<<log author email::test.user@example.com>>[39m<<log:: >>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:07>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::2>>[0m[38;5;8m<<log commit_id shortest rest::30dd059>>[39m<<log::>>
+    <<edge::│  >>[38;5;2m<<log empty::(empty)>>[39m<<log:: >>[38;5;2m<<log empty description placeholder::(no description set)>>[39m<<log::>>
+    <<edge::│  >><<log::>>
+    [1m[38;5;14m<<node immutable::◆>>[0m<<edge::  >>[1m[38;5;5m<<log change_id shortest prefix::z>>[0m[38;5;8m<<log change_id shortest rest::zzzzzzz>>[39m<<log:: >>[38;5;2m<<log root::root()>>[39m<<log:: >>[1m[38;5;4m<<log commit_id shortest prefix::0>>[0m[38;5;8m<<log commit_id shortest rest::0000000>>[39m<<log::>>
+    <<edge::   >><<log::>>
     "#);
 
     insta::assert_snapshot!(render(r#"builtin_log_detailed"#), @r###"
-    [1m[38;5;2m<<node working_copy::@>>[0m  <<log::Commit ID: >>[38;5;4m<<log commit_id::dc31539712c7294d1d712cec63cef4504b94ca74>>[39m<<log::>>
-    │  <<log::Change ID: >>[38;5;5m<<log change_id::rlvkpnrzqnoowoytxnquwvuryrwnrmlp>>[39m<<log::>>
-    │  <<log::Bookmarks: >>[38;5;5m<<log local_bookmarks name::my-bookmark>>[39m<<log::>>
-    │  <<log::Author: >>[38;5;1m<<log name placeholder::(no name set)>>[39m<<log:: <>>[38;5;1m<<log email placeholder::(no email set)>>[39m<<log::> (>>[38;5;6m<<log author timestamp local format::2001-02-03 08:05:08>>[39m<<log::)>>
-    │  <<log::Committer: >>[38;5;1m<<log name placeholder::(no name set)>>[39m<<log:: <>>[38;5;1m<<log email placeholder::(no email set)>>[39m<<log::> (>>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:08>>[39m<<log::)>>
-    │  <<log::>>
-    │  [38;5;2m<<log empty description placeholder::    (no description set)>>[39m<<log::>>
-    │  <<log::>>
-    <<node::○>>  <<log::Commit ID: >>[38;5;4m<<log commit_id::230dd059e1b059aefc0da06a2e5a7dbf22362f22>>[39m<<log::>>
-    │  <<log::Change ID: >>[38;5;5m<<log change_id::qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu>>[39m<<log::>>
-    │  <<log::Author: >><<log author name::Test User>><<log:: <>>[38;5;3m<<log author email::test.user@example.com>>[39m<<log::> (>>[38;5;6m<<log author timestamp local format::2001-02-03 08:05:07>>[39m<<log::)>>
-    │  <<log::Committer: >><<log committer name::Test User>><<log:: <>>[38;5;3m<<log committer email::test.user@example.com>>[39m<<log::> (>>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:07>>[39m<<log::)>>
-    │  <<log::>>
-    │  [38;5;2m<<log empty description placeholder::    (no description set)>>[39m<<log::>>
-    │  <<log::>>
-    [1m[38;5;14m<<node immutable::◆>>[0m  <<log::Commit ID: >>[38;5;4m<<log commit_id::0000000000000000000000000000000000000000>>[39m<<log::>>
-       <<log::Change ID: >>[38;5;5m<<log change_id::zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz>>[39m<<log::>>
-       <<log::Author: >>[38;5;1m<<log name placeholder::(no name set)>>[39m<<log:: <>>[38;5;1m<<log email placeholder::(no email set)>>[39m<<log::> (>>[38;5;6m<<log author timestamp local format::1970-01-01 11:00:00>>[39m<<log::)>>
-       <<log::Committer: >>[38;5;1m<<log name placeholder::(no name set)>>[39m<<log:: <>>[38;5;1m<<log email placeholder::(no email set)>>[39m<<log::> (>>[38;5;6m<<log committer timestamp local format::1970-01-01 11:00:00>>[39m<<log::)>>
-       <<log::>>
-       [38;5;2m<<log empty description placeholder::    (no description set)>>[39m<<log::>>
-       <<log::>>
+    [1m[38;5;2m<<node working_copy::@>>[0m<<edge::  >><<log::Commit ID: >>[38;5;4m<<log commit_id::dc31539712c7294d1d712cec63cef4504b94ca74>>[39m<<log::>>
+    <<edge::│  >><<log::Change ID: >>[38;5;5m<<log change_id::rlvkpnrzqnoowoytxnquwvuryrwnrmlp>>[39m<<log::>>
+    <<edge::│  >><<log::Bookmarks: >>[38;5;5m<<log local_bookmarks name::my-bookmark>>[39m<<log::>>
+    <<edge::│  >><<log::Author: >>[38;5;1m<<log name placeholder::(no name set)>>[39m<<log:: <>>[38;5;1m<<log email placeholder::(no email set)>>[39m<<log::> (>>[38;5;6m<<log author timestamp local format::2001-02-03 08:05:08>>[39m<<log::)>>
+    <<edge::│  >><<log::Committer: >>[38;5;1m<<log name placeholder::(no name set)>>[39m<<log:: <>>[38;5;1m<<log email placeholder::(no email set)>>[39m<<log::> (>>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:08>>[39m<<log::)>>
+    <<edge::│  >><<log::>>
+    <<edge::│  >>[38;5;2m<<log empty description placeholder::    (no description set)>>[39m<<log::>>
+    <<edge::│  >><<log::>>
+    <<node::○>><<edge::  >><<log::Commit ID: >>[38;5;4m<<log commit_id::230dd059e1b059aefc0da06a2e5a7dbf22362f22>>[39m<<log::>>
+    <<edge::│  >><<log::Change ID: >>[38;5;5m<<log change_id::qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu>>[39m<<log::>>
+    <<edge::│  >><<log::Author: >><<log author name::Test User>><<log:: <>>[38;5;3m<<log author email::test.user@example.com>>[39m<<log::> (>>[38;5;6m<<log author timestamp local format::2001-02-03 08:05:07>>[39m<<log::)>>
+    <<edge::│  >><<log::Committer: >><<log committer name::Test User>><<log:: <>>[38;5;3m<<log committer email::test.user@example.com>>[39m<<log::> (>>[38;5;6m<<log committer timestamp local format::2001-02-03 08:05:07>>[39m<<log::)>>
+    <<edge::│  >><<log::>>
+    <<edge::│  >>[38;5;2m<<log empty description placeholder::    (no description set)>>[39m<<log::>>
+    <<edge::│  >><<log::>>
+    [1m[38;5;14m<<node immutable::◆>>[0m<<edge::  >><<log::Commit ID: >>[38;5;4m<<log commit_id::0000000000000000000000000000000000000000>>[39m<<log::>>
+    <<edge::   >><<log::Change ID: >>[38;5;5m<<log change_id::zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz>>[39m<<log::>>
+    <<edge::   >><<log::Author: >>[38;5;1m<<log name placeholder::(no name set)>>[39m<<log:: <>>[38;5;1m<<log email placeholder::(no email set)>>[39m<<log::> (>>[38;5;6m<<log author timestamp local format::1970-01-01 11:00:00>>[39m<<log::)>>
+    <<edge::   >><<log::Committer: >>[38;5;1m<<log name placeholder::(no name set)>>[39m<<log:: <>>[38;5;1m<<log email placeholder::(no email set)>>[39m<<log::> (>>[38;5;6m<<log committer timestamp local format::1970-01-01 11:00:00>>[39m<<log::)>>
+    <<edge::   >><<log::>>
+    <<edge::   >>[38;5;2m<<log empty description placeholder::    (no description set)>>[39m<<log::>>
+    <<edge::   >><<log::>>
     "###);
 }
 
@@ -1181,3 +1209,296 @@ fn test_log_diff_predefined_formats() {
     +c
     "###);
 }
+
+#[test]
+fn test_log_diff_files() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "a\n").unwrap();
+    std::fs::write(repo_path.join("rename-source"), "rename").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file1"), "b\n").unwrap();
+    std::fs::remove_file(repo_path.join("file2")).unwrap();
+    std::fs::write(repo_path.join("file3"), "c\n").unwrap();
+    std::fs::rename(
+        repo_path.join("rename-source"),
+        repo_path.join("rename-target"),
+    )
+    .unwrap();
+
+    let template = r#"
+    diff.files().map(|e| concat(
+      e.status(), " ", e.source_path(), " -> ", e.path(), "\n",
+    )).join("")
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    modified file1 -> file1
+    removed file2 -> file2
+    added file3 -> file3
+    renamed rename-source -> rename-target
+    "###);
+
+    // cwd != workspace root
+    let template = r#"diff.files().map(|e| e.path()).join(",") ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        test_env.env_root(),
+        &["log", "-Rrepo", "--no-graph", "-r@", "-T", template],
+    );
+    insta::assert_snapshot!(stdout.replace('\\', "/"), @r###"
+    repo/file1,repo/file2,repo/file3,repo/rename-target
+    "###);
+
+    // filtered by fileset
+    let template = r#"self.diff("file1").files().len() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    1
+    "###);
+}
+
+#[test]
+fn test_log_diff_stat_numbers() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\nb\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file1"), "a\nb\nc\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "b\nc\n").unwrap();
+    std::fs::write(repo_path.join("file3"), "x\n").unwrap();
+
+    let template = r#"
+    separate(" ",
+      change_id.short(4),
+      "+" ++ diff.stat().total_added() ++ "/-" ++ diff.stat().total_removed(),
+      diff.stat().files_changed() ++ " files",
+      "(" ++ self.diff("file2").stat().total_added() ++ ")",
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    rlvk +4/-1 3 files (2)
+    qpvu +3/-0 2 files (1)
+    zzzz +0/-0 0 files (0)
+    "###);
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r@", "-T", "json(diff.stat())"],
+    );
+    insta::assert_snapshot!(stdout, @r#"{"files_changed":3,"total_added":4,"total_removed":1}"#);
+}
+
+#[test]
+fn test_log_operation() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "@-", "-m", "first (rewritten)"]);
+
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      "by",
+      operation.id().short(),
+      operation.description(),
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  second by a747a5b97107 describe commit fa15625b4a986997697639dfc2844138900c79f2
+    ○  first (rewritten) by a747a5b97107 describe commit fa15625b4a986997697639dfc2844138900c79f2
+    ◆  by 19a0f1964e23 initialize repo
+    "###);
+
+    let template = r#"json(operation.id()) ++ " " ++ operation.user() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  "a747a5b97107829f32bc4f42c61067ce0e3808fa958d7404d784d9b677017946796eb7cfa628a6d5cb634145a0f9bb4556206e58741a972fae7a9dbe61a3d1f4" test-username@host.example.com
+    │
+    ~
+    "###);
+
+    // Operation isn't printable
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", "operation"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template: Expected expression of type "Template", but actual type is "Option<Operation>"
+    Caused by:  --> 1:1
+      |
+    1 | operation
+      | ^-------^
+      |
+      = Expected expression of type "Template", but actual type is "Option<Operation>"
+    "###);
+}
+
+#[test]
+fn test_log_nearest_ref() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo = {
+        let mut git_repo_path = repo_path.clone();
+        git_repo_path.extend([".jj", "repo", "store", "git"]);
+        git2::Repository::open(git_repo_path).unwrap()
+    };
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "b"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "c"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "c"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "d"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "a", "-m", "e"]);
+    test_env.jj_cmd_ok(&repo_path, &["git", "export"]);
+    let oid = git_repo
+        .find_reference("refs/heads/a")
+        .unwrap()
+        .target()
+        .unwrap();
+    git_repo.reference("refs/tags/v1.0", oid, true, "").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["git", "import"]);
+
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      "tag=" ++ self.nearest_tag(),
+      "bookmark=" ++ self.closest_bookmark(),
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  e tag=v1.0+1 bookmark=a+1
+    │ ○  d tag=v1.0+3 bookmark=c+1
+    │ ○  c tag=v1.0+2 bookmark=c
+    │ ○  b tag=v1.0+1 bookmark=a+1
+    ├─╯
+    ◆  a tag=v1.0 bookmark=a
+    ◆  tag= bookmark=
+    "###);
+}
+
+#[test]
+fn test_log_trailers() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "first\n\nSigned-off-by: Alice <alice@example.com>\nReviewed-by: Bob <bob@example.com>",
+        ],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second\n\nno trailers: here"]);
+
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      description.trailers().len(),
+      "reviewed=" ++ description.trailers().get("Reviewed-by"),
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  second 0 reviewed=
+    ○  first 2 reviewed=Bob <bob@example.com>
+    ◆  0 reviewed=
+    "###);
+
+    let template = r#"description.trailers().map(|t| t.key()).join(",") ++ "\n""#;
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@-", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    Signed-off-by,Reviewed-by
+    "###);
+}
+
+#[test]
+fn test_log_context_functions() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_env_var("JJ_TEST_TEMPLATE_VAR", "hello");
+
+    let template = r#"
+    separate(" ",
+      workspace_name(),
+      workspace_root().ends_with("repo"),
+      current_operation_id().short(),
+      "var=" ++ env("JJ_TEST_TEMPLATE_VAR"),
+      "unset=" ++ env("JJ_TEST_TEMPLATE_UNSET"),
+    ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    default true 738f1e4eb9b7 var=hello unset=
+    "###);
+
+    // Commit-specific functions are available in the commit template only
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "log", "-T", "workspace_name()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template: Function "workspace_name" doesn't exist
+    Caused by:  --> 1:1
+      |
+    1 | workspace_name()
+      | ^------------^
+      |
+      = Function "workspace_name" doesn't exist
+    "###);
+}
+
+#[test]
+fn test_log_json() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first \"quoted\"\n\nbody"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "main"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    let template = r#"json(self) ++ "\n""#;
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@-", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"author":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08.000+07:00"},"change_id":"qpvuntsmwlqtpsluzzsnyyzlmlwvmlnu","commit_id":"757462a4c558b8d66c7a4d15cdce6ee599650a39","committer":{"email":"test.user@example.com","name":"Test User","timestamp":"2001-02-03T04:05:08.000+07:00"},"description":"first \"quoted\"\n\nbody\n","parents":["0000000000000000000000000000000000000000"]}
+    "###);
+
+    let template = r#"
+    json(
+      change_id=change_id.short(),
+      bookmarks=bookmarks,
+      diff=self.diff().files(),
+    ) ++ "\n"
+    "#;
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@-", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    {"bookmarks":[{"conflict":false,"name":"main","present":true,"remote":null,"target":["757462a4c558b8d66c7a4d15cdce6ee599650a39"]}],"change_id":"qpvuntsmwlqt","diff":[{"path":"file1","source_path":"file1","status":"added"}]}
+    "###);
+
+    // TreeDiff can't be serialized
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "-T", "json(self.diff())"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template: Expected expression of type "Serializable", but actual type is "TreeDiff"
+    Caused by:  --> 1:6
+      |
+    1 | json(self.diff())
+      |      ^---------^
+      |
+      = Expected expression of type "Serializable", but actual type is "TreeDiff"
+    "###);
+}
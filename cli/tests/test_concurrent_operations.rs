@@ -34,18 +34,18 @@ fn test_concurrent_operation_divergence() {
     let stderr = test_env.jj_cmd_failure(&repo_path, &["op", "log", "--at-op=@"]);
     insta::assert_snapshot!(stderr, @r###"
     Error: The "@" expression resolved to more than one operation
-    Hint: Try specifying one of the operations by ID: e31015019d90, 48f4a48f3f70
+    Hint: Try specifying one of the operations by ID: 92b6edbd6c6d, c44ce5d8cca5
     "###);
 
     // "op log --at-op" should work without merging the head operations
-    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--at-op=48f4a48f3f70"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "--at-op=c44ce5d8cca5"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  48f4a48f3f70 test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
+    @  c44ce5d8cca5 test-username@host.example.com 2001-02-03 04:05:09.000 +07:00 - 2001-02-03 04:05:09.000 +07:00
     │  describe commit 230dd059e1b059aefc0da06a2e5a7dbf22362f22
     │  args: jj describe -m 'message 2' --at-op @-
-    ○  b51416386f26 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  738f1e4eb9b7 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
-    ○  9a7d829846af test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  19a0f1964e23 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  initialize repo
     ○  000000000000 root()
     "###);
@@ -73,15 +73,15 @@ fn test_concurrent_operations_auto_rebase() {
     test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "initial"]);
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  66d1dd775c54 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    @  dc54b594e0b4 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     │  describe commit 4e8f9d2be039994f589b4e57ac5e9488703e604d
     │  args: jj describe -m initial
-    ○  130d67859810 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    ○  c373e04113fe test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     │  snapshot working copy
     │  args: jj describe -m initial
-    ○  b51416386f26 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  738f1e4eb9b7 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
-    ○  9a7d829846af test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  19a0f1964e23 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  initialize repo
     ○  000000000000 root()
     "###);
@@ -188,21 +188,17 @@ fn test_concurrent_snapshot_wc_reloadable() {
     let template = r#"id ++ "\n" ++ description ++ "\n" ++ tags"#;
     let op_log_stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-T", template]);
     insta::assert_snapshot!(op_log_stdout, @r###"
-    @  9f11958bcf79340028eeabf9b0381cd8d2ae2258d0097b8ce8bd24fe7138eca08d9eb113bb4722ebacd9b7a6fa017e3888f72907be7487f275823c8d21359eed
+    @  533f7b46f1e024152d81bbdc2135ce72e35033ef8cd564232f7d5d62131055397b4be919b1ec13c51967165494bb82bfa1d3d63ae2f492c61d90de20b34cc302
     │  commit 554d22b2c43c1c47e279430197363e8daabe2fd6
-    │  args: jj commit -m 'new child1'
-    ○  f5460e8f43a04fbc61553d12fa5ba8d3b12e4fdcfda1999db6b67cc8e1e473b7e62cc0536196a53b84f34e18c1c6d608f427bb64bd5f834f845a9859e39cb320
+    ○  c36fd513af86bdf4fc4034d96262348563f983677bebedb344835d1f60286dced0ef25453f49e21fa6d7004a0c05c0e39c4e39c6690db2d23e7ff28cd3125e1b
     │  snapshot working copy
-    │  args: jj commit -m 'new child1'
-    ○  49359b6597ead3fbb66802a6bbd8761c0ad4646a2b089090d6fd72fb6e2568aa99c4a92f9f1f252a83cce56ec84961c36e85f731f19fc5a4c24d6a3f7282b774
+    ○  dfee367aeba952d8ca4516d7b7230352e2fb9093119b962116d8c3359e6701e94d0ecff64ba39dc1c0d18d97b5b644c940325824105e80ad17528b93ac06e1ad
     │  commit de71e09289762a65f80bb1c3dae2a949df6bcde7
-    │  args: jj commit -m initial
-    ○  86dbba2b96a4a801abef7f77f8fdf338b6e36f81ea4a531aacf06acbd06f4037731fffef42503c2225fdb206488971c1601ca8b2b4a83a3fe2dce64ee4db085e
+    ○  52b9e2e844a897466d38ab1e68ef1fe1a8ede55ea499a73788c7b053827807842d63d942ba02d2d02c013dcb9f8ed537f64ac4fbf9f1c004debf076f5c47652f
     │  snapshot working copy
-    │  args: jj commit -m initial
-    ○  b51416386f2685fd5493f2b20e8eec3c24a1776d9e1a7cb5ed7e30d2d9c88c0c1e1fe71b0b7358cba60de42533d1228ed9878f2f89817d892c803395ccf9fe92
+    ○  738f1e4eb9b7047d61ccc2cfd0318d2918d9d3048155f29c03e90114cf6d0e9c8c9ea316017c57d7bbb763ca6ea97620cd055b558fd36d057173050a9763145d
     │  add workspace 'default'
-    ○  9a7d829846af88a2f7a1e348fb46ff58729e49632bc9c6a052aec8501563cb0d10f4a4e6010ffde529f84a2b9b5b3a4c211a889106a41f6c076dfdacc79f6af7
+    ○  19a0f1964e23cef4c6fdace35ee6dcaeac4977118e8018c5bd1e173c5141ba13db3c649abef7894825e1b2aebccbda9bce1d3e15386893c9a38e883e141e6f4d
     │  initialize repo
     ○  00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000
 
@@ -241,6 +237,108 @@ fn test_concurrent_snapshot_wc_reloadable() {
     "###);
 }
 
+#[test]
+fn test_concurrent_operations_reconcile() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "base"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "main"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "A"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "main", "-m", "B"]);
+
+    // Nothing to do without divergent operations
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "reconcile"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    There are no divergent operations to reconcile.
+    "###);
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["op", "reconcile", "--at-op=@"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: --at-op is not respected
+    "###);
+
+    let create_divergent_operations = || {
+        let op_id = test_env.jj_cmd_success(
+            &repo_path,
+            &["op", "log", "--no-graph", "--limit=1", "-T", "id.short()"],
+        );
+        test_env.jj_cmd_ok(
+            &repo_path,
+            &["bookmark", "set", "main", "-r", "description(A)"],
+        );
+        test_env.jj_cmd_ok(
+            &repo_path,
+            &[
+                "bookmark",
+                "set",
+                "main",
+                "-r",
+                "description(B)",
+                "--at-op",
+                &op_id,
+            ],
+        );
+    };
+    let conflicted_bookmarks = || {
+        test_env.jj_cmd_success(
+            &repo_path,
+            &["bookmark", "list", "--conflicted", "-T", r#"name ++ "\n""#],
+        )
+    };
+
+    // The conflict is kept if the user can't be prompted
+    create_divergent_operations();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "reconcile"]);
+    assert!(stdout.contains("Bookmark main was moved to different revisions"));
+    assert!(stdout.contains(" A\n"));
+    assert!(stdout.contains(" B\n"));
+    assert!(stdout.contains(r#"or "k" to keep the conflict: k"#));
+    insta::assert_snapshot!(stderr, @r###"
+    Reconciled 2 divergent operations.
+    "###);
+    insta::assert_snapshot!(conflicted_bookmarks(), @r###"
+    main
+    "###);
+
+    // The bookmark points to the chosen revision
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "bookmark",
+            "set",
+            "main",
+            "--allow-backwards",
+            "-r",
+            "description(base)",
+        ],
+    );
+    create_divergent_operations();
+    let (stdout, _stderr) = test_env.jj_cmd_stdin_ok(&repo_path, &["op", "reconcile"], "1\n");
+    let chosen = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("1: "))
+        .unwrap()
+        .split_once(' ')
+        .unwrap()
+        .1
+        .to_owned();
+    insta::assert_snapshot!(conflicted_bookmarks(), @"");
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "main",
+            "-T",
+            "description.first_line()",
+        ],
+    );
+    assert_eq!(stdout, chosen);
+}
+
 fn get_log_output_with_stderr(test_env: &TestEnvironment, cwd: &Path) -> (String, String) {
     let template = r#"commit_id ++ " " ++ description"#;
     test_env.jj_cmd_ok(cwd, &["log", "-T", template])
@@ -0,0 +1,67 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[cfg(unix)]
+#[test]
+fn test_daemon_start_stop() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["daemon", "status"]);
+    insta::assert_snapshot!(stdout, @r###"
+    The daemon is not running.
+    "###);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["daemon", "start"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Started the daemon.
+    "###);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["daemon", "start"]);
+    insta::assert_snapshot!(stderr, @r###"
+    The daemon is already running.
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["daemon", "status"]);
+    insta::assert_snapshot!(stdout, @r###"
+    The daemon is running.
+    "###);
+
+    // A client that doesn't send its request doesn't block the others
+    let idle_client =
+        std::os::unix::net::UnixStream::connect(repo_path.join(".jj/repo/daemon.sock")).unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["daemon", "status"]);
+    insta::assert_snapshot!(stdout, @r###"
+    The daemon is running.
+    "###);
+    drop(idle_client);
+
+    // The index is built by the daemon once it's gone
+    std::fs::remove_dir_all(repo_path.join(".jj/repo/index/operations")).unwrap();
+    std::fs::create_dir(repo_path.join(".jj/repo/index/operations")).unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r", "root()", "-T", "'ok'"]);
+    insta::assert_snapshot!(stdout, @r###"
+    ◆  ok
+    "###);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["daemon", "stop"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Stopped the daemon.
+    "###);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["daemon", "stop"]);
+    insta::assert_snapshot!(stderr, @r###"
+    The daemon is not running.
+    "###);
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use insta::assert_snapshot;
+use jj_lib::lock::FileLock;
 use regex::Regex;
 
 use crate::common::TestEnvironment;
@@ -147,6 +148,135 @@ fn test_debug_reindex() {
     );
 }
 
+#[test]
+fn test_debug_index_changed_paths() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_path, &["debug", "index-changed-paths"]);
+    assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Computed changed paths of 2 commits.
+    "###);
+
+    // The filters of new commits are computed when they're indexed
+    std::fs::write(workspace_path.join("file1"), "contents 1").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m", "first"]);
+    std::fs::create_dir(workspace_path.join("dir")).unwrap();
+    std::fs::write(workspace_path.join("dir").join("file2"), "contents 2").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m", "second"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_path, &["debug", "index-changed-paths"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Computed changed paths of 0 commits.
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "index"]);
+    assert!(
+        stdout.contains("Number of changed-path filters: "),
+        "{stdout}"
+    );
+
+    let log = |revset: &str| {
+        test_env.jj_cmd_success(
+            &workspace_path,
+            &["log", "--no-graph", "-T", r#"description"#, "-r", revset],
+        )
+    };
+    insta::assert_snapshot!(log("file(file1)"), @r###"
+    first
+    "###);
+    insta::assert_snapshot!(log("file(dir)"), @r###"
+    second
+    "###);
+    insta::assert_snapshot!(log("file(dir/file2) | file(file1)"), @r###"
+    second
+    first
+    "###);
+    insta::assert_snapshot!(log("file(nonexistent)"), @"");
+}
+
+#[test]
+fn test_debug_index_copy_records() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    std::fs::write(workspace_path.join("file1"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m", "first"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_path, &["debug", "index-copy-records"]);
+    assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Detected copies of 5 commits.
+    "###);
+
+    // The copy records of new commits are detected when they're indexed
+    std::fs::rename(workspace_path.join("file1"), workspace_path.join("file2")).unwrap();
+    test_env.jj_cmd_ok(&workspace_path, &["commit", "-m", "second"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_path, &["debug", "index-copy-records"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Detected copies of 0 commits.
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "index"]);
+    assert!(
+        stdout.contains("Number of commits with copy records: "),
+        "{stdout}"
+    );
+
+    // The stored records are used to show renames
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["diff", "-r", "@-", "--summary"]);
+    insta::assert_snapshot!(stdout, @r###"
+    R {file1 => file2}
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "log",
+            "--no-graph",
+            "-T",
+            r#"description"#,
+            "-r",
+            "follows(file2)",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    first
+    "###);
+}
+
+#[test]
+fn test_debug_store_bench() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    std::fs::create_dir(workspace_path.join("dir")).unwrap();
+    std::fs::write(workspace_path.join("dir").join("file1"), "contents 1").unwrap();
+    std::fs::write(workspace_path.join("file2"), "contents 2").unwrap();
+
+    // The commit, two trees, and two files are read in each pass
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "store-bench"]);
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(
+        lines[0].starts_with("Pass 1: read 5 objects (20 bytes) in "),
+        "{stdout}"
+    );
+    assert!(
+        lines[1].starts_with("Pass 2: read 5 objects (20 bytes) in "),
+        "{stdout}"
+    );
+
+    // The object cache can be disabled
+    let stdout = test_env.jj_cmd_success(
+        &workspace_path,
+        &[
+            "debug",
+            "store-bench",
+            "--passes=1",
+            "--config-toml=git.object-cache-size=0",
+        ],
+    );
+    assert!(stdout.starts_with("Pass 1: read 5 objects "), "{stdout}");
+}
+
 #[test]
 fn test_debug_tree() {
     let test_env = TestEnvironment::default();
@@ -236,11 +366,108 @@ fn test_debug_operation_id() {
     let stdout =
         test_env.jj_cmd_success(&workspace_path, &["debug", "operation", "--display", "id"]);
     assert_snapshot!(filter_index_stats(&stdout), @r###"
-    b51416386f2685fd5493f2b20e8eec3c24a1776d9e1a7cb5ed7e30d2d9c88c0c1e1fe71b0b7358cba60de42533d1228ed9878f2f89817d892c803395ccf9fe92
+    738f1e4eb9b7047d61ccc2cfd0318d2918d9d3048155f29c03e90114cf6d0e9c8c9ea316017c57d7bbb763ca6ea97620cd055b558fd36d057173050a9763145d
     "###
     );
 }
 
+#[test]
+fn test_debug_locks() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "locks"]);
+    assert_snapshot!(stdout, @r###"
+    working copy: not locked
+    operation heads: not locked
+    commit metadata: not locked
+    "###);
+
+    // Hold the working-copy lock as if another jj process was running
+    let lock_path = workspace_path.join(".jj/working_copy/working_copy.lock");
+    let lock = FileLock::lock(lock_path);
+    let stdout = test_env.jj_cmd_success(&workspace_path, &["debug", "locks"]);
+    let holder = format!(
+        "working copy: locked by process {} for ",
+        std::process::id()
+    );
+    assert!(stdout.starts_with(&holder), "{stdout}");
+    assert!(stdout.ends_with("operation heads: not locked\ncommit metadata: not locked\n"));
+
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_path,
+        &["describe", "-m", "locked", "--wait-for-lock", "10ms"],
+    );
+    assert!(
+        stderr.starts_with("Error: Failed to lock the working copy\n"),
+        "{stderr}"
+    );
+    let hint = format!(
+        "Hint: The lock has been held by process {} (",
+        std::process::id()
+    );
+    assert!(stderr.contains(&hint), "{stderr}");
+
+    // The command succeeds once the lock is released
+    drop(lock);
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["describe", "-m", "unlocked", "--wait-for-lock", "10ms"],
+    );
+}
+
+#[test]
+fn test_debug_fast_export_import() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    std::fs::write(repo_path.join("other"), "c\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "second"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "main"]);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["debug", "fast-export", "-r", "::main", "-o", "../stream"],
+    );
+    assert_snapshot!(stderr, @r###"
+    Exported 2 commits
+    "###);
+
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "imported"]);
+    let imported_path = test_env.env_root().join("imported");
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&imported_path, &["debug", "fast-import", "../stream"]);
+    assert_snapshot!(stderr, @r###"
+    Imported 2 commits
+    Set bookmarks: main
+    "###);
+
+    let template = r#"separate(" ", description.first_line(), bookmarks) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &imported_path,
+        &["log", "-r", "::main", "--no-graph", "-T", template],
+    );
+    assert_snapshot!(stdout, @r###"
+    second main
+    first
+
+    "###);
+    let stdout = test_env.jj_cmd_success(&imported_path, &["file", "show", "-r", "main", "file"]);
+    assert_snapshot!(stdout, @r###"
+    b
+    "###);
+
+    // An empty stream is an error
+    std::fs::write(test_env.env_root().join("empty"), "").unwrap();
+    let stderr = test_env.jj_cmd_failure(&imported_path, &["debug", "fast-import", "../empty"]);
+    assert_snapshot!(stderr, @r###"
+    Error: The stream contains no commits
+    "###);
+}
+
 fn filter_index_stats(text: &str) -> String {
     let regex = Regex::new(r"    Name: [0-9a-z]+").unwrap();
     regex.replace_all(text, "    Name: [hash]").to_string()
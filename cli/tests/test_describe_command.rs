@@ -380,6 +380,81 @@ fn test_describe_multiple_commits() {
     assert!(stderr.contains("exited with an error"));
 }
 
+#[test]
+fn test_describe_reword_editor_template() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let edit_script = test_env.set_up_fake_editor();
+
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  c6349e79bbfd
+    ○  65b6b74e0897
+    ○  230dd059e1b0
+    ◆  000000000000
+    "###);
+
+    let template = r#""JJ: Change " ++ change_id.short(8) ++ "\n" ++ description"#;
+    std::fs::write(&edit_script, "dump editor0").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "-r",
+            "@-::@",
+            "--reword-editor-template",
+            template,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor0")).unwrap(), @r###"
+    JJ: Enter or edit commit descriptions after the `JJ: describe` lines.
+    JJ: Warning:
+    JJ: - The text you enter will be lost on a syntax error.
+    JJ: - The syntax of the separator lines may change in the future.
+
+    JJ: describe 65b6b74e0897 -------
+    JJ: Change rlvkpnrz
+
+    JJ: describe c6349e79bbfd -------
+    JJ: Change kkmpptxz
+
+    JJ: Lines starting with "JJ: " (like this one) will be removed.
+    "###);
+
+    // The template is also used when editing a single description
+    std::fs::write(&edit_script, "dump editor1").unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["describe", "@", "--reword-editor-template", template],
+    );
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("editor1")).unwrap(), @r###"
+    JJ: Change kkmpptxz
+
+    JJ: Lines starting with "JJ: " (like this one) will be removed.
+    "###);
+
+    // The template isn't rendered if the description is given
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "foo",
+            "--reword-editor-template",
+            template,
+        ],
+    );
+    assert!(stderr.contains("cannot be used with"));
+}
+
 #[test]
 fn test_multiple_message_args() {
     let test_env = TestEnvironment::default();
@@ -487,6 +562,59 @@ fn test_describe_default_description() {
     "###);
 }
 
+#[test]
+fn test_describe_trailers_and_lint() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+        [templates]
+        commit_trailers = '"Signed-off-by: " ++ committer.name() ++ " <" ++ committer.email() ++ ">"'
+        [commit-message.lint]
+        subject-max-length = 10
+        "#,
+    );
+    let get_description =
+        || test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "a long subject"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr.lines().next().unwrap(), @r###"Warning: Description "a long subject": the first line is longer than 10 characters"###);
+    insta::assert_snapshot!(get_description(), @r###"
+    a long subject
+
+    Signed-off-by: Test User <test.user@example.com>
+    "###);
+
+    // The trailer isn't added again
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "short\n\nSigned-off-by: Test User <test.user@example.com>",
+        ],
+    );
+    insta::assert_snapshot!(get_description(), @r###"
+    short
+
+    Signed-off-by: Test User <test.user@example.com>
+    "###);
+
+    // Empty descriptions are left alone
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", ""]);
+    insta::assert_snapshot!(get_description(), @"");
+
+    test_env.add_config(r#"commit-message.lint.severity = "error""#);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["describe", "-m", "a long subject"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Description "a long subject": the first line is longer than 10 characters
+    Hint: Fix the description, or set `commit-message.lint.severity` to "warn" to allow it anyway.
+    "###);
+    insta::assert_snapshot!(get_description(), @"");
+}
+
 #[test]
 fn test_describe_author() {
     let test_env = TestEnvironment::default();
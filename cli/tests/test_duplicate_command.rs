@@ -76,7 +76,7 @@ fn test_duplicate() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r#"
-    Undid operation c4b0b2a977fe 2001-02-03 04:05:17.000 +07:00 - 2001-02-03 04:05:17.000 +07:00 duplicate 1 commit(s)
+    Undid operation 9acc72971e08 2001-02-03 04:05:17.000 +07:00 - 2001-02-03 04:05:17.000 +07:00 duplicate 1 commit(s)
     "#);
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate" /* duplicates `c` */]);
     insta::assert_snapshot!(stdout, @"");
@@ -280,7 +280,7 @@ fn test_undo_after_duplicate() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r#"
-    Undid operation 5a2bcfcdd78b 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00 duplicate 1 commit(s)
+    Undid operation c4b4e8813f4a 2001-02-03 04:05:11.000 +07:00 - 2001-02-03 04:05:11.000 +07:00 duplicate 1 commit(s)
     "#);
     insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
     @  2443ea76b0b1   a
@@ -350,6 +350,52 @@ fn test_rebase_duplicates() {
     "###);
 }
 
+#[test]
+fn test_duplicate_destination() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let get_log = || {
+        let template =
+            r#"separate(" ", description.first_line(), diff.files().map(|e| e.path()).join(","))"#;
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", template])
+    };
+
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    create_commit(&test_env, &repo_path, "d", &[]);
+    // Test the setup
+    insta::assert_snapshot!(get_log(), @r###"
+    @  d d
+    │ ○  c c
+    │ ○  b b
+    │ ○  a a
+    ├─╯
+    ◆
+    "###);
+
+    // The stack keeps its structure, and only its own changes are copied
+    let (stdout, _stderr) = test_env.jj_cmd_ok(&repo_path, &["duplicate", "b::c", "--onto", "d"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(get_log(), @r###"
+    ○  c c
+    ○  b b
+    @  d d
+    │ ○  c c
+    │ ○  b b
+    │ ○  a a
+    ├─╯
+    ◆
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "list", "-r", "heads(d::)"]);
+    insta::assert_snapshot!(stdout, @r###"
+    b
+    c
+    d
+    "###);
+}
+
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     let template = r#"commit_id.short() ++ "   " ++ description.first_line()"#;
     test_env.jj_cmd_success(repo_path, &["log", "-T", template])
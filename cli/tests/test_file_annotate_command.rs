@@ -0,0 +1,172 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::common::TestEnvironment;
+
+/// Replaces the change and commit IDs of the given revisions with their names,
+/// and redacts the timestamps.
+fn redact(test_env: &TestEnvironment, repo_path: &Path, output: &str, revs: &[&str]) -> String {
+    let mut output = output.to_owned();
+    for (i, rev) in revs.iter().enumerate() {
+        let template = r#"change_id.short() ++ " " ++ commit_id ++ " " ++ change_id"#;
+        let stdout =
+            test_env.jj_cmd_success(repo_path, &["log", "--no-graph", "-r", rev, "-T", template]);
+        let [short_change_id, commit_id, change_id] = stdout.split(' ').collect::<Vec<_>>()[..]
+        else {
+            panic!("unexpected output: {stdout}");
+        };
+        output = output
+            .replace(change_id, &format!("<change {i}>"))
+            .replace(commit_id, &format!("<commit {i}>"))
+            .replace(short_change_id, &format!("<change {i}>"));
+    }
+    let timestamp = Regex::new(r"\d{4}-\d\d-\d\d \d\d:\d\d:\d\d").unwrap();
+    let time = Regex::new(r"-time \d+").unwrap();
+    let output = timestamp.replace_all(&output, "<timestamp>");
+    time.replace_all(&output, "-time <time>").into_owned()
+}
+
+#[test]
+fn test_annotate() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "line1\nline2\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "initial"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "insert a line"]);
+    std::fs::write(repo_path.join("file"), "line1\nnew\nline2\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "append a line"]);
+    std::fs::write(repo_path.join("file"), "line1\nnew\nline2\nlast").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "annotate", "file"]);
+    insta::assert_snapshot!(redact(&test_env, &repo_path, &stdout, &["@--", "@-", "@"]), @r###"
+    <change 0> Test User <timestamp> 1: line1
+    <change 1> Test User <timestamp> 2: new
+    <change 0> Test User <timestamp> 3: line2
+    <change 2> Test User <timestamp> 4: last
+    "###);
+
+    // Annotate an earlier revision
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "annotate", "-r", "@--", "file"]);
+    insta::assert_snapshot!(redact(&test_env, &repo_path, &stdout, &["@--"]), @r###"
+    <change 0> Test User <timestamp> 1: line1
+    <change 0> Test User <timestamp> 2: line2
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["file", "annotate", "nonexistent"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No such path: nonexistent
+    "###);
+}
+
+#[test]
+fn test_annotate_line_porcelain() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "a\nb\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "initial\n\nbody"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "prepend"]);
+    std::fs::write(repo_path.join("file"), "new\na\nb\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "annotate", "--line-porcelain", "file"],
+    );
+    insta::assert_snapshot!(redact(&test_env, &repo_path, &stdout, &["@-", "@"]), @r###"
+    <commit 1> 1 1 1
+    author Test User
+    author-mail <test.user@example.com>
+    author-time <time>
+    author-tz +0700
+    committer Test User
+    committer-mail <test.user@example.com>
+    committer-time <time>
+    committer-tz +0700
+    summary prepend
+    change-id <change 1>
+    filename file
+    	new
+    <commit 0> 1 2 2
+    author Test User
+    author-mail <test.user@example.com>
+    author-time <time>
+    author-tz +0700
+    committer Test User
+    committer-mail <test.user@example.com>
+    committer-time <time>
+    committer-tz +0700
+    summary initial
+    change-id <change 0>
+    filename file
+    	a
+    <commit 0> 2 3
+    author Test User
+    author-mail <test.user@example.com>
+    author-time <time>
+    author-tz +0700
+    committer Test User
+    committer-mail <test.user@example.com>
+    committer-time <time>
+    committer-tz +0700
+    summary initial
+    change-id <change 0>
+    filename file
+    	b
+    "###);
+}
+
+#[test]
+fn test_annotate_rename_with_template() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("old"), "line1\nline2\nline3\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "initial"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "rename"]);
+    std::fs::rename(repo_path.join("old"), repo_path.join("new")).unwrap();
+    std::fs::write(repo_path.join("new"), "line1\nline2\nline3\nline4\n").unwrap();
+
+    let template = r#"separate(" ", description.first_line(), author.name()) ++ ": ""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "annotate", "-T", template, "new"]);
+    insta::assert_snapshot!(stdout, @r###"
+    initial Test User: line1
+    initial Test User: line2
+    initial Test User: line3
+    rename Test User: line4
+    "###);
+
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["file", "annotate", "--line-porcelain", "new"]);
+    let filenames = stdout
+        .lines()
+        .filter(|line| line.starts_with("filename "))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        filenames,
+        [
+            "filename old",
+            "filename old",
+            "filename old",
+            "filename new"
+        ]
+    );
+}
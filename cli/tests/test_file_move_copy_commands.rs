@@ -0,0 +1,82 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_move_and_copy() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\nb\nc\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "d\ne\nf\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    test_env.jj_cmd_ok(&repo_path, &["file", "move", "file1", "dir/moved"]);
+    test_env.jj_cmd_ok(&repo_path, &["file", "copy", "file2", "copied"]);
+    assert!(!repo_path.join("file1").exists());
+    assert!(repo_path.join("file2").exists());
+    // The copies are followed even though the contents are completely
+    // different now.
+    std::fs::write(repo_path.join("dir").join("moved"), "x\ny\nz\n").unwrap();
+    std::fs::write(repo_path.join("copied"), "u\nv\nw\n").unwrap();
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout.replace('\\', "/"), @r###"
+    C {file2 => copied}
+    R {file1 => dir/moved}
+    "###);
+
+    // Moving the copy again records it from the original source
+    test_env.jj_cmd_ok(&repo_path, &["file", "move", "copied", "copied2"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary"]);
+    insta::assert_snapshot!(stdout.replace('\\', "/"), @r###"
+    C {file2 => copied2}
+    R {file1 => dir/moved}
+    "###);
+
+    // The rename is kept when the working-copy commit is described
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "moves"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "--summary", "-r", "@"]);
+    insta::assert_snapshot!(stdout.replace('\\', "/"), @r###"
+    C {file2 => copied2}
+    R {file1 => dir/moved}
+    "###);
+}
+
+#[test]
+fn test_move_and_copy_errors() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "b\n").unwrap();
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["file", "move", "nonexistent", "file3"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No such file: nonexistent
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["file", "copy", "file1", "file2"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Destination already exists: file2
+    "###);
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &["file", "move", "--ignore-working-copy", "file1", "file3"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: --ignore-working-copy is not respected
+    "###);
+    assert!(repo_path.join("file1").exists());
+}
@@ -94,6 +94,79 @@ fn test_print() {
     "###);
 }
 
+#[test]
+fn test_print_to_output_dir() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::create_dir_all(repo_path.join("dir").join("sub")).unwrap();
+    std::fs::write(repo_path.join("dir").join("file2"), "b\n").unwrap();
+    std::fs::write(repo_path.join("dir").join("sub").join("file3"), "c\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    // Only the specified revision is extracted, not the working copy
+    std::fs::write(repo_path.join("dir").join("file2"), "modified\n").unwrap();
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["file", "show", "-r@-", "-o", "../out", "dir", "file1"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Wrote 3 files to ../out
+    "###);
+    let out_path = test_env.env_root().join("out");
+    let read = |path: &[&str]| {
+        let path = path
+            .iter()
+            .fold(out_path.clone(), |dir, name| dir.join(name));
+        std::fs::read_to_string(path).unwrap()
+    };
+    assert_eq!(read(&["file1"]), "a\n");
+    assert_eq!(read(&["dir", "file2"]), "b\n");
+    assert_eq!(read(&["dir", "sub", "file3"]), "c\n");
+
+    // --output and --archive can't be combined
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &["file", "show", "-o", "../out", "--archive=tar", "dir"],
+    );
+    assert!(stderr.contains("cannot be used with"));
+}
+
+#[test]
+fn test_print_archive() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file1"), "a\n").unwrap();
+    std::fs::create_dir(repo_path.join("dir")).unwrap();
+    std::fs::write(repo_path.join("dir").join("script"), "#!/bin/sh\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["file", "chmod", "x", "dir/script"]);
+
+    let assert = test_env
+        .jj_cmd(&repo_path, &["file", "show", "--archive=tar", "."])
+        .assert()
+        .success()
+        .stderr("");
+    let mut archive = tar::Archive::new(assert.get_output().stdout.as_slice());
+    let mut entries = String::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let mode = entry.header().mode().unwrap();
+        let path = entry.path().unwrap().display().to_string();
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut entry, &mut contents).unwrap();
+        entries.push_str(&format!("{path} {mode:o} {contents:?}\n"));
+    }
+    insta::assert_snapshot!(entries, @r###"
+    dir/script 755 "#!/bin/sh\n"
+    file1 644 "a\n"
+    "###);
+}
+
 #[cfg(unix)]
 #[test]
 fn test_print_symlink() {
@@ -0,0 +1,203 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use regex::Regex;
+
+use crate::common::TestEnvironment;
+
+/// Sets up a repo with a remote, and a fake `gh` program that logs its
+/// arguments.
+fn set_up() -> (TestEnvironment, PathBuf) {
+    let test_env = TestEnvironment::default();
+    git2::Repository::init_bare(test_env.env_root().join("origin")).unwrap();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let origin_path = test_env.env_root().join("origin");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "add",
+            "origin",
+            origin_path.to_str().unwrap(),
+        ],
+    );
+
+    let fake_gh_dir = test_env.env_root().join("fake-gh");
+    std::fs::create_dir(&fake_gh_dir).unwrap();
+    let fake_gh_path = fake_gh_dir.join("gh");
+    std::fs::write(
+        &fake_gh_path,
+        format!(
+            r#"#!/bin/sh
+dir='{dir}'
+printf '%s\n' "$*" >> "$dir/log"
+case "$2" in
+list) cat "$dir/list-output" 2>/dev/null || true ;;
+create)
+    n=$(($(cat "$dir/count" 2>/dev/null || echo 0) + 1))
+    echo "$n" > "$dir/count"
+    echo "https://github.com/octo/repo/pull/$n"
+    ;;
+esac
+"#,
+            dir = fake_gh_dir.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&fake_gh_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    test_env.add_config(&format!(
+        r#"
+        forge.github.program = "{}"
+        forge.github.repository = "octo/repo"
+        "#,
+        fake_gh_path.display()
+    ));
+    (test_env, repo_path)
+}
+
+fn take_gh_log(test_env: &TestEnvironment) -> String {
+    let log_path = test_env.env_root().join("fake-gh").join("log");
+    let log = std::fs::read_to_string(&log_path).unwrap_or_default();
+    std::fs::remove_file(&log_path).ok();
+    Regex::new(r"push-[k-z]+")
+        .unwrap()
+        .replace_all(&log, "push-<id>")
+        .into_owned()
+}
+
+fn get_bookmark_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"name ++ if(remote, "@" ++ remote) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        repo_path,
+        &["bookmark", "list", "--all-remotes", "-T", template],
+    );
+    Regex::new(r"push-[k-z]+")
+        .unwrap()
+        .replace_all(&stdout, "push-<id>")
+        .into_owned()
+}
+
+#[test]
+fn test_forge_submit() {
+    let (test_env, repo_path) = set_up();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first\n\nDetails of first"]);
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second\n\nDetails of second"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    // A dry run neither pushes nor talks to GitHub
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["forge", "submit", "-r", "root()..@-", "--dry-run"],
+    );
+    assert!(stderr.contains("Would submit 2 changes:\n"), "{stderr}");
+    assert!(
+        stderr.ends_with("Dry-run requested, not submitting.\n"),
+        "{stderr}"
+    );
+    insta::assert_snapshot!(take_gh_log(&test_env), @"");
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &repo_path), @"");
+
+    // Each change gets a bookmark and a pull request on top of its parent's
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["forge", "submit", "-r", "root()..@-"]);
+    assert!(
+        stderr.contains("Created pull request #1 for push-"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("Created pull request #2 for push-"),
+        "{stderr}"
+    );
+    insta::assert_snapshot!(take_gh_log(&test_env), @r###"
+    pr list --repo octo/repo --head push-<id> --state open --json number,baseRefName,url --jq .[] | "\(.number) \(.baseRefName) \(.url)"
+    pr create --repo octo/repo --head push-<id> --base main --title first --body Details of first
+    pr list --repo octo/repo --head push-<id> --state open --json number,baseRefName,url --jq .[] | "\(.number) \(.baseRefName) \(.url)"
+    pr create --repo octo/repo --head push-<id> --base push-<id> --title second --body Details of second
+    "###);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &repo_path), @r###"
+    push-<id>
+    push-<id>@origin
+    push-<id>
+    push-<id>@origin
+    "###);
+
+    // Existing pull requests are retargeted if their base changed
+    std::fs::write(
+        test_env.env_root().join("fake-gh").join("list-output"),
+        "1 main https://github.com/octo/repo/pull/1\n",
+    )
+    .unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["forge", "submit", "-r", "root()..@-", "--base", "main"],
+    );
+    assert!(stderr.contains("Pull request #1 for push-"), "{stderr}");
+    assert!(
+        stderr.contains("Retargeted pull request #1 for push-"),
+        "{stderr}"
+    );
+    let log = take_gh_log(&test_env);
+    assert!(
+        log.ends_with("pr edit 1 --repo octo/repo --base push-<id>\n"),
+        "{log}"
+    );
+}
+
+#[test]
+fn test_forge_submit_no_description() {
+    let (test_env, repo_path) = set_up();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["forge", "submit", "-r", "@"]);
+    assert!(
+        stderr.starts_with("Error: Won't submit commit "),
+        "{stderr}"
+    );
+    assert!(
+        stderr.ends_with(" since it has no description\n"),
+        "{stderr}"
+    );
+    insta::assert_snapshot!(take_gh_log(&test_env), @"");
+}
+
+#[test]
+fn test_forge_submit_unknown_repository() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "add",
+            "origin",
+            "https://example.com/repo.git",
+        ],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["forge", "submit", "-r", "@"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Cannot tell the GitHub repository from the remote URL: https://example.com/repo.git
+    Hint: Set `forge.github.repository` to the repository in `owner/name` form.
+    "###);
+}
@@ -0,0 +1,245 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use indoc::indoc;
+use regex::Regex;
+
+use crate::common::TestEnvironment;
+
+fn redact_ids(text: &str) -> String {
+    let regex = Regex::new("[0-9a-f]{40}|[0-9a-f]{10}\\.\\.[0-9a-f]{10}").unwrap();
+    regex.replace_all(text, "<id>").to_string()
+}
+
+#[test]
+fn test_format_patch() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["commit", "-m", "second: change file\n\nThe body."],
+    );
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["format-patch", "-r", "::@- ~ root()", "--stdout"],
+    );
+    let stdout = redact_ids(&stdout);
+    let version_line = stdout.lines().find(|line| line.starts_with("jj ")).unwrap();
+    let stdout = stdout.replace(version_line, "jj <version>");
+    insta::assert_snapshot!(stdout, @r###"
+    From <id> Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:08 +0700
+    Subject: [PATCH 1/2] first
+    Message-Id: <<id>@example.com>
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    ---
+    file | 1 +
+    1 file changed, 1 insertion(+), 0 deletions(-)
+
+    diff --git a/file b/file
+    new file mode 100644
+    index <id>
+    --- /dev/null
+    +++ b/file
+    @@ -1,0 +1,1 @@
+    +a
+    -- 
+    jj <version>
+
+    From <id> Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: Sat, 3 Feb 2001 04:05:09 +0700
+    Subject: [PATCH 2/2] second: change file
+    Message-Id: <<id>@example.com>
+    In-Reply-To: <<id>@example.com>
+    References: <<id>@example.com>
+    MIME-Version: 1.0
+    Content-Type: text/plain; charset=UTF-8
+    Content-Transfer-Encoding: 8bit
+
+    The body.
+    ---
+    file | 2 +-
+    1 file changed, 1 insertion(+), 1 deletion(-)
+
+    diff --git a/file b/file
+    index <id> 100644
+    --- a/file
+    +++ b/file
+    @@ -1,1 +1,1 @@
+    -a
+    +b
+    -- 
+    jj <version>
+
+    "###);
+
+    // Numbered files with a cover letter
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "format-patch",
+            "-r",
+            "::@- ~ root()",
+            "-o",
+            "patches",
+            "--cover-letter",
+            "-v2",
+            "--in-reply-to",
+            "v1@example.com",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    $TEST_ENV/repo/patches/v2-0000-cover-letter.patch
+    $TEST_ENV/repo/patches/v2-0001-first.patch
+    $TEST_ENV/repo/patches/v2-0002-second-change-file.patch
+    "###);
+    let cover_letter =
+        std::fs::read_to_string(repo_path.join("patches/v2-0000-cover-letter.patch")).unwrap();
+    assert!(
+        cover_letter.contains("Subject: [PATCH v2 0/2] *** SUBJECT HERE ***\n"),
+        "{cover_letter}"
+    );
+    assert!(
+        cover_letter.contains("In-Reply-To: <v1@example.com>\n"),
+        "{cover_letter}"
+    );
+    assert!(
+        cover_letter.contains("Test User (2):\n  first\n  second: change file\n"),
+        "{cover_letter}"
+    );
+    let patch = std::fs::read_to_string(repo_path.join("patches/v2-0002-second-change-file.patch"))
+        .unwrap();
+    assert!(
+        patch.contains("Subject: [PATCH v2 2/2] second: change file\n"),
+        "{patch}"
+    );
+    assert!(
+        patch.contains("References: <v1@example.com> <cover."),
+        "{patch}"
+    );
+}
+
+#[test]
+fn test_format_patch_merge() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "b"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["new", "description(a)", "description(b)", "-m", "merge"],
+    );
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["format-patch", "-r", "@"]);
+    assert!(
+        stderr.starts_with("Error: Cannot format merge commit "),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_am() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let mbox = indoc! {"
+        From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+        From: Some One <some.one@example.com>
+        Date: Tue, 1 Oct 2024 12:34:56 +0200
+        Subject: [PATCH 1/2] add file
+
+        ---
+         file | 1 +
+         1 file changed, 1 insertion(+)
+
+        diff --git a/file b/file
+        new file mode 100644
+        --- /dev/null
+        +++ b/file
+        @@ -0,0 +1 @@
+        +a
+        -- 
+        2.46.0
+
+        From 2222222222222222222222222222222222222222 Mon Sep 17 00:00:00 2001
+        From: \"Other, Person\" <other@example.com>
+        Date: Wed, 2 Oct 2024 01:02:03 -0700
+        Subject: [PATCH 2/2] change
+         file
+
+        With a body.
+        ---
+         file | 2 +-
+
+        diff --git a/file b/file
+        --- a/file
+        +++ b/file
+        @@ -1 +1 @@
+        -a
+        +b
+        -- 
+        2.46.0
+    "};
+    let (_stdout, stderr) = test_env.jj_cmd_stdin_ok(&repo_path, &["am"], mbox);
+    assert!(stderr.starts_with("Applied 2 patches:"), "{stderr}");
+    let template = r#"
+        separate(" | ",
+          author.name(),
+          author.email(),
+          author.timestamp().utc(),
+          description.first_line()
+        ) ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "@+::", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    Other, Person | other@example.com | 2024-10-02 08:02:03.000 +00:00 | change file
+    Some One | some.one@example.com | 2024-10-01 10:34:56.000 +00:00 | add file
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "description(change)",
+            "-T",
+            "description",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    change file
+
+    With a body.
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "show", "-r", "description(change)", "file"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    b
+    "###);
+}
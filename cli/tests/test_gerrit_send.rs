@@ -0,0 +1,224 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::common::TestEnvironment;
+
+fn set_up() -> (TestEnvironment, PathBuf) {
+    let test_env = TestEnvironment::default();
+    git2::Repository::init_bare(test_env.env_root().join("gerrit")).unwrap();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let gerrit_path = test_env.env_root().join("gerrit");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "add",
+            "origin",
+            gerrit_path.to_str().unwrap(),
+        ],
+    );
+    (test_env, repo_path)
+}
+
+fn get_description(test_env: &TestEnvironment, repo_path: &Path, rev: &str) -> String {
+    test_env.jj_cmd_success(
+        repo_path,
+        &["log", "--no-graph", "-r", rev, "-T", "description"],
+    )
+}
+
+fn get_change_id_trailer(test_env: &TestEnvironment, repo_path: &Path, rev: &str) -> String {
+    let template = r#""Change-Id: I6a6a6964" ++ change_id.normal_hex()"#;
+    test_env.jj_cmd_success(repo_path, &["log", "--no-graph", "-r", rev, "-T", template])
+}
+
+fn get_commit_id(test_env: &TestEnvironment, repo_path: &Path, rev: &str) -> String {
+    test_env.jj_cmd_success(
+        repo_path,
+        &["log", "--no-graph", "-r", rev, "-T", "commit_id"],
+    )
+}
+
+fn get_gerrit_ref(test_env: &TestEnvironment, name: &str) -> Option<String> {
+    let git_repo = git2::Repository::open(test_env.env_root().join("gerrit")).unwrap();
+    let reference = git_repo.find_reference(name).ok()?;
+    Some(reference.target().unwrap().to_string())
+}
+
+#[test]
+fn test_gerrit_send() {
+    let (test_env, repo_path) = set_up();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    // A dry run doesn't rewrite or push anything
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["gerrit", "send", "-r", "@-", "--dry-run"]);
+    insta::assert_snapshot!(stdout, @"");
+    assert!(
+        stderr.starts_with("Would send 2 commits to refs/for/main on origin:\n"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.ends_with(
+            "Would add or update the Change-Id trailer of 2 commits\nDry-run requested, not \
+             sending.\n"
+        ),
+        "{stderr}"
+    );
+    insta::assert_snapshot!(get_description(&test_env, &repo_path, "@-"), @"second");
+    assert_eq!(get_gerrit_ref(&test_env, "refs/for/main"), None);
+
+    // Both commits get a Change-Id trailer, and the working copy is rebased
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["gerrit", "send", "-r", "@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    assert!(
+        stderr.starts_with(
+            "Rebased 1 descendant commits\nSending 2 commits to refs/for/main on origin:\n"
+        ),
+        "{stderr}"
+    );
+    for (rev, subject) in [("@--", "first"), ("@-", "second")] {
+        assert_eq!(
+            get_description(&test_env, &repo_path, rev),
+            format!(
+                "{subject}\n\n{}\n",
+                get_change_id_trailer(&test_env, &repo_path, rev)
+            )
+        );
+    }
+    let sent_commit_id = get_commit_id(&test_env, &repo_path, "@-");
+    assert_eq!(
+        get_gerrit_ref(&test_env, "refs/for/main"),
+        Some(sent_commit_id.clone())
+    );
+
+    // Sending again doesn't rewrite anything
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["gerrit", "send", "-r", "@-"]);
+    assert!(
+        stderr.starts_with("Sending 2 commits to refs/for/main on origin:\n"),
+        "{stderr}"
+    );
+    assert_eq!(get_commit_id(&test_env, &repo_path, "@-"), sent_commit_id);
+}
+
+#[test]
+fn test_gerrit_send_existing_change_id() {
+    let (test_env, repo_path) = set_up();
+    // A trailer added by Gerrit's commit-msg hook is kept
+    let hook_trailer = "Change-Id: I0123456789abcdef0123456789abcdef01234567";
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            &format!("first\n\nSigned-off-by: Test User\n{hook_trailer}"),
+        ],
+    );
+    // A trailer generated for another change is updated
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "new",
+            "-m",
+            "second\n\nChange-Id: I6a6a696400000000000000000000000000000000",
+        ],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["gerrit", "send", "-r", "@"]);
+    assert_eq!(
+        get_description(&test_env, &repo_path, "@-"),
+        format!("first\n\nSigned-off-by: Test User\n{hook_trailer}\n")
+    );
+    assert_eq!(
+        get_description(&test_env, &repo_path, "@"),
+        format!(
+            "second\n\n{}\n",
+            get_change_id_trailer(&test_env, &repo_path, "@")
+        )
+    );
+}
+
+#[test]
+fn test_gerrit_send_options() {
+    let (test_env, repo_path) = set_up();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "gerrit",
+            "send",
+            "-r",
+            "@",
+            "--for",
+            "stable",
+            "--topic",
+            "foo",
+            "--reviewer",
+            "alice@example.com",
+            "--cc",
+            "bob@example.com",
+        ],
+    );
+    let commit_id = get_commit_id(&test_env, &repo_path, "@");
+    assert_eq!(
+        get_gerrit_ref(
+            &test_env,
+            "refs/for/stable%topic=foo,r=alice@example.com,cc=bob@example.com"
+        ),
+        Some(commit_id.clone())
+    );
+
+    // The target branch can be configured
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "gerrit",
+            "send",
+            "-r",
+            "@",
+            "--config-toml=gerrit.default-branch='develop'",
+        ],
+    );
+    assert_eq!(
+        get_gerrit_ref(&test_env, "refs/for/develop"),
+        Some(commit_id)
+    );
+}
+
+#[test]
+fn test_gerrit_send_no_description() {
+    let (test_env, repo_path) = set_up();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["gerrit", "send", "-r", "@"]);
+    assert!(stderr.starts_with("Error: Won't send commit "), "{stderr}");
+    assert!(
+        stderr.ends_with(" since it has no description\n"),
+        "{stderr}"
+    );
+
+    // Nothing to send if the revisions are already on the remote
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["gerrit", "send", "-r", "root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    No commits to send.
+    "###);
+}
@@ -0,0 +1,116 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_git_apply() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\nb\nc\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "base"]);
+    std::fs::write(repo_path.join("file"), "a\nB\nc\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "change"]);
+    let patch = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+
+    // Apply the patch to the working copy from stdin
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(base)"]);
+    let (stdout, stderr) = test_env.jj_cmd_stdin_ok(&repo_path, &["git", "apply"], &patch);
+    insta::assert_snapshot!(stdout, @"");
+    assert!(stderr.starts_with("Rewrote "), "{stderr}");
+    let file = std::fs::read_to_string(repo_path.join("file")).unwrap();
+    assert_eq!(file, "a\nB\nc\n");
+
+    // Apply the patch as a new commit from a file
+    std::fs::write(test_env.env_root().join("patch"), &patch).unwrap();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "apply",
+            "../patch",
+            "-r",
+            "description(base)",
+            "--new",
+            "-m",
+            "applied",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["file", "show", "-r", "description(applied)", "file"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    a
+    B
+    c
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "description(applied)-",
+            "-T",
+            "description",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    base
+    "###);
+
+    // Applying the patch again merges to the same contents
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["git", "apply", "../patch"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+}
+
+#[test]
+fn test_git_apply_three_way() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "a\nb\nc\nd\ne\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "base"]);
+    std::fs::write(repo_path.join("file"), "a\nB\nc\nd\ne\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "change"]);
+    let patch = test_env.jj_cmd_success(&repo_path, &["diff", "--git"]);
+
+    // The context doesn't match, so the patch is merged using the preimage
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(base)"]);
+    std::fs::write(repo_path.join("file"), "a\nb\nc\nD\ne\n").unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_stdin_ok(&repo_path, &["git", "apply"], &patch);
+    assert!(
+        stderr.contains("Applied patch to file with three-way merge"),
+        "{stderr}"
+    );
+    let file = std::fs::read_to_string(repo_path.join("file")).unwrap();
+    assert_eq!(file, "a\nB\nc\nD\ne\n");
+
+    // Conflicting changes are left conflicted
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(base)"]);
+    std::fs::write(repo_path.join("file"), "a\nbee\nc\nd\ne\n").unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_stdin_ok(&repo_path, &["git", "apply"], &patch);
+    assert!(
+        stderr.contains("There were conflicts in 1 files when merging the patches:"),
+        "{stderr}"
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    file    2-sided conflict
+    "###);
+}
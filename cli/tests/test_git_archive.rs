@@ -0,0 +1,152 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs::File;
+use std::io::Read as _;
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn read_tar_entries(reader: impl std::io::Read) -> String {
+    let mut archive = tar::Archive::new(reader);
+    let mut output = String::new();
+    for entry in archive.entries().unwrap() {
+        let mut entry = entry.unwrap();
+        let header = entry.header().clone();
+        let path = entry.path().unwrap().display().to_string();
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).unwrap();
+        output.push_str(&format!(
+            "{path} {:o} {} {contents:?}\n",
+            header.mode().unwrap(),
+            header.mtime().unwrap(),
+        ));
+    }
+    output
+}
+
+fn read_zip_entries(path: &Path) -> String {
+    let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+    let mut output = String::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).unwrap();
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).unwrap();
+        output.push_str(&format!(
+            "{} {:o} {} {contents:?}\n",
+            entry.name(),
+            entry.unix_mode().unwrap(),
+            entry.last_modified().unwrap(),
+        ));
+    }
+    output
+}
+
+#[test]
+fn test_git_archive_tar() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::create_dir(repo_path.join("dir")).unwrap();
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    std::fs::write(repo_path.join("dir").join("script"), "#!/bin/sh\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["file", "chmod", "x", "dir/script"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    // Only the parent revision is archived, not the modified working copy
+    std::fs::write(repo_path.join("file"), "modified\n").unwrap();
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "archive",
+            "-r@-",
+            "-o",
+            "../out.tar",
+            "--prefix=project/",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Wrote 2 files to ../out.tar
+    "###);
+    // The mtime defaults to the committer timestamp
+    let file = File::open(test_env.env_root().join("out.tar")).unwrap();
+    insta::assert_snapshot!(read_tar_entries(file), @r###"
+    project/dir/script 755 981147908 "#!/bin/sh\n"
+    project/file 644 981147908 "contents\n"
+    "###);
+
+    // The format is inferred from the extension, and paths can be filtered
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "archive",
+            "-o",
+            "../out.tgz",
+            "--mtime=2024-01-01T00:00:00Z",
+            "file",
+        ],
+    );
+    let file = File::open(test_env.env_root().join("out.tgz")).unwrap();
+    insta::assert_snapshot!(read_tar_entries(flate2::read::GzDecoder::new(file)), @r###"
+    file 644 1704067200 "modified\n"
+    "###);
+}
+
+#[test]
+fn test_git_archive_zip() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "base\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    std::fs::write(repo_path.join("file"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "@-"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "all:@-+"]);
+
+    // Conflicts are archived with conflict markers
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "archive",
+            "--format=zip",
+            "-o",
+            "../out",
+            "--mtime=2024-02-03T04:05:06+09:00",
+        ],
+    );
+    insta::assert_snapshot!(read_zip_entries(&test_env.env_root().join("out")), @r###"
+    file 100644 2024-02-03 04:05:06 "<<<<<<< Conflict 1 of 1\n%%%%%%% Changes from base to side #1\n-base\n+b\n+++++++ Contents of side #2\na\n>>>>>>> Conflict 1 of 1 ends\n"
+    "###);
+}
+
+#[test]
+fn test_git_archive_bad_mtime() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &["git", "archive", "-o", "../out.tar", "--mtime=yesterday"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    error: invalid value 'yesterday' for '--mtime <MTIME>': input contains invalid characters
+
+    For more information, try '--help'.
+    "###);
+}
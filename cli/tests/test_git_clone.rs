@@ -476,7 +476,7 @@ fn test_git_clone_ignore_working_copy() {
     // TODO: Correct, but might be better to check out the root commit?
     let stderr = test_env.jj_cmd_failure(&clone_path, &["status"]);
     insta::assert_snapshot!(stderr, @r###"
-    Error: The working copy is stale (not updated since operation b51416386f26).
+    Error: The working copy is stale (not updated since operation 738f1e4eb9b7).
     Hint: Run `jj workspace update-stale` to update it.
     See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     "###);
@@ -522,6 +522,78 @@ fn test_git_clone_with_remote_name() {
     "#);
 }
 
+#[test]
+fn test_git_clone_with_depth() {
+    let test_env = TestEnvironment::default();
+    let git_repo_path = test_env.env_root().join("source");
+    let git_repo = git2::Repository::init(git_repo_path).unwrap();
+    set_up_non_empty_git_repo(&git_repo);
+
+    // libgit2 doesn't support shallow clones from the local transport, so it is
+    // difficult to test this beyond checking that the depth is passed along.
+    let stderr = test_env.jj_cmd_failure(
+        test_env.env_root(),
+        &["git", "clone", "--depth", "1", "source", "clone"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Fetching into new repo in "$TEST_ENV/clone"
+    Error: shallow fetch is not supported by the local transport; class=Net (12)
+    "###);
+}
+
+#[test]
+fn test_git_clone_since_and_fetch_unshallow() {
+    let test_env = TestEnvironment::default();
+    let git_repo_path = test_env.env_root().join("source");
+    let git_repo = git2::Repository::init(git_repo_path).unwrap();
+    let tree_oid = git_repo.treebuilder(None).unwrap().write().unwrap();
+    let tree = git_repo.find_tree(tree_oid).unwrap();
+    let mut parents = vec![];
+    // The commits are made 10 days apart, starting from 1970-01-01
+    for (i, message) in ["first", "second", "third"].into_iter().enumerate() {
+        let time = git2::Time::new(i as i64 * 10 * 24 * 60 * 60, 0);
+        let signature = git2::Signature::new("Some One", "some.one@example.com", &time).unwrap();
+        let oid = git_repo
+            .commit(
+                Some("refs/heads/main"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        parents = vec![git_repo.find_commit(oid).unwrap()];
+    }
+    git_repo.set_head("refs/heads/main").unwrap();
+
+    // The oldest fetched commit becomes a child of the root commit
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["git", "clone", "--since=1970-01-05", "source", "clone"],
+    );
+    let clone_path = test_env.env_root().join("clone");
+    let template = r#"description.first_line() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&clone_path, &["log", "-rall()", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    ◆  third
+    ◆  second
+    ◆
+    "###);
+
+    // The index is rebuilt once the commit gets its parent
+    test_env.jj_cmd_ok(&clone_path, &["git", "fetch", "--unshallow"]);
+    let stdout = test_env.jj_cmd_success(&clone_path, &["log", "-rall()", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    ◆  third
+    ◆  second
+    ◆  first
+    ◆
+    "###);
+}
+
 fn get_bookmark_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     test_env.jj_cmd_success(repo_path, &["bookmark", "list", "--all-remotes"])
 }
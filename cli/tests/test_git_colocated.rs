@@ -759,7 +759,7 @@ fn test_git_colocated_undo_head_move() {
     insta::assert_snapshot!(stderr, @r#"
     Working copy now at: royxmykx eb08b363 (empty) (no description set)
     Parent commit      : qpvuntsm 230dd059 (empty) (no description set)
-    Undid operation 951a1c249028 2001-02-03 04:05:13.000 +07:00 - 2001-02-03 04:05:13.000 +07:00 new empty commit
+    Undid operation 1df55861fa55 2001-02-03 04:05:13.000 +07:00 - 2001-02-03 04:05:13.000 +07:00 new empty commit
     "#);
     insta::assert_snapshot!(
         git_repo.head().unwrap().target().unwrap().to_string(),
@@ -870,6 +870,102 @@ fn test_git_colocated_unreachable_commits() {
     "###);
 }
 
+#[test]
+fn test_git_colocated_worktree_workspace() {
+    let test_env = TestEnvironment::default();
+    let workspace_root = test_env.env_root().join("repo");
+    let git_repo = git2::Repository::init(&workspace_root).unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["git", "init", "--git-repo", "."]);
+    std::fs::write(workspace_root.join("file"), "contents").unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["commit", "-m", "initial"]);
+
+    // The new workspace is a Git worktree with HEAD at the working-copy parent
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &["workspace", "add", "--colocate", "../second"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    assert!(stderr.starts_with("Created workspace in \"../second\""));
+    let second_root = test_env.env_root().join("second");
+    let worktree_repo = git2::Repository::open(&second_root).unwrap();
+    assert!(worktree_repo.is_worktree());
+    let head_commit = worktree_repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head_commit.message(), Some("initial\n"));
+    assert_eq!(
+        std::fs::read_to_string(second_root.join("file")).unwrap(),
+        "contents"
+    );
+    assert_eq!(
+        std::fs::read_to_string(second_root.join(".jj/.gitignore")).unwrap(),
+        "/*\n"
+    );
+
+    // A commit made by Git in the worktree is imported as the new working-copy
+    // parent, but HEAD of the main worktree isn't affected
+    let signature = git2::Signature::new(
+        "Someone",
+        "someone@example.com",
+        &git2::Time::new(1234567890, 60),
+    )
+    .unwrap();
+    worktree_repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "from git",
+            &head_commit.tree().unwrap(),
+            &[&head_commit],
+        )
+        .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &second_root,
+        &["log", "--no-graph", "-r", "@-", "-T", "description"],
+    );
+    insta::assert_snapshot!(stdout, @"from git");
+    insta::assert_snapshot!(stderr, @r###"
+    Reset the working copy parent to the new Git HEAD.
+    "###);
+    let main_head_commit = git_repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(main_head_commit.message(), Some("initial\n"));
+
+    // Moving the working-copy commit in jj moves HEAD of the worktree
+    test_env.jj_cmd_ok(&second_root, &["new", "@--"]);
+    let head_commit = worktree_repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head_commit.message(), Some("initial\n"));
+    let stdout = test_env.jj_cmd_success(
+        &second_root,
+        &["log", "--no-graph", "-r", "@-", "-T", "description"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    initial
+    "###);
+
+    // An existing worktree can be adopted as a workspace
+    worktree_repo
+        .worktree("third", &test_env.env_root().join("third"), None)
+        .unwrap();
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &["workspace", "add", "--colocate", "-r", "@", "../third"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Done importing changes from the underlying Git repo.
+    Error: Cannot specify revisions when adopting an existing Git worktree
+    "###);
+    test_env.jj_cmd_ok(
+        &workspace_root,
+        &["workspace", "add", "--colocate", "../third"],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &test_env.env_root().join("third"),
+        &["log", "--no-graph", "-r", "@-", "-T", "description"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    initial
+    "###);
+}
+
 fn get_bookmark_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     // --quiet to suppress deleted bookmarks hint
     test_env.jj_cmd_success(repo_path, &["bookmark", "list", "--all-remotes", "--quiet"])
@@ -119,6 +119,105 @@ fn test_git_fetch_single_remote() {
     "###);
 }
 
+#[test]
+fn test_git_fetch_refspecs_from_git_config() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    add_git_remote(&test_env, &repo_path, "origin");
+    let remote_git_repo = git2::Repository::open(test_env.env_root().join("origin")).unwrap();
+    let commit_oid = remote_git_repo.refname_to_id("refs/heads/origin").unwrap();
+    for name in ["team/a", "team/b", "other"] {
+        remote_git_repo
+            .reference(&format!("refs/heads/{name}"), commit_oid, false, "")
+            .unwrap();
+    }
+
+    // Fetch only the branches configured by the remote's fetch refspecs
+    let git_repo = git2::Repository::open(repo_path.join(".jj/repo/store/git")).unwrap();
+    let mut git_config = git_repo.config().unwrap();
+    git_config
+        .set_multivar(
+            "remote.origin.fetch",
+            ".*",
+            "+refs/heads/team/*:refs/remotes/origin/team/*",
+        )
+        .unwrap();
+    git_repo
+        .remote_add_fetch("origin", "refs/heads/other:refs/remotes/origin/other")
+        .unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch"]);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &repo_path), @r###"
+    other@origin: oputwtnw ffecd2d6 message
+    team/a@origin: oputwtnw ffecd2d6 message
+    team/b@origin: oputwtnw ffecd2d6 message
+    "###);
+
+    // Explicit --branch overrides the refspecs
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch", "--branch=origin"]);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &repo_path), @r###"
+    origin@origin: oputwtnw ffecd2d6 message
+    other@origin: oputwtnw ffecd2d6 message
+    team/a@origin: oputwtnw ffecd2d6 message
+    team/b@origin: oputwtnw ffecd2d6 message
+    "###);
+
+    // Refspecs mapping branches to different names aren't supported
+    git_repo
+        .remote_add_fetch("origin", "+refs/heads/*:refs/remotes/origin/mirror/*")
+        .unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["git", "fetch"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Unsupported fetch refspec for remote origin: +refs/heads/*:refs/remotes/origin/mirror/*
+    Hint: Only refspecs of the form `+refs/heads/<branch>:refs/remotes/origin/<branch>` are supported
+    "###);
+}
+
+#[test]
+fn test_git_fetch_tags() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    add_git_remote(&test_env, &repo_path, "origin");
+    let remote_git_repo = git2::Repository::open(test_env.env_root().join("origin")).unwrap();
+    let commit = remote_git_repo
+        .find_commit(remote_git_repo.refname_to_id("refs/heads/origin").unwrap())
+        .unwrap();
+    remote_git_repo
+        .tag_lightweight("on-branch", commit.as_object(), false)
+        .unwrap();
+    let signature =
+        git2::Signature::new("Some One", "some.one@example.com", &git2::Time::new(0, 0)).unwrap();
+    let orphan_oid = remote_git_repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "orphan",
+            &commit.tree().unwrap(),
+            &[],
+        )
+        .unwrap();
+    remote_git_repo
+        .tag_lightweight(
+            "orphan",
+            &remote_git_repo.find_object(orphan_oid, None).unwrap(),
+            false,
+        )
+        .unwrap();
+
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch", "--no-tags"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["tag", "list"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    test_env.jj_cmd_ok(&repo_path, &["git", "fetch", "--tags"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["tag", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    on-branch: oputwtnw ffecd2d6 message
+    orphan: swryovnu 68f4ffb5 orphan
+    "###);
+}
+
 #[test]
 fn test_git_fetch_single_remote_all_remotes_flag() {
     let test_env = TestEnvironment::default();
@@ -846,7 +945,7 @@ fn test_git_fetch_undo() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&target_jj_repo_path, &["undo"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r#"
-    Undid operation bbebbf6a6ebe 2001-02-03 04:05:18.000 +07:00 - 2001-02-03 04:05:18.000 +07:00 fetch from git remote(s) origin
+    Undid operation e4fc6c0b7ded 2001-02-03 04:05:18.000 +07:00 - 2001-02-03 04:05:18.000 +07:00 fetch from git remote(s) origin
     "#);
     // The undo works as expected
     insta::assert_snapshot!(get_log_output(&test_env, &target_jj_repo_path), @r###"
@@ -1289,3 +1388,39 @@ fn test_git_fetch_remote_only_bookmark() {
     feature2@origin: mzyxwzks 9f01a0e0 message
     "###);
 }
+
+#[test]
+fn test_git_auto_fetch() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    add_git_remote(&test_env, &repo_path, "origin");
+    let last_fetch_marker = repo_path.join(".jj/repo/git_last_fetch");
+
+    // Automatic fetching is off by default
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    assert!(!last_fetch_marker.exists());
+
+    // Read-only commands and --ignore-working-copy don't fetch
+    test_env.add_config("git.auto-fetch = true");
+    test_env.jj_cmd_ok(&repo_path, &["log"]);
+    test_env.jj_cmd_ok(&repo_path, &["status"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "--ignore-working-copy"]);
+    assert!(!last_fetch_marker.exists());
+
+    // A command that changes the repo fetches in the background
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "changed"]);
+    assert!(last_fetch_marker.exists());
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+    loop {
+        let stdout = test_env.jj_cmd_success(
+            &repo_path,
+            &["bookmark", "list", "--all-remotes", "--ignore-working-copy"],
+        );
+        if stdout.contains("origin@origin") {
+            break;
+        }
+        assert!(std::time::Instant::now() < deadline, "{stdout}");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+}
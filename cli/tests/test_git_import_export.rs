@@ -101,7 +101,7 @@ fn test_git_export_undo() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "undo"]);
     insta::assert_snapshot!(stdout, @"");
     insta::assert_snapshot!(stderr, @r#"
-    Undid operation 1bc51ad79d63 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00 export git refs
+    Undid operation 8177b1bf9cee 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00 export git refs
     "#);
     insta::assert_debug_snapshot!(get_git_repo_refs(&git_repo), @r###"
     [
@@ -254,6 +254,102 @@ fn test_git_import_move_export_with_default_undo() {
     "###);
 }
 
+#[test]
+fn test_git_sync() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo = git2::Repository::open(repo_path.join(".jj/repo/store/git")).unwrap();
+
+    // Create bookmark "a" in git repo, and bookmark "b" in jj
+    let commit_id =
+        test_env.jj_cmd_success(&repo_path, &["log", "-Tcommit_id", "--no-graph", "-r@"]);
+    let commit = git_repo
+        .find_commit(git2::Oid::from_str(&commit_id).unwrap())
+        .unwrap();
+    git_repo.branch("a", &commit, true).unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "b"]);
+
+    // Both bookmarks are synced in a single operation
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["git", "sync"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    bookmark: a [new] tracked
+    "###);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &repo_path), @r###"
+    a: qpvuntsm 230dd059 (empty) (no description set)
+      @git: qpvuntsm 230dd059 (empty) (no description set)
+    b: qpvuntsm 230dd059 (empty) (no description set)
+      @git: qpvuntsm 230dd059 (empty) (no description set)
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log", "-n1", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @"@  sync git refs");
+
+    // Nothing to sync
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["git", "sync"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    // Watching requires a colocated repo
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["git", "sync", "--watch"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: `--watch` is only supported in colocated repos
+    Hint: Run `jj git sync` without `--watch` to sync the refs once.
+    "###);
+}
+
+#[test]
+fn test_git_sync_watch() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "--colocate", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+
+    // A branch created by Git, and a file that isn't snapshotted yet
+    let git_repo = git2::Repository::open(&repo_path).unwrap();
+    let head_commit = git_repo.head().unwrap().peel_to_commit().unwrap();
+    git_repo.branch("from-git", &head_commit, false).unwrap();
+    std::fs::write(repo_path.join("file"), "contents").unwrap();
+
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["git", "sync", "--watch", "--max-syncs", "1"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    bookmark: from-git [new] tracked
+    "###);
+    // The working copy wasn't snapshotted
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "-n2",
+            "--no-graph",
+            "--ignore-working-copy",
+            r#"-Tdescription ++ "\n""#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    sync git refs
+    new empty commit
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "--ignore-working-copy",
+            "-r",
+            "from-git",
+            "-Tdescription",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"first");
+}
+
 fn get_bookmark_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     test_env.jj_cmd_success(repo_path, &["bookmark", "list", "--all-remotes"])
 }
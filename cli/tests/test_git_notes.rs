@@ -0,0 +1,190 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_git_notes_add_show_remove() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second"]);
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "notes", "add", "-r@-", "-m", "build: ok"],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "notes", "show", "-r@-"]);
+    insta::assert_snapshot!(stdout, @r###"
+    build: ok
+    "###);
+
+    // The note is shown by the template keyword
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "-T",
+            r#"separate(" ", description.first_line(), git_notes)"#,
+        ],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @  second
+    ○  first build: ok
+    ◆
+    "###);
+
+    // An existing note isn't replaced without --force
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["git", "notes", "add", "-r@-", "-m", "build: failed"],
+    );
+    assert!(stderr.contains("already has a note"), "{stderr}");
+    assert!(stderr.contains("Hint: Use --force"), "{stderr}");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "notes",
+            "add",
+            "-r@-",
+            "-m",
+            "build: failed",
+            "--force",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "notes", "show", "-r@-"]);
+    insta::assert_snapshot!(stdout, @r###"
+    build: failed
+    "###);
+
+    // Notes in other refs are separate
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "notes", "add", "-r@-", "-m", "LGTM", "--ref=review"],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["git", "notes", "show", "-r@-", "--ref=refs/notes/review"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    LGTM
+    "###);
+
+    test_env.jj_cmd_ok(&repo_path, &["git", "notes", "remove", "-r@-"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["git", "notes", "show", "-r@-"]);
+    assert!(
+        stderr.contains("has no note in refs/notes/commits"),
+        "{stderr}"
+    );
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["git", "notes", "add", "-rroot()", "-m", "note"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Cannot attach a note to the root commit
+    "###);
+}
+
+#[test]
+fn test_git_notes_push_fetch() {
+    let test_env = TestEnvironment::default();
+    git2::Repository::init_bare(test_env.env_root().join("origin")).unwrap();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["git", "remote", "add", "origin", "../origin"]);
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "main"]);
+    test_env.jj_cmd_ok(&repo_path, &["git", "push", "--bookmark=main"]);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["git", "notes", "push"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: There are no notes in refs/notes/commits
+    "###);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "notes", "add", "-rmain", "-m", "build: ok"],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["git", "notes", "push"]);
+
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "clone", "origin", "clone"]);
+    let clone_path = test_env.env_root().join("clone");
+    // Notes aren't fetched by default
+    let stderr = test_env.jj_cmd_failure(&clone_path, &["git", "notes", "show", "-rmain@origin"]);
+    assert!(stderr.contains("has no note"), "{stderr}");
+    test_env.jj_cmd_ok(&clone_path, &["git", "notes", "fetch"]);
+    let stdout = test_env.jj_cmd_success(&clone_path, &["git", "notes", "show", "-rmain@origin"]);
+    insta::assert_snapshot!(stdout, @r###"
+    build: ok
+    "###);
+
+    // A fast-forward of the local notes is pushed
+    test_env.jj_cmd_ok(
+        &clone_path,
+        &[
+            "git",
+            "notes",
+            "add",
+            "-rmain@origin",
+            "-m",
+            "deployed",
+            "--force",
+        ],
+    );
+    test_env.jj_cmd_ok(&clone_path, &["git", "notes", "push"]);
+    test_env.jj_cmd_ok(&repo_path, &["git", "notes", "fetch"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "notes", "show", "-rmain"]);
+    insta::assert_snapshot!(stdout, @r###"
+    deployed
+    "###);
+
+    // Diverged notes can't be fetched
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "notes",
+            "add",
+            "-rmain",
+            "-m",
+            "from repo",
+            "--force",
+        ],
+    );
+    test_env.jj_cmd_ok(
+        &clone_path,
+        &[
+            "git",
+            "notes",
+            "add",
+            "-rmain@origin",
+            "-m",
+            "from clone",
+            "--force",
+        ],
+    );
+    test_env.jj_cmd_ok(&clone_path, &["git", "notes", "push"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["git", "notes", "fetch"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Notes ref refs/notes/commits has diverged from the one on remote origin
+    Hint: Merge the notes with `git notes merge`, or remove the local notes ref to use the remote's
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "notes", "show", "-rmain"]);
+    insta::assert_snapshot!(stdout, @r###"
+    from repo
+    "###);
+}
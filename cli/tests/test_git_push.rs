@@ -15,6 +15,8 @@
 use std::path::Path;
 use std::path::PathBuf;
 
+use regex::Regex;
+
 use crate::common::TestEnvironment;
 
 fn set_up() -> (TestEnvironment, PathBuf) {
@@ -90,10 +92,13 @@ fn test_git_push_current_bookmark() {
     // First dry-run. `bookmark1` should not get pushed.
     let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--dry-run"]);
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Move forward bookmark bookmark2 from 8476341eb395 to bc7610b65a91
       Add bookmark my-bookmark to bc7610b65a91
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark2* my-bookmark | (empty) foo
+    Would send 1 commits (1 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
     let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push"]);
@@ -140,6 +145,60 @@ fn test_git_push_current_bookmark() {
     "#);
 }
 
+#[test]
+fn test_git_push_options() {
+    let (test_env, workspace_root) = set_up();
+    test_env.jj_cmd_ok(&workspace_root, &["new", "bookmark1", "-m", "foo"]);
+    test_env.jj_cmd_ok(&workspace_root, &["bookmark", "set", "bookmark1"]);
+    // The options are sent to the remote, but local remotes don't support them
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &["git", "push", "-o", "ci.skip", "--push-option=topic=foo"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to 0c142ff3a34f
+    Error: push-options not supported by remote; class=Invalid (3)
+    "###);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &workspace_root), @r###"
+    bookmark1: vruxwmqv 0c142ff3 (empty) foo
+      @origin (behind by 1 commits): xtvrqkyv d13ecdbd (empty) description 1
+    bookmark2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    "###);
+}
+
+#[test]
+fn test_git_push_signed() {
+    let (test_env, workspace_root) = set_up();
+    test_env.jj_cmd_ok(&workspace_root, &["new", "bookmark1", "-m", "foo"]);
+    test_env.jj_cmd_ok(&workspace_root, &["bookmark", "set", "bookmark1"]);
+    // A signing backend is required to sign the push certificate
+    let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push", "--signed"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to 0c142ff3a34f
+    Error: Cannot sign the push because no signing backend is configured
+    Hint: Set `signing.backend` to `gpg` or `ssh`, and `signing.key` to the key to sign with.
+    "###);
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_root,
+        &["git", "push", "--config-toml=git.sign-on-push=true"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to 0c142ff3a34f
+    Error: Cannot sign the push because no signing backend is configured
+    Hint: Set `signing.backend` to `gpg` or `ssh`, and `signing.key` to the key to sign with.
+    "###);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &workspace_root), @r###"
+    bookmark1: vruxwmqv 0c142ff3 (empty) foo
+      @origin (behind by 1 commits): xtvrqkyv d13ecdbd (empty) description 1
+    bookmark2: rlzusymt 8476341e (empty) description 2
+      @origin: rlzusymt 8476341e (empty) description 2
+    "###);
+}
+
 #[test]
 fn test_git_push_parent_bookmark() {
     let (test_env, workspace_root) = set_up();
@@ -256,6 +315,18 @@ fn test_git_push_forward_unexpectedly_moved() {
     std::fs::write(workspace_root.join("local"), "local").unwrap();
     test_env.jj_cmd_ok(&workspace_root, &["bookmark", "set", "bookmark1"]);
 
+    // Dry-run contacts the remote, and warns that the push would fail
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--dry-run"]);
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to 6750425ff51c
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark1* | local
+    Would send 1 commits (3 objects, [SIZE])
+    Warning: Bookmark bookmark1 unexpectedly moved on the remote. Pushing it would fail.
+    Dry-run requested, not pushing.
+    "#);
+
     // Pushing should fail
     let stderr = test_env.jj_cmd_failure(&workspace_root, &["git", "push"]);
     insta::assert_snapshot!(stderr, @r#"
@@ -304,6 +375,21 @@ fn test_git_push_sideways_unexpectedly_moved() {
     Error: Refusing to push a bookmark that unexpectedly moved on the remote. Affected refs: refs/heads/bookmark1
     Hint: Try fetching from the remote, then make the bookmark point to where you want it to be, and push again.
     "#);
+
+    // Pushing with --force overwrites the remote bookmark
+    let (stdout, stderr) = test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--force"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Changes to push to origin:
+      Move sideways bookmark bookmark1 from d13ecdbda2a2 to 0f8bf988588e
+    "###);
+    test_env.jj_cmd_ok(&origin_path, &["git", "import"]);
+    insta::assert_snapshot!(get_bookmark_output(&test_env, &origin_path), @r###"
+    bookmark1: rqxmnlyz 0f8bf988 local
+      @git: rqxmnlyz 0f8bf988 local
+    bookmark2: zsuskuln 8476341e (empty) description 2
+      @git: zsuskuln 8476341e (empty) description 2
+    "###);
 }
 
 // This tests whether the push checks that the remote bookmarks are in expected
@@ -480,11 +566,14 @@ fn test_git_push_multiple() {
     let (stdout, stderr) =
         test_env.jj_cmd_ok(&workspace_root, &["git", "push", "--all", "--dry-run"]);
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Delete bookmark bookmark1 from d13ecdbda2a2
       Move sideways bookmark bookmark2 from 8476341eb395 to c4a3c3105d92
       Add bookmark my-bookmark to c4a3c3105d92
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark2* my-bookmark | (empty) foo
+    Would send 1 commits (1 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
     // Dry run requesting two specific bookmarks
@@ -493,10 +582,13 @@ fn test_git_push_multiple() {
         &["git", "push", "-b=bookmark1", "-b=my-bookmark", "--dry-run"],
     );
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Delete bookmark bookmark1 from d13ecdbda2a2
       Add bookmark my-bookmark to c4a3c3105d92
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark2* my-bookmark | (empty) foo
+    Would send 1 commits (1 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
     // Dry run requesting two specific bookmarks twice
@@ -513,10 +605,13 @@ fn test_git_push_multiple() {
         ],
     );
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Delete bookmark bookmark1 from d13ecdbda2a2
       Add bookmark my-bookmark to c4a3c3105d92
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark2* my-bookmark | (empty) foo
+    Would send 1 commits (1 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
     // Dry run with glob pattern
@@ -525,10 +620,13 @@ fn test_git_push_multiple() {
         &["git", "push", "-b=glob:bookmark?", "--dry-run"],
     );
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Delete bookmark bookmark1 from d13ecdbda2a2
       Move sideways bookmark bookmark2 from 8476341eb395 to c4a3c3105d92
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark2* my-bookmark | (empty) foo
+    Would send 1 commits (1 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
 
@@ -730,9 +828,13 @@ fn test_git_push_revisions() {
     let (stdout, stderr) =
         test_env.jj_cmd_ok(&workspace_root, &["git", "push", "-r=@-", "--dry-run"]);
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Add bookmark bookmark-1 to 5f432a855e59
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark-1 | bar
+      [CHANGE_ID] [COMMIT_ID] foo
+    Would send 2 commits (6 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
     // Push multiple revisions of which some have bookmarks
@@ -741,20 +843,29 @@ fn test_git_push_revisions() {
         &["git", "push", "-r=@--", "-r=@-", "--dry-run"],
     );
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Warning: No bookmarks point to the specified revisions: @--
     Changes to push to origin:
       Add bookmark bookmark-1 to 5f432a855e59
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark-1 | bar
+      [CHANGE_ID] [COMMIT_ID] foo
+    Would send 2 commits (6 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
     // Push a revision with a multiple bookmarks
     let (stdout, stderr) =
         test_env.jj_cmd_ok(&workspace_root, &["git", "push", "-r=@", "--dry-run"]);
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Add bookmark bookmark-2a to 84f499037f5c
       Add bookmark bookmark-2b to 84f499037f5c
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark-2a bookmark-2b | baz
+      [CHANGE_ID] [COMMIT_ID] bookmark-1 | bar
+      [CHANGE_ID] [COMMIT_ID] foo
+    Would send 3 commits (9 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
     // Repeating a commit doesn't result in repeated messages about the bookmark
@@ -763,9 +874,13 @@ fn test_git_push_revisions() {
         &["git", "push", "-r=@-", "-r=@-", "--dry-run"],
     );
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Add bookmark bookmark-1 to 5f432a855e59
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] bookmark-1 | bar
+      [CHANGE_ID] [COMMIT_ID] foo
+    Would send 2 commits (6 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
 }
@@ -900,9 +1015,13 @@ fn test_git_push_no_description_in_immutable() {
         &["git", "push", "--bookmark=my-bookmark", "--dry-run"],
     );
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Add bookmark my-bookmark to ea7373507ad9
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] my-bookmark | foo
+      [CHANGE_ID] [COMMIT_ID] imm | (empty) (no description set)
+    Would send 2 commits (4 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
 }
@@ -968,9 +1087,14 @@ fn test_git_push_missing_author_in_immutable() {
         &["git", "push", "--bookmark=my-bookmark", "--dry-run"],
     );
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Add bookmark my-bookmark to 68fdae89de4f
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] my-bookmark | foo
+      [CHANGE_ID] [COMMIT_ID] imm | (empty) no author email
+      [CHANGE_ID] [COMMIT_ID] (empty) no author name
+    Would send 3 commits (5 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
 }
@@ -1047,9 +1171,14 @@ fn test_git_push_missing_committer_in_immutable() {
         &["git", "push", "--bookmark=my-bookmark", "--dry-run"],
     );
     insta::assert_snapshot!(stdout, @"");
-    insta::assert_snapshot!(stderr, @r#"
+    insta::assert_snapshot!(filter_push_preview(&stderr), @r#"
     Changes to push to origin:
       Add bookmark my-bookmark to c79f85e90b4a
+    Commits to send:
+      [CHANGE_ID] [COMMIT_ID] my-bookmark | foo
+      [CHANGE_ID] [COMMIT_ID] imm | (empty) no committer email
+      [CHANGE_ID] [COMMIT_ID] (empty) no committer name
+    Would send 3 commits (5 objects, [SIZE])
     Dry-run requested, not pushing.
     "#);
 }
@@ -1311,6 +1440,15 @@ fn test_git_push_to_remote_named_git() {
     "#);
 }
 
+/// Filters out the ids of the commits to send and the pack size from the
+/// output of `jj git push --dry-run`.
+fn filter_push_preview(text: &str) -> String {
+    let commit_regex = Regex::new(r"(?m)^  [k-z]{8} [0-9a-f]{8} ").unwrap();
+    let size_regex = Regex::new(r"\d+\.\d(Ki|Mi|Gi)?B\)").unwrap();
+    let text = commit_regex.replace_all(text, "  [CHANGE_ID] [COMMIT_ID] ");
+    size_regex.replace_all(&text, "[SIZE])").into_owned()
+}
+
 fn get_bookmark_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     // --quiet to suppress deleted bookmarks hint
     test_env.jj_cmd_success(repo_path, &["bookmark", "list", "--all-remotes", "--quiet"])
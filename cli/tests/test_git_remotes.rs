@@ -144,6 +144,200 @@ fn test_git_remote_set_url() {
     "###);
 }
 
+#[test]
+fn test_git_remote_set_push_url() {
+    let test_env = TestEnvironment::default();
+
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "remote", "add", "foo", "http://example.com/repo/foo"],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "remote", "list", "--verbose"]);
+    insta::assert_snapshot!(stdout, @r###"
+    foo http://example.com/repo/foo
+    "###);
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "set-url",
+            "--push",
+            "foo",
+            "http://example.com/repo/push1",
+        ],
+    );
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "set-url",
+            "--push",
+            "--add",
+            "foo",
+            "http://example.com/repo/push2",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "remote", "list", "--verbose"]);
+    insta::assert_snapshot!(stdout, @r###"
+    foo http://example.com/repo/foo
+      Push URL: http://example.com/repo/push1
+      Push URL: http://example.com/repo/push2
+    "###);
+    // The push URLs don't show up without --verbose
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "remote", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    foo http://example.com/repo/foo
+    "###);
+
+    // Without --add, all the push URLs are replaced
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "set-url",
+            "--push",
+            "foo",
+            "http://example.com/repo/push3",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "remote", "list", "--verbose"]);
+    insta::assert_snapshot!(stdout, @r###"
+    foo http://example.com/repo/foo
+      Push URL: http://example.com/repo/push3
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "set-url",
+            "--add",
+            "foo",
+            "http://example.com/repo/push4",
+        ],
+    );
+    assert!(stderr.contains("--push"), "{stderr}");
+}
+
+#[test]
+fn test_git_remote_push_to_multiple_urls() {
+    let test_env = TestEnvironment::default();
+    for name in ["origin", "mirror1", "mirror2"] {
+        git2::Repository::init_bare(test_env.env_root().join(name)).unwrap();
+    }
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let url = |name: &str| test_env.env_root().join(name).to_str().unwrap().to_owned();
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "remote", "add", "origin", &url("origin")],
+    );
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "set-url",
+            "--push",
+            "origin",
+            &url("mirror1"),
+        ],
+    );
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "remote",
+            "set-url",
+            "--push",
+            "--add",
+            "origin",
+            &url("mirror2"),
+        ],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "foo"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "create", "main"]);
+    test_env.jj_cmd_ok(&repo_path, &["git", "push", "--bookmark", "main"]);
+
+    // Both push URLs received the bookmark, but not the fetch URL
+    for name in ["mirror1", "mirror2"] {
+        let git_repo = git2::Repository::open(test_env.env_root().join(name)).unwrap();
+        assert!(git_repo.find_reference("refs/heads/main").is_ok(), "{name}");
+    }
+    let git_repo = git2::Repository::open(test_env.env_root().join("origin")).unwrap();
+    assert!(git_repo.find_reference("refs/heads/main").is_err());
+
+    // The remote bookmark is tracked as if a single URL was pushed to
+    let stdout = test_env.jj_cmd_success(&repo_path, &["bookmark", "list", "--all-remotes"]);
+    insta::assert_snapshot!(stdout.lines().count(), @"2");
+    assert!(stdout.contains("@origin"), "{stdout}");
+}
+
+#[test]
+fn test_git_remote_prune() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "origin"]);
+    let origin_path = test_env.env_root().join("origin");
+    let origin_git_repo_path = origin_path.join(".jj/repo/store/git");
+    test_env.jj_cmd_ok(&origin_path, &["describe", "-m=description 1"]);
+    test_env.jj_cmd_ok(&origin_path, &["bookmark", "create", "bookmark1"]);
+    test_env.jj_cmd_ok(&origin_path, &["new", "root()", "-m=description 2"]);
+    test_env.jj_cmd_ok(&origin_path, &["bookmark", "create", "bookmark2"]);
+    test_env.jj_cmd_ok(&origin_path, &["git", "export"]);
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &[
+            "git",
+            "clone",
+            "--config-toml=git.auto-local-branch=true",
+            origin_git_repo_path.to_str().unwrap(),
+            "local",
+        ],
+    );
+    let local_path = test_env.env_root().join("local");
+    test_env.jj_cmd_ok(&origin_path, &["new", "bookmark1", "-m=description 3"]);
+    test_env.jj_cmd_ok(&origin_path, &["bookmark", "set", "bookmark1"]);
+    test_env.jj_cmd_ok(&origin_path, &["git", "export"]);
+    test_env.jj_cmd_ok(&local_path, &["git", "fetch"]);
+    let stdout = test_env.jj_cmd_success(&local_path, &["git", "remote", "list", "--verbose"]);
+    insta::assert_snapshot!(stdout, @r###"
+    origin $TEST_ENV/origin/.jj/repo/store/git
+      Tracked bookmarks: bookmark1, bookmark2
+      Last fetched: 2001-02-03 04:05:17.000 +07:00
+    "###);
+
+    // Nothing to prune
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&local_path, &["git", "remote", "prune", "origin"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+
+    test_env.jj_cmd_ok(&origin_path, &["bookmark", "delete", "bookmark2"]);
+    test_env.jj_cmd_ok(&origin_path, &["git", "export"]);
+    test_env.jj_cmd_ok(&local_path, &["git", "remote", "prune", "origin"]);
+    let template = r#"name ++ if(remote, "@" ++ remote) ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(
+        &local_path,
+        &["bookmark", "list", "--all-remotes", "-T", template],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    bookmark1
+    bookmark1@origin
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&local_path, &["git", "remote", "prune", "nonexistent"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No git remote named 'nonexistent'
+    "###);
+}
+
 #[test]
 fn test_git_remote_rename() {
     let test_env = TestEnvironment::default();
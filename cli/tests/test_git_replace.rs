@@ -0,0 +1,190 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_git_replace_graft() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "second"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "third"]);
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    let log_template = r#"description.first_line()"#;
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "replace", "list"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // The replaced commit keeps its id but gets the new parents
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "replace",
+            "graft",
+            "description(third)",
+            "description(first)",
+        ],
+    );
+    assert!(stderr.starts_with("Replaced commit "), "{stderr}");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    ○  third
+    ○  first
+    ◆
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "replace", "list"]);
+    assert_eq!(stdout.lines().count(), 1, "{stdout}");
+    assert!(stdout.contains(" -> "), "{stdout}");
+
+    // An existing replacement isn't overwritten without --force
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["git", "replace", "graft", "description(third)", "root()"],
+    );
+    assert!(stderr.contains("is already replaced"), "{stderr}");
+    assert!(stderr.contains("Hint: Use --force"), "{stderr}");
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "replace",
+            "graft",
+            "description(third)",
+            "root()",
+            "--force",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    ○  third
+    ◆
+    "###);
+
+    // Removing the replacement restores the original history
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "replace", "remove", "description(third)"],
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", log_template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    ○  third
+    ○  second
+    ○  first
+    ◆
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "replace", "list"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["git", "replace", "remove", "description(third)"],
+    );
+    assert!(stderr.contains("is not replaced"), "{stderr}");
+}
+
+#[test]
+fn test_git_replace_add() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "old"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "child"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "new"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "description(child)"]);
+    let commit_id = |revision| {
+        test_env.jj_cmd_success(
+            &repo_path,
+            &["log", "--no-graph", "-r", revision, "-T", "commit_id"],
+        )
+    };
+    let old_id = commit_id("description(old)");
+    let child_id = commit_id("description(child)");
+
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &[
+            "git",
+            "replace",
+            "add",
+            "description(old)",
+            "description(new)",
+        ],
+    );
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-r", "::@", "-T", "description.first_line()"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    ○  child
+    ○  new
+    ◆
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "git",
+            "replace",
+            "add",
+            "description(child)",
+            "description(child)",
+        ],
+    );
+    assert!(stderr.contains("cannot replace itself"), "{stderr}");
+
+    // Replacements made by `git replace` are used as well, and the index is
+    // rebuilt when they change
+    let git_repo = git2::Repository::open(repo_path.join(".jj/repo/store/git")).unwrap();
+    assert!(git_repo
+        .find_reference(&format!("refs/replace/{old_id}"))
+        .is_ok());
+    let mut git_ref = git_repo
+        .reference(
+            &format!("refs/replace/{child_id}"),
+            git2::Oid::from_str(&old_id).unwrap(),
+            false,
+            "",
+        )
+        .unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-r", "::@", "-T", "description.first_line()"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    ○  old
+    ◆
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["git", "replace", "list"]);
+    assert_eq!(stdout.lines().count(), 2, "{stdout}");
+
+    git_ref.delete().unwrap();
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "-r", "::@", "-T", "description.first_line()"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    @
+    ○  child
+    ○  new
+    ◆
+    "###);
+}
@@ -63,3 +63,185 @@ fn test_gitsubmodule_print_gitmodules() {
 	path:new
     "###);
 }
+
+/// Creates a commit with a `.gitmodules` file for `url` and a submodule
+/// pointing to `submodule_oid` at "sub".
+fn write_submodule_commit(
+    git_repo: &git2::Repository,
+    parent: Option<&git2::Commit>,
+    url: &str,
+    submodule_oid: git2::Oid,
+) -> git2::Oid {
+    let gitmodules = format!("[submodule \"sub\"]\n\tpath = sub\n\turl = {url}\n");
+    let gitmodules_oid = git_repo.blob(gitmodules.as_bytes()).unwrap();
+    let mut tree_builder = git_repo.treebuilder(None).unwrap();
+    tree_builder
+        .insert(".gitmodules", gitmodules_oid, 0o100644)
+        .unwrap();
+    tree_builder.insert("sub", submodule_oid, 0o160000).unwrap();
+    let tree_oid = tree_builder.write().unwrap();
+    let tree = git_repo.find_tree(tree_oid).unwrap();
+    let signature =
+        git2::Signature::new("Some One", "some.one@example.com", &git2::Time::new(0, 0)).unwrap();
+    git_repo
+        .commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            "message",
+            &tree,
+            &parent.into_iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_gitsubmodule_list_and_diff() {
+    let test_env = TestEnvironment::default();
+    let git_repo_path = test_env.env_root().join("git-repo");
+    let git_repo = git2::Repository::init_bare(&git_repo_path).unwrap();
+    let old_oid = git2::Oid::from_str("1111111111111111111111111111111111111111").unwrap();
+    let new_oid = git2::Oid::from_str("2222222222222222222222222222222222222222").unwrap();
+    let url = "https://example.com/sub.git";
+    let commit1_oid = write_submodule_commit(&git_repo, None, url, old_oid);
+    let commit1 = git_repo.find_commit(commit1_oid).unwrap();
+    write_submodule_commit(&git_repo, Some(&commit1), url, new_oid);
+
+    test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["git", "init", "repo", "--git-repo", "git-repo"],
+    );
+    let workspace_root = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&workspace_root, &["new", "main"]);
+
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "submodule", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    222222222222 sub (https://example.com/sub.git)
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &workspace_root,
+        &["git", "submodule", "list", "-r", "main-"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    111111111111 sub (https://example.com/sub.git)
+    "###);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_root,
+        &["git", "submodule", "list", "-r", "root()"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    No submodules.
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--git", "-r", "main"]);
+    insta::assert_snapshot!(stdout, @r###"
+    diff --git a/sub b/sub
+    index 1111111111..2222222222 160000
+    --- a/sub
+    +++ b/sub
+    @@ -1,1 +1,1 @@
+    -Subproject commit 1111111111111111111111111111111111111111
+    +Subproject commit 2222222222222222222222222222222222222222
+    "###);
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["diff", "--types", "-r", "main"]);
+    insta::assert_snapshot!(stdout, @r###"
+    GG sub
+    "###);
+}
+
+/// Creates a commit on the "main" branch of `git_repo` with a single file.
+fn write_file_commit(
+    git_repo: &git2::Repository,
+    parent: Option<&git2::Commit>,
+    contents: &str,
+) -> git2::Oid {
+    let blob_oid = git_repo.blob(contents.as_bytes()).unwrap();
+    let mut tree_builder = git_repo.treebuilder(None).unwrap();
+    tree_builder.insert("file", blob_oid, 0o100644).unwrap();
+    let tree_oid = tree_builder.write().unwrap();
+    let tree = git_repo.find_tree(tree_oid).unwrap();
+    let signature =
+        git2::Signature::new("Some One", "some.one@example.com", &git2::Time::new(0, 0)).unwrap();
+    git_repo
+        .commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            contents,
+            &tree,
+            &parent.into_iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+}
+
+#[test]
+fn test_gitsubmodule_clone_recursive_status_and_update() {
+    let test_env = TestEnvironment::default();
+    let sub_repo_path = test_env.env_root().join("sub");
+    let sub_repo = git2::Repository::init_bare(&sub_repo_path).unwrap();
+    let sub_commit1_oid = write_file_commit(&sub_repo, None, "v1\n");
+    let sub_commit1 = sub_repo.find_commit(sub_commit1_oid).unwrap();
+    let sub_commit2_oid = write_file_commit(&sub_repo, Some(&sub_commit1), "v2\n");
+    sub_repo.set_head("refs/heads/main").unwrap();
+
+    let super_repo_path = test_env.env_root().join("super");
+    let super_repo = git2::Repository::init_bare(&super_repo_path).unwrap();
+    write_submodule_commit(
+        &super_repo,
+        None,
+        sub_repo_path.to_str().unwrap(),
+        sub_commit1_oid,
+    );
+    super_repo.set_head("refs/heads/main").unwrap();
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(
+        test_env.env_root(),
+        &["git", "clone", "--recursive", "super", "clone"],
+    );
+    let workspace_root = test_env.env_root().join("clone");
+    assert!(
+        stderr.contains("Submodule clone/sub: checked out"),
+        "{stderr}"
+    );
+    assert_eq!(
+        std::fs::read_to_string(workspace_root.join("sub").join("file")).unwrap(),
+        "v1\n"
+    );
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "submodule", "status"]);
+    assert_eq!(stdout, format!(" {:.12} sub\n", sub_commit1_oid));
+
+    // Move the submodule to another commit
+    let submodule_repo =
+        git2::Repository::open(workspace_root.join(".jj/repo/store/git/modules/sub")).unwrap();
+    submodule_repo
+        .set_workdir(&workspace_root.join("sub"), false)
+        .unwrap();
+    submodule_repo.set_head_detached(sub_commit2_oid).unwrap();
+    submodule_repo
+        .checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(workspace_root.join("sub").join("file")).unwrap(),
+        "v2\n"
+    );
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "submodule", "status"]);
+    assert_eq!(stdout, format!("+{:.12} sub\n", sub_commit2_oid));
+
+    // The submodule is checked out at the recorded commit again
+    test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    assert_eq!(
+        std::fs::read_to_string(workspace_root.join("sub").join("file")).unwrap(),
+        "v1\n"
+    );
+    let stdout = test_env.jj_cmd_success(&workspace_root, &["git", "submodule", "status"]);
+    assert_eq!(stdout, format!(" {:.12} sub\n", sub_commit1_oid));
+
+    // The submodule is cloned again if its working copy is removed
+    std::fs::remove_dir_all(workspace_root.join("sub")).unwrap();
+    test_env.jj_cmd_ok(&workspace_root, &["git", "submodule", "update"]);
+    assert_eq!(
+        std::fs::read_to_string(workspace_root.join("sub").join("file")).unwrap(),
+        "v1\n"
+    );
+}
@@ -609,7 +609,9 @@ fn test_help() {
           --ignore-working-copy          Don't snapshot the working copy, and don't update it
           --ignore-immutable             Allow rewriting immutable commits
           --at-operation <AT_OPERATION>  Operation to load the repo at [aliases: at-op]
+          --wait-for-lock <TIMEOUT>      Give up waiting for another jj process after this long
           --debug                        Enable debug logging
+          --profile[=<FILE>]             Print how long the phases of the command took
           --color <WHEN>                 When to colorize output (always, never, debug, auto)
           --quiet                        Silence non-primary command output
           --no-pager                     Disable the pager
@@ -635,3 +637,42 @@ fn test_debug_logging_enabled() {
     // Luckily, insta will print this in colour when reviewing.
     insta::assert_snapshot!(log_line, @"[32m INFO[0m [2mjj_cli::cli_util[0m[2m:[0m debug logging enabled");
 }
+
+#[test]
+fn test_profile() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    // The summary is printed to stderr, after the command's output
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["log", "-r@", "--no-graph", "-Tcommit_id", "--profile"],
+    );
+    insta::assert_snapshot!(stdout, @"230dd059e1b059aefc0da06a2e5a7dbf22362f22");
+    let lines = stderr.lines().collect::<Vec<_>>();
+    assert!(lines[0].starts_with("Profile of "), "{stderr}");
+    assert_eq!(
+        lines[1].split_whitespace().collect::<Vec<_>>(),
+        ["Total", "Self", "Calls", "Span"]
+    );
+    assert!(
+        lines
+            .iter()
+            .any(|line| line.ends_with(" jj_cli::commands::log::cmd_log")),
+        "{stderr}"
+    );
+
+    // With a file name, a Chrome trace is written instead
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["log", "--profile=trace.json"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Wrote profile to trace.json
+    "###);
+    let trace: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(repo_path.join("trace.json")).unwrap()).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+    assert!(
+        events.iter().any(|event| event["name"] == "cmd_log"),
+        "{trace}"
+    );
+}
@@ -0,0 +1,153 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use crate::common::TestEnvironment;
+
+fn init_repo(test_env: &TestEnvironment) -> PathBuf {
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("a.txt"), "foo\nbar\nbaz\n").unwrap();
+    std::fs::write(repo_path.join("b.txt"), "Foo\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+    std::fs::write(repo_path.join("a.txt"), "foo\nbar\nqux\nfoo\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "second"]);
+    repo_path
+}
+
+#[test]
+fn test_grep() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    let revisions = "description(first) | description(second)";
+
+    // Matches are grouped by revision
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", revisions, "foo"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    a.txt:1:foo
+    a.txt:4:foo
+
+    first
+    a.txt:1:foo
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", revisions, "-i", "foo"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    a.txt:1:foo
+    a.txt:4:foo
+    b.txt:1:Foo
+
+    first
+    a.txt:1:foo
+    b.txt:1:Foo
+    "###);
+
+    // Smart case is only case-insensitive for lowercase patterns
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", "@-", "-S", "Foo"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    b.txt:1:Foo
+    "###);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", revisions, "-l", "-i", "foo"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    a.txt
+    b.txt
+
+    first
+    a.txt
+    b.txt
+    "###);
+
+    // Only the given paths are searched
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", "@-", "-i", "foo", "b.txt"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    b.txt:1:Foo
+    "###);
+
+    // The working-copy commit is searched by default, and revisions without
+    // matches are omitted
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "working copy"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "qux"]);
+    insta::assert_snapshot!(stdout, @r###"
+    working copy
+    a.txt:3:qux
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", revisions, "qux"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    a.txt:3:qux
+    "###);
+}
+
+#[test]
+fn test_grep_fixed_strings() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", "@-", "b.r"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    a.txt:2:bar
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", "@-", "-F", "b.r"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["grep", "("]);
+    assert!(
+        stderr.starts_with("Error: Invalid pattern: (\n"),
+        "{stderr}"
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-F", "("]);
+    insta::assert_snapshot!(stdout, @"");
+}
+
+#[test]
+fn test_grep_context() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", "@-", "-C1", "qux"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    a.txt-2-bar
+    a.txt:3:qux
+    a.txt-4-foo
+    "###);
+
+    // Groups of lines that aren't adjacent are separated
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", "@-", "-A1", "foo"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    a.txt:1:foo
+    a.txt-2-bar
+    --
+    a.txt:4:foo
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["grep", "-r", "@-", "-B2", "foo"]);
+    insta::assert_snapshot!(stdout, @r###"
+    second
+    a.txt:1:foo
+    a.txt-2-bar
+    a.txt-3-qux
+    a.txt:4:foo
+    "###);
+}
@@ -753,6 +753,20 @@ fn test_log_divergence() {
     insta::assert_snapshot!(stderr, @r###"
     Concurrent modification detected, resolving automatically.
     "###);
+
+    // Other versions of the divergent change
+    let template = r#"
+    description.first_line() ++ " " ++ hidden ++ " ["
+      ++ divergent_versions.map(|c| c.description().first_line()).join(", ")
+      ++ "]"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    ○  description 2 false [description 1]
+    │ @  description 1 false [description 2]
+    ├─╯
+    ◆   false []
+    "###);
 }
 
 #[test]
@@ -892,6 +906,53 @@ fn test_log_filtered_by_path() {
     "###);
 }
 
+#[test]
+fn test_log_follow_renames() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("a"), "1\n2\n3\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "add a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "rename a to b"]);
+    std::fs::remove_file(repo_path.join("a")).unwrap();
+    std::fs::write(repo_path.join("b"), "1\n2\n3\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "modify b"]);
+    std::fs::write(repo_path.join("b"), "1\n2\n3\n4\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "add c"]);
+    std::fs::write(repo_path.join("c"), "c\n").unwrap();
+
+    let template = r#"description.first_line() ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template, "b"]);
+    insta::assert_snapshot!(stdout, @r###"
+    modify b
+    rename a to b
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-T", template, "--follow", "b"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    modify b
+    rename a to b
+    add a
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-T", template, "-r", "follows(b)"],
+    );
+    insta::assert_snapshot!(stdout, @r###"
+    modify b
+    rename a to b
+    add a
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["log", "--follow", "a", "b"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: `--follow` requires exactly one path
+    "###);
+}
+
 #[test]
 fn test_log_limit() {
     let test_env = TestEnvironment::default();
@@ -974,6 +1035,61 @@ fn test_log_limit() {
     "###);
 }
 
+#[test]
+fn test_log_more_commits_than_prefetched() {
+    // Commits are read ahead in chunks. Test that all of them are rendered in
+    // order, with and without the parents being read ahead.
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let num_commits = 40;
+    for i in 1..=num_commits {
+        if i % 2 == 0 {
+            std::fs::write(repo_path.join("file"), format!("{i}\n")).unwrap();
+        }
+        test_env.jj_cmd_ok(&repo_path, &["commit", "-m", &format!("commit {i}")]);
+    }
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "..@-", "-T", "description"],
+    );
+    let expected = (1..=num_commits)
+        .rev()
+        .map(|i| format!("commit {i}\n"))
+        .collect::<String>();
+    assert_eq!(stdout, expected);
+
+    let template = r#"description.first_line() ++ if(empty, " (empty)") ++ "\n""#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r", "..@-", "-T", template]);
+    let expected = (1..=num_commits)
+        .rev()
+        .map(|i| {
+            let empty = if i % 2 == 0 { "" } else { " (empty)" };
+            format!("○  commit {i}{empty}\n")
+        })
+        .collect::<String>();
+    assert_eq!(stdout, expected + "│\n~\n");
+
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            "..@-",
+            "-T",
+            template,
+            "--summary",
+        ],
+    );
+    assert_eq!(stdout.lines().filter(|line| *line == "A file").count(), 1);
+    assert_eq!(
+        stdout.lines().filter(|line| *line == "M file").count(),
+        num_commits / 2 - 1
+    );
+}
+
 #[test]
 fn test_log_warn_path_might_be_revset() {
     let test_env = TestEnvironment::default();
@@ -1168,14 +1284,92 @@ fn test_graph_template_color() {
     "###);
     let stdout = test_env.jj_cmd_success(&repo_path, &["--color=debug", "log", "-T", template]);
     insta::assert_snapshot!(stdout, @r###"
-    [1m[38;5;2m<<node working_copy::@>>[0m  [1m[38;5;2m<<log working_copy description::single line>>[0m
-    <<node::○>>  [38;5;1m<<log description::first line>>[39m
-    │  [38;5;1m<<log description::second line>>[39m
-    │  [38;5;1m<<log description::third line>>[39m
+    [1m[38;5;2m<<node working_copy::@>>[0m<<edge::  >>[1m[38;5;2m<<log working_copy description::single line>>[0m
+    <<node::○>><<edge::  >>[38;5;1m<<log description::first line>>[39m
+    <<edge::│  >>[38;5;1m<<log description::second line>>[39m
+    <<edge::│  >>[38;5;1m<<log description::third line>>[39m
     [1m[38;5;14m<<node immutable::◆>>[0m
     "###);
 }
 
+#[test]
+fn test_graph_content_and_node_per_commit() {
+    // Test that the content and node of a commit don't carry over to the next
+    // commits, whose output is shorter and differently colored.
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "x"]);
+    test_env.jj_cmd_ok(
+        &repo_path,
+        &["new", "-m", "a much longer description\nwith two lines"],
+    );
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "short"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "side"]);
+
+    test_env.add_config(
+        r#"
+        templates.log_node = 'if(current_working_copy, "@@", if(root, "^", "*"))'
+        [colors]
+        description = "red"
+        "working_copy description" = "green"
+        "#,
+    );
+    let template = r#"label(if(current_working_copy, "working_copy"), description)"#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @@  side
+    │ *  short
+    │ *  a much longer description
+    │ │  with two lines
+    │ *  x
+    ├─╯
+    ^
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["--color=always", "log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    @@  [1m[38;5;2mside[0m
+    │ *  [38;5;1mshort[39m
+    │ *  [38;5;1ma much longer description[39m
+    │ │  [38;5;1mwith two lines[39m
+    │ *  [38;5;1mx[39m
+    ├─╯
+    ^
+    "###);
+}
+
+#[test]
+fn test_graph_edge_color() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "b"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "c", "@-"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "merge", "@", "@-"]);
+    test_env.add_config(
+        r#"
+        colors.edge = "blue"
+        templates.log_node = 'if(self.contained_in("merges()"), "M", builtin_log_node)'
+        "#,
+    );
+
+    let template = r#"description.first_line()"#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["--color=always", "log", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    M[38;5;4m    [39mmerge
+    [38;5;4m├─╮[39m
+    ○[38;5;4m │  [39mc
+    [38;5;4m├─╯[39m
+    [38;5;4m│ [39m○[38;5;4m  [39mb
+    [38;5;4m├─╯[39m
+    ○[38;5;4m  [39ma
+    [1m[38;5;14m◆[0m
+    "###);
+}
+
 #[test]
 fn test_graph_styles() {
     // Test that different graph styles are available.
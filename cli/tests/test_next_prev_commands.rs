@@ -471,6 +471,33 @@ fn test_prev_on_merge_commit() {
     "###);
 }
 
+#[test]
+fn test_prev_on_merge_commit_with_bookmark_filter() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["desc", "-m", "first"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "c", "left"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "second"]);
+    test_env.jj_cmd_ok(&repo_path, &["bookmark", "c", "right"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "left", "right"]);
+
+    // The bookmark filter picks one of the parents without prompting
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["prev", "--edit", "--bookmark", "right"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr,@r###"
+    Working copy now at: zsuskuln b0d21db3 right | (empty) second
+    Parent commit      : zzzzzzzz 00000000 (empty) (no description set)
+    "###);
+
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["prev", "--edit", "--bookmark", "glob:x*"]);
+    assert!(stderr.starts_with(
+        "Error: No ancestor found 1 commit(s) back descending from a bookmark matching x*\n"
+    ));
+}
+
 #[test]
 fn test_prev_on_merge_commit_with_parent_merge() {
     let test_env = TestEnvironment::default();
@@ -0,0 +1,102 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::common::TestEnvironment;
+
+#[cfg(unix)]
+#[test]
+fn test_post_operation_hook() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"hooks.post-operation = ["sh", "-c", "cat > ../input; echo \"$JJ_HOOK $JJ_OP_ID\" > ../env"]"#,
+    );
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    let op_id = test_env.jj_cmd_success(
+        &repo_path,
+        &[
+            "op",
+            "log",
+            "--no-graph",
+            "-n1",
+            "-Tid",
+            "--ignore-working-copy",
+        ],
+    );
+    let env = std::fs::read_to_string(test_env.env_root().join("env")).unwrap();
+    assert_eq!(env, format!("post-operation {op_id}\n"));
+    let input: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(test_env.env_root().join("input")).unwrap()).unwrap();
+    assert_eq!(input["hook"], "post-operation");
+    assert_eq!(input["id"], op_id.as_str());
+    assert_eq!(input["username"], "test-username");
+    assert!(input["description"]
+        .as_str()
+        .unwrap()
+        .starts_with("describe commit "));
+    assert_eq!(input["args"][1], "describe");
+    assert_eq!(input["workspace_id"], "default");
+    assert_eq!(input["exit_status"], 0);
+    assert!(input["tags"].get("args").is_none());
+
+    // A failing hook only produces a warning
+    test_env.add_config(r#"hooks.post-operation = ["false"]"#);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "second"]);
+    assert!(
+        stderr.contains("Warning: Post-operation hook 'false' failed (exit status: 1)"),
+        "{stderr}"
+    );
+    let stdout =
+        test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-r@", "-Tdescription"]);
+    insta::assert_snapshot!(stdout, @"second");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pre_operation_hook() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"hooks.pre-operation = ["sh", "-c", "grep -q '\"description\":\"describe' && test \"$JJ_HOOK\" = pre-operation"]"#,
+    );
+
+    // The hook allows the operation
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "first"]);
+    // The hook rejects the operation, which isn't committed
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["new"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Pre-operation hook 'sh' rejected the operation (exit status: 1)
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["op", "log", "--no-graph", "-n1", "-Tdescription"],
+    );
+    assert!(stdout.starts_with("describe commit "), "{stdout}");
+
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &[
+            "describe",
+            "-m",
+            "second",
+            "--config-toml=hooks.pre-operation='nonexistent-hook'",
+        ],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to run pre-operation hook 'nonexistent-hook'
+    Caused by: No such file or directory (os error 2)
+    "###);
+}
@@ -2454,6 +2454,195 @@ fn test_rebase_skip_if_on_destination() {
     "###);
 }
 
+#[test]
+fn test_rebase_interactive() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(
+        r#"templates.commit_summary = 'if(description, description.first_line(), "(no description)")'"#,
+    );
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &["a"]);
+    create_commit(&test_env, &repo_path, "c", &["b"]);
+    create_commit(&test_env, &repo_path, "d", &["c"]);
+    let edit_script = test_env.set_up_fake_editor();
+
+    // The plan lists the revisions parents first
+    let [b, c, d] = ["b", "c", "d"].map(|rev| get_short_commit_id(&test_env, &repo_path, rev));
+    std::fs::write(
+        &edit_script,
+        [
+            "dump plan",
+            &format!("write\npick {c} c\npick {b} b\nfixup {d} d\n"),
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["rebase", "-i", "-s", "b", "-d", "a"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 2 commits
+    Squashed 1 commits
+    Working copy now at: (no description)
+    Parent commit      : b
+    "###);
+    let plan = std::fs::read_to_string(test_env.env_root().join("plan")).unwrap();
+    assert_eq!(
+        plan.lines().take(4).collect::<Vec<_>>(),
+        [
+            format!("pick {b} b"),
+            format!("pick {c} c"),
+            format!("pick {d} d"),
+            "".to_owned()
+        ]
+    );
+    insta::assert_snapshot!(get_description_log_output(&test_env, &repo_path), @r###"
+    @  (no description): b
+    ○  b: c
+    ○  c: a
+    ○  a
+    ◆  root
+    "###);
+
+    // Revisions can be reworded and dropped
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    std::fs::write(
+        &edit_script,
+        [
+            &format!("write\npick {b}\nreword {c}\ndrop {d}\n"),
+            "next invocation\n",
+            "write\nnew c\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["rebase", "-i", "-d", "a"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 2 commits
+    Abandoned 1 commits
+    Working copy now at: (no description)
+    Parent commit      : new c
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+    insta::assert_snapshot!(get_description_log_output(&test_env, &repo_path), @r###"
+    @  (no description): new c
+    ○  new c: b
+    ○  b: a
+    ○  a
+    ◆  root
+    "###);
+
+    // Descendants outside the rebased revisions follow them
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    std::fs::write(&edit_script, format!("write\npick {c}\npick {b}\n")).unwrap();
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["rebase", "-i", "-r", "b|c", "-d", "a"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 2 commits
+    Rebased 1 descendant commits
+    Working copy now at: d
+    Parent commit      : c
+    Added 0 files, modified 0 files, removed 1 files
+    "###);
+    insta::assert_snapshot!(get_description_log_output(&test_env, &repo_path), @r###"
+    @  d: c
+    │ ○  b: c
+    ├─╯
+    ○  c: a
+    ○  a
+    ◆  root
+    "###);
+}
+
+#[test]
+fn test_rebase_interactive_conflict() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "x"]);
+    std::fs::write(repo_path.join("file"), "1\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "y"]);
+    std::fs::write(repo_path.join("file"), "2\n").unwrap();
+    let edit_script = test_env.set_up_fake_editor();
+
+    // The conflict in the reordered revision is resolved again by the next one
+    let [x, y] = ["@-", "@"].map(|rev| get_short_commit_id(&test_env, &repo_path, rev));
+    std::fs::write(&edit_script, format!("write\npick {y}\npick {x}\n")).unwrap();
+    let (_stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["rebase", "-i", "-r", "@-::@", "-d", "root()"]);
+    assert!(
+        stderr.starts_with("Rebased 2 commits\nNew conflicts appeared in these commits:\n  y\n"),
+        "{stderr}"
+    );
+    insta::assert_snapshot!(get_description_log_output(&test_env, &repo_path), @r###"
+    ○  x: y
+    @  y conflict
+    ◆  root
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "@+", "file"]);
+    insta::assert_snapshot!(stdout, @r###"
+    2
+    "###);
+}
+
+#[test]
+fn test_rebase_interactive_invalid() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(&test_env, &repo_path, "a", &[]);
+    create_commit(&test_env, &repo_path, "b", &[]);
+    create_commit(&test_env, &repo_path, "c", &["a", "b"]);
+    let edit_script = test_env.set_up_fake_editor();
+
+    // Merge commits can't be rebased interactively
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["rebase", "-i", "-r", "c", "-d", "a"]);
+    assert!(
+        stderr.starts_with("Error: Cannot rebase merge commit"),
+        "{stderr}"
+    );
+
+    // Errors in the plan are reported
+    std::fs::write(&edit_script, "write\nedit 123\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["rebase", "-i", "-r", "b", "-d", "a"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Unknown command "edit" in rebase plan
+    "###);
+    std::fs::write(&edit_script, "write\npick 123\n").unwrap();
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["rebase", "-i", "-r", "b", "-d", "a"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Revision "123" in rebase plan is not one of the revisions being rebased
+    "###);
+
+    // An empty plan does nothing
+    std::fs::write(&edit_script, "write\n").unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["rebase", "-i", "-r", "b", "-d", "a"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing to rebase: the plan is empty.
+    "###);
+}
+
+fn get_short_commit_id(test_env: &TestEnvironment, repo_path: &Path, rev: &str) -> String {
+    test_env.jj_cmd_success(
+        repo_path,
+        &["log", "--no-graph", "-r", rev, "-T", "commit_id.short()"],
+    )
+}
+
+fn get_description_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
+    let template = r#"
+    if(root, "root",
+      separate(" ",
+        if(description, description.first_line(), "(no description)"),
+        if(conflict, "conflict"),
+      ) ++ surround(": ", "", parents.map(|c| c.description().first_line()))
+    )"#;
+    test_env.jj_cmd_success(repo_path, &["log", "-T", template])
+}
+
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     let template = "bookmarks ++ surround(': ', '', parents.map(|c| c.bookmarks()))";
     test_env.jj_cmd_success(repo_path, &["log", "-T", template])
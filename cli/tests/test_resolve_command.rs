@@ -981,3 +981,216 @@ fn test_multiple_conflicts() {
     Error: No conflicts found at this revision
     "###);
 }
+
+#[test]
+fn test_learned_resolutions() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("merge.learn-resolutions = true");
+
+    create_commit(&test_env, &repo_path, "base", &[], &[("file", "base\n")]);
+    create_commit(&test_env, &repo_path, "a", &["base"], &[("file", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["base"], &[("file", "b\n")]);
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    // Resolving the conflict in the working copy records the resolution
+    std::fs::write(repo_path.join("file"), "resolution\n").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["debug", "snapshot"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Learned the resolutions of 1 conflicted hunks
+    "###);
+
+    // The same conflict is resolved when rebasing one side onto the other
+    test_env.jj_cmd_ok(&repo_path, &["rebase", "-r", "b", "-d", "a"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "b", "file"]), @r###"
+    resolution
+    "###);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["resolve", "--forget-learned"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Forgot 1 learned resolutions
+    "###);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["resolve", "--forget-learned"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Forgot 0 learned resolutions
+    "###);
+}
+
+#[test]
+fn test_merge_drivers() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+        [merge.drivers.changelog]
+        patterns = ["glob:'**/CHANGELOG'"]
+        strategy = "union"
+        "#,
+    );
+
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[("CHANGELOG", "base\n"), ("file", "base\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("CHANGELOG", "a\nbase\n"), ("file", "a\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("CHANGELOG", "b\nbase\n"), ("file", "b\n")],
+    );
+
+    // Only the conflict in the file matching the patterns is resolved
+    test_env.jj_cmd_ok(&repo_path, &["rebase", "-r", "b", "-d", "a"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "b", "CHANGELOG"]), @r###"
+    a
+    b
+    base
+    "###);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["resolve", "--list", "-r", "b"]), @r###"
+    file    2-sided conflict
+    "###);
+
+    // A driver needs either a strategy or a command
+    test_env.add_config(
+        r#"
+        [merge.drivers.invalid]
+        patterns = ["all()"]
+        "#,
+    );
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["rebase", "-r", "b", "-d", "base"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: Merge driver `invalid` must have either a `strategy` or a `command`
+    For help, see https://martinvonz.github.io/jj/latest/config/.
+    "###);
+}
+
+#[test]
+fn test_resolve_mode_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    // The file is added with the same contents on both sides, but is only
+    // executable on one side
+    create_commit(&test_env, &repo_path, "base", &[], &[]);
+    create_commit(&test_env, &repo_path, "n", &["base"], &[("file", "a\n")]);
+    create_commit(&test_env, &repo_path, "x", &["base"], &[("file", "a\n")]);
+    test_env.jj_cmd_ok(&repo_path, &["file", "chmod", "x", "file", "-r=x"]);
+    create_commit(&test_env, &repo_path, "conflict", &["n", "x"], &[]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]), @r###"
+    file    mode conflict 644 vs 755
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["resolve", "--take-mode-from", "3"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The mode conflict at file has no side #3
+    "###);
+
+    test_env.jj_cmd_ok(&repo_path, &["resolve", "--take-mode-from", "2"]);
+    insta::assert_snapshot!(test_env.jj_cmd_success(&repo_path, &["debug", "tree"]), @r###"
+    file: Ok(Resolved(Some(File { id: FileId("78981922613b2afb6025042ff6bd878ac1994e85"), executable: true })))
+    "###);
+
+    // Other conflicts are left to merge tools
+    test_env.jj_cmd_ok(&repo_path, &["new", "n"]);
+    std::fs::write(repo_path.join("file"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "@", "x"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]), @r###"
+    file    2-sided conflict including an executable
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["resolve", "--take-mode-from", "1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No mode conflicts found
+    "###);
+}
+
+#[test]
+fn test_resolve_take_side() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit(
+        &test_env,
+        &repo_path,
+        "base",
+        &[],
+        &[("file", "base\n"), ("generated", "base\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &["base"],
+        &[("file", "a\n"), ("generated", "a\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["base"],
+        &[("file", "b\n"), ("generated", "b\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "c",
+        &["base"],
+        &[("file", "c\n"), ("generated", "c\n")],
+    );
+    create_commit(&test_env, &repo_path, "conflict", &["a", "b"], &[]);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["resolve", "--take", "3"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: The conflict at file has no side #3
+    "###);
+
+    // Only the conflicts at the given paths are resolved
+    test_env.jj_cmd_ok(&repo_path, &["resolve", "--take", "2", "generated"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["file", "show", "generated"]), @r###"
+    b
+    "###);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["resolve", "--list"]), @r###"
+    file    2-sided conflict
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["resolve", "--take", "ours"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["file", "show", "file"]), @r###"
+    a
+    "###);
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["resolve", "--list"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No conflicts found at this revision
+    "###);
+
+    // The sides of multi-way conflicts can be taken too
+    create_commit(&test_env, &repo_path, "conflict3", &["a", "b", "c"], &[]);
+    test_env.jj_cmd_ok(&repo_path, &["resolve", "--take", "theirs"]);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["file", "show", "file", "generated"]), @r###"
+    c
+    c
+    "###);
+}
@@ -261,6 +261,79 @@ fn test_restore_conflicted_merge() {
     "###);
 }
 
+#[test]
+fn test_restore_interactive() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    // Keep the status messages free of commit IDs
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let repo_path = test_env.env_root().join("repo");
+    create_commit(
+        &test_env,
+        &repo_path,
+        "a",
+        &[],
+        &[("file1", "a\n"), ("file2", "a\n")],
+    );
+    create_commit(
+        &test_env,
+        &repo_path,
+        "b",
+        &["a"],
+        &[("file1", "b\n"), ("file2", "b\n")],
+    );
+
+    // Only the selected changes are restored
+    let edit_script = test_env.set_up_fake_diff_editor();
+    std::fs::write(
+        &edit_script,
+        ["dump JJ-INSTRUCTIONS instrs", "reset file2"].join("\0"),
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["restore", "-i"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Created b
+    Working copy now at: b
+    Parent commit      : a
+    Added 0 files, modified 1 files, removed 0 files
+    "###);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("instrs")).unwrap(), @r###"
+    You are restoring changes from: the parents of b
+    into commit: b
+
+    The left side of the diff shows the current contents of the commit you're
+    restoring into. The right side initially shows the contents you're restoring
+    from.
+
+    Adjust the right side until it shows the contents you want for the
+    destination. If you don't make any changes, then all the changes will be
+    restored.
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r###"
+    M file2
+    "###);
+
+    // Without changes in the diff editor, everything is restored
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    std::fs::write(&edit_script, "").unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["restore", "--tool=fake-diff-editor", "--from=a"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Created b
+    Working copy now at: b
+    Parent commit      : a
+    Added 0 files, modified 2 files, removed 0 files
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @"");
+}
+
 fn create_commit(
     test_env: &TestEnvironment,
     repo_path: &Path,
@@ -0,0 +1,158 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+fn create_commit(
+    test_env: &TestEnvironment,
+    repo_path: &Path,
+    name: &str,
+    parents: &[&str],
+    files: &[(&str, &str)],
+) {
+    if parents.is_empty() {
+        test_env.jj_cmd_ok(repo_path, &["new", "root()", "-m", name]);
+    } else {
+        let mut args = vec!["new", "-m", name];
+        args.extend(parents);
+        test_env.jj_cmd_ok(repo_path, &args);
+    }
+    for (name, contents) in files {
+        std::fs::write(repo_path.join(name), contents).unwrap();
+    }
+    test_env.jj_cmd_ok(repo_path, &["bookmark", "create", name]);
+}
+
+fn init_repo(test_env: &TestEnvironment) -> std::path::PathBuf {
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    // Keep the status messages free of commit IDs
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    test_env.env_root().join("repo")
+}
+
+#[test]
+fn test_revert() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    create_commit(&test_env, &repo_path, "a", &[], &[("a", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["a"], &[("b", "b\n")]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "a"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Reverted a as Revert "a"
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ○  Revert "a"
+    @  b
+    ○  a
+    ◆  root
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s", "-r", "@+"]);
+    insta::assert_snapshot!(stdout, @r###"
+    D a
+    "###);
+    let stdout = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "@+", "-T", "description"],
+    );
+    let commit_id = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "a", "-T", "commit_id"],
+    );
+    assert_eq!(
+        stdout,
+        format!("Revert \"a\"\n\nThis reverts commit {commit_id}.\n")
+    );
+
+    // The destination can be given
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "b", "-d", "a"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Reverted b as Revert "b"
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ○  Revert "b"
+    │ ○  Revert "a"
+    │ @  b
+    ├─╯
+    ○  a
+    ◆  root
+    "###);
+
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["revert"]);
+    assert!(stderr.contains("--revisions <REVISIONS>"), "{stderr}");
+}
+
+#[test]
+fn test_revert_multiple() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    create_commit(&test_env, &repo_path, "a", &[], &[("a", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["a"], &[("a", "a\nb\n")]);
+    create_commit(&test_env, &repo_path, "c", &["b"], &[("a", "a\nb\nc\n")]);
+
+    // Descendants are reverted first, so each revert applies cleanly
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "b|c"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Reverted c as Revert "c"
+    Reverted b as Revert "b"
+    "###);
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ○  Revert "b"
+    ○  Revert "c"
+    @  c
+    ○  b
+    ○  a
+    ◆  root
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["file", "show", "-r", "@++", "a"]);
+    insta::assert_snapshot!(stdout, @r###"
+    a
+    "###);
+}
+
+#[test]
+fn test_revert_conflict() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    create_commit(&test_env, &repo_path, "a", &[], &[("file", "a\n")]);
+    create_commit(&test_env, &repo_path, "b", &["a"], &[("file", "b\n")]);
+    create_commit(&test_env, &repo_path, "c", &["b"], &[("file", "c\n")]);
+
+    // The revert of b conflicts with the change in c, which is recorded in the
+    // new commit
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["revert", "-r", "b"]);
+    assert!(
+        stderr.starts_with(
+            "Reverted b as Revert \"b\"\nNew conflicts appeared in these commits:\n  Revert \
+             \"b\"\n"
+        ),
+        "{stderr}"
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    ×  Revert "b" conflict
+    @  c
+    ○  b
+    ○  a
+    ◆  root
+    "###);
+}
+
+fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
+    let template =
+        r#"separate(" ", if(root, "root"), description.first_line(), if(conflict, "conflict"))"#;
+    test_env.jj_cmd_success(cwd, &["log", "-T", template])
+}
@@ -0,0 +1,128 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use itertools::Itertools;
+
+use crate::common::get_stderr_string;
+use crate::common::get_stdout_string;
+use crate::common::TestEnvironment;
+
+#[cfg(unix)]
+#[test]
+fn test_run_per_revision() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("file"), "ok\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "good"]);
+    std::fs::write(repo_path.join("file"), "bad\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "bad"]);
+
+    let assert = test_env
+        .jj_cmd(
+            &repo_path,
+            &[
+                "run",
+                "grep -q ok file || { echo not ok; exit 1; }",
+                "-r",
+                "description(good) | description(bad)",
+            ],
+        )
+        .assert()
+        .code(1);
+    let stdout = test_env.normalize_output(&get_stdout_string(&assert));
+    let stderr = test_env.normalize_output(&get_stderr_string(&assert));
+    insta::assert_snapshot!(stdout, @r###"
+    PASS qpvuntsm d91897f8 good
+    FAIL rlvkpnrz 73011521 bad
+    "###);
+    assert!(stderr.contains("not ok\n"), "{stderr}");
+    assert!(
+        stderr.ends_with("Error: Command failed in 1 of 2 revisions\n"),
+        "{stderr}"
+    );
+
+    // Results are cached, and reported in topological order
+    let assert = test_env
+        .jj_cmd(
+            &repo_path,
+            &[
+                "run",
+                "grep -q ok file || { echo not ok; exit 1; }",
+                "-r",
+                "description(good) | description(bad)",
+            ],
+        )
+        .assert()
+        .code(1);
+    let stdout = test_env.normalize_output(&get_stdout_string(&assert));
+    let lines = stdout.lines().collect_vec();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(lines[0].starts_with("PASS "), "{stdout}");
+    assert!(lines[0].ends_with(" good (cached)"), "{stdout}");
+    assert!(lines[1].starts_with("FAIL "), "{stdout}");
+    assert!(lines[1].ends_with(" bad (cached)"), "{stdout}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_environment() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "first"]);
+
+    let commit_id = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "@-", "-T", "commit_id"],
+    );
+    let change_id = test_env.jj_cmd_success(
+        &repo_path,
+        &["log", "--no-graph", "-r", "@-", "-T", "change_id"],
+    );
+    // The command runs in a checkout of the revision, not in the workspace
+    let shell_command = format!(
+        r#"test "$JJ_COMMIT_ID" = {commit_id} && test "$JJ_CHANGE_ID" = {change_id} && test -f file && test "$PWD" != {repo}"#,
+        repo = repo_path.display(),
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["run", &shell_command, "-r", "@-"]);
+    assert!(stdout.starts_with("PASS "), "{stdout}");
+}
+
+#[test]
+fn test_run_clean() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["run", "echo hello"]);
+    assert!(stdout.starts_with("PASS "), "{stdout}");
+    assert!(repo_path.join(".jj/repo/run").is_dir());
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["run", "--clean"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Removed cached working copies and results.
+    "###);
+    assert!(!repo_path.join(".jj/repo/run").exists());
+
+    // Cleaning again is a no-op
+    test_env.jj_cmd_ok(&repo_path, &["run", "--clean"]);
+
+    let stderr = test_env.jj_cmd_cli_error(&repo_path, &["run"]);
+    assert!(stderr.contains("<SHELL_COMMAND>"), "{stderr}");
+}
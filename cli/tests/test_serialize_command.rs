@@ -0,0 +1,121 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_serialize_siblings() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let workspace_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m=1"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "root()", "-m=2"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "root()", "-m=3"]);
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &[
+            "new",
+            "description(1)",
+            "description(2)",
+            "description(3)",
+            "-m=merge",
+        ],
+    );
+
+    // The plan is shown in the order of the resulting stack
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &workspace_path,
+        &[
+            "serialize",
+            "--dry-run",
+            "description(1)",
+            "description(3)",
+            "description(2)",
+        ],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Would stack these revisions, from top to bottom:
+      2
+      3
+      1
+    "###);
+
+    // The merge only keeps the top of the stack as parent
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &[
+            "serialize",
+            "description(1)",
+            "description(3)",
+            "description(2)",
+        ],
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r###"
+    @  merge parents: 2
+    ○  2 parents: 3
+    ○  3 parents: 1
+    ○  1 parents:
+    ◆  parents:
+    "###);
+
+    // A revision can't be moved below its ancestor
+    let stderr = test_env.jj_cmd_failure(
+        &workspace_path,
+        &["serialize", "description(2)", "description(1)"],
+    );
+    assert!(stderr.contains("can't be stacked on top of its descendant"));
+}
+
+#[test]
+fn test_serialize_revset_order() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let workspace_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&workspace_path, &["describe", "-m=1"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "-m=1c"]);
+    test_env.jj_cmd_ok(&workspace_path, &["new", "root()", "-m=2"]);
+
+    // Within a revset, ancestors go below their descendants
+    test_env.jj_cmd_ok(
+        &workspace_path,
+        &["serialize", "description(2)", "description(1)::"],
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &workspace_path), @r###"
+    ○  1c parents: 1
+    ○  1 parents: 2
+    @  2 parents:
+    ◆  parents:
+    "###);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&workspace_path, &["serialize", "description(2)"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing changed.
+    "###);
+}
+
+fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
+    let template = r#"
+    separate(" ",
+        description.first_line(),
+        "parents:",
+        parents.map(|c|c.description().first_line())
+    )"#;
+    test_env.jj_cmd_success(cwd, &["log", "-T", template])
+}
@@ -22,6 +22,11 @@ fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
     test_env.jj_cmd_success(cwd, &["log", "-T", template])
 }
 
+fn get_file_list_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
+    let template = r#"separate(" ", if(description, description.first_line(), "(no description)"), diff.files().map(|e| e.path()).join(","))"#;
+    test_env.jj_cmd_success(cwd, &["log", "-T", template])
+}
+
 fn get_recorded_dates(test_env: &TestEnvironment, cwd: &Path, revset: &str) -> String {
     let template = r#"separate("\n", "Author date:  " ++ author.timestamp(), "Committer date: " ++ committer.timestamp())"#;
     test_env.jj_cmd_success(cwd, &["log", "--no-graph", "-T", template, "-r", revset])
@@ -521,6 +526,100 @@ fn test_split_siblings_with_merge_child() {
     "###);
 }
 
+#[test]
+fn test_split_by_multiple_filesets() {
+    let mut test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(
+        r#"templates.commit_summary = 'if(description, description.first_line(), "(no description)")'"#,
+    );
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::write(repo_path.join("a1"), "foo\n").unwrap();
+    std::fs::write(repo_path.join("b1"), "foo\n").unwrap();
+    std::fs::write(repo_path.join("c"), "foo\n").unwrap();
+
+    // Each fileset gets its own commit, and the remaining changes are in the
+    // last one
+    let edit_script = test_env.set_up_fake_editor();
+    std::fs::write(
+        &edit_script,
+        ["write\npart a", "next invocation\n", "write\npart b"].join("\0"),
+    )
+    .unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["split", "glob:a*", "glob:b*"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Part 1: part a
+    Part 2: part b
+    Part 3: (no description)
+    Working copy now at: (no description)
+    Parent commit      : part b
+    "###);
+    insta::assert_snapshot!(get_file_list_log_output(&test_env, &repo_path), @r###"
+    @  (no description) c
+    ○  part b b1
+    ○  part a a1
+    ◆  (no description)
+    "###);
+
+    // With --parallel, the commits are siblings
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    std::fs::write(
+        &edit_script,
+        ["write\npart a", "next invocation\n", "write\npart b"].join("\0"),
+    )
+    .unwrap();
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["split", "--parallel", "glob:a*", "glob:b*"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Part 1: part a
+    Part 2: part b
+    Part 3: (no description)
+    Working copy now at: (no description)
+    Parent commit      : (no description)
+    Added 0 files, modified 0 files, removed 2 files
+    "###);
+    insta::assert_snapshot!(get_file_list_log_output(&test_env, &repo_path), @r###"
+    @  (no description) c
+    │ ○  part b b1
+    ├─╯
+    │ ○  part a a1
+    ├─╯
+    ◆  (no description)
+    "###);
+
+    // A fileset that matches nothing results in an empty commit
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    std::fs::write(
+        &edit_script,
+        ["write\npart a", "next invocation\n", "write\npart b"].join("\0"),
+    )
+    .unwrap();
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["split", "glob:a*", "nonexistent"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Warning: The given paths do not match any file: nonexistent
+    Part 1: part a
+    Part 2: part b
+    Part 3: (no description)
+    Working copy now at: (no description)
+    Parent commit      : part b
+    "###);
+    insta::assert_snapshot!(get_file_list_log_output(&test_env, &repo_path), @r###"
+    @  (no description) b1,c
+    ○  part b
+    ○  part a a1
+    ◆  (no description)
+    "###);
+
+    // Interactive selection only works with a single fileset
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["split", "-i", "glob:a*", "glob:b*"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: `--interactive` can't be used with more than one fileset
+    "###);
+}
+
 // Make sure `jj split` would refuse to split an empty commit.
 #[test]
 fn test_split_empty() {
@@ -1001,6 +1001,92 @@ fn test_squash_from_multiple_partial_no_op() {
     "###);
 }
 
+#[test]
+fn test_squash_into_multiple_destinations() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(
+        r#"templates.commit_summary = 'if(description, description.first_line(), "(no description)")'"#,
+    );
+    let repo_path = test_env.env_root().join("repo");
+    let get_log = || {
+        let template = r#"separate(" ", if(description, description.first_line(), "(no description)"), diff.files().map(|e| e.path()).join(","))"#;
+        test_env.jj_cmd_success(&repo_path, &["log", "-T", template])
+    };
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=a"]);
+    std::fs::write(repo_path.join("a"), "a\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=b"]);
+    std::fs::write(repo_path.join("b"), "b\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=c"]);
+    std::fs::write(repo_path.join("x"), "x\n").unwrap();
+    std::fs::write(repo_path.join("y"), "y\n").unwrap();
+    std::fs::write(repo_path.join("z"), "z\n").unwrap();
+    insta::assert_snapshot!(get_log(), @r###"
+    @  c x,y,z
+    ○  b b
+    ○  a a
+    ◆  (no description)
+    "###);
+
+    // The paths following each `--into` are moved into that revision. The
+    // destinations are rewritten one after the other, so the commits on top of
+    // the first one are rebased like after moving changes into a single
+    // ancestor.
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["squash", "--into=@--", "x", "--into=@-", "y"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 1 descendant commits
+    Working copy now at: c
+    Parent commit      : b
+    "###);
+    insta::assert_snapshot!(get_log(), @r###"
+    @  c z
+    ○  b b,y
+    ○  a a,x
+    ◆  (no description)
+    "###);
+
+    // The source is abandoned if all of its changes are moved
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(
+        &repo_path,
+        &["squash", "--into=@--", "x", "--into=@-", "y", "z"],
+    );
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Rebased 1 descendant commits
+    Working copy now at: (no description)
+    Parent commit      : b
+    "###);
+    insta::assert_snapshot!(get_log(), @r###"
+    @  (no description)
+    ○  b b,y,z
+    ○  a a,x
+    ◆  (no description)
+    "###);
+
+    // Every path needs a destination, and every destination needs paths
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["squash", "x", "--into=@--", "--into=@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No destination given for path x
+    Hint: Put the paths after the `--into` of the revision to move them into
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["squash", "--into=@--", "x", "--into=@-"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No paths given for destination @-
+    "###);
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["squash", "-m=d", "--into=@--", "x", "--into=@-", "y"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: `--message` can't be used with more than one destination
+    "###);
+}
+
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> String {
     let template = r#"separate(
         " ",
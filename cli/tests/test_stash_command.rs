@@ -0,0 +1,166 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::common::TestEnvironment;
+
+fn init_repo(test_env: &TestEnvironment) -> PathBuf {
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    // Keep the status messages free of commit IDs
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "base\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["commit", "-m", "base"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "wip"]);
+    repo_path
+}
+
+#[test]
+fn test_stash_push_pop() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    std::fs::write(repo_path.join("file"), "changed\n").unwrap();
+    std::fs::write(repo_path.join("other"), "new\n").unwrap();
+
+    // The changes are moved out of the working-copy commit, and the stash
+    // isn't visible in the log
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["stash", "push"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Stashed changes as wip
+    Working copy now at: wip
+    Parent commit      : base
+    Added 0 files, modified 1 files, removed 1 files
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  wip
+    ○  base
+    ◆  root
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stash", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    0: wip
+    "###);
+
+    // The changes can be popped after the working copy has moved
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "moved"]);
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["stash", "pop"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Applied stash wip
+    Working copy now at: moved
+    Parent commit      : wip
+    Added 1 files, modified 1 files, removed 0 files
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r###"
+    M file
+    A other
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stash", "list"]);
+    insta::assert_snapshot!(stdout, @"");
+
+    // Undoing the pop brings the stash back
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stash", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    0: wip
+    "###);
+}
+
+#[test]
+fn test_stash_multiple() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["stash", "push"]);
+    insta::assert_snapshot!(stderr, @r###"
+    No changes to stash.
+    "###);
+
+    // Only the changes in the given paths are stashed
+    std::fs::write(repo_path.join("file1"), "1\n").unwrap();
+    std::fs::write(repo_path.join("file2"), "2\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["stash", "push", "file1", "-m", "first"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file2
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["stash", "push", "-m", "second"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stash", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    0: second
+    1: first
+    "###);
+
+    // Stashes are selected by their index
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["stash", "pop", "1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Applied stash first
+    Working copy now at: wip
+    Parent commit      : base
+    Added 1 files, modified 0 files, removed 0 files
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["diff", "-s"]);
+    insta::assert_snapshot!(stdout, @r###"
+    A file1
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["stash", "drop", "1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No stash at index 1
+    "###);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["stash", "drop"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Dropped stash second
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stash", "list"]);
+    insta::assert_snapshot!(stdout, @"");
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["stash", "pop"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: There are no stashed changes
+    "###);
+}
+
+#[test]
+fn test_stash_pop_conflict() {
+    let test_env = TestEnvironment::default();
+    let repo_path = init_repo(&test_env);
+    std::fs::write(repo_path.join("file"), "stashed\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["stash", "push"]);
+    std::fs::write(repo_path.join("file"), "changed\n").unwrap();
+
+    // The conflict is recorded in the working-copy commit
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["stash", "pop"]);
+    assert!(
+        stderr.starts_with("Applied stash wip\nNew conflicts appeared in these commits:\n  wip\n"),
+        "{stderr}"
+    );
+    insta::assert_snapshot!(get_log_output(&test_env, &repo_path), @r###"
+    @  wip conflict
+    ○  base
+    ◆  root
+    "###);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["stash", "list"]);
+    insta::assert_snapshot!(stdout, @"");
+}
+
+fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> String {
+    let template =
+        r#"separate(" ", if(root, "root"), description.first_line(), if(conflict, "conflict"))"#;
+    test_env.jj_cmd_success(cwd, &["log", "-T", template])
+}
@@ -93,6 +93,50 @@ fn test_status_merge() {
     "###);
 }
 
+#[test]
+fn test_status_template() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=second"]);
+    std::fs::write(repo_path.join("file"), "content").unwrap();
+    test_env.add_config(
+        r#"
+        templates.status = '''
+        "@ " ++ change_id.short() ++ " " ++ description.first_line() ++ "\n"
+          ++ parents.map(|c|
+            "^ " ++ c.change_id().short() ++ " " ++ c.description().first_line() ++ "\n"
+          ).join("")
+        '''
+        "#,
+    );
+
+    let stdout = test_env.jj_cmd_success(&repo_path, &["status"]);
+    insta::assert_snapshot!(stdout, @r###"
+    Working copy changes:
+    A file
+    @ kkmpptxzrspx second
+    ^ qpvuntsmwlqt first
+    "###);
+
+    // Invalid template is reported before any output
+    let stderr = test_env.jj_cmd_failure(
+        &repo_path,
+        &["status", "--config-toml=templates.status='commit_id.bad()'"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Failed to parse template: Method "bad" doesn't exist for type "CommitOrChangeId"
+    Caused by:  --> 1:11
+      |
+    1 | commit_id.bad()
+      |           ^-^
+      |
+      = Method "bad" doesn't exist for type "CommitOrChangeId"
+    "###);
+}
+
 // See https://github.com/martinvonz/jj/issues/2051.
 #[test]
 fn test_status_ignored_gitignore() {
@@ -117,3 +117,82 @@ fn test_tag_list() {
     added_targets: commit2
     "###);
 }
+
+#[test]
+fn test_tag_create_delete() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    test_env.add_config(r#"templates.commit_summary = "description.first_line()""#);
+    let repo_path = test_env.env_root().join("repo");
+    let git_repo = {
+        let mut git_repo_path = repo_path.clone();
+        git_repo_path.extend([".jj", "repo", "store", "git"]);
+        git2::Repository::open(git_repo_path).unwrap()
+    };
+    let list_template = r#"name ++ ": " ++ normal_target.description().first_line() ++ "\n""#;
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m=first"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m=second"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "create", "v1", "-r", "@-"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Created 1 tags pointing to first
+    "###);
+    // The tagged working-copy commit becomes immutable
+    let (stdout, stderr) =
+        test_env.jj_cmd_ok(&repo_path, &["tag", "create", "v2", "-m", "Release 2"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Created 1 tags pointing to second
+    Warning: The working-copy commit in workspace 'default' became immutable, so a new commit has been created on top of it.
+    Working copy now at: 
+    Parent commit      : second
+    "###);
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["tag", "list", "-T", list_template]), @r###"
+    v1: first
+    v2: second
+    "###);
+
+    // The annotated tag is written to the Git repo right away, the lightweight
+    // tag when the refs are exported
+    assert!(git_repo.find_reference("refs/tags/v1").is_err());
+    let tag = git_repo
+        .find_reference("refs/tags/v2")
+        .unwrap()
+        .peel_to_tag()
+        .unwrap();
+    assert_eq!(tag.message().unwrap().trim_end(), "Release 2");
+    test_env.jj_cmd_ok(&repo_path, &["git", "export"]);
+    assert!(git_repo.find_reference("refs/tags/v1").is_ok());
+
+    // Existing tags aren't moved
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "create", "v1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Tag already exists: v1
+    Hint: Use `jj tag delete` to delete it first.
+    "###);
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "create", "v0", "-r", "root()"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Cannot tag the root commit
+    "###);
+
+    // Deleted tags, including annotated ones, are removed from the Git repo on
+    // export
+    let (stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["tag", "delete", "glob:v*"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Deleted 2 tags.
+    "###);
+    test_env.jj_cmd_ok(&repo_path, &["git", "export"]);
+    assert!(git_repo.find_reference("refs/tags/v1").is_err());
+    assert!(git_repo.find_reference("refs/tags/v2").is_err());
+    insta::assert_snapshot!(
+        test_env.jj_cmd_success(&repo_path, &["tag", "list", "-T", list_template]), @"");
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["tag", "delete", "v1"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No matching tags for pattern: v1
+    "###);
+}
@@ -81,7 +81,7 @@ fn test_templater_parse_error() {
       |                    ^^
       |
       = Keyword "se" doesn't exist
-    Hint: Did you mean "s", "self"?
+    Hint: Did you mean "closest_bookmark", "s", "self"?
     "###);
     insta::assert_snapshot!(render_err(r#"format_id(commit_id)"#), @r###"
     Error: Failed to parse template: Alias "format_id(id)" cannot be expanded
@@ -129,6 +129,99 @@ fn test_templater_upper_lower() {
       render(r#""Hello".upper() ++ "Hello".lower()"#), @"HELLOhello");
 }
 
+#[test]
+fn test_templater_label_if() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    std::fs::write(repo_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&repo_path, &["new"]);
+    test_env.add_config(
+        r#"
+    [colors]
+    "empty" = "green"
+    "root" = "red"
+    "#,
+    );
+    let render = |rev, template| get_colored_template_output(&test_env, &repo_path, rev, template);
+
+    // The label is only applied if the condition is true
+    let template = r#"label_if(empty, "empty", "text")"#;
+    insta::assert_snapshot!(render("@", template), @"[38;5;2mtext[39m");
+    insta::assert_snapshot!(render("@-", template), @"text");
+
+    // Nested and computed labels
+    let template = r#"label_if(empty, "empty", label_if(root, "ro" ++ "ot", "text"))"#;
+    insta::assert_snapshot!(render("root()", template), @"[38;5;1mtext[39m");
+    insta::assert_snapshot!(render("@", template), @"[38;5;2mtext[39m");
+}
+
+#[test]
+fn test_templater_hyperlink() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    let template = r#"hyperlink("https://example.com/" ++ commit_id.short(), "link")"#;
+
+    // Plain text output doesn't contain escape sequences
+    insta::assert_snapshot!(get_template_output(&test_env, &repo_path, "@", template), @"link");
+    insta::assert_snapshot!(
+        get_colored_template_output(&test_env, &repo_path, "@", template),
+        @r#"]8;;https://example.com/230dd059e1b0\link]8;;\"#);
+}
+
+#[test]
+fn test_templater_timestamp_format() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config(
+        r#"
+    [template-aliases]
+    'timestamp_format' = '"%d/%m/%Y %H:%M"'
+    'format_timestamp(timestamp)' = 'timestamp.utc().format(timestamp_format)'
+    "#,
+    );
+    let render = |template| get_template_output(&test_env, &repo_path, "@", template);
+
+    // The format string can be shared through an alias
+    insta::assert_snapshot!(render(r#"format_timestamp(author.timestamp())"#), @"02/02/2001 21:05");
+    insta::assert_snapshot!(
+        render(r#"author.timestamp().format(timestamp_format ++ " UTC%:z")"#), @"03/02/2001 04:05 UTC+07:00");
+    insta::assert_snapshot!(
+        render(r#"author.timestamp().format(if(description, "%Y", "%H:%M"))"#), @"04:05");
+
+    // An invalid dynamic format string is reported when rendering
+    insta::assert_snapshot!(
+        render(r#"author.timestamp().format("%" ++ "_")"#), @"<Error: Invalid time format>");
+
+    // The default log template uses the format_timestamp() alias
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-r@", "--no-graph"]);
+    insta::assert_snapshot!(stdout, @r###"
+    qpvuntsm test.user@example.com 02/02/2001 21:05 230dd059
+    (empty) (no description set)
+    "###);
+}
+
+#[test]
+fn test_templater_width_aware_columns() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "短い説明文です"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "a longer description"]);
+
+    let template = r#"
+    description.first_line().truncate(10, "…").pad_end(10) ++ "|" ++ "\n"
+    "#;
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(stdout, @r###"
+    a longer …|
+    短い説明… |
+              |
+    "###);
+}
+
 #[test]
 fn test_templater_alias() {
     let test_env = TestEnvironment::default();
@@ -155,6 +248,8 @@ fn test_templater_alias() {
 
     insta::assert_snapshot!(render("my_commit_id"), @"000000000000");
     insta::assert_snapshot!(render("identity(my_commit_id)"), @"000000000000");
+    insta::assert_snapshot!(render(r#"coalesce(y=my_commit_id, x="")"#), @"000000000000");
+    insta::assert_snapshot!(render(r#"coalesce("", y=my_commit_id)"#), @"000000000000");
 
     insta::assert_snapshot!(render_err("commit_id ++ syntax_error"), @r###"
     Error: Failed to parse template: Alias "syntax_error" cannot be expanded
@@ -273,6 +368,25 @@ fn test_templater_alias() {
       = Function "identity": Expected 1 arguments
     "###);
 
+    insta::assert_snapshot!(render_err(r#"coalesce(x="", z="")"#), @r###"
+    Error: Failed to parse template: Function "coalesce": Unexpected keyword argument "z"
+    Caused by:  --> 1:16
+      |
+    1 | coalesce(x="", z="")
+      |                ^--^
+      |
+      = Function "coalesce": Unexpected keyword argument "z"
+    "###);
+    insta::assert_snapshot!(render_err(r#"commit_id.short(len=1)"#), @r###"
+    Error: Failed to parse template: Function "short": Unexpected keyword arguments
+    Caused by:  --> 1:17
+      |
+    1 | commit_id.short(len=1)
+      |                 ^---^
+      |
+      = Function "short": Unexpected keyword arguments
+    "###);
+
     insta::assert_snapshot!(render_err(r#"coalesce(label("x", "not boolean"), "")"#), @r###"
     Error: Failed to parse template: Alias "coalesce(x, y)" cannot be expanded
     Caused by:
@@ -46,6 +46,102 @@ fn test_undo_rewrite_with_child() {
     "###);
 }
 
+#[test]
+fn test_op_revert_keeps_later_operations() {
+    // Test that reverting an old operation only undoes its own changes
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "b"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "root()", "-m", "c"]);
+    test_env.jj_cmd_ok(&repo_path, &["abandon", "description(b)"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
+    let op_id_hex = stdout[3..15].to_string();
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "c2"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "d"]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  d
+    ○  c2
+    │ ○  a
+    ├─╯
+    ◆
+    "###);
+
+    test_env.jj_cmd_ok(&repo_path, &["op", "revert", &op_id_hex]);
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  d
+    ○  c2
+    │ ○  b
+    │ ○  a
+    ├─╯
+    ◆
+    "###);
+}
+
+#[test]
+fn test_undo_steps_and_redo() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "repo"]);
+    let repo_path = test_env.env_root().join("repo");
+
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "a"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "b"]);
+    test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "c"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["undo", "--steps", "2"]);
+    assert!(stderr.contains("Undid 2 operations:"), "{stderr}");
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  a
+    ◆
+    "###);
+
+    // Both operations are redone at once
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["redo"]);
+    assert!(!stderr.contains("Warning"), "{stderr}");
+    assert!(
+        stderr.contains("Redid the changes undone by operation"),
+        "{stderr}"
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  c
+    ◆
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["redo"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Nothing to redo
+    Hint: Only operations undone by `jj undo` can be redone.
+    "###);
+
+    // Operations done since the undo are kept
+    test_env.jj_cmd_ok(&repo_path, &["undo"]);
+    test_env.jj_cmd_ok(&repo_path, &["new", "-m", "d"]);
+    let (_stdout, stderr) = test_env.jj_cmd_ok(&repo_path, &["op", "redo"]);
+    assert!(
+        stderr.contains(
+            "Warning: 1 other operations were done since the undo. Their changes are kept, but \
+             might conflict with the redone changes."
+        ),
+        "{stderr}"
+    );
+    let stdout = test_env.jj_cmd_success(&repo_path, &["log", "-T", "description"]);
+    insta::assert_snapshot!(stdout, @r###"
+    @  d
+    ○  c
+    ◆
+    "###);
+
+    let stderr = test_env.jj_cmd_failure(&repo_path, &["undo", "--steps", "10"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Error: Cannot undo repo initialization
+    "###);
+}
+
 #[test]
 fn test_git_push_undo() {
     let test_env = TestEnvironment::default();
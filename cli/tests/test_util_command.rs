@@ -91,7 +91,7 @@ fn test_gc_operation_log() {
     // Now this doesn't work.
     let stderr = test_env.jj_cmd_failure(&repo_path, &["debug", "operation", &op_to_remove]);
     insta::assert_snapshot!(stderr, @r###"
-    Error: No operation ID matching "54c5f04ab3dbb32ac86f805c819cb1ef78a3c9758ee560680a465e843fa0b05f42706d0641805905ef4bab550ca805effd1ba73d1980f6cdcc42126f008e51f4"
+    Error: No operation ID matching "a66612886d41b062b87c4467c6f4098dabf9726339c618d730cd205f316d9200d1c6de877972e6d695e4911134f1f898df5023b1d89434b41ed20acae2eba334"
     "###);
 }
 
@@ -120,6 +120,82 @@ fn test_workspaces_sparse_patterns() {
     insta::assert_snapshot!(stdout, @"");
 }
 
+/// Test copying sparse patterns from another workspace or a profile
+#[test]
+fn test_workspaces_sparse_from_and_profile() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(
+        r#"
+    [sparse.profiles]
+    web = ["web", "shared"]
+    bad = ["../outside"]
+    "#,
+    );
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "ws1"]);
+    let ws1_path = test_env.env_root().join("ws1");
+    let ws2_path = test_env.env_root().join("ws2");
+    let ws3_path = test_env.env_root().join("ws3");
+    let ws4_path = test_env.env_root().join("ws4");
+
+    test_env.jj_cmd_ok(&ws1_path, &["workspace", "add", "../ws2"]);
+    test_env.jj_cmd_ok(&ws2_path, &["sparse", "set", "--clear", "--add=foo"]);
+
+    // --sparse-from copies the patterns of another workspace, not the current
+    // one
+    test_env.jj_cmd_ok(
+        &ws1_path,
+        &["workspace", "add", "--sparse-from=../ws2", "../ws3"],
+    );
+    let stdout = test_env.jj_cmd_success(&ws3_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    foo
+    "###);
+
+    // --sparse-profile uses the patterns from the config
+    test_env.jj_cmd_ok(
+        &ws1_path,
+        &["workspace", "add", "--sparse-profile=web", "../ws4"],
+    );
+    let stdout = test_env.jj_cmd_success(&ws4_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    shared
+    web
+    "###);
+
+    // Errors are reported before the workspace is created
+    let stderr = test_env.jj_cmd_failure(
+        &ws1_path,
+        &["workspace", "add", "--sparse-from=../nowhere", "../ws5"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: There is no jj repo in "../nowhere"
+    "###);
+    let stderr = test_env.jj_cmd_failure(
+        &ws1_path,
+        &["workspace", "add", "--sparse-profile=missing", "../ws5"],
+    );
+    insta::assert_snapshot!(stderr, @r###"
+    Error: No sparse profile named 'missing'
+    Hint: Sparse profiles are defined in the `sparse.profiles` config table.
+    "###);
+    assert!(!test_env.env_root().join("ws5").exists());
+
+    // `jj sparse set --add-profile` adds the patterns of the profile
+    test_env.jj_cmd_ok(&ws2_path, &["sparse", "set", "--add-profile=web"]);
+    let stdout = test_env.jj_cmd_success(&ws2_path, &["sparse", "list"]);
+    insta::assert_snapshot!(stdout, @r###"
+    foo
+    shared
+    web
+    "###);
+    let stderr = test_env.jj_cmd_failure(&ws2_path, &["sparse", "set", "--add-profile=bad"]);
+    insta::assert_snapshot!(stderr, @r###"
+    Config error: Invalid pattern in sparse profile 'bad': ../outside
+    Caused by: Invalid component ".." in repo-relative path "../outside"
+    For help, see https://martinvonz.github.io/jj/latest/config/.
+    "###);
+}
+
 /// Test adding a second workspace while the current workspace is editing a
 /// merge
 #[test]
@@ -501,14 +577,14 @@ fn test_workspaces_conflicting_edits() {
     "###);
     let stderr = test_env.jj_cmd_failure(&secondary_path, &["st"]);
     insta::assert_snapshot!(stderr, @r###"
-    Error: The working copy is stale (not updated since operation 0da24da631e3).
+    Error: The working copy is stale (not updated since operation e6df65ad8a3d).
     Hint: Run `jj workspace update-stale` to update it.
     See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     "###);
     // Same error on second run, and from another command
     let stderr = test_env.jj_cmd_failure(&secondary_path, &["log"]);
     insta::assert_snapshot!(stderr, @r###"
-    Error: The working copy is stale (not updated since operation 0da24da631e3).
+    Error: The working copy is stale (not updated since operation e6df65ad8a3d).
     Hint: Run `jj workspace update-stale` to update it.
     See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     "###);
@@ -588,7 +664,7 @@ fn test_workspaces_updated_by_other() {
     "###);
     let stderr = test_env.jj_cmd_failure(&secondary_path, &["st"]);
     insta::assert_snapshot!(stderr, @r###"
-    Error: The working copy is stale (not updated since operation 0da24da631e3).
+    Error: The working copy is stale (not updated since operation e6df65ad8a3d).
     Hint: Run `jj workspace update-stale` to update it.
     See https://martinvonz.github.io/jj/latest/working-copy/#stale-working-copy for more information.
     "###);
@@ -659,15 +735,15 @@ fn test_workspaces_current_op_discarded_by_other() {
         ],
     );
     insta::assert_snapshot!(stdout, @r###"
-    @  7337338f0b abandon commit 20dd439c4bd12c6ad56c187ac490bd0141804618f638dc5c4dc92ff9aecba20f152b23160db9dcf61beb31a5cb14091d9def5a36d11c9599cc4d2e5689236af1
-    ○  f4bd4d046b create initial working-copy commit in workspace secondary
-    ○  0f99641958 add workspace 'secondary'
-    ○  5641361f60 new empty commit
-    ○  3a6c319c59 snapshot working copy
-    ○  42c6005842 new empty commit
-    ○  6a45045541 snapshot working copy
-    ○  a9e6630bf0 add workspace 'default'
-    ○  cecfee9647 initialize repo
+    @  122a5e3af2 abandon commit 14a8afec70514eeffbc0a49c41ab7a246f190b90db0b1e35f2b8267f106a5343c765ba0864a6076c62bfc4427da75c4b8d66b5343db543157500616137bcd5a7
+    ○  5da35e7ccf create initial working-copy commit in workspace secondary
+    ○  6f6613eae0 add workspace 'secondary'
+    ○  5a7d6f037a new empty commit
+    ○  1d90b842d0 snapshot working copy
+    ○  87d49890a1 new empty commit
+    ○  145c444692 snapshot working copy
+    ○  5d92836cfc add workspace 'default'
+    ○  816b5e4ecd initialize repo
     ○  0000000000
     "###);
 
@@ -676,10 +752,10 @@ fn test_workspaces_current_op_discarded_by_other() {
     test_env.jj_cmd_ok(&main_path, &["util", "gc", "--expire=now"]);
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r###"
-    ○  96b31dafdc41 secondary@
-    │ @  6c051bd1ccd5 default@
+    ○  f344920b1511 secondary@
+    │ @  0e27f999f99f default@
     ├─╯
-    ○  7c5b25a4fc8f
+    ○  3eba60336e91
     ◆  000000000000
     "###);
 
@@ -692,17 +768,17 @@ fn test_workspaces_current_op_discarded_by_other() {
 
     let (stdout, stderr) = test_env.jj_cmd_ok(&secondary_path, &["workspace", "update-stale"]);
     insta::assert_snapshot!(stderr, @r###"
-    Failed to read working copy's current operation; attempting recovery. Error message from read attempt: Object f4bd4d046b3cdf61b0fda7738a0b1414c0aedc6c8229d39a35ee26facc358cad8b588b04d7eba1302a82409c529f69dbb1ff9ea28789d935b74f123f377aa30b of type operation not found
-    Created and checked out recovery commit 62f70695e3b0
+    Failed to read working copy's current operation; attempting recovery. Error message from read attempt: Object 5da35e7ccf4bc74d24f6c2a428876f70fa2661a58632f85b79b03f6fb04ea766aaba146d8e360bf9678b367fe33bdb9275cece0cd496bcdf9ddaff525781f07e of type operation not found
+    Created and checked out recovery commit d135e2f9c461
     "###);
     insta::assert_snapshot!(stdout, @"");
 
     insta::assert_snapshot!(get_log_output(&test_env, &main_path), @r###"
-    ○  b0b400439a82 secondary@
-    ○  96b31dafdc41
-    │ @  6c051bd1ccd5 default@
+    ○  53739e2afe4e secondary@
+    ○  f344920b1511
+    │ @  0e27f999f99f default@
     ├─╯
-    ○  7c5b25a4fc8f
+    ○  3eba60336e91
     ◆  000000000000
     "###);
 
@@ -720,8 +796,8 @@ fn test_workspaces_current_op_discarded_by_other() {
     A added
     D deleted
     M modified
-    Working copy : kmkuslsw b0b40043 (no description set)
-    Parent commit: rzvqmyuk 96b31daf (empty) (no description set)
+    Working copy : kmkuslsw 53739e2a (no description set)
+    Parent commit: rzvqmyuk f344920b (empty) (no description set)
     "###);
     // The modified file should have the same contents it had before (not reset to
     // the base contents)
@@ -732,9 +808,9 @@ fn test_workspaces_current_op_discarded_by_other() {
     let (stdout, stderr) = test_env.jj_cmd_ok(&secondary_path, &["evolog"]);
     insta::assert_snapshot!(stderr, @"");
     insta::assert_snapshot!(stdout, @r###"
-    @  kmkuslsw test.user@example.com 2001-02-03 08:05:18 secondary@ b0b40043
+    @  kmkuslsw test.user@example.com 2001-02-03 08:05:18 secondary@ 53739e2a
     │  (no description set)
-    ○  kmkuslsw hidden test.user@example.com 2001-02-03 08:05:18 62f70695
+    ○  kmkuslsw hidden test.user@example.com 2001-02-03 08:05:18 d135e2f9
        (empty) (no description set)
     "###);
 }
@@ -803,6 +879,58 @@ fn test_workspaces_update_stale_snapshot() {
     "###);
 }
 
+/// Test updating all stale workspaces at once
+#[test]
+fn test_workspaces_update_stale_all() {
+    let test_env = TestEnvironment::default();
+    test_env.jj_cmd_ok(test_env.env_root(), &["git", "init", "main"]);
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    std::fs::write(main_path.join("file"), "contents\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["new"]);
+    test_env.jj_cmd_ok(&main_path, &["workspace", "add", "../secondary"]);
+    test_env.jj_cmd_ok(&main_path, &["workspace", "add", "../third"]);
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&main_path, &["workspace", "update-stale", "--all"]);
+    insta::assert_snapshot!(stdout, @"");
+    insta::assert_snapshot!(stderr, @r###"
+    Nothing to do (no working copy is stale).
+    "###);
+
+    // Rewriting the tree of the common parent makes the other workspaces stale
+    std::fs::write(main_path.join("file"), "changed in main\n").unwrap();
+    test_env.jj_cmd_ok(&main_path, &["squash"]);
+    let stderr = test_env.jj_cmd_failure(&secondary_path, &["st"]);
+    assert!(stderr.contains("The working copy is stale"), "{stderr}");
+
+    let (stdout, stderr) = test_env.jj_cmd_ok(&main_path, &["workspace", "update-stale", "--all"]);
+    insta::assert_snapshot!(stdout, @"");
+    assert!(!stderr.contains("workspace default"), "{stderr}");
+    assert!(
+        stderr.contains("Working copy of workspace secondary now at: "),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("Working copy of workspace third now at: "),
+        "{stderr}"
+    );
+    test_env.jj_cmd_ok(&secondary_path, &["st"]);
+
+    // Workspaces deleted from disk are skipped
+    std::fs::remove_dir_all(&secondary_path).unwrap();
+    let (stdout, stderr) = test_env.jj_cmd_ok(&main_path, &["workspace", "update-stale", "--all"]);
+    insta::assert_snapshot!(stdout, @"");
+    assert!(
+        stderr.starts_with("Warning: Skipping workspace secondary at "),
+        "{stderr}"
+    );
+    assert!(
+        stderr.ends_with("Nothing to do (no working copy is stale).\n"),
+        "{stderr}"
+    );
+}
+
 /// Test forgetting workspaces
 #[test]
 fn test_workspaces_forget() {
@@ -896,7 +1024,7 @@ fn test_workspaces_forget_multi_transaction() {
     // the op log should have multiple workspaces forgotten in a single tx
     let stdout = test_env.jj_cmd_success(&main_path, &["op", "log", "--limit", "1"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  b7ab9f1c16cc test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
+    @  6d859666b71a test-username@host.example.com 2001-02-03 04:05:12.000 +07:00 - 2001-02-03 04:05:12.000 +07:00
     │  forget workspaces second, third
     │  args: jj workspace forget second third
     "###);
@@ -1052,27 +1180,27 @@ fn test_debug_snapshot() {
     test_env.jj_cmd_ok(&repo_path, &["debug", "snapshot"]);
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  e1e762d39b39 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    @  aec3deb7b35e test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     │  snapshot working copy
     │  args: jj debug snapshot
-    ○  b51416386f26 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  738f1e4eb9b7 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
-    ○  9a7d829846af test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  19a0f1964e23 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  initialize repo
     ○  000000000000 root()
     "###);
     test_env.jj_cmd_ok(&repo_path, &["describe", "-m", "initial"]);
     let stdout = test_env.jj_cmd_success(&repo_path, &["op", "log"]);
     insta::assert_snapshot!(stdout, @r###"
-    @  9ac6e7144e8a test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
+    @  6430825dccba test-username@host.example.com 2001-02-03 04:05:10.000 +07:00 - 2001-02-03 04:05:10.000 +07:00
     │  describe commit 4e8f9d2be039994f589b4e57ac5e9488703e604d
     │  args: jj describe -m initial
-    ○  e1e762d39b39 test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
+    ○  aec3deb7b35e test-username@host.example.com 2001-02-03 04:05:08.000 +07:00 - 2001-02-03 04:05:08.000 +07:00
     │  snapshot working copy
     │  args: jj debug snapshot
-    ○  b51416386f26 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  738f1e4eb9b7 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  add workspace 'default'
-    ○  9a7d829846af test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
+    ○  19a0f1964e23 test-username@host.example.com 2001-02-03 04:05:07.000 +07:00 - 2001-02-03 04:05:07.000 +07:00
     │  initialize repo
     ○  000000000000 root()
     "###);
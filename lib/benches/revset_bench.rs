@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use jj_lib::repo::ReadonlyRepo;
+use jj_lib::repo::Repo as _;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::RevsetFilterPredicate;
+use jj_lib::str_util::StringPattern;
+use testutils::TestRepo;
+
+/// Creates a linear history of `count` commits, one in `match_every` of which
+/// has the description "match".
+fn linear_history(count: usize, match_every: usize) -> (TestRepo, Arc<ReadonlyRepo>) {
+    let test_repo = TestRepo::init();
+    let settings = testutils::user_settings();
+    let mut tx = test_repo.repo.start_transaction(&settings);
+    let mut parent_id = tx.repo().store().root_commit_id().clone();
+    for i in 0..count {
+        let description = if i % match_every == 0 {
+            "match".to_owned()
+        } else {
+            format!("commit {i}")
+        };
+        let commit = testutils::create_random_commit(tx.repo_mut(), &settings)
+            .set_parents(vec![parent_id])
+            .set_description(description)
+            .write()
+            .unwrap();
+        parent_id = commit.id().clone();
+    }
+    let repo = tx.commit("test");
+    (test_repo, repo)
+}
+
+/// Takes the first 10 commits of revsets whose filter matches few commits.
+/// The time should depend on how far apart the matches are, not on the size
+/// of the history.
+fn bench_revset_take_first(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_revset_take_first");
+    let description = || {
+        RevsetExpression::filter(RevsetFilterPredicate::Description(StringPattern::exact(
+            "match",
+        )))
+    };
+    for count in [1000, 10000] {
+        let label = format!("{}k", count / 1000);
+        let (_test_repo, repo) = linear_history(count, 20);
+        let expressions = [
+            ("filter", description()),
+            ("ancestors_of_filter", description().ancestors()),
+            (
+                "range_to_filter",
+                RevsetExpression::root().range(&description()),
+            ),
+        ];
+        for (name, expression) in expressions {
+            group.bench_function(BenchmarkId::new(name, &label), |b| {
+                b.iter(|| {
+                    let revset = expression
+                        .clone()
+                        .evaluate_programmatic(repo.as_ref())
+                        .unwrap();
+                    revset.iter().take(10).count()
+                });
+            });
+        }
+    }
+}
+
+criterion_group!(benches, bench_revset_take_first);
+criterion_main!(benches);
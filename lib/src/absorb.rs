@@ -0,0 +1,315 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Moves the changes of a commit into the ancestors that last modified the
+//! same lines, "absorbing" them away.
+//!
+//! Each changed file is split into hunks, and the removed lines of each hunk
+//! are annotated. If they were all introduced by the same destination commit,
+//! the hunk is moved into that commit. Hunks that only add lines are ambiguous
+//! and are left in the source commit.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Read as _;
+use std::ops::Range;
+use std::rc::Rc;
+
+use bstr::BStr;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+use crate::annotate::get_annotation_in_domain;
+use crate::annotate::AnnotateError;
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::CommitId;
+use crate::backend::FileId;
+use crate::backend::TreeValue;
+use crate::commit::Commit;
+use crate::diff::Diff;
+use crate::diff::DiffHunk;
+use crate::matchers::Matcher;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::MergedTreeBuilder;
+use crate::merged_tree::TreeDiffEntry;
+use crate::repo::MutableRepo;
+use crate::repo::Repo;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::revset::RevsetEvaluationError;
+use crate::revset::RevsetExpression;
+use crate::settings::UserSettings;
+use crate::store::Store;
+
+/// The commit whose changes are absorbed into its ancestors.
+#[derive(Clone, Debug)]
+pub struct AbsorbSource {
+    commit: Commit,
+    parent_tree: MergedTree,
+}
+
+impl AbsorbSource {
+    /// Creates an absorb source from a single commit.
+    pub fn from_commit(repo: &dyn Repo, commit: Commit) -> BackendResult<Self> {
+        let parent_tree = commit.parent_tree(repo)?;
+        Ok(AbsorbSource {
+            commit,
+            parent_tree,
+        })
+    }
+
+    /// The source commit.
+    pub fn commit(&self) -> &Commit {
+        &self.commit
+    }
+}
+
+/// Error that may occur while splitting the source changes into hunks.
+#[derive(Debug, Error)]
+pub enum AbsorbError {
+    /// Failed to read a commit or a file from the backend.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    /// Failed to find the destination commits.
+    #[error(transparent)]
+    RevsetEvaluation(#[from] RevsetEvaluationError),
+}
+
+impl From<AnnotateError> for AbsorbError {
+    fn from(err: AnnotateError) -> Self {
+        match err {
+            AnnotateError::Backend(err) => AbsorbError::Backend(err),
+            AnnotateError::RevsetEvaluation(err) => AbsorbError::RevsetEvaluation(err),
+        }
+    }
+}
+
+/// The source changes split by destination commit.
+#[derive(Default)]
+pub struct SelectedTrees {
+    /// Trees to be merged into the destination commits, keyed by the ids of
+    /// the destination commits. Each tree is the source parent tree with the
+    /// hunks selected for that destination applied.
+    pub target_commits: HashMap<CommitId, MergedTreeBuilder>,
+    /// Paths that were skipped, and why.
+    pub skipped_paths: Vec<(RepoPathBuf, String)>,
+}
+
+/// Splits the changes of the `source` commit into hunks, and selects the
+/// hunks to be absorbed into each of the `destinations`.
+///
+/// The `destinations` should be ancestors of the source commit, and must be
+/// resolvable without a symbol resolver.
+pub fn split_hunks_to_trees(
+    repo: &dyn Repo,
+    source: &AbsorbSource,
+    destinations: &Rc<RevsetExpression>,
+    matcher: &dyn Matcher,
+) -> Result<SelectedTrees, AbsorbError> {
+    let mut selected_trees = SelectedTrees::default();
+    let Some(first_parent) = source.commit.parents().next().transpose()? else {
+        return Ok(selected_trees);
+    };
+    let destination_ids: HashSet<CommitId> = destinations
+        .clone()
+        .evaluate_programmatic(repo)?
+        .iter()
+        .collect();
+    let left_tree = &source.parent_tree;
+    let right_tree = source.commit.tree()?;
+    let diff_entries: Vec<TreeDiffEntry> = left_tree
+        .diff_stream(&right_tree, matcher)
+        .collect()
+        .block_on();
+    for TreeDiffEntry { path, values } in diff_entries {
+        let (left_value, right_value) = values?;
+        let (left_id, executable) = match to_file_id(left_value) {
+            Ok(Some(file)) => file,
+            // Added files have no lines to be attributed.
+            Ok(None) => continue,
+            Err(reason) => {
+                selected_trees.skipped_paths.push((path, reason.to_owned()));
+                continue;
+            }
+        };
+        let right_id = match to_file_id(right_value) {
+            Ok(Some((id, _))) => id,
+            // Deleted files are left in the source commit.
+            Ok(None) => continue,
+            Err(reason) => {
+                selected_trees.skipped_paths.push((path, reason.to_owned()));
+                continue;
+            }
+        };
+        let left_text = read_file_contents(repo.store(), &path, &left_id)?;
+        let right_text = read_file_contents(repo.store(), &path, &right_id)?;
+        let annotation = get_annotation_in_domain(repo, &first_parent, destinations, &path)?;
+        // If the source is a merge, the annotated file of the first parent may
+        // not be the file the changes were made to.
+        if !annotation
+            .iter()
+            .map(|line| line.content.as_slice())
+            .eq(left_text.split_inclusive(|b| *b == b'\n'))
+        {
+            let reason = "File differs from the first parent".to_owned();
+            selected_trees.skipped_paths.push((path, reason));
+            continue;
+        }
+
+        let mut hunks_by_commit: HashMap<&CommitId, Vec<(Range<usize>, &BStr)>> = HashMap::new();
+        let mut left_pos = 0;
+        let mut left_line = 0;
+        for hunk in Diff::by_line([&left_text, &right_text]).hunks() {
+            match hunk {
+                DiffHunk::Matching(text) => {
+                    left_pos += text.len();
+                    left_line += count_lines(text);
+                }
+                DiffHunk::Different(texts) => {
+                    let byte_range = left_pos..left_pos + texts[0].len();
+                    let line_range = left_line..left_line + count_lines(texts[0]);
+                    left_pos = byte_range.end;
+                    left_line = line_range.end;
+                    // Hunks that only add lines can't be attributed.
+                    let Ok(commit_id) = annotation[line_range]
+                        .iter()
+                        .map(|line| &line.commit_id)
+                        .all_equal_value()
+                    else {
+                        continue;
+                    };
+                    if destination_ids.contains(commit_id) {
+                        hunks_by_commit
+                            .entry(commit_id)
+                            .or_default()
+                            .push((byte_range, texts[1]));
+                    }
+                }
+            }
+        }
+
+        for (commit_id, hunks) in hunks_by_commit {
+            let mut new_text = vec![];
+            let mut pos = 0;
+            for (range, right) in hunks {
+                new_text.extend_from_slice(&left_text[pos..range.start]);
+                new_text.extend_from_slice(right);
+                pos = range.end;
+            }
+            new_text.extend_from_slice(&left_text[pos..]);
+            let id = repo
+                .store()
+                .write_file(&path, &mut new_text.as_slice())
+                .block_on()?;
+            selected_trees
+                .target_commits
+                .entry(commit_id.clone())
+                .or_insert_with(|| MergedTreeBuilder::new(left_tree.id()))
+                .set_or_remove(
+                    path.clone(),
+                    Merge::normal(TreeValue::File { id, executable }),
+                );
+        }
+    }
+    Ok(selected_trees)
+}
+
+/// Describes changes made by [`absorb_hunks()`].
+#[derive(Clone, Debug)]
+pub struct AbsorbStats {
+    /// The source commit rebased onto the rewritten destinations, without the
+    /// absorbed hunks.
+    pub rewritten_source: Option<Commit>,
+    /// The destination commits the hunks were absorbed into.
+    pub rewritten_destinations: Vec<Commit>,
+    /// Number of other descendant commits that were rebased.
+    pub num_rebased: usize,
+}
+
+/// Merges the selected trees into the destination commits, and rebases their
+/// descendants.
+///
+/// The source commit is kept even if it becomes empty.
+pub fn absorb_hunks(
+    repo: &mut MutableRepo,
+    settings: &UserSettings,
+    source: &AbsorbSource,
+    mut selected_trees: HashMap<CommitId, MergedTreeBuilder>,
+) -> BackendResult<AbsorbStats> {
+    let store = repo.store().clone();
+    let mut rewritten_source = None;
+    let mut rewritten_destinations = vec![];
+    let mut num_rebased = 0;
+    let roots = selected_trees.keys().cloned().collect_vec();
+    repo.transform_descendants(settings, roots, |rewriter| {
+        if rewriter.old_commit().id() == source.commit.id() {
+            // The absorbed hunks are now in the parent tree, so they vanish
+            // from the rebased source commit.
+            rewritten_source = Some(rewriter.rebase(settings)?.write()?);
+            return Ok(());
+        }
+        let Some(tree_builder) = selected_trees.remove(rewriter.old_commit().id()) else {
+            rewriter.rebase(settings)?.write()?;
+            num_rebased += 1;
+            return Ok(());
+        };
+        let selected_tree = store.get_root_tree(&tree_builder.write_tree(&store)?)?;
+        let commit_builder = rewriter.rebase(settings)?;
+        let destination_tree = store.get_root_tree(commit_builder.tree_id())?;
+        let new_tree = destination_tree.merge(&source.parent_tree, &selected_tree)?;
+        let mut predecessors = commit_builder.predecessors().to_vec();
+        predecessors.push(source.commit.id().clone());
+        let new_commit = commit_builder
+            .set_tree_id(new_tree.id())
+            .set_predecessors(predecessors)
+            .write()?;
+        rewritten_destinations.push(new_commit);
+        Ok(())
+    })?;
+    Ok(AbsorbStats {
+        rewritten_source,
+        rewritten_destinations,
+        num_rebased,
+    })
+}
+
+fn to_file_id(value: Merge<Option<TreeValue>>) -> Result<Option<(FileId, bool)>, &'static str> {
+    match value.into_resolved() {
+        Ok(None) => Ok(None),
+        Ok(Some(TreeValue::File { id, executable })) => Ok(Some((id, executable))),
+        Ok(Some(_)) => Err("Not a regular file"),
+        Err(_) => Err("Is a conflict"),
+    }
+}
+
+fn read_file_contents(store: &Store, path: &RepoPath, id: &FileId) -> BackendResult<Vec<u8>> {
+    let mut reader = store.read_file(path, id)?;
+    let mut content = vec![];
+    reader
+        .read_to_end(&mut content)
+        .map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    Ok(content)
+}
+
+fn count_lines(text: &[u8]) -> usize {
+    text.split_inclusive(|b| *b == b'\n').count()
+}
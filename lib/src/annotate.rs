@@ -0,0 +1,309 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Attributes each line of a file to the commit that introduced it.
+//!
+//! Starting from the file in a given commit, the lines are traced back through
+//! the ancestors. A line is attributed to the first commit in which it can't be
+//! found in any of the parents. If the file doesn't exist in a parent, its
+//! lines are traced to the file it was copied or renamed from, as reported by
+//! the backend's copy records.
+
+use std::collections::HashMap;
+use std::io::Read as _;
+use std::rc::Rc;
+
+use bstr::BString;
+use futures::StreamExt as _;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::CommitId;
+use crate::commit::Commit;
+use crate::conflicts::materialize_merge_result;
+use crate::conflicts::materialize_tree_value;
+use crate::conflicts::MaterializedTreeValue;
+use crate::copies;
+use crate::diff::Diff;
+use crate::diff::DiffHunk;
+use crate::repo::Repo;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::revset::RevsetEvaluationError;
+use crate::revset::RevsetExpression;
+
+/// A line of the annotated file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AnnotatedLine {
+    /// The commit that introduced the line.
+    pub commit_id: CommitId,
+    /// The path of the file in that commit. This differs from the annotated
+    /// path if the file was renamed since.
+    pub path: RepoPathBuf,
+    /// 0-based line number of the line in the file of that commit.
+    pub original_line_number: usize,
+    /// The content of the line, including the line terminator if any.
+    pub content: BString,
+}
+
+/// Error that may occur while annotating a file.
+#[derive(Debug, Error)]
+pub enum AnnotateError {
+    /// Failed to read a commit or a file from the backend.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    /// Failed to walk the ancestors of the starting commit.
+    #[error(transparent)]
+    RevsetEvaluation(#[from] RevsetEvaluationError),
+}
+
+/// Lines of a file in some commit that still need to be attributed.
+struct PendingLines {
+    path: RepoPathBuf,
+    content: Vec<u8>,
+    /// Pairs of the line number in the starting commit and the line number in
+    /// `content`.
+    lines: Vec<(usize, usize)>,
+}
+
+/// Annotates the file at `file_path` in `starting_commit`.
+///
+/// Returns one entry per line of the file. A file that doesn't exist, or
+/// isn't a regular file, has no lines. Conflicted files are annotated with
+/// their conflict markers materialized.
+pub fn get_annotation_for_file(
+    repo: &dyn Repo,
+    starting_commit: &Commit,
+    file_path: &RepoPath,
+) -> Result<Vec<AnnotatedLine>, AnnotateError> {
+    get_annotation_in_domain(repo, starting_commit, &RevsetExpression::all(), file_path)
+}
+
+/// Annotates the file at `file_path` in `starting_commit`, only tracing the
+/// lines through the ancestors in `domain`.
+///
+/// A line that came from outside the domain is attributed to the first commit
+/// outside the domain in which it was found. The `domain` must be resolvable
+/// without a symbol resolver.
+pub fn get_annotation_in_domain(
+    repo: &dyn Repo,
+    starting_commit: &Commit,
+    domain: &Rc<RevsetExpression>,
+    file_path: &RepoPath,
+) -> Result<Vec<AnnotatedLine>, AnnotateError> {
+    let original_content = get_file_contents(repo, starting_commit, file_path)?;
+    let num_lines = split_lines(&original_content).count();
+    let mut attributions: Vec<Option<(CommitId, RepoPathBuf, usize)>> = vec![None; num_lines];
+
+    // A commit can have pending lines from more than one path if the file was
+    // copied or renamed on only some of the paths leading to it.
+    let mut pending: HashMap<CommitId, Vec<PendingLines>> = HashMap::new();
+    pending.insert(
+        starting_commit.id().clone(),
+        vec![PendingLines {
+            path: file_path.to_owned(),
+            content: original_content.clone(),
+            lines: (0..num_lines).map(|i| (i, i)).collect(),
+        }],
+    );
+    // Ancestors are visited children first, so all the lines a commit received
+    // from its children are known by the time it is visited.
+    let ancestors = RevsetExpression::commit(starting_commit.id().clone())
+        .ancestors()
+        .intersection(domain)
+        .evaluate_programmatic(repo)?;
+    for commit_id in ancestors.iter() {
+        let Some(pending_files) = pending.remove(&commit_id) else {
+            continue;
+        };
+        let commit = repo.store().get_commit(&commit_id)?;
+        let tree = commit.tree()?;
+        for PendingLines {
+            path,
+            content,
+            lines,
+        } in pending_files
+        {
+            let value = tree.path_value(&path)?;
+            let mut unattributed = lines;
+            for parent in commit.parents() {
+                let parent = parent?;
+                if unattributed.is_empty() {
+                    break;
+                }
+                let (parent_path, parent_content) = if parent.tree_id() == commit.tree_id() {
+                    (path.clone(), content.clone())
+                } else {
+                    let parent_value = parent.tree()?.path_value(&path)?;
+                    if parent_value == value {
+                        (path.clone(), content.clone())
+                    } else if parent_value.is_present() {
+                        (path.clone(), get_file_contents(repo, &parent, &path)?)
+                    } else if let Some(source) = find_copy_source(repo, &parent, &commit, &path)? {
+                        let source_content = get_file_contents(repo, &parent, &source)?;
+                        (source, source_content)
+                    } else {
+                        continue;
+                    }
+                };
+                let line_map = map_unchanged_lines(&content, &parent_content);
+                let (moved, remaining): (Vec<_>, Vec<_>) = unattributed
+                    .into_iter()
+                    .partition(|(_, line)| line_map.contains_key(line));
+                unattributed = remaining;
+                if moved.is_empty() {
+                    continue;
+                }
+                let parent_files = pending.entry(parent.id().clone()).or_default();
+                let entry = match parent_files
+                    .iter()
+                    .position(|pending_lines| pending_lines.path == parent_path)
+                {
+                    Some(index) => &mut parent_files[index],
+                    None => {
+                        parent_files.push(PendingLines {
+                            path: parent_path,
+                            content: parent_content,
+                            lines: vec![],
+                        });
+                        parent_files.last_mut().unwrap()
+                    }
+                };
+                entry.lines.extend(
+                    moved
+                        .into_iter()
+                        .map(|(original, line)| (original, line_map[&line])),
+                );
+            }
+            for (original, line) in unattributed {
+                attributions[original] = Some((commit_id.clone(), path.clone(), line));
+            }
+        }
+        if pending.is_empty() {
+            break;
+        }
+    }
+    for (commit_id, pending_files) in pending {
+        for PendingLines { path, lines, .. } in pending_files {
+            for (original, line) in lines {
+                attributions[original] = Some((commit_id.clone(), path.clone(), line));
+            }
+        }
+    }
+
+    let annotated_lines = split_lines(&original_content)
+        .zip(attributions)
+        .map(|(content, attribution)| {
+            // Every line is attributed by the time the root commit is visited,
+            // or by the end of the domain.
+            let (commit_id, path, original_line_number) =
+                attribution.expect("line should have been attributed");
+            AnnotatedLine {
+                commit_id,
+                path,
+                original_line_number,
+                content: content.into(),
+            }
+        })
+        .collect();
+    Ok(annotated_lines)
+}
+
+fn split_lines(content: &[u8]) -> impl Iterator<Item = &[u8]> {
+    content.split_inclusive(|b| *b == b'\n')
+}
+
+fn count_lines(content: &[u8]) -> usize {
+    split_lines(content).count()
+}
+
+/// Returns a map from the line numbers in `content` to the line numbers in
+/// `parent_content` for the lines that are the same in both.
+fn map_unchanged_lines(content: &[u8], parent_content: &[u8]) -> HashMap<usize, usize> {
+    let mut line_map = HashMap::new();
+    if content == parent_content {
+        line_map.extend((0..count_lines(content)).map(|i| (i, i)));
+        return line_map;
+    }
+    let diff = Diff::by_line([content, parent_content]);
+    let mut line = 0;
+    let mut parent_line = 0;
+    for hunk in diff.hunks() {
+        match hunk {
+            DiffHunk::Matching(text) => {
+                let num_lines = count_lines(text);
+                line_map.extend((0..num_lines).map(|i| (line + i, parent_line + i)));
+                line += num_lines;
+                parent_line += num_lines;
+            }
+            DiffHunk::Different(texts) => {
+                line += count_lines(texts[0]);
+                parent_line += count_lines(texts[1]);
+            }
+        }
+    }
+    line_map
+}
+
+/// Finds the path in `parent` that `path` in `commit` was copied or renamed
+/// from.
+fn find_copy_source(
+    repo: &dyn Repo,
+    parent: &Commit,
+    commit: &Commit,
+    path: &RepoPath,
+) -> Result<Option<RepoPathBuf>, AnnotateError> {
+    // The root commit has no files to copy from, and it may not be readable
+    // by the backend.
+    if parent.id() == repo.store().root_commit_id() {
+        return Ok(None);
+    }
+    let paths = [path.to_owned()];
+    let mut records = copies::get_copy_records(repo, Some(&paths), parent.id(), commit.id())?;
+    while let Some(record) = records.next().block_on() {
+        let record = record?;
+        if *record.target == *path {
+            return Ok(Some(record.source));
+        }
+    }
+    Ok(None)
+}
+
+fn get_file_contents(
+    repo: &dyn Repo,
+    commit: &Commit,
+    file_path: &RepoPath,
+) -> Result<Vec<u8>, AnnotateError> {
+    let value = commit.tree()?.path_value(file_path)?;
+    let materialized = materialize_tree_value(repo.store(), file_path, value).block_on()?;
+    let mut content = vec![];
+    match materialized {
+        MaterializedTreeValue::File { id, mut reader, .. } => {
+            reader
+                .read_to_end(&mut content)
+                .map_err(|err| BackendError::ReadFile {
+                    path: file_path.to_owned(),
+                    id,
+                    source: err.into(),
+                })?;
+        }
+        MaterializedTreeValue::FileConflict { contents, .. } => {
+            materialize_merge_result(&contents, &mut content)
+                .expect("writing to an in-memory buffer should never fail");
+        }
+        _ => {}
+    }
+    Ok(content)
+}
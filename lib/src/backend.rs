@@ -140,6 +140,19 @@ pub struct Commit {
     pub author: Signature,
     pub committer: Signature,
     pub secure_sig: Option<SecureSig>,
+    /// Copies and renames that were recorded when the commit was created,
+    /// relative to its parents.
+    pub recorded_copies: Vec<RecordedCopy>,
+}
+
+/// A copy or rename of a file, recorded by the user instead of detected by
+/// comparing contents.
+#[derive(ContentHash, Debug, PartialEq, Eq, Clone)]
+pub struct RecordedCopy {
+    /// The path the file was copied from, in the parents of the commit.
+    pub source: RepoPathBuf,
+    /// The path the file was copied to, in the commit.
+    pub target: RepoPathBuf,
 }
 
 #[derive(ContentHash, Debug, PartialEq, Eq, Clone)]
@@ -376,6 +389,7 @@ pub fn make_root_commit(root_change_id: ChangeId, empty_tree_id: TreeId) -> Comm
         author: signature.clone(),
         committer: signature,
         secure_sig: None,
+        recorded_copies: vec![],
     }
 }
 
@@ -452,6 +466,18 @@ pub trait Backend: Send + Sync + Debug {
         sign_with: Option<&mut SigningFn>,
     ) -> BackendResult<(CommitId, Commit)>;
 
+    /// Starts buffering writes that the backend can apply in bulk, such as
+    /// the metadata of written commits. Objects written within the batch can
+    /// be read back by this backend, but other processes may not see them
+    /// until `finish_write_batch()` is called.
+    fn start_write_batch(&self) {}
+
+    /// Applies the writes buffered since `start_write_batch()`, and stops
+    /// buffering.
+    fn finish_write_batch(&self) -> BackendResult<()> {
+        Ok(())
+    }
+
     /// Get copy records for the dag range `root..head`.  If `paths` is None
     /// include all paths, otherwise restrict to only `paths`.
     ///
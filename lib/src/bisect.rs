@@ -0,0 +1,236 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Finds the first bad commit by bisecting the commit graph.
+//!
+//! The state of a bisection is stored as refs in the view, so it's recorded by
+//! operations and can be undone like any other change to the repo.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use itertools::Itertools as _;
+use thiserror::Error;
+
+use crate::backend::CommitId;
+use crate::graph::GraphEdgeType;
+use crate::object_id::ObjectId as _;
+use crate::op_store::RefTarget;
+use crate::repo::MutableRepo;
+use crate::repo::Repo;
+use crate::revset::RevsetEvaluationError;
+use crate::revset::RevsetExpression;
+use crate::view::View;
+
+const ORIGINAL_REF: &str = "original";
+const CURRENT_REF: &str = "current";
+const BAD_REF: &str = "bad";
+const GOOD_REF_PREFIX: &str = "good-";
+const SKIP_REF_PREFIX: &str = "skip-";
+
+/// Error that may occur while bisecting.
+#[derive(Debug, Error)]
+pub enum BisectError {
+    /// The bad commit has no ancestors that aren't known to be good.
+    #[error("The bad revision {} is an ancestor of a good revision", .0.hex())]
+    BadIsAncestorOfGood(CommitId),
+    /// Failed to evaluate the candidate revisions.
+    #[error(transparent)]
+    RevsetEvaluation(#[from] RevsetEvaluationError),
+}
+
+/// Revisions marked during a bisection.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BisectState {
+    /// The working-copy commit when the bisection was started. `None` if no
+    /// bisection is in progress.
+    pub original: Option<CommitId>,
+    /// The revision that was last checked out for testing.
+    pub current: Option<CommitId>,
+    /// The most recent revision marked as bad.
+    pub bad: Option<CommitId>,
+    /// Revisions marked as good.
+    pub good: Vec<CommitId>,
+    /// Revisions that couldn't be tested.
+    pub skipped: Vec<CommitId>,
+}
+
+impl BisectState {
+    /// Reads the bisection state from the refs in the view.
+    ///
+    /// Refs that became conflicted by concurrent operations contribute all of
+    /// their added commits to the good and skipped sets. A conflicted bad ref
+    /// is treated as unset, so the user has to mark a bad revision again.
+    pub fn load(view: &View) -> Self {
+        let mut state = BisectState::default();
+        for (name, target) in view.bisect_refs() {
+            if name == ORIGINAL_REF {
+                state.original = target.as_normal().cloned();
+            } else if name == CURRENT_REF {
+                state.current = target.as_normal().cloned();
+            } else if name == BAD_REF {
+                state.bad = target.as_normal().cloned();
+            } else if name.starts_with(GOOD_REF_PREFIX) {
+                state.good.extend(target.added_ids().cloned());
+            } else if name.starts_with(SKIP_REF_PREFIX) {
+                state.skipped.extend(target.added_ids().cloned());
+            }
+        }
+        state.good = state.good.into_iter().unique().collect();
+        state.skipped = state.skipped.into_iter().unique().collect();
+        state
+    }
+
+    /// Whether a bisection has been started.
+    pub fn is_in_progress(&self) -> bool {
+        self.original.is_some()
+    }
+
+    /// Replaces the bisection refs in the repo with this state. Saving the
+    /// default state ends the bisection.
+    pub fn save(&self, mut_repo: &mut MutableRepo) {
+        let old_names = mut_repo.view().bisect_refs().keys().cloned().collect_vec();
+        for name in old_names {
+            mut_repo.set_bisect_ref_target(&name, RefTarget::absent());
+        }
+        let single_refs = [
+            (ORIGINAL_REF, &self.original),
+            (CURRENT_REF, &self.current),
+            (BAD_REF, &self.bad),
+        ];
+        for (name, id) in single_refs {
+            if let Some(id) = id {
+                mut_repo.set_bisect_ref_target(name, RefTarget::normal(id.clone()));
+            }
+        }
+        for id in &self.good {
+            let name = format!("{GOOD_REF_PREFIX}{}", id.hex());
+            mut_repo.set_bisect_ref_target(&name, RefTarget::normal(id.clone()));
+        }
+        for id in &self.skipped {
+            let name = format!("{SKIP_REF_PREFIX}{}", id.hex());
+            mut_repo.set_bisect_ref_target(&name, RefTarget::normal(id.clone()));
+        }
+    }
+}
+
+/// The outcome of a bisection step.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BisectStep {
+    /// The commit should be tested next.
+    Test {
+        /// The commit to test.
+        commit_id: CommitId,
+        /// The number of untested candidates left after testing the commit, in
+        /// the worst case.
+        remaining: usize,
+    },
+    /// The first bad commit was found.
+    Found(CommitId),
+    /// All remaining candidates were skipped, so the first bad commit is one
+    /// of these, in reverse topological order.
+    Inconclusive(Vec<CommitId>),
+}
+
+/// Picks the next commit to test in order to find the first bad commit.
+///
+/// The candidates are the ancestors of `bad` that aren't ancestors of any of
+/// the `good` commits. The commit that splits the candidates most evenly into
+/// its ancestors and the rest is tested next, which also works across merges.
+/// Ties are broken in favor of the commit that comes first in reverse
+/// topological order, so the result is deterministic.
+pub fn next_step(
+    repo: &dyn Repo,
+    bad: &CommitId,
+    good: &[CommitId],
+    skipped: &HashSet<CommitId>,
+) -> Result<BisectStep, BisectError> {
+    let candidates_expression = RevsetExpression::commits(good.to_vec())
+        .range(&RevsetExpression::commits(vec![bad.clone()]));
+    let candidates = candidates_expression.evaluate_programmatic(repo)?;
+    // The graph is in reverse topological order. Edges point to the closest
+    // ancestors within the candidates, except for missing edges, which point
+    // to commits outside of it.
+    let graph = candidates
+        .iter_graph()
+        .map(|(id, edges)| {
+            let parents = edges
+                .into_iter()
+                .filter(|edge| edge.edge_type != GraphEdgeType::Missing)
+                .map(|edge| edge.target)
+                .collect_vec();
+            (id, parents)
+        })
+        .collect_vec();
+    if graph.is_empty() {
+        return Err(BisectError::BadIsAncestorOfGood(bad.clone()));
+    }
+    let num_candidates = graph.len();
+    let positions: HashMap<&CommitId, usize> = graph
+        .iter()
+        .enumerate()
+        .map(|(pos, (id, _))| (id, pos))
+        .collect();
+
+    // Compute the set of ancestors of each candidate as a bitset, visiting
+    // parents before their children. Bitsets are needed to avoid counting
+    // ancestors reachable through multiple parents of a merge twice.
+    let num_words = num_candidates.div_ceil(64);
+    let mut ancestors: Vec<Vec<u64>> = vec![vec![]; num_candidates];
+    for pos in (0..num_candidates).rev() {
+        let mut bits = vec![0u64; num_words];
+        bits[pos / 64] |= 1u64 << (pos % 64);
+        for parent_id in &graph[pos].1 {
+            let parent_bits = &ancestors[positions[parent_id]];
+            for (word, parent_word) in bits.iter_mut().zip(parent_bits) {
+                *word |= parent_word;
+            }
+        }
+        ancestors[pos] = bits;
+    }
+
+    let mut best: Option<(usize, usize)> = None;
+    for (pos, (id, _)) in graph.iter().enumerate() {
+        if id == bad || skipped.contains(id) {
+            continue;
+        }
+        let num_ancestors: usize = ancestors[pos]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        let score = num_ancestors.min(num_candidates - num_ancestors);
+        if best.map_or(true, |(_, best_score)| score > best_score) {
+            best = Some((pos, score));
+        }
+    }
+    match best {
+        Some((pos, _)) => {
+            let num_ancestors: usize = ancestors[pos]
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum();
+            // If the commit is bad, its other ancestors are left. If it's good,
+            // the candidates other than its ancestors and the bad commit are.
+            let remaining = (num_ancestors - 1).max(num_candidates - num_ancestors - 1);
+            Ok(BisectStep::Test {
+                commit_id: graph[pos].0.clone(),
+                remaining,
+            })
+        }
+        None if num_candidates == 1 => Ok(BisectStep::Found(bad.clone())),
+        None => Ok(BisectStep::Inconclusive(
+            graph.into_iter().map(|(id, _)| id).collect(),
+        )),
+    }
+}
@@ -29,6 +29,7 @@ use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
+use crate::backend::RecordedCopy;
 use crate::backend::Signature;
 use crate::merged_tree::MergedTree;
 use crate::repo::Repo;
@@ -162,6 +163,11 @@ impl Commit {
         &self.data.committer
     }
 
+    /// Copies and renames recorded in this commit, relative to its parents.
+    pub fn recorded_copies(&self) -> &[RecordedCopy] {
+        &self.data.recorded_copies
+    }
+
     /// A commit is discardable if it has no change from its parent, and an
     /// empty description.
     pub fn is_discardable(&self, repo: &dyn Repo) -> BackendResult<bool> {
@@ -23,6 +23,7 @@ use crate::backend::BackendResult;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
+use crate::backend::RecordedCopy;
 use crate::backend::Signature;
 use crate::commit::Commit;
 use crate::repo::MutableRepo;
@@ -114,6 +115,15 @@ impl CommitBuilder<'_> {
         self
     }
 
+    pub fn recorded_copies(&self) -> &[RecordedCopy] {
+        self.inner.recorded_copies()
+    }
+
+    pub fn set_recorded_copies(mut self, recorded_copies: Vec<RecordedCopy>) -> Self {
+        self.inner.set_recorded_copies(recorded_copies);
+        self
+    }
+
     pub fn sign_settings(&self) -> &SignSettings {
         self.inner.sign_settings()
     }
@@ -165,6 +175,7 @@ impl DetachedCommitBuilder {
             author: signature.clone(),
             committer: signature,
             secure_sig: None,
+            recorded_copies: vec![],
         };
         DetachedCommitBuilder {
             store,
@@ -295,6 +306,15 @@ impl DetachedCommitBuilder {
         self
     }
 
+    pub fn recorded_copies(&self) -> &[RecordedCopy] {
+        &self.commit.recorded_copies
+    }
+
+    pub fn set_recorded_copies(&mut self, recorded_copies: Vec<RecordedCopy>) -> &mut Self {
+        self.commit.recorded_copies = recorded_copies;
+        self
+    }
+
     pub fn sign_settings(&self) -> &SignSettings {
         &self.sign_settings
     }
@@ -19,6 +19,7 @@ use std::io::Write;
 use std::iter::zip;
 
 use bstr::BString;
+use bstr::ByteSlice as _;
 use futures::stream::BoxStream;
 use futures::try_join;
 use futures::Stream;
@@ -42,6 +43,7 @@ use crate::diff::Diff;
 use crate::diff::DiffHunk;
 use crate::files;
 use crate::files::MergeResult;
+use crate::merge::FileMode;
 use crate::merge::Merge;
 use crate::merge::MergeBuilder;
 use crate::merge::MergedTreeValue;
@@ -53,11 +55,15 @@ const CONFLICT_END_LINE: &[u8] = b">>>>>>>";
 const CONFLICT_DIFF_LINE: &[u8] = b"%%%%%%%";
 const CONFLICT_MINUS_LINE: &[u8] = b"-------";
 const CONFLICT_PLUS_LINE: &[u8] = b"+++++++";
+const CONFLICT_BASE_LINE: &[u8] = b"|||||||";
+const CONFLICT_SEPARATOR_LINE: &[u8] = b"=======";
 const CONFLICT_START_LINE_CHAR: u8 = CONFLICT_START_LINE[0];
 const CONFLICT_END_LINE_CHAR: u8 = CONFLICT_END_LINE[0];
 const CONFLICT_DIFF_LINE_CHAR: u8 = CONFLICT_DIFF_LINE[0];
 const CONFLICT_MINUS_LINE_CHAR: u8 = CONFLICT_MINUS_LINE[0];
 const CONFLICT_PLUS_LINE_CHAR: u8 = CONFLICT_PLUS_LINE[0];
+const CONFLICT_BASE_LINE_CHAR: u8 = CONFLICT_BASE_LINE[0];
+const CONFLICT_SEPARATOR_LINE_CHAR: u8 = CONFLICT_SEPARATOR_LINE[0];
 
 /// A conflict marker is one of the separators, optionally followed by a space
 /// and some text.
@@ -65,7 +71,7 @@ const CONFLICT_PLUS_LINE_CHAR: u8 = CONFLICT_PLUS_LINE[0];
 // separators. This could be useful to make it possible to allow conflict
 // markers inside the text of the conflicts.
 static CONFLICT_MARKER_REGEX: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
-    RegexBuilder::new(r"^(<{7}|>{7}|%{7}|\-{7}|\+{7})( .*)?$")
+    RegexBuilder::new(r"^(<{7}|>{7}|%{7}|\-{7}|\+{7}|\|{7}|={7})( .*)?$")
         .multi_line(true)
         .build()
         .unwrap()
@@ -132,6 +138,125 @@ pub async fn extract_as_single_hunk(
     Ok(builder.build())
 }
 
+async fn get_file_or_symlink_contents(
+    store: &Store,
+    path: &RepoPath,
+    term: &Option<TreeValue>,
+) -> BackendResult<Option<BString>> {
+    match term {
+        None => Ok(None),
+        Some(TreeValue::File { id, executable: _ }) => {
+            let content = get_file_contents(store, path, &Some(id.clone())).await?;
+            Ok(Some(content))
+        }
+        Some(TreeValue::Symlink(id)) => {
+            let target = store.read_symlink_async(path, id).await?;
+            Ok(Some(target.into()))
+        }
+        Some(_) => unreachable!("not a file or a symlink: {term:?}"),
+    }
+}
+
+/// A conflict between files and symlinks whose contents can be resolved but
+/// whose modes can't. For example, a file added with the same contents on
+/// both sides, but executable on only one of them.
+#[derive(Clone, Debug)]
+pub struct ModeConflict {
+    /// The modes of the terms of the simplified conflict.
+    pub modes: Merge<Option<FileMode>>,
+    // A side with the resolved contents
+    resolved: TreeValue,
+}
+
+impl ModeConflict {
+    /// Resolves the conflict to the resolved contents with the given `mode`.
+    /// A file becomes a symlink to its contents, and a symlink becomes a file
+    /// containing its target.
+    pub async fn resolve(
+        &self,
+        store: &Store,
+        path: &RepoPath,
+        mode: FileMode,
+    ) -> BackendResult<TreeValue> {
+        let executable = mode == FileMode::Executable;
+        match (&self.resolved, mode) {
+            (TreeValue::File { id, executable: _ }, FileMode::Normal | FileMode::Executable) => {
+                Ok(TreeValue::File {
+                    id: id.clone(),
+                    executable,
+                })
+            }
+            (TreeValue::Symlink(id), FileMode::Symlink) => Ok(TreeValue::Symlink(id.clone())),
+            (TreeValue::File { id, executable: _ }, FileMode::Symlink) => {
+                let content = get_file_contents(store, path, &Some(id.clone())).await?;
+                let target = content.to_str().map_err(|err| {
+                    BackendError::Other(
+                        format!(
+                            "Contents of {} are not a valid symlink target: {err}",
+                            path.as_internal_file_string()
+                        )
+                        .into(),
+                    )
+                })?;
+                let id = store.write_symlink(path, target).await?;
+                Ok(TreeValue::Symlink(id))
+            }
+            (TreeValue::Symlink(id), FileMode::Normal | FileMode::Executable) => {
+                let target = store.read_symlink_async(path, id).await?;
+                let id = store.write_file(path, &mut target.as_bytes()).await?;
+                Ok(TreeValue::File { id, executable })
+            }
+            (resolved, _) => unreachable!("not a file or a symlink: {resolved:?}"),
+        }
+    }
+}
+
+/// Returns the mode conflict if `conflict` is between files and symlinks that
+/// only differ in their modes. The targets of symlinks are compared with the
+/// contents of files. Conflicts with deleted sides aren't mode conflicts.
+pub async fn extract_mode_conflict(
+    store: &Store,
+    path: &RepoPath,
+    conflict: &MergedTreeValue,
+) -> BackendResult<Option<ModeConflict>> {
+    let conflict = conflict.clone().simplify();
+    let Some(modes) = conflict.to_mode_merge() else {
+        return Ok(None);
+    };
+    if modes.adds().any(|mode| mode.is_none()) || modes.resolve_trivial().is_some() {
+        return Ok(None);
+    }
+    let resolved_index = if let Some(file_ids) = conflict.to_file_merge() {
+        // Avoid reading the contents if there are only files
+        let Some(Some(resolved_id)) = file_ids.resolve_trivial() else {
+            return Ok(None);
+        };
+        file_ids
+            .adds()
+            .position(|id| id.as_ref() == Some(resolved_id))
+    } else {
+        let builder: MergeBuilder<Option<BString>> = futures::stream::iter(conflict.iter())
+            .then(|term| get_file_or_symlink_contents(store, path, term))
+            .try_collect()
+            .await?;
+        let contents = builder.build();
+        let Some(Some(resolved_content)) = contents.resolve_trivial() else {
+            return Ok(None);
+        };
+        let index = contents
+            .adds()
+            .position(|content| content.as_ref() == Some(resolved_content));
+        index
+    };
+    // The trivial resolution is one of the sides
+    let resolved = conflict
+        .get_add(resolved_index.unwrap())
+        .unwrap()
+        .clone()
+        .unwrap();
+    Ok(Some(ModeConflict { modes, resolved }))
+}
+
 /// A type similar to `MergedTreeValue` but with associated data to include in
 /// e.g. the working copy or in a diff.
 pub enum MaterializedTreeValue {
@@ -229,9 +354,37 @@ async fn materialize_tree_value_no_access_denied(
     }
 }
 
+/// Style of the conflict markers written when materializing conflicts. Conflict
+/// markers of any style can be parsed back.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictMarkerStyle {
+    /// Sides as diffs from the bases where possible, and as snapshots
+    /// otherwise.
+    #[default]
+    Diff,
+    /// All sides and bases as snapshots.
+    Snapshot,
+    /// Git's "diff3" style. Conflicts with more than two sides are written in
+    /// the snapshot style instead.
+    Git,
+    /// Like `Git`, but lines both sides start or end with are moved out of the
+    /// conflict, like Git's "zdiff3" style.
+    Zdiff3,
+}
+
+/// Writes `single_hunk` with conflict markers in the default style.
 pub fn materialize_merge_result(
     single_hunk: &Merge<BString>,
     output: &mut dyn Write,
+) -> std::io::Result<()> {
+    materialize_merge_result_with_style(single_hunk, ConflictMarkerStyle::default(), output)
+}
+
+pub fn materialize_merge_result_with_style(
+    single_hunk: &Merge<BString>,
+    conflict_marker_style: ConflictMarkerStyle,
+    output: &mut dyn Write,
 ) -> std::io::Result<()> {
     let merge_result = files::merge(single_hunk);
     match merge_result {
@@ -247,82 +400,31 @@ pub fn materialize_merge_result(
             for hunk in hunks {
                 if let Some(content) = hunk.as_resolved() {
                     output.write_all(content)?;
-                } else {
-                    conflict_index += 1;
-                    output.write_all(CONFLICT_START_LINE)?;
-                    output.write_all(
-                        format!(" Conflict {conflict_index} of {num_conflicts}\n").as_bytes(),
-                    )?;
-                    let mut add_index = 0;
-                    for (base_index, left) in hunk.removes().enumerate() {
-                        // The vast majority of conflicts one actually tries to
-                        // resolve manually have 1 base.
-                        let base_str = if hunk.removes().len() == 1 {
-                            "base".to_string()
-                        } else {
-                            format!("base #{}", base_index + 1)
-                        };
-
-                        let right1 = if let Some(right1) = hunk.get_add(add_index) {
-                            right1
-                        } else {
-                            // If we have no more positive terms, emit the remaining negative
-                            // terms as snapshots.
-                            output.write_all(CONFLICT_MINUS_LINE)?;
-                            output.write_all(format!(" Contents of {base_str}\n").as_bytes())?;
-                            output.write_all(left)?;
-                            continue;
-                        };
-                        let diff1 = Diff::by_line([&left, &right1]).hunks().collect_vec();
-                        // Check if the diff against the next positive term is better. Since
-                        // we want to preserve the order of the terms, we don't match against
-                        // any later positive terms.
-                        if let Some(right2) = hunk.get_add(add_index + 1) {
-                            let diff2 = Diff::by_line([&left, &right2]).hunks().collect_vec();
-                            if diff_size(&diff2) < diff_size(&diff1) {
-                                // If the next positive term is a better match, emit
-                                // the current positive term as a snapshot and the next
-                                // positive term as a diff.
-                                output.write_all(CONFLICT_PLUS_LINE)?;
-                                output.write_all(
-                                    format!(" Contents of side #{}\n", add_index + 1).as_bytes(),
-                                )?;
-                                output.write_all(right1)?;
-                                output.write_all(CONFLICT_DIFF_LINE)?;
-                                output.write_all(
-                                    format!(
-                                        " Changes from {base_str} to side #{}\n",
-                                        add_index + 2
-                                    )
-                                    .as_bytes(),
-                                )?;
-                                write_diff_hunks(&diff2, output)?;
-                                add_index += 2;
-                                continue;
-                            }
-                        }
-
-                        output.write_all(CONFLICT_DIFF_LINE)?;
-                        output.write_all(
-                            format!(" Changes from {base_str} to side #{}\n", add_index + 1)
-                                .as_bytes(),
-                        )?;
-                        write_diff_hunks(&diff1, output)?;
-                        add_index += 1;
+                    continue;
+                }
+                conflict_index += 1;
+                let conflict_info = format!("Conflict {conflict_index} of {num_conflicts}");
+                match conflict_marker_style {
+                    ConflictMarkerStyle::Diff => {
+                        materialize_diff_style_conflict(&hunk, &conflict_info, output)?;
                     }
-
-                    //  Emit the remaining positive terms as snapshots.
-                    for (add_index, slice) in hunk.adds().enumerate().skip(add_index) {
-                        output.write_all(CONFLICT_PLUS_LINE)?;
-                        output.write_all(
-                            format!(" Contents of side #{}\n", add_index + 1).as_bytes(),
+                    ConflictMarkerStyle::Git | ConflictMarkerStyle::Zdiff3
+                        if hunk.num_sides() == 2 =>
+                    {
+                        let trim_common_lines =
+                            conflict_marker_style == ConflictMarkerStyle::Zdiff3;
+                        materialize_git_style_conflict(
+                            &hunk,
+                            &conflict_info,
+                            trim_common_lines,
+                            output,
                         )?;
-                        output.write_all(slice)?;
                     }
-                    output.write_all(CONFLICT_END_LINE)?;
-                    output.write_all(
-                        format!(" Conflict {conflict_index} of {num_conflicts} ends\n").as_bytes(),
-                    )?;
+                    ConflictMarkerStyle::Snapshot
+                    | ConflictMarkerStyle::Git
+                    | ConflictMarkerStyle::Zdiff3 => {
+                        materialize_snapshot_style_conflict(&hunk, &conflict_info, output)?;
+                    }
                 }
             }
         }
@@ -330,6 +432,176 @@ pub fn materialize_merge_result(
     Ok(())
 }
 
+fn base_label(hunk: &Merge<BString>, base_index: usize) -> String {
+    // The vast majority of conflicts one actually tries to resolve manually
+    // have 1 base.
+    if hunk.removes().len() == 1 {
+        "base".to_string()
+    } else {
+        format!("base #{}", base_index + 1)
+    }
+}
+
+fn materialize_diff_style_conflict(
+    hunk: &Merge<BString>,
+    conflict_info: &str,
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    output.write_all(CONFLICT_START_LINE)?;
+    output.write_all(format!(" {conflict_info}\n").as_bytes())?;
+    let mut add_index = 0;
+    for (base_index, left) in hunk.removes().enumerate() {
+        let base_str = base_label(hunk, base_index);
+
+        let right1 = if let Some(right1) = hunk.get_add(add_index) {
+            right1
+        } else {
+            // If we have no more positive terms, emit the remaining negative
+            // terms as snapshots.
+            output.write_all(CONFLICT_MINUS_LINE)?;
+            output.write_all(format!(" Contents of {base_str}\n").as_bytes())?;
+            output.write_all(left)?;
+            continue;
+        };
+        let diff1 = Diff::by_line([&left, &right1]).hunks().collect_vec();
+        // Check if the diff against the next positive term is better. Since
+        // we want to preserve the order of the terms, we don't match against
+        // any later positive terms.
+        if let Some(right2) = hunk.get_add(add_index + 1) {
+            let diff2 = Diff::by_line([&left, &right2]).hunks().collect_vec();
+            if diff_size(&diff2) < diff_size(&diff1) {
+                // If the next positive term is a better match, emit
+                // the current positive term as a snapshot and the next
+                // positive term as a diff.
+                output.write_all(CONFLICT_PLUS_LINE)?;
+                output.write_all(format!(" Contents of side #{}\n", add_index + 1).as_bytes())?;
+                output.write_all(right1)?;
+                output.write_all(CONFLICT_DIFF_LINE)?;
+                output.write_all(
+                    format!(" Changes from {base_str} to side #{}\n", add_index + 2).as_bytes(),
+                )?;
+                write_diff_hunks(&diff2, output)?;
+                add_index += 2;
+                continue;
+            }
+        }
+
+        output.write_all(CONFLICT_DIFF_LINE)?;
+        output.write_all(
+            format!(" Changes from {base_str} to side #{}\n", add_index + 1).as_bytes(),
+        )?;
+        write_diff_hunks(&diff1, output)?;
+        add_index += 1;
+    }
+
+    //  Emit the remaining positive terms as snapshots.
+    for (add_index, slice) in hunk.adds().enumerate().skip(add_index) {
+        output.write_all(CONFLICT_PLUS_LINE)?;
+        output.write_all(format!(" Contents of side #{}\n", add_index + 1).as_bytes())?;
+        output.write_all(slice)?;
+    }
+    output.write_all(CONFLICT_END_LINE)?;
+    output.write_all(format!(" {conflict_info} ends\n").as_bytes())?;
+    Ok(())
+}
+
+fn materialize_snapshot_style_conflict(
+    hunk: &Merge<BString>,
+    conflict_info: &str,
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    output.write_all(CONFLICT_START_LINE)?;
+    output.write_all(format!(" {conflict_info}\n").as_bytes())?;
+    for (add_index, add) in hunk.adds().enumerate() {
+        output.write_all(CONFLICT_PLUS_LINE)?;
+        output.write_all(format!(" Contents of side #{}\n", add_index + 1).as_bytes())?;
+        output.write_all(add)?;
+        if let Some(remove) = hunk.get_remove(add_index) {
+            output.write_all(CONFLICT_MINUS_LINE)?;
+            let base_str = base_label(hunk, add_index);
+            output.write_all(format!(" Contents of {base_str}\n").as_bytes())?;
+            output.write_all(remove)?;
+        }
+    }
+    output.write_all(CONFLICT_END_LINE)?;
+    output.write_all(format!(" {conflict_info} ends\n").as_bytes())?;
+    Ok(())
+}
+
+/// Writes a conflict with two sides using Git's markers. If
+/// `trim_common_lines` is true, the lines both sides start or end with are
+/// written outside the markers.
+fn materialize_git_style_conflict(
+    hunk: &Merge<BString>,
+    conflict_info: &str,
+    trim_common_lines: bool,
+    output: &mut dyn Write,
+) -> std::io::Result<()> {
+    let left = hunk.get_add(0).unwrap();
+    let base = hunk.get_remove(0).unwrap();
+    let right = hunk.get_add(1).unwrap();
+    let (prefix_len, suffix_len) = if trim_common_lines {
+        common_line_affix_lens(left, right)
+    } else {
+        (0, 0)
+    };
+    output.write_all(&left[..prefix_len])?;
+    output.write_all(CONFLICT_START_LINE)?;
+    output.write_all(format!(" Side #1 ({conflict_info})\n").as_bytes())?;
+    output.write_all(&left[prefix_len..left.len() - suffix_len])?;
+    output.write_all(CONFLICT_BASE_LINE)?;
+    output.write_all(b" Base\n")?;
+    output.write_all(base)?;
+    output.write_all(CONFLICT_SEPARATOR_LINE)?;
+    output.write_all(b"\n")?;
+    output.write_all(&right[prefix_len..right.len() - suffix_len])?;
+    output.write_all(CONFLICT_END_LINE)?;
+    output.write_all(format!(" Side #2 ({conflict_info} ends)\n").as_bytes())?;
+    output.write_all(&left[left.len() - suffix_len..])?;
+    Ok(())
+}
+
+/// Returns the lengths in bytes of the lines `left` and `right` both start
+/// with, and of the lines they both end with. The two don't overlap.
+fn common_line_affix_lens(left: &[u8], right: &[u8]) -> (usize, usize) {
+    let left_lines = left.split_inclusive(|b| *b == b'\n').collect_vec();
+    let right_lines = right.split_inclusive(|b| *b == b'\n').collect_vec();
+    let num_prefix_lines = zip(&left_lines, &right_lines)
+        .take_while(|(l, r)| l == r)
+        .count();
+    let num_suffix_lines = zip(left_lines.iter().rev(), right_lines.iter().rev())
+        .take(left_lines.len().min(right_lines.len()) - num_prefix_lines)
+        .take_while(|(l, r)| l == r)
+        .count();
+    let prefix_len = left_lines[..num_prefix_lines]
+        .iter()
+        .map(|line| line.len())
+        .sum();
+    let suffix_len = left_lines[left_lines.len() - num_suffix_lines..]
+        .iter()
+        .map(|line| line.len())
+        .sum();
+    (prefix_len, suffix_len)
+}
+
+/// Materializes the conflict markers of `single_hunk` on the rayon thread
+/// pool. Awaiting several of these concurrently, e.g. from a buffered stream,
+/// materializes the conflicts of different files in parallel.
+pub async fn materialize_merge_result_in_pool(
+    single_hunk: Merge<BString>,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> BString {
+    let (tx, rx) = futures::channel::oneshot::channel();
+    rayon::spawn(move || {
+        let mut data = vec![];
+        materialize_merge_result_with_style(&single_hunk, conflict_marker_style, &mut data)
+            .expect("Failed to materialize conflict to in-memory buffer");
+        tx.send(BString::from(data)).ok();
+    });
+    rx.await
+        .expect("materialization task should not be dropped")
+}
+
 fn diff_size(hunks: &[DiffHunk]) -> usize {
     hunks
         .iter()
@@ -415,6 +687,26 @@ pub fn parse_conflict(input: &[u8], num_sides: usize) -> Option<Vec<Merge<BStrin
 }
 
 fn parse_conflict_hunk(input: &[u8]) -> Merge<BString> {
+    // Conflicts in jj's styles start with a marker, while conflicts in Git's
+    // style start with the contents of the first side.
+    let starts_with_jj_marker = input
+        .split_inclusive(|b| *b == b'\n')
+        .next()
+        .filter(|line| CONFLICT_MARKER_REGEX.is_match_at(line, 0))
+        .is_some_and(|line| {
+            matches!(
+                line[0],
+                CONFLICT_DIFF_LINE_CHAR | CONFLICT_MINUS_LINE_CHAR | CONFLICT_PLUS_LINE_CHAR
+            )
+        });
+    if starts_with_jj_marker {
+        parse_jj_style_conflict_hunk(input)
+    } else {
+        parse_git_style_conflict_hunk(input)
+    }
+}
+
+fn parse_jj_style_conflict_hunk(input: &[u8]) -> Merge<BString> {
     enum State {
         Diff,
         Minus,
@@ -481,14 +773,57 @@ fn parse_conflict_hunk(input: &[u8]) -> Merge<BString> {
     }
 }
 
+fn parse_git_style_conflict_hunk(input: &[u8]) -> Merge<BString> {
+    #[derive(PartialEq, Eq)]
+    enum State {
+        Left,
+        Base,
+        Right,
+    }
+    let mut state = State::Left;
+    let mut left = BString::new(vec![]);
+    let mut base = BString::new(vec![]);
+    let mut right = BString::new(vec![]);
+    for line in input.split_inclusive(|b| *b == b'\n') {
+        if CONFLICT_MARKER_REGEX.is_match_at(line, 0) {
+            match (line[0], &state) {
+                (CONFLICT_BASE_LINE_CHAR, State::Left) => {
+                    state = State::Base;
+                    continue;
+                }
+                (CONFLICT_SEPARATOR_LINE_CHAR, State::Base) => {
+                    state = State::Right;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        match state {
+            State::Left => left.extend_from_slice(line),
+            State::Base => base.extend_from_slice(line),
+            State::Right => right.extend_from_slice(line),
+        }
+    }
+
+    if state == State::Right {
+        Merge::from_removes_adds(vec![base], vec![left, right])
+    } else {
+        // Doesn't look like a valid conflict
+        Merge::resolved(BString::new(vec![]))
+    }
+}
+
 /// Parses conflict markers in `content` and returns an updated version of
 /// `file_ids` with the new contents. If no (valid) conflict markers remain, a
-/// single resolves `FileId` will be returned.
+/// single resolves `FileId` will be returned. The conflict markers may be in
+/// any style, but the content is only known to be unchanged if it was
+/// materialized in `conflict_marker_style`.
 pub async fn update_from_content(
     file_ids: &Merge<Option<FileId>>,
     store: &Store,
     path: &RepoPath,
     content: &[u8],
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> BackendResult<Merge<Option<FileId>>> {
     let simplified_file_ids = file_ids.clone().simplify();
     let simplified_file_ids = &simplified_file_ids;
@@ -500,7 +835,8 @@ pub async fn update_from_content(
     // copy.
     let mut old_content = Vec::with_capacity(content.len());
     let merge_hunk = extract_as_single_hunk(simplified_file_ids, store, path).await?;
-    materialize_merge_result(&merge_hunk, &mut old_content).unwrap();
+    materialize_merge_result_with_style(&merge_hunk, conflict_marker_style, &mut old_content)
+        .unwrap();
     if content == old_content {
         return Ok(file_ids.clone());
     }
@@ -20,13 +20,17 @@ use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
 
+use futures::stream;
+use futures::stream::BoxStream;
 use futures::Stream;
 
 use crate::backend::BackendResult;
+use crate::backend::CommitId;
 use crate::backend::CopyRecord;
 use crate::merge::MergedTreeValue;
 use crate::merged_tree::MergedTree;
 use crate::merged_tree::TreeDiffStream;
+use crate::repo::Repo;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 
@@ -90,6 +94,31 @@ impl CopyRecords {
     }
 }
 
+/// Gets copy records for the dag range `root..head`, restricted to the
+/// targets in `paths` if specified.
+///
+/// If `root` is a parent of `head` and the index of the `repo` stores the copy
+/// records of `head`, they're used instead of detecting the copies again.
+pub fn get_copy_records<'a>(
+    repo: &'a dyn Repo,
+    paths: Option<&[RepoPathBuf]>,
+    root: &CommitId,
+    head: &CommitId,
+) -> BackendResult<BoxStream<'a, BackendResult<CopyRecord>>> {
+    match repo.index().copy_records(head, root) {
+        Some(records) => {
+            let paths = paths.map(|paths| paths.to_vec());
+            let records = records.into_iter().filter(move |record| {
+                paths
+                    .as_ref()
+                    .map_or(true, |paths| paths.contains(&record.target))
+            });
+            Ok(Box::pin(stream::iter(records.map(Ok))))
+        }
+        None => repo.store().get_copy_records(paths, root, head),
+    }
+}
+
 /// Whether or not the source path was deleted.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum CopyOperation {
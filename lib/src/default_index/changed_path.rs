@@ -0,0 +1,276 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-commit Bloom filters of the paths changed by commits.
+//!
+//! The filters are stored in a stacked table keyed by commit id, separately
+//! from the index segments. They are only maintained once the table has been
+//! created, like the changed-path filters of Git's commit-graph.
+
+#![allow(missing_docs)]
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use blake2::Blake2b512;
+use digest::Digest;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+
+use super::composite::CompositeIndex;
+use crate::backend::BackendResult;
+use crate::backend::CommitId;
+use crate::commit::Commit;
+use crate::fileset::FilePattern;
+use crate::fileset::FilesetExpression;
+use crate::matchers::EverythingMatcher;
+use crate::object_id::ObjectId;
+use crate::repo_path::RepoPath;
+use crate::rewrite;
+use crate::stacked_table::MutableTable;
+use crate::stacked_table::ReadonlyTable;
+use crate::stacked_table::TableSegment;
+
+/// Number of bits set per path.
+const NUM_HASHES: u64 = 7;
+/// Number of bits allocated per path. With 7 hashes, this gives a false
+/// positive rate of about 1%.
+const BITS_PER_PATH: usize = 10;
+/// Commits changing more paths than this get no filter.
+const MAX_PATHS: usize = 512;
+
+/// Bloom filter of the paths changed by a commit compared to its parents.
+///
+/// The parent directories of the changed paths are included, so a filter can
+/// also answer whether anything under a directory changed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct ChangedPathFilter {
+    // Empty if the commit changed too many paths to be worth a filter.
+    bits: Vec<u8>,
+}
+
+impl ChangedPathFilter {
+    pub fn from_paths<'a>(paths: impl IntoIterator<Item = &'a RepoPath>) -> Self {
+        let mut all_paths = HashSet::new();
+        for path in paths {
+            let mut path = Some(path);
+            while let Some(p) = path.filter(|p| !p.is_root()) {
+                if !all_paths.insert(p) {
+                    break;
+                }
+                path = p.parent();
+            }
+        }
+        if all_paths.len() > MAX_PATHS {
+            return ChangedPathFilter { bits: vec![] };
+        }
+        let num_bytes = (all_paths.len() * BITS_PER_PATH).div_ceil(8).max(1);
+        let mut filter = ChangedPathFilter {
+            bits: vec![0; num_bytes],
+        };
+        for path in all_paths {
+            for bit in filter.bit_positions(path) {
+                filter.bits[bit / 8] |= 1 << (bit % 8);
+            }
+        }
+        filter
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        ChangedPathFilter {
+            bits: bytes.to_vec(),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+
+    /// Returns false if the commit definitely didn't change the `path` or
+    /// anything under it.
+    pub fn may_contain(&self, path: &RepoPath) -> bool {
+        if self.bits.is_empty() || path.is_root() {
+            return true;
+        }
+        self.bit_positions(path)
+            .all(|bit| self.bits[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    fn bit_positions(&self, path: &RepoPath) -> impl Iterator<Item = usize> {
+        let hash = Blake2b512::digest(path.as_internal_file_string().as_bytes());
+        let h1 = u64::from_le_bytes(hash[0..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(hash[8..16].try_into().unwrap());
+        let num_bits = self.bits.len() as u64 * 8;
+        (0..NUM_HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+}
+
+/// Computes the filter of the paths `commit` changed compared to its merged
+/// parents.
+///
+/// The parent trees are merged without resolving conflicts, so the filter may
+/// contain paths whose conflicts would resolve to the committed contents. That
+/// is fine since the filter is only used to rule out paths.
+pub(super) fn compute_changed_path_filter(
+    index: &CompositeIndex,
+    commit: &Commit,
+) -> BackendResult<ChangedPathFilter> {
+    let store = commit.store();
+    let parents: Vec<_> = commit.parents().try_collect()?;
+    if let [parent] = parents.as_slice() {
+        if commit.tree_id() == parent.tree_id() {
+            return Ok(ChangedPathFilter::from_paths([]));
+        }
+    }
+    let from_tree = rewrite::merge_commit_trees_no_resolve_without_repo(store, &index, &parents)?;
+    let to_tree = commit.tree()?;
+    let paths = from_tree
+        .diff_stream(&to_tree, &EverythingMatcher)
+        .map(|entry| entry.path)
+        .collect::<Vec<_>>()
+        .block_on();
+    Ok(ChangedPathFilter::from_paths(
+        paths.iter().map(|path| path.as_ref()),
+    ))
+}
+
+/// Read-only view of the stored changed-path filters.
+#[derive(Clone)]
+pub(super) struct ChangedPathIndex {
+    table: Arc<ReadonlyTable>,
+}
+
+impl ChangedPathIndex {
+    pub fn new(table: Arc<ReadonlyTable>) -> Self {
+        ChangedPathIndex { table }
+    }
+
+    pub fn num_filters(&self) -> usize {
+        self.table.num_entries()
+    }
+
+    /// Returns false if the commit is known not to have changed any of the
+    /// `paths`. Returns true if it may have, or if there's no filter for the
+    /// commit.
+    pub fn may_contain_any<'a>(
+        &self,
+        commit_id: &CommitId,
+        paths: impl IntoIterator<Item = &'a RepoPath>,
+    ) -> bool {
+        match self.table.get_value(commit_id.as_bytes()) {
+            Some(bytes) => {
+                let filter = ChangedPathFilter::from_bytes(bytes);
+                paths.into_iter().any(|path| filter.may_contain(path))
+            }
+            None => true,
+        }
+    }
+}
+
+impl Debug for ChangedPathIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChangedPathIndex")
+            .field("num_filters", &self.num_filters())
+            .finish_non_exhaustive()
+    }
+}
+
+pub(super) fn add_changed_path_filter(
+    mut_table: &mut MutableTable,
+    commit_id: &CommitId,
+    filter: &ChangedPathFilter,
+) {
+    mut_table.add_entry(commit_id.to_bytes(), filter.as_bytes().to_vec());
+}
+
+/// Returns the paths which any file matched by `expression` must be in or
+/// under, or `None` if the expression can match files anywhere.
+pub(super) fn fileset_filter_paths(expression: &FilesetExpression) -> Option<Vec<&RepoPath>> {
+    match expression {
+        FilesetExpression::None => Some(vec![]),
+        FilesetExpression::All => None,
+        FilesetExpression::Pattern(pattern) => {
+            let path = match pattern {
+                FilePattern::FilePath(path) => path,
+                FilePattern::PrefixPath(path) => path,
+                FilePattern::FileGlob { dir, .. } => dir,
+            };
+            (!path.is_root()).then(|| vec![path.as_ref()])
+        }
+        FilesetExpression::UnionAll(expressions) => {
+            let mut paths = vec![];
+            for expression in expressions {
+                paths.extend(fileset_filter_paths(expression)?);
+            }
+            Some(paths)
+        }
+        FilesetExpression::Intersection(expression1, expression2) => {
+            fileset_filter_paths(expression1).or_else(|| fileset_filter_paths(expression2))
+        }
+        FilesetExpression::Difference(expression, _) => fileset_filter_paths(expression),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo_path::RepoPathBuf;
+
+    fn repo_path(value: &str) -> &RepoPath {
+        RepoPath::from_internal_string(value)
+    }
+
+    #[test]
+    fn test_changed_path_filter() {
+        let filter = ChangedPathFilter::from_paths([repo_path("dir/file"), repo_path("other")]);
+        assert!(filter.may_contain(repo_path("dir/file")));
+        assert!(filter.may_contain(repo_path("dir")));
+        assert!(filter.may_contain(repo_path("other")));
+        assert!(filter.may_contain(RepoPath::root()));
+        assert!(!filter.may_contain(repo_path("dir/other")));
+        assert!(!filter.may_contain(repo_path("file")));
+
+        let filter = ChangedPathFilter::from_bytes(filter.as_bytes());
+        assert!(filter.may_contain(repo_path("dir/file")));
+        assert!(!filter.may_contain(repo_path("file")));
+
+        let filter = ChangedPathFilter::from_paths([]);
+        assert!(!filter.may_contain(repo_path("file")));
+    }
+
+    #[test]
+    fn test_changed_path_filter_too_many_paths() {
+        let paths = (0..=MAX_PATHS)
+            .map(|i| RepoPathBuf::from_internal_string(format!("file{i}")))
+            .collect::<Vec<_>>();
+        let filter = ChangedPathFilter::from_paths(paths.iter().map(|path| path.as_ref()));
+        assert!(filter.as_bytes().is_empty());
+        assert!(filter.may_contain(repo_path("unrelated")));
+    }
+
+    #[test]
+    fn test_changed_path_filter_false_positive_rate() {
+        let paths = (0..100)
+            .map(|i| RepoPathBuf::from_internal_string(format!("file{i}")))
+            .collect::<Vec<_>>();
+        let filter = ChangedPathFilter::from_paths(paths.iter().map(|path| path.as_ref()));
+        let num_false_positives = (0..1000)
+            .filter(|i| filter.may_contain(&RepoPathBuf::from_internal_string(format!("x{i}"))))
+            .count();
+        assert!(num_false_positives < 50, "{num_false_positives}");
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-commit copy records relative to the parents of commits.
+//!
+//! Like the changed-path filters, the records are stored in a stacked table
+//! keyed by commit id, and are only maintained once the table has been
+//! created. Copy detection can be as expensive as diffing the trees, so
+//! storing the records makes following renames cheap.
+
+#![allow(missing_docs)]
+
+use std::fmt;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use futures::TryStreamExt as _;
+use pollster::FutureExt as _;
+
+use crate::backend::BackendResult;
+use crate::backend::CommitId;
+use crate::backend::CopyRecord;
+use crate::backend::FileId;
+use crate::commit::Commit;
+use crate::object_id::ObjectId;
+use crate::repo_path::RepoPathBuf;
+use crate::stacked_table::MutableTable;
+use crate::stacked_table::ReadonlyTable;
+use crate::stacked_table::TableSegment;
+
+/// Copy records of a commit, grouped by the parent they're relative to.
+///
+/// Parents without stored records, such as the root commit, are omitted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct CommitCopyRecords {
+    parents: Vec<(CommitId, Vec<CopyRecord>)>,
+}
+
+impl CommitCopyRecords {
+    /// Serializes the records. The commit ids of the records aren't stored
+    /// since they're implied by the key and the parent.
+    ///
+    /// For each parent: the parent id, the number of records, and the source,
+    /// target and source file id of each record. Variable-length fields are
+    /// prefixed by their u32 length.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        for (parent_id, records) in &self.parents {
+            write_bytes(&mut buf, parent_id.as_bytes());
+            buf.extend(u32::try_from(records.len()).unwrap().to_le_bytes());
+            for record in records {
+                write_bytes(&mut buf, record.source.as_internal_file_string().as_bytes());
+                write_bytes(&mut buf, record.target.as_internal_file_string().as_bytes());
+                write_bytes(&mut buf, record.source_file.as_bytes());
+            }
+        }
+        buf
+    }
+
+    /// Deserializes the records of `commit_id`. Returns `None` if the data is
+    /// malformed.
+    pub fn from_bytes(commit_id: &CommitId, mut bytes: &[u8]) -> Option<Self> {
+        let mut parents = vec![];
+        while !bytes.is_empty() {
+            let parent_id = CommitId::from_bytes(read_bytes(&mut bytes)?);
+            let num_records = read_u32(&mut bytes)?;
+            let mut records = vec![];
+            for _ in 0..num_records {
+                let source = read_path(&mut bytes)?;
+                let target = read_path(&mut bytes)?;
+                let source_file = FileId::from_bytes(read_bytes(&mut bytes)?);
+                records.push(CopyRecord {
+                    target,
+                    target_commit: commit_id.clone(),
+                    source,
+                    source_file,
+                    source_commit: parent_id.clone(),
+                });
+            }
+            parents.push((parent_id, records));
+        }
+        Some(CommitCopyRecords { parents })
+    }
+
+    /// Returns the records relative to `parent_id`, or `None` if they weren't
+    /// stored.
+    pub fn for_parent(&self, parent_id: &CommitId) -> Option<&[CopyRecord]> {
+        self.parents
+            .iter()
+            .find(|(id, _)| id == parent_id)
+            .map(|(_, records)| records.as_slice())
+    }
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend(u32::try_from(bytes.len()).unwrap().to_le_bytes());
+    buf.extend(bytes);
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Option<u32> {
+    let value = bytes.get(..4)?.try_into().unwrap();
+    *bytes = &bytes[4..];
+    Some(u32::from_le_bytes(value))
+}
+
+fn read_bytes<'a>(bytes: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = usize::try_from(read_u32(bytes)?).ok()?;
+    if bytes.len() < len {
+        return None;
+    }
+    let (value, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Some(value)
+}
+
+fn read_path(bytes: &mut &[u8]) -> Option<RepoPathBuf> {
+    let value = std::str::from_utf8(read_bytes(bytes)?).ok()?;
+    Some(RepoPathBuf::from_internal_string(value))
+}
+
+/// Detects the copies `commit` made relative to each of its parents.
+pub(super) fn compute_copy_records(commit: &Commit) -> BackendResult<CommitCopyRecords> {
+    let store = commit.store();
+    let mut parents = vec![];
+    for parent_id in commit.parent_ids() {
+        // The root commit has no files to copy from, and it may not be
+        // readable by the backend.
+        if parent_id == store.root_commit_id() {
+            continue;
+        }
+        let records: Vec<_> = store
+            .get_copy_records(None, parent_id, commit.id())?
+            .try_collect()
+            .block_on()?;
+        parents.push((parent_id.clone(), records));
+    }
+    Ok(CommitCopyRecords { parents })
+}
+
+/// Read-only view of the stored copy records.
+#[derive(Clone)]
+pub(super) struct CopyRecordIndex {
+    table: Arc<ReadonlyTable>,
+}
+
+impl CopyRecordIndex {
+    pub fn new(table: Arc<ReadonlyTable>) -> Self {
+        CopyRecordIndex { table }
+    }
+
+    pub fn num_entries(&self) -> usize {
+        self.table.num_entries()
+    }
+
+    /// Returns the copy records of `commit_id` relative to its parent
+    /// `parent_id`, or `None` if they aren't stored.
+    pub fn get(&self, commit_id: &CommitId, parent_id: &CommitId) -> Option<Vec<CopyRecord>> {
+        let bytes = self.table.get_value(commit_id.as_bytes())?;
+        let records = CommitCopyRecords::from_bytes(commit_id, bytes)?;
+        records
+            .for_parent(parent_id)
+            .map(|records| records.to_vec())
+    }
+}
+
+impl Debug for CopyRecordIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CopyRecordIndex")
+            .field("num_entries", &self.num_entries())
+            .finish_non_exhaustive()
+    }
+}
+
+pub(super) fn add_copy_records(
+    mut_table: &mut MutableTable,
+    commit_id: &CommitId,
+    records: &CommitCopyRecords,
+) {
+    mut_table.add_entry(commit_id.to_bytes(), records.to_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_path_buf(value: &str) -> RepoPathBuf {
+        RepoPathBuf::from_internal_string(value)
+    }
+
+    #[test]
+    fn test_commit_copy_records_round_trip() {
+        let commit_id = CommitId::from_hex("aaaa");
+        let parent1 = CommitId::from_hex("bbbb");
+        let parent2 = CommitId::from_hex("cccc");
+        let record = CopyRecord {
+            target: repo_path_buf("dir/new"),
+            target_commit: commit_id.clone(),
+            source: repo_path_buf("old"),
+            source_file: FileId::from_hex("dddd"),
+            source_commit: parent1.clone(),
+        };
+        let records = CommitCopyRecords {
+            parents: vec![
+                (parent1.clone(), vec![record.clone()]),
+                (parent2.clone(), vec![]),
+            ],
+        };
+        let bytes = records.to_bytes();
+        let decoded = CommitCopyRecords::from_bytes(&commit_id, &bytes).unwrap();
+        assert_eq!(decoded, records);
+        assert_eq!(decoded.for_parent(&parent1), Some([record].as_slice()));
+        assert_eq!(decoded.for_parent(&parent2), Some([].as_slice()));
+        assert_eq!(decoded.for_parent(&CommitId::from_hex("eeee")), None);
+
+        // Truncated data is rejected
+        assert_eq!(
+            CommitCopyRecords::from_bytes(&commit_id, &bytes[..bytes.len() - 1]),
+            None
+        );
+    }
+}
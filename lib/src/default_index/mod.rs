@@ -21,7 +21,9 @@
 
 #![allow(missing_docs)]
 
+mod changed_path;
 mod composite;
+mod copy_records;
 mod entry;
 mod mutable;
 mod readonly;
@@ -42,6 +44,7 @@ pub use self::readonly::ReadonlyIndexLoadError;
 pub use self::store::DefaultIndexStore;
 pub use self::store::DefaultIndexStoreError;
 pub use self::store::DefaultIndexStoreInitError;
+pub use self::store::IndexProgress;
 
 #[cfg(test)]
 mod tests {
@@ -455,17 +455,28 @@ impl IndexSegment for MutableIndexSegment {
 }
 
 /// In-memory mutable records for the on-disk commit index backend.
-pub struct DefaultMutableIndex(MutableIndexSegment);
+pub struct DefaultMutableIndex {
+    segment: MutableIndexSegment,
+    // Commits added by `add_commit()`, for which changed-path filters may have
+    // to be computed when the index is saved.
+    new_commits: Vec<Commit>,
+}
 
 impl DefaultMutableIndex {
     pub(crate) fn full(commit_id_length: usize, change_id_length: usize) -> Self {
         let mutable_segment = MutableIndexSegment::full(commit_id_length, change_id_length);
-        DefaultMutableIndex(mutable_segment)
+        DefaultMutableIndex {
+            segment: mutable_segment,
+            new_commits: vec![],
+        }
     }
 
     pub(super) fn incremental(parent_file: Arc<ReadonlyIndexSegment>) -> Self {
         let mutable_segment = MutableIndexSegment::incremental(parent_file);
-        DefaultMutableIndex(mutable_segment)
+        DefaultMutableIndex {
+            segment: mutable_segment,
+            new_commits: vec![],
+        }
     }
 
     #[cfg(test)]
@@ -475,17 +486,27 @@ impl DefaultMutableIndex {
         change_id: ChangeId,
         parent_ids: &[CommitId],
     ) {
-        self.0.add_commit_data(commit_id, change_id, parent_ids);
+        self.segment
+            .add_commit_data(commit_id, change_id, parent_ids);
+    }
+
+    pub(super) fn commit_id_length(&self) -> usize {
+        self.segment.commit_id_length
+    }
+
+    /// Commits added by `add_commit()` since the index was created.
+    pub(super) fn new_commits(&self) -> &[Commit] {
+        &self.new_commits
     }
 
     pub(super) fn squash_and_save_in(self, dir: &Path) -> io::Result<Arc<ReadonlyIndexSegment>> {
-        self.0.maybe_squash_with_ancestors().save_in(dir)
+        self.segment.maybe_squash_with_ancestors().save_in(dir)
     }
 }
 
 impl AsCompositeIndex for DefaultMutableIndex {
     fn as_composite(&self) -> &CompositeIndex {
-        self.0.as_composite()
+        self.segment.as_composite()
     }
 }
 
@@ -551,7 +572,10 @@ impl MutableIndex for DefaultMutableIndex {
     }
 
     fn add_commit(&mut self, commit: &Commit) {
-        self.0.add_commit(commit);
+        if !self.has_id(commit.id()) {
+            self.segment.add_commit(commit);
+            self.new_commits.push(commit.clone());
+        }
     }
 
     fn merge_in(&mut self, other: &dyn ReadonlyIndex) {
@@ -559,7 +583,7 @@ impl MutableIndex for DefaultMutableIndex {
             .as_any()
             .downcast_ref::<DefaultReadonlyIndex>()
             .expect("index to merge in must be a DefaultReadonlyIndex");
-        self.0.merge_in(other.as_segment().clone());
+        self.segment.merge_in(other.as_segment().clone());
     }
 }
 
@@ -21,23 +21,29 @@ use std::fmt::Formatter;
 use std::fs::File;
 use std::io;
 use std::io::Read;
+use std::ops::Deref;
 use std::path::Path;
 use std::sync::Arc;
 
+use memmap2::Mmap;
 use smallvec::smallvec;
 use thiserror::Error;
 
+use super::changed_path::ChangedPathIndex;
 use super::composite::AsCompositeIndex;
 use super::composite::ChangeIdIndexImpl;
 use super::composite::CompositeIndex;
 use super::composite::IndexSegment;
+use super::copy_records::CopyRecordIndex;
 use super::entry::IndexPosition;
 use super::entry::LocalPosition;
 use super::entry::SmallIndexPositionsVec;
 use super::entry::SmallLocalPositionsVec;
 use super::mutable::DefaultMutableIndex;
+use super::revset_engine;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
+use crate::backend::CopyRecord;
 use crate::index::AllHeadsForGcUnsupported;
 use crate::index::ChangeIdIndex;
 use crate::index::Index;
@@ -233,7 +239,76 @@ pub(super) struct ReadonlyIndexSegment {
     change_pos_table_base: usize,
     parent_overflow_base: usize,
     change_overflow_base: usize,
-    data: Vec<u8>,
+    data: SegmentData,
+}
+
+/// Local entries of a segment file.
+///
+/// The entries have a fixed size and are read in place, so the file can be
+/// mapped into memory instead of being read. Mapped files are shared through
+/// the page cache by concurrent processes, and loading them doesn't depend on
+/// their size. The table sizes declared in the header are checked against the
+/// mapped length before any entry is accessed, so a truncated file is rejected
+/// the same way as when it's read.
+enum SegmentData {
+    Owned(Vec<u8>),
+    Mapped { mmap: Mmap, offset: usize },
+}
+
+impl Deref for SegmentData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            SegmentData::Owned(data) => data,
+            SegmentData::Mapped { mmap, offset } => &mmap[*offset..],
+        }
+    }
+}
+
+/// Maps the segment `file` into memory.
+///
+/// Segment files are never modified once written. They're written to a
+/// temporary file which is then renamed, and are only deleted by `reinit()`,
+/// which leaves existing mappings intact on Unix.
+#[cfg(unix)]
+#[allow(unsafe_code)]
+fn map_segment_file(file: &File) -> io::Result<Mmap> {
+    // SAFETY: the file isn't modified or truncated while it's mapped, as
+    // explained above.
+    unsafe { Mmap::map(file) }
+}
+
+/// Mapped files can't be deleted on Windows, which would make `reinit()` fail
+/// while other processes have the index loaded. The file is read instead.
+#[cfg(not(unix))]
+fn map_segment_file(_file: &File) -> io::Result<Mmap> {
+    Err(io::Error::from(io::ErrorKind::Unsupported))
+}
+
+/// Numbers of entries in the tables of a segment file.
+struct SegmentCounts {
+    num_local_commits: u32,
+    num_local_change_ids: u32,
+    num_parent_overflow_entries: u32,
+    num_change_overflow_entries: u32,
+}
+
+impl SegmentCounts {
+    fn read_from(file: &mut dyn Read, name: &str) -> Result<Self, ReadonlyIndexLoadError> {
+        let mut read_u32 = || {
+            let mut buf = [0; 4];
+            file.read_exact(&mut buf)
+                .map_err(|err| ReadonlyIndexLoadError::from_io_err(name, err))?;
+            Ok(u32::from_le_bytes(buf))
+        };
+        Ok(SegmentCounts {
+            num_local_commits: read_u32()?,
+            num_local_change_ids: read_u32()?,
+            num_parent_overflow_entries: read_u32()?,
+            num_change_overflow_entries: read_u32()?,
+        })
+    }
 }
 
 impl Debug for ReadonlyIndexSegment {
@@ -255,7 +330,34 @@ impl ReadonlyIndexSegment {
     ) -> Result<Arc<ReadonlyIndexSegment>, ReadonlyIndexLoadError> {
         let mut file = File::open(dir.join(&name))
             .map_err(|err| ReadonlyIndexLoadError::from_io_err(&name, err))?;
-        Self::load_from(&mut file, dir, name, commit_id_length, change_id_length)
+        match map_segment_file(&file) {
+            Ok(mmap) => Self::load_from_mmap(mmap, dir, name, commit_id_length, change_id_length),
+            // Empty files can't be mapped, for example.
+            Err(_) => Self::load_from(&mut file, dir, name, commit_id_length, change_id_length),
+        }
+    }
+
+    /// Loads both parent segments and local entries from the mapped file.
+    fn load_from_mmap(
+        mmap: Mmap,
+        dir: &Path,
+        name: String,
+        commit_id_length: usize,
+        change_id_length: usize,
+    ) -> Result<Arc<ReadonlyIndexSegment>, ReadonlyIndexLoadError> {
+        let mut header: &[u8] = &mmap;
+        let parent_file =
+            Self::load_header(&mut header, dir, &name, commit_id_length, change_id_length)?;
+        let counts = SegmentCounts::read_from(&mut header, &name)?;
+        let offset = mmap.len() - header.len();
+        Self::from_data(
+            SegmentData::Mapped { mmap, offset },
+            counts,
+            name,
+            parent_file,
+            commit_id_length,
+            change_id_length,
+        )
     }
 
     /// Loads both parent segments and local entries from the given `file`.
@@ -266,7 +368,27 @@ impl ReadonlyIndexSegment {
         commit_id_length: usize,
         change_id_length: usize,
     ) -> Result<Arc<ReadonlyIndexSegment>, ReadonlyIndexLoadError> {
-        let from_io_err = |err| ReadonlyIndexLoadError::from_io_err(&name, err);
+        let maybe_parent_file =
+            Self::load_header(file, dir, &name, commit_id_length, change_id_length)?;
+        Self::load_with_parent_file(
+            file,
+            name,
+            maybe_parent_file,
+            commit_id_length,
+            change_id_length,
+        )
+    }
+
+    /// Reads the format version and the parent file name from the `file`, and
+    /// loads the parent segments.
+    fn load_header(
+        file: &mut dyn Read,
+        dir: &Path,
+        name: &str,
+        commit_id_length: usize,
+        change_id_length: usize,
+    ) -> Result<Option<Arc<ReadonlyIndexSegment>>, ReadonlyIndexLoadError> {
+        let from_io_err = |err| ReadonlyIndexLoadError::from_io_err(name, err);
         let read_u32 = |file: &mut dyn Read| {
             let mut buf = [0; 4];
             file.read_exact(&mut buf).map_err(from_io_err)?;
@@ -285,7 +407,7 @@ impl ReadonlyIndexSegment {
             file.read_exact(&mut parent_filename_bytes)
                 .map_err(from_io_err)?;
             let parent_filename = String::from_utf8(parent_filename_bytes).map_err(|_| {
-                ReadonlyIndexLoadError::invalid_data(&name, "parent file name is not valid UTF-8")
+                ReadonlyIndexLoadError::invalid_data(name, "parent file name is not valid UTF-8")
             })?;
             let parent_file = ReadonlyIndexSegment::load(
                 dir,
@@ -297,13 +419,7 @@ impl ReadonlyIndexSegment {
         } else {
             None
         };
-        Self::load_with_parent_file(
-            file,
-            name,
-            maybe_parent_file,
-            commit_id_length,
-            change_id_length,
-        )
+        Ok(maybe_parent_file)
     }
 
     /// Loads local entries from the given `file`, returns new segment linked to
@@ -315,21 +431,37 @@ impl ReadonlyIndexSegment {
         commit_id_length: usize,
         change_id_length: usize,
     ) -> Result<Arc<ReadonlyIndexSegment>, ReadonlyIndexLoadError> {
-        let from_io_err = |err| ReadonlyIndexLoadError::from_io_err(&name, err);
-        let read_u32 = |file: &mut dyn Read| {
-            let mut buf = [0; 4];
-            file.read_exact(&mut buf).map_err(from_io_err)?;
-            Ok(u32::from_le_bytes(buf))
-        };
+        let counts = SegmentCounts::read_from(file, &name)?;
+        let mut data = vec![];
+        file.read_to_end(&mut data)
+            .map_err(|err| ReadonlyIndexLoadError::from_io_err(&name, err))?;
+        Self::from_data(
+            SegmentData::Owned(data),
+            counts,
+            name,
+            parent_file,
+            commit_id_length,
+            change_id_length,
+        )
+    }
+
+    fn from_data(
+        data: SegmentData,
+        counts: SegmentCounts,
+        name: String,
+        parent_file: Option<Arc<ReadonlyIndexSegment>>,
+        commit_id_length: usize,
+        change_id_length: usize,
+    ) -> Result<Arc<ReadonlyIndexSegment>, ReadonlyIndexLoadError> {
+        let SegmentCounts {
+            num_local_commits,
+            num_local_change_ids,
+            num_parent_overflow_entries,
+            num_change_overflow_entries,
+        } = counts;
         let num_parent_commits = parent_file
             .as_ref()
             .map_or(0, |segment| segment.as_composite().num_commits());
-        let num_local_commits = read_u32(file)?;
-        let num_local_change_ids = read_u32(file)?;
-        let num_parent_overflow_entries = read_u32(file)?;
-        let num_change_overflow_entries = read_u32(file)?;
-        let mut data = vec![];
-        file.read_to_end(&mut data).map_err(from_io_err)?;
 
         let commit_graph_entry_size = CommitGraphEntry::size(commit_id_length);
         let graph_size = (num_local_commits as usize) * commit_graph_entry_size;
@@ -587,21 +719,55 @@ impl IndexSegment for ReadonlyIndexSegment {
 
 /// Commit index backend which stores data on local disk.
 #[derive(Clone, Debug)]
-pub struct DefaultReadonlyIndex(Arc<ReadonlyIndexSegment>);
+pub struct DefaultReadonlyIndex {
+    segment: Arc<ReadonlyIndexSegment>,
+    changed_paths: Option<ChangedPathIndex>,
+    copy_records: Option<CopyRecordIndex>,
+}
 
 impl DefaultReadonlyIndex {
     pub(super) fn from_segment(segment: Arc<ReadonlyIndexSegment>) -> Self {
-        DefaultReadonlyIndex(segment)
+        DefaultReadonlyIndex {
+            segment,
+            changed_paths: None,
+            copy_records: None,
+        }
+    }
+
+    pub(super) fn with_changed_paths(self, changed_paths: Option<ChangedPathIndex>) -> Self {
+        DefaultReadonlyIndex {
+            changed_paths,
+            ..self
+        }
+    }
+
+    pub(super) fn with_copy_records(self, copy_records: Option<CopyRecordIndex>) -> Self {
+        DefaultReadonlyIndex {
+            copy_records,
+            ..self
+        }
     }
 
     pub(super) fn as_segment(&self) -> &Arc<ReadonlyIndexSegment> {
-        &self.0
+        &self.segment
+    }
+
+    /// Returns the number of commits which have a changed-path filter, or
+    /// `None` if the filters aren't enabled.
+    pub fn num_changed_path_filters(&self) -> Option<usize> {
+        self.changed_paths.as_ref().map(|index| index.num_filters())
+    }
+
+    /// Returns the number of commits which have stored copy records, or `None`
+    /// if the copy records aren't enabled.
+    pub fn num_copy_record_entries(&self) -> Option<usize> {
+        self.copy_records.as_ref().map(|index| index.num_entries())
     }
 }
 
 impl AsCompositeIndex for DefaultReadonlyIndex {
     fn as_composite(&self) -> &CompositeIndex {
-        self.0.as_composite()
+        self.segment.as_composite()
     }
 }
 
@@ -642,7 +808,18 @@ impl Index for DefaultReadonlyIndex {
         expression: &ResolvedExpression,
         store: &Arc<Store>,
     ) -> Result<Box<dyn Revset + 'index>, RevsetEvaluationError> {
-        self.as_composite().evaluate_revset(expression, store)
+        let revset_impl = revset_engine::evaluate_with_side_indexes(
+            expression,
+            store,
+            self.as_composite(),
+            self.changed_paths.clone(),
+            self.copy_records.clone(),
+        )?;
+        Ok(Box::new(revset_impl))
+    }
+
+    fn copy_records(&self, commit_id: &CommitId, parent_id: &CommitId) -> Option<Vec<CopyRecord>> {
+        self.copy_records.as_ref()?.get(commit_id, parent_id)
     }
 }
 
@@ -663,7 +840,7 @@ impl ReadonlyIndex for DefaultReadonlyIndex {
     }
 
     fn start_modification(&self) -> Box<dyn MutableIndex> {
-        Box::new(DefaultMutableIndex::incremental(self.0.clone()))
+        Box::new(DefaultMutableIndex::incremental(self.segment.clone()))
     }
 }
 
@@ -15,6 +15,7 @@
 #![allow(missing_docs)]
 
 use std::cmp::max;
+use std::cmp::min;
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 use std::collections::HashMap;
@@ -390,6 +391,10 @@ impl<'a> RevWalkBuilder<'a> {
     }
 
     /// Walks ancestors.
+    ///
+    /// Revsets use `RevWalkLazyAncestors` instead, which doesn't need all
+    /// heads upfront.
+    #[cfg(test)]
     pub fn ancestors(self) -> RevWalkAncestors<'a> {
         self.ancestors_with_min_pos(IndexPosition::MIN)
     }
@@ -435,9 +440,6 @@ impl<'a> RevWalkBuilder<'a> {
         self,
         root_positions: impl IntoIterator<Item = IndexPosition>,
     ) -> RevWalkAncestors<'a> {
-        // We can also make it stop visiting based on the generation number. Maybe
-        // it will perform better for unbalanced branchy history.
-        // https://github.com/martinvonz/jj/pull/1492#discussion_r1160678325
         let min_pos = root_positions
             .into_iter()
             .min()
@@ -445,6 +447,36 @@ impl<'a> RevWalkBuilder<'a> {
         self.ancestors_with_min_pos(min_pos)
     }
 
+    /// Walks ancestors that may be descendants of the `root_positions`.
+    ///
+    /// Like `ancestors_until_roots()`, but also stops at entries whose
+    /// generation number is smaller than the roots'. The caller still needs to
+    /// filter out unwanted entries.
+    pub fn ancestors_until_root_generations(
+        self,
+        root_positions: impl IntoIterator<Item = IndexPosition>,
+    ) -> RevWalkAncestorsUntilRootGenerations<'a> {
+        // Descendants of the roots can't precede the roots in the index, nor have
+        // smaller generation numbers. Since the ancestors of an entry also have
+        // smaller positions and generation numbers, they don't need to be visited
+        // once the entry is out of the bounds.
+        let index = self.index;
+        let (min_pos, min_generation) = root_positions
+            .into_iter()
+            .map(|pos| (pos, index.entry_by_pos(pos).generation_number()))
+            .reduce(|(pos1, gen1), (pos2, gen2)| (min(pos1, pos2), min(gen1, gen2)))
+            .unwrap_or((IndexPosition::MAX, u32::MAX));
+        let mut walk = RevWalkUntilGenerationImpl {
+            queue: RevWalkQueue::with_min_pos(min_pos),
+            min_generation,
+        };
+        let wanted = walk.filter_by_generation(index, SmallVec::from_vec(self.wanted));
+        let unwanted = walk.filter_by_generation(index, SmallVec::from_vec(self.unwanted));
+        walk.queue.extend_wanted(wanted, ());
+        walk.queue.extend_unwanted(unwanted);
+        RevWalkBorrowedIndexIter { index, walk }
+    }
+
     /// Fully consumes ancestors and walks back from the `root_positions`.
     ///
     /// The returned iterator yields entries in order of ascending index
@@ -456,7 +488,7 @@ impl<'a> RevWalkBuilder<'a> {
         let index = self.index;
         let root_positions = HashSet::from_iter(root_positions);
         let candidate_positions = self
-            .ancestors_until_roots(root_positions.iter().copied())
+            .ancestors_until_root_generations(root_positions.iter().copied())
             .collect();
         RevWalkBorrowedIndexIter {
             index,
@@ -482,7 +514,7 @@ impl<'a> RevWalkBuilder<'a> {
     ) -> RevWalkDescendantsGenerationRange {
         let index = self.index;
         let root_positions = Vec::from_iter(root_positions);
-        let positions = self.ancestors_until_roots(root_positions.iter().copied());
+        let positions = self.ancestors_until_root_generations(root_positions.iter().copied());
         let descendants_index = RevWalkDescendantsIndex::build(index, positions);
 
         let mut queue = RevWalkQueue::with_min_pos(Reverse(IndexPosition::MAX));
@@ -540,6 +572,119 @@ impl<I: RevWalkIndex + ?Sized> RevWalk<I> for RevWalkImpl<I::Position> {
     }
 }
 
+pub(super) type RevWalkAncestorsUntilRootGenerations<'a> =
+    RevWalkBorrowedIndexIter<'a, CompositeIndex, RevWalkUntilGenerationImpl>;
+
+/// Walks ancestors, skipping entries with a generation number smaller than
+/// `min_generation` along with their ancestors.
+#[derive(Clone)]
+#[must_use]
+pub(super) struct RevWalkUntilGenerationImpl {
+    queue: RevWalkQueue<IndexPosition, ()>,
+    min_generation: u32,
+}
+
+impl RevWalkUntilGenerationImpl {
+    fn filter_by_generation(
+        &self,
+        index: &CompositeIndex,
+        mut positions: SmallIndexPositionsVec,
+    ) -> SmallIndexPositionsVec {
+        positions.retain(|pos| index.entry_by_pos(*pos).generation_number() >= self.min_generation);
+        positions
+    }
+}
+
+impl RevWalk<CompositeIndex> for RevWalkUntilGenerationImpl {
+    type Item = IndexPosition;
+
+    fn next(&mut self, index: &CompositeIndex) -> Option<Self::Item> {
+        while let Some(item) = self.queue.pop() {
+            self.queue.skip_while_eq(&item.pos);
+            let parent_positions =
+                self.filter_by_generation(index, index.adjacent_positions(item.pos));
+            if item.is_wanted() {
+                self.queue.extend_wanted(parent_positions, ());
+                return Some(item.pos);
+            } else if self.queue.items.len() == self.queue.unwanted_count {
+                // No more wanted entries to walk
+                debug_assert!(!self.queue.items.iter().any(|x| x.is_wanted()));
+                return None;
+            } else {
+                self.queue.extend_unwanted(parent_positions);
+            }
+        }
+
+        debug_assert_eq!(
+            self.queue.items.iter().filter(|x| !x.is_wanted()).count(),
+            self.queue.unwanted_count
+        );
+        None
+    }
+}
+
+/// Walks ancestors of heads that are pulled from another walk only as far as
+/// the walk has progressed.
+///
+/// Unlike `RevWalkBuilder::ancestors()`, this doesn't need all heads upfront,
+/// so the walk over a lazily evaluated set of heads can stop early.
+#[must_use]
+pub(super) struct RevWalkLazyAncestors<W: RevWalk<CompositeIndex>> {
+    // Sorted in descending order.
+    heads: PeekableRevWalk<CompositeIndex, W>,
+    queue: RevWalkQueue<IndexPosition, ()>,
+}
+
+impl<W> RevWalkLazyAncestors<W>
+where
+    W: RevWalk<CompositeIndex, Item = IndexPosition>,
+{
+    /// Creates a walk over the ancestors of `heads`, excluding the ancestors of
+    /// `unwanted_roots`. The `heads` must be sorted in descending order.
+    pub fn new(heads: W, unwanted_roots: impl IntoIterator<Item = IndexPosition>) -> Self {
+        let mut queue = RevWalkQueue::with_min_pos(IndexPosition::MIN);
+        queue.extend_unwanted(unwanted_roots);
+        RevWalkLazyAncestors {
+            heads: heads.peekable(),
+            queue,
+        }
+    }
+}
+
+impl<W> RevWalk<CompositeIndex> for RevWalkLazyAncestors<W>
+where
+    W: RevWalk<CompositeIndex, Item = IndexPosition>,
+{
+    type Item = IndexPosition;
+
+    fn next(&mut self, index: &CompositeIndex) -> Option<Self::Item> {
+        loop {
+            // Heads at or above the next queued position have to be queued
+            // before that position is popped.
+            let queue = &mut self.queue;
+            while let Some(pos) = self.heads.next_if(index, |&pos| {
+                queue.items.peek().map_or(true, |item| pos >= item.pos)
+            }) {
+                queue.push_wanted(pos, ());
+            }
+            let item = self.queue.pop()?;
+            self.queue.skip_while_eq(&item.pos);
+            let parent_positions = index.adjacent_positions(item.pos);
+            if item.is_wanted() {
+                self.queue.extend_wanted(parent_positions, ());
+                return Some(item.pos);
+            } else if self.queue.items.len() == self.queue.unwanted_count
+                && self.heads.peek(index).is_none()
+            {
+                // No more wanted entries to walk
+                return None;
+            } else {
+                self.queue.extend_unwanted(parent_positions);
+            }
+        }
+    }
+}
+
 pub(super) type RevWalkAncestorsGenerationRange<'a> =
     RevWalkBorrowedIndexIter<'a, CompositeIndex, RevWalkGenerationRangeImpl<IndexPosition>>;
 pub(super) type RevWalkDescendantsGenerationRange = RevWalkOwnedIndexIter<
@@ -775,6 +920,9 @@ impl AncestorsBitSet {
 
 #[cfg(test)]
 mod tests {
+    use std::cell::Cell;
+    use std::slice;
+
     use itertools::Itertools as _;
 
     use super::super::composite::AsCompositeIndex as _;
@@ -919,6 +1067,102 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_walk_lazy_ancestors() {
+        let mut new_change_id = change_id_generator();
+        let mut index = DefaultMutableIndex::full(3, 16);
+        // 5
+        // |\
+        // 4 | 3
+        // | |/
+        // 1 2
+        // |/
+        // 0
+        let id_0 = CommitId::from_hex("000000");
+        let id_1 = CommitId::from_hex("111111");
+        let id_2 = CommitId::from_hex("222222");
+        let id_3 = CommitId::from_hex("333333");
+        let id_4 = CommitId::from_hex("444444");
+        let id_5 = CommitId::from_hex("555555");
+        index.add_commit_data(id_0.clone(), new_change_id(), &[]);
+        index.add_commit_data(id_1.clone(), new_change_id(), slice::from_ref(&id_0));
+        index.add_commit_data(id_2.clone(), new_change_id(), slice::from_ref(&id_0));
+        index.add_commit_data(id_3.clone(), new_change_id(), slice::from_ref(&id_2));
+        index.add_commit_data(id_4.clone(), new_change_id(), slice::from_ref(&id_1));
+        index.add_commit_data(id_5.clone(), new_change_id(), &[id_4.clone(), id_2.clone()]);
+
+        let walk_commit_ids = |wanted: &[CommitId], unwanted: &[CommitId]| {
+            let index = index.as_composite();
+            let mut heads = to_positions_vec(index, wanted);
+            heads.sort_unstable_by_key(|&pos| Reverse(pos));
+            let eager = RevWalkBuilder::new(index)
+                .wanted_heads(heads.clone())
+                .unwanted_roots(to_positions_vec(index, unwanted))
+                .ancestors()
+                .collect_vec();
+            let lazy = RevWalkLazyAncestors::new(
+                EagerRevWalk::new(heads.into_iter()),
+                to_positions_vec(index, unwanted),
+            )
+            .attach(index)
+            .collect_vec();
+            assert_eq!(lazy, eager);
+            lazy.iter()
+                .map(|&pos| index.entry_by_pos(pos).commit_id())
+                .collect_vec()
+        };
+
+        assert!(walk_commit_ids(&[], &[]).is_empty());
+        assert!(walk_commit_ids(&[], slice::from_ref(&id_1)).is_empty());
+        assert_eq!(
+            walk_commit_ids(slice::from_ref(&id_4), &[]),
+            vec![id_4.clone(), id_1.clone(), id_0.clone()]
+        );
+        assert_eq!(
+            walk_commit_ids(slice::from_ref(&id_0), slice::from_ref(&id_0)),
+            vec![]
+        );
+        assert_eq!(
+            walk_commit_ids(&[id_1.clone(), id_0.clone()], &[]),
+            vec![id_1.clone(), id_0.clone()]
+        );
+        assert_eq!(
+            walk_commit_ids(slice::from_ref(&id_2), slice::from_ref(&id_1)),
+            vec![id_2.clone()]
+        );
+        assert_eq!(
+            walk_commit_ids(slice::from_ref(&id_1), slice::from_ref(&id_3)),
+            vec![id_1.clone()]
+        );
+        assert_eq!(
+            walk_commit_ids(&[id_5.clone(), id_3.clone()], slice::from_ref(&id_2)),
+            vec![id_5.clone(), id_4.clone(), id_3.clone(), id_1.clone()]
+        );
+
+        // Heads are only pulled once the walk reaches them
+        let index = index.as_composite();
+        let num_pulled = Cell::new(0);
+        let heads = to_positions_vec(index, &[id_5.clone(), id_3.clone(), id_1.clone()]);
+        let mut walk = RevWalkLazyAncestors::new(
+            EagerRevWalk::new(
+                heads
+                    .into_iter()
+                    .inspect(|_| num_pulled.set(num_pulled.get() + 1)),
+            ),
+            [],
+        );
+        assert_eq!(
+            walk.next(index),
+            Some(index.commit_id_to_pos(&id_5).unwrap())
+        );
+        assert_eq!(num_pulled.get(), 2);
+        assert_eq!(
+            walk.next(index),
+            Some(index.commit_id_to_pos(&id_4).unwrap())
+        );
+        assert_eq!(num_pulled.get(), 2);
+    }
+
     #[test]
     fn test_walk_ancestors_until_roots() {
         let mut new_change_id = change_id_generator();
@@ -976,6 +1220,76 @@ mod tests {
         assert!(iter.walk.queue.items.is_empty()); // no ids should be queued
     }
 
+    #[test]
+    fn test_walk_ancestors_until_root_generations() {
+        let mut new_change_id = change_id_generator();
+        let mut index = DefaultMutableIndex::full(3, 16);
+        //   7
+        // 6 |
+        // 5 |
+        // 4 |
+        // | 3
+        // | 2
+        // |/
+        // 1
+        // 0
+        let id_0 = CommitId::from_hex("000000");
+        let id_1 = CommitId::from_hex("111111");
+        let id_2 = CommitId::from_hex("222222");
+        let id_3 = CommitId::from_hex("333333");
+        let id_4 = CommitId::from_hex("444444");
+        let id_5 = CommitId::from_hex("555555");
+        let id_6 = CommitId::from_hex("666666");
+        let id_7 = CommitId::from_hex("777777");
+        index.add_commit_data(id_0.clone(), new_change_id(), &[]);
+        index.add_commit_data(id_1.clone(), new_change_id(), &[id_0.clone()]);
+        index.add_commit_data(id_2.clone(), new_change_id(), &[id_1.clone()]);
+        index.add_commit_data(id_3.clone(), new_change_id(), &[id_2.clone()]);
+        index.add_commit_data(id_4.clone(), new_change_id(), &[id_1.clone()]);
+        index.add_commit_data(id_5.clone(), new_change_id(), &[id_4.clone()]);
+        index.add_commit_data(id_6.clone(), new_change_id(), &[id_5.clone()]);
+        index.add_commit_data(id_7.clone(), new_change_id(), &[id_3.clone()]);
+
+        let index = index.as_composite();
+        let make_iter = |heads: &[CommitId], roots: &[CommitId]| {
+            RevWalkBuilder::new(index)
+                .wanted_heads(to_positions_vec(index, heads))
+                .ancestors_until_root_generations(to_positions_vec(index, roots))
+        };
+        let to_commit_id = |pos| index.entry_by_pos(pos).commit_id();
+
+        let mut iter = make_iter(&[id_6.clone(), id_7.clone()], &[id_3.clone()]);
+        assert_eq!(iter.walk.queue.items.len(), 2);
+        assert_eq!(iter.next().map(to_commit_id), Some(id_7.clone()));
+        assert_eq!(iter.next().map(to_commit_id), Some(id_6.clone()));
+        assert_eq!(iter.next().map(to_commit_id), Some(id_5.clone()));
+        // id_4 shouldn't be queued since its generation is smaller than id_3's
+        assert_eq!(iter.walk.queue.items.len(), 1);
+        assert_eq!(iter.next().map(to_commit_id), Some(id_3.clone()));
+        assert_eq!(iter.walk.queue.items.len(), 0); // id_2 shouldn't be queued
+        assert!(iter.next().is_none());
+
+        // Heads with smaller generation numbers than the roots aren't queued
+        let iter = make_iter(&[id_6.clone(), id_7.clone(), id_4.clone()], &[id_3.clone()]);
+        assert_eq!(iter.walk.queue.items.len(), 2); // id_4 shouldn't be queued
+
+        // The smallest generation number of the roots is used
+        let iter = make_iter(&[id_6.clone(), id_7.clone()], &[id_3.clone(), id_4.clone()]);
+        assert_eq!(
+            iter.map(to_commit_id).collect_vec(),
+            vec![
+                id_7.clone(),
+                id_6.clone(),
+                id_5.clone(),
+                id_4.clone(),
+                id_3.clone(),
+            ]
+        );
+
+        let iter = make_iter(&[id_6.clone(), id_7.clone()], &[]);
+        assert!(iter.walk.queue.items.is_empty()); // no ids should be queued
+    }
+
     #[test]
     fn test_walk_ancestors_filtered_by_generation() {
         let mut new_change_id = change_id_generator();
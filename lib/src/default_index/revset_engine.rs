@@ -31,10 +31,14 @@ use futures::StreamExt as _;
 use itertools::Itertools;
 use pollster::FutureExt as _;
 
+use super::changed_path::fileset_filter_paths;
+use super::changed_path::ChangedPathIndex;
+use super::copy_records::CopyRecordIndex;
 use super::rev_walk::EagerRevWalk;
 use super::rev_walk::PeekableRevWalk;
 use super::rev_walk::RevWalk;
 use super::rev_walk::RevWalkBuilder;
+use super::rev_walk::RevWalkLazyAncestors;
 use super::revset_graph_iterator::RevsetGraphWalk;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
@@ -49,10 +53,12 @@ use crate::default_index::AsCompositeIndex;
 use crate::default_index::CompositeIndex;
 use crate::default_index::IndexPosition;
 use crate::graph::GraphEdge;
+use crate::matchers::FilesMatcher;
 use crate::matchers::Matcher;
 use crate::matchers::Visit;
 use crate::merged_tree::resolve_file_values;
 use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
 use crate::revset::ResolvedExpression;
 use crate::revset::ResolvedPredicateExpression;
 use crate::revset::Revset;
@@ -362,6 +368,53 @@ where
     })
 }
 
+/// Ancestors of `heads`, excluding the ancestors of `roots`.
+///
+/// The heads are evaluated only as far as the ancestors are walked, so e.g.
+/// `::description(foo)` doesn't have to test every commit before emitting the
+/// first one.
+#[derive(Debug)]
+struct AncestorsRevset<S> {
+    heads: S,
+    roots: Vec<IndexPosition>,
+}
+
+impl<S: InternalRevset> InternalRevset for AncestorsRevset<S> {
+    fn positions<'a>(&self) -> BoxedRevWalk<'a>
+    where
+        Self: 'a,
+    {
+        // Pre-filter heads so queries like 'immutable_heads()..' can
+        // terminate early. immutable_heads() usually includes some
+        // visible heads, which can be trivially rejected.
+        let head_positions = difference_by(
+            self.heads.positions(),
+            EagerRevWalk::new(self.roots.clone().into_iter()),
+            |pos1, pos2| pos1.cmp(pos2).reverse(),
+        );
+        Box::new(RevWalkLazyAncestors::new(
+            head_positions,
+            self.roots.iter().copied(),
+        ))
+    }
+
+    fn into_predicate<'a>(self: Box<Self>) -> Box<dyn ToPredicateFn + 'a>
+    where
+        Self: 'a,
+    {
+        self
+    }
+}
+
+impl<S: InternalRevset> ToPredicateFn for AncestorsRevset<S> {
+    fn to_predicate_fn<'a>(&self) -> BoxedPredicateFn<'a>
+    where
+        Self: 'a,
+    {
+        predicate_fn_from_rev_walk(self.positions())
+    }
+}
+
 #[derive(Debug)]
 struct FilterRevset<S, P> {
     candidates: S,
@@ -726,10 +779,25 @@ pub fn evaluate<I: AsCompositeIndex + Clone>(
     expression: &ResolvedExpression,
     store: &Arc<Store>,
     index: I,
+) -> Result<RevsetImpl<I>, RevsetEvaluationError> {
+    evaluate_with_side_indexes(expression, store, index, None, None)
+}
+
+/// Like `evaluate()`, but uses the changed-path filters to skip diffing
+/// commits that can't match `file()` predicates, and the stored copy records
+/// to follow renames without detecting copies.
+pub(super) fn evaluate_with_side_indexes<I: AsCompositeIndex + Clone>(
+    expression: &ResolvedExpression,
+    store: &Arc<Store>,
+    index: I,
+    changed_paths: Option<ChangedPathIndex>,
+    copy_records: Option<CopyRecordIndex>,
 ) -> Result<RevsetImpl<I>, RevsetEvaluationError> {
     let context = EvaluationContext {
         store: store.clone(),
         index: index.as_composite(),
+        changed_paths,
+        copy_records,
     };
     let internal_revset = context.evaluate(expression)?;
     Ok(RevsetImpl::new(internal_revset, index))
@@ -738,6 +806,8 @@ pub fn evaluate<I: AsCompositeIndex + Clone>(
 struct EvaluationContext<'index> {
     store: Arc<Store>,
     index: &'index CompositeIndex,
+    changed_paths: Option<ChangedPathIndex>,
+    copy_records: Option<CopyRecordIndex>,
 }
 
 fn to_u32_generation_range(range: &Range<u64>) -> Result<Range<u32>, RevsetEvaluationError> {
@@ -763,12 +833,14 @@ impl<'index> EvaluationContext<'index> {
             }
             ResolvedExpression::Ancestors { heads, generation } => {
                 let head_set = self.evaluate(heads)?;
-                let head_positions = head_set.positions().attach(index);
-                let builder = RevWalkBuilder::new(index).wanted_heads(head_positions);
                 if generation == &GENERATION_RANGE_FULL {
-                    let walk = builder.ancestors().detach();
-                    Ok(Box::new(RevWalkRevset { walk }))
+                    Ok(Box::new(AncestorsRevset {
+                        heads: head_set,
+                        roots: vec![],
+                    }))
                 } else {
+                    let head_positions = head_set.positions().attach(index);
+                    let builder = RevWalkBuilder::new(index).wanted_heads(head_positions);
                     let generation = to_u32_generation_range(generation)?;
                     let walk = builder
                         .ancestors_filtered_by_generation(generation)
@@ -783,29 +855,29 @@ impl<'index> EvaluationContext<'index> {
             } => {
                 let root_set = self.evaluate(roots)?;
                 let root_positions = root_set.positions().attach(index).collect_vec();
+                let head_set = self.evaluate(heads)?;
+                if generation == &GENERATION_RANGE_FULL {
+                    return Ok(Box::new(AncestorsRevset {
+                        heads: head_set,
+                        roots: root_positions,
+                    }));
+                }
                 // Pre-filter heads so queries like 'immutable_heads()..' can
                 // terminate early. immutable_heads() usually includes some
                 // visible heads, which can be trivially rejected.
-                let head_set = self.evaluate(heads)?;
                 let head_positions = difference_by(
                     head_set.positions(),
                     EagerRevWalk::new(root_positions.iter().copied()),
                     |pos1, pos2| pos1.cmp(pos2).reverse(),
                 )
                 .attach(index);
-                let builder = RevWalkBuilder::new(index)
+                let generation = to_u32_generation_range(generation)?;
+                let walk = RevWalkBuilder::new(index)
                     .wanted_heads(head_positions)
-                    .unwanted_roots(root_positions);
-                if generation == &GENERATION_RANGE_FULL {
-                    let walk = builder.ancestors().detach();
-                    Ok(Box::new(RevWalkRevset { walk }))
-                } else {
-                    let generation = to_u32_generation_range(generation)?;
-                    let walk = builder
-                        .ancestors_filtered_by_generation(generation)
-                        .detach();
-                    Ok(Box::new(RevWalkRevset { walk }))
-                }
+                    .unwanted_roots(root_positions)
+                    .ancestors_filtered_by_generation(generation)
+                    .detach();
+                Ok(Box::new(RevWalkRevset { walk }))
             }
             ResolvedExpression::DagRange {
                 roots,
@@ -948,9 +1020,12 @@ impl<'index> EvaluationContext<'index> {
         expression: &ResolvedPredicateExpression,
     ) -> Result<Box<dyn ToPredicateFn>, RevsetEvaluationError> {
         match expression {
-            ResolvedPredicateExpression::Filter(predicate) => {
-                Ok(build_predicate_fn(self.store.clone(), predicate))
-            }
+            ResolvedPredicateExpression::Filter(predicate) => Ok(build_predicate_fn(
+                self.store.clone(),
+                self.changed_paths.clone(),
+                self.copy_records.clone(),
+                predicate,
+            )),
             ResolvedPredicateExpression::Set(expression) => {
                 Ok(self.evaluate(expression)?.into_predicate())
             }
@@ -1057,6 +1132,8 @@ fn box_pure_predicate_fn<'a>(
 
 fn build_predicate_fn(
     store: Arc<Store>,
+    changed_paths: Option<ChangedPathIndex>,
+    copy_records: Option<CopyRecordIndex>,
     predicate: &RevsetFilterPredicate,
 ) -> Box<dyn ToPredicateFn> {
     // TODO: propagate BackendError
@@ -1115,8 +1192,16 @@ fn build_predicate_fn(
         }
         RevsetFilterPredicate::File(expr) => {
             let matcher: Rc<dyn Matcher> = expr.to_matcher().into();
+            let filter_paths: Option<Vec<RepoPathBuf>> = fileset_filter_paths(expr)
+                .map(|paths| paths.into_iter().map(|path| path.to_owned()).collect());
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
+                if let (Some(changed_paths), Some(paths)) = (&changed_paths, &filter_paths) {
+                    let paths = paths.iter().map(|path| path.as_ref());
+                    if !changed_paths.may_contain_any(&entry.commit_id(), paths) {
+                        return false;
+                    }
+                }
                 let commit = store.get_commit(&entry.commit_id()).unwrap();
                 has_diff_from_parent(&store, index, &commit, matcher.as_ref()).unwrap()
             })
@@ -1131,6 +1216,12 @@ fn build_predicate_fn(
                     .unwrap()
             })
         }
+        RevsetFilterPredicate::Follows(path) => Box::new(FollowsPredicateFn {
+            store,
+            changed_paths,
+            copy_records,
+            path: path.clone(),
+        }),
         RevsetFilterPredicate::HasConflict => box_pure_predicate_fn(move |index, pos| {
             let entry = index.entry_by_pos(pos);
             let commit = store.get_commit(&entry.commit_id()).unwrap();
@@ -1147,6 +1238,88 @@ fn build_predicate_fn(
     }
 }
 
+/// Predicate that matches the commits modifying a file, following the file
+/// across copies and renames.
+///
+/// Since the commits are visited from descendants to ancestors, the paths the
+/// file was copied or renamed from can be tracked as they're found. This makes
+/// the predicate function stateful. The paths are found in the commits being
+/// filtered only.
+#[derive(Debug)]
+struct FollowsPredicateFn {
+    store: Arc<Store>,
+    changed_paths: Option<ChangedPathIndex>,
+    copy_records: Option<CopyRecordIndex>,
+    path: RepoPathBuf,
+}
+
+impl ToPredicateFn for FollowsPredicateFn {
+    fn to_predicate_fn<'a>(&self) -> BoxedPredicateFn<'a>
+    where
+        Self: 'a,
+    {
+        let store = self.store.clone();
+        let changed_paths = self.changed_paths.clone();
+        let copy_records = self.copy_records.clone();
+        let mut paths = vec![self.path.clone()];
+        Box::new(move |index, pos| {
+            let entry = index.entry_by_pos(pos);
+            if let Some(changed_paths) = &changed_paths {
+                let paths = paths.iter().map(|path| path.as_ref());
+                if !changed_paths.may_contain_any(&entry.commit_id(), paths) {
+                    return false;
+                }
+            }
+            let commit = store.get_commit(&entry.commit_id()).unwrap();
+            let matcher = FilesMatcher::new(&paths);
+            if !has_diff_from_parent(&store, index, &commit, &matcher).unwrap() {
+                return false;
+            }
+            let sources = find_copy_sources(&store, copy_records.as_ref(), &commit, &paths);
+            for source in sources.unwrap() {
+                if !paths.contains(&source) {
+                    paths.push(source);
+                }
+            }
+            true
+        })
+    }
+}
+
+/// Finds the paths in the parents of the commit that any of the `paths` were
+/// copied or renamed from.
+fn find_copy_sources(
+    store: &Arc<Store>,
+    copy_records: Option<&CopyRecordIndex>,
+    commit: &Commit,
+    paths: &[RepoPathBuf],
+) -> BackendResult<Vec<RepoPathBuf>> {
+    let mut sources = vec![];
+    for parent_id in commit.parent_ids() {
+        // The root commit has no files to copy from.
+        if parent_id == store.root_commit_id() {
+            continue;
+        }
+        if let Some(records) = copy_records.and_then(|index| index.get(commit.id(), parent_id)) {
+            sources.extend(
+                records
+                    .into_iter()
+                    .filter(|record| paths.contains(&record.target))
+                    .map(|record| record.source),
+            );
+            continue;
+        }
+        let mut records = store.get_copy_records(Some(paths), parent_id, commit.id())?;
+        while let Some(record) = records.next().block_on() {
+            let record = record?;
+            if paths.contains(&record.target) {
+                sources.push(record.source);
+            }
+        }
+    }
+    Ok(sources)
+}
+
 fn has_diff_from_parent(
     store: &Arc<Store>,
     index: &CompositeIndex,
@@ -15,6 +15,7 @@
 #![allow(missing_docs)]
 
 use std::any::Any;
+use std::cell::Cell;
 use std::collections::HashSet;
 use std::fs;
 use std::io;
@@ -27,13 +28,24 @@ use itertools::Itertools;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
+use super::changed_path::add_changed_path_filter;
+use super::changed_path::compute_changed_path_filter;
+use super::changed_path::ChangedPathIndex;
+use super::composite::AsCompositeIndex;
+use super::composite::CompositeIndex;
+use super::copy_records::add_copy_records;
+use super::copy_records::compute_copy_records;
+use super::copy_records::CopyRecordIndex;
+use super::entry::IndexPosition;
 use super::mutable::DefaultMutableIndex;
+use super::mutable::MutableIndexSegment;
 use super::readonly::DefaultReadonlyIndex;
 use super::readonly::ReadonlyIndexLoadError;
 use super::readonly::ReadonlyIndexSegment;
 use crate::backend::BackendError;
 use crate::backend::BackendInitError;
 use crate::backend::CommitId;
+use crate::commit::Commit;
 use crate::commit::CommitByCommitterTimestamp;
 use crate::dag_walk;
 use crate::file_util;
@@ -50,11 +62,18 @@ use crate::object_id::ObjectId;
 use crate::op_store::OpStoreError;
 use crate::op_store::OperationId;
 use crate::operation::Operation;
+use crate::stacked_table::TableSegment;
+use crate::stacked_table::TableStore;
+use crate::stacked_table::TableStoreError;
 use crate::store::Store;
 
 // BLAKE2b-512 hash length in hex string
 const SEGMENT_FILE_NAME_LENGTH: usize = 64 * 2;
 
+/// Number of commits after which a partially built index is saved, so an
+/// interrupted build can resume from there.
+const INDEX_CHUNK_SIZE: usize = 100_000;
+
 /// Error that may occur during `DefaultIndexStore` initialization.
 #[derive(Debug, Error)]
 #[error("Failed to initialize index store")]
@@ -87,10 +106,34 @@ pub enum DefaultIndexStoreError {
         op_id: OperationId,
         source: BackendError,
     },
+    #[error("Failed to compute paths changed by commit {commit_id}", commit_id = commit_id.hex())]
+    ChangedPaths {
+        commit_id: CommitId,
+        source: BackendError,
+    },
+    #[error("Failed to access changed-path filters")]
+    ChangedPathTable(#[source] TableStoreError),
+    #[error("Failed to detect copies made by commit {commit_id}", commit_id = commit_id.hex())]
+    CopyRecords {
+        commit_id: CommitId,
+        source: BackendError,
+    },
+    #[error("Failed to access copy records")]
+    CopyRecordTable(#[source] TableStoreError),
     #[error(transparent)]
     OpStore(#[from] OpStoreError),
 }
 
+/// Progress of building the index of an operation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndexProgress {
+    /// The commits to index are being read from the store. The total isn't
+    /// known yet.
+    LoadingCommits { loaded: usize },
+    /// The loaded commits are being added to the index.
+    IndexingCommits { indexed: usize, total: usize },
+}
+
 #[derive(Debug)]
 pub struct DefaultIndexStore {
     dir: PathBuf,
@@ -120,6 +163,7 @@ impl DefaultIndexStore {
         self.ensure_base_dirs()?;
         // Remove all operation links to trigger rebuilding.
         file_util::remove_dir_contents(&self.operations_dir())?;
+        file_util::remove_dir_contents(&self.partial_dir())?;
         // Remove index segments to save disk space. If raced, new segment file
         // will be created by the other process.
         file_util::remove_dir_contents(&self.segments_dir())?;
@@ -137,7 +181,11 @@ impl DefaultIndexStore {
     }
 
     fn ensure_base_dirs(&self) -> Result<(), PathError> {
-        for dir in [self.operations_dir(), self.segments_dir()] {
+        for dir in [
+            self.operations_dir(),
+            self.partial_dir(),
+            self.segments_dir(),
+        ] {
             file_util::create_or_reuse_dir(&dir).context(&dir)?;
         }
         Ok(())
@@ -147,10 +195,216 @@ impl DefaultIndexStore {
         self.dir.join("operations")
     }
 
+    /// Directory of links from operations to the segments of their partially
+    /// built index.
+    fn partial_dir(&self) -> PathBuf {
+        self.dir.join("partial")
+    }
+
     fn segments_dir(&self) -> PathBuf {
         self.dir.join("segments")
     }
 
+    /// Returns true if the index of the operation has been built.
+    pub fn has_index_at_operation(&self, op_id: &OperationId) -> bool {
+        self.operations_dir().join(op_id.hex()).is_file()
+    }
+
+    fn changed_paths_dir(&self) -> PathBuf {
+        self.dir.join("changed_paths")
+    }
+
+    /// Returns the store of changed-path filters, or `None` if the filters
+    /// haven't been enabled by `build_changed_path_index()`.
+    fn changed_path_table_store(&self, commit_id_length: usize) -> Option<TableStore> {
+        let dir = self.changed_paths_dir();
+        dir.join("heads")
+            .is_dir()
+            .then(|| TableStore::load(dir, commit_id_length))
+    }
+
+    fn load_changed_path_index(
+        &self,
+        commit_id_length: usize,
+    ) -> Result<Option<ChangedPathIndex>, DefaultIndexStoreError> {
+        let Some(table_store) = self.changed_path_table_store(commit_id_length) else {
+            return Ok(None);
+        };
+        let table = table_store
+            .get_head()
+            .map_err(DefaultIndexStoreError::ChangedPathTable)?;
+        Ok(Some(ChangedPathIndex::new(table)))
+    }
+
+    /// Computes and stores the changed-path filters of the `commits` which
+    /// don't have one yet. Returns the number of filters added.
+    fn add_changed_path_filters(
+        &self,
+        table_store: &TableStore,
+        index: &CompositeIndex,
+        commits: impl IntoIterator<Item = Result<Commit, DefaultIndexStoreError>>,
+    ) -> Result<usize, DefaultIndexStoreError> {
+        let (table, _lock) = table_store
+            .get_head_locked()
+            .map_err(DefaultIndexStoreError::ChangedPathTable)?;
+        let mut mut_table = table.start_mutation();
+        let mut num_added = 0;
+        for commit in commits {
+            let commit = commit?;
+            if table.get_value(commit.id().as_bytes()).is_some() {
+                continue;
+            }
+            let filter = compute_changed_path_filter(index, &commit).map_err(|source| {
+                DefaultIndexStoreError::ChangedPaths {
+                    commit_id: commit.id().clone(),
+                    source,
+                }
+            })?;
+            add_changed_path_filter(&mut mut_table, commit.id(), &filter);
+            num_added += 1;
+        }
+        if num_added > 0 {
+            table_store
+                .save_table(mut_table)
+                .map_err(DefaultIndexStoreError::ChangedPathTable)?;
+        }
+        Ok(num_added)
+    }
+
+    /// Enables the changed-path filters, and computes them for all commits in
+    /// the `index` which don't have one yet. Returns the number of filters
+    /// added.
+    ///
+    /// Once enabled, the filters of new commits are computed when they're
+    /// indexed.
+    pub fn build_changed_path_index(
+        &self,
+        index: &DefaultReadonlyIndex,
+        store: &Arc<Store>,
+    ) -> Result<usize, DefaultIndexStoreError> {
+        let table_store = match self.changed_path_table_store(store.commit_id_length()) {
+            Some(table_store) => table_store,
+            None => {
+                let dir = self.changed_paths_dir();
+                file_util::create_or_reuse_dir(&dir)
+                    .map_err(|err| DefaultIndexStoreError::ChangedPathTable(err.into()))?;
+                TableStore::init(dir, store.commit_id_length())
+            }
+        };
+        let index = index.as_composite();
+        let commits = (0..index.num_commits()).map(|pos| {
+            let commit_id = index.entry_by_pos(IndexPosition(pos)).commit_id();
+            store
+                .get_commit(&commit_id)
+                .map_err(|source| DefaultIndexStoreError::ChangedPaths { commit_id, source })
+        });
+        self.add_changed_path_filters(&table_store, index, commits)
+    }
+
+    fn copy_records_dir(&self) -> PathBuf {
+        self.dir.join("copy_records")
+    }
+
+    /// Returns the store of copy records, or `None` if the copy records haven't
+    /// been enabled by `build_copy_record_index()`.
+    fn copy_record_table_store(&self, commit_id_length: usize) -> Option<TableStore> {
+        let dir = self.copy_records_dir();
+        dir.join("heads")
+            .is_dir()
+            .then(|| TableStore::load(dir, commit_id_length))
+    }
+
+    fn load_copy_record_index(
+        &self,
+        commit_id_length: usize,
+    ) -> Result<Option<CopyRecordIndex>, DefaultIndexStoreError> {
+        let Some(table_store) = self.copy_record_table_store(commit_id_length) else {
+            return Ok(None);
+        };
+        let table = table_store
+            .get_head()
+            .map_err(DefaultIndexStoreError::CopyRecordTable)?;
+        Ok(Some(CopyRecordIndex::new(table)))
+    }
+
+    /// Detects and stores the copy records of the `commits` which don't have
+    /// them yet. Returns the number of commits added.
+    fn add_copy_records(
+        &self,
+        table_store: &TableStore,
+        commits: impl IntoIterator<Item = Result<Commit, DefaultIndexStoreError>>,
+    ) -> Result<usize, DefaultIndexStoreError> {
+        let (table, _lock) = table_store
+            .get_head_locked()
+            .map_err(DefaultIndexStoreError::CopyRecordTable)?;
+        let mut mut_table = table.start_mutation();
+        let mut num_added = 0;
+        for commit in commits {
+            let commit = commit?;
+            if table.get_value(commit.id().as_bytes()).is_some() {
+                continue;
+            }
+            let records = compute_copy_records(&commit).map_err(|source| {
+                DefaultIndexStoreError::CopyRecords {
+                    commit_id: commit.id().clone(),
+                    source,
+                }
+            })?;
+            add_copy_records(&mut mut_table, commit.id(), &records);
+            num_added += 1;
+        }
+        if num_added > 0 {
+            table_store
+                .save_table(mut_table)
+                .map_err(DefaultIndexStoreError::CopyRecordTable)?;
+        }
+        Ok(num_added)
+    }
+
+    /// Enables the stored copy records, and detects them for all commits in
+    /// the `index` which don't have them yet. Returns the number of commits
+    /// added.
+    ///
+    /// Once enabled, the copy records of new commits are detected when they're
+    /// indexed.
+    pub fn build_copy_record_index(
+        &self,
+        index: &DefaultReadonlyIndex,
+        store: &Arc<Store>,
+    ) -> Result<usize, DefaultIndexStoreError> {
+        let table_store = match self.copy_record_table_store(store.commit_id_length()) {
+            Some(table_store) => table_store,
+            None => {
+                let dir = self.copy_records_dir();
+                file_util::create_or_reuse_dir(&dir)
+                    .map_err(|err| DefaultIndexStoreError::CopyRecordTable(err.into()))?;
+                TableStore::init(dir, store.commit_id_length())
+            }
+        };
+        let index = index.as_composite();
+        let commits = (0..index.num_commits()).map(|pos| {
+            let commit_id = index.entry_by_pos(IndexPosition(pos)).commit_id();
+            store
+                .get_commit(&commit_id)
+                .map_err(|source| DefaultIndexStoreError::CopyRecords { commit_id, source })
+        });
+        self.add_copy_records(&table_store, commits)
+    }
+
+    /// Loads the side indexes enabled in this store, and attaches them to
+    /// the `index_segment`.
+    fn readonly_index_with_side_indexes(
+        &self,
+        index_segment: Arc<ReadonlyIndexSegment>,
+        commit_id_length: usize,
+    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
+        let changed_paths = self.load_changed_path_index(commit_id_length)?;
+        let copy_records = self.load_copy_record_index(commit_id_length)?;
+        Ok(DefaultReadonlyIndex::from_segment(index_segment)
+            .with_changed_paths(changed_paths)
+            .with_copy_records(copy_records))
+    }
+
     fn load_index_segments_at_operation(
         &self,
         op_id: &OperationId,
@@ -178,15 +432,69 @@ impl DefaultIndexStore {
         operation: &Operation,
         store: &Arc<Store>,
     ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
-        let index_segment = self.build_index_segments_at_operation(operation, store)?;
-        Ok(DefaultReadonlyIndex::from_segment(index_segment))
+        self.build_index_at_operation_with_progress(operation, store, None)
     }
 
-    #[tracing::instrument(skip(self, store))]
+    /// Like `build_index_at_operation()`, but reports the progress to the
+    /// `progress` callback.
+    pub fn build_index_at_operation_with_progress(
+        &self,
+        operation: &Operation,
+        store: &Arc<Store>,
+        progress: Option<&dyn Fn(&IndexProgress)>,
+    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
+        let index_segment = self.build_index_segments_at_operation(operation, store, progress)?;
+        self.readonly_index_with_side_indexes(index_segment, store.commit_id_length())
+    }
+
+    /// Squashes the index at the operation into a single segment file if it's
+    /// stacked on more than `max_segments` files. Returns true if the index
+    /// was squashed.
+    ///
+    /// Every lookup in the index visits each segment, so an index with fewer
+    /// segments is faster to load and to query.
+    pub fn squash_index_at_operation(
+        &self,
+        op_id: &OperationId,
+        store: &Arc<Store>,
+        max_segments: usize,
+    ) -> Result<bool, DefaultIndexStoreError> {
+        let index_segment = self.load_index_segments_at_operation(
+            op_id,
+            store.commit_id_length(),
+            store.change_id_length(),
+        )?;
+        let ancestor_files = index_segment
+            .as_composite()
+            .ancestor_files_without_local()
+            .collect_vec();
+        let num_segments = ancestor_files.len() + 1;
+        if num_segments <= max_segments.max(1) {
+            return Ok(false);
+        }
+        let mut squashed =
+            MutableIndexSegment::full(store.commit_id_length(), store.change_id_length());
+        for file in ancestor_files.iter().rev() {
+            squashed.add_commits_from(file.as_ref());
+        }
+        squashed.add_commits_from(index_segment.as_ref());
+        let squashed = squashed
+            .save_in(&self.segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+        self.associate_file_with_operation(&squashed, op_id)
+            .map_err(|source| DefaultIndexStoreError::AssociateIndex {
+                op_id: op_id.to_owned(),
+                source,
+            })?;
+        Ok(true)
+    }
+
+    #[tracing::instrument(skip(self, store, progress))]
     fn build_index_segments_at_operation(
         &self,
         operation: &Operation,
         store: &Arc<Store>,
+        progress: Option<&dyn Fn(&IndexProgress)>,
     ) -> Result<Arc<ReadonlyIndexSegment>, DefaultIndexStoreError> {
         let view = operation.view()?;
         let operations_dir = self.operations_dir();
@@ -219,14 +527,22 @@ impl DefaultIndexStore {
                 }
             }
         }
+        // An earlier build of this index may have been interrupted, in which
+        // case it continues from the last saved chunk.
+        let partial_file =
+            self.load_partial_index_segment(operation.id(), commit_id_length, change_id_length);
         let maybe_parent_file;
         let mut mutable_index;
-        match parent_op_id {
-            None => {
+        match (partial_file, parent_op_id) {
+            (Some(partial_file), _) => {
+                maybe_parent_file = Some(partial_file.clone());
+                mutable_index = DefaultMutableIndex::incremental(partial_file);
+            }
+            (None, None) => {
                 maybe_parent_file = None;
                 mutable_index = DefaultMutableIndex::full(commit_id_length, change_id_length);
             }
-            Some(parent_op_id) => {
+            (None, Some(parent_op_id)) => {
                 let parent_file = self.load_index_segments_at_operation(
                     &parent_op_id,
                     commit_id_length,
@@ -249,8 +565,15 @@ impl DefaultIndexStore {
                 .as_ref()
                 .map_or(false, |segment| segment.as_composite().has_id(id))
         };
+        let num_loaded = Cell::new(0);
         let get_commit_with_op = |commit_id: &CommitId, op_id: &OperationId| {
             let op_id = op_id.clone();
+            num_loaded.set(num_loaded.get() + 1);
+            if let Some(progress) = progress {
+                progress(&IndexProgress::LoadingCommits {
+                    loaded: num_loaded.get(),
+                });
+            }
             match store.get_commit(commit_id) {
                 // Propagate head's op_id to report possible source of an error.
                 // The op_id doesn't have to be included in the sort key, but
@@ -272,11 +595,25 @@ impl DefaultIndexStore {
                     .collect_vec()
             },
         )?;
-        for (CommitByCommitterTimestamp(commit), _) in commits.iter().rev() {
+        for (i, (CommitByCommitterTimestamp(commit), _)) in commits.iter().rev().enumerate() {
             mutable_index.add_commit(commit);
+            let num_indexed = i + 1;
+            if let Some(progress) = progress {
+                progress(&IndexProgress::IndexingCommits {
+                    indexed: num_indexed,
+                    total: commits.len(),
+                });
+            }
+            if num_indexed % INDEX_CHUNK_SIZE == 0 && num_indexed < commits.len() {
+                let index_segment = self.save_partial_index(mutable_index, operation.id())?;
+                mutable_index = DefaultMutableIndex::incremental(index_segment);
+            }
         }
 
         let index_file = self.save_mutable_index(mutable_index, operation.id())?;
+        // The partial index isn't needed anymore. It's fine if it couldn't be
+        // removed, since it's ignored once the index is built.
+        fs::remove_file(self.partial_dir().join(operation.id().hex())).ok();
         tracing::info!(
             ?index_file,
             commits_count = commits.len(),
@@ -286,14 +623,49 @@ impl DefaultIndexStore {
         Ok(index_file)
     }
 
-    fn save_mutable_index(
+    /// Loads the partially built index of the operation, if any.
+    fn load_partial_index_segment(
+        &self,
+        op_id: &OperationId,
+        commit_id_length: usize,
+        change_id_length: usize,
+    ) -> Option<Arc<ReadonlyIndexSegment>> {
+        let index_file_id_hex = fs::read_to_string(self.partial_dir().join(op_id.hex())).ok()?;
+        // The segment may have been removed by reinit(), in which case the
+        // index is built from scratch.
+        ReadonlyIndexSegment::load(
+            &self.segments_dir(),
+            index_file_id_hex,
+            commit_id_length,
+            change_id_length,
+        )
+        .ok()
+    }
+
+    /// Saves a chunk of the index being built for the operation.
+    fn save_partial_index(
         &self,
         mutable_index: DefaultMutableIndex,
         op_id: &OperationId,
     ) -> Result<Arc<ReadonlyIndexSegment>, DefaultIndexStoreError> {
-        let index_segment = mutable_index
-            .squash_and_save_in(&self.segments_dir())
+        let index_segment = self.save_index_segment(mutable_index)?;
+        let partial_dir = self.partial_dir();
+        let link_path = partial_dir.join(op_id.hex());
+        // The link of the previous chunk isn't overwritten on Windows.
+        fs::remove_file(&link_path).ok();
+        // The directory may be missing if the store was initialized by old jj.
+        file_util::create_or_reuse_dir(&partial_dir)
+            .and_then(|()| self.write_segment_link(&index_segment, &link_path))
             .map_err(DefaultIndexStoreError::SaveIndex)?;
+        Ok(index_segment)
+    }
+
+    fn save_mutable_index(
+        &self,
+        mutable_index: DefaultMutableIndex,
+        op_id: &OperationId,
+    ) -> Result<Arc<ReadonlyIndexSegment>, DefaultIndexStoreError> {
+        let index_segment = self.save_index_segment(mutable_index)?;
         self.associate_file_with_operation(&index_segment, op_id)
             .map_err(|source| DefaultIndexStoreError::AssociateIndex {
                 op_id: op_id.to_owned(),
@@ -302,16 +674,39 @@ impl DefaultIndexStore {
         Ok(index_segment)
     }
 
+    fn save_index_segment(
+        &self,
+        mutable_index: DefaultMutableIndex,
+    ) -> Result<Arc<ReadonlyIndexSegment>, DefaultIndexStoreError> {
+        let commit_id_length = mutable_index.commit_id_length();
+        if let Some(table_store) = self.changed_path_table_store(commit_id_length) {
+            let commits = mutable_index.new_commits().iter().cloned().map(Ok);
+            self.add_changed_path_filters(&table_store, mutable_index.as_composite(), commits)?;
+        }
+        if let Some(table_store) = self.copy_record_table_store(commit_id_length) {
+            let commits = mutable_index.new_commits().iter().cloned().map(Ok);
+            self.add_copy_records(&table_store, commits)?;
+        }
+        let index_segment = mutable_index
+            .squash_and_save_in(&self.segments_dir())
+            .map_err(DefaultIndexStoreError::SaveIndex)?;
+        Ok(index_segment)
+    }
+
     /// Records a link from the given operation to the this index version.
     fn associate_file_with_operation(
         &self,
         index: &ReadonlyIndexSegment,
         op_id: &OperationId,
     ) -> io::Result<()> {
+        self.write_segment_link(index, &self.operations_dir().join(op_id.hex()))
+    }
+
+    fn write_segment_link(&self, index: &ReadonlyIndexSegment, path: &Path) -> io::Result<()> {
         let mut temp_file = NamedTempFile::new_in(&self.dir)?;
         let file = temp_file.as_file_mut();
         file.write_all(index.name().as_bytes())?;
-        persist_content_addressed_temp_file(temp_file, self.operations_dir().join(op_id.hex()))?;
+        persist_content_addressed_temp_file(temp_file, path)?;
         Ok(())
     }
 }
@@ -338,7 +733,7 @@ impl IndexStore for DefaultIndexStore {
             Err(DefaultIndexStoreError::LoadAssociation(err))
                 if err.kind() == io::ErrorKind::NotFound =>
             {
-                self.build_index_segments_at_operation(op, store)
+                self.build_index_segments_at_operation(op, store, None)
             }
             Err(DefaultIndexStoreError::LoadIndex(err)) if err.is_corrupt_or_not_found() => {
                 // If the index was corrupt (maybe it was written in a different format),
@@ -361,12 +756,15 @@ impl IndexStore for DefaultIndexStore {
                     }
                 }
                 self.reinit().map_err(|err| IndexReadError(err.into()))?;
-                self.build_index_segments_at_operation(op, store)
+                self.build_index_segments_at_operation(op, store, None)
             }
             result => result,
         }
         .map_err(|err| IndexReadError(err.into()))?;
-        Ok(Box::new(DefaultReadonlyIndex::from_segment(index_segment)))
+        let index = self
+            .readonly_index_with_side_indexes(index_segment, store.commit_id_length())
+            .map_err(|err| IndexReadError(err.into()))?;
+        Ok(Box::new(index))
     }
 
     fn write_index(
@@ -378,9 +776,13 @@ impl IndexStore for DefaultIndexStore {
             .into_any()
             .downcast::<DefaultMutableIndex>()
             .expect("index to merge in must be a DefaultMutableIndex");
+        let commit_id_length = index.commit_id_length();
         let index_segment = self
             .save_mutable_index(*index, op.id())
             .map_err(|err| IndexWriteError(err.into()))?;
-        Ok(Box::new(DefaultReadonlyIndex::from_segment(index_segment)))
+        let index = self
+            .readonly_index_with_side_indexes(index_segment, commit_id_length)
+            .map_err(|err| IndexWriteError(err.into()))?;
+        Ok(Box::new(index))
     }
 }
@@ -617,10 +617,6 @@ where
         // For better error indication, builtin functions are shadowed by name,
         // not by (name, arity).
         if let Some(overloads) = self.aliases_map.get_function_overloads(function.name) {
-            // TODO: add support for keyword arguments
-            function
-                .ensure_no_keyword_arguments()
-                .map_err(E::invalid_arguments)?;
             let Some((id, params, defn)) = overloads.find_by_arity(function.arity()) else {
                 let min = overloads.min_arity();
                 let max = overloads.max_arity();
@@ -631,9 +627,20 @@ where
                 };
                 return Err(E::invalid_arguments(err));
             };
+            // Keyword arguments are matched against the parameter names of the
+            // overload selected by arity.
+            let args = if function.keyword_args.is_empty() {
+                function.args
+            } else {
+                let names = params.iter().map(|s| s.as_str()).collect_vec();
+                let (args, _) = function
+                    .expect_named_arguments_vec(&names, names.len(), names.len())
+                    .map_err(E::invalid_arguments)?;
+                args.into_iter().cloned().collect()
+            };
             // Resolve arguments in the current scope, and pass them in to the alias
             // expansion scope.
-            let args = fold_expression_nodes(self, function.args)?;
+            let args = fold_expression_nodes(self, args)?;
             let locals = params.iter().map(|s| s.as_str()).zip(args).collect();
             self.expand_defn(id, defn, locals, span)
         } else {
@@ -0,0 +1,1064 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading and writing of the stream format of `git fast-import`.
+//!
+//! Many version control systems and history-rewriting tools can produce or
+//! consume this format, so it can be used to move history into and out of
+//! repos that aren't backed by Git. Change ids and predecessors have no
+//! representation in the format, so they aren't preserved.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io;
+use std::io::BufRead;
+use std::io::Read as _;
+use std::io::Write;
+
+use futures::StreamExt as _;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::CommitId;
+use crate::backend::FileId;
+use crate::backend::MergedTreeId;
+use crate::backend::MillisSinceEpoch;
+use crate::backend::Signature;
+use crate::backend::Timestamp;
+use crate::backend::TreeValue;
+use crate::commit::Commit;
+use crate::conflicts::materialize_merge_result;
+use crate::conflicts::materialize_tree_value;
+use crate::conflicts::MaterializedTreeValue;
+use crate::hex_util::to_reverse_hex;
+use crate::matchers::EverythingMatcher;
+use crate::matchers::PrefixMatcher;
+use crate::merge::Merge;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::MergedTreeBuilder;
+use crate::object_id::ObjectId as _;
+use crate::op_store::RefTarget;
+use crate::repo::MutableRepo;
+use crate::repo::Repo;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::settings::UserSettings;
+
+/// Error that may occur while writing a fast-import stream.
+#[derive(Debug, Error)]
+pub enum FastExportError {
+    /// Failed to write to the output.
+    #[error("Failed to write the fast-import stream")]
+    Io(#[from] io::Error),
+    /// Failed to read an object from the store.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Describes the result of [`write_fast_export()`].
+#[derive(Clone, Debug, Default)]
+pub struct FastExportStats {
+    /// Number of exported commits.
+    pub num_commits: usize,
+    /// Commits whose conflicted files were written with conflict markers.
+    pub conflicted_commits: Vec<CommitId>,
+}
+
+/// Error that may occur while reading a fast-import stream.
+#[derive(Debug, Error)]
+pub enum FastImportError {
+    /// Failed to read from the input.
+    #[error("Failed to read the fast-import stream")]
+    Io(#[from] io::Error),
+    /// The stream is malformed or uses an unsupported feature.
+    #[error("Line {line}: {message}")]
+    Syntax {
+        /// Line number in the stream, starting from 1.
+        line: usize,
+        /// Description of the problem.
+        message: String,
+    },
+    /// Failed to write an object to the store.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Describes the result of [`read_fast_import()`].
+#[derive(Clone, Debug, Default)]
+pub struct FastImportStats {
+    /// Number of imported commits.
+    pub num_commits: usize,
+    /// Local bookmarks set from `refs/heads/*`, sorted by name.
+    pub bookmarks: Vec<String>,
+    /// Tags set from `refs/tags/*`, sorted by name.
+    pub tags: Vec<String>,
+}
+
+/// Writes the given commits to `output` as a fast-import stream.
+///
+/// The commits must be sorted topologically, with parents before children.
+/// Parents that aren't in `commits` are referred to by their commit id, so
+/// the receiving repo must already have them. Local bookmarks and tags
+/// pointing to the exported commits are written as `refs/heads/*` and
+/// `refs/tags/*`. Heads that no bookmark or tag points to are written as
+/// `refs/jj/heads/<change id>` so that they aren't lost.
+pub fn write_fast_export(
+    repo: &dyn Repo,
+    commits: &[Commit],
+    output: &mut dyn Write,
+) -> Result<FastExportStats, FastExportError> {
+    let store = repo.store();
+    let root_commit_id = store.root_commit_id();
+    let commits_by_id: HashMap<&CommitId, &Commit> =
+        commits.iter().map(|commit| (commit.id(), commit)).collect();
+
+    let is_exported = |id: &&CommitId| commits_by_id.contains_key(*id);
+    let mut ref_targets: Vec<(String, &CommitId)> = vec![];
+    for (name, target) in repo.view().local_bookmarks() {
+        if let Some(id) = target.as_normal().filter(is_exported) {
+            ref_targets.push((format!("refs/heads/{name}"), id));
+        }
+    }
+    for (name, target) in repo.view().tags() {
+        if let Some(id) = target.as_normal().filter(is_exported) {
+            ref_targets.push((format!("refs/tags/{name}"), id));
+        }
+    }
+    let referenced_ids: HashSet<&CommitId> = ref_targets.iter().map(|(_, id)| *id).collect();
+    let ids_with_children: HashSet<&CommitId> =
+        commits.iter().flat_map(|c| c.parent_ids()).collect();
+    for commit in commits {
+        if !ids_with_children.contains(commit.id()) && !referenced_ids.contains(commit.id()) {
+            let change_hex = to_reverse_hex(&commit.change_id().hex()).unwrap();
+            ref_targets.push((format!("refs/jj/heads/{change_hex}"), commit.id()));
+        }
+    }
+
+    // Like `git fast-export`, write each commit to the first ref it's
+    // reachable from.
+    let mut commit_refs: HashMap<&CommitId, &str> = HashMap::new();
+    for (name, id) in &ref_targets {
+        let mut to_visit = vec![*id];
+        while let Some(id) = to_visit.pop() {
+            let Some(&commit) = commits_by_id.get(id) else {
+                continue;
+            };
+            if commit_refs.contains_key(id) {
+                continue;
+            }
+            commit_refs.insert(id, name);
+            to_visit.extend(commit.parent_ids());
+        }
+    }
+
+    let mut writer = FastExportWriter {
+        output,
+        next_mark: 1,
+        commit_marks: HashMap::new(),
+        blob_marks: HashMap::new(),
+    };
+    let mut stats = FastExportStats::default();
+    for commit in commits {
+        let parent_ids = commit
+            .parent_ids()
+            .iter()
+            .filter(|id| *id != root_commit_id)
+            .collect::<Vec<_>>();
+        let parent_tree = match parent_ids.first() {
+            Some(id) => store.get_commit(id)?.tree()?,
+            None => store.get_root_tree(&store.empty_merged_tree_id())?,
+        };
+        let (file_commands, has_conflict) =
+            writer.write_file_changes(repo, &parent_tree, &commit.tree()?)?;
+        if has_conflict {
+            stats.conflicted_commits.push(commit.id().clone());
+        }
+
+        let ref_name = commit_refs[commit.id()];
+        let mark = writer.new_mark();
+        writer.commit_marks.insert(commit.id().clone(), mark);
+        let output = &mut *writer.output;
+        if parent_ids.is_empty() {
+            // Otherwise, the commit would be a child of the current tip of the
+            // ref.
+            writeln!(output, "reset {ref_name}")?;
+        }
+        writeln!(output, "commit {ref_name}")?;
+        writeln!(output, "mark :{mark}")?;
+        write_identity(output, "author", commit.author())?;
+        write_identity(output, "committer", commit.committer())?;
+        write_data(output, commit.description().as_bytes())?;
+        for (i, parent_id) in parent_ids.iter().enumerate() {
+            let command = if i == 0 { "from" } else { "merge" };
+            match writer.commit_marks.get(*parent_id) {
+                Some(mark) => writeln!(output, "{command} :{mark}")?,
+                None => writeln!(output, "{command} {}", parent_id.hex())?,
+            }
+        }
+        for file_command in &file_commands {
+            file_command.write(output)?;
+        }
+        writeln!(output)?;
+        stats.num_commits += 1;
+    }
+
+    let output = &mut *writer.output;
+    for (name, id) in &ref_targets {
+        let mark = writer.commit_marks[*id];
+        writeln!(output, "reset {name}")?;
+        writeln!(output, "from :{mark}")?;
+        writeln!(output)?;
+    }
+    Ok(stats)
+}
+
+struct FastExportWriter<'a> {
+    output: &'a mut dyn Write,
+    next_mark: usize,
+    commit_marks: HashMap<CommitId, usize>,
+    blob_marks: HashMap<FileId, usize>,
+}
+
+impl FastExportWriter<'_> {
+    fn new_mark(&mut self) -> usize {
+        let mark = self.next_mark;
+        self.next_mark += 1;
+        mark
+    }
+
+    /// Writes the blobs of the files changed between the trees. Returns the
+    /// file commands to include in the commit, and whether any of the changed
+    /// files were conflicted.
+    fn write_file_changes(
+        &mut self,
+        repo: &dyn Repo,
+        from_tree: &MergedTree,
+        to_tree: &MergedTree,
+    ) -> Result<(Vec<FileCommand>, bool), FastExportError> {
+        let entries: Vec<_> = from_tree
+            .diff_stream(to_tree, &EverythingMatcher)
+            .collect()
+            .block_on();
+        let mut file_commands = vec![];
+        let mut has_conflict = false;
+        for entry in entries {
+            let path = entry.path;
+            let (_, after) = entry.values?;
+            if after.is_absent() {
+                file_commands.push(FileCommand::Delete(path));
+                continue;
+            }
+            let command = match materialize_tree_value(repo.store(), &path, after).block_on()? {
+                MaterializedTreeValue::Absent => panic!("absent values should be excluded"),
+                MaterializedTreeValue::AccessDenied(err) => {
+                    return Err(BackendError::ReadAccessDenied {
+                        object_type: "file".to_owned(),
+                        hash: path.as_internal_file_string().to_owned(),
+                        source: err,
+                    }
+                    .into());
+                }
+                MaterializedTreeValue::File {
+                    id,
+                    executable,
+                    mut reader,
+                } => {
+                    let mark = match self.blob_marks.get(&id) {
+                        Some(mark) => *mark,
+                        None => {
+                            let mut contents = vec![];
+                            reader.read_to_end(&mut contents)?;
+                            let mark = self.new_mark();
+                            writeln!(self.output, "blob")?;
+                            writeln!(self.output, "mark :{mark}")?;
+                            write_data(self.output, &contents)?;
+                            self.blob_marks.insert(id, mark);
+                            mark
+                        }
+                    };
+                    FileCommand::Modify {
+                        mode: file_mode(executable),
+                        data_ref: format!(":{mark}"),
+                        path,
+                    }
+                }
+                MaterializedTreeValue::Symlink { target, .. } => FileCommand::ModifyInline {
+                    mode: "120000",
+                    data: target.into_bytes(),
+                    path,
+                },
+                MaterializedTreeValue::FileConflict {
+                    contents,
+                    executable,
+                    ..
+                } => {
+                    has_conflict = true;
+                    let mut data = vec![];
+                    materialize_merge_result(&contents, &mut data)?;
+                    FileCommand::ModifyInline {
+                        mode: file_mode(executable),
+                        data,
+                        path,
+                    }
+                }
+                MaterializedTreeValue::OtherConflict { id } => {
+                    has_conflict = true;
+                    FileCommand::ModifyInline {
+                        mode: file_mode(false),
+                        data: id.describe().into_bytes(),
+                        path,
+                    }
+                }
+                MaterializedTreeValue::GitSubmodule(id) => FileCommand::Modify {
+                    mode: "160000",
+                    data_ref: id.hex(),
+                    path,
+                },
+                MaterializedTreeValue::Tree(_) => panic!("diff should not contain trees"),
+            };
+            file_commands.push(command);
+        }
+        Ok((file_commands, has_conflict))
+    }
+}
+
+enum FileCommand {
+    Delete(RepoPathBuf),
+    Modify {
+        mode: &'static str,
+        data_ref: String,
+        path: RepoPathBuf,
+    },
+    ModifyInline {
+        mode: &'static str,
+        data: Vec<u8>,
+        path: RepoPathBuf,
+    },
+}
+
+impl FileCommand {
+    fn write(&self, output: &mut dyn Write) -> io::Result<()> {
+        match self {
+            FileCommand::Delete(path) => writeln!(output, "D {}", quote_path(path)),
+            FileCommand::Modify {
+                mode,
+                data_ref,
+                path,
+            } => writeln!(output, "M {mode} {data_ref} {}", quote_path(path)),
+            FileCommand::ModifyInline { mode, data, path } => {
+                writeln!(output, "M {mode} inline {}", quote_path(path))?;
+                write_data(output, data)
+            }
+        }
+    }
+}
+
+fn file_mode(executable: bool) -> &'static str {
+    if executable {
+        "100755"
+    } else {
+        "100644"
+    }
+}
+
+fn write_identity(output: &mut dyn Write, kind: &str, signature: &Signature) -> io::Result<()> {
+    write!(output, "{kind}")?;
+    if !signature.name.is_empty() {
+        write!(output, " {}", signature.name)?;
+    }
+    let seconds = signature.timestamp.timestamp.0.div_euclid(1000);
+    let offset = signature.timestamp.tz_offset;
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    writeln!(
+        output,
+        " <{}> {seconds} {sign}{:02}{:02}",
+        signature.email,
+        offset / 60,
+        offset % 60
+    )
+}
+
+fn write_data(output: &mut dyn Write, data: &[u8]) -> io::Result<()> {
+    writeln!(output, "data {}", data.len())?;
+    output.write_all(data)?;
+    writeln!(output)
+}
+
+/// Quotes the path as a C-style string if it can't be written as is.
+fn quote_path(path: &RepoPath) -> String {
+    let path = path.as_internal_file_string();
+    if !path.starts_with('"') && !path.contains(['\n', '\\']) {
+        return path.to_owned();
+    }
+    let mut quoted = String::with_capacity(path.len() + 2);
+    quoted.push('"');
+    for c in path.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Reads a fast-import stream from `input` and writes its commits to the repo.
+///
+/// Commits without a parent become children of the root commit. Refs named
+/// `refs/heads/*` and `refs/tags/*` are imported as local bookmarks and tags.
+/// Other refs aren't imported, but all imported commits are made visible.
+pub fn read_fast_import(
+    mut_repo: &mut MutableRepo,
+    settings: &UserSettings,
+    input: &mut dyn BufRead,
+) -> Result<FastImportStats, FastImportError> {
+    let mut importer = FastImporter {
+        reader: StreamReader {
+            input,
+            line_number: 0,
+            peeked: None,
+        },
+        mut_repo,
+        settings,
+        marks: HashMap::new(),
+        refs: BTreeMap::new(),
+        num_commits: 0,
+    };
+    importer.run()?;
+
+    let FastImporter {
+        mut_repo,
+        refs,
+        num_commits,
+        ..
+    } = importer;
+    let mut stats = FastImportStats {
+        num_commits,
+        ..FastImportStats::default()
+    };
+    for (ref_name, id) in refs {
+        if let Some(name) = ref_name.strip_prefix("refs/heads/") {
+            mut_repo.set_local_bookmark_target(name, RefTarget::normal(id));
+            stats.bookmarks.push(name.to_owned());
+        } else if let Some(name) = ref_name.strip_prefix("refs/tags/") {
+            mut_repo.set_tag_target(name, RefTarget::normal(id));
+            stats.tags.push(name.to_owned());
+        }
+    }
+    Ok(stats)
+}
+
+#[derive(Clone, Debug)]
+enum Mark {
+    Blob(FileId),
+    Commit(CommitId),
+}
+
+struct StreamReader<'a> {
+    input: &'a mut dyn BufRead,
+    line_number: usize,
+    peeked: Option<String>,
+}
+
+impl StreamReader<'_> {
+    fn syntax_error(&self, message: impl Into<String>) -> FastImportError {
+        FastImportError::Syntax {
+            line: self.line_number,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the next line without the trailing LF, skipping comments.
+    fn next_line(&mut self) -> Result<Option<String>, FastImportError> {
+        if let Some(line) = self.peeked.take() {
+            return Ok(Some(line));
+        }
+        loop {
+            let mut buf = vec![];
+            if self.input.read_until(b'\n', &mut buf)? == 0 {
+                return Ok(None);
+            }
+            self.line_number += 1;
+            if buf.last() == Some(&b'\n') {
+                buf.pop();
+            }
+            if buf.starts_with(b"#") {
+                continue;
+            }
+            let line =
+                String::from_utf8(buf).map_err(|_| self.syntax_error("Line is not valid UTF-8"))?;
+            return Ok(Some(line));
+        }
+    }
+
+    /// Returns the rest of the next line if it starts with `prefix`.
+    /// Otherwise, the line is left to be read by the next call.
+    fn next_line_with_prefix(&mut self, prefix: &str) -> Result<Option<String>, FastImportError> {
+        let Some(line) = self.next_line()? else {
+            return Ok(None);
+        };
+        if let Some(rest) = line.strip_prefix(prefix) {
+            Ok(Some(rest.to_owned()))
+        } else {
+            self.peeked = Some(line);
+            Ok(None)
+        }
+    }
+
+    /// Reads the contents of a `data` command.
+    fn read_data(&mut self) -> Result<Vec<u8>, FastImportError> {
+        let Some(header) = self.next_line_with_prefix("data ")? else {
+            return Err(self.syntax_error("Expected `data` command"));
+        };
+        if let Some(delimiter) = header.strip_prefix("<<") {
+            let mut data = vec![];
+            loop {
+                let mut buf = vec![];
+                if self.input.read_until(b'\n', &mut buf)? == 0 {
+                    return Err(self.syntax_error("Unterminated `data` command"));
+                }
+                self.line_number += 1;
+                if buf.strip_suffix(b"\n").unwrap_or(&buf) == delimiter.as_bytes() {
+                    break;
+                }
+                data.extend(buf);
+            }
+            Ok(data)
+        } else {
+            let len: u64 = header
+                .parse()
+                .map_err(|_| self.syntax_error(format!("Invalid data length: {header}")))?;
+            let mut data = vec![];
+            io::Read::take(&mut *self.input, len).read_to_end(&mut data)?;
+            if (data.len() as u64) < len {
+                return Err(self.syntax_error("Unexpected end of stream in `data` command"));
+            }
+            self.line_number += data.iter().filter(|&&b| b == b'\n').count();
+            // The LF after the data is optional.
+            if self.input.fill_buf()?.first() == Some(&b'\n') {
+                self.input.consume(1);
+                self.line_number += 1;
+            }
+            Ok(data)
+        }
+    }
+}
+
+struct FastImporter<'a, 'r> {
+    reader: StreamReader<'a>,
+    mut_repo: &'r mut MutableRepo,
+    settings: &'a UserSettings,
+    marks: HashMap<u64, Mark>,
+    refs: BTreeMap<String, CommitId>,
+    num_commits: usize,
+}
+
+impl FastImporter<'_, '_> {
+    fn run(&mut self) -> Result<(), FastImportError> {
+        while let Some(line) = self.reader.next_line()? {
+            let (command, arg) = line.split_once(' ').unwrap_or((&line, ""));
+            match command {
+                "" => {}
+                "blob" => self.read_blob()?,
+                "commit" => self.read_commit(arg)?,
+                "reset" => self.read_reset(arg)?,
+                "tag" => self.read_tag(arg)?,
+                "alias" => self.read_alias()?,
+                "done" => break,
+                "progress" | "checkpoint" | "option" => {}
+                "feature" => match arg {
+                    "done" | "date-format=raw" | "notes" => {}
+                    _ => {
+                        return Err(self
+                            .reader
+                            .syntax_error(format!("Unsupported feature: {arg}")))
+                    }
+                },
+                _ => {
+                    return Err(self
+                        .reader
+                        .syntax_error(format!("Unsupported command: {command}")))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_mark(&mut self) -> Result<Option<u64>, FastImportError> {
+        let Some(mark) = self.reader.next_line_with_prefix("mark ")? else {
+            return Ok(None);
+        };
+        let id = parse_mark(&mark)
+            .ok_or_else(|| self.reader.syntax_error(format!("Invalid mark: {mark}")))?;
+        Ok(Some(id))
+    }
+
+    fn skip_original_oid(&mut self) -> Result<(), FastImportError> {
+        self.reader.next_line_with_prefix("original-oid ")?;
+        Ok(())
+    }
+
+    fn read_blob(&mut self) -> Result<(), FastImportError> {
+        let mark = self.read_mark()?;
+        self.skip_original_oid()?;
+        let data = self.reader.read_data()?;
+        let id = self.write_file(&data)?;
+        if let Some(mark) = mark {
+            self.marks.insert(mark, Mark::Blob(id));
+        }
+        Ok(())
+    }
+
+    fn read_alias(&mut self) -> Result<(), FastImportError> {
+        let Some(mark) = self.read_mark()? else {
+            return Err(self.reader.syntax_error("Expected `mark` in `alias`"));
+        };
+        let Some(target) = self.reader.next_line_with_prefix("to ")? else {
+            return Err(self.reader.syntax_error("Expected `to` in `alias`"));
+        };
+        let id = self.resolve_commitish(&target)?;
+        self.marks.insert(mark, Mark::Commit(id));
+        Ok(())
+    }
+
+    fn read_reset(&mut self, ref_name: &str) -> Result<(), FastImportError> {
+        match self.reader.next_line_with_prefix("from ")? {
+            Some(from) => {
+                let id = self.resolve_commitish(&from)?;
+                self.refs.insert(ref_name.to_owned(), id);
+            }
+            None => {
+                self.refs.remove(ref_name);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_tag(&mut self, name: &str) -> Result<(), FastImportError> {
+        // Annotated tags are imported as lightweight tags.
+        self.read_mark()?;
+        let Some(from) = self.reader.next_line_with_prefix("from ")? else {
+            return Err(self.reader.syntax_error("Expected `from` in `tag`"));
+        };
+        let id = self.resolve_commitish(&from)?;
+        self.skip_original_oid()?;
+        self.reader.next_line_with_prefix("tagger ")?;
+        self.reader.read_data()?;
+        self.refs.insert(format!("refs/tags/{name}"), id);
+        Ok(())
+    }
+
+    fn read_commit(&mut self, ref_name: &str) -> Result<(), FastImportError> {
+        let mark = self.read_mark()?;
+        self.skip_original_oid()?;
+        let author = match self.reader.next_line_with_prefix("author ")? {
+            Some(author) => Some(self.parse_identity(&author)?),
+            None => None,
+        };
+        let Some(committer) = self.reader.next_line_with_prefix("committer ")? else {
+            return Err(self.reader.syntax_error("Expected `committer` in `commit`"));
+        };
+        let committer = self.parse_identity(&committer)?;
+        if self.reader.next_line_with_prefix("gpgsig ")?.is_some() {
+            self.reader.read_data()?;
+        }
+        self.reader.next_line_with_prefix("encoding ")?;
+        let description = String::from_utf8_lossy(&self.reader.read_data()?).into_owned();
+
+        let mut parent_ids = vec![];
+        if let Some(from) = self.reader.next_line_with_prefix("from ")? {
+            parent_ids.push(self.resolve_commitish(&from)?);
+        } else if let Some(id) = self.refs.get(ref_name) {
+            parent_ids.push(id.clone());
+        }
+        while let Some(merge) = self.reader.next_line_with_prefix("merge ")? {
+            parent_ids.push(self.resolve_commitish(&merge)?);
+        }
+
+        let store = self.mut_repo.store().clone();
+        let base_tree = match parent_ids.first() {
+            Some(id) => store.get_commit(id)?.tree()?,
+            None => store.get_root_tree(&store.empty_merged_tree_id())?,
+        };
+        let mut tree_state = TreeState {
+            base: base_tree,
+            changes: BTreeMap::new(),
+        };
+        self.read_file_commands(&mut tree_state)?;
+        let tree_id = tree_state.write()?;
+
+        if parent_ids.is_empty() {
+            parent_ids.push(store.root_commit_id().clone());
+        }
+        let commit = self
+            .mut_repo
+            .new_commit(self.settings, parent_ids, tree_id)
+            .set_description(description)
+            .set_author(author.unwrap_or_else(|| committer.clone()))
+            .set_committer(committer)
+            .write()?;
+        if let Some(mark) = mark {
+            self.marks.insert(mark, Mark::Commit(commit.id().clone()));
+        }
+        self.refs.insert(ref_name.to_owned(), commit.id().clone());
+        self.num_commits += 1;
+        Ok(())
+    }
+
+    fn read_file_commands(&mut self, tree_state: &mut TreeState) -> Result<(), FastImportError> {
+        while let Some(line) = self.reader.next_line()? {
+            if line == "deleteall" {
+                let store = self.mut_repo.store();
+                tree_state.base = store.get_root_tree(&store.empty_merged_tree_id())?;
+                tree_state.changes.clear();
+            } else if let Some(arg) = line.strip_prefix("M ") {
+                let Some((mode, rest)) = arg.split_once(' ') else {
+                    return Err(self.reader.syntax_error("Invalid `M` command"));
+                };
+                let Some((data_ref, path)) = rest.split_once(' ') else {
+                    return Err(self.reader.syntax_error("Invalid `M` command"));
+                };
+                let path = self.parse_path(path)?;
+                let value = self.read_file_value(mode, data_ref)?;
+                tree_state.set(path, Merge::normal(value))?;
+            } else if let Some(arg) = line.strip_prefix("D ") {
+                let path = self.parse_path(arg)?;
+                tree_state.remove(&path)?;
+            } else if let Some(arg) = line.strip_prefix("C ") {
+                let (source, target) = self.parse_path_pair(arg)?;
+                tree_state.copy(&source, &target)?;
+            } else if let Some(arg) = line.strip_prefix("R ") {
+                let (source, target) = self.parse_path_pair(arg)?;
+                tree_state.copy(&source, &target)?;
+                tree_state.remove(&source)?;
+            } else if let Some(arg) = line.strip_prefix("N ") {
+                // Notes have no equivalent in jj.
+                if arg.starts_with("inline ") {
+                    self.reader.read_data()?;
+                }
+            } else {
+                self.reader.peeked = Some(line);
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_file_value(
+        &mut self,
+        mode: &str,
+        data_ref: &str,
+    ) -> Result<TreeValue, FastImportError> {
+        if mode == "160000" {
+            let id = CommitId::try_from_hex(data_ref).map_err(|_| {
+                self.reader
+                    .syntax_error(format!("Invalid submodule commit id: {data_ref}"))
+            })?;
+            return Ok(TreeValue::GitSubmodule(id));
+        }
+        let id = if data_ref == "inline" {
+            let data = self.reader.read_data()?;
+            self.write_file(&data)?
+        } else if let Some(mark) = parse_mark(data_ref) {
+            match self.marks.get(&mark) {
+                Some(Mark::Blob(id)) => id.clone(),
+                _ => {
+                    return Err(self
+                        .reader
+                        .syntax_error(format!("Mark {data_ref} is not a blob")))
+                }
+            }
+        } else {
+            FileId::try_from_hex(data_ref).map_err(|_| {
+                self.reader
+                    .syntax_error(format!("Invalid data reference: {data_ref}"))
+            })?
+        };
+        match mode {
+            "100644" | "644" => Ok(TreeValue::File {
+                id,
+                executable: false,
+            }),
+            "100755" | "755" => Ok(TreeValue::File {
+                id,
+                executable: true,
+            }),
+            "120000" => {
+                let store = self.mut_repo.store();
+                let mut target = String::new();
+                store
+                    .read_file(RepoPath::root(), &id)?
+                    .read_to_string(&mut target)?;
+                let id = store.write_symlink(RepoPath::root(), &target).block_on()?;
+                Ok(TreeValue::Symlink(id))
+            }
+            _ => Err(self
+                .reader
+                .syntax_error(format!("Unsupported file mode: {mode}"))),
+        }
+    }
+
+    fn write_file(&self, data: &[u8]) -> Result<FileId, FastImportError> {
+        // Blobs aren't associated with a path in the stream.
+        let id = self
+            .mut_repo
+            .store()
+            .write_file(RepoPath::root(), &mut &data[..])
+            .block_on()?;
+        Ok(id)
+    }
+
+    fn resolve_commitish(&self, commitish: &str) -> Result<CommitId, FastImportError> {
+        if let Some(mark) = parse_mark(commitish) {
+            return match self.marks.get(&mark) {
+                Some(Mark::Commit(id)) => Ok(id.clone()),
+                _ => Err(self
+                    .reader
+                    .syntax_error(format!("Mark {commitish} is not a commit"))),
+            };
+        }
+        let ref_name = commitish.strip_suffix("^0").unwrap_or(commitish);
+        if let Some(id) = self.refs.get(ref_name) {
+            return Ok(id.clone());
+        }
+        match CommitId::try_from_hex(commitish) {
+            Ok(id) if self.mut_repo.index().has_id(&id) => Ok(id),
+            _ => Err(self
+                .reader
+                .syntax_error(format!("Unknown commit: {commitish}"))),
+        }
+    }
+
+    fn parse_identity(&self, identity: &str) -> Result<Signature, FastImportError> {
+        let invalid = || {
+            self.reader
+                .syntax_error(format!("Invalid identity: {identity}"))
+        };
+        let (name, rest) = identity.split_once('<').ok_or_else(invalid)?;
+        let (email, when) = rest.split_once('>').ok_or_else(invalid)?;
+        let (seconds, offset) = when.trim().split_once(' ').ok_or_else(invalid)?;
+        let seconds: i64 = seconds.parse().map_err(|_| invalid())?;
+        let offset = parse_tz_offset(offset).ok_or_else(invalid)?;
+        Ok(Signature {
+            name: name.trim_end().to_owned(),
+            email: email.to_owned(),
+            timestamp: Timestamp {
+                timestamp: MillisSinceEpoch(seconds * 1000),
+                tz_offset: offset,
+            },
+        })
+    }
+
+    fn parse_path(&self, path: &str) -> Result<RepoPathBuf, FastImportError> {
+        let path = if path.starts_with('"') {
+            let (path, rest) = unquote_path(path).ok_or_else(|| {
+                self.reader
+                    .syntax_error(format!("Invalid quoted path: {path}"))
+            })?;
+            if !rest.is_empty() {
+                return Err(self
+                    .reader
+                    .syntax_error(format!("Unexpected text after path: {rest}")));
+            }
+            path
+        } else {
+            path.to_owned()
+        };
+        let is_valid = !path.is_empty()
+            && path
+                .split('/')
+                .all(|component| !matches!(component, "" | "." | ".."));
+        if !is_valid {
+            return Err(self.reader.syntax_error(format!("Invalid path: {path}")));
+        }
+        Ok(RepoPathBuf::from_internal_string(path))
+    }
+
+    /// Parses the source and target paths of `C` and `R` commands. An unquoted
+    /// source path ends at the first space.
+    fn parse_path_pair(&self, arg: &str) -> Result<(RepoPathBuf, RepoPathBuf), FastImportError> {
+        let (source, target) = if arg.starts_with('"') {
+            let (source, rest) = unquote_path(arg).ok_or_else(|| {
+                self.reader
+                    .syntax_error(format!("Invalid quoted path: {arg}"))
+            })?;
+            let target = rest.strip_prefix(' ').ok_or_else(|| {
+                self.reader
+                    .syntax_error(format!("Expected target path: {arg}"))
+            })?;
+            (self.parse_path(&source)?, self.parse_path(target)?)
+        } else {
+            let (source, target) = arg.split_once(' ').ok_or_else(|| {
+                self.reader
+                    .syntax_error(format!("Expected target path: {arg}"))
+            })?;
+            (self.parse_path(source)?, self.parse_path(target)?)
+        };
+        Ok((source, target))
+    }
+}
+
+fn parse_mark(mark: &str) -> Option<u64> {
+    mark.strip_prefix(':')?.parse().ok()
+}
+
+/// Parses a time zone offset like `+0900` into minutes.
+fn parse_tz_offset(offset: &str) -> Option<i32> {
+    let (sign, digits) = if let Some(digits) = offset.strip_prefix('+') {
+        (1, digits)
+    } else if let Some(digits) = offset.strip_prefix('-') {
+        (-1, digits)
+    } else {
+        return None;
+    };
+    if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 60 + minutes))
+}
+
+/// Parses a C-style quoted string at the start of `quoted`. Returns the
+/// unquoted string and the text after the closing quote.
+fn unquote_path(quoted: &str) -> Option<(String, &str)> {
+    let mut bytes = vec![];
+    let mut chars = quoted.strip_prefix('"')?.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                let path = String::from_utf8(bytes).ok()?;
+                return Some((path, &quoted[i + 2..]));
+            }
+            '\\' => {
+                let (_, escaped) = chars.next()?;
+                let byte = match escaped {
+                    'a' => 0x07,
+                    'b' => 0x08,
+                    'f' => 0x0c,
+                    'n' => b'\n',
+                    'r' => b'\r',
+                    't' => b'\t',
+                    'v' => 0x0b,
+                    '"' => b'"',
+                    '\\' => b'\\',
+                    '0'..='3' => {
+                        // Octal escape of a byte, as written by Git for
+                        // non-ASCII paths.
+                        let mut value = escaped.to_digit(8)?;
+                        for _ in 0..2 {
+                            let (_, digit) = chars.next()?;
+                            value = value * 8 + digit.to_digit(8)?;
+                        }
+                        value as u8
+                    }
+                    _ => return None,
+                };
+                bytes.push(byte);
+            }
+            c => {
+                let mut buf = [0; 4];
+                bytes.extend(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    None
+}
+
+/// Tree of a commit being imported, as changes on top of a base tree.
+struct TreeState {
+    base: MergedTree,
+    changes: BTreeMap<RepoPathBuf, MergedTreeValue>,
+}
+
+impl TreeState {
+    fn value(&self, path: &RepoPath) -> Result<MergedTreeValue, BackendError> {
+        match self.changes.get(path) {
+            Some(value) => Ok(value.clone()),
+            None => self.base.path_value(path),
+        }
+    }
+
+    /// Returns the files at or under the path.
+    fn entries(
+        &self,
+        path: &RepoPath,
+    ) -> Result<Vec<(RepoPathBuf, MergedTreeValue)>, BackendError> {
+        let mut entries = BTreeMap::new();
+        for (entry_path, value) in self.base.entries_matching(&PrefixMatcher::new([path])) {
+            entries.insert(entry_path, value?);
+        }
+        for (entry_path, value) in &self.changes {
+            if entry_path.starts_with(path) {
+                entries.insert(entry_path.clone(), value.clone());
+            }
+        }
+        Ok(entries
+            .into_iter()
+            .filter(|(_, value)| value.is_present())
+            .collect())
+    }
+
+    fn set(&mut self, path: RepoPathBuf, value: MergedTreeValue) -> Result<(), BackendError> {
+        // A file replaces a directory at the same path, and the other way
+        // around.
+        self.remove(&path)?;
+        let mut ancestor = path.parent();
+        while let Some(dir) = ancestor {
+            let value = self.value(dir)?;
+            if !dir.is_root() && value.is_present() && !value.is_tree() {
+                self.changes.insert(dir.to_owned(), Merge::absent());
+            }
+            ancestor = dir.parent();
+        }
+        self.changes.insert(path, value);
+        Ok(())
+    }
+
+    fn remove(&mut self, path: &RepoPath) -> Result<(), BackendError> {
+        for (entry_path, _) in self.entries(path)? {
+            self.changes.insert(entry_path, Merge::absent());
+        }
+        Ok(())
+    }
+
+    fn copy(&mut self, source: &RepoPath, target: &RepoPath) -> Result<(), BackendError> {
+        let entries = self.entries(source)?;
+        self.remove(target)?;
+        for (entry_path, value) in entries {
+            let relative_path = entry_path.strip_prefix(source).unwrap();
+            let mut target_path = target.to_owned();
+            for component in relative_path.components() {
+                target_path = target_path.join(component);
+            }
+            self.set(target_path, value)?;
+        }
+        Ok(())
+    }
+
+    fn write(self) -> Result<MergedTreeId, BackendError> {
+        let store = self.base.store().clone();
+        let mut builder = MergedTreeBuilder::new(self.base.id());
+        for (path, value) in self.changes {
+            builder.set_or_remove(path, value);
+        }
+        builder.write_tree(&store)
+    }
+}
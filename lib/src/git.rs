@@ -15,13 +15,20 @@
 #![allow(missing_docs)]
 
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::default::Default;
 use std::fmt;
+use std::fs;
+use std::io;
 use std::io::Read;
+use std::num::NonZeroU32;
+use std::path::Path;
 use std::path::PathBuf;
+use std::process::Command;
+use std::rc::Rc;
 use std::str;
 
 use git2::Oid;
@@ -31,20 +38,42 @@ use thiserror::Error;
 
 use crate::backend::BackendError;
 use crate::backend::CommitId;
+use crate::backend::FileId;
+use crate::backend::Signature;
+use crate::backend::SymlinkId;
+use crate::backend::TreeValue;
 use crate::commit::Commit;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::git_backend::signature_to_git;
 use crate::git_backend::GitBackend;
+use crate::git_backend::EMPTY_STRING_PLACEHOLDER;
 use crate::index::Index;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::MergedTreeBuilder;
 use crate::object_id::ObjectId;
+use crate::op_store::OpStore;
+use crate::op_store::OpStoreError;
+use crate::op_store::OperationId;
 use crate::op_store::RefTarget;
 use crate::op_store::RefTargetOptionExt;
 use crate::op_store::RemoteRef;
 use crate::op_store::RemoteRefState;
+use crate::op_store::ViewId;
+use crate::op_store::WorkspaceId;
+use crate::op_walk;
+use crate::operation::Operation;
 use crate::refs;
 use crate::refs::BookmarkPushUpdate;
 use crate::repo::MutableRepo;
 use crate::repo::Repo;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
 use crate::revset::RevsetExpression;
 use crate::settings::GitSettings;
+use crate::settings::UserSettings;
+use crate::simple_op_store;
 use crate::store::Store;
 use crate::str_util::StringPattern;
 use crate::view::View;
@@ -53,6 +82,9 @@ use crate::view::View;
 pub const REMOTE_NAME_FOR_LOCAL_GIT_REPO: &str = "git";
 /// Ref name used as a placeholder to unset HEAD without a commit.
 const UNBORN_ROOT_REF_NAME: &str = "refs/jj/root";
+/// Prefix of the refs recording the last known HEAD of linked worktrees. The
+/// view only records HEAD of the main worktree.
+const WORKTREE_HEAD_REF_PREFIX: &str = "refs/jj/worktree-heads/";
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Debug)]
 pub enum RefName {
@@ -635,6 +667,35 @@ pub enum FailedRefExportReason {
     FailedToSet(#[source] Box<gix::reference::edit::Error>),
 }
 
+/// Creates an annotated tag pointing to `target` in the underlying Git repo,
+/// and records it as a tag in the view.
+///
+/// The tag object is written to the Git repo immediately. Lightweight tags
+/// don't need this, since they're written by `export_refs()` like branches.
+pub fn create_annotated_tag(
+    mut_repo: &mut MutableRepo,
+    name: &str,
+    target: &CommitId,
+    message: &str,
+    tagger: &Signature,
+) -> Result<(), GitExportError> {
+    let git_repo = get_git_repo(mut_repo.store()).ok_or(GitExportError::UnexpectedBackend)?;
+    git_repo
+        .tag(
+            name,
+            gix::ObjectId::try_from(target.as_bytes()).unwrap(),
+            gix::object::Kind::Commit,
+            Some(signature_to_git(tagger)),
+            message,
+            gix::refs::transaction::PreviousValue::MustNotExist,
+        )
+        .map_err(GitExportError::from_git)?;
+    let new_target = RefTarget::normal(target.clone());
+    mut_repo.set_git_ref_target(&format!("refs/tags/{name}"), new_target.clone());
+    mut_repo.set_tag_target(name, new_target);
+    Ok(())
+}
+
 #[derive(Debug)]
 struct RefsToExport {
     branches_to_update: BTreeMap<RefName, (Option<gix::ObjectId>, gix::ObjectId)>,
@@ -650,9 +711,9 @@ struct RefsToExport {
 /// repo compared to our last remembered view of the Git repo). These will be
 /// marked conflicted by the next `jj git import`.
 ///
-/// We do not export tags and other refs at the moment, since these aren't
-/// supposed to be modified by JJ. For them, the Git state is considered
-/// authoritative.
+/// Tags are exported like local branches. Other refs aren't exported, since
+/// they aren't supposed to be modified by JJ. For them, the Git state is
+/// considered authoritative.
 pub fn export_refs(mut_repo: &mut MutableRepo) -> Result<Vec<FailedRefExport>, GitExportError> {
     export_some_refs(mut_repo, |_| true)
 }
@@ -779,22 +840,28 @@ fn diff_refs_to_export(
 ) -> RefsToExport {
     // Local targets will be copied to the "git" remote if successfully exported. So
     // the local branches are considered to be the new "git" remote branches.
-    let mut all_branch_targets: HashMap<RefName, (&RefTarget, &RefTarget)> = itertools::chain(
-        view.local_bookmarks()
-            .map(|(branch, target)| (RefName::LocalBranch(branch.to_owned()), target)),
-        view.all_remote_bookmarks()
-            .filter(|&((_, remote), _)| remote != REMOTE_NAME_FOR_LOCAL_GIT_REPO)
-            .map(|((branch, remote), remote_ref)| {
-                let ref_name = RefName::RemoteBranch {
-                    branch: branch.to_owned(),
-                    remote: remote.to_owned(),
-                };
-                (ref_name, &remote_ref.target)
-            }),
-    )
-    .map(|(ref_name, new_target)| (ref_name, (RefTarget::absent_ref(), new_target)))
-    .filter(|(ref_name, _)| git_ref_filter(ref_name))
-    .collect();
+    let mut all_branch_targets: HashMap<RefName, (&RefTarget, &RefTarget)> = view
+        .local_bookmarks()
+        .map(|(branch, target)| (RefName::LocalBranch(branch.to_owned()), target))
+        .chain(
+            view.tags()
+                .iter()
+                .map(|(name, target)| (RefName::Tag(name.to_owned()), target)),
+        )
+        .chain(
+            view.all_remote_bookmarks()
+                .filter(|&((_, remote), _)| remote != REMOTE_NAME_FOR_LOCAL_GIT_REPO)
+                .map(|((branch, remote), remote_ref)| {
+                    let ref_name = RefName::RemoteBranch {
+                        branch: branch.to_owned(),
+                        remote: remote.to_owned(),
+                    };
+                    (ref_name, &remote_ref.target)
+                }),
+        )
+        .map(|(ref_name, new_target)| (ref_name, (RefTarget::absent_ref(), new_target)))
+        .filter(|(ref_name, _)| git_ref_filter(ref_name))
+        .collect();
     let known_git_refs = view
         .git_refs()
         .iter()
@@ -808,7 +875,7 @@ fn diff_refs_to_export(
             // 2. `jj op undo`/`restore` in colocated repo
             matches!(
                 ref_name,
-                RefName::LocalBranch(..) | RefName::RemoteBranch { .. }
+                RefName::LocalBranch(..) | RefName::RemoteBranch { .. } | RefName::Tag(..)
             )
         })
         .filter(|(ref_name, _)| git_ref_filter(ref_name));
@@ -868,7 +935,16 @@ fn delete_git_ref(
     old_oid: &gix::oid,
 ) -> Result<(), FailedRefExportReason> {
     if let Ok(git_ref) = git_repo.find_reference(git_ref_name) {
-        if git_ref.inner.target.try_id() == Some(old_oid) {
+        // Annotated tags point to tag objects, so they're compared by the
+        // commit they point to.
+        let is_annotated_tag_of_old_oid = || {
+            git_ref_name.starts_with("refs/tags/")
+                && git_ref
+                    .clone()
+                    .into_fully_peeled_id()
+                    .is_ok_and(|id| &*id == old_oid)
+        };
+        if git_ref.inner.target.try_id() == Some(old_oid) || is_annotated_tag_of_old_oid() {
             // The branch has not been updated by git, so go ahead and delete it
             git_ref
                 .delete()
@@ -1051,6 +1127,159 @@ pub fn reset_head(
     Ok(())
 }
 
+/// Returns the Git directory shared by all worktrees of `git_repo`.
+///
+/// For a linked worktree, this is the directory pointed to by its `commondir`
+/// file. Otherwise, it's the Git directory of `git_repo` itself.
+pub fn git_common_dir(git_repo: &git2::Repository) -> PathBuf {
+    let git_dir = git_repo.path();
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common_dir) => git_dir.join(common_dir.trim_end_matches(['\r', '\n'])),
+        Err(_) => git_dir.to_owned(),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GitWorktreeError {
+    #[error("Git worktree named '{0}' already exists")]
+    WorktreeExists(String),
+    #[error("Invalid Git worktree name '{0}'")]
+    InvalidName(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    InternalGitError(#[from] git2::Error),
+}
+
+/// Creates a linked worktree of `git_repo` in the existing directory
+/// `workdir`.
+///
+/// Unlike `git worktree add`, this doesn't create a branch or check out any
+/// files. HEAD of the new worktree is unborn until it's reset to the parent of
+/// the working-copy commit by `reset_worktree_head()`.
+pub fn add_worktree(
+    git_repo: &git2::Repository,
+    name: &str,
+    workdir: &Path,
+) -> Result<git2::Repository, GitWorktreeError> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(GitWorktreeError::InvalidName(name.to_owned()));
+    }
+    let worktree_git_dir = git_common_dir(git_repo).join("worktrees").join(name);
+    if worktree_git_dir.exists() {
+        return Err(GitWorktreeError::WorktreeExists(name.to_owned()));
+    }
+    let workdir = workdir.canonicalize()?;
+    fs::create_dir_all(&worktree_git_dir)?;
+    let worktree_git_dir = worktree_git_dir.canonicalize()?;
+    fs::write(
+        worktree_git_dir.join("HEAD"),
+        format!("ref: {UNBORN_ROOT_REF_NAME}\n"),
+    )?;
+    fs::write(worktree_git_dir.join("commondir"), "../..\n")?;
+    fs::write(
+        worktree_git_dir.join("gitdir"),
+        format!("{}\n", workdir.join(".git").display()),
+    )?;
+    fs::write(
+        workdir.join(".git"),
+        format!("gitdir: {}\n", worktree_git_dir.display()),
+    )?;
+    Ok(git2::Repository::open(&workdir)?)
+}
+
+/// Imports HEAD of the linked worktree used by the given workspace.
+///
+/// Returns the new HEAD commit if HEAD has been moved since it was last
+/// imported or reset by jj. The commit is added to the repo, but the
+/// working-copy commit isn't updated.
+pub fn import_worktree_head(
+    mut_repo: &mut MutableRepo,
+    worktree_repo: &git2::Repository,
+    workspace_id: &WorkspaceId,
+) -> Result<Option<CommitId>, GitImportError> {
+    let store = mut_repo.store().clone();
+    let git_backend = get_git_backend(&store).ok_or(GitImportError::UnexpectedBackend)?;
+    let head_ref_name = worktree_head_ref_name(workspace_id);
+    let old_head_oid = worktree_repo.refname_to_id(&head_ref_name).ok();
+    let new_head_oid = worktree_repo.head().ok().and_then(|head| head.target());
+    if old_head_oid == new_head_oid {
+        return Ok(None);
+    }
+    record_worktree_head(worktree_repo, &head_ref_name, new_head_oid)
+        .map_err(GitImportError::from_git)?;
+    let Some(new_head_oid) = new_head_oid else {
+        return Ok(None);
+    };
+
+    let head_id = CommitId::from_bytes(new_head_oid.as_bytes());
+    if !mut_repo.index().has_id(&head_id) {
+        git_backend.import_head_commits([&head_id]).map_err(|err| {
+            GitImportError::MissingHeadTarget {
+                id: head_id.clone(),
+                err,
+            }
+        })?;
+    }
+    store
+        .get_commit(&head_id)
+        .and_then(|commit| mut_repo.add_head(&commit))
+        .map_err(GitImportError::InternalBackend)?;
+    Ok(Some(head_id))
+}
+
+/// Points HEAD of the linked worktree used by the given workspace to the
+/// parent of the working-copy commit, and resets the worktree's index.
+pub fn reset_worktree_head(
+    worktree_repo: &git2::Repository,
+    workspace_id: &WorkspaceId,
+    wc_commit: &Commit,
+) -> Result<(), git2::Error> {
+    let head_ref_name = worktree_head_ref_name(workspace_id);
+    let first_parent_id = &wc_commit.parent_ids()[0];
+    let new_head_oid = (first_parent_id != wc_commit.store().root_commit_id())
+        .then(|| Oid::from_bytes(first_parent_id.as_bytes()).unwrap());
+    let old_head_oid = worktree_repo.refname_to_id(&head_ref_name).ok();
+    if old_head_oid == new_head_oid {
+        return Ok(());
+    }
+    if let Some(new_head_oid) = new_head_oid {
+        let new_head_commit = worktree_repo.find_commit(new_head_oid)?;
+        worktree_repo.set_head_detached(new_head_oid)?;
+        worktree_repo.reset(new_head_commit.as_object(), git2::ResetType::Mixed, None)?;
+    } else {
+        // HEAD is per worktree, so the placeholder ref can be shared with the
+        // main worktree. See reset_head().
+        worktree_repo.reference_symbolic("HEAD", UNBORN_ROOT_REF_NAME, true, "unset HEAD by jj")?;
+        let mut index = worktree_repo.index()?;
+        index.clear()?;
+        index.write()?;
+        worktree_repo.cleanup_state()?;
+    }
+    record_worktree_head(worktree_repo, &head_ref_name, new_head_oid)
+}
+
+fn worktree_head_ref_name(workspace_id: &WorkspaceId) -> String {
+    format!("{WORKTREE_HEAD_REF_PREFIX}{}", workspace_id.as_str())
+}
+
+fn record_worktree_head(
+    git_repo: &git2::Repository,
+    ref_name: &str,
+    head_oid: Option<Oid>,
+) -> Result<(), git2::Error> {
+    if let Some(head_oid) = head_oid {
+        git_repo.reference(ref_name, head_oid, true, "record worktree HEAD by jj")?;
+    } else {
+        match git_repo.find_reference(ref_name) {
+            Ok(mut git_ref) => git_ref.delete()?,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Error)]
 pub enum GitRemoteManagementError {
     #[error("No git remote named '{0}'")]
@@ -1186,6 +1415,240 @@ pub fn set_remote_url(
     Ok(())
 }
 
+/// Sets the URL the remote is pushed to, which is the fetch URL by default.
+///
+/// If `add` is true, the URL is added to the existing push URLs, and
+/// `git push` pushes to all of them.
+pub fn set_remote_push_url(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    new_push_url: &str,
+    add: bool,
+) -> Result<(), GitRemoteManagementError> {
+    if remote_name == REMOTE_NAME_FOR_LOCAL_GIT_REPO {
+        return Err(GitRemoteManagementError::RemoteReservedForLocalGitRepo);
+    }
+    git_repo.find_remote(remote_name).map_err(|err| {
+        if is_remote_not_found_err(&err) {
+            GitRemoteManagementError::NoSuchRemote(remote_name.to_owned())
+        } else {
+            GitRemoteManagementError::InternalGitError(err)
+        }
+    })?;
+
+    let mut config = git_repo
+        .config()
+        .map_err(GitRemoteManagementError::InternalGitError)?;
+    let key = format!("remote.{remote_name}.pushurl");
+    if !add {
+        match config.remove_multivar(&key, ".*") {
+            Ok(()) => {}
+            Err(err) if err.code() == git2::ErrorCode::NotFound => {}
+            Err(err) => return Err(GitRemoteManagementError::InternalGitError(err)),
+        }
+    }
+    // "^$" doesn't match any existing value, so the URL is appended.
+    config
+        .set_multivar(&key, "^$", new_push_url)
+        .map_err(GitRemoteManagementError::InternalGitError)?;
+    Ok(())
+}
+
+/// Returns the URLs the remote is pushed to, or an empty list if the remote is
+/// pushed to its fetch URL.
+pub fn get_remote_push_urls(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+) -> Result<Vec<String>, git2::Error> {
+    let config = git_repo.config()?;
+    let mut urls = vec![];
+    let key = format!("remote.{remote_name}.pushurl");
+    match config.multivar(&key, None) {
+        Ok(entries) => {
+            entries.for_each(|entry| {
+                if let Some(url) = entry.value() {
+                    urls.push(url.to_owned());
+                }
+            })?;
+        }
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    Ok(urls)
+}
+
+/// Rewrites the URL according to the `git.url.<base>` settings. Returns `None`
+/// if no rule matches.
+///
+/// Like in Git, the longest matching prefix wins, and `push-instead-of` rules
+/// take precedence over `instead-of` rules when pushing. The `url.<base>`
+/// rules of the Git config are applied by libgit2 itself.
+pub fn rewrite_remote_url(
+    git_settings: &GitSettings,
+    url: &str,
+    direction: git2::Direction,
+) -> Option<String> {
+    let find_rule = |push: bool| {
+        git_settings
+            .url_rewrites
+            .iter()
+            .flat_map(|(base, rewrite)| {
+                let prefixes = if push {
+                    &rewrite.push_instead_of
+                } else {
+                    &rewrite.instead_of
+                };
+                prefixes.iter().map(move |prefix| (base, prefix))
+            })
+            .filter(|(_, prefix)| url.starts_with(prefix.as_str()))
+            .max_by_key(|(_, prefix)| prefix.len())
+    };
+    let (base, prefix) = match direction {
+        git2::Direction::Fetch => find_rule(false),
+        git2::Direction::Push => find_rule(true).or_else(|| find_rule(false)),
+    }?;
+    Some(format!("{base}{}", &url[prefix.len()..]))
+}
+
+/// Looks up the remote, applying the URL rewrites of the settings. If a rule
+/// matches, an anonymous remote with the rewritten URL is returned.
+fn find_remote_rewritten<'r>(
+    git_repo: &'r git2::Repository,
+    remote_name: &str,
+    git_settings: &GitSettings,
+    direction: git2::Direction,
+) -> Result<git2::Remote<'r>, git2::Error> {
+    let remote = git_repo.find_remote(remote_name)?;
+    let rewritten_url = match (direction, remote.pushurl()) {
+        // Like in Git, `push-instead-of` doesn't apply to explicit push URLs.
+        (git2::Direction::Push, Some(url)) => {
+            rewrite_remote_url(git_settings, url, git2::Direction::Fetch)
+        }
+        (direction, _) => remote
+            .url()
+            .and_then(|url| rewrite_remote_url(git_settings, url, direction)),
+    };
+    match rewritten_url {
+        Some(url) => git_repo.remote_anonymous(&url),
+        None => Ok(remote),
+    }
+}
+
+/// Returns the push URLs with the URL rewrites of the settings applied, or
+/// `None` if no rule matches.
+fn rewrite_push_urls(
+    git_settings: &GitSettings,
+    remote: &git2::Remote,
+    push_urls: &[String],
+) -> Option<Vec<String>> {
+    if push_urls.is_empty() {
+        let url = rewrite_remote_url(git_settings, remote.url()?, git2::Direction::Push)?;
+        return Some(vec![url]);
+    }
+    // Like in Git, `push-instead-of` doesn't apply to explicit push URLs.
+    let rewritten_urls = push_urls
+        .iter()
+        .map(|url| rewrite_remote_url(git_settings, url, git2::Direction::Fetch))
+        .collect_vec();
+    rewritten_urls.iter().any(Option::is_some).then(|| {
+        rewritten_urls
+            .into_iter()
+            .zip(push_urls)
+            .map(|(rewritten, url)| rewritten.unwrap_or_else(|| url.clone()))
+            .collect()
+    })
+}
+
+/// Returns the proxy options for the remote. Unless a proxy is configured in
+/// the settings, the proxy is detected from the Git config and environment.
+fn remote_proxy_options(
+    git_settings: &GitSettings,
+    remote_name: &str,
+) -> git2::ProxyOptions<'static> {
+    let mut proxy_options = git2::ProxyOptions::new();
+    let proxy = git_settings
+        .remote_proxies
+        .get(remote_name)
+        .or(git_settings.proxy.as_ref());
+    match proxy {
+        // An empty proxy disables the proxy like in Git.
+        Some(url) if url.is_empty() => {}
+        Some(url) => {
+            proxy_options.url(url);
+        }
+        None => {
+            proxy_options.auto();
+        }
+    }
+    proxy_options
+}
+
+/// Deletes the remote-tracking branches of branches that aren't in
+/// `remote_heads`.
+fn prune_remote_tracking_refs(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    remote_heads: &HashSet<String>,
+) -> Result<(), git2::Error> {
+    let prefix = format!("refs/remotes/{remote_name}/");
+    for git_ref in git_repo.references_glob(&format!("{prefix}*"))? {
+        let mut git_ref = git_ref?;
+        let is_stale = git_ref
+            .name()
+            .and_then(|name| name.strip_prefix(&prefix))
+            .is_some_and(|branch| {
+                branch != "HEAD" && !remote_heads.contains(&format!("refs/heads/{branch}"))
+            });
+        if is_stale {
+            git_ref.delete()?;
+        }
+    }
+    Ok(())
+}
+
+/// Deletes the remote-tracking branches of branches that no longer exist on
+/// the remote, and imports the deletions.
+pub fn prune_remote(
+    mut_repo: &mut MutableRepo,
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
+) -> Result<GitImportStats, GitFetchError> {
+    let mut remote =
+        find_remote_rewritten(git_repo, remote_name, git_settings, git2::Direction::Fetch)
+            .map_err(|err| {
+                if is_remote_not_found_err(&err) {
+                    GitFetchError::NoSuchRemote(remote_name.to_string())
+                } else {
+                    GitFetchError::InternalGitError(err)
+                }
+            })?;
+    {
+        let mut connection = remote.connect_auth(
+            git2::Direction::Fetch,
+            Some(callbacks.into_git()),
+            Some(remote_proxy_options(git_settings, remote_name)),
+        )?;
+        tracing::debug!("remote.prune");
+        if connection.remote().name().is_some() {
+            connection.remote().prune(None)?;
+        } else {
+            // An anonymous remote has no refspecs to prune by.
+            let remote_heads = connection
+                .list()?
+                .iter()
+                .map(|head| head.name().to_owned())
+                .collect();
+            prune_remote_tracking_refs(git_repo, remote_name, &remote_heads)?;
+        }
+    }
+    let import_stats = import_some_refs(mut_repo, git_settings, |ref_name| {
+        to_remote_branch(ref_name, remote_name).is_some()
+    })?;
+    Ok(import_stats)
+}
+
 fn rename_remote_refs(mut_repo: &mut MutableRepo, old_remote_name: &str, new_remote_name: &str) {
     mut_repo.rename_remote(old_remote_name, new_remote_name);
     let prefix = format!("refs/remotes/{old_remote_name}/");
@@ -1225,6 +1688,47 @@ pub enum GitFetchError {
     // TODO: I'm sure there are other errors possible, such as transport-level errors.
     #[error("Unexpected git error when fetching")]
     InternalGitError(#[from] git2::Error),
+    #[error("Failed to run git fetch command: {0}")]
+    FetchCommand(String),
+    #[error("git fetch command exited with an error: {0}")]
+    FetchCommandErrorStatus(String),
+}
+
+/// Which tags to download when fetching.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum GitFetchTagPolicy {
+    /// Download tags pointing to the fetched commits, as Git does by default.
+    #[default]
+    Auto,
+    /// Download all tags from the remote.
+    All,
+    /// Don't download tags.
+    None,
+}
+
+impl GitFetchTagPolicy {
+    fn to_git2(self) -> git2::AutotagOption {
+        match self {
+            GitFetchTagPolicy::Auto => git2::AutotagOption::Unspecified,
+            GitFetchTagPolicy::All => git2::AutotagOption::All,
+            GitFetchTagPolicy::None => git2::AutotagOption::None,
+        }
+    }
+}
+
+/// Options for `fetch()`.
+#[derive(Clone, Debug, Default)]
+pub struct GitFetchOptions {
+    /// If specified, the history is truncated to that many commits from the
+    /// tip of each fetched branch.
+    pub depth: Option<NonZeroU32>,
+    /// If specified, the history is truncated to the commits newer than the
+    /// date, in any format accepted by `git fetch --shallow-since`.
+    pub shallow_since: Option<String>,
+    /// Fetch the full history of a shallow repo.
+    pub unshallow: bool,
+    /// Which tags to download.
+    pub tags: GitFetchTagPolicy,
 }
 
 /// Describes successful `fetch()` result.
@@ -1236,30 +1740,28 @@ pub struct GitFetchStats {
     pub import_stats: GitImportStats,
 }
 
+/// Fetches the given branches from the remote and imports them.
 #[tracing::instrument(skip(mut_repo, git_repo, callbacks))]
 pub fn fetch(
     mut_repo: &mut MutableRepo,
     git_repo: &git2::Repository,
     remote_name: &str,
     branch_names: &[StringPattern],
+    options: &GitFetchOptions,
     callbacks: RemoteCallbacks<'_>,
     git_settings: &GitSettings,
 ) -> Result<GitFetchStats, GitFetchError> {
     // Perform a `git fetch` on the local git repo, updating the remote-tracking
     // branches in the git repo.
-    let mut remote = git_repo.find_remote(remote_name).map_err(|err| {
-        if is_remote_not_found_err(&err) {
-            GitFetchError::NoSuchRemote(remote_name.to_string())
-        } else {
-            GitFetchError::InternalGitError(err)
-        }
-    })?;
-    let mut fetch_options = git2::FetchOptions::new();
-    let mut proxy_options = git2::ProxyOptions::new();
-    proxy_options.auto();
-    fetch_options.proxy_options(proxy_options);
-    let callbacks = callbacks.into_git();
-    fetch_options.remote_callbacks(callbacks);
+    let mut remote =
+        find_remote_rewritten(git_repo, remote_name, git_settings, git2::Direction::Fetch)
+            .map_err(|err| {
+                if is_remote_not_found_err(&err) {
+                    GitFetchError::NoSuchRemote(remote_name.to_string())
+                } else {
+                    GitFetchError::InternalGitError(err)
+                }
+            })?;
     // At this point, we are only updating Git's remote tracking branches, not the
     // local branches.
     let refspecs: Vec<_> = branch_names
@@ -1277,17 +1779,53 @@ pub fn fetch(
         let stats = GitFetchStats::default();
         return Ok(stats);
     }
-    tracing::debug!("remote.download");
-    remote.download(&refspecs, Some(&mut fetch_options))?;
-    tracing::debug!("remote.prune");
-    remote.prune(None)?;
-    tracing::debug!("remote.update_tips");
-    remote.update_tips(
-        None,
-        git2::RemoteUpdateFlags::empty(),
-        git2::AutotagOption::Unspecified,
-        None,
-    )?;
+    // libgit2 doesn't support these, and can't fetch from a shallow repo over
+    // the local transport, so the `git` command is run instead.
+    let shallow_arg = if let Some(since) = &options.shallow_since {
+        Some(format!("--shallow-since={since}"))
+    } else if options.unshallow {
+        Some("--unshallow".to_owned())
+    } else {
+        None
+    };
+    if let Some(shallow_arg) = shallow_arg {
+        run_git_fetch_shallow(
+            git_repo,
+            remote_name,
+            &remote,
+            &refspecs,
+            &shallow_arg,
+            options,
+            git_settings,
+        )?;
+        // Connect only to look up the default branch below.
+        tracing::debug!("remote.connect");
+        remote.connect_auth(
+            git2::Direction::Fetch,
+            Some(callbacks.into_git()),
+            Some(remote_proxy_options(git_settings, remote_name)),
+        )?;
+    } else {
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.proxy_options(remote_proxy_options(git_settings, remote_name));
+        if let Some(depth) = options.depth {
+            // Values of i32::MAX or more would mean unshallowing.
+            fetch_options.depth(i32::try_from(depth.get()).unwrap_or(i32::MAX - 1));
+        }
+        fetch_options.download_tags(options.tags.to_git2());
+        fetch_options.remote_callbacks(callbacks.into_git());
+        tracing::debug!("remote.download");
+        remote.download(&refspecs, Some(&mut fetch_options))?;
+        tracing::debug!("remote.prune");
+        remote.prune(None)?;
+        tracing::debug!("remote.update_tips");
+        remote.update_tips(
+            None,
+            git2::RemoteUpdateFlags::empty(),
+            options.tags.to_git2(),
+            None,
+        )?;
+    }
     // TODO: We could make it optional to get the default branch since we only care
     // about it on clone.
     let mut default_branch = None;
@@ -1320,6 +1858,57 @@ pub fn fetch(
     Ok(stats)
 }
 
+/// Downloads the `refspecs` with `git fetch` and the given `shallow_arg`, and
+/// updates the remote-tracking branches.
+fn run_git_fetch_shallow(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    remote: &git2::Remote,
+    refspecs: &[String],
+    shallow_arg: &str,
+    options: &GitFetchOptions,
+    git_settings: &GitSettings,
+) -> Result<(), GitFetchError> {
+    let mut git = Command::new("git");
+    git.arg("--git-dir=."); // turn off discovery
+    if let Some(proxy) = git_settings
+        .remote_proxies
+        .get(remote_name)
+        .or(git_settings.proxy.as_ref())
+    {
+        git.arg("-c").arg(format!("http.proxy={proxy}"));
+    }
+    git.args(["fetch", "--prune", "--no-write-fetch-head", shallow_arg]);
+    match options.tags {
+        GitFetchTagPolicy::Auto => {}
+        GitFetchTagPolicy::All => {
+            git.arg("--tags");
+        }
+        GitFetchTagPolicy::None => {
+            git.arg("--no-tags");
+        }
+    }
+    // The URL may have been rewritten by the settings, so it's passed instead
+    // of the remote name. The refspecs name the remote-tracking branches.
+    let url = remote
+        .url()
+        .ok_or_else(|| GitFetchError::FetchCommand("remote has no URL".to_owned()))?;
+    git.arg("--").arg(url).args(refspecs);
+    // Don't specify it by GIT_DIR/--git-dir. On Windows, the "\\?\" path might
+    // not be supported by git.
+    git.current_dir(git_repo.path());
+    let output = git
+        .output()
+        .map_err(|err| GitFetchError::FetchCommand(err.to_string()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitFetchError::FetchCommandErrorStatus(
+            stderr.trim_end().to_owned(),
+        ));
+    }
+    Ok(())
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum GitPushError {
     #[error("No git remote named '{0}'")]
@@ -1331,17 +1920,30 @@ pub enum GitPushError {
     RemoteReservedForLocalGitRepo,
     #[error("Refs in unexpected location: {0:?}")]
     RefInUnexpectedLocation(Vec<String>),
-    #[error("Remote rejected the update of some refs (do you have permission to push to {0:?}?)")]
-    RefUpdateRejected(Vec<String>),
+    /// Remote rejected the update of some refs. Each entry is a pair of the
+    /// qualified ref name and the status message sent by the remote, if any.
+    #[error(
+        "Remote rejected the update of some refs (do you have permission to push to {:?}?)",
+        .0.iter().map(|(name, _)| name).collect_vec()
+    )]
+    RefUpdateRejected(Vec<(String, Option<String>)>),
     // TODO: I'm sure there are other errors possible, such as transport-level errors,
     // and errors caused by the remote rejecting the push.
     #[error("Unexpected git error when pushing")]
     InternalGitError(#[from] git2::Error),
+    #[error("Signing backend '{0}' cannot be used to sign pushes")]
+    UnsupportedSigningBackend(String),
+    #[error("Failed to run git push command: {0}")]
+    SignedPushCommand(String),
+    #[error("git push command exited with an error: {0}")]
+    SignedPushCommandErrorStatus(String),
 }
 
 #[derive(Clone, Debug)]
 pub struct GitBranchPushTargets {
     pub branch_updates: Vec<(String, BookmarkPushUpdate)>,
+    /// Push the branches even if they unexpectedly moved on the remote
+    pub force: bool,
 }
 
 pub struct GitRefUpdate {
@@ -1352,6 +1954,78 @@ pub struct GitRefUpdate {
     /// This is sourced from the local remote-tracking branch.
     pub expected_current_target: Option<CommitId>,
     pub new_target: Option<CommitId>,
+    /// Whether to update the ref even if it isn't at `expected_current_target`
+    /// on the remote
+    pub force: bool,
+}
+
+/// How to sign the push certificate of a signed push.
+///
+/// The certificate is created by `git push --signed`, which is configured to
+/// use the same program and key as the commit signing backend.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitPushSigning {
+    /// Name of the signing backend, `gpg` or `ssh`.
+    pub backend: String,
+    /// The key to sign with, as configured in `signing.key`.
+    pub key: Option<String>,
+    /// The program of the signing backend, if configured.
+    pub program: Option<String>,
+}
+
+impl GitPushSigning {
+    /// Loads the push signing settings from the commit signing config. Returns
+    /// `None` if no signing backend is configured.
+    pub fn from_settings(settings: &UserSettings) -> Option<Self> {
+        let backend = settings.signing_backend()?;
+        let program = settings
+            .config()
+            .get_string(&format!("signing.backends.{backend}.program"))
+            .ok();
+        Some(GitPushSigning {
+            backend,
+            key: settings.sign_settings().key,
+            program,
+        })
+    }
+
+    /// Returns the Git config overrides that make `git push --signed` use
+    /// this signing backend.
+    fn to_git_config(&self) -> Result<Vec<(&'static str, &str)>, GitPushError> {
+        let mut config = vec![];
+        match self.backend.as_str() {
+            "gpg" => {
+                config.push(("gpg.format", "openpgp"));
+                if let Some(program) = &self.program {
+                    config.push(("gpg.program", program));
+                }
+            }
+            "ssh" => {
+                config.push(("gpg.format", "ssh"));
+                if let Some(program) = &self.program {
+                    config.push(("gpg.ssh.program", program));
+                }
+            }
+            backend => return Err(GitPushError::UnsupportedSigningBackend(backend.to_owned())),
+        }
+        if let Some(key) = &self.key {
+            config.push(("user.signingKey", key));
+        }
+        Ok(config)
+    }
+}
+
+fn branch_push_targets_to_ref_updates(targets: &GitBranchPushTargets) -> Vec<GitRefUpdate> {
+    targets
+        .branch_updates
+        .iter()
+        .map(|(branch_name, update)| GitRefUpdate {
+            qualified_name: format!("refs/heads/{branch_name}"),
+            expected_current_target: update.old_target.clone(),
+            new_target: update.new_target.clone(),
+            force: targets.force,
+        })
+        .collect_vec()
 }
 
 /// Pushes the specified branches and updates the repo view accordingly.
@@ -1360,19 +2034,49 @@ pub fn push_branches(
     git_repo: &git2::Repository,
     remote_name: &str,
     targets: &GitBranchPushTargets,
+    push_options: &[&str],
     callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
 ) -> Result<(), GitPushError> {
-    let ref_updates = targets
-        .branch_updates
-        .iter()
-        .map(|(branch_name, update)| GitRefUpdate {
-            qualified_name: format!("refs/heads/{branch_name}"),
-            expected_current_target: update.old_target.clone(),
-            new_target: update.new_target.clone(),
-        })
-        .collect_vec();
-    push_updates(mut_repo, git_repo, remote_name, &ref_updates, callbacks)?;
+    let ref_updates = branch_push_targets_to_ref_updates(targets);
+    push_updates(
+        mut_repo,
+        git_repo,
+        remote_name,
+        &ref_updates,
+        push_options,
+        callbacks,
+        git_settings,
+    )?;
+    record_pushed_branches(mut_repo, remote_name, targets);
+    Ok(())
+}
+
+/// Pushes the specified branches with a signed push certificate and updates
+/// the repo view accordingly.
+///
+/// libgit2 doesn't support push certificates, so this runs `git push
+/// --signed`. Credentials are therefore handled by Git, not by jj's
+/// callbacks. The remote must advertise support for signed pushes.
+pub fn push_branches_signed(
+    mut_repo: &mut MutableRepo,
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    targets: &GitBranchPushTargets,
+    push_options: &[&str],
+    signing: &GitPushSigning,
+) -> Result<(), GitPushError> {
+    let ref_updates = branch_push_targets_to_ref_updates(targets);
+    push_updates_signed(git_repo, remote_name, &ref_updates, push_options, signing)?;
+    record_pushed_branches(mut_repo, remote_name, targets);
+    Ok(())
+}
 
+fn record_pushed_branches(
+    mut_repo: &mut MutableRepo,
+    remote_name: &str,
+    targets: &GitBranchPushTargets,
+) {
     // TODO: add support for partially pushed refs? we could update the view
     // excluding rejected refs, but the transaction would be aborted anyway
     // if we returned an Err.
@@ -1385,25 +2089,25 @@ pub fn push_branches(
         mut_repo.set_git_ref_target(&git_ref_name, new_remote_ref.target.clone());
         mut_repo.set_remote_bookmark(branch_name, remote_name, new_remote_ref);
     }
-
-    Ok(())
 }
 
 /// Pushes the specified Git refs without updating the repo view.
+///
+/// The `push_options` are sent to the remote as Git push options.
+#[tracing::instrument(skip_all)]
 pub fn push_updates(
     repo: &dyn Repo,
     git_repo: &git2::Repository,
     remote_name: &str,
     updates: &[GitRefUpdate],
+    push_options: &[&str],
     callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
 ) -> Result<(), GitPushError> {
-    let mut qualified_remote_refs_expected_locations = HashMap::new();
+    let mut qualified_remote_refs = HashMap::new();
     let mut refspecs = vec![];
     for update in updates {
-        qualified_remote_refs_expected_locations.insert(
-            update.qualified_name.as_str(),
-            update.expected_current_target.as_ref(),
-        );
+        qualified_remote_refs.insert(update.qualified_name.as_str(), update);
         if let Some(new_target) = &update.new_target {
             // We always force-push. We use the push_negotiation callback in
             // `push_refs` to check that the refs did not unexpectedly move on
@@ -1422,19 +2126,24 @@ pub fn push_updates(
         repo,
         git_repo,
         remote_name,
-        &qualified_remote_refs_expected_locations,
+        &qualified_remote_refs,
         &refspecs,
+        push_options,
         callbacks,
+        git_settings,
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn push_refs(
     repo: &dyn Repo,
     git_repo: &git2::Repository,
     remote_name: &str,
-    qualified_remote_refs_expected_locations: &HashMap<&str, Option<&CommitId>>,
+    qualified_remote_refs: &HashMap<&str, &GitRefUpdate>,
     refspecs: &[String],
+    remote_push_options: &[&str],
     callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
 ) -> Result<(), GitPushError> {
     if remote_name == REMOTE_NAME_FOR_LOCAL_GIT_REPO {
         return Err(GitPushError::RemoteReservedForLocalGitRepo);
@@ -1446,25 +2155,81 @@ fn push_refs(
             GitPushError::InternalGitError(err)
         }
     })?;
-    let mut remaining_remote_refs: HashSet<_> = qualified_remote_refs_expected_locations
-        .keys()
-        .copied()
-        .collect();
+    let push_urls = get_remote_push_urls(git_repo, remote_name)?;
+    let rewritten_urls = rewrite_push_urls(git_settings, &remote, &push_urls);
+    if rewritten_urls.is_none() && push_urls.len() <= 1 {
+        return push_refs_to_remote(
+            repo,
+            &mut remote,
+            remote_name,
+            qualified_remote_refs,
+            refspecs,
+            remote_push_options,
+            callbacks,
+            git_settings,
+        );
+    }
+
+    // libgit2 only pushes to one of the push URLs, so push to each of them like
+    // Git does.
+    let mut callbacks = callbacks;
+    for url in &rewritten_urls.unwrap_or(push_urls) {
+        let mut url_remote = git_repo.remote_anonymous(url)?;
+        push_refs_to_remote(
+            repo,
+            &mut url_remote,
+            remote_name,
+            qualified_remote_refs,
+            refspecs,
+            remote_push_options,
+            callbacks.reborrow(),
+            git_settings,
+        )?;
+    }
+    // The anonymous remotes don't update the remote-tracking branches.
+    for (&qualified_name, update) in qualified_remote_refs {
+        let Some(branch_name) = qualified_name.strip_prefix("refs/heads/") else {
+            continue;
+        };
+        let tracking_ref_name = format!("refs/remotes/{remote_name}/{branch_name}");
+        if let Some(new_target) = &update.new_target {
+            let oid = Oid::from_bytes(new_target.as_bytes())?;
+            git_repo.reference(&tracking_ref_name, oid, true, "push")?;
+        } else if let Ok(mut tracking_ref) = git_repo.find_reference(&tracking_ref_name) {
+            tracking_ref.delete()?;
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_refs_to_remote(
+    repo: &dyn Repo,
+    remote: &mut git2::Remote,
+    remote_name: &str,
+    qualified_remote_refs: &HashMap<&str, &GitRefUpdate>,
+    refspecs: &[String],
+    remote_push_options: &[&str],
+    callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
+) -> Result<(), GitPushError> {
+    let mut remaining_remote_refs: HashSet<_> = qualified_remote_refs.keys().copied().collect();
+    let mut rejection_messages: HashMap<String, String> = HashMap::new();
     let mut failed_push_negotiations = vec![];
     let push_result = {
         let mut push_options = git2::PushOptions::new();
-        let mut proxy_options = git2::ProxyOptions::new();
-        proxy_options.auto();
-        push_options.proxy_options(proxy_options);
+        push_options.proxy_options(remote_proxy_options(git_settings, remote_name));
+        push_options.remote_push_options(remote_push_options);
         let mut callbacks = callbacks.into_git();
         callbacks.push_negotiation(|updates| {
             for update in updates {
                 let dst_refname = update
                     .dst_refname()
                     .expect("Expect reference name to be valid UTF-8");
-                let expected_remote_location = *qualified_remote_refs_expected_locations
+                let ref_update = qualified_remote_refs
                     .get(dst_refname)
                     .expect("Push is trying to move a ref it wasn't asked to move");
+                let expected_remote_location = ref_update.expected_current_target.as_ref();
                 let oid_to_maybe_commitid =
                     |oid: git2::Oid| (!oid.is_zero()).then(|| CommitId::from_bytes(oid.as_bytes()));
                 let actual_remote_location = oid_to_maybe_commitid(update.src());
@@ -1495,6 +2260,13 @@ fn push_refs(
                              actual location is a descendant of the expected location.",
                         );
                     }
+                    Err(()) if ref_update.force => {
+                        tracing::info!(
+                            "Force-pushing {dst_refname} to {local_location:?}, even though it \
+                             is unexpectedly at {actual_remote_location:?} on the server rather \
+                             than the expected {expected_remote_location:?}",
+                        );
+                    }
                     Err(()) => {
                         // While we show debug info in the message with `--debug`,
                         // there's probably no need to show the detailed commit
@@ -1519,7 +2291,9 @@ fn push_refs(
         });
         callbacks.push_update_reference(|refname, status| {
             // The status is Some if the ref update was rejected
-            if status.is_none() {
+            if let Some(message) = status {
+                rejection_messages.insert(refname.to_owned(), message.to_owned());
+            } else {
                 remaining_remote_refs.remove(refname);
             }
             Ok(())
@@ -1548,13 +2322,181 @@ fn push_refs(
                 remaining_remote_refs
                     .iter()
                     .sorted()
-                    .map(|name| name.to_string())
+                    .map(|&name| (name.to_owned(), rejection_messages.remove(name)))
                     .collect(),
             ))
         }
     }
 }
 
+/// Pushes the specified tags as they are in the underlying Git repo, so
+/// annotated tags are pushed with their tag objects.
+///
+/// Tags aren't force-pushed, so the remote rejects a tag that already exists
+/// there with a different target.
+pub fn push_tags(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    tag_names: &[String],
+    callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
+) -> Result<(), GitPushError> {
+    if remote_name == REMOTE_NAME_FOR_LOCAL_GIT_REPO {
+        return Err(GitPushError::RemoteReservedForLocalGitRepo);
+    }
+    let mut remote = git_repo.find_remote(remote_name).map_err(|err| {
+        if is_remote_not_found_err(&err) {
+            GitPushError::NoSuchRemote(remote_name.to_string())
+        } else {
+            GitPushError::InternalGitError(err)
+        }
+    })?;
+    let qualified_names = tag_names
+        .iter()
+        .map(|name| format!("refs/tags/{name}"))
+        .collect_vec();
+    let refspecs = qualified_names
+        .iter()
+        .map(|name| format!("{name}:{name}"))
+        .collect_vec();
+    let mut remaining_remote_refs: HashSet<_> = qualified_names.iter().cloned().collect();
+    let mut rejection_messages: HashMap<String, String> = HashMap::new();
+    let push_result = {
+        let mut push_options = git2::PushOptions::new();
+        push_options.proxy_options(remote_proxy_options(git_settings, remote_name));
+        let mut callbacks = callbacks.into_git();
+        callbacks.push_update_reference(|refname, status| {
+            // The status is Some if the ref update was rejected
+            if let Some(message) = status {
+                rejection_messages.insert(refname.to_owned(), message.to_owned());
+            } else {
+                remaining_remote_refs.remove(refname);
+            }
+            Ok(())
+        });
+        push_options.remote_callbacks(callbacks);
+        remote.push(&refspecs, Some(&mut push_options))
+    };
+    push_result?;
+    if remaining_remote_refs.is_empty() {
+        Ok(())
+    } else {
+        Err(GitPushError::RefUpdateRejected(
+            remaining_remote_refs
+                .into_iter()
+                .sorted()
+                .map(|name| {
+                    let message = rejection_messages.remove(&name);
+                    (name, message)
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// Pushes the specified Git refs with `git push --signed` without updating
+/// the repo view.
+///
+/// The expected remote locations are checked by `--force-with-lease`, so unlike
+/// `push_updates()`, a ref that unexpectedly moved on the remote is never
+/// pushed, even if the push would be a fast-forward.
+pub fn push_updates_signed(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    updates: &[GitRefUpdate],
+    push_options: &[&str],
+    signing: &GitPushSigning,
+) -> Result<(), GitPushError> {
+    if remote_name == REMOTE_NAME_FOR_LOCAL_GIT_REPO {
+        return Err(GitPushError::RemoteReservedForLocalGitRepo);
+    }
+    git_repo.find_remote(remote_name).map_err(|err| {
+        if is_remote_not_found_err(&err) {
+            GitPushError::NoSuchRemote(remote_name.to_string())
+        } else {
+            GitPushError::InternalGitError(err)
+        }
+    })?;
+
+    let mut git = Command::new("git");
+    git.arg("--git-dir=."); // turn off discovery
+    for (key, value) in signing.to_git_config()? {
+        git.arg("-c").arg(format!("{key}={value}"));
+    }
+    git.args(["push", "--porcelain", "--signed"]);
+    for option in push_options {
+        git.arg(format!("--push-option={option}"));
+    }
+    let mut refspecs = vec![];
+    for update in updates {
+        if !update.force {
+            let expected = update
+                .expected_current_target
+                .as_ref()
+                .map(|id| id.hex())
+                .unwrap_or_default();
+            git.arg(format!(
+                "--force-with-lease={}:{expected}",
+                update.qualified_name
+            ));
+        }
+        let force_prefix = if update.force { "+" } else { "" };
+        if let Some(new_target) = &update.new_target {
+            refspecs.push(format!(
+                "{force_prefix}{}:{}",
+                new_target.hex(),
+                update.qualified_name
+            ));
+        } else {
+            refspecs.push(format!(":{}", update.qualified_name));
+        }
+    }
+    git.arg("--").arg(remote_name).args(&refspecs);
+    // Don't specify it by GIT_DIR/--git-dir. On Windows, the "\\?\" path might
+    // not be supported by git.
+    git.current_dir(git_repo.path());
+    let output = git
+        .output()
+        .map_err(|err| GitPushError::SignedPushCommand(err.to_string()))?;
+
+    // Each ref is reported as "<flag>\t<from>:<to>\t<summary>", where the flag
+    // is "!" if the ref was rejected.
+    let mut failed_push_negotiations = vec![];
+    let mut rejections = vec![];
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.split('\t');
+        let (Some("!"), Some(refs), summary) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        let Some((_, dst_refname)) = refs.split_once(':') else {
+            continue;
+        };
+        let summary = summary.unwrap_or_default();
+        if summary.contains("stale info") {
+            failed_push_negotiations.push(dst_refname.to_owned());
+        } else {
+            rejections.push((dst_refname.to_owned(), Some(summary.to_owned())));
+        }
+    }
+    if !failed_push_negotiations.is_empty() {
+        failed_push_negotiations.sort();
+        Err(GitPushError::RefInUnexpectedLocation(
+            failed_push_negotiations,
+        ))
+    } else if !rejections.is_empty() {
+        rejections.sort();
+        Err(GitPushError::RefUpdateRejected(rejections))
+    } else if !output.status.success() {
+        Err(GitPushError::SignedPushCommandErrorStatus(
+            String::from_utf8_lossy(&output.stderr)
+                .trim_end()
+                .to_owned(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum PushAllowReason {
     NormalMatch,
@@ -1613,6 +2555,1107 @@ fn allow_push(
     }
 }
 
+/// What pushing branches would do, as negotiated with the remote.
+#[derive(Clone, Debug, Default)]
+pub struct GitPushPreview {
+    /// Refs that unexpectedly moved on the remote. Pushing them would fail
+    /// unless forced.
+    pub unexpectedly_moved_refs: Vec<String>,
+    /// Commits the remote doesn't have yet, children before parents.
+    pub commits: Vec<CommitId>,
+    /// Number of objects that would be sent.
+    pub num_objects: usize,
+    /// Size of the pack that would be sent, in bytes.
+    pub pack_size: u64,
+}
+
+/// Connects to the remote and computes what pushing the specified branches
+/// would do, without updating anything.
+pub fn preview_push_branches(
+    repo: &dyn Repo,
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    targets: &GitBranchPushTargets,
+    callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
+) -> Result<GitPushPreview, GitPushError> {
+    if remote_name == REMOTE_NAME_FOR_LOCAL_GIT_REPO {
+        return Err(GitPushError::RemoteReservedForLocalGitRepo);
+    }
+    let mut remote =
+        find_remote_rewritten(git_repo, remote_name, git_settings, git2::Direction::Push).map_err(
+            |err| {
+                if is_remote_not_found_err(&err) {
+                    GitPushError::NoSuchRemote(remote_name.to_string())
+                } else {
+                    GitPushError::InternalGitError(err)
+                }
+            },
+        )?;
+    let remote_heads: HashMap<String, Oid> = {
+        let connection = remote.connect_auth(
+            git2::Direction::Push,
+            Some(callbacks.into_git()),
+            Some(remote_proxy_options(git_settings, remote_name)),
+        )?;
+        connection
+            .list()?
+            .iter()
+            .map(|head| (head.name().to_owned(), head.oid()))
+            .collect()
+    };
+
+    let mut preview = GitPushPreview::default();
+    for (branch_name, update) in &targets.branch_updates {
+        let qualified_name = format!("refs/heads/{branch_name}");
+        let actual_remote_location = remote_heads
+            .get(&qualified_name)
+            .map(|oid| CommitId::from_bytes(oid.as_bytes()));
+        let allowed = allow_push(
+            repo.index(),
+            actual_remote_location.as_ref(),
+            update.old_target.as_ref(),
+            update.new_target.as_ref(),
+        );
+        if allowed.is_err() && !targets.force {
+            preview.unexpectedly_moved_refs.push(qualified_name);
+        }
+    }
+
+    // The remote has all objects reachable from its refs. Refs pointing to
+    // commits we don't have can't be used to exclude anything.
+    let new_oids = targets
+        .branch_updates
+        .iter()
+        .filter_map(|(_, update)| update.new_target.as_ref())
+        .map(|id| Oid::from_bytes(id.as_bytes()).unwrap())
+        .collect_vec();
+    let remote_oids = remote_heads
+        .values()
+        .filter(|&&oid| git_repo.find_commit(oid).is_ok())
+        .unique()
+        .copied()
+        .collect_vec();
+    let new_revwalk = || {
+        let mut revwalk = git_repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL)?;
+        for &oid in &new_oids {
+            revwalk.push(oid)?;
+        }
+        for &oid in &remote_oids {
+            revwalk.hide(oid)?;
+        }
+        Ok::<_, git2::Error>(revwalk)
+    };
+    preview.commits = new_revwalk()?
+        .map_ok(|oid| CommitId::from_bytes(oid.as_bytes()))
+        .try_collect()?;
+    if !preview.commits.is_empty() {
+        let mut pack_builder = git_repo.packbuilder()?;
+        pack_builder.insert_walk(&mut new_revwalk()?)?;
+        preview.num_objects = pack_builder.object_count();
+        pack_builder.foreach(|chunk| {
+            preview.pack_size += chunk.len() as u64;
+            true
+        })?;
+    }
+    Ok(preview)
+}
+
+/// The notes ref used when neither `--ref` nor `core.notesRef` is set, as in
+/// Git.
+pub const DEFAULT_NOTES_REF: &str = "refs/notes/commits";
+
+#[derive(Error, Debug)]
+pub enum GitNotesError {
+    #[error("No git remote named '{0}'")]
+    NoSuchRemote(String),
+    #[error(
+        "Git remote named '{name}' is reserved for local Git repository",
+        name = REMOTE_NAME_FOR_LOCAL_GIT_REPO
+    )]
+    RemoteReservedForLocalGitRepo,
+    #[error("Commit {} already has a note", .0.hex())]
+    NoteExists(CommitId),
+    #[error("Notes ref {notes_ref} has diverged from the one on remote {remote_name}")]
+    Diverged {
+        notes_ref: String,
+        remote_name: String,
+    },
+    /// Remote rejected the update of the notes ref with the given status
+    /// message.
+    #[error("Remote rejected the update of {0}: {1}")]
+    RefUpdateRejected(String, String),
+    #[error("Unexpected git error when accessing notes")]
+    InternalGitError(#[from] git2::Error),
+}
+
+/// Returns the notes ref to read and write if none is specified: the value of
+/// `core.notesRef`, or [`DEFAULT_NOTES_REF`].
+pub fn default_notes_ref(git_repo: &git2::Repository) -> Result<String, git2::Error> {
+    let notes_ref = git_repo.note_default_ref()?;
+    Ok(notes_ref)
+}
+
+/// Returns the note attached to the commit, if any.
+pub fn get_note(
+    git_repo: &git2::Repository,
+    notes_ref: &str,
+    commit_id: &CommitId,
+) -> Result<Option<String>, git2::Error> {
+    let oid = Oid::from_bytes(commit_id.as_bytes()).unwrap();
+    match git_repo.find_note(Some(notes_ref), oid) {
+        Ok(note) => Ok(Some(
+            String::from_utf8_lossy(note.message_bytes()).into_owned(),
+        )),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns all notes in the notes ref, keyed by the annotated commit.
+pub fn get_all_notes(
+    git_repo: &git2::Repository,
+    notes_ref: &str,
+) -> Result<HashMap<CommitId, String>, git2::Error> {
+    let notes = match git_repo.notes(Some(notes_ref)) {
+        Ok(notes) => notes,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err),
+    };
+    let mut notes_by_commit = HashMap::new();
+    for item in notes {
+        let (note_id, annotated_id) = item?;
+        let blob = git_repo.find_blob(note_id)?;
+        notes_by_commit.insert(
+            CommitId::from_bytes(annotated_id.as_bytes()),
+            String::from_utf8_lossy(blob.content()).into_owned(),
+        );
+    }
+    Ok(notes_by_commit)
+}
+
+/// Attaches a note to the commit. If the commit already has a note, it's
+/// replaced if `force` is true, and an error is returned otherwise.
+pub fn set_note(
+    git_repo: &git2::Repository,
+    notes_ref: &str,
+    commit_id: &CommitId,
+    message: &str,
+    signature: &Signature,
+    force: bool,
+) -> Result<(), GitNotesError> {
+    let oid = Oid::from_bytes(commit_id.as_bytes()).unwrap();
+    let signature = to_git2_signature(signature)?;
+    git_repo
+        .note(&signature, &signature, Some(notes_ref), oid, message, force)
+        .map_err(|err| {
+            if err.code() == git2::ErrorCode::Exists {
+                GitNotesError::NoteExists(commit_id.clone())
+            } else {
+                GitNotesError::InternalGitError(err)
+            }
+        })?;
+    Ok(())
+}
+
+/// Removes the note attached to the commit. Returns `false` if the commit had
+/// no note.
+pub fn remove_note(
+    git_repo: &git2::Repository,
+    notes_ref: &str,
+    commit_id: &CommitId,
+    signature: &Signature,
+) -> Result<bool, git2::Error> {
+    let oid = Oid::from_bytes(commit_id.as_bytes()).unwrap();
+    let signature = to_git2_signature(signature)?;
+    match git_repo.note_delete(oid, Some(notes_ref), &signature, &signature) {
+        Ok(()) => Ok(true),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+fn to_git2_signature(signature: &Signature) -> Result<git2::Signature<'static>, git2::Error> {
+    // git does not support empty names or emails
+    let name = if !signature.name.is_empty() {
+        &signature.name
+    } else {
+        EMPTY_STRING_PLACEHOLDER
+    };
+    let email = if !signature.email.is_empty() {
+        &signature.email
+    } else {
+        EMPTY_STRING_PLACEHOLDER
+    };
+    let time = git2::Time::new(
+        signature.timestamp.timestamp.0.div_euclid(1000),
+        signature.timestamp.tz_offset,
+    );
+    git2::Signature::new(name, email, &time)
+}
+
+fn find_remote_for_notes<'r>(
+    git_repo: &'r git2::Repository,
+    remote_name: &str,
+    git_settings: &GitSettings,
+    direction: git2::Direction,
+) -> Result<git2::Remote<'r>, GitNotesError> {
+    if remote_name == REMOTE_NAME_FOR_LOCAL_GIT_REPO {
+        return Err(GitNotesError::RemoteReservedForLocalGitRepo);
+    }
+    find_remote_rewritten(git_repo, remote_name, git_settings, direction).map_err(|err| {
+        if is_remote_not_found_err(&err) {
+            GitNotesError::NoSuchRemote(remote_name.to_string())
+        } else {
+            GitNotesError::InternalGitError(err)
+        }
+    })
+}
+
+/// Fetches the notes ref from the remote.
+///
+/// The local notes ref is fast-forwarded to the remote one. Notes can't be
+/// merged, so if both sides have been updated, [`GitNotesError::Diverged`] is
+/// returned and the local notes are left unchanged. Returns `false` if the
+/// remote doesn't have the notes ref.
+pub fn fetch_notes(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    notes_ref: &str,
+    callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
+) -> Result<bool, GitNotesError> {
+    let mut remote =
+        find_remote_for_notes(git_repo, remote_name, git_settings, git2::Direction::Fetch)?;
+    // The fetched notes ref is stored under refs/jj/ so that it can be
+    // compared with the local one before updating it.
+    let fetched_ref = format!(
+        "refs/jj/remote-notes/{remote_name}/{}",
+        notes_ref.strip_prefix("refs/notes/").unwrap_or(notes_ref)
+    );
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.proxy_options(remote_proxy_options(git_settings, remote_name));
+    fetch_options.download_tags(git2::AutotagOption::None);
+    fetch_options.remote_callbacks(callbacks.into_git());
+    let refspec = format!("+{notes_ref}:{fetched_ref}");
+    tracing::debug!(refspec, "remote.fetch");
+    remote.fetch(&[refspec], Some(&mut fetch_options), None)?;
+    remote.disconnect()?;
+
+    let remote_oid = match git_repo.refname_to_id(&fetched_ref) {
+        Ok(oid) => oid,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    let result = match git_repo.refname_to_id(notes_ref) {
+        Ok(local_oid) if local_oid == remote_oid => Ok(true),
+        Ok(local_oid) if git_repo.graph_descendant_of(local_oid, remote_oid)? => Ok(true),
+        Ok(local_oid) if git_repo.graph_descendant_of(remote_oid, local_oid)? => {
+            git_repo.reference(notes_ref, remote_oid, true, "fetch notes")?;
+            Ok(true)
+        }
+        Ok(_) => Err(GitNotesError::Diverged {
+            notes_ref: notes_ref.to_owned(),
+            remote_name: remote_name.to_owned(),
+        }),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {
+            git_repo.reference(notes_ref, remote_oid, false, "fetch notes")?;
+            Ok(true)
+        }
+        Err(err) => Err(err.into()),
+    };
+    git_repo.find_reference(&fetched_ref)?.delete()?;
+    result
+}
+
+/// Pushes the notes ref to the remote.
+///
+/// The push isn't forced, so the remote is expected to reject it unless the
+/// update is a fast-forward.
+pub fn push_notes(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    notes_ref: &str,
+    callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
+) -> Result<(), GitNotesError> {
+    let mut remote =
+        find_remote_for_notes(git_repo, remote_name, git_settings, git2::Direction::Push)?;
+    let mut rejection = None;
+    let push_result = {
+        let mut push_options = git2::PushOptions::new();
+        push_options.proxy_options(remote_proxy_options(git_settings, remote_name));
+        let mut callbacks = callbacks.into_git();
+        callbacks.push_update_reference(|_refname, status| {
+            // The status is Some if the ref update was rejected
+            if let Some(message) = status {
+                rejection = Some(message.to_owned());
+            }
+            Ok(())
+        });
+        push_options.remote_callbacks(callbacks);
+        remote.push(
+            &[format!("{notes_ref}:{notes_ref}")],
+            Some(&mut push_options),
+        )
+    };
+    push_result?;
+    if let Some(message) = rejection {
+        return Err(GitNotesError::RefUpdateRejected(
+            notes_ref.to_owned(),
+            message,
+        ));
+    }
+    Ok(())
+}
+
+/// Prefix of the refs that replace Git objects, as created by `git replace`.
+///
+/// The name of each ref is the full hex ID of the replaced object. The Git
+/// backend reads the commits from their replacements, so the commit index must
+/// be rebuilt when these refs change (see
+/// [`GitBackend::replacements_changed()`]).
+pub const REPLACE_REF_PREFIX: &str = "refs/replace/";
+
+#[derive(Error, Debug)]
+pub enum GitReplaceError {
+    #[error("Commit {} is already replaced", .0.hex())]
+    AlreadyReplaced(CommitId),
+    #[error("Commit {} cannot replace itself", .0.hex())]
+    ReplaceWithSelf(CommitId),
+    #[error("Unexpected git error when accessing replacements")]
+    InternalGitError(#[from] git2::Error),
+}
+
+/// Returns the replaced commits and their replacements, as of when the backend
+/// was loaded, sorted by the replaced commit.
+pub fn get_replacements(git_backend: &GitBackend) -> Vec<(CommitId, CommitId)> {
+    git_backend
+        .replacements()
+        .iter()
+        .map(|(original, replacement)| (original.clone(), replacement.clone()))
+        .collect()
+}
+
+/// Makes `replacement` replace the `original` commit. If the commit is already
+/// replaced, the replacement is overwritten if `force` is true, and an error is
+/// returned otherwise.
+///
+/// The replacement takes effect once the backend is loaded again, and the
+/// commit index is rebuilt.
+pub fn set_replacement(
+    git_backend: &GitBackend,
+    original: &CommitId,
+    replacement: &CommitId,
+    force: bool,
+) -> Result<(), GitReplaceError> {
+    if original == replacement {
+        return Err(GitReplaceError::ReplaceWithSelf(original.clone()));
+    }
+    let git_repo = git_backend.open_git_repo()?;
+    let ref_name = format!("{REPLACE_REF_PREFIX}{}", original.hex());
+    let target = Oid::from_bytes(replacement.as_bytes()).unwrap();
+    // Both must be commits, or reading the replaced commit would fail.
+    git_repo.find_commit(Oid::from_bytes(original.as_bytes()).unwrap())?;
+    git_repo.find_commit(target)?;
+    match git_repo.reference(&ref_name, target, force, "replace commit") {
+        Ok(_) => {}
+        Err(err) if err.code() == git2::ErrorCode::Exists => {
+            return Err(GitReplaceError::AlreadyReplaced(original.clone()));
+        }
+        Err(err) => return Err(err.into()),
+    }
+    Ok(())
+}
+
+/// Replaces the `original` commit by a copy of it with the given parents, like
+/// `git replace --graft`. An empty list of parents makes the commit a root
+/// commit. Returns the ID of the replacement commit.
+pub fn set_graft(
+    git_backend: &GitBackend,
+    original: &CommitId,
+    parents: &[CommitId],
+    force: bool,
+) -> Result<CommitId, GitReplaceError> {
+    let git_repo = git_backend.open_git_repo()?;
+    let commit = git_repo.find_commit(Oid::from_bytes(original.as_bytes()).unwrap())?;
+    let parents: Vec<_> = parents
+        .iter()
+        .map(|id| git_repo.find_commit(Oid::from_bytes(id.as_bytes()).unwrap()))
+        .try_collect()?;
+    let message = String::from_utf8_lossy(commit.message_raw_bytes());
+    let buffer = git_repo.commit_create_buffer(
+        &commit.author(),
+        &commit.committer(),
+        &message,
+        &commit.tree()?,
+        &parents.iter().collect_vec(),
+    )?;
+    let oid = git_repo.odb()?.write(git2::ObjectType::Commit, &buffer)?;
+    let replacement = CommitId::from_bytes(oid.as_bytes());
+    set_replacement(git_backend, original, &replacement, force)?;
+    Ok(replacement)
+}
+
+/// Removes the replacement of the commit. Returns `false` if the commit wasn't
+/// replaced.
+pub fn remove_replacement(
+    git_backend: &GitBackend,
+    original: &CommitId,
+) -> Result<bool, GitReplaceError> {
+    let git_repo = git_backend.open_git_repo()?;
+    let ref_name = format!("{REPLACE_REF_PREFIX}{}", original.hex());
+    match git_repo.find_reference(&ref_name) {
+        Ok(mut reference) => reference.delete()?,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(false),
+        Err(err) => return Err(err.into()),
+    }
+    Ok(true)
+}
+
+/// Ref pointing to the Git commit the operation log is exported to by
+/// [`push_operations()`].
+pub const OP_LOG_REF: &str = "refs/jj/op-log";
+
+#[derive(Error, Debug)]
+pub enum GitOpLogError {
+    #[error("No git remote named '{0}'")]
+    NoSuchRemote(String),
+    #[error(
+        "Git remote named '{name}' is reserved for local Git repository",
+        name = REMOTE_NAME_FOR_LOCAL_GIT_REPO
+    )]
+    RemoteReservedForLocalGitRepo,
+    #[error("Invalid {kind} {name} in the operation log fetched from the remote")]
+    InvalidObject { kind: &'static str, name: String },
+    /// Remote rejected the update of the operation log ref with the given
+    /// status message.
+    #[error("Remote rejected the update of the operation log: {0}")]
+    RefUpdateRejected(String),
+    #[error(transparent)]
+    OpStore(#[from] OpStoreError),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    #[error("Unexpected git error when syncing the operation log")]
+    InternalGitError(#[from] git2::Error),
+}
+
+/// Operations imported by [`fetch_operations()`].
+#[derive(Clone, Debug)]
+pub struct FetchedOperations {
+    /// The head operations of the remote operation log.
+    pub head_ids: Vec<OperationId>,
+    /// The number of operations that didn't exist locally.
+    pub num_imported: usize,
+}
+
+/// Returns the ref the operation log fetched from the remote is stored in.
+fn remote_op_log_ref(remote_name: &str) -> String {
+    format!("refs/jj/remote-op-log/{remote_name}")
+}
+
+fn find_remote_for_op_log<'r>(
+    git_repo: &'r git2::Repository,
+    remote_name: &str,
+    git_settings: &GitSettings,
+    direction: git2::Direction,
+) -> Result<git2::Remote<'r>, GitOpLogError> {
+    if remote_name == REMOTE_NAME_FOR_LOCAL_GIT_REPO {
+        return Err(GitOpLogError::RemoteReservedForLocalGitRepo);
+    }
+    find_remote_rewritten(git_repo, remote_name, git_settings, direction).map_err(|err| {
+        if is_remote_not_found_err(&err) {
+            GitOpLogError::NoSuchRemote(remote_name.to_string())
+        } else {
+            GitOpLogError::InternalGitError(err)
+        }
+    })
+}
+
+/// Exports the operations reachable from `head_ops`, and their views, to a Git
+/// commit, and points [`OP_LOG_REF`] to it.
+///
+/// The tree of the commit has the encoded operations and views in the
+/// `operations` and `views` directories, and the IDs of the head operations in
+/// the `heads` file. Its parents are the previously exported commit, the
+/// commit at `fetched_ref` if it exists, and the head commits of the views, so
+/// that pushing the commit also pushes the commits the views refer to.
+fn export_operations(
+    git_repo: &git2::Repository,
+    head_ops: &[Operation],
+    signature: &Signature,
+    fetched_ref: &str,
+) -> Result<Oid, GitOpLogError> {
+    let mut operations_builder = git_repo.treebuilder(None)?;
+    let mut views_builder = git_repo.treebuilder(None)?;
+    let mut view_head_ids = HashSet::new();
+    for op in op_walk::walk_ancestors(head_ops) {
+        let op = op?;
+        let op_store = op.op_store();
+        // The root operation and its empty view are the same in all repos.
+        if op.id() == op_store.root_operation_id() {
+            continue;
+        }
+        let blob = git_repo.blob(&simple_op_store::encode_operation(op.store_operation()))?;
+        operations_builder.insert(op.id().hex(), blob, git2::FileMode::Blob.into())?;
+        if views_builder.get(op.view_id().hex())?.is_none() {
+            let view = op_store.read_view(op.view_id())?;
+            let blob = git_repo.blob(&simple_op_store::encode_view(&view))?;
+            views_builder.insert(op.view_id().hex(), blob, git2::FileMode::Blob.into())?;
+            view_head_ids.extend(view.head_ids);
+        }
+    }
+    let heads = head_ops
+        .iter()
+        .map(|op| format!("{}\n", op.id().hex()))
+        .join("");
+    let mut tree_builder = git_repo.treebuilder(None)?;
+    tree_builder.insert(
+        "operations",
+        operations_builder.write()?,
+        git2::FileMode::Tree.into(),
+    )?;
+    tree_builder.insert("views", views_builder.write()?, git2::FileMode::Tree.into())?;
+    tree_builder.insert(
+        "heads",
+        git_repo.blob(heads.as_bytes())?,
+        git2::FileMode::Blob.into(),
+    )?;
+    let tree = git_repo.find_tree(tree_builder.write()?)?;
+
+    let mut log_parents: Vec<git2::Commit> = vec![];
+    for ref_name in [OP_LOG_REF, fetched_ref] {
+        let commit = match git_repo.find_reference(ref_name) {
+            Ok(reference) => reference.peel_to_commit()?,
+            Err(err) if err.code() == git2::ErrorCode::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        if log_parents.iter().all(|parent| parent.id() != commit.id()) {
+            log_parents.push(commit);
+        }
+    }
+    if let [previous] = log_parents.as_slice() {
+        if previous.tree_id() == tree.id() {
+            return Ok(previous.id());
+        }
+    }
+    // The root commit and commits missing from the Git repo can't be parents.
+    let head_commits = view_head_ids
+        .iter()
+        .sorted()
+        .filter_map(|id| Oid::from_bytes(id.as_bytes()).ok())
+        .filter_map(|oid| git_repo.find_commit(oid).ok());
+    let parents = log_parents.into_iter().chain(head_commits).collect_vec();
+    let signature = to_git2_signature(signature)?;
+    let oid = git_repo.commit(
+        None,
+        &signature,
+        &signature,
+        "jj operation log\n",
+        &tree,
+        &parents.iter().collect_vec(),
+    )?;
+    git_repo.reference(OP_LOG_REF, oid, true, "export operations")?;
+    Ok(oid)
+}
+
+/// Exports the operations reachable from `head_ops` and pushes them to the
+/// remote.
+///
+/// The push isn't forced, so the remote is expected to reject it if the
+/// operation log on the remote has been updated since it was last fetched.
+pub fn push_operations(
+    git_repo: &git2::Repository,
+    remote_name: &str,
+    head_ops: &[Operation],
+    signature: &Signature,
+    callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
+) -> Result<(), GitOpLogError> {
+    let mut remote =
+        find_remote_for_op_log(git_repo, remote_name, git_settings, git2::Direction::Push)?;
+    let fetched_ref = remote_op_log_ref(remote_name);
+    let oid = export_operations(git_repo, head_ops, signature, &fetched_ref)?;
+    let mut rejection = None;
+    let push_result = {
+        let mut push_options = git2::PushOptions::new();
+        push_options.proxy_options(remote_proxy_options(git_settings, remote_name));
+        let mut callbacks = callbacks.into_git();
+        callbacks.push_update_reference(|_refname, status| {
+            // The status is Some if the ref update was rejected
+            if let Some(message) = status {
+                rejection = Some(message.to_owned());
+            }
+            Ok(())
+        });
+        push_options.remote_callbacks(callbacks);
+        remote.push(
+            &[format!("{OP_LOG_REF}:{OP_LOG_REF}")],
+            Some(&mut push_options),
+        )
+    };
+    push_result?;
+    if let Some(message) = rejection {
+        return Err(GitOpLogError::RefUpdateRejected(message));
+    }
+    // The remote now has the exported operations, so the next export can
+    // build on them.
+    git_repo.reference(&fetched_ref, oid, true, "push operations")?;
+    Ok(())
+}
+
+/// Fetches the operation log from the remote, and writes the operations and
+/// views missing from the `op_store` to it.
+///
+/// The head operations of the remote are returned so that the caller can add
+/// them to the operation heads, in which case they're merged with the local
+/// ones like concurrent operations. Returns `None` if the remote has no
+/// operation log.
+pub fn fetch_operations(
+    git_repo: &git2::Repository,
+    store: &Store,
+    op_store: &dyn OpStore,
+    remote_name: &str,
+    callbacks: RemoteCallbacks<'_>,
+    git_settings: &GitSettings,
+) -> Result<Option<FetchedOperations>, GitOpLogError> {
+    let mut remote =
+        find_remote_for_op_log(git_repo, remote_name, git_settings, git2::Direction::Fetch)?;
+    let fetched_ref = remote_op_log_ref(remote_name);
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.proxy_options(remote_proxy_options(git_settings, remote_name));
+    fetch_options.download_tags(git2::AutotagOption::None);
+    fetch_options.remote_callbacks(callbacks.into_git());
+    let refspec = format!("+{OP_LOG_REF}:{fetched_ref}");
+    tracing::debug!(refspec, "remote.fetch");
+    remote.fetch(&[refspec], Some(&mut fetch_options), None)?;
+    remote.disconnect()?;
+
+    let commit = match git_repo.find_reference(&fetched_ref) {
+        Ok(reference) => reference.peel_to_commit()?,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    import_operations(git_repo, store, op_store, &commit.tree()?).map(Some)
+}
+
+fn import_operations(
+    git_repo: &git2::Repository,
+    store: &Store,
+    op_store: &dyn OpStore,
+    tree: &git2::Tree,
+) -> Result<FetchedOperations, GitOpLogError> {
+    let find_subtree = |name: &str| -> Result<Option<git2::Tree>, GitOpLogError> {
+        match tree.get_name(name) {
+            Some(entry) => Ok(Some(git_repo.find_tree(entry.id())?)),
+            None => Ok(None),
+        }
+    };
+    let invalid_object = |kind: &'static str, name: &str| GitOpLogError::InvalidObject {
+        kind,
+        name: name.to_owned(),
+    };
+
+    // Views are written first so that an imported operation never points to a
+    // missing view.
+    let mut view_head_ids = vec![];
+    for entry in find_subtree("views")?.iter().flat_map(|tree| tree.iter()) {
+        let name = entry.name().unwrap_or_default();
+        let id = ViewId::try_from_hex(name).map_err(|_| invalid_object("view", name))?;
+        match op_store.read_view(&id) {
+            Ok(_) => continue,
+            Err(OpStoreError::ObjectNotFound { .. }) => {}
+            Err(err) => return Err(err.into()),
+        }
+        let blob = git_repo.find_blob(entry.id())?;
+        let view = simple_op_store::decode_view(blob.content())
+            .map_err(|_| invalid_object("view", name))?;
+        if op_store.write_view(&view)? != id {
+            return Err(invalid_object("view", name));
+        }
+        view_head_ids.extend(view.head_ids);
+    }
+    let mut num_imported = 0;
+    for entry in find_subtree("operations")?
+        .iter()
+        .flat_map(|tree| tree.iter())
+    {
+        let name = entry.name().unwrap_or_default();
+        let id = OperationId::try_from_hex(name).map_err(|_| invalid_object("operation", name))?;
+        match op_store.read_operation(&id) {
+            Ok(_) => continue,
+            Err(OpStoreError::ObjectNotFound { .. }) => {}
+            Err(err) => return Err(err.into()),
+        }
+        let blob = git_repo.find_blob(entry.id())?;
+        let operation = simple_op_store::decode_operation(blob.content())
+            .map_err(|_| invalid_object("operation", name))?;
+        if operation.parents.is_empty() || op_store.write_operation(&operation)? != id {
+            return Err(invalid_object("operation", name));
+        }
+        num_imported += 1;
+    }
+    if let Some(git_backend) = get_git_backend(store) {
+        git_backend.import_head_commits(&view_head_ids)?;
+    }
+
+    let heads = match tree.get_name("heads") {
+        Some(entry) => git_repo.find_blob(entry.id())?.content().to_vec(),
+        None => vec![],
+    };
+    let heads = str::from_utf8(&heads).map_err(|_| invalid_object("file", "heads"))?;
+    let head_ids: Vec<_> = heads
+        .lines()
+        .map(|hex| OperationId::try_from_hex(hex).map_err(|_| invalid_object("operation", hex)))
+        .try_collect()?;
+    for id in &head_ids {
+        op_store.read_operation(id)?;
+    }
+    Ok(FetchedOperations {
+        head_ids,
+        num_imported,
+    })
+}
+
+#[derive(Error, Debug)]
+pub enum GitApplyError {
+    #[error("Failed to parse the patch")]
+    InvalidPatch(#[source] git2::Error),
+    #[error("Invalid path in the patch: {0}")]
+    InvalidPath(String),
+    #[error("Patch does not apply to {}", .0.as_internal_file_string())]
+    DoesNotApply(RepoPathBuf),
+    #[error("Path {} in the patch already exists", .0.as_internal_file_string())]
+    AlreadyExists(RepoPathBuf),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    #[error("Unexpected git error when applying patch")]
+    InternalGitError(#[from] git2::Error),
+}
+
+/// The result of applying a patch with `apply_patch()`.
+#[derive(Debug)]
+pub struct AppliedPatch {
+    /// The tree with the patch applied. It has conflicts if a three-way merge
+    /// failed.
+    pub tree: MergedTree,
+    /// Paths that were merged with the preimage of the patch because the patch
+    /// didn't apply cleanly.
+    pub merged_paths: Vec<RepoPathBuf>,
+    /// Merged paths that ended up conflicted.
+    pub conflicted_paths: Vec<RepoPathBuf>,
+}
+
+/// A file patch that didn't apply to the tree, but applied to the preimage
+/// recorded in the patch.
+struct ThreeWayPatch {
+    old_path: Option<RepoPathBuf>,
+    new_path: Option<RepoPathBuf>,
+    preimage: Option<TreeValue>,
+    postimage: Option<TreeValue>,
+}
+
+/// Applies a patch in the format of `git diff` to the tree, like `git apply`.
+/// Binary patches, renames, and mode changes are supported. Traditional
+/// unified diffs are converted to Git's format first.
+///
+/// Each file patch is applied to the file in the tree. If that fails, the
+/// patch is applied to the preimage blob recorded in the `index` line of the
+/// patch instead, if the blob exists in the repo, and the result is merged
+/// into the tree like `git apply --3way`.
+pub fn apply_patch(
+    git_repo: &git2::Repository,
+    tree: &MergedTree,
+    patch: &[u8],
+) -> Result<AppliedPatch, GitApplyError> {
+    let patch = to_git_patch(patch);
+    let diff = git2::Diff::from_buffer(&patch).map_err(GitApplyError::InvalidPatch)?;
+    let preimage_ids = parse_preimage_ids(&patch);
+    let store = tree.store();
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    let mut three_way_patches = vec![];
+    for (index, delta) in diff.deltas().enumerate() {
+        let old_path = match delta.status() {
+            git2::Delta::Added => None,
+            _ => delta.old_file().path().map(patch_repo_path).transpose()?,
+        };
+        let new_path = match delta.status() {
+            git2::Delta::Deleted => None,
+            _ => delta.new_file().path().map(patch_repo_path).transpose()?,
+        };
+        let path = old_path.as_ref().or(new_path.as_ref()).unwrap();
+        if let Some(new_path) = new_path
+            .as_ref()
+            .filter(|&path| Some(path) != old_path.as_ref())
+        {
+            if tree.path_value(new_path)?.is_present() {
+                return Err(GitApplyError::AlreadyExists(new_path.clone()));
+            }
+        }
+        let current = match &old_path {
+            Some(old_path) => tree.path_value(old_path)?.as_resolved().cloned(),
+            None => Some(None),
+        };
+        if let Some(current) = current {
+            let applied = apply_file_patch(
+                git_repo,
+                &diff,
+                index,
+                old_path.as_deref(),
+                current.as_ref(),
+                new_path.as_deref(),
+            )?;
+            if let Some(postimage) = applied {
+                if let Some(old_path) = &old_path {
+                    tree_builder.set_or_remove(old_path.clone(), Merge::absent());
+                }
+                if let Some(new_path) = &new_path {
+                    tree_builder.set_or_remove(new_path.clone(), Merge::resolved(postimage));
+                }
+                continue;
+            }
+        }
+        let Some(preimage_id) = preimage_ids.get(index).copied().flatten() else {
+            return Err(GitApplyError::DoesNotApply(path.clone()));
+        };
+        let Ok(preimage_blob) =
+            git_repo.find_object_by_prefix(preimage_id, Some(git2::ObjectType::Blob))
+        else {
+            return Err(GitApplyError::DoesNotApply(path.clone()));
+        };
+        let preimage = tree_value_from_git(preimage_blob.id(), delta.old_file().mode().into());
+        let Some(postimage) = apply_file_patch(
+            git_repo,
+            &diff,
+            index,
+            old_path.as_deref(),
+            Some(&preimage),
+            new_path.as_deref(),
+        )?
+        else {
+            return Err(GitApplyError::DoesNotApply(path.clone()));
+        };
+        three_way_patches.push(ThreeWayPatch {
+            old_path,
+            new_path,
+            preimage: Some(preimage),
+            postimage,
+        });
+    }
+
+    let tree_id = tree_builder.write_tree(store)?;
+    let mut tree = store.get_root_tree(&tree_id)?;
+    let mut merged_paths = vec![];
+    let mut conflicted_paths = vec![];
+    if !three_way_patches.is_empty() {
+        let mut base_builder = MergedTreeBuilder::new(tree_id.clone());
+        let mut other_builder = MergedTreeBuilder::new(tree_id);
+        for patch in &three_way_patches {
+            if let Some(old_path) = &patch.old_path {
+                base_builder
+                    .set_or_remove(old_path.clone(), Merge::resolved(patch.preimage.clone()));
+                other_builder.set_or_remove(old_path.clone(), Merge::absent());
+            }
+            if let Some(new_path) = &patch.new_path {
+                if patch.old_path.as_ref() != Some(new_path) {
+                    base_builder.set_or_remove(new_path.clone(), Merge::absent());
+                }
+                other_builder
+                    .set_or_remove(new_path.clone(), Merge::resolved(patch.postimage.clone()));
+            }
+        }
+        let base_tree = store.get_root_tree(&base_builder.write_tree(store)?)?;
+        let other_tree = store.get_root_tree(&other_builder.write_tree(store)?)?;
+        tree = tree.merge(&base_tree, &other_tree)?;
+        for patch in three_way_patches {
+            for path in [patch.old_path, patch.new_path]
+                .into_iter()
+                .flatten()
+                .dedup()
+            {
+                if !tree.path_value(&path)?.is_resolved() {
+                    conflicted_paths.push(path.clone());
+                }
+                merged_paths.push(path);
+            }
+        }
+    }
+    Ok(AppliedPatch {
+        tree,
+        merged_paths,
+        conflicted_paths,
+    })
+}
+
+fn patch_repo_path(path: &Path) -> Result<RepoPathBuf, GitApplyError> {
+    RepoPathBuf::from_relative_path(path)
+        .map_err(|_| GitApplyError::InvalidPath(path.display().to_string()))
+}
+
+/// Applies the `index`th file patch of the diff to a tree containing only the
+/// given value at `old_path`. Returns the value at `new_path` afterwards, or
+/// `None` if the patch didn't apply.
+fn apply_file_patch(
+    git_repo: &git2::Repository,
+    diff: &git2::Diff,
+    index: usize,
+    old_path: Option<&RepoPath>,
+    old_value: Option<&TreeValue>,
+    new_path: Option<&RepoPath>,
+) -> Result<Option<Option<TreeValue>>, git2::Error> {
+    let empty_tree = git_repo.find_tree(git_repo.treebuilder(None)?.write()?)?;
+    let mut tree_update = git2::build::TreeUpdateBuilder::new();
+    if let (Some(path), Some(value)) = (old_path, old_value) {
+        let (oid, mode) = match value {
+            TreeValue::File { id, executable } => (
+                Oid::from_bytes(id.as_bytes()).unwrap(),
+                if *executable {
+                    git2::FileMode::BlobExecutable
+                } else {
+                    git2::FileMode::Blob
+                },
+            ),
+            TreeValue::Symlink(id) => (
+                Oid::from_bytes(id.as_bytes()).unwrap(),
+                git2::FileMode::Link,
+            ),
+            TreeValue::GitSubmodule(id) => (
+                Oid::from_bytes(id.as_bytes()).unwrap(),
+                git2::FileMode::Commit,
+            ),
+            // A patch doesn't apply to a directory
+            TreeValue::Tree(_) | TreeValue::Conflict(_) => return Ok(None),
+        };
+        tree_update.upsert(path.as_internal_file_string(), oid, mode);
+    }
+    // libgit2 removes the old paths of all deleted and renamed files from the
+    // result, even if their file patches are filtered out, and fails if the
+    // paths don't exist. Add placeholders for them.
+    let placeholder_id = git_repo.blob(b"")?;
+    for (i, delta) in diff.deltas().enumerate() {
+        if i == index || !matches!(delta.status(), git2::Delta::Deleted | git2::Delta::Renamed) {
+            continue;
+        }
+        if let Some(path) = delta.old_file().path() {
+            if Some(path) != old_path.map(|path| Path::new(path.as_internal_file_string())) {
+                tree_update.upsert(path, placeholder_id, git2::FileMode::Blob);
+            }
+        }
+    }
+    let scratch_tree = git_repo.find_tree(tree_update.create_updated(git_repo, &empty_tree)?)?;
+    let mut delta_index = 0;
+    let mut options = git2::ApplyOptions::new();
+    options.delta_callback(|_| {
+        let selected = delta_index == index;
+        delta_index += 1;
+        selected
+    });
+    let result = match git_repo.apply_to_tree(&scratch_tree, diff, Some(&mut options)) {
+        Ok(result) => result,
+        Err(err) if err.code() == git2::ErrorCode::ApplyFail => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let new_value = new_path.and_then(|path| {
+        let entry = result.get_path(Path::new(path.as_internal_file_string()), 0)?;
+        Some(tree_value_from_git(entry.id, entry.mode as i32))
+    });
+    Ok(Some(new_value))
+}
+
+fn tree_value_from_git(oid: Oid, mode: i32) -> TreeValue {
+    match mode {
+        0o120000 => TreeValue::Symlink(SymlinkId::from_bytes(oid.as_bytes())),
+        0o160000 => TreeValue::GitSubmodule(CommitId::from_bytes(oid.as_bytes())),
+        mode => TreeValue::File {
+            id: FileId::from_bytes(oid.as_bytes()),
+            executable: mode & 0o111 != 0,
+        },
+    }
+}
+
+/// Returns the abbreviated ids of the preimage blobs from the `index` lines of
+/// the patch, in the order of the file patches. The id is `None` for added
+/// files and file patches without an `index` line.
+fn parse_preimage_ids(patch: &[u8]) -> Vec<Option<&str>> {
+    let mut ids = vec![];
+    let mut in_header = false;
+    for line in patch.split(|&b| b == b'\n') {
+        if line.starts_with(b"diff --git ") {
+            ids.push(None);
+            in_header = true;
+        } else if line.starts_with(b"@@ ") || line.starts_with(b"GIT binary patch") {
+            in_header = false;
+        } else if let Some(range) = line.strip_prefix(b"index ").filter(|_| in_header) {
+            let old_id = str::from_utf8(range)
+                .ok()
+                .and_then(|range| range.split_once(".."))
+                .map(|(old_id, _)| old_id)
+                .filter(|old_id| old_id.bytes().any(|b| b != b'0'));
+            *ids.last_mut().unwrap() = old_id;
+        }
+    }
+    ids
+}
+
+/// Converts a traditional unified diff, as produced by `diff -u`, to Git's
+/// format by adding `diff --git` headers. As with `git apply`, the first
+/// component of the paths is stripped. Patches that already have Git headers
+/// are returned as is.
+fn to_git_patch(patch: &[u8]) -> Cow<'_, [u8]> {
+    let lines = patch.split_inclusive(|&b| b == b'\n').collect_vec();
+    if lines.iter().any(|line| line.starts_with(b"diff --git ")) {
+        return Cow::Borrowed(patch);
+    }
+    let header_path = |line: &[u8], prefix: &[u8]| -> Option<String> {
+        let path = line.strip_prefix(prefix)?;
+        let path = String::from_utf8_lossy(path);
+        // Drop the timestamp
+        let path = path.split('\t').next().unwrap().trim_end();
+        if path == "/dev/null" {
+            return Some(path.to_owned());
+        }
+        Some(
+            path.split_once('/')
+                .map_or(path, |(_, rest)| rest)
+                .to_owned(),
+        )
+    };
+    let mut converted = Vec::with_capacity(patch.len());
+    let mut lines = lines.into_iter().peekable();
+    while let Some(line) = lines.next() {
+        let paths = header_path(line, b"--- ").and_then(|old_path| {
+            let new_path = header_path(lines.peek()?, b"+++ ")?;
+            Some((old_path, new_path))
+        });
+        let Some((old_path, new_path)) = paths else {
+            converted.extend_from_slice(line);
+            continue;
+        };
+        lines.next();
+        let path = if new_path == "/dev/null" {
+            &old_path
+        } else {
+            &new_path
+        };
+        let mut header = format!("diff --git a/{path} b/{path}\n");
+        if old_path == "/dev/null" {
+            header.push_str("new file mode 100644\n--- /dev/null\n");
+        } else if new_path == "/dev/null" {
+            header.push_str(&format!("deleted file mode 100644\n--- a/{path}\n"));
+        } else {
+            header.push_str(&format!("--- a/{path}\n"));
+        }
+        if new_path == "/dev/null" {
+            header.push_str("+++ /dev/null\n");
+        } else {
+            header.push_str(&format!("+++ b/{path}\n"));
+        }
+        converted.extend_from_slice(header.as_bytes());
+    }
+    Cow::Owned(converted)
+}
+
 #[non_exhaustive]
 #[derive(Default)]
 #[allow(clippy::type_complexity)]
@@ -1625,18 +3668,87 @@ pub struct RemoteCallbacks<'a> {
 }
 
 impl<'a> RemoteCallbacks<'a> {
+    /// Reborrows the callbacks so they can be used for more than one remote
+    /// operation.
+    fn reborrow(&mut self) -> RemoteCallbacks<'_> {
+        RemoteCallbacks {
+            progress: self
+                .progress
+                .as_mut()
+                .map(|cb| &mut **cb as &mut dyn FnMut(&Progress)),
+            sideband_progress: self
+                .sideband_progress
+                .as_mut()
+                .map(|cb| &mut **cb as &mut dyn FnMut(&[u8])),
+            get_ssh_keys: self
+                .get_ssh_keys
+                .as_mut()
+                .map(|cb| &mut **cb as &mut dyn FnMut(&str) -> Vec<PathBuf>),
+            get_password: self
+                .get_password
+                .as_mut()
+                .map(|cb| &mut **cb as &mut dyn FnMut(&str, &str) -> Option<String>),
+            get_username_password: self
+                .get_username_password
+                .as_mut()
+                .map(|cb| &mut **cb as &mut dyn FnMut(&str) -> Option<(String, String)>),
+        }
+    }
+
     fn into_git(mut self) -> git2::RemoteCallbacks<'a> {
         let mut callbacks = git2::RemoteCallbacks::new();
         if let Some(progress_cb) = self.progress {
+            // The callback is shared by the fetch and push progress callbacks.
+            let progress_cb = Rc::new(RefCell::new(progress_cb));
+            let transfer_progress_cb = progress_cb.clone();
             callbacks.transfer_progress(move |progress| {
-                progress_cb(&Progress {
-                    bytes_downloaded: (progress.received_objects() < progress.total_objects())
-                        .then(|| progress.received_bytes() as u64),
+                let receiving = progress.received_objects() < progress.total_objects();
+                let (phase, objects) = if receiving {
+                    let objects = (progress.received_objects(), progress.total_objects());
+                    (ProgressPhase::Receiving, objects)
+                } else {
+                    let objects = (progress.indexed_deltas(), progress.total_deltas());
+                    (ProgressPhase::Resolving, objects)
+                };
+                (*transfer_progress_cb.borrow_mut())(&Progress {
+                    bytes_transferred: receiving.then(|| progress.received_bytes() as u64),
                     overall: (progress.indexed_objects() + progress.indexed_deltas()) as f32
                         / (progress.total_objects() + progress.total_deltas()) as f32,
+                    phase,
+                    objects,
                 });
                 true
             });
+            let pack_progress_cb = progress_cb.clone();
+            callbacks.pack_progress(move |stage, current, total| {
+                let phase = match stage {
+                    git2::PackBuilderStage::AddingObjects => ProgressPhase::Counting,
+                    git2::PackBuilderStage::Deltafication => ProgressPhase::Compressing,
+                };
+                (*pack_progress_cb.borrow_mut())(&Progress {
+                    bytes_transferred: None,
+                    // The total isn't known while counting.
+                    overall: if total > 0 {
+                        current as f32 / total as f32
+                    } else {
+                        0.0
+                    },
+                    phase,
+                    objects: (current, total),
+                });
+            });
+            callbacks.push_transfer_progress(move |current, total, bytes| {
+                (*progress_cb.borrow_mut())(&Progress {
+                    bytes_transferred: Some(bytes as u64),
+                    overall: if total > 0 {
+                        current as f32 / total as f32
+                    } else {
+                        1.0
+                    },
+                    phase: ProgressPhase::Sending,
+                    objects: (current, total),
+                });
+            });
         }
         if let Some(sideband_progress_cb) = self.sideband_progress {
             callbacks.sideband_progress(move |data| {
@@ -1658,6 +3770,14 @@ impl<'a> RemoteCallbacks<'a> {
             if let Ok(creds) = credential_helper {
                 tracing::info!("using credential_helper");
                 return Ok(creds);
+            } else if username_from_url.is_none()
+                && allowed_types.contains(git2::CredentialType::USERNAME)
+            {
+                // SSH URL without username. Like OpenSSH, default to the local
+                // user name. We'll be asked for the key with this username next.
+                let username = whoami::username();
+                tracing::info!(username, "using local username");
+                return git2::Cred::username(&username);
             } else if let Some(username) = username_from_url {
                 if allowed_types.contains(git2::CredentialType::SSH_KEY) {
                     // Try to get the SSH key from the agent once. We don't even check if
@@ -1723,8 +3843,28 @@ impl<'a> RemoteCallbacks<'a> {
 
 pub struct Progress {
     /// `Some` iff data transfer is currently in progress
-    pub bytes_downloaded: Option<u64>,
+    pub bytes_transferred: Option<u64>,
+    /// Progress of the current phase, from 0.0 to 1.0
     pub overall: f32,
+    pub phase: ProgressPhase,
+    /// Number of objects processed in the current phase, and the total number
+    /// of objects, which is 0 if unknown
+    pub objects: (usize, usize),
+}
+
+/// What a fetch or push is currently doing.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProgressPhase {
+    /// Receiving objects from the remote.
+    Receiving,
+    /// Resolving the deltas of the received objects.
+    Resolving,
+    /// Counting the objects to send to the remote.
+    Counting,
+    /// Compressing the objects to send to the remote.
+    Compressing,
+    /// Sending objects to the remote.
+    Sending,
 }
 
 #[derive(Default)]
@@ -1806,3 +3946,143 @@ pub fn parse_gitmodules(
         .collect();
     Ok(ret)
 }
+
+#[derive(Error, Debug)]
+pub enum GitSubmoduleError {
+    #[error("Commit {} of submodule {name} was not found in {url}", .commit_id.hex())]
+    CommitNotFound {
+        name: String,
+        url: String,
+        commit_id: CommitId,
+    },
+    #[error("Submodule path {} exists and is not a checkout of the submodule", .0.display())]
+    PathExists(PathBuf),
+    #[error(transparent)]
+    Path(#[from] PathError),
+    #[error("Unexpected git error when updating submodule")]
+    InternalGitError(#[from] git2::Error),
+}
+
+/// Returns the path of the Git repo of the submodule, which is stored in the
+/// `modules` directory of the superproject's Git repo like Git does.
+fn submodule_git_dir(git_repo: &git2::Repository, submodule: &SubmoduleConfig) -> PathBuf {
+    git_repo.path().join("modules").join(&submodule.name)
+}
+
+/// Resolves the URL of the submodule. URLs starting with `./` or `../` are
+/// relative to the URL of the superproject's `origin` remote.
+fn resolve_submodule_url(git_repo: &git2::Repository, url: &str) -> String {
+    if !url.starts_with("./") && !url.starts_with("../") {
+        return url.to_owned();
+    }
+    let Some(base_url) = git_repo
+        .find_remote("origin")
+        .ok()
+        .and_then(|remote| remote.url().map(ToOwned::to_owned))
+    else {
+        return url.to_owned();
+    };
+    let mut base = base_url.trim_end_matches('/').to_owned();
+    let mut rest = url;
+    loop {
+        if let Some(stripped) = rest.strip_prefix("./") {
+            rest = stripped;
+        } else if let Some(stripped) = rest.strip_prefix("../") {
+            rest = stripped;
+            base.truncate(base.rfind('/').unwrap_or(0));
+        } else {
+            break;
+        }
+    }
+    format!("{base}/{rest}")
+}
+
+/// Returns the commit checked out in the working copy of the submodule, or
+/// `None` if the submodule isn't checked out.
+pub fn get_submodule_head(
+    git_repo: &git2::Repository,
+    submodule: &SubmoduleConfig,
+) -> Result<Option<CommitId>, GitSubmoduleError> {
+    let git_dir = submodule_git_dir(git_repo, submodule);
+    if !git_dir.exists() {
+        return Ok(None);
+    }
+    let submodule_repo = git2::Repository::open(&git_dir)?;
+    let head = match submodule_repo.head() {
+        Ok(head) => head,
+        Err(err) if err.code() == git2::ErrorCode::UnbornBranch => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let commit = head.peel_to_commit()?;
+    Ok(Some(CommitId::from_bytes(commit.id().as_bytes())))
+}
+
+/// Checks out `commit_id` in the working copy of the submodule at `workdir`.
+///
+/// The submodule is cloned into the `modules` directory of the superproject's
+/// Git repo first if needed, and fetched from its URL if the commit is missing.
+/// Changes in the working copy of the submodule are overwritten.
+pub fn update_submodule(
+    git_repo: &git2::Repository,
+    submodule: &SubmoduleConfig,
+    workdir: &Path,
+    commit_id: &CommitId,
+    mut callbacks: RemoteCallbacks<'_>,
+) -> Result<(), GitSubmoduleError> {
+    let git_dir = submodule_git_dir(git_repo, submodule);
+    let submodule_repo = if git_dir.exists() {
+        // Recreate the working copy if it was removed. The repo can't be
+        // opened if its working copy doesn't exist.
+        let recreate = !workdir.join(".git").exists();
+        if recreate {
+            fs::create_dir_all(workdir).context(workdir)?;
+        }
+        let submodule_repo = git2::Repository::open(&git_dir)?;
+        if recreate {
+            submodule_repo.set_workdir(workdir, true)?;
+        }
+        submodule_repo
+    } else {
+        if workdir
+            .read_dir()
+            .is_ok_and(|mut entries| entries.next().is_some())
+        {
+            return Err(GitSubmoduleError::PathExists(workdir.to_owned()));
+        }
+        fs::create_dir_all(workdir).context(workdir)?;
+        let url = resolve_submodule_url(git_repo, &submodule.url);
+        // The working copy gets a `.git` file pointing to the repo.
+        git2::Repository::init_opts(
+            &git_dir,
+            git2::RepositoryInitOptions::new()
+                .workdir_path(workdir)
+                .origin_url(&url),
+        )?
+    };
+    let oid = Oid::from_bytes(commit_id.as_bytes()).unwrap();
+    if submodule_repo.find_commit(oid).is_err() {
+        let mut remote = submodule_repo.find_remote("origin")?;
+        let mut fetch = |refspecs: &[&str]| {
+            let mut fetch_options = git2::FetchOptions::new();
+            fetch_options.remote_callbacks(callbacks.reborrow().into_git());
+            remote.fetch(refspecs, Some(&mut fetch_options), None)
+        };
+        fetch(&["+refs/heads/*:refs/remotes/origin/*"])?;
+        // The commit may not be reachable from any branch. Not all servers
+        // allow fetching it by ID, which is reported below.
+        if submodule_repo.find_commit(oid).is_err() {
+            fetch(&[&commit_id.hex()]).ok();
+        }
+    }
+    if submodule_repo.find_commit(oid).is_err() {
+        let remote = submodule_repo.find_remote("origin")?;
+        return Err(GitSubmoduleError::CommitNotFound {
+            name: submodule.name.clone(),
+            url: remote.url().unwrap_or_default().to_owned(),
+            commit_id: commit_id.clone(),
+        });
+    }
+    submodule_repo.set_head_detached(oid)?;
+    submodule_repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))?;
+    Ok(())
+}
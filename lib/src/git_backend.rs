@@ -15,6 +15,8 @@
 #![allow(missing_docs)]
 
 use std::any::Any;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fmt::Error;
@@ -23,6 +25,7 @@ use std::fs;
 use std::io;
 use std::io::Cursor;
 use std::io::Read;
+use std::io::Write as _;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
@@ -43,6 +46,7 @@ use itertools::Itertools;
 use pollster::FutureExt;
 use prost::Message;
 use smallvec::SmallVec;
+use tempfile::NamedTempFile;
 use thiserror::Error;
 
 use crate::backend::make_root_commit;
@@ -61,6 +65,7 @@ use crate::backend::CopyRecord;
 use crate::backend::FileId;
 use crate::backend::MergedTreeId;
 use crate::backend::MillisSinceEpoch;
+use crate::backend::RecordedCopy;
 use crate::backend::SecureSig;
 use crate::backend::Signature;
 use crate::backend::SigningFn;
@@ -90,6 +95,14 @@ const HASH_LENGTH: usize = 20;
 const CHANGE_ID_LENGTH: usize = 16;
 /// Ref namespace used only for preventing GC.
 const NO_GC_REF_NAMESPACE: &str = "refs/jj/keep/";
+/// Ref namespace of the commits replaced by `git replace`.
+const REPLACE_REF_NAMESPACE: &str = "refs/replace/";
+/// File in the store directory recording the replacements the commit index was
+/// built with.
+const REPLACEMENTS_FILE: &str = "git_replacements";
+/// File in the store directory recording the shallow commits the commit index
+/// was built with.
+const SHALLOW_FILE: &str = "git_shallow";
 const CONFLICT_SUFFIX: &str = ".jjconflict";
 
 const JJ_TREES_COMMIT_HEADER: &[u8] = b"jj:trees";
@@ -100,6 +113,8 @@ pub enum GitBackendInitError {
     InitRepository(#[source] gix::init::Error),
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
+    #[error("Failed to read git replace refs")]
+    ReadReplaceRefs(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error(transparent)]
     Path(PathError),
 }
@@ -114,6 +129,8 @@ impl From<Box<GitBackendInitError>> for BackendInitError {
 pub enum GitBackendLoadError {
     #[error("Failed to open git repository")]
     OpenRepository(#[source] gix::open::Error),
+    #[error("Failed to read git replace refs")]
+    ReadReplaceRefs(#[source] Box<dyn std::error::Error + Send + Sync>),
     #[error(transparent)]
     Path(PathError),
 }
@@ -147,6 +164,14 @@ pub enum GitGcError {
     GcCommandErrorStatus(ExitStatus),
 }
 
+#[derive(Debug, Error)]
+pub enum GitLazyFetchError {
+    #[error("Failed to run git cat-file command")]
+    CatFileCommand(#[source] std::io::Error),
+    #[error("git cat-file command exited with an error: {0}")]
+    CatFileCommandErrorStatus(ExitStatus),
+}
+
 pub struct GitBackend {
     // While gix::Repository can be created from gix::ThreadSafeRepository, it's
     // cheaper to cache the thread-local instance behind a mutex than creating
@@ -159,6 +184,27 @@ pub struct GitBackend {
     empty_tree_id: TreeId,
     extra_metadata_store: TableStore,
     cached_extra_metadata: Mutex<Option<Arc<ReadonlyTable>>>,
+    store_path: PathBuf,
+    /// Commits replaced by `refs/replace/*` refs, and their replacements.
+    replacements: BTreeMap<CommitId, CommitId>,
+    /// Set while writes are batched.
+    write_batch: Mutex<Option<WriteBatch>>,
+}
+
+/// Writes buffered between `start_write_batch()` and `finish_write_batch()`.
+#[derive(Default)]
+struct WriteBatch {
+    /// Created by the first write of the batch, so the table lock is only
+    /// held by batches that write something.
+    pending: Option<PendingMetadata>,
+}
+
+/// Extra metadata entries to be saved, and the commits to create no-gc refs
+/// for.
+struct PendingMetadata {
+    mut_table: MutableTable,
+    table_lock: FileLock,
+    no_gc_ids: Vec<CommitId>,
 }
 
 impl GitBackend {
@@ -166,7 +212,12 @@ impl GitBackend {
         "git"
     }
 
-    fn new(base_repo: gix::ThreadSafeRepository, extra_metadata_store: TableStore) -> Self {
+    fn new(
+        base_repo: gix::ThreadSafeRepository,
+        extra_metadata_store: TableStore,
+        store_path: &Path,
+        replacements: BTreeMap<CommitId, CommitId>,
+    ) -> Self {
         let repo = Mutex::new(base_repo.to_thread_local());
         let root_commit_id = CommitId::from_bytes(&[0; HASH_LENGTH]);
         let root_change_id = ChangeId::from_bytes(&[0; CHANGE_ID_LENGTH]);
@@ -179,6 +230,9 @@ impl GitBackend {
             empty_tree_id,
             extra_metadata_store,
             cached_extra_metadata: Mutex::new(None),
+            store_path: store_path.to_owned(),
+            replacements,
+            write_batch: Mutex::new(None),
         }
     }
 
@@ -271,7 +325,14 @@ impl GitBackend {
                 .map_err(GitBackendInitError::Path)?;
         };
         let extra_metadata_store = TableStore::init(extra_path, HASH_LENGTH);
-        Ok(GitBackend::new(git_repo, extra_metadata_store))
+        let replacements = read_replace_refs(&git_repo.to_thread_local())
+            .map_err(GitBackendInitError::ReadReplaceRefs)?;
+        Ok(GitBackend::new(
+            git_repo,
+            extra_metadata_store,
+            store_path,
+            replacements,
+        ))
     }
 
     pub fn load(
@@ -294,7 +355,14 @@ impl GitBackend {
         )
         .map_err(GitBackendLoadError::OpenRepository)?;
         let extra_metadata_store = TableStore::load(store_path.join("extra"), HASH_LENGTH);
-        Ok(GitBackend::new(repo, extra_metadata_store))
+        let replacements = read_replace_refs(&repo.to_thread_local())
+            .map_err(GitBackendLoadError::ReadReplaceRefs)?;
+        Ok(GitBackend::new(
+            repo,
+            extra_metadata_store,
+            store_path,
+            replacements,
+        ))
     }
 
     fn lock_git_repo(&self) -> MutexGuard<'_, gix::Repository> {
@@ -306,6 +374,40 @@ impl GitBackend {
         self.base_repo.to_thread_local()
     }
 
+    /// Commits replaced by `refs/replace/*` refs, and their replacements, as
+    /// of when the backend was loaded.
+    pub fn replacements(&self) -> &BTreeMap<CommitId, CommitId> {
+        &self.replacements
+    }
+
+    /// Returns true if the replacements or the shallow commits differ from the
+    /// ones recorded by `record_indexed_state()`. The commit index caches the
+    /// parents of the commits, which depend on both, so it must then be
+    /// rebuilt.
+    pub fn indexed_state_changed(&self) -> Result<bool, PathError> {
+        let recorded_replacements = read_replacements(&self.store_path.join(REPLACEMENTS_FILE))?;
+        let recorded_shallow_commits = read_shallow_file(&self.store_path.join(SHALLOW_FILE))?;
+        let shallow_commits = read_shallow_file(&self.git_repo_path().join("shallow"))?;
+        Ok(recorded_replacements != self.replacements
+            || recorded_shallow_commits != shallow_commits)
+    }
+
+    /// Records the replacements and the shallow commits the commit index is
+    /// built with.
+    pub fn record_indexed_state(&self) -> Result<(), PathError> {
+        let replacements: String = self
+            .replacements
+            .iter()
+            .map(|(original, replacement)| format!("{} {}\n", original.hex(), replacement.hex()))
+            .collect();
+        write_file_atomically(&self.store_path.join(REPLACEMENTS_FILE), &replacements)?;
+        let shallow_commits: String = read_shallow_file(&self.git_repo_path().join("shallow"))?
+            .iter()
+            .map(|id| format!("{id}\n"))
+            .collect();
+        write_file_atomically(&self.store_path.join(SHALLOW_FILE), &shallow_commits)
+    }
+
     /// Creates new owned git repository instance.
     pub fn open_git_repo(&self) -> Result<git2::Repository, git2::Error> {
         git2::Repository::open(self.git_repo_path())
@@ -359,6 +461,67 @@ impl GitBackend {
         Ok(())
     }
 
+    fn start_pending_metadata(&self) -> BackendResult<PendingMetadata> {
+        let (table, table_lock) = self.read_extra_metadata_table_locked()?;
+        Ok(PendingMetadata {
+            mut_table: table.start_mutation(),
+            table_lock,
+            no_gc_ids: vec![],
+        })
+    }
+
+    fn save_pending_metadata(
+        &self,
+        git_repo: &gix::Repository,
+        pending: PendingMetadata,
+    ) -> BackendResult<()> {
+        // The no-gc refs are created first so the objects are kept even if
+        // saving the table fails.
+        if !pending.no_gc_ids.is_empty() {
+            git_repo
+                .edit_references(pending.no_gc_ids.iter().map(to_no_gc_ref_update))
+                .map_err(|err| BackendError::Other(Box::new(err)))?;
+        }
+        self.save_extra_metadata_table(pending.mut_table, &pending.table_lock)
+    }
+
+    /// Calls `f` with the extra metadata to be saved. Within a write batch,
+    /// the metadata is saved when the batch is finished, otherwise right away.
+    fn update_extra_metadata<T>(
+        &self,
+        git_repo: &gix::Repository,
+        f: impl FnOnce(&mut PendingMetadata) -> BackendResult<T>,
+    ) -> BackendResult<T> {
+        let mut locked_batch = self.write_batch.lock().unwrap();
+        if let Some(batch) = locked_batch.as_mut() {
+            if batch.pending.is_none() {
+                batch.pending = Some(self.start_pending_metadata()?);
+            }
+            return f(batch.pending.as_mut().unwrap());
+        }
+        drop(locked_batch);
+        let mut pending = self.start_pending_metadata()?;
+        let value = f(&mut pending)?;
+        self.save_pending_metadata(git_repo, pending)?;
+        Ok(value)
+    }
+
+    /// Looks up the extra metadata of a commit, including the metadata of
+    /// commits written by the current write batch.
+    fn read_extra_metadata(&self, id: &CommitId) -> BackendResult<Option<Vec<u8>>> {
+        if let Some(batch) = self.write_batch.lock().unwrap().as_ref() {
+            let pending_value = batch
+                .pending
+                .as_ref()
+                .and_then(|pending| pending.mut_table.get_value(id.as_bytes()));
+            if let Some(value) = pending_value {
+                return Ok(Some(value.to_vec()));
+            }
+        }
+        let table = self.cached_extra_metadata_table()?;
+        Ok(table.get_value(id.as_bytes()).map(|value| value.to_vec()))
+    }
+
     /// Imports the given commits and ancestors from the backing Git repo.
     ///
     /// The `head_ids` may contain commits that have already been imported, but
@@ -397,27 +560,43 @@ impl GitBackend {
             heads_count = head_ids.len(),
             "import extra metadata entries"
         );
-        let (table, table_lock) = self.read_extra_metadata_table_locked()?;
-        let mut mut_table = table.start_mutation();
-        import_extra_metadata_entries_from_heads(
-            &locked_repo,
-            &mut mut_table,
-            &table_lock,
-            &head_ids,
-            uses_tree_conflict_format,
-        )?;
-        self.save_extra_metadata_table(mut_table, &table_lock)
+        self.update_extra_metadata(&locked_repo, |pending| {
+            import_extra_metadata_entries_from_heads(
+                &locked_repo,
+                &mut pending.mut_table,
+                &pending.table_lock,
+                &head_ids,
+                uses_tree_conflict_format,
+            )
+        })
     }
 
     fn read_file_sync(&self, id: &FileId) -> BackendResult<Box<dyn Read>> {
         let git_blob_id = validate_git_object_id(id)?;
-        let locked_repo = self.lock_git_repo();
-        let mut blob = locked_repo
-            .find_object(git_blob_id)
-            .map_err(|err| map_not_found_err(err, id))?
-            .try_into_blob()
-            .map_err(|err| to_read_object_err(err, id))?;
-        Ok(Box::new(Cursor::new(blob.take_data())))
+        {
+            let locked_repo = self.lock_git_repo();
+            match locked_repo.find_object(git_blob_id) {
+                Ok(git_object) => {
+                    let mut blob = git_object
+                        .try_into_blob()
+                        .map_err(|err| to_read_object_err(err, id))?;
+                    return Ok(Box::new(Cursor::new(blob.take_data())));
+                }
+                Err(gix::object::find::existing::Error::NotFound { .. })
+                    if is_partial_clone(&locked_repo) => {}
+                Err(err) => return Err(map_not_found_err(err, id)),
+            };
+        }
+        // Blobs may be omitted from a partial clone. Let git fetch the missing
+        // blob from the promisor remote.
+        let data = run_git_cat_file_blob(self.git_repo_path(), git_blob_id).map_err(|err| {
+            BackendError::ReadObject {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source: err.into(),
+            }
+        })?;
+        Ok(Box::new(Cursor::new(data)))
     }
 
     fn new_diff_platform(&self) -> BackendResult<gix::diff::blob::Platform> {
@@ -481,6 +660,7 @@ pub fn canonicalize_git_repo_path(path: &Path) -> io::Result<PathBuf> {
 fn gix_open_opts_from_settings(settings: &UserSettings) -> gix::open::Options {
     let user_name = settings.user_name();
     let user_email = settings.user_email();
+    let object_cache_size = settings.git_settings().object_cache_size;
     gix::open::Options::default()
         .config_overrides([
             // Committer has to be configured to record reflog. Author isn't
@@ -489,6 +669,10 @@ fn gix_open_opts_from_settings(settings: &UserSettings) -> gix::open::Options {
             format!("author.email={user_email}"),
             format!("committer.name={user_name}"),
             format!("committer.email={user_email}"),
+            // Decompressed objects are kept in an LRU cache, so objects that are
+            // read repeatedly don't have to be resolved from their delta chains
+            // again.
+            format!("gitoxide.objects.cacheLimit={object_cache_size}"),
         ])
         // The git_target path should point the repository, not the working directory.
         .open_path_as_is(true)
@@ -590,10 +774,11 @@ fn commit_from_git_without_root_parent(
         author,
         committer,
         secure_sig,
+        recorded_copies: vec![],
     })
 }
 
-const EMPTY_STRING_PLACEHOLDER: &str = "JJ_EMPTY_STRING";
+pub(crate) const EMPTY_STRING_PLACEHOLDER: &str = "JJ_EMPTY_STRING";
 
 fn signature_from_git(signature: gix::actor::SignatureRef) -> Signature {
     let name = signature.name;
@@ -620,7 +805,7 @@ fn signature_from_git(signature: gix::actor::SignatureRef) -> Signature {
     }
 }
 
-fn signature_to_git(signature: &Signature) -> gix::actor::SignatureRef<'_> {
+pub(crate) fn signature_to_git(signature: &Signature) -> gix::actor::SignatureRef<'_> {
     // git does not support empty names or emails
     let name = if !signature.name.is_empty() {
         &signature.name
@@ -657,6 +842,14 @@ fn serialize_extras(commit: &Commit) -> Vec<u8> {
     for predecessor in &commit.predecessors {
         proto.predecessors.push(predecessor.to_bytes());
     }
+    for recorded_copy in &commit.recorded_copies {
+        proto
+            .recorded_copies
+            .push(crate::protos::git_store::commit::RecordedCopy {
+                source: recorded_copy.source.as_internal_file_string().to_owned(),
+                target: recorded_copy.target.as_internal_file_string().to_owned(),
+            });
+    }
     proto.encode_to_vec()
 }
 
@@ -690,6 +883,12 @@ fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
     for predecessor in &proto.predecessors {
         commit.predecessors.push(CommitId::from_bytes(predecessor));
     }
+    for recorded_copy in proto.recorded_copies {
+        commit.recorded_copies.push(RecordedCopy {
+            source: RepoPathBuf::from_internal_string(recorded_copy.source),
+            target: RepoPathBuf::from_internal_string(recorded_copy.target),
+        });
+    }
 }
 
 /// Returns `RefEdit` that will create a ref in `refs/jj/keep` if not exist.
@@ -809,6 +1008,34 @@ fn run_git_gc(git_dir: &Path) -> Result<(), GitGcError> {
     Ok(())
 }
 
+/// Returns true if the repo was cloned with a filter (e.g. `--filter=blob:none`)
+/// and may be missing objects that can be fetched from the promisor remote.
+fn is_partial_clone(git_repo: &gix::Repository) -> bool {
+    let config = git_repo.config_snapshot();
+    config.string("extensions.partialClone").is_some()
+        || git_repo.remote_names().iter().any(|name| {
+            config
+                .boolean(format!("remote.{name}.promisor").as_str())
+                .unwrap_or(false)
+        })
+}
+
+fn run_git_cat_file_blob(
+    git_dir: &Path,
+    blob_id: gix::ObjectId,
+) -> Result<Vec<u8>, GitLazyFetchError> {
+    let mut git = Command::new("git");
+    git.arg("--git-dir=."); // turn off discovery
+    git.args(["cat-file", "blob"]);
+    git.arg(blob_id.to_string());
+    git.current_dir(git_dir);
+    let output = git.output().map_err(GitLazyFetchError::CatFileCommand)?;
+    if !output.status.success() {
+        return Err(GitLazyFetchError::CatFileCommandErrorStatus(output.status));
+    }
+    Ok(output.stdout)
+}
+
 fn validate_git_object_id(id: &impl ObjectId) -> BackendResult<gix::ObjectId> {
     if id.as_bytes().len() != HASH_LENGTH {
         return Err(BackendError::InvalidHashLength {
@@ -871,8 +1098,11 @@ fn import_extra_metadata_entries_from_heads(
         // TODO(#1624): Should we read the root tree here and check if it has a
         // `.jjconflict-...` entries? That could happen if the user used `git` to e.g.
         // change the description of a commit with tree-level conflicts.
-        let commit =
+        let mut commit =
             commit_from_git_without_root_parent(&id, &git_object, uses_tree_conflict_format)?;
+        if is_shallow_commit(git_repo, &id)? {
+            commit.parents.clear();
+        }
         mut_table.add_entry(id.to_bytes(), serialize_extras(&commit));
         work_ids.extend(
             commit
@@ -884,6 +1114,108 @@ fn import_extra_metadata_entries_from_heads(
     Ok(())
 }
 
+/// Returns true if the commit is at the boundary of a shallow clone, i.e. its
+/// parents don't exist in the repo.
+fn is_shallow_commit(git_repo: &gix::Repository, id: &CommitId) -> BackendResult<bool> {
+    let shallow_commits = git_repo
+        .shallow_commits()
+        .map_err(|err| BackendError::Other(Box::new(err)))?;
+    let Some(shallow_commits) = shallow_commits else {
+        return Ok(false);
+    };
+    let git_commit_id = validate_git_object_id(id)?;
+    Ok(shallow_commits.binary_search(&git_commit_id).is_ok())
+}
+
+/// Reads the IDs listed in a Git `shallow` file, or in the copy of it recorded
+/// in the store.
+fn read_shallow_file(path: &Path) -> Result<BTreeSet<String>, PathError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(BTreeSet::new()),
+        Err(err) => Err(err).context(path),
+    }
+}
+
+fn write_file_atomically(path: &Path, content: &str) -> Result<(), PathError> {
+    let dir = path.parent().unwrap();
+    let mut temp_file = NamedTempFile::new_in(dir).context(dir)?;
+    temp_file
+        .write_all(content.as_bytes())
+        .context(temp_file.path())?;
+    temp_file
+        .persist(path)
+        .map_err(|err| err.error)
+        .context(path)?;
+    Ok(())
+}
+
+/// Reads the commits replaced by `refs/replace/*` refs. Replacements of other
+/// kinds of objects, and refs not named after an object ID, are ignored.
+fn read_replace_refs(
+    git_repo: &gix::Repository,
+) -> Result<BTreeMap<CommitId, CommitId>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut replacements = BTreeMap::new();
+    let git_references = git_repo.references()?;
+    for git_ref in git_references.prefixed(REPLACE_REF_NAMESPACE)? {
+        let git_ref = git_ref?.detach();
+        let Some(oid) = git_ref.target.try_id() else {
+            continue;
+        };
+        let name = &git_ref.name.as_bstr()[REPLACE_REF_NAMESPACE.len()..];
+        let Some(original) = str::from_utf8(name)
+            .ok()
+            .and_then(|hex| CommitId::try_from_hex(hex).ok())
+            .filter(|id| id.as_bytes().len() == HASH_LENGTH)
+        else {
+            continue;
+        };
+        let is_commit = git_repo
+            .try_find_header(oid)?
+            .is_some_and(|header| header.kind() == gix::object::Kind::Commit);
+        if is_commit {
+            replacements.insert(original, CommitId::from_bytes(oid.as_bytes()));
+        }
+    }
+    Ok(replacements)
+}
+
+/// Reads the replacements recorded in the file at `path`, one
+/// `<original> <replacement>` pair of hex IDs per line.
+fn read_replacements(path: &Path) -> Result<BTreeMap<CommitId, CommitId>, PathError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(err).context(path),
+    };
+    content
+        .lines()
+        .map(|line| {
+            let (original, replacement) = line
+                .split_once(' ')
+                .and_then(|(original, replacement)| {
+                    Some((
+                        CommitId::try_from_hex(original).ok()?,
+                        CommitId::try_from_hex(replacement).ok()?,
+                    ))
+                })
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid replacement: {line:?}"),
+                    )
+                })
+                .context(path)?;
+            Ok((original, replacement))
+        })
+        .collect()
+}
+
 impl Debug for GitBackend {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         f.debug_struct("GitBackend")
@@ -1139,18 +1471,28 @@ impl Backend for GitBackend {
 
         let mut commit = {
             let locked_repo = self.lock_git_repo();
+            // A replaced commit is read from its replacement, but keeps its ID.
+            let git_object_id = match self.replacements.get(id) {
+                Some(replacement) => validate_git_object_id(replacement)?,
+                None => git_commit_id,
+            };
             let git_object = locked_repo
-                .find_object(git_commit_id)
+                .find_object(git_object_id)
                 .map_err(|err| map_not_found_err(err, id))?;
-            commit_from_git_without_root_parent(id, &git_object, false)?
+            let mut commit = commit_from_git_without_root_parent(id, &git_object, false)?;
+            if is_shallow_commit(&locked_repo, id)? {
+                // The parents are beyond the shallow boundary, so the commit
+                // is treated as a root commit.
+                commit.parents.clear();
+            }
+            commit
         };
         if commit.parents.is_empty() {
             commit.parents.push(self.root_commit_id.clone());
         };
 
-        let table = self.cached_extra_metadata_table()?;
-        if let Some(extras) = table.get_value(id.as_bytes()) {
-            deserialize_extras(&mut commit, extras);
+        if let Some(extras) = self.read_extra_metadata(id)? {
+            deserialize_extras(&mut commit, &extras);
         } else {
             // TODO: Remove this hack and map to ObjectNotFound error if we're sure that
             // there are no reachable ancestor commits without extras metadata. Git commits
@@ -1158,9 +1500,8 @@ impl Backend for GitBackend {
             // https://github.com/martinvonz/jj/issues/2343
             tracing::info!("unimported Git commit found");
             self.import_head_commits([id])?;
-            let table = self.cached_extra_metadata_table()?;
-            let extras = table.get_value(id.as_bytes()).unwrap();
-            deserialize_extras(&mut commit, extras);
+            let extras = self.read_extra_metadata(id)?.unwrap();
+            deserialize_extras(&mut commit, &extras);
         }
         Ok(commit)
     }
@@ -1224,63 +1565,62 @@ impl Backend for GitBackend {
         // To prevent such race condition locally, we extend the scope covered by the
         // table lock. This is still racy if multiple machines are involved and the
         // repository is rsync-ed.
-        let (table, table_lock) = self.read_extra_metadata_table_locked()?;
-        let id = loop {
-            let mut commit = gix::objs::Commit {
-                message: message.to_owned().into(),
-                tree: git_tree_id,
-                author: author.into(),
-                committer: committer.into(),
-                encoding: None,
-                parents: parents.clone(),
-                extra_headers: extra_headers.clone(),
-            };
-
-            if let Some(sign) = &mut sign_with {
-                // we don't use gix pool, but at least use their heuristic
-                let mut data = Vec::with_capacity(512);
-                commit.write_to(&mut data).unwrap();
+        let id = self.update_extra_metadata(&locked_repo, |pending| {
+            let id = loop {
+                let mut commit = gix::objs::Commit {
+                    message: message.to_owned().into(),
+                    tree: git_tree_id,
+                    author: author.into(),
+                    committer: committer.into(),
+                    encoding: None,
+                    parents: parents.clone(),
+                    extra_headers: extra_headers.clone(),
+                };
 
-                let sig = sign(&data).map_err(|err| BackendError::WriteObject {
-                    object_type: "commit",
-                    source: Box::new(err),
-                })?;
-                commit
-                    .extra_headers
-                    .push(("gpgsig".into(), sig.clone().into()));
-                contents.secure_sig = Some(SecureSig { data, sig });
-            }
+                if let Some(sign) = &mut sign_with {
+                    // we don't use gix pool, but at least use their heuristic
+                    let mut data = Vec::with_capacity(512);
+                    commit.write_to(&mut data).unwrap();
 
-            let git_id =
-                locked_repo
-                    .write_object(&commit)
-                    .map_err(|err| BackendError::WriteObject {
+                    let sig = sign(&data).map_err(|err| BackendError::WriteObject {
                         object_type: "commit",
                         source: Box::new(err),
                     })?;
-
-            match table.get_value(git_id.as_bytes()) {
-                Some(existing_extras) if existing_extras != extras => {
-                    // It's possible a commit already exists with the same commit id but different
-                    // change id. Adjust the timestamp until this is no longer the case.
-                    committer.time.seconds -= 1;
+                    commit
+                        .extra_headers
+                        .push(("gpgsig".into(), sig.clone().into()));
+                    contents.secure_sig = Some(SecureSig { data, sig });
                 }
-                _ => break CommitId::from_bytes(git_id.as_bytes()),
-            }
-        };
 
-        // Everything up to this point had no permanent effect on the repo except
-        // GC-able objects
-        locked_repo
-            .edit_reference(to_no_gc_ref_update(&id))
-            .map_err(|err| BackendError::Other(Box::new(err)))?;
+                let git_id =
+                    locked_repo
+                        .write_object(&commit)
+                        .map_err(|err| BackendError::WriteObject {
+                            object_type: "commit",
+                            source: Box::new(err),
+                        })?;
+
+                match pending.mut_table.get_value(git_id.as_bytes()) {
+                    Some(existing_extras) if existing_extras != extras => {
+                        // It's possible a commit already exists with the same commit id but
+                        // different change id. Adjust the timestamp until this is no longer
+                        // the case.
+                        committer.time.seconds -= 1;
+                    }
+                    _ => break CommitId::from_bytes(git_id.as_bytes()),
+                }
+            };
+            // Everything up to this point had no permanent effect on the repo
+            // except GC-able objects. The no-gc ref and the metadata are
+            // written together, at the end of the write batch if any.
+            pending.no_gc_ids.push(id.clone());
+            pending.mut_table.add_entry(id.to_bytes(), extras);
+            Ok(id)
+        })?;
 
         // Update the signature to match the one that was actually written to the object
         // store
         contents.committer.timestamp.timestamp = MillisSinceEpoch(committer.time.seconds * 1000);
-        let mut mut_table = table.start_mutation();
-        mut_table.add_entry(id.to_bytes(), extras);
-        self.save_extra_metadata_table(mut_table, &table_lock)?;
         Ok((id, contents))
     }
 
@@ -1359,6 +1699,26 @@ impl Backend for GitBackend {
         Ok(Box::pin(futures::stream::iter(records)))
     }
 
+    fn start_write_batch(&self) {
+        let mut locked_batch = self.write_batch.lock().unwrap();
+        if locked_batch.is_none() {
+            *locked_batch = Some(WriteBatch::default());
+        }
+    }
+
+    /// Saves the extra metadata of the commits written by the batch to a
+    /// single table segment, and creates their no-gc refs in a single ref
+    /// transaction, instead of doing both for every commit.
+    #[tracing::instrument(skip(self))]
+    fn finish_write_batch(&self) -> BackendResult<()> {
+        let locked_repo = self.lock_git_repo();
+        let batch = self.write_batch.lock().unwrap().take();
+        match batch.and_then(|batch| batch.pending) {
+            Some(pending) => self.save_pending_metadata(&locked_repo, pending),
+            None => Ok(()),
+        }
+    }
+
     #[tracing::instrument(skip(self, index))]
     fn gc(&self, index: &dyn Index, keep_newer: SystemTime) -> BackendResult<()> {
         let git_repo = self.lock_git_repo();
@@ -1722,6 +2082,56 @@ mod tests {
         );
     }
 
+    #[test]
+    fn read_shallow_git_commit() {
+        let settings = user_settings();
+        let temp_dir = testutils::new_temp_dir();
+        let store_path = temp_dir.path();
+        let git_repo_path = temp_dir.path().join("git");
+        let git_repo = git2::Repository::init(git_repo_path).unwrap();
+
+        let signature = git2::Signature::now("Someone", "someone@example.com").unwrap();
+        let empty_tree_id = Oid::from_str("4b825dc642cb6eb9a060e54bf8d69288fbee4904").unwrap();
+        let empty_tree = git_repo.find_tree(empty_tree_id).unwrap();
+        let git_commit_id1 = git_repo
+            .commit(None, &signature, &signature, "parent", &empty_tree, &[])
+            .unwrap();
+        let git_commit1 = git_repo.find_commit(git_commit_id1).unwrap();
+        let git_commit_id2 = git_repo
+            .commit(
+                None,
+                &signature,
+                &signature,
+                "child",
+                &empty_tree,
+                &[&git_commit1],
+            )
+            .unwrap();
+
+        // Make the repo look like a shallow clone of depth 1: the parent object
+        // is missing and the child is listed in the "shallow" file.
+        let hex1 = git_commit_id1.to_string();
+        std::fs::remove_file(
+            git_repo
+                .path()
+                .join("objects")
+                .join(&hex1[..2])
+                .join(&hex1[2..]),
+        )
+        .unwrap();
+        std::fs::write(
+            git_repo.path().join("shallow"),
+            format!("{git_commit_id2}\n"),
+        )
+        .unwrap();
+
+        let backend = GitBackend::init_external(&settings, store_path, git_repo.path()).unwrap();
+        let commit_id2 = CommitId::from_bytes(git_commit_id2.as_bytes());
+        backend.import_head_commits([&commit_id2]).unwrap();
+        let commit = backend.read_commit(&commit_id2).block_on().unwrap();
+        assert_eq!(commit.parents, vec![backend.root_commit_id().clone()]);
+    }
+
     #[test]
     fn read_signed_git_commit() {
         let settings = user_settings();
@@ -1832,6 +2242,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            recorded_copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -1914,6 +2325,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            recorded_copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -2007,6 +2419,7 @@ mod tests {
             author: signature.clone(),
             committer: signature,
             secure_sig: None,
+            recorded_copies: vec![],
         };
         let commit_id = backend.write_commit(commit, None).block_on().unwrap().0;
         let git_refs: Vec<_> = git_repo
@@ -2084,6 +2497,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            recorded_copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
@@ -2130,6 +2544,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            recorded_copies: vec![],
         };
 
         let mut signer = |data: &_| {
@@ -22,6 +22,7 @@ use thiserror::Error;
 
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
+use crate::backend::CopyRecord;
 use crate::commit::Commit;
 use crate::object_id::HexPrefix;
 use crate::object_id::PrefixResolution;
@@ -126,6 +127,17 @@ pub trait Index: Send + Sync {
         expression: &ResolvedExpression,
         store: &Arc<Store>,
     ) -> Result<Box<dyn Revset + 'index>, RevsetEvaluationError>;
+
+    /// Returns the copy records of the `commit_id` commit relative to its
+    /// parent `parent_id` if the index stores them. Returns `None` if the
+    /// records have to be detected by the backend.
+    fn copy_records(
+        &self,
+        _commit_id: &CommitId,
+        _parent_id: &CommitId,
+    ) -> Option<Vec<CopyRecord>> {
+        None
+    }
 }
 
 #[allow(missing_docs)]
@@ -0,0 +1,356 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolutions of conflicts, recorded to be reused when the same conflicts
+//! reappear.
+//!
+//! Like Git's "rerere", the resolutions are recorded per conflicted hunk, in
+//! the repo directory. Each resolution is stored in a file named after the
+//! hash of the hunk. The sides of the hunk are sorted before hashing, so the
+//! resolution is found no matter in which order the sides were merged.
+
+#![allow(missing_docs)]
+
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::iter::zip;
+use std::path::Path;
+use std::path::PathBuf;
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+use tempfile::NamedTempFile;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::TreeValue;
+use crate::conflicts::extract_as_single_hunk;
+use crate::conflicts::materialize_merge_result;
+use crate::conflicts::update_from_content;
+use crate::conflicts::ConflictMarkerStyle;
+use crate::content_hash::blake2b_hash;
+use crate::file_util::create_or_reuse_dir;
+use crate::file_util::remove_dir_contents;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::files;
+use crate::files::MergeResult;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::MergedTreeBuilder;
+
+const LEARNED_RESOLUTIONS_DIR: &str = "learned_resolutions";
+
+/// Store of the learned resolutions of the repo.
+#[derive(Debug)]
+pub struct LearnedResolutions {
+    dir: PathBuf,
+}
+
+impl LearnedResolutions {
+    /// Returns the learned resolutions of the repo at `repo_path`. The
+    /// directory is created when the first resolution is recorded.
+    pub fn new(repo_path: &Path) -> Self {
+        LearnedResolutions {
+            dir: repo_path.join(LEARNED_RESOLUTIONS_DIR),
+        }
+    }
+
+    /// Returns the recorded resolution of the conflicted `hunk`.
+    pub fn get(&self, hunk: &Merge<BString>) -> Result<Option<BString>, PathError> {
+        let Some(path) = self.hunk_path(hunk) else {
+            return Ok(None);
+        };
+        match fs::read(&path) {
+            Ok(content) => Ok(Some(BString::new(content))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).context(&path),
+        }
+    }
+
+    /// Records `resolution` as the resolution of the conflicted `hunk`,
+    /// replacing any earlier resolution.
+    pub fn insert(&self, hunk: &Merge<BString>, resolution: &[u8]) -> Result<(), PathError> {
+        let Some(path) = self.hunk_path(hunk) else {
+            return Ok(());
+        };
+        create_or_reuse_dir(&self.dir).context(&self.dir)?;
+        let mut temp_file = NamedTempFile::new_in(&self.dir).context(&self.dir)?;
+        temp_file.write_all(resolution).context(temp_file.path())?;
+        temp_file
+            .persist(&path)
+            .map_err(|err| err.error)
+            .context(&path)?;
+        Ok(())
+    }
+
+    /// Forgets all the recorded resolutions. Returns the number of forgotten
+    /// resolutions.
+    pub fn clear(&self) -> Result<usize, PathError> {
+        let num_entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries.count(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err).context(&self.dir),
+        };
+        remove_dir_contents(&self.dir)?;
+        Ok(num_entries)
+    }
+
+    /// Records the resolutions of the file conflicts in `old_tree` that are
+    /// resolved in `new_tree`. Returns the number of recorded hunks.
+    ///
+    /// A file is skipped if the lines around its conflicted hunks were
+    /// changed, since the resolutions of the hunks can't be told apart then.
+    pub fn learn_from_trees(
+        &self,
+        old_tree: &MergedTree,
+        new_tree: &MergedTree,
+    ) -> BackendResult<usize> {
+        let store = old_tree.store();
+        let mut num_learned = 0;
+        for (path, conflict) in old_tree.conflicts() {
+            let Some(file_merge) = conflict.to_file_merge() else {
+                continue;
+            };
+            let new_value = new_tree.path_value(&path)?;
+            let Some(Some(TreeValue::File { id, .. })) = new_value.as_resolved() else {
+                continue;
+            };
+            let contents =
+                extract_as_single_hunk(&file_merge.simplify(), store, &path).block_on()?;
+            let MergeResult::Conflict(hunks) = files::merge(&contents) else {
+                continue;
+            };
+            let new_content = extract_as_single_hunk(&Merge::normal(id.clone()), store, &path)
+                .block_on()?
+                .into_resolved()
+                .unwrap();
+            let Some(resolutions) = split_resolutions(&hunks, &new_content) else {
+                continue;
+            };
+            for (hunk, resolution) in resolutions {
+                self.insert(hunk, resolution).map_err(to_backend_error)?;
+                num_learned += 1;
+            }
+        }
+        Ok(num_learned)
+    }
+
+    /// Resolves the hunks of the file conflicts in `tree` that have recorded
+    /// resolutions. Files with other conflicted hunks are left partially
+    /// resolved.
+    pub fn resolve_tree(&self, tree: &MergedTree) -> BackendResult<MergedTree> {
+        let store = tree.store();
+        let mut tree_builder = MergedTreeBuilder::new(tree.id());
+        let mut has_resolutions = false;
+        for (path, conflict) in tree.conflicts() {
+            let Some(file_merge) = conflict.to_file_merge() else {
+                continue;
+            };
+            let contents =
+                extract_as_single_hunk(&file_merge.clone().simplify(), store, &path).block_on()?;
+            let MergeResult::Conflict(hunks) = files::merge(&contents) else {
+                continue;
+            };
+            let mut has_file_resolutions = false;
+            let new_hunks: Vec<_> = hunks
+                .into_iter()
+                .map(|hunk| -> Result<_, PathError> {
+                    if hunk.is_resolved() {
+                        return Ok(hunk);
+                    }
+                    match self.get(&hunk)? {
+                        Some(resolution) => {
+                            has_file_resolutions = true;
+                            Ok(Merge::resolved(resolution))
+                        }
+                        None => Ok(hunk),
+                    }
+                })
+                .try_collect()
+                .map_err(to_backend_error)?;
+            if !has_file_resolutions {
+                continue;
+            }
+
+            // Like when resolving in a text editor, the new contents go
+            // through conflict markers so that absent sides and simplified
+            // conflicts are handled.
+            let mut materialized = vec![];
+            materialize_merge_result(&concat_hunks(&new_hunks), &mut materialized)
+                .expect("Writing to an in-memory buffer should never fail");
+            let new_file_ids = update_from_content(
+                &file_merge,
+                store,
+                &path,
+                &materialized,
+                ConflictMarkerStyle::default(),
+            )
+            .block_on()?;
+            let new_value = match new_file_ids.into_resolved() {
+                Ok(new_file_id) => {
+                    let executable = match conflict.to_executable_merge() {
+                        Some(merge) => merge.resolve_trivial().copied().unwrap_or_default(),
+                        None => false,
+                    };
+                    Merge::normal(TreeValue::File {
+                        id: new_file_id.unwrap(),
+                        executable,
+                    })
+                }
+                Err(new_file_ids) => conflict.with_new_file_ids(&new_file_ids),
+            };
+            tree_builder.set_or_remove(path, new_value);
+            has_resolutions = true;
+        }
+        if !has_resolutions {
+            return Ok(tree.clone());
+        }
+        let new_tree_id = tree_builder.write_tree(store)?;
+        store.get_root_tree(&new_tree_id)
+    }
+
+    fn hunk_path(&self, hunk: &Merge<BString>) -> Option<PathBuf> {
+        let hunk = hunk.clone().simplify();
+        if hunk.is_resolved() {
+            return None;
+        }
+        let removes = hunk
+            .removes()
+            .map(|term| term.to_vec())
+            .sorted()
+            .collect_vec();
+        let adds = hunk.adds().map(|term| term.to_vec()).sorted().collect_vec();
+        let hash = blake2b_hash(&vec![removes, adds]);
+        Some(self.dir.join(hex::encode(hash)))
+    }
+}
+
+fn to_backend_error(err: PathError) -> BackendError {
+    BackendError::Other(err.into())
+}
+
+/// Splits `content` into the resolutions of the conflicted `hunks`, using the
+/// resolved hunks between them as anchors. Returns `None` if the resolved
+/// hunks can't be found in `content`.
+fn split_resolutions<'a>(
+    hunks: &'a [Merge<BString>],
+    mut content: &'a [u8],
+) -> Option<Vec<(&'a Merge<BString>, &'a [u8])>> {
+    let mut resolutions = vec![];
+    let mut pending_conflict = None;
+    for (i, hunk) in hunks.iter().enumerate() {
+        let Some(anchor) = hunk.as_resolved() else {
+            if pending_conflict.replace(hunk).is_some() {
+                return None;
+            }
+            continue;
+        };
+        let start = match pending_conflict.take() {
+            None => {
+                if !content.starts_with(anchor) {
+                    return None;
+                }
+                0
+            }
+            Some(conflict) => {
+                // The last hunk has to be at the end. Otherwise, the first
+                // occurrence is assumed to be the anchor.
+                let start = if i == hunks.len() - 1 {
+                    content
+                        .ends_with(anchor)
+                        .then(|| content.len() - anchor.len())?
+                } else {
+                    content.find(anchor)?
+                };
+                resolutions.push((conflict, &content[..start]));
+                start
+            }
+        };
+        content = &content[start + anchor.len()..];
+    }
+    match pending_conflict {
+        Some(conflict) => resolutions.push((conflict, content)),
+        None if !content.is_empty() => return None,
+        None => {}
+    }
+    Some(resolutions)
+}
+
+/// Concatenates the contents of `hunks` into a single (possibly conflicted)
+/// hunk.
+fn concat_hunks(hunks: &[Merge<BString>]) -> Merge<BString> {
+    let num_sides = hunks.iter().map(|hunk| hunk.num_sides()).max().unwrap_or(1);
+    let mut contents = Merge::from_vec(vec![BString::default(); num_sides * 2 - 1]);
+    for hunk in hunks {
+        if let Some(slice) = hunk.as_resolved() {
+            for content in contents.iter_mut() {
+                content.extend_from_slice(slice);
+            }
+        } else {
+            for (content, slice) in zip(contents.iter_mut(), hunk.iter()) {
+                content.extend_from_slice(slice);
+            }
+        }
+    }
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunk(terms: &[&str]) -> Merge<BString> {
+        Merge::from_vec(terms.iter().map(|&term| BString::from(term)).collect_vec())
+    }
+
+    #[test]
+    fn test_split_resolutions() {
+        let hunks = vec![
+            hunk(&["a\n"]),
+            hunk(&["b1\n", "b0\n", "b2\n"]),
+            hunk(&["c\n"]),
+            hunk(&["d1\n", "d0\n", "d2\n"]),
+        ];
+        assert_eq!(
+            split_resolutions(&hunks, b"a\nb\nc\nd\nd\n"),
+            Some(vec![
+                (&hunks[1], b"b\n".as_slice()),
+                (&hunks[3], b"d\nd\n".as_slice())
+            ])
+        );
+        // An empty resolution
+        assert_eq!(
+            split_resolutions(&hunks, b"a\nc\n"),
+            Some(vec![
+                (&hunks[1], b"".as_slice()),
+                (&hunks[3], b"".as_slice())
+            ])
+        );
+        // The resolved hunks were changed
+        assert_eq!(split_resolutions(&hunks, b"x\nb\nc\nd\n"), None);
+        assert_eq!(split_resolutions(&hunks, b"a\nb\nd\n"), None);
+
+        // The last resolved hunk is anchored at the end
+        let hunks = vec![hunk(&["x\n", "", "y\n"]), hunk(&["z\n"])];
+        assert_eq!(
+            split_resolutions(&hunks, b"z\nz\n"),
+            Some(vec![(&hunks[0], b"z\n".as_slice())])
+        );
+        assert_eq!(split_resolutions(&hunks, b"z\nw\n"), None);
+    }
+}
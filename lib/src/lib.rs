@@ -16,7 +16,7 @@
 
 #![warn(missing_docs)]
 #![deny(unused_must_use)]
-#![forbid(unsafe_code)]
+#![deny(unsafe_code)]
 
 // Needed so that proc macros can be used inside jj_lib and by external crates
 // that depend on it.
@@ -28,7 +28,10 @@ extern crate self as jj_lib;
 #[macro_use]
 pub mod content_hash;
 
+pub mod absorb;
+pub mod annotate;
 pub mod backend;
+pub mod bisect;
 pub mod commit;
 pub mod commit_builder;
 pub mod conflicts;
@@ -39,6 +42,7 @@ pub mod default_submodule_store;
 pub mod diff;
 pub mod dsl_util;
 pub mod extensions_map;
+pub mod fast_import;
 pub mod file_util;
 pub mod files;
 pub mod fileset;
@@ -55,17 +59,21 @@ pub mod graph;
 pub mod hex_util;
 pub mod id_prefix;
 pub mod index;
+pub mod learned_resolutions;
 pub mod local_backend;
 pub mod local_working_copy;
 pub mod lock;
+pub mod mailmap;
 pub mod matchers;
 pub mod merge;
+pub mod merge_drivers;
 pub mod merged_tree;
 pub mod object_id;
 pub mod op_heads_store;
 pub mod op_store;
 pub mod op_walk;
 pub mod operation;
+pub mod protected_ops;
 #[allow(missing_docs)]
 pub mod protos;
 pub mod refs;
@@ -86,6 +94,7 @@ pub mod store;
 pub mod str_util;
 pub mod submodule_store;
 pub mod time_util;
+pub mod trailer;
 pub mod transaction;
 pub mod tree;
 pub mod tree_builder;
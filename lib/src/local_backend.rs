@@ -47,6 +47,7 @@ use crate::backend::CopyRecord;
 use crate::backend::FileId;
 use crate::backend::MergedTreeId;
 use crate::backend::MillisSinceEpoch;
+use crate::backend::RecordedCopy;
 use crate::backend::SecureSig;
 use crate::backend::Signature;
 use crate::backend::SigningFn;
@@ -370,6 +371,11 @@ pub fn commit_to_proto(commit: &Commit) -> crate::protos::local_store::Commit {
     proto.description = commit.description.clone();
     proto.author = Some(signature_to_proto(&commit.author));
     proto.committer = Some(signature_to_proto(&commit.committer));
+    proto.recorded_copies = commit
+        .recorded_copies
+        .iter()
+        .map(recorded_copy_to_proto)
+        .collect();
     proto
 }
 
@@ -400,6 +406,29 @@ fn commit_from_proto(mut proto: crate::protos::local_store::Commit) -> Commit {
         author: signature_from_proto(proto.author.unwrap_or_default()),
         committer: signature_from_proto(proto.committer.unwrap_or_default()),
         secure_sig,
+        recorded_copies: proto
+            .recorded_copies
+            .into_iter()
+            .map(recorded_copy_from_proto)
+            .collect(),
+    }
+}
+
+fn recorded_copy_to_proto(
+    recorded_copy: &RecordedCopy,
+) -> crate::protos::local_store::commit::RecordedCopy {
+    crate::protos::local_store::commit::RecordedCopy {
+        source: recorded_copy.source.as_internal_file_string().to_owned(),
+        target: recorded_copy.target.as_internal_file_string().to_owned(),
+    }
+}
+
+fn recorded_copy_from_proto(
+    proto: crate::protos::local_store::commit::RecordedCopy,
+) -> RecordedCopy {
+    RecordedCopy {
+        source: RepoPathBuf::from_internal_string(proto.source),
+        target: RepoPathBuf::from_internal_string(proto.target),
     }
 }
 
@@ -556,6 +585,7 @@ mod tests {
             author: create_signature(),
             committer: create_signature(),
             secure_sig: None,
+            recorded_copies: vec![],
         };
 
         let write_commit = |commit: Commit| -> BackendResult<(CommitId, Commit)> {
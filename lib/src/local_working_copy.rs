@@ -16,8 +16,10 @@
 #![allow(clippy::let_unit_value)]
 
 use std::any::Any;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::error::Error;
+use std::ffi::OsString;
 use std::fs;
 use std::fs::File;
 use std::fs::Metadata;
@@ -60,8 +62,9 @@ use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::conflicts;
-use crate::conflicts::materialize_merge_result;
+use crate::conflicts::materialize_merge_result_in_pool;
 use crate::conflicts::materialize_tree_value;
+use crate::conflicts::ConflictMarkerStyle;
 use crate::conflicts::MaterializedTreeValue;
 use crate::file_util::check_symlink_support;
 use crate::file_util::try_symlink;
@@ -71,6 +74,7 @@ use crate::fsmonitor::FsmonitorSettings;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::WatchmanConfig;
 use crate::gitignore::GitIgnoreFile;
+use crate::lock;
 use crate::lock::FileLock;
 use crate::matchers::DifferenceMatcher;
 use crate::matchers::EverythingMatcher;
@@ -94,6 +98,7 @@ use crate::settings::HumanByteSize;
 use crate::store::Store;
 use crate::tree::Tree;
 use crate::working_copy::CheckoutError;
+use crate::working_copy::CheckoutOptions;
 use crate::working_copy::CheckoutStats;
 use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::ResetError;
@@ -288,6 +293,35 @@ impl<'a> FileStates<'a> {
             .ok()
     }
 
+    /// Returns the names of the files directly in the `dir` directory. The
+    /// files in its subdirectories are skipped by binary search, so this
+    /// doesn't depend on how many there are.
+    fn child_file_names(&self, dir: &RepoPath) -> Vec<&'a RepoPathComponent> {
+        let mut names = vec![];
+        let mut data = self.prefixed(dir).data;
+        while let Some(entry) = data.first() {
+            let path = RepoPath::from_internal_string(&entry.path);
+            let Some(name) = path
+                .strip_prefix(dir)
+                .and_then(|rel| rel.components().next())
+            else {
+                data = &data[1..];
+                continue;
+            };
+            if path.parent() == Some(dir) {
+                names.push(name);
+                data = &data[1..];
+            } else {
+                let subdir = dir.join(name);
+                let len = data.partition_point(|entry| {
+                    RepoPath::from_internal_string(&entry.path).starts_with(&subdir)
+                });
+                data = &data[len..];
+            }
+        }
+        names
+    }
+
     fn prefixed_range(&self, base: &RepoPath) -> Range<usize> {
         let start = self
             .data
@@ -334,6 +368,9 @@ pub struct TreeState {
     sparse_patterns: Vec<RepoPathBuf>,
     own_mtime: MillisSinceEpoch,
     symlink_support: bool,
+    /// Directories whose listing can be reused by the next snapshot if their
+    /// mtime hasn't changed.
+    directory_states: HashMap<RepoPathBuf, DirectoryState>,
 
     /// The most recent clock value returned by Watchman. Will only be set if
     /// the repo is configured to use the Watchman filesystem monitor and
@@ -513,6 +550,71 @@ struct FsmonitorMatcher {
     watchman_clock: Option<crate::protos::working_copy::WatchmanClock>,
 }
 
+/// Fingerprint of a directory recorded by a snapshot.
+///
+/// Adding, removing or renaming an entry updates the mtime of the directory,
+/// so if the mtime is unchanged, the entries are the tracked files and the
+/// recorded subdirectories. Only directories without untracked entries are
+/// recorded, which means that changes to ignore rules can't make anything new
+/// to track in them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct DirectoryState {
+    mtime: MillisSinceEpoch,
+    /// Number of tracked files and subdirectories.
+    num_entries: usize,
+    /// Names of the subdirectories, sorted.
+    subdirs: Vec<String>,
+}
+
+fn directory_states_from_proto(
+    entries: Vec<crate::protos::working_copy::DirectoryStateEntry>,
+) -> HashMap<RepoPathBuf, DirectoryState> {
+    entries
+        .into_iter()
+        .map(|entry| {
+            let state = entry.state.unwrap_or_default();
+            let path = RepoPathBuf::from_internal_string(entry.path);
+            let state = DirectoryState {
+                mtime: MillisSinceEpoch(state.mtime_millis_since_epoch),
+                num_entries: state.num_entries as usize,
+                subdirs: state.subdirs,
+            };
+            (path, state)
+        })
+        .collect()
+}
+
+fn directory_states_to_proto(
+    directory_states: &HashMap<RepoPathBuf, DirectoryState>,
+) -> Vec<crate::protos::working_copy::DirectoryStateEntry> {
+    directory_states
+        .iter()
+        .sorted_unstable_by_key(|(path, _)| *path)
+        .map(
+            |(path, state)| crate::protos::working_copy::DirectoryStateEntry {
+                path: path.as_internal_file_string().to_owned(),
+                state: Some(crate::protos::working_copy::DirectoryState {
+                    mtime_millis_since_epoch: state.mtime.0,
+                    num_entries: u32::try_from(state.num_entries).unwrap(),
+                    subdirs: state.subdirs.clone(),
+                }),
+            },
+        )
+        .collect()
+}
+
+/// How an entry of a visited directory was handled by the snapshot.
+enum VisitedEntry {
+    /// A file that is tracked after the snapshot.
+    Tracked,
+    /// A subdirectory that was visited.
+    Subdir(String),
+    /// The `.jj` or `.git` directory.
+    Internal,
+    /// Anything else, such as ignored or vanished paths.
+    Untracked,
+}
+
 struct DirectoryToVisit<'a> {
     dir: RepoPathBuf,
     disk_dir: PathBuf,
@@ -588,6 +690,7 @@ impl TreeState {
             sparse_patterns: vec![RepoPathBuf::root()],
             own_mtime: MillisSinceEpoch(0),
             symlink_support: check_symlink_support().unwrap_or(false),
+            directory_states: HashMap::new(),
             watchman_clock: None,
         }
     }
@@ -652,6 +755,7 @@ impl TreeState {
             FileStatesMap::from_proto(proto.file_states, proto.is_file_states_sorted);
         self.sparse_patterns = sparse_patterns_from_proto(proto.sparse_patterns.as_ref());
         self.watchman_clock = proto.watchman_clock;
+        self.directory_states = directory_states_from_proto(proto.directory_states);
         Ok(())
     }
 
@@ -679,6 +783,7 @@ impl TreeState {
         }
         proto.sparse_patterns = Some(sparse_patterns);
         proto.watchman_clock = self.watchman_clock.clone();
+        proto.directory_states = directory_states_to_proto(&self.directory_states);
 
         let mut temp_file = NamedTempFile::new_in(&self.state_path).unwrap();
         temp_file
@@ -799,6 +904,7 @@ impl TreeState {
             progress,
             start_tracking_matcher,
             max_new_file_size,
+            conflict_marker_style,
         } = options;
 
         let sparse_matcher = self.sparse_matcher();
@@ -824,6 +930,11 @@ impl TreeState {
         let (tree_entries_tx, tree_entries_rx) = channel();
         let (file_states_tx, file_states_rx) = channel();
         let (present_files_tx, present_files_rx) = channel();
+        // The fsmonitor matcher skips the unchanged files of a directory, so
+        // the directory's entries can't be inferred from what was visited.
+        let (directory_states_tx, directory_states_rx) = channel();
+        let directory_states_tx =
+            (*fsmonitor_settings == FsmonitorSettings::None).then_some(directory_states_tx);
 
         trace_span!("traverse filesystem").in_scope(|| -> Result<(), SnapshotError> {
             let current_tree = self.current_tree()?;
@@ -840,9 +951,11 @@ impl TreeState {
                 tree_entries_tx,
                 file_states_tx,
                 present_files_tx,
+                directory_states_tx,
                 directory_to_visit,
                 *progress,
                 *max_new_file_size,
+                *conflict_marker_style,
             )
         })?;
 
@@ -886,6 +999,11 @@ impl TreeState {
             self.file_states
                 .merge_in(changed_file_states, &deleted_files);
         });
+        trace_span!("process directory states").in_scope(|| {
+            let directory_states: HashMap<_, _> = directory_states_rx.iter().collect();
+            is_dirty |= directory_states != self.directory_states;
+            self.directory_states = directory_states;
+        });
         trace_span!("write tree").in_scope(|| {
             let new_tree_id = tree_builder.write_tree(&self.store).unwrap();
             is_dirty |= new_tree_id != self.tree_id;
@@ -914,9 +1032,11 @@ impl TreeState {
         tree_entries_tx: Sender<(RepoPathBuf, MergedTreeValue)>,
         file_states_tx: Sender<(RepoPathBuf, FileState)>,
         present_files_tx: Sender<RepoPathBuf>,
+        directory_states_tx: Option<Sender<(RepoPathBuf, DirectoryState)>>,
         directory_to_visit: DirectoryToVisit,
         progress: Option<&SnapshotProgress>,
         max_new_file_size: u64,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<(), SnapshotError> {
         let DirectoryToVisit {
             dir,
@@ -931,161 +1051,260 @@ impl TreeState {
 
         let git_ignore = git_ignore
             .chain_with_file(&dir.to_internal_dir_string(), disk_dir.join(".gitignore"))?;
-        let dir_entries = disk_dir
-            .read_dir()
-            .unwrap()
-            .map(|maybe_entry| maybe_entry.unwrap())
-            .collect_vec();
-        dir_entries.into_par_iter().try_for_each_with(
-            (
-                tree_entries_tx.clone(),
-                file_states_tx.clone(),
-                present_files_tx.clone(),
-            ),
-            |(tree_entries_tx, file_states_tx, present_files_tx),
-             entry|
-             -> Result<(), SnapshotError> {
-                let file_type = entry.file_type().unwrap();
-                let file_name = entry.file_name();
-                let name = file_name
-                    .to_str()
-                    .ok_or_else(|| SnapshotError::InvalidUtf8Path {
-                        path: file_name.clone(),
+        // The mtime is read before listing the directory so that entries added
+        // while we're listing it are detected by the next snapshot.
+        let dir_mtime = match &directory_states_tx {
+            Some(_) => {
+                let metadata = disk_dir
+                    .symlink_metadata()
+                    .map_err(|err| SnapshotError::Other {
+                        message: format!("Failed to stat directory {}", disk_dir.display()),
+                        err: err.into(),
                     })?;
-
-                if name == ".jj" || name == ".git" {
-                    return Ok(());
-                }
-                let path = dir.join(RepoPathComponent::new(name));
-                let maybe_current_file_state = file_states.get(&path);
-                if let Some(file_state) = &maybe_current_file_state {
-                    if file_state.file_type == FileType::GitSubmodule {
-                        return Ok(());
+                Some(mtime_from_metadata(&metadata))
+            }
+            None => None,
+        };
+        let dir_entries = match dir_mtime
+            .and_then(|mtime| self.unchanged_directory_entries(&dir, mtime, file_states))
+        {
+            Some(dir_entries) => dir_entries,
+            None => disk_dir
+                .read_dir()
+                .unwrap()
+                .map(|maybe_entry| {
+                    let entry = maybe_entry.unwrap();
+                    (entry.file_name(), entry.file_type().unwrap().is_dir())
+                })
+                .collect_vec(),
+        };
+        let visited_entries = dir_entries
+            .into_par_iter()
+            .map_with(
+                (
+                    tree_entries_tx.clone(),
+                    file_states_tx.clone(),
+                    present_files_tx.clone(),
+                    directory_states_tx.clone(),
+                ),
+                |(tree_entries_tx, file_states_tx, present_files_tx, directory_states_tx),
+                 (file_name, is_dir)|
+                 -> Result<VisitedEntry, SnapshotError> {
+                    let name =
+                        file_name
+                            .to_str()
+                            .ok_or_else(|| SnapshotError::InvalidUtf8Path {
+                                path: file_name.clone(),
+                            })?;
+
+                    if name == ".jj" || name == ".git" {
+                        return Ok(VisitedEntry::Internal);
+                    }
+                    let path = dir.join(RepoPathComponent::new(name));
+                    let disk_path = disk_dir.join(&file_name);
+                    let maybe_current_file_state = file_states.get(&path);
+                    if let Some(file_state) = &maybe_current_file_state {
+                        if file_state.file_type == FileType::GitSubmodule {
+                            return Ok(VisitedEntry::Tracked);
+                        }
                     }
-                }
 
-                if file_type.is_dir() {
-                    let file_states = file_states.prefixed(&path);
-                    if git_ignore.matches(&path.to_internal_dir_string())
-                        || start_tracking_matcher.visit(&path).is_nothing()
-                    {
-                        // TODO: Report this directory to the caller if there are unignored paths we
-                        // should not start tracking.
-
-                        // If the whole directory is ignored, visit only paths we're already
-                        // tracking.
-                        for (tracked_path, current_file_state) in file_states {
-                            if !matcher.matches(tracked_path) {
-                                continue;
-                            }
-                            let disk_path = tracked_path.to_fs_path(&self.working_copy_path);
-                            let metadata = match disk_path.symlink_metadata() {
-                                Ok(metadata) => metadata,
-                                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                    if is_dir {
+                        let file_states = file_states.prefixed(&path);
+                        if git_ignore.matches(&path.to_internal_dir_string())
+                            || start_tracking_matcher.visit(&path).is_nothing()
+                        {
+                            // TODO: Report this directory to the caller if there are
+                            // unignored paths we should not start tracking.
+
+                            // If the whole directory is ignored, visit only paths we're
+                            // already tracking.
+                            for (tracked_path, current_file_state) in file_states {
+                                if !matcher.matches(tracked_path) {
                                     continue;
                                 }
-                                Err(err) => {
-                                    return Err(SnapshotError::Other {
-                                        message: format!(
-                                            "Failed to stat file {}",
-                                            disk_path.display()
-                                        ),
-                                        err: err.into(),
-                                    });
-                                }
-                            };
-                            if let Some(new_file_state) = file_state(&metadata) {
-                                present_files_tx.send(tracked_path.to_owned()).ok();
-                                let update = self.get_updated_tree_value(
-                                    tracked_path,
-                                    disk_path,
-                                    Some(&current_file_state),
-                                    current_tree,
-                                    &new_file_state,
-                                )?;
-                                if let Some(tree_value) = update {
-                                    tree_entries_tx
-                                        .send((tracked_path.to_owned(), tree_value))
-                                        .ok();
-                                }
-                                if new_file_state != current_file_state {
-                                    file_states_tx
-                                        .send((tracked_path.to_owned(), new_file_state))
-                                        .ok();
+                                let disk_path = tracked_path.to_fs_path(&self.working_copy_path);
+                                let metadata = match disk_path.symlink_metadata() {
+                                    Ok(metadata) => metadata,
+                                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                                        continue;
+                                    }
+                                    Err(err) => {
+                                        return Err(SnapshotError::Other {
+                                            message: format!(
+                                                "Failed to stat file {}",
+                                                disk_path.display()
+                                            ),
+                                            err: err.into(),
+                                        });
+                                    }
+                                };
+                                if let Some(new_file_state) = file_state(&metadata) {
+                                    present_files_tx.send(tracked_path.to_owned()).ok();
+                                    let update = self.get_updated_tree_value(
+                                        tracked_path,
+                                        disk_path,
+                                        Some(&current_file_state),
+                                        current_tree,
+                                        &new_file_state,
+                                        conflict_marker_style,
+                                    )?;
+                                    if let Some(tree_value) = update {
+                                        tree_entries_tx
+                                            .send((tracked_path.to_owned(), tree_value))
+                                            .ok();
+                                    }
+                                    if new_file_state != current_file_state {
+                                        file_states_tx
+                                            .send((tracked_path.to_owned(), new_file_state))
+                                            .ok();
+                                    }
                                 }
                             }
+                            Ok(VisitedEntry::Untracked)
+                        } else {
+                            let directory_to_visit = DirectoryToVisit {
+                                dir: path,
+                                disk_dir: disk_path,
+                                git_ignore: git_ignore.clone(),
+                                file_states,
+                            };
+                            self.visit_directory(
+                                matcher,
+                                start_tracking_matcher,
+                                current_tree,
+                                tree_entries_tx.clone(),
+                                file_states_tx.clone(),
+                                present_files_tx.clone(),
+                                directory_states_tx.clone(),
+                                directory_to_visit,
+                                progress,
+                                max_new_file_size,
+                                conflict_marker_style,
+                            )?;
+                            Ok(VisitedEntry::Subdir(name.to_owned()))
                         }
-                    } else {
-                        let directory_to_visit = DirectoryToVisit {
-                            dir: path,
-                            disk_dir: entry.path(),
-                            git_ignore: git_ignore.clone(),
-                            file_states,
+                    } else if matcher.matches(&path) {
+                        if let Some(progress) = progress {
+                            progress(&path);
+                        }
+                        if maybe_current_file_state.is_none()
+                            && git_ignore.matches(path.as_internal_file_string())
+                        {
+                            // If it wasn't already tracked and it matches
+                            // the ignored paths, then ignore it.
+                            return Ok(VisitedEntry::Untracked);
+                        } else if maybe_current_file_state.is_none()
+                            && !start_tracking_matcher.matches(&path)
+                        {
+                            // Leave the file untracked
+                            // TODO: Report this path to the caller
+                            return Ok(VisitedEntry::Untracked);
+                        }
+                        let metadata = match disk_path.symlink_metadata() {
+                            Ok(metadata) => metadata,
+                            // Deleted since the directory was listed
+                            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                                return Ok(VisitedEntry::Untracked);
+                            }
+                            Err(err) => {
+                                return Err(SnapshotError::Other {
+                                    message: format!("Failed to stat file {}", disk_path.display()),
+                                    err: err.into(),
+                                });
+                            }
                         };
-                        self.visit_directory(
-                            matcher,
-                            start_tracking_matcher,
-                            current_tree,
-                            tree_entries_tx.clone(),
-                            file_states_tx.clone(),
-                            present_files_tx.clone(),
-                            directory_to_visit,
-                            progress,
-                            max_new_file_size,
-                        )?;
-                    }
-                } else if matcher.matches(&path) {
-                    if let Some(progress) = progress {
-                        progress(&path);
-                    }
-                    if maybe_current_file_state.is_none()
-                        && git_ignore.matches(path.as_internal_file_string())
-                    {
-                        // If it wasn't already tracked and it matches
-                        // the ignored paths, then ignore it.
-                    } else if maybe_current_file_state.is_none()
-                        && !start_tracking_matcher.matches(&path)
-                    {
-                        // Leave the file untracked
-                        // TODO: Report this path to the caller
-                    } else {
-                        let metadata = entry.metadata().map_err(|err| SnapshotError::Other {
-                            message: format!("Failed to stat file {}", entry.path().display()),
-                            err: err.into(),
-                        })?;
                         if maybe_current_file_state.is_none() && metadata.len() > max_new_file_size
                         {
                             // TODO: Maybe leave the file untracked instead
                             return Err(SnapshotError::NewFileTooLarge {
-                                path: entry.path().clone(),
+                                path: disk_path,
                                 size: HumanByteSize(metadata.len()),
                                 max_size: HumanByteSize(max_new_file_size),
                             });
                         }
-                        if let Some(new_file_state) = file_state(&metadata) {
-                            present_files_tx.send(path.clone()).ok();
-                            let update = self.get_updated_tree_value(
-                                &path,
-                                entry.path(),
-                                maybe_current_file_state.as_ref(),
-                                current_tree,
-                                &new_file_state,
-                            )?;
-                            if let Some(tree_value) = update {
-                                tree_entries_tx.send((path.clone(), tree_value)).ok();
-                            }
-                            if Some(&new_file_state) != maybe_current_file_state.as_ref() {
-                                file_states_tx.send((path, new_file_state)).ok();
-                            }
+                        let Some(new_file_state) = file_state(&metadata) else {
+                            return Ok(VisitedEntry::Untracked);
+                        };
+                        present_files_tx.send(path.clone()).ok();
+                        let update = self.get_updated_tree_value(
+                            &path,
+                            disk_path,
+                            maybe_current_file_state.as_ref(),
+                            current_tree,
+                            &new_file_state,
+                            conflict_marker_style,
+                        )?;
+                        if let Some(tree_value) = update {
+                            tree_entries_tx.send((path.clone(), tree_value)).ok();
+                        }
+                        if Some(&new_file_state) != maybe_current_file_state.as_ref() {
+                            file_states_tx.send((path, new_file_state)).ok();
                         }
+                        Ok(VisitedEntry::Tracked)
+                    } else {
+                        Ok(VisitedEntry::Untracked)
+                    }
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let (Some(directory_states_tx), Some(mtime)) = (directory_states_tx, dir_mtime) {
+            let mut num_entries = 0;
+            let mut subdirs = vec![];
+            for entry in visited_entries {
+                match entry {
+                    VisitedEntry::Tracked => num_entries += 1,
+                    VisitedEntry::Subdir(name) => {
+                        num_entries += 1;
+                        subdirs.push(name);
                     }
+                    VisitedEntry::Internal => {}
+                    // The next snapshot has to list the directory to find out
+                    // whether the entry should be tracked by then.
+                    VisitedEntry::Untracked => return Ok(()),
                 }
-                Ok(())
-            },
-        )?;
+            }
+            subdirs.sort_unstable();
+            let state = DirectoryState {
+                mtime,
+                num_entries,
+                subdirs,
+            };
+            directory_states_tx.send((dir, state)).ok();
+        }
         Ok(())
     }
 
+    /// Returns the entries of `dir` if it's unchanged since a previous
+    /// snapshot recorded it, so that it doesn't have to be listed again. The
+    /// tracked files still have to be stat'ed since modifying a file doesn't
+    /// update the mtime of its directory.
+    fn unchanged_directory_entries(
+        &self,
+        dir: &RepoPath,
+        mtime: MillisSinceEpoch,
+        file_states: FileStates,
+    ) -> Option<Vec<(OsString, bool)>> {
+        let state = self.directory_states.get(dir)?;
+        // Like for files, if the directory's mtime is the same as this state
+        // file's own mtime, the directory may have changed after it was listed.
+        if state.mtime != mtime || state.mtime >= self.own_mtime {
+            return None;
+        }
+        let tracked_files = file_states
+            .child_file_names(dir)
+            .into_iter()
+            .map(|name| (OsString::from(name.as_str()), false));
+        let subdirs = state
+            .subdirs
+            .iter()
+            .map(|name| (OsString::from(name), true));
+        let entries = tracked_files.chain(subdirs).collect_vec();
+        // Files may have been untracked without touching the directory.
+        (entries.len() == state.num_entries).then_some(entries)
+    }
+
     #[instrument(skip_all)]
     fn make_fsmonitor_matcher(
         &self,
@@ -1138,6 +1357,7 @@ impl TreeState {
         maybe_current_file_state: Option<&FileState>,
         current_tree: &MergedTree,
         new_file_state: &FileState,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<Option<MergedTreeValue>, SnapshotError> {
         let clean = match maybe_current_file_state {
             None => {
@@ -1167,7 +1387,13 @@ impl TreeState {
             };
             let new_tree_values = match new_file_type {
                 FileType::Normal { executable } => self
-                    .write_path_to_store(repo_path, &disk_path, &current_tree_values, executable)
+                    .write_path_to_store(
+                        repo_path,
+                        &disk_path,
+                        &current_tree_values,
+                        executable,
+                        conflict_marker_style,
+                    )
                     .block_on()?,
                 FileType::Symlink => {
                     let id = self
@@ -1191,6 +1417,7 @@ impl TreeState {
         disk_path: &Path,
         current_tree_values: &MergedTreeValue,
         executable: FileExecutableFlag,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<MergedTreeValue, SnapshotError> {
         // If the file contained a conflict before and is now a normal file on disk, we
         // try to parse any conflict markers in the file into a conflict.
@@ -1219,6 +1446,7 @@ impl TreeState {
                 self.store.as_ref(),
                 repo_path,
                 &content,
+                conflict_marker_style,
             )
             .block_on()?;
             match new_file_ids.into_resolved() {
@@ -1334,7 +1562,11 @@ impl TreeState {
         Ok(())
     }
 
-    pub fn check_out(&mut self, new_tree: &MergedTree) -> Result<CheckoutStats, CheckoutError> {
+    pub fn check_out(
+        &mut self,
+        new_tree: &MergedTree,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         let old_tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
                 source: Box::new(err),
@@ -1342,7 +1574,12 @@ impl TreeState {
             other => CheckoutError::InternalBackendError(other),
         })?;
         let stats = self
-            .update(&old_tree, new_tree, self.sparse_matcher().as_ref())
+            .update(
+                &old_tree,
+                new_tree,
+                self.sparse_matcher().as_ref(),
+                options.conflict_marker_style,
+            )
             .block_on()?;
         self.tree_id = new_tree.id();
         Ok(stats)
@@ -1351,6 +1588,7 @@ impl TreeState {
     pub fn set_sparse_patterns(
         &mut self,
         sparse_patterns: Vec<RepoPathBuf>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
@@ -1363,9 +1601,21 @@ impl TreeState {
         let added_matcher = DifferenceMatcher::new(&new_matcher, &old_matcher);
         let removed_matcher = DifferenceMatcher::new(&old_matcher, &new_matcher);
         let empty_tree = MergedTree::resolved(Tree::empty(self.store.clone(), RepoPathBuf::root()));
-        let added_stats = self.update(&empty_tree, &tree, &added_matcher).block_on()?;
+        let added_stats = self
+            .update(
+                &empty_tree,
+                &tree,
+                &added_matcher,
+                options.conflict_marker_style,
+            )
+            .block_on()?;
         let removed_stats = self
-            .update(&tree, &empty_tree, &removed_matcher)
+            .update(
+                &tree,
+                &empty_tree,
+                &removed_matcher,
+                options.conflict_marker_style,
+            )
             .block_on()?;
         self.sparse_patterns = sparse_patterns;
         assert_eq!(added_stats.updated_files, 0);
@@ -1386,6 +1636,7 @@ impl TreeState {
         old_tree: &MergedTree,
         new_tree: &MergedTree,
         matcher: &dyn Matcher,
+        conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: maybe it's better not include the skipped counts in the "intended"
         // counts
@@ -1397,22 +1648,45 @@ impl TreeState {
         };
         let mut changed_file_states = Vec::new();
         let mut deleted_files = HashSet::new();
+        // Conflict markers are materialized on the rayon thread pool, so the
+        // stream is buffered enough to keep the threads busy. The results are
+        // still processed in path order.
+        let buffer_size = self
+            .store
+            .concurrency()
+            .max(rayon::current_num_threads())
+            .max(1);
         let mut diff_stream = Box::pin(
             old_tree
                 .diff_stream(new_tree, matcher)
                 .map(|TreeDiffEntry { path, values }| async {
                     match values {
                         Ok((before, after)) => {
-                            let result = materialize_tree_value(&self.store, &path, after).await;
-                            (path, result.map(|value| (before.is_present(), value)))
+                            let result = async {
+                                let value =
+                                    materialize_tree_value(&self.store, &path, after).await?;
+                                let conflict_data = match &value {
+                                    MaterializedTreeValue::FileConflict { contents, .. } => Some(
+                                        materialize_merge_result_in_pool(
+                                            contents.clone(),
+                                            conflict_marker_style,
+                                        )
+                                        .await,
+                                    ),
+                                    _ => None,
+                                };
+                                Ok::<_, BackendError>((before.is_present(), value, conflict_data))
+                            }
+                            .await;
+                            (path, result)
                         }
                         Err(err) => (path, Err(err)),
                     }
                 })
-                .buffered(self.store.concurrency().max(1)),
+                .buffered(buffer_size),
         );
         while let Some((path, data)) = diff_stream.next().await {
-            let (present_before, after) = data?;
+            let (present_before, after, conflict_data) = data?;
             if after.is_absent() {
                 stats.removed_files += 1;
             } else if !present_before {
@@ -1471,13 +1745,11 @@ impl TreeState {
                 }
                 MaterializedTreeValue::FileConflict {
                     id: _,
-                    contents,
+                    contents: _,
                     executable,
                 } => {
-                    let mut data = vec![];
-                    materialize_merge_result(&contents, &mut data)
-                        .expect("Failed to materialize conflict to in-memory buffer");
-                    self.write_conflict(&disk_path, data, executable)?
+                    let data = conflict_data.unwrap();
+                    self.write_conflict(&disk_path, data.into(), executable)?
                 }
                 MaterializedTreeValue::OtherConflict { id } => {
                     // Unless all terms are regular files, we can't do much
@@ -1546,6 +1818,8 @@ impl TreeState {
         }
         self.file_states
             .merge_in(changed_file_states, &deleted_files);
+        // The recorded directories may now have untracked entries.
+        self.directory_states.clear();
         self.tree_id = new_tree.id();
         Ok(())
     }
@@ -1606,7 +1880,12 @@ impl WorkingCopy for LocalWorkingCopy {
 
     fn start_mutation(&self) -> Result<Box<dyn LockedWorkingCopy>, WorkingCopyStateError> {
         let lock_path = self.state_path.join("working_copy.lock");
-        let lock = FileLock::lock(lock_path);
+        let lock = FileLock::lock_with_timeout(lock_path, lock::lock_timeout()).map_err(|err| {
+            WorkingCopyStateError {
+                message: "Failed to lock the working copy".to_string(),
+                err: err.into(),
+            }
+        })?;
 
         let wc = LocalWorkingCopy {
             store: self.store.clone(),
@@ -1858,7 +2137,11 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
         Ok(tree_state.current_tree_id().clone())
     }
 
-    fn check_out(&mut self, commit: &Commit) -> Result<CheckoutStats, CheckoutError> {
+    fn check_out(
+        &mut self,
+        commit: &Commit,
+        options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with the new TreeId so we can
         // continue an interrupted update if we find such a file.
         let new_tree = commit.tree()?;
@@ -1869,7 +2152,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
                 message: "Failed to load the working copy state".to_string(),
                 err: err.into(),
             })?
-            .check_out(&new_tree)?;
+            .check_out(&new_tree, options)?;
         self.tree_state_dirty = true;
         Ok(stats)
     }
@@ -1913,6 +2196,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
     fn set_sparse_patterns(
         &mut self,
         new_sparse_patterns: Vec<RepoPathBuf>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with new sparse patterns so we can
         // continue an interrupted update if we find such a file.
@@ -1923,7 +2207,7 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
                 message: "Failed to load the working copy state".to_string(),
                 err: err.into(),
             })?
-            .set_sparse_patterns(new_sparse_patterns)?;
+            .set_sparse_patterns(new_sparse_patterns, options)?;
         self.tree_state_dirty = true;
         Ok(stats)
     }
@@ -2074,6 +2358,18 @@ mod tests {
             file_states.prefixed(repo_path("bc")).paths().collect_vec(),
             ["bc"].map(repo_path)
         );
+
+        let child_names = |dir| {
+            file_states
+                .child_file_names(repo_path(dir))
+                .into_iter()
+                .map(|name| name.as_str())
+                .collect_vec()
+        };
+        assert_eq!(child_names(""), ["aa", "b#", "bc"]);
+        assert_eq!(child_names("b"), ["c", "e"]);
+        assert_eq!(child_names("b/d"), ["e"]);
+        assert!(child_names("a").is_empty());
         assert!(file_states.prefixed(repo_path("z")).is_empty());
 
         assert!(!file_states.contains_path(repo_path("a")));
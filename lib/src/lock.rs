@@ -18,14 +18,151 @@
 #[cfg_attr(not(unix), path = "lock/fallback.rs")]
 mod platform;
 
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use itertools::Itertools as _;
+use thiserror::Error;
+
 pub use platform::FileLock;
 
+/// The process holding a lock, as recorded in the lock file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub command: String,
+    pub acquired_at: SystemTime,
+}
+
+impl LockHolder {
+    fn current() -> Self {
+        LockHolder {
+            pid: std::process::id(),
+            command: std::env::args_os()
+                .map(|arg| arg.to_string_lossy().replace('\n', " "))
+                .join(" "),
+            acquired_at: SystemTime::now(),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let millis = self
+            .acquired_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        format!(
+            "pid: {}\ntime: {millis}\ncommand: {}\n",
+            self.pid, self.command
+        )
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut pid = None;
+        let mut millis = None;
+        let mut command = None;
+        for line in text.lines() {
+            let Some(entry) = line.split_once(": ") else {
+                continue;
+            };
+            match entry {
+                ("pid", value) => pid = value.parse().ok(),
+                ("time", value) => millis = value.parse().ok(),
+                ("command", value) => command = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+        Some(LockHolder {
+            pid: pid?,
+            command: command?,
+            acquired_at: SystemTime::UNIX_EPOCH + Duration::from_millis(millis?),
+        })
+    }
+}
+
+/// Records the current process as the holder of the lock.
+fn write_lock_holder(file: &File) -> io::Result<()> {
+    file.set_len(0)?;
+    let mut file = file;
+    file.write_all(LockHolder::current().to_text().as_bytes())
+}
+
+/// Reads the process holding the lock at `path`. Returns `None` if the lock
+/// file doesn't exist or doesn't record its holder.
+pub fn read_lock_holder(path: &Path) -> Option<LockHolder> {
+    let text = fs::read_to_string(path).ok()?;
+    LockHolder::parse(&text)
+}
+
+#[derive(Debug, Error)]
+pub enum FileLockError {
+    #[error("Timed out waiting for lock {}", path.display())]
+    Timeout {
+        path: PathBuf,
+        holder: Option<LockHolder>,
+    },
+    #[error("Failed to lock {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+static LOCK_TIMEOUT: Mutex<Option<Duration>> = Mutex::new(None);
+
+/// Sets how long locks taken with [`lock_timeout()`] wait for another process
+/// to release them. `None`, the default, waits indefinitely.
+pub fn set_lock_timeout(timeout: Option<Duration>) {
+    *LOCK_TIMEOUT.lock().unwrap() = timeout;
+}
+
+/// The timeout set by [`set_lock_timeout()`].
+pub fn lock_timeout() -> Option<Duration> {
+    *LOCK_TIMEOUT.lock().unwrap()
+}
+
+impl FileLock {
+    /// Waits at most `timeout` for the lock to be released by another
+    /// process, or indefinitely if `timeout` is `None`.
+    pub fn lock_with_timeout(
+        path: PathBuf,
+        timeout: Option<Duration>,
+    ) -> Result<FileLock, FileLockError> {
+        let Some(timeout) = timeout else {
+            return Ok(FileLock::lock(path));
+        };
+        let deadline = Instant::now() + timeout;
+        let mut interval = Duration::from_millis(1);
+        loop {
+            match FileLock::try_lock(path.clone()) {
+                Ok(Some(lock)) => return Ok(lock),
+                Ok(None) => {}
+                Err(source) => return Err(FileLockError::Io { path, source }),
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                let holder = read_lock_holder(&path);
+                return Err(FileLockError::Timeout { path, holder });
+            }
+            thread::sleep(interval.min(deadline - now));
+            interval = (interval * 2).min(Duration::from_millis(100));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::cmp::max;
-    use std::fs;
-    use std::thread;
-    use std::time::Duration;
 
     use super::*;
 
@@ -63,4 +200,40 @@ mod tests {
         let value = u32::from_le_bytes(data.try_into().unwrap());
         assert_eq!(value, num_threads as u32);
     }
+
+    #[test]
+    fn lock_holder() {
+        let temp_dir = testutils::new_temp_dir();
+        let lock_path = temp_dir.path().join("test.lock");
+        assert_eq!(read_lock_holder(&lock_path), None);
+        {
+            let _lock = FileLock::lock(lock_path.clone());
+            let holder = read_lock_holder(&lock_path).unwrap();
+            assert_eq!(holder.pid, std::process::id());
+            assert!(holder.acquired_at <= SystemTime::now());
+        }
+        assert_eq!(read_lock_holder(&lock_path), None);
+    }
+
+    #[test]
+    fn lock_timeout() {
+        let temp_dir = testutils::new_temp_dir();
+        let lock_path = temp_dir.path().join("test.lock");
+        let lock = FileLock::lock(lock_path.clone());
+        assert!(FileLock::try_lock(lock_path.clone()).unwrap().is_none());
+        let result =
+            FileLock::lock_with_timeout(lock_path.clone(), Some(Duration::from_millis(10)));
+        let Err(FileLockError::Timeout {
+            holder: Some(holder),
+            ..
+        }) = result
+        else {
+            panic!("lock should have timed out with a known holder");
+        };
+        assert_eq!(holder.pid, std::process::id());
+        drop(lock);
+        let result =
+            FileLock::lock_with_timeout(lock_path.clone(), Some(Duration::from_millis(10)));
+        assert!(result.is_ok());
+    }
 }
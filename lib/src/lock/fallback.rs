@@ -14,6 +14,7 @@
 
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::io;
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -21,6 +22,8 @@ use backoff::retry;
 use backoff::ExponentialBackoff;
 use tracing::instrument;
 
+use super::write_lock_holder;
+
 pub struct FileLock {
     path: PathBuf,
     _file: File,
@@ -28,26 +31,12 @@ pub struct FileLock {
 
 impl FileLock {
     pub fn lock(path: PathBuf) -> FileLock {
-        let mut options = OpenOptions::new();
-        options.create_new(true);
-        options.write(true);
-        let try_write_lock_file = || match options.open(&path) {
-            Ok(file) => Ok(FileLock {
-                path: path.clone(),
-                _file: file,
+        let try_write_lock_file = || match FileLock::try_lock(path.clone()) {
+            Ok(Some(file_lock)) => Ok(file_lock),
+            Ok(None) => Err(backoff::Error::Transient {
+                err: io::Error::from(io::ErrorKind::AlreadyExists),
+                retry_after: None,
             }),
-            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
-                Err(backoff::Error::Transient {
-                    err,
-                    retry_after: None,
-                })
-            }
-            Err(err) if cfg!(windows) && err.kind() == std::io::ErrorKind::PermissionDenied => {
-                Err(backoff::Error::Transient {
-                    err,
-                    retry_after: None,
-                })
-            }
             Err(err) => Err(backoff::Error::Permanent(err)),
         };
         let backoff = ExponentialBackoff {
@@ -64,6 +53,24 @@ impl FileLock {
             Ok(file_lock) => file_lock,
         }
     }
+
+    /// Takes the lock unless another process holds it.
+    pub fn try_lock(path: PathBuf) -> io::Result<Option<FileLock>> {
+        let mut options = OpenOptions::new();
+        options.create_new(true);
+        options.write(true);
+        match options.open(&path) {
+            Ok(file) => {
+                // The holder is only recorded for diagnostics, so failing to
+                // write it doesn't matter.
+                _ = write_lock_holder(&file);
+                Ok(Some(FileLock { path, _file: file }))
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => Ok(None),
+            Err(err) if cfg!(windows) && err.kind() == io::ErrorKind::PermissionDenied => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
 }
 
 impl Drop for FileLock {
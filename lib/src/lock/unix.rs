@@ -15,11 +15,16 @@
 #![allow(missing_docs)]
 
 use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
 use std::path::PathBuf;
 
 use rustix::fs::FlockOperation;
 use tracing::instrument;
 
+use super::write_lock_holder;
+
 pub struct FileLock {
     path: PathBuf,
     file: File,
@@ -29,7 +34,7 @@ impl FileLock {
     pub fn lock(path: PathBuf) -> FileLock {
         loop {
             // Create lockfile, or open pre-existing one
-            let file = File::create(&path).expect("failed to open lockfile");
+            let file = open_lock_file(&path).expect("failed to open lockfile");
             // If the lock was already held, wait for it to be released
             rustix::fs::flock(&file, FlockOperation::LockExclusive)
                 .expect("failed to lock lockfile");
@@ -43,9 +48,43 @@ impl FileLock {
                 continue;
             }
 
-            return Self { path, file };
+            return Self::acquired(path, file);
+        }
+    }
+
+    /// Takes the lock unless another process holds it.
+    pub fn try_lock(path: PathBuf) -> io::Result<Option<FileLock>> {
+        loop {
+            let file = open_lock_file(&path)?;
+            match rustix::fs::flock(&file, FlockOperation::NonBlockingLockExclusive) {
+                Ok(()) => {}
+                Err(rustix::io::Errno::WOULDBLOCK) => return Ok(None),
+                Err(err) => return Err(err.into()),
+            }
+            if rustix::fs::fstat(&file)?.st_nlink == 0 {
+                // Deleted by the previous holder, see `lock()`.
+                continue;
+            }
+            return Ok(Some(Self::acquired(path, file)));
         }
     }
+
+    fn acquired(path: PathBuf, file: File) -> Self {
+        // The holder is only recorded for diagnostics, so failing to write it
+        // doesn't matter.
+        _ = write_lock_holder(&file);
+        Self { path, file }
+    }
+}
+
+/// Opens the lockfile without truncating it, since it records the holder of
+/// the lock.
+fn open_lock_file(path: &Path) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
 }
 
 impl Drop for FileLock {
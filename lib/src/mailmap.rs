@@ -0,0 +1,229 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and applying Git `.mailmap` files.
+//!
+//! A mailmap maps the names and email addresses recorded in commits to the
+//! canonical identities of their authors. See `git help gitmailmap` for the
+//! file format.
+
+use std::io::Read as _;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::Signature;
+use crate::backend::TreeValue;
+use crate::commit::Commit;
+use crate::repo_path::RepoPath;
+
+/// A parsed `.mailmap` file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Mailmap {
+    entries: Vec<MailmapEntry>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct MailmapEntry {
+    proper_name: Option<String>,
+    proper_email: Option<String>,
+    commit_name: Option<String>,
+    commit_email: String,
+}
+
+impl Mailmap {
+    /// Parses the content of a `.mailmap` file. Malformed lines are ignored,
+    /// like Git does.
+    pub fn parse(text: &str) -> Self {
+        let entries = text.lines().filter_map(parse_mailmap_line).collect();
+        Mailmap { entries }
+    }
+
+    /// Reads the `.mailmap` file at the root of the commit's tree. Returns an
+    /// empty mailmap if there's no such file or if it's conflicted.
+    pub fn read_from_commit(commit: &Commit) -> BackendResult<Self> {
+        let path = RepoPath::from_internal_string(".mailmap");
+        let value = commit.tree()?.path_value(path)?;
+        let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+            return Ok(Mailmap::default());
+        };
+        let mut content = vec![];
+        commit
+            .store()
+            .read_file(path, id)?
+            .read_to_end(&mut content)
+            .map_err(|err| BackendError::ReadFile {
+                path: path.to_owned(),
+                id: id.clone(),
+                source: err.into(),
+            })?;
+        Ok(Mailmap::parse(&String::from_utf8_lossy(&content)))
+    }
+
+    /// Returns true if the mailmap has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the signature with the name and email replaced by the canonical
+    /// identity, if any.
+    ///
+    /// An entry that matches both the name and the email takes precedence over
+    /// an entry that matches only the email. Among equally specific entries,
+    /// the last one wins. Matching is case-insensitive.
+    pub fn resolve(&self, signature: &Signature) -> Signature {
+        let matches_email =
+            |entry: &&MailmapEntry| entry.commit_email.eq_ignore_ascii_case(&signature.email);
+        let entry = self
+            .entries
+            .iter()
+            .rev()
+            .filter(matches_email)
+            .find(|entry| {
+                entry
+                    .commit_name
+                    .as_ref()
+                    .is_some_and(|name| name.to_lowercase() == signature.name.to_lowercase())
+            })
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .rev()
+                    .filter(matches_email)
+                    .find(|entry| entry.commit_name.is_none())
+            });
+        let mut resolved = signature.clone();
+        if let Some(entry) = entry {
+            if let Some(name) = &entry.proper_name {
+                resolved.name = name.clone();
+            }
+            if let Some(email) = &entry.proper_email {
+                resolved.email = email.clone();
+            }
+        }
+        resolved
+    }
+}
+
+/// Parses a line of one of these forms:
+///
+/// ```text
+/// Proper Name <commit@email>
+/// <proper@email> <commit@email>
+/// Proper Name <proper@email> <commit@email>
+/// Proper Name <proper@email> Commit Name <commit@email>
+/// ```
+fn parse_mailmap_line(line: &str) -> Option<MailmapEntry> {
+    let line = line.split_once('#').map_or(line, |(line, _comment)| line);
+    let (name1, email1, rest) = parse_name_and_email(line)?;
+    let non_empty = |name: &str| (!name.is_empty()).then(|| name.to_owned());
+    match parse_name_and_email(rest) {
+        None => Some(MailmapEntry {
+            proper_name: non_empty(name1),
+            proper_email: None,
+            commit_name: None,
+            commit_email: email1.to_owned(),
+        }),
+        Some((name2, email2, _)) => Some(MailmapEntry {
+            proper_name: non_empty(name1),
+            proper_email: non_empty(email1),
+            commit_name: non_empty(name2),
+            commit_email: email2.to_owned(),
+        }),
+    }
+}
+
+/// Splits `Name <email> rest` into its trimmed parts.
+fn parse_name_and_email(text: &str) -> Option<(&str, &str, &str)> {
+    let (name, rest) = text.split_once('<')?;
+    let (email, rest) = rest.split_once('>')?;
+    Some((name.trim(), email.trim(), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MillisSinceEpoch;
+    use crate::backend::Timestamp;
+
+    fn signature(name: &str, email: &str) -> Signature {
+        Signature {
+            name: name.to_owned(),
+            email: email.to_owned(),
+            timestamp: Timestamp {
+                timestamp: MillisSinceEpoch(0),
+                tz_offset: 0,
+            },
+        }
+    }
+
+    fn resolve(mailmap: &Mailmap, name: &str, email: &str) -> (String, String) {
+        let resolved = mailmap.resolve(&signature(name, email));
+        (resolved.name, resolved.email)
+    }
+
+    #[test]
+    fn test_parse_mailmap() {
+        let mailmap = Mailmap::parse(
+            "# comment\n\nProper Name <commit@example.com>\n<proper@example.com> \
+             <other@example.com> # trailing comment\nBoth <both@example.com> \
+             <old@example.com>\nAlice <alice@example.com> alice <ALICE@example.org>\ngarbage \
+             line\n",
+        );
+        assert_eq!(mailmap.entries.len(), 4);
+        assert_eq!(
+            mailmap.entries[3],
+            MailmapEntry {
+                proper_name: Some("Alice".to_owned()),
+                proper_email: Some("alice@example.com".to_owned()),
+                commit_name: Some("alice".to_owned()),
+                commit_email: "ALICE@example.org".to_owned(),
+            }
+        );
+        assert!(Mailmap::parse("").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_mailmap() {
+        let mailmap = Mailmap::parse(
+            "Proper Name <commit@example.com>\n<proper@example.com> <other@example.com>\nBoth \
+             <both@example.com> <old@example.com>\nAlice <alice@example.com> alice \
+             <alice@example.org>\nFallback <alice@example.org>\n",
+        );
+        assert_eq!(
+            resolve(&mailmap, "nick", "Commit@Example.com"),
+            ("Proper Name".to_owned(), "Commit@Example.com".to_owned())
+        );
+        assert_eq!(
+            resolve(&mailmap, "nick", "other@example.com"),
+            ("nick".to_owned(), "proper@example.com".to_owned())
+        );
+        assert_eq!(
+            resolve(&mailmap, "nick", "old@example.com"),
+            ("Both".to_owned(), "both@example.com".to_owned())
+        );
+        // The entry matching the name takes precedence
+        assert_eq!(
+            resolve(&mailmap, "ALICE", "alice@example.org"),
+            ("Alice".to_owned(), "alice@example.com".to_owned())
+        );
+        assert_eq!(
+            resolve(&mailmap, "bob", "alice@example.org"),
+            ("Fallback".to_owned(), "alice@example.org".to_owned())
+        );
+        assert_eq!(
+            resolve(&mailmap, "nick", "unknown@example.com"),
+            ("nick".to_owned(), "unknown@example.com".to_owned())
+        );
+    }
+}
@@ -18,6 +18,7 @@
 use std::borrow::Borrow;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Write as _;
 use std::hash::Hash;
@@ -525,6 +526,28 @@ pub type MergedTreeVal<'a> = Merge<Option<&'a TreeValue>>;
 /// shouldn't be.
 pub type MergedTreeValue = Merge<Option<TreeValue>>;
 
+/// The mode of a file or symlink, which tells how its contents are
+/// interpreted.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FileMode {
+    /// A regular file.
+    Normal,
+    /// An executable file.
+    Executable,
+    /// A symlink, whose contents are its target.
+    Symlink,
+}
+
+impl Display for FileMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileMode::Normal => write!(f, "644"),
+            FileMode::Executable => write!(f, "755"),
+            FileMode::Symlink => write!(f, "symlink"),
+        }
+    }
+}
+
 impl MergedTreeValue {
     /// Create a `Merge` from a `backend::Conflict`, padding with `None` to
     /// make sure that there is exactly one more `adds()` than `removes()`.
@@ -588,6 +611,24 @@ where
         })
     }
 
+    /// If this merge contains only files, symlinks, or absent entries, returns
+    /// a merge of their modes.
+    pub fn to_mode_merge(&self) -> Option<Merge<Option<FileMode>>> {
+        self.maybe_map(|term| match borrow_tree_value(term.as_ref()) {
+            None => Some(None),
+            Some(TreeValue::File {
+                id: _,
+                executable: false,
+            }) => Some(Some(FileMode::Normal)),
+            Some(TreeValue::File {
+                id: _,
+                executable: true,
+            }) => Some(Some(FileMode::Executable)),
+            Some(TreeValue::Symlink(_)) => Some(Some(FileMode::Symlink)),
+            _ => None,
+        })
+    }
+
     /// If every non-`None` term of a `MergedTreeValue`
     /// is a `TreeValue::Tree`, this converts it to
     /// a `Merge<Tree>`, with empty trees instead of
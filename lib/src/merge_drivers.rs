@@ -0,0 +1,145 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merge drivers resolve the conflicts in files matching some patterns instead
+//! of leaving them to the user.
+
+#![allow(missing_docs)]
+
+use std::fmt::Debug;
+
+use bstr::BString;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+
+use crate::backend::BackendResult;
+use crate::backend::TreeValue;
+use crate::conflicts::extract_as_single_hunk;
+use crate::files;
+use crate::files::MergeResult;
+use crate::fileset::FilesetExpression;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::MergedTreeBuilder;
+use crate::repo_path::RepoPath;
+
+/// Resolves the conflicts of a file.
+pub trait MergeDriver: Debug + Send + Sync {
+    /// Merges the contents of the sides of the conflicted file at `path`.
+    /// Returns `None` if the file should be left conflicted.
+    fn merge(&self, path: &RepoPath, contents: &Merge<BString>) -> BackendResult<Option<BString>>;
+}
+
+/// Built-in merge drivers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// Keeps the lines of all sides of each conflicted hunk, in the order of
+    /// the sides.
+    Union,
+    /// Keeps the first side. When rebasing, that's the destination.
+    Ours,
+    /// Keeps the last side. When rebasing, that's the rebased commit.
+    Theirs,
+}
+
+impl MergeDriver for MergeStrategy {
+    fn merge(&self, _path: &RepoPath, contents: &Merge<BString>) -> BackendResult<Option<BString>> {
+        let content = match self {
+            MergeStrategy::Union => match files::merge(contents) {
+                MergeResult::Resolved(content) => content,
+                MergeResult::Conflict(hunks) => hunks
+                    .iter()
+                    .flat_map(|hunk| match hunk.as_resolved() {
+                        Some(content) => vec![content.as_slice()],
+                        None => hunk.adds().map(|content| content.as_slice()).collect_vec(),
+                    })
+                    .collect_vec()
+                    .concat()
+                    .into(),
+            },
+            MergeStrategy::Ours => contents.adds().next().unwrap().clone(),
+            MergeStrategy::Theirs => contents.adds().last().unwrap().clone(),
+        };
+        Ok(Some(content))
+    }
+}
+
+/// Merge drivers with the patterns of the files they apply to.
+#[derive(Debug, Default)]
+pub struct MergeDrivers {
+    drivers: Vec<(FilesetExpression, Box<dyn MergeDriver>)>,
+}
+
+impl MergeDrivers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.drivers.is_empty()
+    }
+
+    /// Adds a driver for the files matching `fileset`. If several drivers
+    /// match a file, the first one added is used.
+    pub fn add(&mut self, fileset: FilesetExpression, driver: Box<dyn MergeDriver>) {
+        self.drivers.push((fileset, driver));
+    }
+
+    /// Resolves the file conflicts in `tree` with the matching drivers.
+    pub fn resolve_tree(&self, tree: &MergedTree) -> BackendResult<MergedTree> {
+        let store = tree.store();
+        let matchers = self
+            .drivers
+            .iter()
+            .map(|(fileset, driver)| (fileset.to_matcher(), driver))
+            .collect_vec();
+        let mut tree_builder = MergedTreeBuilder::new(tree.id());
+        let mut has_resolutions = false;
+        for (path, conflict) in tree.conflicts() {
+            let Some((_, driver)) = matchers.iter().find(|(matcher, _)| matcher.matches(&path))
+            else {
+                continue;
+            };
+            let Some(file_merge) = conflict.to_file_merge() else {
+                continue;
+            };
+            let contents =
+                extract_as_single_hunk(&file_merge.simplify(), store, &path).block_on()?;
+            let Some(new_content) = driver.merge(&path, &contents)? else {
+                continue;
+            };
+            let new_file_id = store
+                .write_file(&path, &mut new_content.as_slice())
+                .block_on()?;
+            let executable = match conflict.to_executable_merge() {
+                Some(merge) => merge.resolve_trivial().copied().unwrap_or_default(),
+                None => false,
+            };
+            tree_builder.set_or_remove(
+                path,
+                Merge::normal(TreeValue::File {
+                    id: new_file_id,
+                    executable,
+                }),
+            );
+            has_resolutions = true;
+        }
+        if !has_resolutions {
+            return Ok(tree.clone());
+        }
+        let new_tree_id = tree_builder.write_tree(store)?;
+        store.get_root_tree(&new_tree_id)
+    }
+}
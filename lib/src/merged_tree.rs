@@ -18,6 +18,7 @@ use std::borrow::Borrow;
 use std::cmp::max;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::iter;
 use std::iter::zip;
@@ -36,6 +37,8 @@ use futures::TryStreamExt;
 use itertools::EitherOrBoth;
 use itertools::Itertools;
 use pollster::FutureExt;
+use rayon::iter::IntoParallelIterator as _;
+use rayon::iter::ParallelIterator as _;
 
 use crate::backend;
 use crate::backend::BackendResult;
@@ -616,13 +619,57 @@ impl Iterator for TreeEntriesIterator<'_> {
 /// single directory.
 struct ConflictsDirItem {
     entries: Vec<(RepoPathBuf, MergedTreeValue)>,
+    /// Subtrees of upcoming entries, which were read in parallel.
+    subtrees: HashMap<RepoPathBuf, BackendResult<Option<Merge<Tree>>>>,
+}
+
+impl ConflictsDirItem {
+    /// Reads the subtrees at `path`, which is the entry that was just popped,
+    /// or returns `None` if it's a conflict between files, trees, etc.
+    ///
+    /// Like `TreeDiffDirItem::read_subtrees()`, the subtrees of the upcoming
+    /// entries are read in parallel if there are many of them.
+    fn read_subtrees(
+        &mut self,
+        store: &Arc<Store>,
+        path: &RepoPath,
+        value: &MergedTreeValue,
+    ) -> BackendResult<Option<Merge<Tree>>> {
+        if let Some(subtrees) = self.subtrees.remove(path) {
+            return subtrees;
+        }
+        let upcoming = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|(_, value)| value.is_tree())
+            .take(PARALLEL_READ_BATCH_SIZE - 1)
+            .collect_vec();
+        if !value.is_tree() || upcoming.len() + 1 < PARALLEL_READ_THRESHOLD {
+            return value.to_tree_merge(store, path);
+        }
+        let (subtrees, upcoming_subtrees) = rayon::join(
+            || value.to_tree_merge(store, path),
+            || {
+                upcoming
+                    .into_par_iter()
+                    .map(|(path, value)| (path.clone(), value.to_tree_merge(store, path)))
+                    .collect::<Vec<_>>()
+            },
+        );
+        self.subtrees.extend(upcoming_subtrees);
+        subtrees
+    }
 }
 
 impl From<&Merge<Tree>> for ConflictsDirItem {
     fn from(trees: &Merge<Tree>) -> Self {
         let dir = trees.first().dir();
         if trees.is_resolved() {
-            return ConflictsDirItem { entries: vec![] };
+            return ConflictsDirItem {
+                entries: vec![],
+                subtrees: HashMap::new(),
+            };
         }
 
         let mut entries = vec![];
@@ -632,7 +679,10 @@ impl From<&Merge<Tree>> for ConflictsDirItem {
             }
         }
         entries.reverse();
-        ConflictsDirItem { entries }
+        ConflictsDirItem {
+            entries,
+            subtrees: HashMap::new(),
+        }
     }
 }
 
@@ -657,7 +707,7 @@ impl Iterator for ConflictIterator {
         while let Some(top) = self.stack.last_mut() {
             if let Some((path, tree_values)) = top.entries.pop() {
                 // TODO: propagate errors
-                if let Some(trees) = tree_values.to_tree_merge(&self.store, &path).unwrap() {
+                if let Some(trees) = top.read_subtrees(&self.store, &path, &tree_values).unwrap() {
                     // If all sides are trees or missing, descend into the merged tree
                     self.stack.push(ConflictsDirItem::from(&trees));
                 } else {
@@ -681,8 +731,21 @@ pub struct TreeDiffIterator<'matcher> {
     matcher: &'matcher dyn Matcher,
 }
 
+/// Directories with fewer subdirectories than this to diff or to scan for
+/// conflicts get their subtrees read one at a time.
+const PARALLEL_READ_THRESHOLD: usize = 16;
+/// The maximum number of subtrees to read ahead in parallel. Reading only a
+/// limited number ahead keeps the wasted work small when the consumer stops
+/// early.
+const PARALLEL_READ_BATCH_SIZE: usize = 64;
+
+/// The trees on the before and after sides of a diff entry.
+type TreePair = (Merge<Tree>, Merge<Tree>);
+
 struct TreeDiffDirItem {
     entries: Vec<(RepoPathBuf, MergedTreeValue, MergedTreeValue)>,
+    /// Subtrees of upcoming entries, which were read in parallel.
+    subtrees: HashMap<RepoPathBuf, BackendResult<TreePair>>,
 }
 
 enum TreeDiffItem {
@@ -760,18 +823,77 @@ impl TreeDiffDirItem {
             entries.push((path, before.cloned(), after.cloned()));
         }
         entries.reverse();
-        Self { entries }
+        Self {
+            entries,
+            subtrees: HashMap::new(),
+        }
+    }
+
+    /// Reads the subtrees at `path`, which is the entry that was just popped.
+    ///
+    /// If many of the upcoming entries are trees too, their subtrees are read
+    /// in parallel.
+    fn read_subtrees(
+        &mut self,
+        store: &Arc<Store>,
+        path: &RepoPath,
+        before: &MergedTreeValue,
+        after: &MergedTreeValue,
+    ) -> BackendResult<TreePair> {
+        if let Some(subtrees) = self.subtrees.remove(path) {
+            return subtrees;
+        }
+        let upcoming = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|(_, before, after)| before.is_tree() || after.is_tree())
+            .take(PARALLEL_READ_BATCH_SIZE - 1)
+            .collect_vec();
+        if upcoming.len() + 1 < PARALLEL_READ_THRESHOLD {
+            return read_tree_pair(store, path, before, after);
+        }
+        let (subtrees, upcoming_subtrees) = rayon::join(
+            || read_tree_pair(store, path, before, after),
+            || {
+                upcoming
+                    .into_par_iter()
+                    .map(|(path, before, after)| {
+                        (path.clone(), read_tree_pair(store, path, before, after))
+                    })
+                    .collect::<Vec<_>>()
+            },
+        );
+        self.subtrees.extend(upcoming_subtrees);
+        subtrees
     }
 }
 
+/// Gets the trees on both sides of a diff entry. A side that isn't a tree
+/// gets an empty tree.
+fn read_tree_pair(
+    store: &Arc<Store>,
+    dir: &RepoPath,
+    before: &MergedTreeValue,
+    after: &MergedTreeValue,
+) -> BackendResult<TreePair> {
+    let before_tree = TreeDiffIterator::trees(store, dir, before)?;
+    let after_tree = TreeDiffIterator::trees(store, dir, after)?;
+    Ok((before_tree, after_tree))
+}
+
 impl Iterator for TreeDiffIterator<'_> {
     type Item = TreeDiffEntry;
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(top) = self.stack.last_mut() {
-            let (path, before, after) = match top {
+            let (path, before, after, subtrees) = match top {
                 TreeDiffItem::Dir(dir) => match dir.entries.pop() {
-                    Some(entry) => entry,
+                    Some((path, before, after)) => {
+                        let subtrees = (before.is_tree() || after.is_tree())
+                            .then(|| dir.read_subtrees(&self.store, &path, &before, &after));
+                        (path, before, after, subtrees)
+                    }
                     None => {
                         self.stack.pop().unwrap();
                         continue;
@@ -791,22 +913,13 @@ impl Iterator for TreeDiffIterator<'_> {
 
             let tree_before = before.is_tree();
             let tree_after = after.is_tree();
-            let post_subdir = if tree_before || tree_after {
-                let (before_tree, after_tree) = match (
-                    Self::trees(&self.store, &path, &before),
-                    Self::trees(&self.store, &path, &after),
-                ) {
-                    (Ok(before_tree), Ok(after_tree)) => (before_tree, after_tree),
-                    (Err(before_err), _) => {
-                        return Some(TreeDiffEntry {
-                            path,
-                            values: Err(before_err),
-                        });
-                    }
-                    (_, Err(after_err)) => {
+            let post_subdir = if let Some(subtrees) = subtrees {
+                let (before_tree, after_tree) = match subtrees {
+                    Ok(trees) => trees,
+                    Err(err) => {
                         return Some(TreeDiffEntry {
                             path,
-                            values: Err(after_err),
+                            values: Err(err),
                         });
                     }
                 };
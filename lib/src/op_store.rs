@@ -280,6 +280,12 @@ pub struct View {
     // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
     // precise: the commit to which we most recently completed an update to).
     pub wc_commit_ids: HashMap<WorkspaceId, CommitId>,
+    /// Revisions marked by `jj bisect`, keyed by names like `bad` and
+    /// `good-<commit id>`.
+    pub bisect_refs: BTreeMap<String, RefTarget>,
+    /// Changes saved by `jj stash`, keyed by their commit id. The commits
+    /// aren't visible.
+    pub stash_refs: BTreeMap<String, RefTarget>,
 }
 
 /// Represents the state of the remote repo.
@@ -380,6 +386,9 @@ impl Operation {
             hostname: "".to_string(),
             username: "".to_string(),
             is_snapshot: false,
+            args: vec![],
+            workspace_id: None,
+            exit_status: None,
             tags: HashMap::new(),
         };
         Operation {
@@ -401,6 +410,16 @@ pub struct OperationMetadata {
     /// Whether this operation represents a pure snapshotting of the working
     /// copy.
     pub is_snapshot: bool,
+    /// The command-line arguments of the command that created the operation,
+    /// starting with the program name. Empty for operations written by older
+    /// versions, which only recorded them in the `args` tag.
+    pub args: Vec<String>,
+    /// The workspace the command that created the operation ran in, if any.
+    pub workspace_id: Option<WorkspaceId>,
+    /// The exit status of the command that created the operation, as known
+    /// when the operation was written. `None` if the command hadn't finished
+    /// its work yet, e.g. for snapshots of the working copy.
+    pub exit_status: Option<i32>,
     pub tags: HashMap<String, String>,
 }
 
@@ -263,6 +263,8 @@ pub struct ReparentStats {
     pub new_head_ids: Vec<OperationId>,
     /// The number of rewritten operations.
     pub rewritten_count: usize,
+    /// Maps the ids of the rewritten operations to their new ids.
+    pub rewritten_ids: HashMap<OperationId, OperationId>,
     /// The number of ancestor operations that become unreachable from the
     /// rewritten heads.
     pub unreachable_count: usize,
@@ -325,6 +327,64 @@ pub fn reparent_range(
     Ok(ReparentStats {
         new_head_ids,
         rewritten_count: rewritten_ids.len(),
+        rewritten_ids,
         unreachable_count: unreachable_ids.len(),
     })
 }
+
+/// Squashes the ancestors of `head_ops` that `should_keep` rejects into their
+/// nearest kept descendants.
+///
+/// Since each operation records the whole view, dropping an operation doesn't
+/// lose any state of the kept operations. The kept operations are reparented
+/// onto their nearest kept ancestors. The root operation and the `head_ops`
+/// are always kept.
+pub fn squash_operations(
+    op_store: &dyn OpStore,
+    head_ops: &[Operation],
+    mut should_keep: impl FnMut(&Operation) -> bool,
+) -> OpStoreResult<ReparentStats> {
+    let ops: Vec<_> = walk_ancestors(head_ops).try_collect()?;
+    let head_ids: HashSet<_> = head_ops.iter().map(|op| op.id()).collect();
+    // Maps the old operation ids to the ids of the kept operations that
+    // replace them.
+    let mut new_ids: HashMap<OperationId, Vec<OperationId>> = HashMap::new();
+    let mut rewritten_ids = HashMap::new();
+    let mut unreachable_count = 0;
+    for op in ops.iter().rev() {
+        let new_parent_ids = op
+            .parent_ids()
+            .iter()
+            .flat_map(|id| &new_ids[id])
+            .unique()
+            .cloned()
+            .collect_vec();
+        let keep = op.id() == op_store.root_operation_id()
+            || head_ids.contains(op.id())
+            || should_keep(op);
+        let replacement_ids = if !keep {
+            unreachable_count += 1;
+            new_parent_ids
+        } else if new_parent_ids == op.parent_ids() {
+            vec![op.id().clone()]
+        } else {
+            let mut data = op.store_operation().clone();
+            data.parents = new_parent_ids;
+            let new_id = op_store.write_operation(&data)?;
+            rewritten_ids.insert(op.id().clone(), new_id.clone());
+            vec![new_id]
+        };
+        new_ids.insert(op.id().clone(), replacement_ids);
+    }
+
+    let new_head_ids = head_ops
+        .iter()
+        .map(|op| new_ids[op.id()][0].clone())
+        .collect();
+    Ok(ReparentStats {
+        new_head_ids,
+        rewritten_count: rewritten_ids.len(),
+        rewritten_ids,
+        unreachable_count,
+    })
+}
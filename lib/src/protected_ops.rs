@@ -0,0 +1,111 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Operations protected from being abandoned or undone by accident.
+//!
+//! The ids of the protected operations are stored in the repo directory, one
+//! hex id per line. Since operations are content-addressed, the ids have to be
+//! updated when the operations are rewritten.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use itertools::Itertools as _;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::object_id::ObjectId as _;
+use crate::op_store::OperationId;
+
+const PROTECTED_OPS_FILE: &str = "protected_operations";
+
+/// Error that may occur when reading or writing the protected operations.
+#[derive(Debug, Error)]
+pub enum ProtectedOperationsError {
+    /// The file couldn't be read or written.
+    #[error(transparent)]
+    Path(#[from] PathError),
+    /// The file contains a line that isn't an operation id.
+    #[error("Invalid operation id {line:?} in {path}", path = path.display())]
+    InvalidId {
+        /// Path to the file.
+        path: PathBuf,
+        /// The invalid line.
+        line: String,
+    },
+}
+
+/// Reads the ids of the protected operations of the repo at `repo_path`.
+pub fn read_protected_operations(
+    repo_path: &Path,
+) -> Result<HashSet<OperationId>, ProtectedOperationsError> {
+    let path = repo_path.join(PROTECTED_OPS_FILE);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(err) => Err(err).context(&path)?,
+    };
+    text.lines()
+        .map(|line| {
+            OperationId::try_from_hex(line).map_err(|_| ProtectedOperationsError::InvalidId {
+                path: path.clone(),
+                line: line.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Replaces the protected operations of the repo at `repo_path`.
+pub fn write_protected_operations(
+    repo_path: &Path,
+    op_ids: &HashSet<OperationId>,
+) -> Result<(), ProtectedOperationsError> {
+    let path = repo_path.join(PROTECTED_OPS_FILE);
+    let text: String = op_ids.iter().map(|id| id.hex() + "\n").sorted().collect();
+    let mut temp_file = NamedTempFile::new_in(repo_path).context(repo_path)?;
+    temp_file
+        .write_all(text.as_bytes())
+        .context(temp_file.path())?;
+    temp_file
+        .persist(&path)
+        .map_err(|err| err.error)
+        .context(&path)?;
+    Ok(())
+}
+
+/// Updates the protected operations after the operations in `rewritten_ids`
+/// were rewritten, and the ones in `unreachable_ids` abandoned.
+pub fn update_protected_operations(
+    repo_path: &Path,
+    rewritten_ids: &HashMap<OperationId, OperationId>,
+    unreachable_ids: &HashSet<OperationId>,
+) -> Result<(), ProtectedOperationsError> {
+    let old_ids = read_protected_operations(repo_path)?;
+    let new_ids: HashSet<_> = old_ids
+        .iter()
+        .filter(|id| !unreachable_ids.contains(id))
+        .map(|id| rewritten_ids.get(id).unwrap_or(id).clone())
+        .collect();
+    if new_ids != old_ids {
+        write_protected_operations(repo_path, &new_ids)?;
+    }
+    Ok(())
+}
@@ -19,4 +19,17 @@ pub struct Commit {
     #[deprecated]
     #[prost(bool, tag = "9")]
     pub is_pruned: bool,
+    #[prost(message, repeated, tag = "11")]
+    pub recorded_copies: ::prost::alloc::vec::Vec<commit::RecordedCopy>,
+}
+/// Nested message and enum types in `Commit`.
+pub mod commit {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct RecordedCopy {
+        #[prost(string, tag = "1")]
+        pub source: ::prost::alloc::string::String,
+        #[prost(string, tag = "2")]
+        pub target: ::prost::alloc::string::String,
+    }
 }
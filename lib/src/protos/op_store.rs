@@ -96,6 +96,22 @@ pub struct Tag {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BisectRef {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub target: ::core::option::Option<RefTarget>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StashRef {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub target: ::core::option::Option<RefTarget>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct View {
     #[prost(bytes = "vec", repeated, tag = "1")]
     pub head_ids: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
@@ -125,6 +141,10 @@ pub struct View {
     /// Whether "@git" bookmark have been migrated to remote_targets.
     #[prost(bool, tag = "10")]
     pub has_git_refs_migrated_to_remote: bool,
+    #[prost(message, repeated, tag = "11")]
+    pub bisect_refs: ::prost::alloc::vec::Vec<BisectRef>,
+    #[prost(message, repeated, tag = "12")]
+    pub stash_refs: ::prost::alloc::vec::Vec<StashRef>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -160,6 +180,15 @@ pub struct OperationMetadata {
     pub username: ::prost::alloc::string::String,
     #[prost(bool, tag = "7")]
     pub is_snapshot: bool,
+    /// Command-line arguments, starting with the program name
+    #[prost(string, repeated, tag = "8")]
+    pub args: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// The workspace the command ran in
+    #[prost(string, optional, tag = "9")]
+    pub workspace_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// The exit status of the command, if known when the operation was written
+    #[prost(int32, optional, tag = "10")]
+    pub exit_status: ::core::option::Option<i32>,
     #[prost(map = "string, string", tag = "6")]
     pub tags: ::std::collections::HashMap<
         ::prost::alloc::string::String,
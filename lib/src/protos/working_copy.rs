@@ -21,6 +21,27 @@ pub struct FileStateEntry {
     #[prost(message, optional, tag = "2")]
     pub state: ::core::option::Option<FileState>,
 }
+/// Fingerprint of a directory from the last snapshot. Only recorded for
+/// directories whose entries were all tracked files or visited subdirectories.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DirectoryState {
+    #[prost(int64, tag = "1")]
+    pub mtime_millis_since_epoch: i64,
+    /// Number of tracked files and subdirectories in the directory
+    #[prost(uint32, tag = "2")]
+    pub num_entries: u32,
+    #[prost(string, repeated, tag = "3")]
+    pub subdirs: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DirectoryStateEntry {
+    #[prost(string, tag = "1")]
+    pub path: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub state: ::core::option::Option<DirectoryState>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SparsePatterns {
@@ -44,6 +65,8 @@ pub struct TreeState {
     pub sparse_patterns: ::core::option::Option<SparsePatterns>,
     #[prost(message, optional, tag = "4")]
     pub watchman_clock: ::core::option::Option<WatchmanClock>,
+    #[prost(message, repeated, tag = "7")]
+    pub directory_states: ::prost::alloc::vec::Vec<DirectoryStateEntry>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
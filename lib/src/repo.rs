@@ -54,8 +54,10 @@ use crate::index::IndexReadError;
 use crate::index::IndexStore;
 use crate::index::MutableIndex;
 use crate::index::ReadonlyIndex;
+use crate::learned_resolutions::LearnedResolutions;
 use crate::local_backend::LocalBackend;
 use crate::merge::MergeBuilder;
+use crate::merge_drivers::MergeDrivers;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId;
 use crate::object_id::PrefixResolution;
@@ -845,6 +847,8 @@ pub struct MutableRepo {
     //   commits. However, if the type is `Abandoned`, a new working-copy commit should be created
     //   on top of all of the new commits instead.
     parent_mapping: HashMap<CommitId, Rewrite>,
+    merge_drivers: Option<Arc<MergeDrivers>>,
+    learned_resolutions: Option<Arc<LearnedResolutions>>,
 }
 
 impl MutableRepo {
@@ -860,6 +864,8 @@ impl MutableRepo {
             index: mut_index,
             view: DirtyCell::with_clean(mut_view),
             parent_mapping: Default::default(),
+            merge_drivers: None,
+            learned_resolutions: None,
         }
     }
 
@@ -875,6 +881,54 @@ impl MutableRepo {
         self.index.as_ref()
     }
 
+    /// Returns the merge drivers that are applied to conflicts when commits
+    /// are rebased, if any.
+    pub fn merge_drivers(&self) -> Option<&Arc<MergeDrivers>> {
+        self.merge_drivers.as_ref()
+    }
+
+    /// Enables applying `merge_drivers` to conflicts when commits are rebased.
+    pub fn set_merge_drivers(&mut self, merge_drivers: Arc<MergeDrivers>) {
+        self.merge_drivers = Some(merge_drivers);
+    }
+
+    /// Returns the learned resolutions that are applied to conflicts when
+    /// commits are rebased, if enabled.
+    pub fn learned_resolutions(&self) -> Option<&Arc<LearnedResolutions>> {
+        self.learned_resolutions.as_ref()
+    }
+
+    /// Enables applying `learned_resolutions` to conflicts when commits are
+    /// rebased, and recording new ones by `learn_resolutions()`.
+    pub fn set_learned_resolutions(&mut self, learned_resolutions: Arc<LearnedResolutions>) {
+        self.learned_resolutions = Some(learned_resolutions);
+    }
+
+    /// Records the resolutions of the conflicts that were resolved by
+    /// rewriting commits in this transaction without changing their parents.
+    /// Returns the number of recorded hunks. Does nothing unless learned
+    /// resolutions are enabled.
+    pub fn learn_resolutions(&self) -> BackendResult<usize> {
+        let Some(learned_resolutions) = &self.learned_resolutions else {
+            return Ok(0);
+        };
+        let mut num_learned = 0;
+        for (old_id, rewrite) in self.parent_mapping.iter().sorted_by_key(|(id, _)| *id) {
+            let Rewrite::Rewritten(new_id) = rewrite else {
+                continue;
+            };
+            let old_commit = self.store().get_commit(old_id)?;
+            let new_commit = self.store().get_commit(new_id)?;
+            // Conflicts resolved by rebasing weren't resolved by the user.
+            if !old_commit.has_conflict()? || new_commit.parent_ids() != old_commit.parent_ids() {
+                continue;
+            }
+            num_learned +=
+                learned_resolutions.learn_from_trees(&old_commit.tree()?, &new_commit.tree()?)?;
+        }
+        Ok(num_learned)
+    }
+
     pub fn has_changes(&self) -> bool {
         !(self.parent_mapping.is_empty() && self.view() == &self.base_repo.view)
     }
@@ -1232,11 +1286,15 @@ impl MutableRepo {
         mut callback: impl FnMut(CommitRewriter) -> BackendResult<()>,
     ) -> BackendResult<()> {
         let mut to_visit = self.find_descendants_to_rebase(roots)?;
-        while let Some(old_commit) = to_visit.pop() {
-            let new_parent_ids = self.new_parents(old_commit.parent_ids());
-            let rewriter = CommitRewriter::new(self, old_commit, new_parent_ids);
-            callback(rewriter)?;
-        }
+        let store = self.store().clone();
+        store.with_write_batch(|| {
+            while let Some(old_commit) = to_visit.pop() {
+                let new_parent_ids = self.new_parents(old_commit.parent_ids());
+                let rewriter = CommitRewriter::new(self, old_commit, new_parent_ids);
+                callback(rewriter)?;
+            }
+            Ok(())
+        })?;
         self.update_rewritten_references(settings)?;
         // Since we didn't necessarily visit all descendants of rewritten commits (e.g.
         // if they were rewritten in the callback), there can still be commits left to
@@ -1601,6 +1659,38 @@ impl MutableRepo {
         view.set_tag_target(name, new_target);
     }
 
+    pub fn get_bisect_ref(&self, name: &str) -> RefTarget {
+        self.view.with_ref(|v| v.get_bisect_ref(name).clone())
+    }
+
+    pub fn set_bisect_ref_target(&mut self, name: &str, target: RefTarget) {
+        self.view_mut().set_bisect_ref_target(name, target);
+    }
+
+    fn merge_bisect_ref(&mut self, name: &str, base_target: &RefTarget, other_target: &RefTarget) {
+        let view = self.view.get_mut();
+        let index = self.index.as_index();
+        let self_target = view.get_bisect_ref(name);
+        let new_target = merge_ref_targets(index, self_target, base_target, other_target);
+        view.set_bisect_ref_target(name, new_target);
+    }
+
+    pub fn get_stash_ref(&self, name: &str) -> RefTarget {
+        self.view.with_ref(|v| v.get_stash_ref(name).clone())
+    }
+
+    pub fn set_stash_ref_target(&mut self, name: &str, target: RefTarget) {
+        self.view_mut().set_stash_ref_target(name, target);
+    }
+
+    fn merge_stash_ref(&mut self, name: &str, base_target: &RefTarget, other_target: &RefTarget) {
+        let view = self.view.get_mut();
+        let index = self.index.as_index();
+        let self_target = view.get_stash_ref(name);
+        let new_target = merge_ref_targets(index, self_target, base_target, other_target);
+        view.set_stash_ref_target(name, new_target);
+    }
+
     pub fn get_git_ref(&self, name: &str) -> RefTarget {
         self.view.with_ref(|v| v.get_git_ref(name).clone())
     }
@@ -1710,6 +1800,16 @@ impl MutableRepo {
             self.merge_tag(name, base_target, other_target);
         }
 
+        let changed_bisect_refs = diff_named_ref_targets(base.bisect_refs(), other.bisect_refs());
+        for (name, (base_target, other_target)) in changed_bisect_refs {
+            self.merge_bisect_ref(name, base_target, other_target);
+        }
+
+        let changed_stash_refs = diff_named_ref_targets(base.stash_refs(), other.stash_refs());
+        for (name, (base_target, other_target)) in changed_stash_refs {
+            self.merge_stash_ref(name, base_target, other_target);
+        }
+
         let changed_git_refs = diff_named_ref_targets(base.git_refs(), other.git_refs());
         for (name, (base_target, other_target)) in changed_git_refs {
             self.merge_git_ref(name, base_target, other_target);
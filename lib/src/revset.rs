@@ -45,6 +45,7 @@ use crate::object_id::PrefixResolution;
 use crate::op_store::RemoteRefState;
 use crate::op_store::WorkspaceId;
 use crate::repo::Repo;
+use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathUiConverter;
 use crate::revset_parser;
 pub use crate::revset_parser::expect_literal;
@@ -161,6 +162,9 @@ pub enum RevsetFilterPredicate {
     },
     /// Commits with conflicts
     HasConflict,
+    /// Commits modifying the file, following it across the copies and renames
+    /// recorded by the backend, like `git log --follow`.
+    Follows(RepoPathBuf),
     /// Custom predicates provided by extensions
     Extension(Rc<dyn RevsetFilterExtension>),
 }
@@ -783,6 +787,19 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
             RevsetFilterPredicate::DiffContains { text, files },
         ))
     });
+    map.insert("follows", |function, context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let ctx = context.workspace.as_ref().ok_or_else(|| {
+            RevsetParseError::with_span(RevsetParseErrorKind::FsPathWithoutWorkspace, arg.span)
+        })?;
+        let input: String = revset_parser::expect_literal("string", arg)?;
+        let path = ctx.path_converter.parse_file_path(&input).map_err(|err| {
+            RevsetParseError::expression("Invalid file path", arg.span).with_source(err)
+        })?;
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::Follows(
+            path,
+        )))
+    });
     map.insert("conflict", |function, _context| {
         function.expect_no_arguments()?;
         Ok(RevsetExpression::filter(RevsetFilterPredicate::HasConflict))
@@ -2669,6 +2686,11 @@ mod tests {
         insta::assert_debug_snapshot!(
             parse_with_workspace("file(all())", &WorkspaceId::default()).unwrap(),
             @"Filter(File(All))");
+        insta::assert_debug_snapshot!(
+            parse_with_workspace("follows(foo)", &WorkspaceId::default()).unwrap(),
+            @r###"Filter(Follows("foo"))"###);
+        assert!(parse_with_workspace("follows()", &WorkspaceId::default()).is_err());
+        assert!(parse_with_workspace("follows(foo, bar)", &WorkspaceId::default()).is_err());
         insta::assert_debug_snapshot!(
             parse_with_workspace(r#"file(file:"foo")"#, &WorkspaceId::default()).unwrap(),
             @r###"Filter(File(Pattern(FilePath("foo"))))"###);
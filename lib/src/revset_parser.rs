@@ -1709,15 +1709,37 @@ mod tests {
             }
         );
 
-        // Keyword argument isn't supported for now.
+        // Keyword arguments are matched by parameter name.
+        assert_eq!(
+            with_aliases([("F(x)", "x")]).parse_normalized("F(x=y)"),
+            parse_normalized("y")
+        );
+        assert_eq!(
+            with_aliases([("F(x,y)", "x|y")]).parse_normalized("F(y=b, x=a)"),
+            parse_normalized("a|b")
+        );
+        assert_eq!(
+            with_aliases([("F(x,y)", "x|y")]).parse_normalized("F(a, y=b)"),
+            parse_normalized("a|b")
+        );
         assert_eq!(
             with_aliases([("F(x)", "x")])
-                .parse("F(x=y)")
+                .parse("F(z=y)")
+                .unwrap_err()
+                .kind,
+            RevsetParseErrorKind::InvalidFunctionArguments {
+                name: "F".to_owned(),
+                message: r#"Unexpected keyword argument "z""#.to_owned()
+            }
+        );
+        assert_eq!(
+            with_aliases([("F(x,y)", "x|y")])
+                .parse("F(a, x=b)")
                 .unwrap_err()
                 .kind,
             RevsetParseErrorKind::InvalidFunctionArguments {
                 name: "F".to_owned(),
-                message: "Unexpected keyword arguments".to_owned()
+                message: r#"Got multiple values for keyword "x""#.to_owned()
             }
         );
 
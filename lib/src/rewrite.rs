@@ -252,9 +252,13 @@ impl<'repo> CommitRewriter<'repo> {
             let old_base_tree = merge_commit_trees(self.mut_repo, &old_parents)?;
             let new_base_tree = merge_commit_trees(self.mut_repo, &new_parents)?;
             let old_tree = self.old_commit.tree()?;
+            let new_tree = resolve_configured_conflicts(
+                self.mut_repo,
+                new_base_tree.merge(&old_base_tree, &old_tree)?,
+            )?;
             (
                 old_base_tree.id() == *self.old_commit.tree_id(),
-                new_base_tree.merge(&old_base_tree, &old_tree)?.id(),
+                new_tree.id(),
             )
         };
         // Ensure we don't abandon commits with multiple parents (merge commits), even
@@ -296,6 +300,25 @@ impl<'repo> CommitRewriter<'repo> {
     }
 }
 
+/// Resolves the conflicts in `tree` with the merge drivers and the learned
+/// resolutions enabled in `mut_repo`.
+fn resolve_configured_conflicts(
+    mut_repo: &MutableRepo,
+    mut tree: MergedTree,
+) -> BackendResult<MergedTree> {
+    if let Some(merge_drivers) = mut_repo.merge_drivers() {
+        if tree.has_conflict() {
+            tree = merge_drivers.resolve_tree(&tree)?;
+        }
+    }
+    if let Some(learned_resolutions) = mut_repo.learned_resolutions() {
+        if tree.has_conflict() {
+            tree = learned_resolutions.resolve_tree(&tree)?;
+        }
+    }
+    Ok(tree)
+}
+
 pub enum RebasedCommit {
     Rewritten(Commit),
     Abandoned { parent: Commit },
@@ -445,9 +468,13 @@ impl<'settings, 'repo> DescendantRebaser<'settings, 'repo> {
     }
 
     pub fn rebase_all(&mut self) -> BackendResult<()> {
-        while let Some(old_commit) = self.to_visit.pop() {
-            self.rebase_one(old_commit)?;
-        }
+        let store = self.mut_repo.store().clone();
+        store.with_write_batch(|| {
+            while let Some(old_commit) = self.to_visit.pop() {
+                self.rebase_one(old_commit)?;
+            }
+            Ok(())
+        })?;
         self.mut_repo.update_rewritten_references(self.settings)
     }
 }
@@ -804,22 +831,26 @@ pub fn move_commits(
     // TODO(ilyagr): Consider making it possible for descendants of the target set
     // to become emptied, like --skip-empty. This would require writing careful
     // tests.
-    while let Some(old_commit_id) = to_visit.pop() {
-        let old_commit = to_visit_commits.get(&old_commit_id).unwrap();
-        let parent_ids = to_visit_commits_new_parents.get(&old_commit_id).unwrap();
-        let new_parent_ids = mut_repo.new_parents(parent_ids);
-        let rewriter = CommitRewriter::new(mut_repo, old_commit.clone(), new_parent_ids);
-        if rewriter.parents_changed() {
-            rewriter.rebase(settings)?.write()?;
-            if target_commit_ids.contains(&old_commit_id) {
-                num_rebased_targets += 1;
+    let store = mut_repo.store().clone();
+    store.with_write_batch(|| {
+        while let Some(old_commit_id) = to_visit.pop() {
+            let old_commit = to_visit_commits.get(&old_commit_id).unwrap();
+            let parent_ids = to_visit_commits_new_parents.get(&old_commit_id).unwrap();
+            let new_parent_ids = mut_repo.new_parents(parent_ids);
+            let rewriter = CommitRewriter::new(mut_repo, old_commit.clone(), new_parent_ids);
+            if rewriter.parents_changed() {
+                rewriter.rebase(settings)?.write()?;
+                if target_commit_ids.contains(&old_commit_id) {
+                    num_rebased_targets += 1;
+                } else {
+                    num_rebased_descendants += 1;
+                }
             } else {
-                num_rebased_descendants += 1;
+                num_skipped_rebases += 1;
             }
-        } else {
-            num_skipped_rebases += 1;
         }
-    }
+        Ok(())
+    })?;
     mut_repo.update_rewritten_references(settings)?;
 
     Ok(MoveCommitsStats {
@@ -14,6 +14,8 @@
 
 #![allow(missing_docs)]
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -26,6 +28,7 @@ use crate::backend::ChangeId;
 use crate::backend::Commit;
 use crate::backend::Signature;
 use crate::backend::Timestamp;
+use crate::conflicts::ConflictMarkerStyle;
 use crate::fmt_util::binary_prefix;
 use crate::fsmonitor::FsmonitorSettings;
 use crate::signing::SignBehavior;
@@ -42,19 +45,61 @@ pub struct RepoSettings {
     _config: config::Config,
 }
 
+/// Trees and small files are read over and over by diffs and rebases, so
+/// caching a few MiB of them avoids most of the decompression and delta
+/// resolution.
+const DEFAULT_GIT_OBJECT_CACHE_SIZE: usize = 16 << 20;
+
 #[derive(Debug, Clone)]
 pub struct GitSettings {
     pub auto_local_bookmark: bool,
     pub abandon_unreachable_commits: bool,
+    /// URL rewrite rules keyed by the replacement base URL, like
+    /// `url.<base>.insteadOf` in Git config.
+    pub url_rewrites: BTreeMap<String, GitUrlRewrite>,
+    /// Proxy used for remotes without a proxy of their own. An empty string
+    /// disables the proxy.
+    pub proxy: Option<String>,
+    /// Proxies keyed by remote name.
+    pub remote_proxies: HashMap<String, String>,
+    /// Memory in bytes used to cache decompressed Git objects. Zero disables
+    /// the cache.
+    pub object_cache_size: usize,
+}
+
+/// URL prefixes to be replaced by the base URL of a `git.url.<base>` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GitUrlRewrite {
+    #[serde(default)]
+    pub instead_of: Vec<String>,
+    #[serde(default)]
+    pub push_instead_of: Vec<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+struct GitRemoteConfig {
+    proxy: Option<String>,
 }
 
 impl GitSettings {
     pub fn from_config(config: &config::Config) -> Self {
+        let remotes: HashMap<String, GitRemoteConfig> =
+            config.get("git.remotes").unwrap_or_default();
         GitSettings {
             auto_local_bookmark: config.get_bool("git.auto-local-branch").unwrap_or(false),
             abandon_unreachable_commits: config
                 .get_bool("git.abandon-unreachable-commits")
                 .unwrap_or(true),
+            url_rewrites: config.get("git.url").unwrap_or_default(),
+            proxy: config.get_string("git.proxy").ok(),
+            remote_proxies: remotes
+                .into_iter()
+                .filter_map(|(name, remote)| Some((name, remote.proxy?)))
+                .collect(),
+            object_cache_size: config
+                .get("git.object-cache-size")
+                .unwrap_or(DEFAULT_GIT_OBJECT_CACHE_SIZE),
         }
     }
 }
@@ -64,6 +109,10 @@ impl Default for GitSettings {
         GitSettings {
             auto_local_bookmark: false,
             abandon_unreachable_commits: true,
+            url_rewrites: BTreeMap::new(),
+            proxy: None,
+            remote_proxies: HashMap::new(),
+            object_cache_size: DEFAULT_GIT_OBJECT_CACHE_SIZE,
         }
     }
 }
@@ -246,6 +295,20 @@ impl UserSettings {
         }
     }
 
+    pub fn conflict_marker_style(&self) -> Result<ConflictMarkerStyle, config::ConfigError> {
+        Ok(self
+            .config
+            .get("merge.conflict-marker-style")
+            .optional()?
+            .unwrap_or_default())
+    }
+
+    pub fn learn_resolutions(&self) -> bool {
+        self.config
+            .get_bool("merge.learn-resolutions")
+            .unwrap_or(false)
+    }
+
     // separate from sign_settings as those two are needed in pretty different
     // places
     pub fn signing_backend(&self) -> Option<String> {
@@ -354,6 +354,28 @@ fn io_to_write_error(err: std::io::Error, object_type: &'static str) -> OpStoreE
     }
 }
 
+/// Encodes the operation in the format it's stored in.
+pub(crate) fn encode_operation(operation: &Operation) -> Vec<u8> {
+    operation_to_proto(operation).encode_to_vec()
+}
+
+/// Decodes an operation encoded by [`encode_operation()`].
+pub(crate) fn decode_operation(buf: &[u8]) -> Result<Operation, prost::DecodeError> {
+    let proto = crate::protos::op_store::Operation::decode(buf)?;
+    Ok(operation_from_proto(proto))
+}
+
+/// Encodes the view in the format it's stored in.
+pub fn encode_view(view: &View) -> Vec<u8> {
+    view_to_proto(view).encode_to_vec()
+}
+
+/// Decodes a view encoded by [`encode_view()`].
+pub fn decode_view(buf: &[u8]) -> Result<View, prost::DecodeError> {
+    let proto = crate::protos::op_store::View::decode(buf)?;
+    Ok(view_from_proto(proto))
+}
+
 fn timestamp_to_proto(timestamp: &Timestamp) -> crate::protos::op_store::Timestamp {
     crate::protos::op_store::Timestamp {
         millis_since_epoch: timestamp.timestamp.0,
@@ -378,6 +400,12 @@ fn operation_metadata_to_proto(
         hostname: metadata.hostname.clone(),
         username: metadata.username.clone(),
         is_snapshot: metadata.is_snapshot,
+        args: metadata.args.clone(),
+        workspace_id: metadata
+            .workspace_id
+            .as_ref()
+            .map(|id| id.as_str().to_owned()),
+        exit_status: metadata.exit_status,
         tags: metadata.tags.clone(),
     }
 }
@@ -394,6 +422,9 @@ fn operation_metadata_from_proto(
         hostname: proto.hostname,
         username: proto.username,
         is_snapshot: proto.is_snapshot,
+        args: proto.args,
+        workspace_id: proto.workspace_id.map(WorkspaceId::new),
+        exit_status: proto.exit_status,
         tags: proto.tags,
     }
 }
@@ -455,6 +486,20 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
 
     proto.git_head = ref_target_to_proto(&view.git_head);
 
+    for (name, target) in &view.bisect_refs {
+        proto.bisect_refs.push(crate::protos::op_store::BisectRef {
+            name: name.clone(),
+            target: ref_target_to_proto(target),
+        });
+    }
+
+    for (name, target) in &view.stash_refs {
+        proto.stash_refs.push(crate::protos::op_store::StashRef {
+            name: name.clone(),
+            target: ref_target_to_proto(target),
+        });
+    }
+
     proto
 }
 
@@ -494,6 +539,16 @@ fn view_from_proto(proto: crate::protos::op_store::View) -> View {
         view.git_refs.insert(git_ref.name, target);
     }
 
+    for bisect_ref in proto.bisect_refs {
+        view.bisect_refs
+            .insert(bisect_ref.name, ref_target_from_proto(bisect_ref.target));
+    }
+
+    for stash_ref in proto.stash_refs {
+        view.stash_refs
+            .insert(stash_ref.name, ref_target_from_proto(stash_ref.target));
+    }
+
     #[allow(deprecated)]
     if proto.git_head.is_some() {
         view.git_head = ref_target_from_proto(proto.git_head);
@@ -768,6 +823,8 @@ mod tests {
                 WorkspaceId::default() => default_wc_commit_id,
                 WorkspaceId::new("test".to_string()) => test_wc_commit_id,
             },
+            bisect_refs: btreemap! {},
+            stash_refs: btreemap! {},
         }
     }
 
@@ -791,6 +848,9 @@ mod tests {
                 hostname: "some.host.example.com".to_string(),
                 username: "someone".to_string(),
                 is_snapshot: false,
+                args: vec!["jj".to_string(), "new".to_string()],
+                workspace_id: Some(WorkspaceId::default()),
+                exit_status: Some(0),
                 tags: hashmap! {
                     "key1".to_string() => "value1".to_string(),
                     "key2".to_string() => "value2".to_string(),
@@ -804,7 +864,7 @@ mod tests {
         // Test exact output so we detect regressions in compatibility
         assert_snapshot!(
             ViewId::new(blake2b_hash(&create_view()).to_vec()).hex(),
-            @"f426676b3a2f7c6b9ec8677cb05ed249d0d244ab7e86a7c51117e2d8a4829db65e55970c761231e2107d303bf3d33a1f2afdd4ed2181f223e99753674b20a35e"
+            @"b3a09ee9978e256d351a971d5921aff8c102d71bc3c5cb2aac7601b559021ce5e56b603c798ce95e0893ef95182e42593b116ebfecea85a8a084b4c5b9d58e0f"
         );
     }
 
@@ -813,7 +873,7 @@ mod tests {
         // Test exact output so we detect regressions in compatibility
         assert_snapshot!(
             OperationId::new(blake2b_hash(&create_operation()).to_vec()).hex(),
-            @"20b495d54aa3be3a672a2ed6dbbf7a711dabce4cc0161d657e5177070491c1e780eec3fd35c2aa9dcc22371462aeb412a502a847f29419e65718f56a0ad1b2d0"
+            @"09cf40a47ed4f437b9519310590eb11e1f9a16820c8e28570288acaa3064fd582afb1cf856b7fd8a70e8b2b978b90148ecaef3beed7abb4704c7152fc06f9f58"
         );
     }
 
@@ -827,6 +887,46 @@ mod tests {
         assert_eq!(read_view, view);
     }
 
+    #[test]
+    fn test_read_write_view_with_bisect_refs() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = SimpleOpStore::init(temp_dir.path());
+        let view = View {
+            bisect_refs: btreemap! {
+                "bad".to_string() => RefTarget::normal(CommitId::from_hex("ccc111")),
+                "good-ccc222".to_string() => RefTarget::normal(CommitId::from_hex("ccc222")),
+            },
+            ..create_view()
+        };
+        let view_id = store.write_view(&view).unwrap();
+        let read_view = store.read_view(&view_id).unwrap();
+        assert_eq!(read_view, view);
+        // Bisection state is part of the view's identity
+        assert_ne!(view_id, store.write_view(&create_view()).unwrap());
+    }
+
+    #[test]
+    fn test_read_write_view_with_stash_refs() {
+        let temp_dir = testutils::new_temp_dir();
+        let store = SimpleOpStore::init(temp_dir.path());
+        let refs = btreemap! {
+            "ccc111".to_string() => RefTarget::normal(CommitId::from_hex("ccc111")),
+        };
+        let view = View {
+            stash_refs: refs.clone(),
+            ..create_view()
+        };
+        let view_id = store.write_view(&view).unwrap();
+        let read_view = store.read_view(&view_id).unwrap();
+        assert_eq!(read_view, view);
+        // The same refs stored as bisection state make a different view
+        let bisect_view = View {
+            bisect_refs: refs,
+            ..create_view()
+        };
+        assert_ne!(view_id, store.write_view(&bisect_view).unwrap());
+    }
+
     #[test]
     fn test_read_write_operation() {
         let temp_dir = testutils::new_temp_dir();
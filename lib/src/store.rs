@@ -15,6 +15,7 @@
 #![allow(missing_docs)]
 
 use std::any::Any;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::io::Read;
@@ -23,7 +24,10 @@ use std::sync::Mutex;
 use std::time::SystemTime;
 
 use clru::CLruCache;
+use futures::future;
+use futures::stream;
 use futures::stream::BoxStream;
+use futures::StreamExt as _;
 use pollster::FutureExt;
 
 use crate::backend;
@@ -38,6 +42,7 @@ use crate::backend::MergedTreeId;
 use crate::backend::SigningFn;
 use crate::backend::SymlinkId;
 use crate::backend::TreeId;
+use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::index::Index;
 use crate::merge::Merge;
@@ -54,6 +59,10 @@ use crate::tree_builder::TreeBuilder;
 const COMMIT_CACHE_CAPACITY: usize = 100;
 const TREE_CACHE_CAPACITY: usize = 1000;
 
+// Number of commits written before a write batch is flushed, so the backend
+// doesn't buffer an unbounded amount of data.
+const WRITE_BATCH_SIZE: usize = 1000;
+
 /// Wraps the low-level backend and makes it return more convenient types. Also
 /// adds caching.
 pub struct Store {
@@ -61,6 +70,15 @@ pub struct Store {
     signer: Signer,
     commit_cache: Mutex<CLruCache<CommitId, Arc<backend::Commit>>>,
     tree_cache: Mutex<CLruCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
+    write_batch: Mutex<WriteBatchState>,
+}
+
+#[derive(Debug, Default)]
+struct WriteBatchState {
+    /// Number of nested batches that have been started but not finished.
+    depth: usize,
+    /// Number of commits written since the backend's batch was started.
+    num_commits: usize,
 }
 
 impl Debug for Store {
@@ -78,9 +96,16 @@ impl Store {
             signer,
             commit_cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
             tree_cache: Mutex::new(CLruCache::new(TREE_CACHE_CAPACITY.try_into().unwrap())),
+            write_batch: Mutex::new(WriteBatchState::default()),
         })
     }
 
+    /// Returns the backend. Reading objects from it bypasses the caches of the
+    /// store.
+    pub fn backend(&self) -> &dyn Backend {
+        self.backend.as_ref()
+    }
+
     pub fn backend_impl(&self) -> &dyn Any {
         self.backend.as_any()
     }
@@ -89,13 +114,99 @@ impl Store {
         &self.signer
     }
 
+    /// Get copy records for the dag range `root..head`.
+    ///
+    /// The copies recorded in the `head` commit take precedence over the ones
+    /// detected by the backend for the same targets.
     pub fn get_copy_records(
         &self,
         paths: Option<&[RepoPathBuf]>,
         root: &CommitId,
         head: &CommitId,
     ) -> BackendResult<BoxStream<BackendResult<CopyRecord>>> {
-        self.backend.get_copy_records(paths, root, head)
+        let detected = self.backend.get_copy_records(paths, root, head)?;
+        let recorded = self.get_recorded_copy_records(paths, root, head)?;
+        if recorded.is_empty() {
+            return Ok(detected);
+        }
+        let recorded_targets: HashSet<RepoPathBuf> = recorded
+            .iter()
+            .map(|record| record.target.clone())
+            .collect();
+        let detected = detected.filter(move |record| {
+            future::ready(match record {
+                Ok(record) => !recorded_targets.contains(&record.target),
+                Err(_) => true,
+            })
+        });
+        Ok(Box::pin(
+            stream::iter(recorded.into_iter().map(Ok)).chain(detected),
+        ))
+    }
+
+    /// Turns the copies recorded in the `head` commit into copy records. Copies
+    /// whose source isn't a file in `root`, or whose target is no longer a file
+    /// in `head`, are skipped.
+    fn get_recorded_copy_records(
+        &self,
+        paths: Option<&[RepoPathBuf]>,
+        root: &CommitId,
+        head: &CommitId,
+    ) -> BackendResult<Vec<CopyRecord>> {
+        let head_commit = self.get_backend_commit(head).block_on()?;
+        let mut records = vec![];
+        for recorded_copy in &head_commit.recorded_copies {
+            if !paths.map_or(true, |paths| paths.contains(&recorded_copy.target)) {
+                continue;
+            }
+            if self
+                .find_file_id(&head_commit.root_tree, &recorded_copy.target)?
+                .is_none()
+            {
+                continue;
+            }
+            let root_commit = self.get_backend_commit(root).block_on()?;
+            let Some(source_file) =
+                self.find_file_id(&root_commit.root_tree, &recorded_copy.source)?
+            else {
+                continue;
+            };
+            records.push(CopyRecord {
+                target: recorded_copy.target.clone(),
+                target_commit: head.clone(),
+                source: recorded_copy.source.clone(),
+                source_file,
+                source_commit: root.clone(),
+            });
+        }
+        Ok(records)
+    }
+
+    /// Looks up the id of the file at `path` in a resolved tree.
+    fn find_file_id(
+        &self,
+        root_tree: &MergedTreeId,
+        path: &RepoPath,
+    ) -> BackendResult<Option<FileId>> {
+        let Ok(mut tree_id) = root_tree.to_merge().into_resolved() else {
+            return Ok(None);
+        };
+        let mut dir = RepoPathBuf::root();
+        let mut components = path.components().peekable();
+        while let Some(name) = components.next() {
+            let tree = self.backend.read_tree(&dir, &tree_id).block_on()?;
+            match tree.value(name) {
+                Some(TreeValue::Tree(id)) if components.peek().is_some() => {
+                    tree_id = id.clone();
+                    dir = dir.join(name);
+                }
+                Some(TreeValue::File { id, .. }) if components.peek().is_none() => {
+                    return Ok(Some(id.clone()));
+                }
+                _ => return Ok(None),
+            }
+        }
+        Ok(None)
     }
 
     pub fn commit_id_length(&self) -> usize {
@@ -139,6 +250,17 @@ impl Store {
         Ok(Commit::new(self.clone(), id.clone(), data))
     }
 
+    /// Reads the given commits into the cache, up to `concurrency()` of them
+    /// at a time. Errors are ignored since they'll be reported again when the
+    /// commits are read.
+    pub async fn prefetch_commits(&self, ids: &[CommitId]) {
+        stream::iter(ids)
+            .map(|id| self.get_backend_commit(id))
+            .buffer_unordered(self.concurrency().max(1))
+            .for_each(|_| future::ready(()))
+            .await;
+    }
+
     async fn get_backend_commit(&self, id: &CommitId) -> BackendResult<Arc<backend::Commit>> {
         {
             let mut locked_cache = self.commit_cache.lock().unwrap();
@@ -166,10 +288,56 @@ impl Store {
             let mut locked_cache = self.commit_cache.lock().unwrap();
             locked_cache.put(commit_id.clone(), data.clone());
         }
+        {
+            let mut locked_batch = self.write_batch.lock().unwrap();
+            if locked_batch.depth > 0 {
+                locked_batch.num_commits += 1;
+                if locked_batch.num_commits >= WRITE_BATCH_SIZE {
+                    self.backend.finish_write_batch()?;
+                    self.backend.start_write_batch();
+                    locked_batch.num_commits = 0;
+                }
+            }
+        }
 
         Ok(Commit::new(self.clone(), commit_id, data))
     }
 
+    /// Lets the backend buffer writes until the matching
+    /// `finish_write_batch()`, and apply them in bulk. Batches can be nested,
+    /// in which case the writes are applied when the outermost batch is
+    /// finished. Large batches are flushed in chunks.
+    pub fn start_write_batch(&self) {
+        let mut locked_batch = self.write_batch.lock().unwrap();
+        if locked_batch.depth == 0 {
+            self.backend.start_write_batch();
+            locked_batch.num_commits = 0;
+        }
+        locked_batch.depth += 1;
+    }
+
+    /// Finishes a batch started by `start_write_batch()`.
+    pub fn finish_write_batch(&self) -> BackendResult<()> {
+        let mut locked_batch = self.write_batch.lock().unwrap();
+        assert!(locked_batch.depth > 0, "no write batch was started");
+        locked_batch.depth -= 1;
+        if locked_batch.depth == 0 {
+            self.backend.finish_write_batch()?;
+        }
+        Ok(())
+    }
+
+    /// Calls `f` within a write batch. The batch is finished even if `f`
+    /// fails.
+    pub fn with_write_batch<T>(&self, f: impl FnOnce() -> BackendResult<T>) -> BackendResult<T> {
+        self.start_write_batch();
+        let result = f();
+        let finished = self.finish_write_batch();
+        let value = result?;
+        finished?;
+        Ok(value)
+    }
+
     pub fn get_tree(self: &Arc<Self>, dir: &RepoPath, id: &TreeId) -> BackendResult<Tree> {
         self.get_tree_async(dir, id).block_on()
     }
@@ -0,0 +1,134 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing of Git-style trailers in commit descriptions.
+
+/// A key-value pair such as `Signed-off-by: Alice <alice@example.com>` found
+/// at the end of a commit description.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trailer {
+    /// Trailer key, e.g. `Signed-off-by`.
+    pub key: String,
+    /// Trailer value. Continuation lines are joined with a single space.
+    pub value: String,
+}
+
+/// Parses the trailers from the last paragraph of the given description.
+///
+/// The trailer block must be separated from the subject line by at least one
+/// blank line, and every line of the block must be either a `Key: value` line
+/// or a continuation line starting with whitespace. Otherwise, the description
+/// is considered to have no trailers.
+pub fn parse_description_trailers(description: &str) -> Vec<Trailer> {
+    let lines: Vec<&str> = description.trim_end().lines().collect();
+    let Some(start) = lines.iter().rposition(|line| line.trim().is_empty()) else {
+        // The only paragraph is the subject, which can't be a trailer block.
+        return vec![];
+    };
+    let mut trailers: Vec<Trailer> = vec![];
+    for line in &lines[start + 1..] {
+        if line.starts_with([' ', '\t']) {
+            let Some(last) = trailers.last_mut() else {
+                return vec![];
+            };
+            last.value.push(' ');
+            last.value.push_str(line.trim());
+        } else if let Some(trailer) = parse_trailer_line(line) {
+            trailers.push(trailer);
+        } else {
+            return vec![];
+        }
+    }
+    trailers
+}
+
+fn parse_trailer_line(line: &str) -> Option<Trailer> {
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim_end();
+    let is_valid_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    is_valid_key.then(|| Trailer {
+        key: key.to_owned(),
+        value: value.trim().to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(description: &str) -> Vec<(String, String)> {
+        parse_description_trailers(description)
+            .into_iter()
+            .map(|Trailer { key, value }| (key, value))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_simple() {
+        let description = indoc::indoc! {"
+            subject
+
+            body
+
+            Signed-off-by: Alice <alice@example.com>
+            Change-Id: I1234
+        "};
+        insta::assert_debug_snapshot!(parse(description), @r###"
+        [
+            (
+                "Signed-off-by",
+                "Alice <alice@example.com>",
+            ),
+            (
+                "Change-Id",
+                "I1234",
+            ),
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_parse_continuation() {
+        let description = indoc::indoc! {"
+            subject
+
+            Reviewed-by: Bob
+              <bob@example.com>
+        "};
+        insta::assert_debug_snapshot!(parse(description), @r###"
+        [
+            (
+                "Reviewed-by",
+                "Bob <bob@example.com>",
+            ),
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_parse_no_trailers() {
+        // Subject only
+        assert!(parse("Fixes: something\n").is_empty());
+        // Last paragraph contains non-trailer line
+        assert!(parse("subject\n\nSigned-off-by: Alice\nnot a trailer\n").is_empty());
+        // Key with whitespace
+        assert!(parse("subject\n\nnot a key: value\n").is_empty());
+        // Continuation line without preceding trailer
+        assert!(parse("subject\n\n  continued\n").is_empty());
+        assert!(parse("").is_empty());
+    }
+}
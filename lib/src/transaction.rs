@@ -24,6 +24,7 @@ use crate::index::ReadonlyIndex;
 use crate::op_heads_store::OpHeadsStore;
 use crate::op_store;
 use crate::op_store::OperationMetadata;
+use crate::op_store::WorkspaceId;
 use crate::operation::Operation;
 use crate::repo::MutableRepo;
 use crate::repo::ReadonlyRepo;
@@ -72,6 +73,28 @@ impl Transaction {
         self.op_metadata.tags.insert(key, value);
     }
 
+    /// Records the command-line arguments of the command creating the
+    /// operation.
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.op_metadata.args = args;
+    }
+
+    /// Records the workspace the command creating the operation runs in.
+    pub fn set_workspace_id(&mut self, workspace_id: WorkspaceId) {
+        self.op_metadata.workspace_id = Some(workspace_id);
+    }
+
+    /// Records the exit status of the command creating the operation.
+    pub fn set_exit_status(&mut self, exit_status: i32) {
+        self.op_metadata.exit_status = Some(exit_status);
+    }
+
+    /// The metadata of the operation to be written. The description and the
+    /// end time are set when the transaction is written.
+    pub fn op_metadata(&self) -> &OperationMetadata {
+        &self.op_metadata
+    }
+
     pub fn repo(&self) -> &MutableRepo {
         &self.mut_repo
     }
@@ -160,6 +183,9 @@ pub fn create_op_metadata(
         hostname,
         username,
         is_snapshot,
+        args: vec![],
+        workspace_id: None,
+        exit_status: None,
         tags: Default::default(),
     }
 }
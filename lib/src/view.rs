@@ -88,6 +88,14 @@ impl View {
         &self.data.git_head
     }
 
+    pub fn bisect_refs(&self) -> &BTreeMap<String, RefTarget> {
+        &self.data.bisect_refs
+    }
+
+    pub fn stash_refs(&self) -> &BTreeMap<String, RefTarget> {
+        &self.data.stash_refs
+    }
+
     pub fn set_wc_commit(&mut self, workspace_id: WorkspaceId, commit_id: CommitId) {
         self.data.wc_commit_ids.insert(workspace_id, commit_id);
     }
@@ -333,6 +341,34 @@ impl View {
         }
     }
 
+    pub fn get_bisect_ref(&self, name: &str) -> &RefTarget {
+        self.data.bisect_refs.get(name).flatten()
+    }
+
+    /// Sets the bisection ref to point to the given target. If the target is
+    /// absent, the ref will be removed.
+    pub fn set_bisect_ref_target(&mut self, name: &str, target: RefTarget) {
+        if target.is_present() {
+            self.data.bisect_refs.insert(name.to_owned(), target);
+        } else {
+            self.data.bisect_refs.remove(name);
+        }
+    }
+
+    pub fn get_stash_ref(&self, name: &str) -> &RefTarget {
+        self.data.stash_refs.get(name).flatten()
+    }
+
+    /// Sets the stash ref to point to the given target. If the target is
+    /// absent, the ref will be removed.
+    pub fn set_stash_ref_target(&mut self, name: &str, target: RefTarget) {
+        if target.is_present() {
+            self.data.stash_refs.insert(name.to_owned(), target);
+        } else {
+            self.data.stash_refs.remove(name);
+        }
+    }
+
     /// Sets `HEAD@git` to point to the given target. If the target is absent,
     /// the reference will be cleared.
     pub fn set_git_head_target(&mut self, target: RefTarget) {
@@ -364,6 +400,8 @@ impl View {
             git_refs,
             git_head,
             wc_commit_ids,
+            bisect_refs,
+            stash_refs,
         } = &self.data;
         itertools::chain!(
             head_ids,
@@ -377,7 +415,9 @@ impl View {
             }),
             git_refs.values().flat_map(ref_target_ids),
             ref_target_ids(git_head),
-            wc_commit_ids.values()
+            wc_commit_ids.values(),
+            bisect_refs.values().flat_map(ref_target_ids),
+            stash_refs.values().flat_map(ref_target_ids)
         )
     }
 
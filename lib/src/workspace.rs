@@ -14,6 +14,7 @@
 
 #![allow(missing_docs)]
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
@@ -53,6 +54,7 @@ use crate::signing::SignInitError;
 use crate::signing::Signer;
 use crate::store::Store;
 use crate::working_copy::CheckoutError;
+use crate::working_copy::CheckoutOptions;
 use crate::working_copy::CheckoutStats;
 use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::WorkingCopy;
@@ -315,6 +317,11 @@ impl Workspace {
             )?;
             let repo_loader = repo.loader();
             let workspace = Workspace::new(workspace_root, repo_dir, working_copy, repo_loader)?;
+            register_workspace_root(
+                workspace.repo_path(),
+                workspace.workspace_id(),
+                workspace.workspace_root(),
+            )?;
             Ok((workspace, repo))
         })()
         .inspect_err(|_err| {
@@ -373,6 +380,11 @@ impl Workspace {
             workspace_id,
         )?;
         let workspace = Workspace::new(workspace_root, repo_dir, working_copy, repo.loader())?;
+        register_workspace_root(
+            workspace.repo_path(),
+            workspace.workspace_id(),
+            workspace.workspace_root(),
+        )?;
         Ok((workspace, repo))
     }
 
@@ -422,6 +434,7 @@ impl Workspace {
         operation_id: OperationId,
         old_tree_id: Option<&MergedTreeId>,
         commit: &Commit,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let mut locked_ws =
             self.start_working_copy_mutation()
@@ -438,7 +451,7 @@ impl Workspace {
                 return Err(CheckoutError::ConcurrentCheckout);
             }
         }
-        let stats = locked_ws.locked_wc().check_out(commit)?;
+        let stats = locked_ws.locked_wc().check_out(commit, options)?;
         locked_ws
             .finish(operation_id)
             .map_err(|err| CheckoutError::Other {
@@ -466,6 +479,66 @@ impl<'a> LockedWorkspace<'a> {
     }
 }
 
+/// Records the root of a workspace in the repo, so that it can be found from
+/// the other workspaces. Workspace roots that aren't valid UTF-8 aren't
+/// recorded.
+pub fn register_workspace_root(
+    repo_path: &Path,
+    workspace_id: &WorkspaceId,
+    workspace_root: &Path,
+) -> Result<(), PathError> {
+    let Some(root) = workspace_root.to_str() else {
+        return Ok(());
+    };
+    let roots_dir = repo_path.join("workspace_roots");
+    fs::create_dir_all(&roots_dir).context(&roots_dir)?;
+    let root_path = roots_dir.join(hex::encode(workspace_id.as_str()));
+    fs::write(&root_path, root).context(&root_path)
+}
+
+/// Removes the root of a workspace recorded by [`register_workspace_root()`].
+pub fn unregister_workspace_root(
+    repo_path: &Path,
+    workspace_id: &WorkspaceId,
+) -> Result<(), PathError> {
+    let root_path = repo_path
+        .join("workspace_roots")
+        .join(hex::encode(workspace_id.as_str()));
+    match fs::remove_file(&root_path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err).context(&root_path),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the workspace roots recorded in the repo. Workspaces created by
+/// older versions aren't recorded until they are updated with `jj workspace
+/// update-stale`.
+pub fn registered_workspace_roots(
+    repo_path: &Path,
+) -> Result<BTreeMap<WorkspaceId, PathBuf>, PathError> {
+    let roots_dir = repo_path.join("workspace_roots");
+    let entries = match fs::read_dir(&roots_dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(err).context(&roots_dir),
+    };
+    let mut roots = BTreeMap::new();
+    for entry in entries {
+        let entry = entry.context(&roots_dir)?;
+        let Some(name) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| hex::decode(name).ok())
+            .and_then(|name| String::from_utf8(name).ok())
+        else {
+            continue;
+        };
+        let root = fs::read_to_string(entry.path()).context(entry.path())?;
+        roots.insert(WorkspaceId::new(name), PathBuf::from(root));
+    }
+    Ok(roots)
+}
+
 // Factory trait to build WorkspaceLoaders given the workspace root.
 pub trait WorkspaceLoaderFactory {
     fn create(&self, workspace_root: &Path)
@@ -0,0 +1,197 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::annotate::get_annotation_for_file;
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::repo::MutableRepo;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::settings::UserSettings;
+use testutils::create_tree;
+use testutils::TestRepo;
+use testutils::TestRepoBackend;
+
+fn create_commit(
+    mut_repo: &mut MutableRepo,
+    settings: &UserSettings,
+    parents: Vec<CommitId>,
+    path: &RepoPath,
+    content: &str,
+) -> Commit {
+    let tree = create_tree(mut_repo.base_repo(), &[(path, content)]);
+    mut_repo
+        .new_commit(settings, parents, tree.id())
+        .write()
+        .unwrap()
+}
+
+/// Returns the annotation as a list of (commit, line number, content).
+fn annotate(repo: &dyn Repo, commit: &Commit, path: &RepoPath) -> Vec<(CommitId, usize, String)> {
+    get_annotation_for_file(repo, commit, path)
+        .unwrap()
+        .into_iter()
+        .map(|line| {
+            (
+                line.commit_id,
+                line.original_line_number,
+                line.content.to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_annotate_linear() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let root_commit_id = repo.store().root_commit_id().clone();
+    let path = RepoPath::from_internal_string("file");
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let commit1 = create_commit(mut_repo, &settings, vec![root_commit_id], path, "1\n2\n");
+    let commit2 = create_commit(
+        mut_repo,
+        &settings,
+        vec![commit1.id().clone()],
+        path,
+        "0\n1\n2\n3",
+    );
+    let commit3 = create_commit(
+        mut_repo,
+        &settings,
+        vec![commit2.id().clone()],
+        path,
+        "0\n1\nb\n3",
+    );
+
+    assert_eq!(
+        annotate(tx.repo(), &commit3, path),
+        vec![
+            (commit2.id().clone(), 0, "0\n".to_owned()),
+            (commit1.id().clone(), 0, "1\n".to_owned()),
+            (commit3.id().clone(), 2, "b\n".to_owned()),
+            (commit2.id().clone(), 3, "3".to_owned()),
+        ]
+    );
+    // Lines are attributed relative to the starting commit
+    assert_eq!(
+        annotate(tx.repo(), &commit1, path),
+        vec![
+            (commit1.id().clone(), 0, "1\n".to_owned()),
+            (commit1.id().clone(), 1, "2\n".to_owned()),
+        ]
+    );
+    // A missing file has no lines
+    let other_path = RepoPath::from_internal_string("other");
+    assert_eq!(annotate(tx.repo(), &commit3, other_path), vec![]);
+}
+
+#[test]
+fn test_annotate_merge() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let root_commit_id = repo.store().root_commit_id().clone();
+    let path = RepoPath::from_internal_string("file");
+
+    // D
+    // |\
+    // B C
+    // |/
+    // A
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let commit_a = create_commit(mut_repo, &settings, vec![root_commit_id], path, "a\n");
+    let commit_b = create_commit(
+        mut_repo,
+        &settings,
+        vec![commit_a.id().clone()],
+        path,
+        "a\nb\n",
+    );
+    let commit_c = create_commit(
+        mut_repo,
+        &settings,
+        vec![commit_a.id().clone()],
+        path,
+        "c\na\n",
+    );
+    let commit_d = create_commit(
+        mut_repo,
+        &settings,
+        vec![commit_b.id().clone(), commit_c.id().clone()],
+        path,
+        "c\na\nb\nd\n",
+    );
+
+    assert_eq!(
+        annotate(tx.repo(), &commit_d, path),
+        vec![
+            (commit_c.id().clone(), 0, "c\n".to_owned()),
+            (commit_a.id().clone(), 0, "a\n".to_owned()),
+            (commit_b.id().clone(), 1, "b\n".to_owned()),
+            (commit_d.id().clone(), 3, "d\n".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test_annotate_rename() {
+    let settings = testutils::user_settings();
+    // Only the git backend detects renames
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let root_commit_id = repo.store().root_commit_id().clone();
+    let old_path = RepoPath::from_internal_string("old");
+    let new_path = RepoPath::from_internal_string("new");
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    let commit1 = create_commit(
+        mut_repo,
+        &settings,
+        vec![root_commit_id],
+        old_path,
+        "1\n2\n3\n4\n",
+    );
+    let commit2 = create_commit(
+        mut_repo,
+        &settings,
+        vec![commit1.id().clone()],
+        new_path,
+        "1\n2\n3\n4\n5\n",
+    );
+
+    let annotation = get_annotation_for_file(tx.repo(), &commit2, new_path).unwrap();
+    assert_eq!(
+        annotation
+            .iter()
+            .map(|line| (
+                line.commit_id.clone(),
+                line.path.as_internal_file_string(),
+                line.original_line_number,
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (commit1.id().clone(), "old", 0),
+            (commit1.id().clone(), "old", 1),
+            (commit1.id().clone(), "old", 2),
+            (commit1.id().clone(), "old", 3),
+            (commit2.id().clone(), "new", 4),
+        ]
+    );
+}
@@ -0,0 +1,235 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use assert_matches::assert_matches;
+use jj_lib::bisect::next_step;
+use jj_lib::bisect::BisectError;
+use jj_lib::bisect::BisectState;
+use jj_lib::bisect::BisectStep;
+use maplit::hashset;
+use testutils::CommitGraphBuilder;
+use testutils::TestRepo;
+
+#[test]
+fn test_bisect_linear() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // E
+    // D
+    // C
+    // B
+    // A
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_c]);
+    let commit_e = graph_builder.commit_with_parents(&[&commit_d]);
+    let repo = tx.repo();
+
+    let step = next_step(
+        repo,
+        commit_e.id(),
+        &[commit_a.id().clone()],
+        &HashSet::new(),
+    )
+    .unwrap();
+    assert_eq!(
+        step,
+        BisectStep::Test {
+            commit_id: commit_c.id().clone(),
+            remaining: 1,
+        }
+    );
+
+    let step = next_step(
+        repo,
+        commit_e.id(),
+        &[commit_c.id().clone()],
+        &HashSet::new(),
+    )
+    .unwrap();
+    assert_eq!(
+        step,
+        BisectStep::Test {
+            commit_id: commit_d.id().clone(),
+            remaining: 0,
+        }
+    );
+
+    let step = next_step(
+        repo,
+        commit_e.id(),
+        &[commit_d.id().clone()],
+        &HashSet::new(),
+    )
+    .unwrap();
+    assert_eq!(step, BisectStep::Found(commit_e.id().clone()));
+}
+
+#[test]
+fn test_bisect_skipped() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // D
+    // C
+    // B
+    // A
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_c]);
+    let repo = tx.repo();
+
+    // The skipped commit is avoided
+    let step = next_step(
+        repo,
+        commit_d.id(),
+        &[commit_a.id().clone()],
+        &hashset! {commit_c.id().clone()},
+    )
+    .unwrap();
+    assert_matches!(step, BisectStep::Test { commit_id, .. } if commit_id == *commit_b.id());
+
+    // Only skipped commits are left
+    let step = next_step(
+        repo,
+        commit_d.id(),
+        &[commit_b.id().clone()],
+        &hashset! {commit_c.id().clone()},
+    )
+    .unwrap();
+    assert_eq!(
+        step,
+        BisectStep::Inconclusive(vec![commit_d.id().clone(), commit_c.id().clone()])
+    );
+}
+
+#[test]
+fn test_bisect_merge() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // F
+    // E
+    // |\
+    // C D
+    // B |
+    // |/
+    // A
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_e = graph_builder.commit_with_parents(&[&commit_c, &commit_d]);
+    let commit_f = graph_builder.commit_with_parents(&[&commit_e]);
+    let repo = tx.repo();
+
+    // The merge has 4 ancestors among the 5 candidates, and C has 2, so C splits
+    // them most evenly.
+    let step = next_step(
+        repo,
+        commit_f.id(),
+        &[commit_a.id().clone()],
+        &HashSet::new(),
+    )
+    .unwrap();
+    assert_eq!(
+        step,
+        BisectStep::Test {
+            commit_id: commit_c.id().clone(),
+            remaining: 2,
+        }
+    );
+
+    // If C is good, D and the merge are left
+    let step = next_step(
+        repo,
+        commit_f.id(),
+        &[commit_a.id().clone(), commit_c.id().clone()],
+        &HashSet::new(),
+    )
+    .unwrap();
+    assert_matches!(
+        step,
+        BisectStep::Test { commit_id, .. }
+            if commit_id == *commit_d.id() || commit_id == *commit_e.id()
+    );
+}
+
+#[test]
+fn test_bisect_bad_is_ancestor_of_good() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let repo = tx.repo();
+
+    let result = next_step(
+        repo,
+        commit_a.id(),
+        &[commit_b.id().clone()],
+        &HashSet::new(),
+    );
+    assert_matches!(result, Err(BisectError::BadIsAncestorOfGood(id)) if id == *commit_a.id());
+}
+
+#[test]
+fn test_bisect_state_roundtrip() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let mut graph_builder = CommitGraphBuilder::new(&settings, tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b]);
+
+    let state = BisectState {
+        original: Some(commit_c.id().clone()),
+        current: Some(commit_b.id().clone()),
+        bad: Some(commit_c.id().clone()),
+        good: vec![commit_a.id().clone()],
+        skipped: vec![commit_b.id().clone()],
+    };
+    assert!(state.is_in_progress());
+    state.save(tx.repo_mut());
+    let repo = tx.commit("test");
+    assert_eq!(BisectState::load(repo.view()), state);
+    assert_eq!(repo.view().bisect_refs().len(), 5);
+
+    // Saving the default state removes all refs
+    let mut tx = repo.start_transaction(&settings);
+    BisectState::default().save(tx.repo_mut());
+    let repo = tx.commit("test");
+    assert!(repo.view().bisect_refs().is_empty());
+    assert!(!BisectState::load(repo.view()).is_in_progress());
+}
@@ -14,10 +14,15 @@
 
 use indoc::indoc;
 use jj_lib::backend::FileId;
+use jj_lib::backend::TreeValue;
 use jj_lib::conflicts::extract_as_single_hunk;
+use jj_lib::conflicts::extract_mode_conflict;
 use jj_lib::conflicts::materialize_merge_result;
+use jj_lib::conflicts::materialize_merge_result_with_style;
 use jj_lib::conflicts::parse_conflict;
 use jj_lib::conflicts::update_from_content;
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::merge::FileMode;
 use jj_lib::merge::Merge;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
@@ -339,6 +344,155 @@ fn test_materialize_parse_roundtrip() {
     "###);
 }
 
+#[test]
+fn test_materialize_conflict_marker_styles() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("file");
+    let base_id = testutils::write_file(store, path, "line 1\nline 2\nline 3\n");
+    let left_id = testutils::write_file(store, path, "line 1 left\nline 2\nline 3 left\n");
+    let right_id = testutils::write_file(store, path, "line 1 right\nline 2\nline 3\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(right_id.clone())],
+    );
+
+    insta::assert_snapshot!(
+        materialize_conflict_string_with_style(store, path, &conflict, ConflictMarkerStyle::Snapshot),
+        @r###"
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    line 1 left
+    ------- Contents of base
+    line 1
+    +++++++ Contents of side #2
+    line 1 right
+    >>>>>>> Conflict 1 of 1 ends
+    line 2
+    line 3 left
+    "###
+    );
+    insta::assert_snapshot!(
+        materialize_conflict_string_with_style(store, path, &conflict, ConflictMarkerStyle::Git),
+        @r###"
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    line 1 left
+    ||||||| Base
+    line 1
+    =======
+    line 1 right
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    line 2
+    line 3 left
+    "###
+    );
+
+    // Git's markers can't represent more than two sides
+    let other_id = testutils::write_file(store, path, "line 1 other\nline 2\nline 3\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone()), Some(base_id.clone())],
+        vec![
+            Some(left_id.clone()),
+            Some(right_id.clone()),
+            Some(other_id.clone()),
+        ],
+    );
+    insta::assert_snapshot!(
+        materialize_conflict_string_with_style(store, path, &conflict, ConflictMarkerStyle::Git),
+        @r###"
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    line 1 left
+    ------- Contents of base #1
+    line 1
+    +++++++ Contents of side #2
+    line 1 right
+    ------- Contents of base #2
+    line 1
+    +++++++ Contents of side #3
+    line 1 other
+    >>>>>>> Conflict 1 of 1 ends
+    line 2
+    line 3 left
+    "###
+    );
+}
+
+#[test]
+fn test_materialize_conflict_zdiff3() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    // Both sides added the first and the last line
+    let path = RepoPath::from_internal_string("file");
+    let base_id = testutils::write_file(store, path, "a\nc\n");
+    let left_id = testutils::write_file(store, path, "a\nx\nleft\nz\nc\n");
+    let right_id = testutils::write_file(store, path, "a\nx\nright\nz\nc\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(right_id.clone())],
+    );
+
+    insta::assert_snapshot!(
+        materialize_conflict_string_with_style(store, path, &conflict, ConflictMarkerStyle::Git),
+        @r###"
+    a
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    x
+    left
+    z
+    ||||||| Base
+    =======
+    x
+    right
+    z
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    c
+    "###
+    );
+    let materialized =
+        materialize_conflict_string_with_style(store, path, &conflict, ConflictMarkerStyle::Zdiff3);
+    insta::assert_snapshot!(
+        materialized,
+        @r###"
+    a
+    x
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    left
+    ||||||| Base
+    =======
+    right
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    z
+    c
+    "###
+    );
+
+    // The common lines are parsed as resolved content
+    insta::assert_debug_snapshot!(
+        parse_conflict(materialized.as_bytes(), conflict.num_sides()),
+        @r###"
+    Some(
+        [
+            Resolved(
+                "a\nx\n",
+            ),
+            Conflicted(
+                [
+                    "left\n",
+                    "",
+                    "right\n",
+                ],
+            ),
+            Resolved(
+                "z\nc\n",
+            ),
+        ],
+    )
+    "###);
+}
+
 #[test]
 fn test_materialize_conflict_no_newlines_at_eof() {
     let test_repo = TestRepo::init();
@@ -835,7 +989,7 @@ fn test_update_conflict_from_content() {
     // old conflict id back.
     let materialized = materialize_conflict_string(store, path, &conflict);
     let parse = |content| {
-        update_from_content(&conflict, store, path, content)
+        update_from_content(&conflict, store, path, content, ConflictMarkerStyle::Diff)
             .block_on()
             .unwrap()
     };
@@ -869,6 +1023,60 @@ fn test_update_conflict_from_content() {
     );
 }
 
+#[test]
+fn test_update_conflict_from_content_marker_styles() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("dir/file");
+    let base_file_id = testutils::write_file(store, path, "line 1\nline 2\nline 3\n");
+    let left_file_id = testutils::write_file(store, path, "left 1\nline 2\nleft 3\n");
+    let right_file_id = testutils::write_file(store, path, "right 1\nline 2\nright 3\n");
+    let other_file_id = testutils::write_file(store, path, "other 1\nline 2\nline 3\n");
+    let conflicts = [
+        Merge::from_removes_adds(
+            vec![Some(base_file_id.clone())],
+            vec![Some(left_file_id.clone()), Some(right_file_id.clone())],
+        ),
+        Merge::from_removes_adds(
+            vec![Some(base_file_id.clone()), Some(left_file_id.clone())],
+            vec![
+                Some(right_file_id.clone()),
+                Some(other_file_id.clone()),
+                Some(base_file_id.clone()),
+            ],
+        ),
+    ];
+    let styles = [
+        ConflictMarkerStyle::Diff,
+        ConflictMarkerStyle::Snapshot,
+        ConflictMarkerStyle::Git,
+        ConflictMarkerStyle::Zdiff3,
+    ];
+    for conflict in &conflicts {
+        for style in styles {
+            let materialized = materialize_conflict_string_with_style(store, path, conflict, style);
+            // The content is unchanged if it was materialized in the same style,
+            // and parsed back to the same conflict otherwise.
+            for parse_style in styles {
+                let new_conflict = update_from_content(
+                    conflict,
+                    store,
+                    path,
+                    materialized.as_bytes(),
+                    parse_style,
+                )
+                .block_on()
+                .unwrap();
+                assert_eq!(
+                    &new_conflict, conflict,
+                    "materialized as {style:?}, parsed as {parse_style:?}"
+                );
+            }
+        }
+    }
+}
+
 #[test]
 fn test_update_conflict_from_content_modify_delete() {
     let test_repo = TestRepo::init();
@@ -884,7 +1092,7 @@ fn test_update_conflict_from_content_modify_delete() {
     // old conflict id back.
     let materialized = materialize_conflict_string(store, path, &conflict);
     let parse = |content| {
-        update_from_content(&conflict, store, path, content)
+        update_from_content(&conflict, store, path, content, ConflictMarkerStyle::Diff)
             .block_on()
             .unwrap()
     };
@@ -938,7 +1146,7 @@ fn test_update_conflict_from_content_simplified_conflict() {
     let materialized = materialize_conflict_string(store, path, &conflict);
     let materialized_simplified = materialize_conflict_string(store, path, &simplified_conflict);
     let parse = |content| {
-        update_from_content(&conflict, store, path, content)
+        update_from_content(&conflict, store, path, content, ConflictMarkerStyle::Diff)
             .block_on()
             .unwrap()
     };
@@ -1058,6 +1266,98 @@ fn test_update_conflict_from_content_simplified_conflict() {
     );
 }
 
+#[test]
+fn test_extract_mode_conflict() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("file");
+    let file_id = testutils::write_file(store, path, "target");
+    let other_file_id = testutils::write_file(store, path, "other");
+    let symlink_id = store.write_symlink(path, "target").block_on().unwrap();
+    let file = |id: &FileId, executable| {
+        Some(TreeValue::File {
+            id: id.clone(),
+            executable,
+        })
+    };
+    let symlink = Some(TreeValue::Symlink(symlink_id.clone()));
+
+    // A file added with the same contents on both sides, but executable on only
+    // one of them
+    let conflict = Merge::from_removes_adds(
+        vec![None],
+        vec![file(&file_id, false), file(&file_id, true)],
+    );
+    let mode_conflict = extract_mode_conflict(store, path, &conflict)
+        .block_on()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        mode_conflict.modes,
+        Merge::from_removes_adds(
+            vec![None],
+            vec![Some(FileMode::Normal), Some(FileMode::Executable)]
+        )
+    );
+    assert_eq!(
+        mode_conflict
+            .resolve(store, path, FileMode::Executable)
+            .block_on()
+            .unwrap(),
+        file(&file_id, true).unwrap()
+    );
+
+    // The contents differ too
+    let conflict = Merge::from_removes_adds(
+        vec![None],
+        vec![file(&file_id, false), file(&other_file_id, true)],
+    );
+    assert!(extract_mode_conflict(store, path, &conflict)
+        .block_on()
+        .unwrap()
+        .is_none());
+
+    // A file and a symlink whose target is the file's contents
+    let conflict =
+        Merge::from_removes_adds(vec![None], vec![file(&file_id, false), symlink.clone()]);
+    let mode_conflict = extract_mode_conflict(store, path, &conflict)
+        .block_on()
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        mode_conflict.modes,
+        Merge::from_removes_adds(
+            vec![None],
+            vec![Some(FileMode::Normal), Some(FileMode::Symlink)]
+        )
+    );
+    assert_eq!(
+        mode_conflict
+            .resolve(store, path, FileMode::Symlink)
+            .block_on()
+            .unwrap(),
+        symlink.clone().unwrap()
+    );
+    assert_eq!(
+        mode_conflict
+            .resolve(store, path, FileMode::Executable)
+            .block_on()
+            .unwrap(),
+        file(&file_id, true).unwrap()
+    );
+
+    // A side deleted the file
+    let conflict = Merge::from_removes_adds(
+        vec![file(&file_id, false)],
+        vec![None, file(&file_id, true)],
+    );
+    assert!(extract_mode_conflict(store, path, &conflict)
+        .block_on()
+        .unwrap()
+        .is_none());
+}
+
 fn materialize_conflict_string(
     store: &Store,
     path: &RepoPath,
@@ -1070,3 +1370,17 @@ fn materialize_conflict_string(
     materialize_merge_result(&contents, &mut result).unwrap();
     String::from_utf8(result).unwrap()
 }
+
+fn materialize_conflict_string_with_style(
+    store: &Store,
+    path: &RepoPath,
+    conflict: &Merge<Option<FileId>>,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> String {
+    let mut result: Vec<u8> = vec![];
+    let contents = extract_as_single_hunk(conflict, store, path)
+        .block_on()
+        .unwrap();
+    materialize_merge_result_with_style(&contents, conflict_marker_style, &mut result).unwrap();
+    String::from_utf8(result).unwrap()
+}
@@ -25,6 +25,8 @@ use std::thread;
 
 use assert_matches::assert_matches;
 use git2::Oid;
+use indoc::formatdoc;
+use indoc::indoc;
 use itertools::Itertools;
 use jj_lib::backend::BackendError;
 use jj_lib::backend::ChangeId;
@@ -32,18 +34,22 @@ use jj_lib::backend::CommitId;
 use jj_lib::backend::MillisSinceEpoch;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
+use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
 use jj_lib::commit_builder::CommitBuilder;
 use jj_lib::git;
 use jj_lib::git::FailedRefExportReason;
+use jj_lib::git::GitApplyError;
 use jj_lib::git::GitBranchPushTargets;
 use jj_lib::git::GitFetchError;
+use jj_lib::git::GitFetchOptions;
 use jj_lib::git::GitImportError;
 use jj_lib::git::GitPushError;
 use jj_lib::git::GitRefUpdate;
 use jj_lib::git::RefName;
 use jj_lib::git::SubmoduleConfig;
 use jj_lib::git_backend::GitBackend;
+use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId;
 use jj_lib::op_store::BookmarkTarget;
 use jj_lib::op_store::RefTarget;
@@ -55,6 +61,7 @@ use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::settings::GitSettings;
+use jj_lib::settings::GitUrlRewrite;
 use jj_lib::settings::UserSettings;
 use jj_lib::signing::Signer;
 use jj_lib::str_util::StringPattern;
@@ -2267,6 +2274,7 @@ fn test_fetch_empty_repo() {
         &test_data.git_repo,
         "origin",
         &[StringPattern::everything()],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     )
@@ -2293,6 +2301,7 @@ fn test_fetch_initial_commit() {
         &test_data.git_repo,
         "origin",
         &[StringPattern::everything()],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     )
@@ -2343,6 +2352,7 @@ fn test_fetch_success() {
         &test_data.git_repo,
         "origin",
         &[StringPattern::everything()],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     )
@@ -2366,6 +2376,7 @@ fn test_fetch_success() {
         &test_data.git_repo,
         "origin",
         &[StringPattern::everything()],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     )
@@ -2408,6 +2419,43 @@ fn test_fetch_success() {
     );
 }
 
+#[test]
+fn test_fetch_progress() {
+    let mut test_data = GitRepoData::create();
+    let git_settings = GitSettings::default();
+    empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
+
+    let mut phases = vec![];
+    let mut last_objects = (0, 0);
+    let mut record_progress = |progress: &git::Progress| {
+        if phases.last() != Some(&progress.phase) {
+            phases.push(progress.phase);
+        }
+        assert!((0.0..=1.0).contains(&progress.overall));
+        last_objects = progress.objects;
+    };
+    let mut callbacks = git::RemoteCallbacks::default();
+    callbacks.progress = Some(&mut record_progress);
+    let mut tx = test_data.repo.start_transaction(&test_data.settings);
+    git::fetch(
+        tx.repo_mut(),
+        &test_data.git_repo,
+        "origin",
+        &[StringPattern::everything()],
+        &GitFetchOptions::default(),
+        callbacks,
+        &git_settings,
+    )
+    .unwrap();
+    test_data.repo = tx.commit("test");
+    assert_eq!(
+        phases,
+        [git::ProgressPhase::Receiving, git::ProgressPhase::Resolving]
+    );
+    // There are no deltas to resolve
+    assert_eq!(last_objects, (0, 0));
+}
+
 #[test]
 fn test_fetch_prune_deleted_ref() {
     let test_data = GitRepoData::create();
@@ -2423,6 +2471,7 @@ fn test_fetch_prune_deleted_ref() {
         &test_data.git_repo,
         "origin",
         &[StringPattern::everything()],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     )
@@ -2446,6 +2495,7 @@ fn test_fetch_prune_deleted_ref() {
         &test_data.git_repo,
         "origin",
         &[StringPattern::everything()],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     )
@@ -2473,6 +2523,7 @@ fn test_fetch_no_default_branch() {
         &test_data.git_repo,
         "origin",
         &[StringPattern::everything()],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     )
@@ -2496,6 +2547,7 @@ fn test_fetch_no_default_branch() {
         &test_data.git_repo,
         "origin",
         &[StringPattern::everything()],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     )
@@ -2517,6 +2569,7 @@ fn test_fetch_empty_refspecs() {
         &test_data.git_repo,
         "origin",
         &[],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     )
@@ -2543,12 +2596,103 @@ fn test_fetch_no_such_remote() {
         &test_data.git_repo,
         "invalid-remote",
         &[StringPattern::everything()],
+        &GitFetchOptions::default(),
         git::RemoteCallbacks::default(),
         &git_settings,
     );
     assert!(matches!(result, Err(GitFetchError::NoSuchRemote(_))));
 }
 
+#[test]
+fn test_rewrite_remote_url() {
+    let git_settings = GitSettings {
+        url_rewrites: btreemap! {
+            "https://example.com/".to_owned() => GitUrlRewrite {
+                instead_of: vec!["ex:".to_owned()],
+                push_instead_of: vec![],
+            },
+            "https://mirror.example.com/org/".to_owned() => GitUrlRewrite {
+                instead_of: vec!["ex:org/".to_owned()],
+                push_instead_of: vec![],
+            },
+            "ssh://git@example.com/".to_owned() => GitUrlRewrite {
+                instead_of: vec![],
+                push_instead_of: vec!["https://example.com/".to_owned()],
+            },
+        },
+        ..Default::default()
+    };
+    let rewrite = |url, direction| git::rewrite_remote_url(&git_settings, url, direction);
+    assert_eq!(
+        rewrite("git://example.com/repo", git2::Direction::Fetch),
+        None
+    );
+    assert_eq!(
+        rewrite("ex:repo", git2::Direction::Fetch).as_deref(),
+        Some("https://example.com/repo")
+    );
+    // The longest prefix wins
+    assert_eq!(
+        rewrite("ex:org/repo", git2::Direction::Fetch).as_deref(),
+        Some("https://mirror.example.com/org/repo")
+    );
+    // push-instead-of only applies to pushes
+    assert_eq!(
+        rewrite("https://example.com/repo", git2::Direction::Fetch),
+        None
+    );
+    assert_eq!(
+        rewrite("https://example.com/repo", git2::Direction::Push).as_deref(),
+        Some("ssh://git@example.com/repo")
+    );
+    // instead-of also applies to pushes
+    assert_eq!(
+        rewrite("ex:repo", git2::Direction::Push).as_deref(),
+        Some("https://example.com/repo")
+    );
+}
+
+#[test]
+fn test_fetch_rewritten_url() {
+    let test_data = GitRepoData::create();
+    let origin_dir = test_data.origin_repo.path().to_str().unwrap().to_owned();
+    test_data
+        .git_repo
+        .remote_set_url("origin", "example:origin")
+        .unwrap();
+    let git_settings = GitSettings {
+        url_rewrites: btreemap! {
+            origin_dir => GitUrlRewrite {
+                instead_of: vec!["example:origin".to_owned()],
+                push_instead_of: vec![],
+            },
+        },
+        ..Default::default()
+    };
+    let initial_git_commit = empty_git_commit(&test_data.origin_repo, "refs/heads/main", &[]);
+
+    let mut tx = test_data.repo.start_transaction(&test_data.settings);
+    git::fetch(
+        tx.repo_mut(),
+        &test_data.git_repo,
+        "origin",
+        &[StringPattern::everything()],
+        &GitFetchOptions::default(),
+        git::RemoteCallbacks::default(),
+        &git_settings,
+    )
+    .unwrap();
+    assert_eq!(
+        *tx.repo_mut().view().git_refs(),
+        btreemap! {
+            "refs/remotes/origin/main".to_string() => RefTarget::normal(jj_id(&initial_git_commit)),
+        }
+    );
+    // The remote itself isn't changed
+    let remote = test_data.git_repo.find_remote("origin").unwrap();
+    assert_eq!(remote.url(), Some("example:origin"));
+}
+
 struct PushTestSetup {
     source_repo_dir: PathBuf,
     jj_repo: Arc<ReadonlyRepo>,
@@ -2658,13 +2802,16 @@ fn test_push_bookmarks_success() {
                 new_target: Some(setup.child_of_main_commit.id().clone()),
             },
         )],
+        force: false,
     };
     let result = git::push_branches(
         tx.repo_mut(),
         &clone_repo,
         "origin",
         &targets,
+        &[],
         git::RemoteCallbacks::default(),
+        &GitSettings::default(),
     );
     assert_eq!(result, Ok(()));
 
@@ -2707,6 +2854,58 @@ fn test_push_bookmarks_success() {
     assert!(!tx.repo_mut().has_changes());
 }
 
+#[test]
+fn test_push_progress() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let clone_repo = get_git_repo(&setup.jj_repo);
+    let mut tx = setup.jj_repo.start_transaction(&settings);
+
+    let mut phases = vec![];
+    let mut last_objects = (0, 0);
+    let mut record_progress = |progress: &git::Progress| {
+        if phases.last() != Some(&progress.phase) {
+            phases.push(progress.phase);
+        }
+        assert!((0.0..=1.0).contains(&progress.overall));
+        last_objects = progress.objects;
+    };
+    let mut callbacks = git::RemoteCallbacks::default();
+    callbacks.progress = Some(&mut record_progress);
+    let targets = GitBranchPushTargets {
+        branch_updates: vec![(
+            "main".to_owned(),
+            BookmarkPushUpdate {
+                old_target: Some(setup.main_commit.id().clone()),
+                new_target: Some(setup.child_of_main_commit.id().clone()),
+            },
+        )],
+        force: false,
+    };
+    let result = git::push_branches(
+        tx.repo_mut(),
+        &clone_repo,
+        "origin",
+        &targets,
+        &[],
+        callbacks,
+        &GitSettings::default(),
+    );
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        phases,
+        [
+            git::ProgressPhase::Counting,
+            git::ProgressPhase::Compressing,
+            git::ProgressPhase::Sending,
+        ]
+    );
+    // All of the objects were sent
+    assert!(last_objects.0 > 0);
+    assert_eq!(last_objects.0, last_objects.1);
+}
+
 #[test]
 fn test_push_bookmarks_deletion() {
     let settings = testutils::user_settings();
@@ -2727,13 +2926,16 @@ fn test_push_bookmarks_deletion() {
                 new_target: None,
             },
         )],
+        force: false,
     };
     let result = git::push_branches(
         tx.repo_mut(),
         &get_git_repo(&setup.jj_repo),
         "origin",
         &targets,
+        &[],
         git::RemoteCallbacks::default(),
+        &GitSettings::default(),
     );
     assert_eq!(result, Ok(()));
 
@@ -2784,13 +2986,16 @@ fn test_push_bookmarks_mixed_deletion_and_addition() {
                 },
             ),
         ],
+        force: false,
     };
     let result = git::push_branches(
         tx.repo_mut(),
         &clone_repo,
         "origin",
         &targets,
+        &[],
         git::RemoteCallbacks::default(),
+        &GitSettings::default(),
     );
     assert_eq!(result, Ok(()));
 
@@ -2843,13 +3048,16 @@ fn test_push_bookmarks_not_fast_forward() {
                 new_target: Some(setup.sideways_commit.id().clone()),
             },
         )],
+        force: false,
     };
     let result = git::push_branches(
         tx.repo_mut(),
         &get_git_repo(&setup.jj_repo),
         "origin",
         &targets,
+        &[],
         git::RemoteCallbacks::default(),
+        &GitSettings::default(),
     );
     assert_eq!(result, Ok(()));
 
@@ -2888,13 +3096,16 @@ fn test_push_updates_unexpectedly_moved_sideways_on_remote() {
             qualified_name: "refs/heads/main".to_string(),
             expected_current_target: Some(setup.sideways_commit.id().clone()),
             new_target: target,
+            force: false,
         }];
         git::push_updates(
             setup.jj_repo.as_ref(),
             &get_git_repo(&setup.jj_repo),
             "origin",
             &targets,
+            &[],
             git::RemoteCallbacks::default(),
+            &GitSettings::default(),
         )
     };
 
@@ -2931,6 +3142,40 @@ fn test_push_updates_unexpectedly_moved_sideways_on_remote() {
     );
 }
 
+#[test]
+fn test_push_updates_force_unexpectedly_moved_sideways_on_remote() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+
+    // Same as above, but the push is forced, so the unexpected location on the
+    // remote is ignored.
+    let targets = [GitRefUpdate {
+        qualified_name: "refs/heads/main".to_string(),
+        expected_current_target: Some(setup.sideways_commit.id().clone()),
+        new_target: Some(setup.parent_of_main_commit.id().clone()),
+        force: true,
+    }];
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &get_git_repo(&setup.jj_repo),
+        "origin",
+        &targets,
+        &[],
+        git::RemoteCallbacks::default(),
+        &GitSettings::default(),
+    );
+    assert_eq!(result, Ok(()));
+
+    let source_repo = git2::Repository::open(&setup.source_repo_dir).unwrap();
+    let new_target = source_repo
+        .find_reference("refs/heads/main")
+        .unwrap()
+        .target();
+    let new_oid = git_id(&setup.parent_of_main_commit);
+    assert_eq!(new_target, Some(new_oid));
+}
+
 #[test]
 fn test_push_updates_unexpectedly_moved_forward_on_remote() {
     let settings = testutils::user_settings();
@@ -2955,13 +3200,16 @@ fn test_push_updates_unexpectedly_moved_forward_on_remote() {
             qualified_name: "refs/heads/main".to_string(),
             expected_current_target: Some(setup.parent_of_main_commit.id().clone()),
             new_target: target,
+            force: false,
         }];
         git::push_updates(
             setup.jj_repo.as_ref(),
             &get_git_repo(&setup.jj_repo),
             "origin",
             &targets,
+            &[],
             git::RemoteCallbacks::default(),
+            &GitSettings::default(),
         )
     };
 
@@ -3013,13 +3261,16 @@ fn test_push_updates_unexpectedly_exists_on_remote() {
             qualified_name: "refs/heads/main".to_string(),
             expected_current_target: None,
             new_target: target,
+            force: false,
         }];
         git::push_updates(
             setup.jj_repo.as_ref(),
             &get_git_repo(&setup.jj_repo),
             "origin",
             &targets,
+            &[],
             git::RemoteCallbacks::default(),
+            &GitSettings::default(),
         )
     };
 
@@ -3049,8 +3300,11 @@ fn test_push_updates_success() {
             qualified_name: "refs/heads/main".to_string(),
             expected_current_target: Some(setup.main_commit.id().clone()),
             new_target: Some(setup.child_of_main_commit.id().clone()),
+            force: false,
         }],
+        &[],
         git::RemoteCallbacks::default(),
+        &GitSettings::default(),
     );
     assert_eq!(result, Ok(()));
 
@@ -3086,8 +3340,11 @@ fn test_push_updates_no_such_remote() {
             qualified_name: "refs/heads/main".to_string(),
             expected_current_target: Some(setup.main_commit.id().clone()),
             new_target: Some(setup.child_of_main_commit.id().clone()),
+            force: false,
         }],
+        &[],
         git::RemoteCallbacks::default(),
+        &GitSettings::default(),
     );
     assert!(matches!(result, Err(GitPushError::NoSuchRemote(_))));
 }
@@ -3105,8 +3362,11 @@ fn test_push_updates_invalid_remote() {
             qualified_name: "refs/heads/main".to_string(),
             expected_current_target: Some(setup.main_commit.id().clone()),
             new_target: Some(setup.child_of_main_commit.id().clone()),
+            force: false,
         }],
+        &[],
         git::RemoteCallbacks::default(),
+        &GitSettings::default(),
     );
     assert!(matches!(result, Err(GitPushError::NoSuchRemote(_))));
 }
@@ -3468,3 +3728,126 @@ ignoreThisSection = foo
 
     assert_eq!(result, expected);
 }
+
+fn file_contents(tree: &MergedTree, path: &RepoPath) -> String {
+    let value = tree.path_value(path).unwrap().into_resolved().unwrap();
+    let Some(TreeValue::File { id, .. }) = value else {
+        panic!("unexpected value at {path:?}: {value:?}");
+    };
+    String::from_utf8(testutils::read_file(tree.store(), path, &id)).unwrap()
+}
+
+#[test]
+fn test_apply_patch() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+    let path_a = RepoPath::from_internal_string("a");
+    let path_b = RepoPath::from_internal_string("dir/b");
+    let tree = testutils::create_tree(repo, &[(path_a, "1\n2\n3\n")]);
+
+    let patch = indoc! {"
+        diff --git a/a b/a
+        --- a/a
+        +++ b/a
+        @@ -1,3 +1,3 @@
+         1
+        -2
+        +two
+         3
+        diff --git a/dir/b b/dir/b
+        new file mode 100644
+        --- /dev/null
+        +++ b/dir/b
+        @@ -0,0 +1 @@
+        +new
+    "};
+    let applied = git::apply_patch(&git_repo, &tree, patch.as_bytes()).unwrap();
+    assert_eq!(file_contents(&applied.tree, path_a), "1\ntwo\n3\n");
+    assert_eq!(file_contents(&applied.tree, path_b), "new\n");
+    assert!(applied.merged_paths.is_empty());
+
+    // Adding a file that exists fails
+    let patch = indoc! {"
+        diff --git a/dir/b b/dir/b
+        new file mode 100644
+        --- /dev/null
+        +++ b/dir/b
+        @@ -0,0 +1 @@
+        +new
+    "};
+    assert_matches!(
+        git::apply_patch(&git_repo, &applied.tree, patch.as_bytes()),
+        Err(GitApplyError::AlreadyExists(path)) if path.as_ref() == path_b
+    );
+}
+
+#[test]
+fn test_apply_patch_unified_diff() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+    let path_a = RepoPath::from_internal_string("a");
+    let path_b = RepoPath::from_internal_string("b");
+    let tree = testutils::create_tree(repo, &[(path_a, "1\n2\n3\n"), (path_b, "b\n")]);
+
+    let patch = indoc! {"
+        --- old/a\t2024-01-01 00:00:00.000000000 +0000
+        +++ new/a\t2024-01-01 00:00:00.000000000 +0000
+        @@ -1,3 +1,3 @@
+         1
+        -2
+        +two
+         3
+        --- old/b\t2024-01-01 00:00:00.000000000 +0000
+        +++ /dev/null\t1970-01-01 00:00:00.000000000 +0000
+        @@ -1 +0,0 @@
+        -b
+    "};
+    let applied = git::apply_patch(&git_repo, &tree, patch.as_bytes()).unwrap();
+    assert_eq!(file_contents(&applied.tree, path_a), "1\ntwo\n3\n");
+    assert!(applied.tree.path_value(path_b).unwrap().is_absent());
+}
+
+#[test]
+fn test_apply_patch_three_way() {
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo = get_git_repo(repo);
+    let path_a = RepoPath::from_internal_string("a");
+    let base_id = testutils::write_file(repo.store(), path_a, "1\n2\n3\n4\n5\n");
+    let patch = formatdoc! {"
+            diff --git a/a b/a
+            index {base_id}..1234567 100644
+            --- a/a
+            +++ b/a
+            @@ -1,4 +1,4 @@
+            -1
+            +one
+             2
+             3
+             4
+        ",
+        base_id = base_id.hex(),
+    };
+
+    // The context doesn't match, but the change merges cleanly with the preimage
+    let tree = testutils::create_tree(repo, &[(path_a, "1\n2\n3\nfour\n5\n")]);
+    let applied = git::apply_patch(&git_repo, &tree, patch.as_bytes()).unwrap();
+    assert_eq!(file_contents(&applied.tree, path_a), "one\n2\n3\nfour\n5\n");
+    assert_eq!(applied.merged_paths, vec![path_a.to_owned()]);
+    assert!(applied.conflicted_paths.is_empty());
+
+    // The changed line was also changed in the tree
+    let tree = testutils::create_tree(repo, &[(path_a, "uno\n2\n3\nfour\n5\n")]);
+    let applied = git::apply_patch(&git_repo, &tree, patch.as_bytes()).unwrap();
+    assert_eq!(applied.conflicted_paths, vec![path_a.to_owned()]);
+    assert!(applied.tree.has_conflict());
+
+    // The preimage isn't in the repo
+    let patch = patch.replace(&base_id.hex(), "abcdef0");
+    assert_matches!(
+        git::apply_patch(&git_repo, &tree, patch.as_bytes()),
+        Err(GitApplyError::DoesNotApply(path)) if path.as_ref() == path_a
+    );
+}
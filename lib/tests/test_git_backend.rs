@@ -14,6 +14,7 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io::Read as _;
 use std::path::Path;
 use std::process::Command;
 use std::sync::Arc;
@@ -21,8 +22,10 @@ use std::time::Duration;
 use std::time::SystemTime;
 
 use futures::executor::block_on_stream;
+use jj_lib::backend::Backend as _;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::CopyRecord;
+use jj_lib::backend::FileId;
 use jj_lib::commit::Commit;
 use jj_lib::git_backend::GitBackend;
 use jj_lib::repo::ReadonlyRepo;
@@ -33,6 +36,7 @@ use jj_lib::settings::UserSettings;
 use jj_lib::store::Store;
 use jj_lib::transaction::Transaction;
 use maplit::hashset;
+use pollster::FutureExt as _;
 use testutils::create_random_commit;
 use testutils::create_tree;
 use testutils::CommitGraphBuilder;
@@ -237,6 +241,54 @@ fn test_gc() {
     assert_eq!(collect_no_gc_refs(git_repo_path), hashset! {});
 }
 
+#[test]
+fn test_write_batch() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init_with_backend(TestRepoBackend::Git);
+    let repo = &test_repo.repo;
+    let git_repo_path = get_git_backend(repo).git_repo_path();
+    let store = repo.store();
+
+    let mut tx = repo.start_transaction(&settings);
+    store.start_write_batch();
+    let commit_a = create_random_commit(tx.repo_mut(), &settings)
+        .write()
+        .unwrap();
+    let commit_b = create_random_commit(tx.repo_mut(), &settings)
+        .set_parents(vec![commit_a.id().clone()])
+        .write()
+        .unwrap();
+
+    // The commits can be read back before the batch is finished, but their
+    // no-gc refs haven't been created yet
+    let backend_commit = store
+        .backend()
+        .read_commit(commit_b.id())
+        .block_on()
+        .unwrap();
+    assert_eq!(&backend_commit.change_id, commit_b.change_id());
+    assert_eq!(collect_no_gc_refs(git_repo_path), hashset! {});
+
+    store.finish_write_batch().unwrap();
+    assert_eq!(
+        collect_no_gc_refs(git_repo_path),
+        hashset! {commit_a.id().clone(), commit_b.id().clone()},
+    );
+    tx.commit("test");
+
+    // The metadata was saved for other processes
+    let repo = testutils::load_repo_at_head(&settings, test_repo.repo_path());
+    for commit in [&commit_a, &commit_b] {
+        let backend_commit = repo
+            .store()
+            .backend()
+            .read_commit(commit.id())
+            .block_on()
+            .unwrap();
+        assert_eq!(&backend_commit.change_id, commit.change_id());
+    }
+}
+
 #[test]
 fn test_copy_detection() {
     let settings = testutils::user_settings();
@@ -295,3 +347,68 @@ fn test_copy_detection() {
         HashMap::default(),
     );
 }
+
+#[test]
+fn test_read_file_from_partial_clone() {
+    // TODO: Better way to disable the test if git command couldn't be executed
+    if Command::new("git").arg("--version").status().is_err() {
+        eprintln!("Skipping because git command might fail to run");
+        return;
+    }
+
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let source_path = temp_dir.path().join("source");
+    let source_repo = git2::Repository::init(&source_path).unwrap();
+    let blob_id = source_repo.blob(b"contents").unwrap();
+    let mut tree_builder = source_repo.treebuilder(None).unwrap();
+    tree_builder.insert("file", blob_id, 0o100644).unwrap();
+    let tree = source_repo
+        .find_tree(tree_builder.write().unwrap())
+        .unwrap();
+    let signature = git2::Signature::now("Someone", "someone@example.com").unwrap();
+    source_repo
+        .commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            "message",
+            &tree,
+            &[],
+        )
+        .unwrap();
+    let mut source_config = source_repo.config().unwrap();
+    source_config
+        .set_bool("uploadpack.allowFilter", true)
+        .unwrap();
+    source_config
+        .set_bool("uploadpack.allowAnySHA1InWant", true)
+        .unwrap();
+
+    // Clone without blobs
+    let clone_path = temp_dir.path().join("clone.git");
+    let status = Command::new("git")
+        .args(["clone", "--quiet", "--bare", "--filter=blob:none"])
+        .arg(format!("file://{}", source_path.display()))
+        .arg(&clone_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let clone_repo = git2::Repository::open(&clone_path).unwrap();
+    assert!(clone_repo.find_blob(blob_id).is_err());
+
+    // The missing blob is fetched on demand
+    let store_path = temp_dir.path().join("store");
+    std::fs::create_dir(&store_path).unwrap();
+    let backend = GitBackend::init_external(&settings, &store_path, &clone_path).unwrap();
+    let mut reader = backend
+        .read_file(
+            RepoPath::from_internal_string("file"),
+            &FileId::from_bytes(blob_id.as_bytes()),
+        )
+        .block_on()
+        .unwrap();
+    let mut content = String::new();
+    reader.read_to_string(&mut content).unwrap();
+    assert_eq!(content, "contents");
+}
@@ -657,6 +657,30 @@ fn test_reindex_corrupt_segment_files() {
     assert!(repo.index().has_id(commit_a.id()));
 }
 
+#[test]
+fn test_reindex_truncated_segment_files() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let commit_a = write_random_commit(tx.repo_mut(), &settings);
+    let repo = tx.commit("test");
+    assert!(repo.index().has_id(commit_a.id()));
+
+    // Drop the last entry bytes, so the header no longer matches the length of
+    // the (mapped) file
+    let segments_dir = test_repo.repo_path().join("index").join("segments");
+    for entry in segments_dir.read_dir().unwrap() {
+        let entry = entry.unwrap();
+        let data = fs::read(entry.path()).unwrap();
+        fs::write(entry.path(), &data[..data.len() - 4]).unwrap()
+    }
+
+    let repo = load_repo_at_head(&settings, test_repo.repo_path());
+    assert!(repo.index().has_id(commit_a.id()));
+}
+
 #[test]
 fn test_reindex_from_merged_operation() {
     let settings = testutils::user_settings();
@@ -738,6 +762,79 @@ fn test_reindex_missing_commit() {
     assert_matches!(err, DefaultIndexStoreError::IndexCommits { op_id, .. } if op_id == *bad_op_id);
 }
 
+#[test]
+fn test_reindex_resume_partial_index() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let commit_a = write_random_commit(tx.repo_mut(), &settings);
+    let repo = tx.commit("test");
+    let op_id_a = repo.op_id().clone();
+    let mut tx = repo.start_transaction(&settings);
+    let commit_b = write_random_commit(tx.repo_mut(), &settings);
+    let repo = tx.commit("test");
+    let op_id_b = repo.op_id().clone();
+
+    // Pretend that building the index of the last operation was interrupted
+    // after a chunk containing commit_a was saved.
+    let index_dir = test_repo.repo_path().join("index");
+    let operations_dir = index_dir.join("operations");
+    let partial_dir = index_dir.join("partial");
+    fs::rename(
+        operations_dir.join(op_id_a.hex()),
+        partial_dir.join(op_id_b.hex()),
+    )
+    .unwrap();
+    fs::remove_file(operations_dir.join(op_id_b.hex())).unwrap();
+
+    let repo = repo.reload_at(repo.operation()).unwrap();
+    assert!(repo.index().has_id(commit_a.id()));
+    assert!(repo.index().has_id(commit_b.id()));
+    assert!(operations_dir.join(op_id_b.hex()).is_file());
+    assert!(!partial_dir.join(op_id_b.hex()).exists());
+}
+
+#[test]
+fn test_squash_index_at_operation() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let mut repo = test_repo.repo.clone();
+
+    // Each transaction adds fewer commits than the previous one, so the new
+    // segments are stacked on top of the old ones.
+    let mut commit_ids = vec![];
+    for num_commits in [8, 4, 2, 1] {
+        let mut tx = repo.start_transaction(&settings);
+        for _ in 0..num_commits {
+            commit_ids.push(write_random_commit(tx.repo_mut(), &settings).id().clone());
+        }
+        repo = tx.commit("test");
+    }
+    let repo = load_repo_at_head(&settings, test_repo.repo_path());
+    let stats = as_readonly_composite(&repo).stats();
+    let num_segments = stats.levels.len();
+    assert!(num_segments > 1);
+
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    assert!(!default_index_store
+        .squash_index_at_operation(repo.op_id(), repo.store(), num_segments)
+        .unwrap());
+    assert!(default_index_store
+        .squash_index_at_operation(repo.op_id(), repo.store(), 1)
+        .unwrap());
+
+    let repo = load_repo_at_head(&settings, test_repo.repo_path());
+    let index = as_readonly_composite(&repo);
+    assert_eq!(index.stats().levels.len(), 1);
+    assert_eq!(index.num_commits(), stats.num_commits);
+    for id in &commit_ids {
+        assert!(index.has_id(id));
+    }
+}
+
 /// Test that .jj/repo/index/type is created when the repo is created.
 #[test]
 fn test_index_store_type() {
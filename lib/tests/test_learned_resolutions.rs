@@ -0,0 +1,158 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use jj_lib::backend::TreeValue;
+use jj_lib::learned_resolutions::LearnedResolutions;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::rewrite::rebase_commit;
+use testutils::create_tree;
+use testutils::read_file;
+use testutils::TestRepo;
+
+fn file_content(tree: &MergedTree, path: &RepoPath) -> String {
+    let value = tree.path_value(path).unwrap();
+    let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+        panic!("unexpected value at {path:?}: {value:?}");
+    };
+    String::from_utf8(read_file(tree.store(), path, id)).unwrap()
+}
+
+#[test]
+fn test_learn_and_resolve_tree() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let learned_resolutions = LearnedResolutions::new(test_repo.repo_path());
+    let path = RepoPath::from_internal_string("file");
+
+    let base = create_tree(repo, &[(path, "a\nb\nc\nd\ne\n")]);
+    let left = create_tree(repo, &[(path, "a\nleft\nc\nd\nleft\n")]);
+    let right = create_tree(repo, &[(path, "a\nright\nc\nd\nright\n")]);
+    let conflict_tree = left.merge(&base, &right).unwrap();
+    assert!(conflict_tree.has_conflict());
+    // The resolved hunks between the conflicts are kept, so the resolution of
+    // each conflicted hunk is recorded
+    let resolved_tree = create_tree(repo, &[(path, "a\nboth\nc\nd\nleft\nright\n")]);
+    assert_eq!(
+        learned_resolutions
+            .learn_from_trees(&conflict_tree, &resolved_tree)
+            .unwrap(),
+        2
+    );
+    // Nothing is recorded if the resolved hunks were changed
+    let changed_tree = create_tree(repo, &[(path, "a\nboth\nx\nd\nright\n")]);
+    assert_eq!(
+        learned_resolutions
+            .learn_from_trees(&conflict_tree, &changed_tree)
+            .unwrap(),
+        0
+    );
+
+    // The same conflict is resolved even if the sides are swapped and the
+    // context has changed
+    let base = create_tree(repo, &[(path, "x\nb\ny\n")]);
+    let left = create_tree(repo, &[(path, "x\nright\ny\n")]);
+    let right = create_tree(repo, &[(path, "x\nleft\ny\n")]);
+    let conflict_tree = left.merge(&base, &right).unwrap();
+    let new_tree = learned_resolutions.resolve_tree(&conflict_tree).unwrap();
+    assert!(!new_tree.has_conflict());
+    assert_eq!(file_content(&new_tree, path), "x\nboth\ny\n");
+
+    // Only the hunks with recorded resolutions are resolved
+    let base = create_tree(repo, &[(path, "b\nc\nd\n")]);
+    let left = create_tree(repo, &[(path, "left\nc\nleft2\n")]);
+    let right = create_tree(repo, &[(path, "right\nc\nright2\n")]);
+    let conflict_tree = left.merge(&base, &right).unwrap();
+    let new_tree = learned_resolutions.resolve_tree(&conflict_tree).unwrap();
+    assert!(new_tree.has_conflict());
+    let new_left = create_tree(repo, &[(path, "both\nc\nleft2\n")]);
+    let new_base = create_tree(repo, &[(path, "both\nc\nd\n")]);
+    let new_right = create_tree(repo, &[(path, "both\nc\nright2\n")]);
+    assert_eq!(
+        new_tree.id(),
+        new_left.merge(&new_base, &new_right).unwrap().id()
+    );
+
+    // Nothing is resolved after forgetting the resolutions
+    assert_eq!(learned_resolutions.clear().unwrap(), 2);
+    let new_tree = learned_resolutions.resolve_tree(&conflict_tree).unwrap();
+    assert_eq!(new_tree.id(), conflict_tree.id());
+    assert_eq!(learned_resolutions.clear().unwrap(), 0);
+}
+
+#[test]
+fn test_rebase_with_learned_resolutions() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let learned_resolutions = Arc::new(LearnedResolutions::new(test_repo.repo_path()));
+    let path = RepoPath::from_internal_string("file");
+
+    let base_tree = create_tree(repo, &[(path, "base\n")]);
+    let left_tree = create_tree(repo, &[(path, "left\n")]);
+    let right_tree = create_tree(repo, &[(path, "right\n")]);
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    mut_repo.set_learned_resolutions(learned_resolutions);
+    let root_id = repo.store().root_commit_id().clone();
+    let commit_base = mut_repo
+        .new_commit(&settings, vec![root_id], base_tree.id())
+        .write()
+        .unwrap();
+    let commit_left = mut_repo
+        .new_commit(&settings, vec![commit_base.id().clone()], left_tree.id())
+        .write()
+        .unwrap();
+    let commit_right = mut_repo
+        .new_commit(&settings, vec![commit_base.id().clone()], right_tree.id())
+        .write()
+        .unwrap();
+    let commit_merge = mut_repo
+        .new_commit(
+            &settings,
+            vec![commit_left.id().clone(), commit_right.id().clone()],
+            left_tree.merge(&base_tree, &right_tree).unwrap().id(),
+        )
+        .write()
+        .unwrap();
+    assert!(commit_merge.has_conflict().unwrap());
+
+    // Resolving the conflict without changing the parents records the
+    // resolution
+    let resolved_tree = create_tree(repo, &[(path, "resolved\n")]);
+    mut_repo
+        .rewrite_commit(&settings, &commit_merge)
+        .set_tree_id(resolved_tree.id())
+        .write()
+        .unwrap();
+    assert_eq!(mut_repo.learn_resolutions().unwrap(), 1);
+
+    // Rebasing the other side onto the first side creates the same conflict,
+    // which is resolved
+    let rebased_right = rebase_commit(
+        &settings,
+        mut_repo,
+        commit_right,
+        vec![commit_left.id().clone()],
+    )
+    .unwrap();
+    assert!(!rebased_right.has_conflict().unwrap());
+    assert_eq!(
+        file_content(&rebased_right.tree().unwrap(), path),
+        "resolved\n"
+    );
+}
@@ -12,7 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use jj_lib::backend::CommitId;
+use jj_lib::repo::Repo as _;
 use jj_lib::repo::RepoLoader;
+use pollster::FutureExt as _;
+use testutils::load_repo_at_head;
+use testutils::test_backend::TestBackend;
 use testutils::write_random_commit;
 use testutils::TestRepo;
 
@@ -52,3 +57,40 @@ fn test_load_at_operation() {
     let old_repo = loader.load_at(repo.operation()).unwrap();
     assert!(old_repo.view().heads().contains(commit.id()));
 }
+
+#[test]
+fn test_prefetch_commits() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction(&settings);
+    let commit1 = write_random_commit(tx.repo_mut(), &settings);
+    let commit2 = write_random_commit(tx.repo_mut(), &settings);
+    let commit3 = write_random_commit(tx.repo_mut(), &settings);
+    tx.commit("test");
+
+    // Load the repo again to start with an empty commit cache
+    let repo = load_repo_at_head(&settings, test_repo.repo_path());
+    let store = repo.store();
+    let missing_id = CommitId::from_hex("abcdef");
+    // Commits that can't be read are skipped
+    store
+        .prefetch_commits(&[
+            commit1.id().clone(),
+            missing_id.clone(),
+            commit2.id().clone(),
+        ])
+        .block_on();
+
+    // The prefetched commits are read from the cache after they're removed
+    // from the backend, unlike the commit that wasn't prefetched.
+    let test_backend: &TestBackend = store.backend_impl().downcast_ref().unwrap();
+    for commit in [&commit1, &commit2, &commit3] {
+        test_backend.remove_commit_unchecked(commit.id());
+    }
+    assert_eq!(store.get_commit(commit1.id()).unwrap(), commit1);
+    assert_eq!(store.get_commit(commit2.id()).unwrap(), commit2);
+    assert!(store.get_commit(commit3.id()).is_err());
+    assert!(store.get_commit(&missing_id).is_err());
+}
@@ -0,0 +1,116 @@
+// Copyright 2024 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+
+use jj_lib::backend::TreeValue;
+use jj_lib::fileset::FilesetExpression;
+use jj_lib::merge_drivers::MergeDrivers;
+use jj_lib::merge_drivers::MergeStrategy;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::rewrite::rebase_commit;
+use test_case::test_case;
+use testutils::create_tree;
+use testutils::read_file;
+use testutils::TestRepo;
+
+fn file_content(tree: &MergedTree, path: &RepoPath) -> String {
+    let value = tree.path_value(path).unwrap();
+    let Some(Some(TreeValue::File { id, .. })) = value.as_resolved() else {
+        panic!("unexpected value at {path:?}: {value:?}");
+    };
+    String::from_utf8(read_file(tree.store(), path, id)).unwrap()
+}
+
+#[test_case(MergeStrategy::Union, "a\nleft\nright\nc\n"; "union")]
+#[test_case(MergeStrategy::Ours, "a\nleft\nc\n"; "ours")]
+#[test_case(MergeStrategy::Theirs, "a\nright\nc\n"; "theirs")]
+fn test_merge_strategy(strategy: MergeStrategy, expected: &str) {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let driver_path = RepoPath::from_internal_string("dir/file");
+    let other_path = RepoPath::from_internal_string("other");
+
+    let base = create_tree(repo, &[(driver_path, "a\nb\nc\n"), (other_path, "b\n")]);
+    let left = create_tree(
+        repo,
+        &[(driver_path, "a\nleft\nc\n"), (other_path, "left\n")],
+    );
+    let right = create_tree(
+        repo,
+        &[(driver_path, "a\nright\nc\n"), (other_path, "right\n")],
+    );
+    let conflict_tree = left.merge(&base, &right).unwrap();
+
+    let mut merge_drivers = MergeDrivers::new();
+    merge_drivers.add(
+        FilesetExpression::prefix_path(driver_path.parent().unwrap().to_owned()),
+        Box::new(strategy),
+    );
+    let new_tree = merge_drivers.resolve_tree(&conflict_tree).unwrap();
+    assert_eq!(file_content(&new_tree, driver_path), expected);
+    // Files that don't match the patterns are left conflicted
+    assert_eq!(
+        new_tree.path_value(other_path).unwrap(),
+        conflict_tree.path_value(other_path).unwrap()
+    );
+}
+
+#[test]
+fn test_rebase_with_merge_drivers() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let path = RepoPath::from_internal_string("CHANGELOG");
+
+    let mut merge_drivers = MergeDrivers::new();
+    merge_drivers.add(
+        FilesetExpression::file_path(path.to_owned()),
+        Box::new(MergeStrategy::Union),
+    );
+    let base_tree = create_tree(repo, &[(path, "base\n")]);
+    let left_tree = create_tree(repo, &[(path, "left\nbase\n")]);
+    let right_tree = create_tree(repo, &[(path, "right\nbase\n")]);
+    let mut tx = repo.start_transaction(&settings);
+    let mut_repo = tx.repo_mut();
+    mut_repo.set_merge_drivers(Arc::new(merge_drivers));
+    let root_id = repo.store().root_commit_id().clone();
+    let commit_base = mut_repo
+        .new_commit(&settings, vec![root_id], base_tree.id())
+        .write()
+        .unwrap();
+    let commit_left = mut_repo
+        .new_commit(&settings, vec![commit_base.id().clone()], left_tree.id())
+        .write()
+        .unwrap();
+    let commit_right = mut_repo
+        .new_commit(&settings, vec![commit_base.id().clone()], right_tree.id())
+        .write()
+        .unwrap();
+
+    let rebased_right = rebase_commit(
+        &settings,
+        mut_repo,
+        commit_right,
+        vec![commit_left.id().clone()],
+    )
+    .unwrap();
+    assert!(!rebased_right.has_conflict().unwrap());
+    assert_eq!(
+        file_content(&rebased_right.tree().unwrap(), path),
+        "left\nright\nbase\n"
+    );
+}
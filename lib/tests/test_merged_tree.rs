@@ -749,6 +749,37 @@ fn test_conflict_iterator_higher_arity() {
     );
 }
 
+/// Conflicts in enough subdirectories for them to be read in parallel
+#[test]
+fn test_conflict_iterator_many_subdirs() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let paths = (0..100)
+        .map(|i| RepoPathBuf::from_internal_string(format!("dir{i:03}/file")))
+        .collect_vec();
+    let create_tree_with = |content: &dyn Fn(usize) -> &'static str| {
+        create_single_tree(
+            repo,
+            &paths
+                .iter()
+                .enumerate()
+                .map(|(i, path)| (path.as_ref(), content(i)))
+                .collect_vec(),
+        )
+    };
+    let base = create_tree_with(&|_| "base");
+    // Every third file is modified on both sides
+    let side1 = create_tree_with(&|i| if i % 3 == 0 { "side1" } else { "base" });
+    let side2 = create_tree_with(&|i| if i % 3 == 0 { "side2" } else { "base" });
+    let tree = MergedTree::new(Merge::from_removes_adds(vec![base], vec![side1, side2]));
+
+    // The conflicts are yielded in path order
+    let conflict_paths = tree.conflicts().map(|(path, _)| path).collect_vec();
+    let expected_paths = paths.iter().step_by(3).cloned().collect_vec();
+    assert_eq!(conflict_paths, expected_paths);
+}
+
 /// Diff two resolved trees
 #[test]
 fn test_diff_resolved() {
@@ -817,6 +848,55 @@ fn test_diff_resolved() {
     diff_stream_equals_iter(&before_merged, &after_merged, &EverythingMatcher);
 }
 
+/// Diff trees with enough subdirectories for them to be read in parallel
+#[test]
+fn test_diff_many_subdirs() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let paths = (0..100)
+        .map(|i| RepoPathBuf::from_internal_string(format!("dir{i:03}/file")))
+        .collect_vec();
+    let before = create_single_tree(
+        repo,
+        &paths
+            .iter()
+            .map(|path| (path.as_ref(), "before"))
+            .collect_vec(),
+    );
+    // Every third file is modified
+    let after = create_single_tree(
+        repo,
+        &paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| (path.as_ref(), if i % 3 == 0 { "after" } else { "before" }))
+            .collect_vec(),
+    );
+    let before_merged = MergedTree::new(Merge::resolved(before));
+    let after_merged = MergedTree::new(Merge::resolved(after));
+
+    let diff_paths = TreeDiffIterator::new(
+        before_merged.as_merge(),
+        after_merged.as_merge(),
+        &EverythingMatcher,
+    )
+    .map(|diff| diff.path)
+    .collect_vec();
+    let expected_paths = paths.iter().step_by(3).cloned().collect_vec();
+    assert_eq!(diff_paths, expected_paths);
+    diff_stream_equals_iter(&before_merged, &after_merged, &EverythingMatcher);
+
+    // Subdirectories that don't match aren't diffed
+    let matcher = PrefixMatcher::new([RepoPath::from_internal_string("dir003")]);
+    let diff_paths =
+        TreeDiffIterator::new(before_merged.as_merge(), after_merged.as_merge(), &matcher)
+            .map(|diff| diff.path)
+            .collect_vec();
+    assert_eq!(diff_paths, vec![paths[3].clone()]);
+    diff_stream_equals_iter(&before_merged, &after_merged, &matcher);
+}
+
 fn create_copy_records(paths: &[(&RepoPath, &RepoPath)]) -> CopyRecords {
     let mut copy_records = CopyRecords::default();
     copy_records
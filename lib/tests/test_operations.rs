@@ -404,6 +404,88 @@ fn test_reparent_range_bookmarky() {
     assert_eq!(new_op_f.parent_ids(), slice::from_ref(repo_d.op_id()));
 }
 
+#[test]
+fn test_squash_operations_linear() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let repo_0 = test_repo.repo;
+    let op_store = repo_0.op_store();
+
+    let read_op = |id| {
+        let data = op_store.read_operation(id).unwrap();
+        Operation::new(op_store.clone(), id.clone(), data)
+    };
+
+    fn op_parents<const N: usize>(op: &Operation) -> [Operation; N] {
+        let parents: Vec<_> = op.parents().try_collect().unwrap();
+        parents.try_into().unwrap()
+    }
+
+    // Set up linear operation graph:
+    // D
+    // C
+    // B
+    // A
+    // 0 (initial)
+    let random_tx = |repo: &Arc<ReadonlyRepo>| {
+        let mut tx = repo.start_transaction(&settings);
+        write_random_commit(tx.repo_mut(), &settings);
+        tx
+    };
+    let repo_a = random_tx(&repo_0).commit("op A");
+    let repo_b = random_tx(&repo_a).commit("op B");
+    let repo_c = random_tx(&repo_b).commit("op C");
+    let repo_d = random_tx(&repo_c).commit("op D");
+
+    // Squash A and B into C:
+    // D'
+    // C'
+    // 0 (initial)
+    let stats = op_walk::squash_operations(
+        op_store.as_ref(),
+        slice::from_ref(repo_d.operation()),
+        |op| !["op A", "op B"].contains(&op.metadata().description.as_str()),
+    )
+    .unwrap();
+    assert_eq!(stats.new_head_ids.len(), 1);
+    assert_eq!(stats.rewritten_count, 2);
+    assert_eq!(stats.unreachable_count, 2);
+    let new_op_d = read_op(&stats.new_head_ids[0]);
+    assert_eq!(new_op_d.metadata(), repo_d.operation().metadata());
+    assert_eq!(new_op_d.view_id(), repo_d.operation().view_id());
+    let [new_op_c] = op_parents(&new_op_d);
+    assert_eq!(new_op_c.metadata(), repo_c.operation().metadata());
+    assert_eq!(new_op_c.view_id(), repo_c.operation().view_id());
+    assert_eq!(new_op_c.parent_ids(), slice::from_ref(repo_0.op_id()));
+
+    // The head operation is kept even if it's rejected
+    let stats = op_walk::squash_operations(
+        op_store.as_ref(),
+        slice::from_ref(repo_d.operation()),
+        |_| false,
+    )
+    .unwrap();
+    assert_eq!(stats.rewritten_count, 1);
+    assert_eq!(stats.unreachable_count, 4);
+    let new_op_d = read_op(&stats.new_head_ids[0]);
+    assert_eq!(new_op_d.view_id(), repo_d.operation().view_id());
+    assert_eq!(
+        new_op_d.parent_ids(),
+        slice::from_ref(op_store.root_operation_id())
+    );
+
+    // Nothing to squash
+    let stats = op_walk::squash_operations(
+        op_store.as_ref(),
+        slice::from_ref(repo_d.operation()),
+        |_| true,
+    )
+    .unwrap();
+    assert_eq!(stats.new_head_ids, vec![repo_d.op_id().clone()]);
+    assert_eq!(stats.rewritten_count, 0);
+    assert_eq!(stats.unreachable_count, 0);
+}
+
 fn stable_op_id_settings() -> UserSettings {
     UserSettings::from_config(
         testutils::base_config()
@@ -426,21 +508,22 @@ fn test_resolve_op_id() {
     let mut operations = Vec::new();
     // The actual value of `i` doesn't matter, we just need to make sure we end
     // up with hashes with ambiguous prefixes.
-    for i in (1..7).chain([16]) {
+    for i in (1..8).chain([1015]) {
         let tx = repo.start_transaction(&settings);
         let repo = tx.commit(format!("transaction {i}"));
         operations.push(repo.operation().clone());
     }
-    // "2" and "0" are ambiguous
+    // "6" and "0" are ambiguous
     insta::assert_debug_snapshot!(operations.iter().map(|op| op.id().hex()).collect_vec(), @r###"
     [
-        "5aebb24d08d6f5282d9e06bded4b51febbb4ff4bc822cdd4db9043961339955d6af912e783c3864245867d0cf6d609cb004c5ff0cef5e914c15a415ba92e38a2",
-        "ad1cda629b220f2651d972475fba75e47c30cd57862c51a727e57b7b6e2fbaa937d0bcb881cf6fcff30ba1a088a4ad588cf880765b9d9680b551cdc446f3489e",
-        "2feaf9eb61232582d35f1cb0203b69425f7a7d07140a5f8ff0d0bf98dd9f433b941e4c9e0882f65a007266affee275e235ae1614b159ceadbf9fe6879077a5da",
-        "0b183be5767c3ff9945c8a9e3ac7639d249ff9873985a551f4c2070782aea8b5018ebff906036d1658038708ab9de0d867be385b1181aa7a81669e2ef6852355",
-        "d1c3031dff7b1db4db1bfb592a9aa0ea6faae9300033ef20cb6da488e4b90524c22af8e2541cdb99ca0ee2be3299c213c0f8d48390cd5f11462c4f80c5790f68",
-        "2369ba98e2596715606978a72608970287aaa064ec890f87c29b7e7df64fdf02b7f24b8b03ee845e132f3e19e3929de359b6cfe1328b42946c45ac5ff80705ca",
-        "00271842a189d274a2c97ac28f937584a47b84463d5b408c6f998089131e0e1329a287667b2ac5d63f8c576e95323bcf992c99caa4ef4612a1c3798fe8a3f74f",
+        "a59c5c3d647b21097047ca3e911e747e93d8f1ee942d594a6e454e2a5986c858d4135f224d24590af611c1b9f6bc1e0ef8669a4ac247db5ef7eb3ebb987d550e",
+        "65c9aa1720ea8f015fe8f7e474cffb8dff227fef95af2bc910a31f9cde5ffdf6160bf86acaf944c71cba28a6b76dd307dbb54ea830ff576094f67a1e7128cc39",
+        "71a60433d9a558bc063d94307beb9033303f21f5d02ed238059f361bf09bcb6254ec4f375f1b659d529eda8368e3941ba3e2b83d81598e261e7490ed3eb10457",
+        "ca88bfa520f3dbff0d127661ef8b47813f660e3fdbe11e3ae2eda5e39239c3ace01b588265f64d40e7d3fdc30ce6917d91c1d35c83beca8ab286a03a7c6eda47",
+        "187d475b4a6aa0b2a05abb451faa5b5b43ee0e6de2b4e61dc0fa5b68e73e1f190949ad3e0f385fb09fcd35d025d46181c1edb580fb33839c931c5ce2ec5c17a7",
+        "8fd9d9677b195951f09326c55ef3a1d446c2476c83e0d12b22f72b7f19ae18e86c0f82114455d7576ff474311e7703c67e4205dc4f2260dd900c2b3585f3973c",
+        "603dc2aaf95f36fe7729805180bb76fe4ba11c4b0194d64b7e35419ab69791feece96c3c4e7ae9058d8a1e9ef29e8ebe9cfcabea5e4af84c4fa3542710ad5876",
+        "002dfa3b37f8d5688a11b5d25b1d7487c8b8d543375ca8d87f8bf3d1f2879b245ad9b2e10d1a50b105a0c79719e2da2540ad3d013748d2922e9b66e0f5adfaae",
     ]
     "###);
 
@@ -461,7 +544,7 @@ fn test_resolve_op_id() {
     );
     // Ambiguous id
     assert_matches!(
-        resolve("2"),
+        resolve("6"),
         Err(OpsetEvaluationError::OpsetResolution(
             OpsetResolutionError::AmbiguousIdPrefix(_)
         ))
@@ -488,7 +571,7 @@ fn test_resolve_op_id() {
     };
     assert_eq!(resolve(&root_operation.id().hex()).unwrap(), root_operation);
     assert_eq!(resolve("000").unwrap(), root_operation);
-    assert_eq!(resolve("002").unwrap(), operations[6]);
+    assert_eq!(resolve("002").unwrap(), operations[7]);
     assert_matches!(
         resolve("0"),
         Err(OpsetEvaluationError::OpsetResolution(
@@ -407,6 +407,7 @@ pub fn commit_with_tree(store: &Arc<Store>, tree_id: MergedTreeId) -> Commit {
         author: signature.clone(),
         committer: signature,
         secure_sig: None,
+        recorded_copies: vec![],
     };
     store.write_commit(commit, None).block_on().unwrap()
 }